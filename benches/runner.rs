@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use tokio::{runtime::Runtime, sync::mpsc};
+
+use shadowrun::gamerunner::{game_runner, dispatcher::Message, loadgen::{seed_game, run_load, send, synthetic_request}};
+
+const REQUESTS_PER_PLAYER: usize = 50;
+const PLAYERS_PER_GAME: usize = 5;
+
+fn runtime() -> Runtime
+{
+    tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap()
+}
+
+// One client, one request at a time: the floor cost of a round trip through the runner.
+fn single_round_trip(c: &mut Criterion)
+{
+    let rt = runtime();
+    let (runner, game) = rt.block_on(async {
+        let (runner, receiver) = mpsc::channel::<Message>(10);
+        tokio::spawn(async move { game_runner(receiver).await; });
+        let game = seed_game(&runner, PLAYERS_PER_GAME).await.unwrap();
+        (runner, game)
+    });
+    let (player_id, character_id) = game.players[0];
+
+    let mut group = c.benchmark_group("round_trip");
+    for n in 0..5
+    {
+        let name = synthetic_request(n, character_id).name();
+        group.bench_function(name, |b| b.to_async(&rt).iter(|| send(&runner, Some(player_id), Some(game.game_id), synthetic_request(n, character_id))));
+    }
+    group.finish();
+}
+
+// Many tables at once, every player waiting on their own replies: how throughput scales as the single loop is shared more widely.
+fn concurrent_tables(c: &mut Criterion)
+{
+    let rt = runtime();
+    let mut group = c.benchmark_group("concurrent_tables");
+    group.sample_size(10);
+
+    for games in [1usize, 10, 50]
+    {
+        let (runner, seeded) = rt.block_on(async {
+            let (runner, receiver) = mpsc::channel::<Message>(10);
+            tokio::spawn(async move { game_runner(receiver).await; });
+            let mut seeded = Vec::with_capacity(games);
+            for _ in 0..games
+            {
+                seeded.push(seed_game(&runner, PLAYERS_PER_GAME).await.unwrap());
+            }
+            (runner, seeded)
+        });
+
+        group.throughput(Throughput::Elements((games * PLAYERS_PER_GAME * REQUESTS_PER_PLAYER) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(games), &seeded, |b, seeded| {
+            b.to_async(&rt).iter(|| run_load(&runner, seeded, REQUESTS_PER_PLAYER))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_round_trip, concurrent_tables);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+// Drives synthetic tables through a fresh game runner and reports how the single runner loop holds up.
+//
+//     cargo run --release --bin loadtest -- [games] [players per game] [requests per player]
+
+use std::process::ExitCode;
+
+use tokio::sync::mpsc;
+
+use shadowrun::gamerunner::{game_runner, dispatcher::Message, loadgen::{LoadProfile, seed_game, run_load}};
+
+fn arg_or(position: usize, default: usize) -> usize
+{
+    std::env::args().nth(position).and_then(|arg| arg.parse().ok()).unwrap_or(default)
+}
+
+fn main() -> ExitCode
+{
+    let profile = LoadProfile { games: arg_or(1, 100), players_per_game: arg_or(2, 5), requests_per_player: arg_or(3, 200) };
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => 
+        {
+            eprintln!("Could not start the tokio runtime: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    runtime.block_on(async {
+        let (runner, receiver) = mpsc::channel::<Message>(10);
+        tokio::spawn(async move { game_runner(receiver).await; });
+
+        let mut games = Vec::with_capacity(profile.games);
+        for _ in 0..profile.games
+        {
+            match seed_game(&runner, profile.players_per_game).await
+            {
+                Ok(game) => games.push(game),
+                Err(err) => 
+                {
+                    eprintln!("Seeding a game failed: {}", err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+
+        println!("Seeded {} games of {} players; sending {} requests per player...", profile.games, profile.players_per_game, profile.requests_per_player);
+        let report = run_load(&runner, &games, profile.requests_per_player).await;
+
+        println!("requests:   {} ({} errors)", report.requests, report.errors);
+        println!("elapsed:    {:.3?}", report.elapsed);
+        println!("throughput: {:.0} requests/s", report.throughput());
+        println!("latency:    p50 {:.3?}  p95 {:.3?}  p99 {:.3?}  max {:.3?}", report.percentile(50.0), report.percentile(95.0), report.percentile(99.0), report.percentile(100.0));
+
+        if report.errors == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+    })
+}
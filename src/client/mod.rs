@@ -0,0 +1,545 @@
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url, cookie::{CookieStore, Jar}, header::COOKIE, redirect::Policy};
+use rocket::futures::{SinkExt, Stream, StreamExt, TryStreamExt, stream};
+use rocket::serde::{de::DeserializeOwned, json};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::{self, Message as Frame, client::IntoClientRequest}};
+use uuid::Uuid;
+
+use crate::http::{session::{SESSION_EXPIRED_HEADER, REGISTRATION_TOKEN_HEADER}, idempotency::IDEMPOTENCY_KEY_HEADER, serde::{NewGame, Character, 
+    AddedCharacterJson, NewState, GameFilter, GameList, TurnSnapshot, CastMemberView, Resumed, ReportScope, SessionReport, CombatTimeline, 
+    ExportFormat, JournalKind, JournalLine, InboxNotice, Credentials, ErrorBody, GameConfirmation, InitiativeRoll, InitiativeRollResults, 
+    Damage, Healing, Recovered, CommandFrame, ClientCommand, SocketFrame}, queue::QueueStats};
+
+// A typed client for the server's JSON API, so integrators and the TUI do not have to hand-write the calls.  It holds the session cookie
+// the way a browser would, and takes and returns the same wire types the routes do.
+//
+// Only what the server exposes can be mirrored here; most runner requests have no route yet.  What happens at the table comes as
+// InboxNotices, whose kind says which change it was and detail what it carried: over a game's socket, which also runs commands, or by
+// long-polling with events.
+
+#[derive(Debug)]
+pub enum ClientError
+{
+    Transport(reqwest::Error),
+    // The server answered with an error status, and why: the route's message and, when the game runner refused, what it was about.
+    Rejected(StatusCode, ErrorBody),
+    // The session has expired or been logged out; start a new one with ApiClient::connect.
+    SessionExpired,
+    // A game's socket could not be opened, or broke, or sent down something that is not a frame.
+    Socket(tungstenite::Error),
+    Unreadable(String),
+}
+
+impl From<reqwest::Error> for ClientError
+{
+    fn from(err: reqwest::Error) -> ClientError
+    {
+        ClientError::Transport(err)
+    }
+}
+
+impl From<tungstenite::Error> for ClientError
+{
+    fn from(err: tungstenite::Error) -> ClientError
+    {
+        ClientError::Socket(err)
+    }
+}
+
+impl ClientError
+{
+    fn rejected(status: StatusCode, message: String) -> ClientError
+    {
+        ClientError::Rejected(status, ErrorBody { message, context: None })
+    }
+
+    // Routes answer with an ErrorBody; anything else that refuses - a guard, or a proxy in front - is taken at its word.
+    async fn from_response(response: Response) -> ClientError
+    {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match rocket::serde::json::from_str::<ErrorBody>(&text)
+        {
+            Ok(body) => ClientError::Rejected(status, body),
+            Err(_) => ClientError::rejected(status, text),
+        }
+    }
+}
+
+pub struct ApiClient
+{
+    http: Client,
+    // Kept apart from the client so the session cookie can be handed to a socket too.
+    cookies: Arc<Jar>,
+    base_url: String,
+}
+
+impl ApiClient
+{
+    // Starts a session under the given handle, the way the sign-in page does, then reconnects so the runner knows the player.  base_url
+    // is wherever the server is mounted, base path included (e.g. "https://table.example.com/combat").
+    pub async fn connect(base_url: &str, handle: &str) -> Result<(ApiClient, Resumed), ClientError>
+    {
+        let cookies = Arc::new(Jar::default());
+        let http = Client::builder().cookie_provider(Arc::clone(&cookies)).redirect(Policy::none()).build()?;
+        let client = ApiClient { http, cookies, base_url: String::from(base_url.trim_end_matches('/')) };
+
+        // The sign-in page hands out the token that proves the session request came from it, in a header as well as in the form.
+        let page = client.http.get(client.url("/")).send().await?;
+        let token = page.headers().get(REGISTRATION_TOKEN_HEADER).and_then(|value| value.to_str().ok()).map(String::from)
+            .ok_or_else(|| ClientError::rejected(page.status(), String::from("The server did not issue a registration token.")))?;
+
+        let response = client.http.post(client.url("/gen_session")).form(&[("player_handle", handle), ("csrf_token", token.as_str())]).send().await?;
+        if !(response.status().is_success() || response.status().is_redirection())
+        {
+            return Err(ClientError::from_response(response).await);
+        }
+
+        let resumed = client.resume().await?;
+        Ok((client, resumed))
+    }
+
+    fn url(&self, path: &str) -> String
+    {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn api(&self, path: &str) -> String
+    {
+        format!("{}/api{}", self.base_url, path)
+    }
+
+    // Where a game's socket is: the same server, over ws for http and wss for https.
+    fn socket_url(&self, game_id: Uuid) -> String
+    {
+        let base = match self.base_url.split_once("://")
+        {
+            Some(("https", rest)) => format!("wss://{}", rest),
+            Some((_, rest)) => format!("ws://{}", rest),
+            None => self.base_url.clone(),
+        };
+        format!("{}/messages/{}/socket", base, game_id)
+    }
+
+    // Sends the post under the key, when there is one, so that sending it again after a dropped answer gets the first answer back
+    // instead of applying it twice.
+    fn keyed(request: RequestBuilder, idempotency_key: Option<&str>) -> RequestBuilder
+    {
+        match idempotency_key
+        {
+            Some(key) => request.header(IDEMPOTENCY_KEY_HEADER, key),
+            None => request,
+        }
+    }
+
+    async fn checked(response: Response) -> Result<Response, ClientError>
+    {
+        if response.status().is_success()
+        {
+            Ok(response)
+        }
+        else if response.status() == StatusCode::UNAUTHORIZED && response.headers().contains_key(SESSION_EXPIRED_HEADER)
+        {
+            Err(ClientError::SessionExpired)
+        }
+        else
+        {
+            Err(ClientError::from_response(response).await)
+        }
+    }
+
+    async fn json<T: DeserializeOwned>(response: Response) -> Result<T, ClientError>
+    {
+        Ok(ApiClient::checked(response).await?.json::<T>().await?)
+    }
+
+    pub async fn resume(&self) -> Result<Resumed, ClientError>
+    {
+        ApiClient::json(self.http.post(self.api("/reconnect")).send().await?).await
+    }
+
+    pub async fn new_game(&self) -> Result<NewGame, ClientError>
+    {
+        ApiClient::json(self.http.post(self.api("/api/game/new")).send().await?).await
+    }
+
+    pub async fn list_games(&self, filter: Option<GameFilter>, page: Option<usize>, page_size: Option<usize>) -> Result<GameList, ClientError>
+    {
+        let mut query = Vec::new();
+        if let Some(filter) = filter
+        {
+            let filter = match filter
+            {
+                GameFilter::All => "all",
+                GameFilter::Joined => "joined",
+                GameFilter::Running => "running",
+                GameFilter::Open => "open",
+                GameFilter::InCombat => "incombat",
+            };
+            query.push(("filter", String::from(filter)));
+        }
+        query.extend(page.map(|page| ("page", page.to_string())));
+        query.extend(page_size.map(|page_size| ("page_size", page_size.to_string())));
+
+        ApiClient::json(self.http.get(self.api("/games")).query(&query).send().await?).await
+    }
+
+    pub async fn register(&self, username: &str, password: &str) -> Result<(), ClientError>
+    {
+        let credentials = Credentials { username: String::from(username), password: String::from(password) };
+        ApiClient::checked(self.http.post(self.api("/account/register")).json(&credentials).send().await?).await?;
+
+        Ok(())
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> Result<Resumed, ClientError>
+    {
+        let credentials = Credentials { username: String::from(username), password: String::from(password) };
+        ApiClient::json(self.http.post(self.api("/account/login")).json(&credentials).send().await?).await
+    }
+
+    pub async fn delete_account(&self) -> Result<(), ClientError>
+    {
+        ApiClient::checked(self.http.delete(self.api("/account")).send().await?).await?;
+
+        Ok(())
+    }
+
+    // The client is no use afterwards; connect again for a new session.
+    pub async fn logout(self) -> Result<(), ClientError>
+    {
+        ApiClient::checked(self.http.post(self.api("/account/logout")).send().await?).await?;
+
+        Ok(())
+    }
+
+    pub async fn queue_stats(&self) -> Result<QueueStats, ClientError>
+    {
+        ApiClient::json(self.http.get(self.api("/runner/queue")).send().await?).await
+    }
+
+    pub async fn combat_report(&self, game_id: Uuid, scope: ReportScope) -> Result<SessionReport, ClientError>
+    {
+        let scope = match scope
+        {
+            ReportScope::LastCombat => "lastcombat",
+            ReportScope::Session => "session",
+        };
+
+        ApiClient::json(self.http.get(self.api(&format!("/{}/report", game_id))).query(&[("scope", scope)]).send().await?).await
+    }
+
+    pub async fn round_timeline(&self, game_id: Uuid) -> Result<CombatTimeline, ClientError>
+    {
+        ApiClient::json(self.http.get(self.api(&format!("/{}/timeline", game_id))).send().await?).await
+    }
+
+    pub async fn turn_state(&self, game_id: Uuid) -> Result<TurnSnapshot, ClientError>
+    {
+        ApiClient::json(self.http.get(self.api(&format!("/{}/turn", game_id))).send().await?).await
+    }
+
+    pub async fn cast_list(&self, game_id: Uuid) -> Result<Vec<CastMemberView>, ClientError>
+    {
+        ApiClient::json(self.http.get(self.api(&format!("/{}/cast", game_id))).send().await?).await
+    }
+
+    // Waits for the notifications in the player's inbox after since, coming back empty if none arrive within the timeout (the server caps
+    // it at a minute).  Pass the sequence of the last notice seen to pick up where it left off.
+    pub async fn poll_events(&self, game_id: Uuid, since: Option<usize>, timeout: Option<Duration>) -> Result<Vec<InboxNotice>, ClientError>
+    {
+        let mut query = Vec::new();
+        query.extend(since.map(|since| ("since", since.to_string())));
+        query.extend(timeout.map(|timeout| ("timeout", format!("{}ms", timeout.as_millis()))));
+
+        ApiClient::json(self.http.get(self.api(&format!("/{}/events", game_id))).query(&query).send().await?).await
+    }
+
+    // The same notices as a stream that never runs dry: each poll picks up after the last notice seen, and an empty one just polls again.
+    // It ends only on an error, such as the session lapsing or the game going away.
+    pub fn events(&self, game_id: Uuid, since: Option<usize>) -> impl Stream<Item = Result<InboxNotice, ClientError>> + '_
+    {
+        stream::try_unfold(since, move |since| async move
+        {
+            let notices = self.poll_events(game_id, since, None).await?;
+            let since = notices.last().map(|notice| Some(notice.sequence)).unwrap_or(since);
+
+            Ok(Some((stream::iter(notices.into_iter().map(Ok::<InboxNotice, ClientError>)), since)))
+        })
+        .try_flatten()
+    }
+
+    // Opens the game's socket as this session's player.  Everything already in the player's inbox comes down it first.
+    pub async fn open_socket(&self, game_id: Uuid) -> Result<GameSocket, ClientError>
+    {
+        let url = self.socket_url(game_id);
+        let mut request = url.as_str().into_client_request()?;
+
+        // The cookie is looked up under the http address, which is where the server set it.
+        let cookie_url = Url::parse(&self.url("/")).map_err(|err| ClientError::Unreadable(format!("{} is not a URL: {}", self.base_url, err)))?;
+        if let Some(cookie) = self.cookies.cookies(&cookie_url)
+        {
+            request.headers_mut().insert(COOKIE, cookie);
+        }
+
+        let (stream, _) = connect_async(request).await?;
+        Ok(GameSocket { stream, game_id, next_id: 1 })
+    }
+
+    // Always asks for JSON lines, which come back parsed; since and until are seconds since the Unix epoch.
+    pub async fn export_journal(&self, game_id: Uuid, since: Option<u64>, until: Option<u64>, kinds: Vec<JournalKind>) -> Result<Vec<JournalLine>, ClientError>
+    {
+        let mut query = vec![("format", String::from(ExportFormat::Jsonl.as_query()))];
+        query.extend(since.map(|since| ("since", since.to_string())));
+        query.extend(until.map(|until| ("until", until.to_string())));
+        query.extend(kinds.into_iter().map(|kind| ("kind", String::from(kind.as_query()))));
+
+        let body = ApiClient::checked(self.http.get(self.api(&format!("/{}/journal", game_id))).query(&query).send().await?).await?.text().await?;
+
+        body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| rocket::serde::json::from_str::<JournalLine>(line)
+                .map_err(|err| ClientError::Unreadable(format!("Unreadable journal line ({}): {}", err, line))))
+            .collect()
+    }
+
+    pub async fn add_character(&self, game_id: Uuid, character: &Character<'_>) -> Result<AddedCharacterJson, ClientError>
+    {
+        ApiClient::json(self.http.post(self.api(&format!("/{}/character", game_id))).json(character).send().await?).await
+    }
+
+    pub async fn change_state(&self, game_id: Uuid, new_state: &NewState) -> Result<(), ClientError>
+    {
+        ApiClient::checked(self.http.put(self.api(&format!("/{}/state", game_id))).json(new_state).send().await?).await?;
+
+        Ok(())
+    }
+
+    // The game's name has to be given, as the GM would type it, to show this is the game meant.
+    pub async fn delete_game(&self, game_id: Uuid, game_name: &str) -> Result<(), ClientError>
+    {
+        let confirmation = GameConfirmation { game_name: String::from(game_name) };
+        ApiClient::checked(self.http.delete(self.api(&format!("/games/{}", game_id))).json(&confirmation).send().await?).await?;
+
+        Ok(())
+    }
+
+    pub async fn archive_game(&self, game_id: Uuid, game_name: &str) -> Result<(), ClientError>
+    {
+        let confirmation = GameConfirmation { game_name: String::from(game_name) };
+        ApiClient::checked(self.http.post(self.api(&format!("/games/{}/archive", game_id))).json(&confirmation).send().await?).await?;
+
+        Ok(())
+    }
+
+    // A refused set is not an error here: the results say which rolls were refused, and that none were kept.
+    pub async fn add_initiative_rolls(&self, game_id: Uuid, rolls: &[InitiativeRoll], idempotency_key: Option<&str>) -> Result<InitiativeRollResults, ClientError>
+    {
+        let request = ApiClient::keyed(self.http.post(self.api(&format!("/{}/initiatives", game_id))).json(rolls), idempotency_key);
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::UNPROCESSABLE_ENTITY
+        {
+            return Ok(response.json::<InitiativeRollResults>().await?);
+        }
+        ApiClient::json(response).await
+    }
+
+    pub async fn damage_character(&self, game_id: Uuid, char_id: Uuid, damage: &Damage, idempotency_key: Option<&str>) -> Result<(), ClientError>
+    {
+        let request = self.http.post(self.api(&format!("/games/{}/characters/{}/damage", game_id, char_id))).json(damage);
+        ApiClient::checked(ApiClient::keyed(request, idempotency_key).send().await?).await?;
+
+        Ok(())
+    }
+
+    pub async fn heal_character(&self, game_id: Uuid, char_id: Uuid, healing: &Healing, idempotency_key: Option<&str>) -> Result<Recovered, ClientError>
+    {
+        let request = self.http.post(self.api(&format!("/games/{}/characters/{}/heal", game_id, char_id))).json(healing);
+        ApiClient::json(ApiClient::keyed(request, idempotency_key).send().await?).await
+    }
+}
+
+// A game's socket, held open.  Commands go up it with send, and next hands back whatever comes down: the player's notices as they land,
+// and the replies to commands, matched by the id send gave out.
+pub struct GameSocket
+{
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    game_id: Uuid,
+    next_id: u64,
+}
+
+impl GameSocket
+{
+    pub async fn send(&mut self, command: ClientCommand) -> Result<u64, ClientError>
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let frame = CommandFrame { id, game_id: self.game_id, command };
+        let text = json::to_string(&frame).map_err(|err| ClientError::Unreadable(err.to_string()))?;
+        self.stream.send(Frame::Text(text)).await?;
+
+        Ok(id)
+    }
+
+    // None once the server has closed the socket.  A server that refuses the socket says why in a reply with no id, then closes it.
+    pub async fn next(&mut self) -> Option<Result<SocketFrame, ClientError>>
+    {
+        while let Some(frame) = self.stream.next().await
+        {
+            match frame
+            {
+                Ok(Frame::Text(text)) => return Some(json::from_str::<SocketFrame>(&text)
+                    .map_err(|err| ClientError::Unreadable(format!("Unreadable frame ({}): {}", err, text)))),
+                Ok(Frame::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(err) => return Some(Err(ClientError::Socket(err))),
+            }
+        }
+        None
+    }
+
+    pub async fn close(mut self) -> Result<(), ClientError>
+    {
+        Ok(self.stream.close(None).await?)
+    }
+}
+
+impl ExportFormat
+{
+    fn as_query(&self) -> &'static str
+    {
+        match self
+        {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+impl JournalKind
+{
+    fn as_query(&self) -> &'static str
+    {
+        match self
+        {
+            JournalKind::Chat => "chat",
+            JournalKind::Roll => "roll",
+            JournalKind::CombatStarted => "combatstarted",
+            JournalKind::RoundStarted => "roundstarted",
+            JournalKind::InitiativeRolled => "initiativerolled",
+            JournalKind::InitiativeAdjusted => "initiativeadjusted",
+            JournalKind::ActionTaken => "actiontaken",
+            JournalKind::Damage => "damage",
+            JournalKind::Healed => "healed",
+            JournalKind::EdgeSpent => "edgespent",
+            JournalKind::Rewound => "rewound",
+            JournalKind::CheckpointRestored => "checkpointrestored",
+            JournalKind::HouseRule => "houserule",
+            JournalKind::GmProxy => "gmproxy",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use reqwest::{Client, cookie::Jar};
+    use rocket::{form::{FromFormField, ValueField}, futures::StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{accept_async, connect_async};
+    use uuid::Uuid;
+
+    use crate::gamerunner::{dispatcher::Request, notifier::ChangeKind, testing::TestTable};
+    use crate::http::{messaging::serve_socket, queue::{RunnerPipe, OverflowPolicy}, serde::{ClientCommand, ChangeDetail, ExportFormat, JournalKind, SocketFrame}};
+
+    use super::{ApiClient, GameSocket};
+
+    fn client(base_url: &str) -> ApiClient
+    {
+        ApiClient { http: Client::new(), cookies: Arc::new(Jar::default()), base_url: String::from(base_url.trim_end_matches('/')) }
+    }
+
+    #[test]
+    pub fn a_game_socket_is_found_under_the_servers_base_path_over_ws_or_wss()
+    {
+        let game_id = Uuid::new_v4();
+
+        assert_eq!(format!("ws://localhost:8000/messages/{}/socket", game_id), client("http://localhost:8000/").socket_url(game_id));
+        assert_eq!(format!("wss://table.example.com/combat/messages/{}/socket", game_id), client("https://table.example.com/combat").socket_url(game_id));
+        assert_eq!(String::from("https://table.example.com/combat/api/games"), client("https://table.example.com/combat").api("/games"));
+    }
+
+    #[test]
+    pub fn the_journal_filters_the_client_asks_for_are_ones_the_server_reads()
+    {
+        let kinds = [JournalKind::Chat, JournalKind::Roll, JournalKind::CombatStarted, JournalKind::RoundStarted, JournalKind::InitiativeRolled,
+            JournalKind::InitiativeAdjusted, JournalKind::ActionTaken, JournalKind::Damage, JournalKind::Healed, JournalKind::EdgeSpent,
+            JournalKind::Rewound, JournalKind::CheckpointRestored, JournalKind::HouseRule, JournalKind::GmProxy];
+
+        for kind in kinds
+        {
+            let read = JournalKind::from_value(ValueField::from_value(kind.as_query()));
+            assert_eq!(Some(kind.as_query()), read.ok().map(|read| read.as_query()));
+        }
+        for format in [ExportFormat::Csv, ExportFormat::Jsonl]
+        {
+            let read = ExportFormat::from_value(ValueField::from_value(format.as_query()));
+            assert_eq!(Some(format.as_query()), read.ok().map(|read| read.as_query()));
+        }
+    }
+
+    // The client's socket against the server's end of one, over loopback: a command sent up it is answered by its id, and what the
+    // player is told comes down it typed by kind.
+    #[tokio::test]
+    pub async fn a_game_socket_sends_commands_and_hands_back_their_replies_and_the_players_notices()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player_id, character_id) = table.players[0];
+        let (game_id, pipe) = (table.game_id, RunnerPipe::new(table.runner.clone(), OverflowPolicy::Wait));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move
+        {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let (outgoing, incoming) = accept_async(tcp).await.unwrap().split();
+            serve_socket(game_id, player_id, pipe, incoming, outgoing).await
+        });
+
+        let (stream, _) = connect_async(format!("ws://{}/", address)).await.unwrap();
+        let mut socket = GameSocket { stream, game_id, next_id: 1 };
+
+        let id = socket.send(ClientCommand::RollInitiative { char_id: character_id, roll: 10 }).await.unwrap();
+        loop
+        {
+            match socket.next().await.expect("The socket closed before the roll was answered.").unwrap()
+            {
+                SocketFrame::Reply(reply) if reply.id == Some(id) => { assert!(reply.ok); break; },
+                _ => {},
+            }
+        }
+
+        table.send(table.gm, Request::StartCombatRound).await;
+        loop
+        {
+            match socket.next().await.expect("The socket closed before the player was told it was their turn.").unwrap()
+            {
+                SocketFrame::Notice(notice) if notice.kind == ChangeKind::YourTurn =>
+                {
+                    assert!(matches!(notice.detail, ChangeDetail::YourTurn { char_id } if char_id == character_id));
+                    break;
+                },
+                _ => {},
+            }
+        }
+
+        socket.close().await.unwrap();
+        assert!(server.await.unwrap().is_ok());
+    }
+}
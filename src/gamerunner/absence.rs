@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::PlayerId;
+
+// What becomes of a player's characters once the player has kept the table waiting longer than the policy allows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AbsentFallback
+{
+    // The player is marked absent, so the GM may roll and act for them.
+    DelegateToGm,
+    // Their characters' turns are passed over without spending any actions.
+    Skip,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AbsencePolicy
+{
+    pub timeout: Duration,
+    pub fallback: AbsentFallback,
+}
+
+// A game's absence policy, if the GM has set one, along with when each player was last heard from and which of them the policy has
+// taken control away from.  Nothing here runs on a clock: a player lapses when the runner next looks, which is whenever the game gets a
+// message or the review clock comes round.  Control comes back when the player is heard from again, or when the next round begins.
+#[derive(Default, Clone)]
+pub struct AbsenceWatch
+{
+    policy: Option<AbsencePolicy>,
+    // Players not heard from since the policy was set count from then.
+    since: Option<Instant>,
+    last_heard: HashMap<PlayerId, Instant>,
+    lapsed: HashSet<PlayerId>,
+}
+
+impl AbsenceWatch
+{
+    pub fn policy(&self) -> Option<AbsencePolicy>
+    {
+        self.policy
+    }
+
+    // Starts every player's clock afresh, so a new policy does not take control from anyone for silence that came before it.  Returns
+    // the players the old policy had lapsed, whose control the caller should give back.
+    pub fn set_policy(&mut self, policy: Option<AbsencePolicy>, now: Instant) -> Vec<PlayerId>
+    {
+        self.policy = policy;
+        self.since = Some(now);
+        self.last_heard.clear();
+
+        self.lapsed.drain().collect()
+    }
+
+    // Returns whether the player had lapsed, and so has just taken control back.
+    pub fn heard_from(&mut self, player_id: PlayerId, now: Instant) -> bool
+    {
+        self.last_heard.insert(player_id, now);
+
+        self.lapsed.remove(&player_id)
+    }
+
+    pub fn is_lapsed(&self, player_id: &PlayerId) -> bool
+    {
+        self.lapsed.contains(player_id)
+    }
+
+    // Whether the player has been quiet past the policy's timeout and has not already lapsed.
+    pub fn overdue(&self, player_id: &PlayerId, now: Instant) -> bool
+    {
+        let Some(policy) = self.policy
+        else { return false };
+        let Some(heard) = self.last_heard.get(player_id).copied().or(self.since)
+        else { return false };
+
+        !self.lapsed.contains(player_id) && now.saturating_duration_since(heard) >= policy.timeout
+    }
+
+    pub fn lapse(&mut self, player_id: PlayerId)
+    {
+        self.lapsed.insert(player_id);
+    }
+
+    // A lapse only lasts the round it happened in.  Everyone who lapsed gets control back and a fresh timeout for the new round.
+    pub fn new_round(&mut self, now: Instant) -> Vec<PlayerId>
+    {
+        let restored: Vec<PlayerId> = self.lapsed.drain().collect();
+        for player_id in &restored
+        {
+            self.last_heard.insert(*player_id, now);
+        }
+
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{Duration, Instant};
+
+    use uuid::Uuid;
+
+    use super::{AbsencePolicy, AbsenceWatch, AbsentFallback};
+
+    #[test]
+    pub fn a_player_is_overdue_once_quiet_past_the_timeout_and_lapses_until_heard_from_or_the_round_ends()
+    {
+        let (started, player_id) = (Instant::now(), Uuid::new_v4());
+        let mut watch = AbsenceWatch::default();
+        assert!(!watch.overdue(&player_id, started + Duration::from_secs(3600)));
+
+        watch.set_policy(Some(AbsencePolicy { timeout: Duration::from_secs(60), fallback: AbsentFallback::Skip }), started);
+        assert!(!watch.overdue(&player_id, started + Duration::from_secs(59)));
+        assert!(watch.overdue(&player_id, started + Duration::from_secs(60)));
+
+        watch.lapse(player_id);
+        assert!(watch.is_lapsed(&player_id));
+        assert!(!watch.overdue(&player_id, started + Duration::from_secs(120)));
+        assert!(watch.heard_from(player_id, started + Duration::from_secs(120)));
+        assert!(!watch.heard_from(player_id, started + Duration::from_secs(121)));
+
+        watch.lapse(player_id);
+        assert_eq!(vec![player_id], watch.new_round(started + Duration::from_secs(200)));
+        assert!(!watch.overdue(&player_id, started + Duration::from_secs(259)));
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::tracker::game::{Game, Phase, TurnState, AfterPass};
+
+use super::CharacterId;
+
+// The requests that move a fight along, cut down to those the game would take right now, so a client can grey out whatever would only be
+// refused for the phase or whose turn it is.  Requests that are taken in any phase - queries, chat, character edits - are left out.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AllowedRequests
+{
+    pub gm: Vec<&'static str>,
+    // What the owner of each combatant could send for them.
+    pub characters: BTreeMap<CharacterId, Vec<&'static str>>,
+}
+
+impl AllowedRequests
+{
+    pub fn of(game: &Game) -> AllowedRequests
+    {
+        let next = game.allowed_transitions();
+        let mut gm = Vec::new();
+
+        match game.phase()
+        {
+            Phase::PreCombat =>
+            {
+                gm.push("StartCombat");
+                if !game.staged_encounters().is_empty()
+                {
+                    gm.push("LaunchEncounter");
+                }
+            },
+            Phase::Initiative => gm.push("OverrideInitiativeRoll"),
+            Phase::ActionRound =>
+            {
+                match game.after_pass()
+                {
+                    Some(AfterPass::NextPass) => gm.extend(["AdvancePass", "ContinueCombat"]),
+                    Some(AfterPass::NextRound) => gm.push("ContinueCombat"),
+                    None if game.waiting_for().map_or(false, |blockers| blockers.is_empty()) => gm.push("AdvanceTurn"),
+                    None => {},
+                }
+            },
+        }
+        for phase in next
+        {
+            gm.push(match phase
+            {
+                Phase::PreCombat => "EndCombat",
+                Phase::Initiative => "BeginInitiativePhase",
+                Phase::ActionRound => "StartCombatRound",
+            });
+        }
+
+        let characters = game.get_combatants().into_iter().map(|id| (id, match game.turn_state(&id)
+        {
+            TurnState::AwaitingInitiative => vec!["AddInitiativeRoll", "AddInitiativeRolls"],
+            TurnState::Acting => vec!["TakeAction", "TakeActions", "DeclareMovement", "DeclareRangedAttack", "DeclareMatrixAction", "GoFullDefense"],
+            TurnState::Waiting | TurnState::Resolved if game.phase() == Phase::ActionRound => vec!["GoFullDefense"],
+            TurnState::OutOfCombat | TurnState::Waiting | TurnState::Resolved => Vec::new(),
+        })).collect();
+
+        AllowedRequests { gm, characters }
+    }
+
+    // What one player could send: everything their own characters could, and the GM's requests if they run the game.
+    pub fn for_player(&self, gm: bool, owned: &HashSet<CharacterId>) -> Vec<&'static str>
+    {
+        let mut allowed = if gm { self.gm.clone() } else { Vec::new() };
+        for request in self.characters.iter().filter(|(id, _)| owned.contains(id)).flat_map(|(_, requests)| requests)
+        {
+            if !allowed.contains(request)
+            {
+                allowed.push(*request);
+            }
+        }
+
+        allowed
+    }
+}
@@ -1,4 +1,4 @@
-use log::debug;
+use tracing::debug;
 
 use super::{PlayerId, GameId, dispatcher::Request, registry::GameRegistry};
 
@@ -41,7 +41,7 @@ pub fn authorize<'a, 'b>(player_id_opt: Option<GameId>, game_id_opt: Option<Play
     // Authority { player_id: todo!(), game_id: todo!(), resource_role: Role::RolePlayer, request: msg.msg }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub enum Role
 {
     RoleGM(PlayerId, GameId),
@@ -71,6 +71,17 @@ impl Authority
     //     self.game_id
     // }
 
+    // For requests that arrive inside another one (a batch), which run with the role their carrier was authorized for.
+    pub fn new(resource_role: Role, request: Request) -> Authority
+    {
+        Authority { resource_role, request }
+    }
+
+    pub fn into_parts(self) -> (Role, Request)
+    {
+        (self.resource_role, self.request)
+    }
+
     pub fn resource_role<'a>(&'a self) -> &'a Role
     {
         &self.resource_role
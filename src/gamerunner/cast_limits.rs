@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::tracker::character::Character;
+
+// How big a game's cast may grow, and whether two of its characters may share a name.  Every limit is off until the GM sets it, so a
+// game nobody configures behaves as it always has.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct CastLimits
+{
+    // Characters any one player may bring.  The GM runs the NPCs and is not held to it.
+    pub per_player: Option<usize>,
+    // Characters in the whole cast, the GM's included.
+    pub cast: Option<usize>,
+    // Whether a new character's name must differ from everyone already in the cast, ignoring case and surrounding spaces.
+    pub unique_names: bool,
+}
+
+// Why the registry would not add a character.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CastRefusal
+{
+    NoSuchGame,
+    PlayerLimitReached(usize),
+    CastFull(usize),
+    DuplicateName(String),
+}
+
+impl CastLimits
+{
+    // Checks a character against the cast it would join; `owned` is how many the adding player already has there, None for the GM.
+    pub fn admit(&self, character: &Character, cast: &[Arc<Character>], owned: Option<usize>) -> Result<(), CastRefusal>
+    {
+        if let (Some(limit), Some(owned)) = (self.per_player, owned)
+        {
+            if owned >= limit
+            {
+                return Err(CastRefusal::PlayerLimitReached(limit));
+            }
+        }
+
+        if let Some(limit) = self.cast
+        {
+            if cast.len() >= limit
+            {
+                return Err(CastRefusal::CastFull(limit));
+            }
+        }
+
+        if self.unique_names && cast.iter().any(|member| same_name(&member.name, &character.name))
+        {
+            return Err(CastRefusal::DuplicateName(character.name.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+fn same_name(left: &str, right: &str) -> bool
+{
+    left.trim().to_lowercase() == right.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use crate::tracker::character::{Character, Metatypes};
+
+    use super::{CastLimits, CastRefusal};
+
+    #[test]
+    pub fn each_limit_refuses_only_once_it_is_reached()
+    {
+        let cast = vec![Arc::new(Character::new_pc(Metatypes::Orc, String::from("Mork"))), Arc::new(Character::new_npc(Metatypes::Dwarf, String::from("Dorf")))];
+        let newcomer = Character::new_pc(Metatypes::Elf, String::from("Elfie"));
+
+        assert_eq!(Ok(()), CastLimits::default().admit(&newcomer, &cast, Some(5)));
+
+        let limits = CastLimits { per_player: Some(1), ..CastLimits::default() };
+        assert_eq!(Err(CastRefusal::PlayerLimitReached(1)), limits.admit(&newcomer, &cast, Some(1)));
+        assert_eq!(Ok(()), limits.admit(&newcomer, &cast, Some(0)));
+        assert_eq!(Ok(()), limits.admit(&newcomer, &cast, None));
+
+        let limits = CastLimits { cast: Some(2), ..CastLimits::default() };
+        assert_eq!(Err(CastRefusal::CastFull(2)), limits.admit(&newcomer, &cast, None));
+        assert_eq!(Ok(()), limits.admit(&newcomer, &cast[..1], None));
+    }
+
+    #[test]
+    pub fn unique_names_ignore_case_and_surrounding_spaces()
+    {
+        let cast = vec![Arc::new(Character::new_pc(Metatypes::Orc, String::from("Mork")))];
+        let limits = CastLimits { unique_names: true, ..CastLimits::default() };
+
+        assert_eq!(Err(CastRefusal::DuplicateName(String::from(" mork"))), limits.admit(&Character::new_pc(Metatypes::Elf, String::from(" mork")), &cast, Some(0)));
+        assert_eq!(Ok(()), limits.admit(&Character::new_pc(Metatypes::Elf, String::from("Mindy")), &cast, Some(0)));
+        assert_eq!(Ok(()), CastLimits::default().admit(&Character::new_pc(Metatypes::Elf, String::from("Mork")), &cast, Some(0)));
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+// How long a game's initiative phase may stay open before the server rolls for the GM's NPCs and nudges whoever else has yet to roll.
+// Like the absence watch nothing here runs on a clock; the deadline passes when the runner next looks after it, on a message to the game
+// or the review clock's next round.
+#[derive(Default, Clone)]
+pub struct InitiativeDeadline
+{
+    timeout: Option<Duration>,
+    opened: Option<Instant>,
+}
+
+impl InitiativeDeadline
+{
+    pub fn timeout(&self) -> Option<Duration>
+    {
+        self.timeout
+    }
+
+    // Counts from when the phase opened, so a timeout set partway through the phase can already have passed.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>)
+    {
+        self.timeout = timeout;
+    }
+
+    pub fn phase_opened(&mut self, now: Instant)
+    {
+        self.opened = Some(now);
+    }
+
+    pub fn passed(&self, now: Instant) -> bool
+    {
+        match (self.timeout, self.opened)
+        {
+            (Some(timeout), Some(opened)) => now.saturating_duration_since(opened) >= timeout,
+            _ => false,
+        }
+    }
+
+    // The deadline only passes once a phase; it is back when the next phase opens.
+    pub fn clear(&mut self)
+    {
+        self.opened = None;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{Duration, Instant};
+
+    use super::InitiativeDeadline;
+
+    #[test]
+    pub fn the_deadline_passes_once_the_phase_has_been_open_for_the_timeout_and_not_again_until_the_next_phase()
+    {
+        let opened = Instant::now();
+        let mut deadline = InitiativeDeadline::default();
+        deadline.phase_opened(opened);
+        assert!(!deadline.passed(opened + Duration::from_secs(3600)));
+
+        deadline.set_timeout(Some(Duration::from_secs(90)));
+        assert!(!deadline.passed(opened + Duration::from_secs(89)));
+        assert!(deadline.passed(opened + Duration::from_secs(90)));
+
+        deadline.clear();
+        assert!(!deadline.passed(opened + Duration::from_secs(200)));
+        deadline.phase_opened(opened + Duration::from_secs(300));
+        assert!(deadline.passed(opened + Duration::from_secs(390)));
+    }
+}
@@ -1,14 +1,16 @@
 use std::sync::Arc;
-use std::{collections::HashMap};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{collections::{HashMap, HashSet}};
+use std::time::{Duration, Instant};
 
-use tokio::sync::mpsc::{channel, Sender, Receiver};
+use tokio::sync::{mpsc::{channel, Sender, Receiver}, watch};
 use tokio::sync::oneshot::Sender as OneShotSender;
-use log::{debug, error};
+use tracing::{debug, error};
 use uuid::Uuid;
 
-use crate::{tracker::{game::{Game, ActionType, GameError, ErrorKind as GameErrorKind}, character::Character}};
+use crate::{tracker::{game::{Game, VisibilityOptions, ActionType, ActionBudget, AvailableAction, FullDefenseCost, InitiativePreview, CharacterSummary, PatchOutcome, AfterPass, GameError, ErrorKind as GameErrorKind, RewindTarget, TimedEvent, SkipReason, Phase}, character::{Character, CharacterPatch, RollMacro, WoundView}, gear::ArmorTestType, movement::{Gait, MovementRecord}, combat::{RangedAttack, AttackModifiers, Engagement, AreaAttack, AreaResolution, DamageEvent, ResistancePrompt, DamageType}, magic::{SpellDeclaration, SustainedSpell, Plane}, encounter::{StagedEncounter, TriggerAction}, journal::{ChatAudience, ChatLine, RollRecord, JournalEntry, JournalFilter}, report::{CombatReport, ReportScope}, timeline::RoundTimeline, names::Name, house_rules::HouseRuleEvent, matrix::{MatrixTarget, MatrixActionDeclaration, MatrixResolution, MatrixGrid}, rules::{Edition, ActionLabels, Healing, Recovery}, archetypes::Archetype, catalog::CatalogAction}};
 
-use super::{registry::GameRegistry, GameId, ErrorKind, Error, PlayerId, WhatChanged, authority::{Authority, Role}, CharacterId, notifier::{Notification, PlayerJoined, NewCharacter}};
+use super::{hooks::HookChain, registry::{GameRegistry, DeliveryRecord, SavedGame}, absence::{AbsencePolicy, AbsentFallback}, notes::{NoteSubject, GmAnnotation}, cast_limits::{CastLimits, CastRefusal}, sync::CombatSync, replay::{Replayed, IdempotencyKey}, allowed::AllowedRequests, lobby::GameSummary, GameId, ErrorKind, Error, ErrorContext, TurnAdvanced, PlayerId, WhatChanged, authority::{Authority, Role}, CharacterId, notifier::{Notification, PlayerJoined, NewCharacter, InitiativeEntry, RoundSummary}};
 
 pub struct Message
 {
@@ -20,22 +22,58 @@ pub struct Message
 
 pub enum Request
 {
-    Enumerate,
+    Enumerate(GameQuery),
+    Reconnect(Delivery),
+    FetchInbox,
+    // The player's inbox after the given number, left in place, for a long-poll through the game the message is sent to.
+    InboxAfter(Option<usize>),
     New,
     Delete,
+    Archive,
+    CloneGame,
+    DeleteAccount,
     NewPlayer,
     JoinGame,
     AddCharacter(Character),
+    QuickCreateCharacter(Archetype, String),
     GetFullCast,
     GetNpcCast,
     GetPcCast,
     GetCharacter(Uuid),
     StartCombat(Vec<Uuid>),
     AddInitiativeRoll(Roll),
+    // Several rolls at once - a player's whole crew, or every NPC the GM runs - taken all together or not at all.
+    AddInitiativeRolls(Vec<Roll>),
+    OverrideInitiativeRoll(Roll),
+    AdjustInitiative(InitiativeAdjustment),
+    ScheduleEvent(EventSchedule),
     BeginInitiativePhase,
     QueryInitiativePhase,
     StartCombatRound,
     TakeAction(Action),
+    TakeActions(Vec<Action>),
+    ContinueCombat,
+    SetAutoAdvance(bool),
+    SetVisibility(VisibilityOptions),
+    SetAutoCyclePasses(bool),
+    SetCarryOver(bool),
+    Rest(Vec<CharacterId>),
+    SitOut(SittingOut),
+    DeclareMovement(Movement),
+    DeclareRangedAttack(RangedAttack),
+    Engage(MeleeTarget),
+    Disengage(MeleeTarget),
+    QueryEngagements,
+    GoFullDefense(Uuid),
+    DeclareAreaAttack(AreaAttack),
+    QueryResistanceTests,
+    ResistanceTestMade(Uuid),
+    CastSpell(SpellDeclaration),
+    DropSpell(SpellDrop),
+    QuerySustainedSpells,
+    OverridePasses(PassOverride),
+    QuerySoakPool(SoakQuery),
+    DegradeArmor(ArmorDamage),
     AdvanceTurn,
     AdvancePass,
     EndCombat,
@@ -49,27 +87,349 @@ pub enum Request
     NextInitiative,
     AllRemainingInitiatives,
     QueryAllCombatants,
+    QueryRemainingActions(Uuid),
+    AvailableActions(CharacterId),
+    QueryMovementLedger,
     BeginEndOfTurn,
+    Chat(ChatMessage),
+    QueryChat,
+    DefineRollMacro(MacroDefinition),
+    RollDice(DiceRoll),
+    ApplyDamage(DamageApplication),
+    Heal(HealingApplication),
+    ApplyDamageEvent(DamageEvent),
+    SpendEdge(EdgeSpend),
+    CombatReport(ReportScope),
+    RoundTimeline,
+    ExportJournal(JournalFilter),
+    JournalAfter(Option<usize>),
+    Rewind(RewindTarget),
+    SaveCheckpoint(String),
+    RestoreCheckpoint(String),
+    ListCheckpoints,
+    StageEncounter(StagedEncounter),
+    QueryStagedEncounters,
+    AnnotateForGm(NoteSubject, Option<GmAnnotation>),
+    QueryGmAnnotations,
+    ResyncCombat,
+    LaunchEncounter(String),
+    PreviewInitiativeOrder,
+    RemindInitiatives,
+    SetInitiativeTimeout(Option<Duration>),
+    SetCastLimits(CastLimits),
+    // What the lobby calls the game.
+    NameGame(String),
+    SetEdition(Edition),
+    SetActionLabels(Option<ActionLabels>),
+    QueryActionLabels,
+    DefineCatalogAction(CatalogAction),
+    RemoveCatalogAction(String),
+    QueryActionCatalog,
+    GetPhase,
+    QueryAllowedRequests,
+    AttachHouseRule(HouseRuleScript),
+    RemoveHouseRule(String),
+    QueryHouseRules,
+    QueryDeliveryHealth,
+    SetPlayerAbsent(PlayerAbsence),
+    SetAbsencePolicy(Option<AbsencePolicy>),
+    RegisterMatrixTarget(MatrixTarget),
+    JackIn(CharacterId),
+    JackOut(CharacterId),
+    DeclareMatrixAction(MatrixActionDeclaration),
+    QueryMatrix,
+    ChangePlane(PlaneChange),
+    UpdateCharacter { id: CharacterId, patch: CharacterPatch },
+    ApproveCharacterUpdate(CharacterId),
+    Batch(Vec<Request>),
+    // Sent by the runner's own clock rather than any player: every game's initiative deadline and absence policy is checked, as they are
+    // for a game each time it gets a message.
+    ReviewClocks,
+    // The request under a key the client picked.  Sent again to the same game with the same key and payload, it is answered with the
+    // first outcome rather than run twice; keys are the sender's own, and only their latest few are remembered.
+    Idempotent(IdempotencyKey, Box<Request>),
 }
 
+impl Request
+{
+    // The variant name on its own, for tagging logs and spans without dumping whatever the request carries.
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Request::Enumerate(_) => "Enumerate",
+            Request::Reconnect(_) => "Reconnect",
+            Request::FetchInbox => "FetchInbox",
+            Request::InboxAfter(_) => "InboxAfter",
+            Request::New => "New",
+            Request::Delete => "Delete",
+            Request::Archive => "Archive",
+            Request::CloneGame => "CloneGame",
+            Request::DeleteAccount => "DeleteAccount",
+            Request::NewPlayer => "NewPlayer",
+            Request::JoinGame => "JoinGame",
+            Request::AddCharacter(_) => "AddCharacter",
+            Request::QuickCreateCharacter(..) => "QuickCreateCharacter",
+            Request::GetFullCast => "GetFullCast",
+            Request::GetNpcCast => "GetNpcCast",
+            Request::GetPcCast => "GetPcCast",
+            Request::GetCharacter(_) => "GetCharacter",
+            Request::StartCombat(_) => "StartCombat",
+            Request::AddInitiativeRoll(_) => "AddInitiativeRoll",
+            Request::AddInitiativeRolls(_) => "AddInitiativeRolls",
+            Request::OverrideInitiativeRoll(_) => "OverrideInitiativeRoll",
+            Request::AdjustInitiative(_) => "AdjustInitiative",
+            Request::ScheduleEvent(_) => "ScheduleEvent",
+            Request::BeginInitiativePhase => "BeginInitiativePhase",
+            Request::QueryInitiativePhase => "QueryInitiativePhase",
+            Request::StartCombatRound => "StartCombatRound",
+            Request::TakeAction(_) => "TakeAction",
+            Request::TakeActions(_) => "TakeActions",
+            Request::ContinueCombat => "ContinueCombat",
+            Request::SetAutoAdvance(_) => "SetAutoAdvance",
+            Request::SetVisibility(_) => "SetVisibility",
+            Request::SetAutoCyclePasses(_) => "SetAutoCyclePasses",
+            Request::SetCarryOver(_) => "SetCarryOver",
+            Request::Rest(_) => "Rest",
+            Request::SitOut(_) => "SitOut",
+            Request::DeclareMovement(_) => "DeclareMovement",
+            Request::DeclareRangedAttack(_) => "DeclareRangedAttack",
+            Request::Engage(_) => "Engage",
+            Request::Disengage(_) => "Disengage",
+            Request::QueryEngagements => "QueryEngagements",
+            Request::GoFullDefense(_) => "GoFullDefense",
+            Request::DeclareAreaAttack(_) => "DeclareAreaAttack",
+            Request::QueryResistanceTests => "QueryResistanceTests",
+            Request::ResistanceTestMade(_) => "ResistanceTestMade",
+            Request::CastSpell(_) => "CastSpell",
+            Request::DropSpell(_) => "DropSpell",
+            Request::QuerySustainedSpells => "QuerySustainedSpells",
+            Request::OverridePasses(_) => "OverridePasses",
+            Request::QuerySoakPool(_) => "QuerySoakPool",
+            Request::DegradeArmor(_) => "DegradeArmor",
+            Request::AdvanceTurn => "AdvanceTurn",
+            Request::AdvancePass => "AdvancePass",
+            Request::EndCombat => "EndCombat",
+            Request::QueryCurrentState => "QueryCurrentState",
+            Request::QueryMissingInitiatives => "QueryMissingInitiatives",
+            Request::WhoGoesThisTurn => "WhoGoesThisTurn",
+            Request::WhatHasYetToHappenThisTurn => "WhatHasYetToHappenThisTurn",
+            Request::WhatHappensNextTurn => "WhatHappensNextTurn",
+            Request::AllEventsThisPass => "AllEventsThisPass",
+            Request::CurrentInitiative => "CurrentInitiative",
+            Request::NextInitiative => "NextInitiative",
+            Request::AllRemainingInitiatives => "AllRemainingInitiatives",
+            Request::QueryAllCombatants => "QueryAllCombatants",
+            Request::QueryRemainingActions(_) => "QueryRemainingActions",
+            Request::AvailableActions(_) => "AvailableActions",
+            Request::QueryMovementLedger => "QueryMovementLedger",
+            Request::BeginEndOfTurn => "BeginEndOfTurn",
+            Request::Chat(_) => "Chat",
+            Request::QueryChat => "QueryChat",
+            Request::DefineRollMacro(_) => "DefineRollMacro",
+            Request::RollDice(_) => "RollDice",
+            Request::ApplyDamage(_) => "ApplyDamage",
+            Request::Heal(_) => "Heal",
+            Request::ApplyDamageEvent(_) => "ApplyDamageEvent",
+            Request::SpendEdge(_) => "SpendEdge",
+            Request::CombatReport(_) => "CombatReport",
+            Request::RoundTimeline => "RoundTimeline",
+            Request::ExportJournal(_) => "ExportJournal",
+            Request::JournalAfter(_) => "JournalAfter",
+            Request::Rewind(_) => "Rewind",
+            Request::SaveCheckpoint(_) => "SaveCheckpoint",
+            Request::RestoreCheckpoint(_) => "RestoreCheckpoint",
+            Request::ListCheckpoints => "ListCheckpoints",
+            Request::StageEncounter(_) => "StageEncounter",
+            Request::QueryStagedEncounters => "QueryStagedEncounters",
+            Request::AnnotateForGm(..) => "AnnotateForGm",
+            Request::QueryGmAnnotations => "QueryGmAnnotations",
+            Request::ResyncCombat => "ResyncCombat",
+            Request::LaunchEncounter(_) => "LaunchEncounter",
+            Request::PreviewInitiativeOrder => "PreviewInitiativeOrder",
+            Request::RemindInitiatives => "RemindInitiatives",
+            Request::SetInitiativeTimeout(_) => "SetInitiativeTimeout",
+            Request::SetCastLimits(_) => "SetCastLimits",
+            Request::NameGame(_) => "NameGame",
+            Request::SetEdition(_) => "SetEdition",
+            Request::SetActionLabels(_) => "SetActionLabels",
+            Request::QueryActionLabels => "QueryActionLabels",
+            Request::DefineCatalogAction(_) => "DefineCatalogAction",
+            Request::RemoveCatalogAction(_) => "RemoveCatalogAction",
+            Request::QueryActionCatalog => "QueryActionCatalog",
+            Request::GetPhase => "GetPhase",
+            Request::QueryAllowedRequests => "QueryAllowedRequests",
+            Request::AttachHouseRule(_) => "AttachHouseRule",
+            Request::RemoveHouseRule(_) => "RemoveHouseRule",
+            Request::QueryHouseRules => "QueryHouseRules",
+            Request::QueryDeliveryHealth => "QueryDeliveryHealth",
+            Request::SetPlayerAbsent(_) => "SetPlayerAbsent",
+            Request::SetAbsencePolicy(_) => "SetAbsencePolicy",
+            Request::RegisterMatrixTarget(_) => "RegisterMatrixTarget",
+            Request::JackIn(_) => "JackIn",
+            Request::JackOut(_) => "JackOut",
+            Request::DeclareMatrixAction(_) => "DeclareMatrixAction",
+            Request::QueryMatrix => "QueryMatrix",
+            Request::ChangePlane(_) => "ChangePlane",
+            Request::UpdateCharacter { .. } => "UpdateCharacter",
+            Request::ApproveCharacterUpdate(_) => "ApproveCharacterUpdate",
+            Request::Batch(_) => "Batch",
+            Request::ReviewClocks => "ReviewClocks",
+            Request::Idempotent(..) => "Idempotent",
+        }
+    }
+
+    // The character the request is about, where it is about one.  Targets are left out - it is the actor an error is usually about.
+    pub fn character_id(&self) -> Option<CharacterId>
+    {
+        match self
+        {
+            Request::GetCharacter(id) | Request::GoFullDefense(id) | Request::ResistanceTestMade(id) | Request::QueryRemainingActions(id)
+                | Request::AvailableActions(id) | Request::JackIn(id) | Request::JackOut(id) | Request::ApproveCharacterUpdate(id)
+                | Request::UpdateCharacter { id, .. } => Some(*id),
+            Request::AddInitiativeRoll(Roll { character_id, .. }) | Request::OverrideInitiativeRoll(Roll { character_id, .. })
+                | Request::AdjustInitiative(InitiativeAdjustment { character_id, .. }) | Request::TakeAction(Action { character_id, .. })
+                | Request::DeclareMovement(Movement { character_id, .. }) | Request::Engage(MeleeTarget { character_id, .. })
+                | Request::Disengage(MeleeTarget { character_id, .. }) | Request::DropSpell(SpellDrop { character_id, .. })
+                | Request::OverridePasses(PassOverride { character_id, .. }) | Request::QuerySoakPool(SoakQuery { character_id, .. })
+                | Request::DegradeArmor(ArmorDamage { character_id, .. }) | Request::DefineRollMacro(MacroDefinition { character_id, .. })
+                | Request::RollDice(DiceRoll { character_id, .. }) | Request::SpendEdge(EdgeSpend { character_id, .. })
+                | Request::ChangePlane(PlaneChange { character_id, .. }) | Request::SitOut(SittingOut { character_id, .. }) => Some(*character_id),
+            Request::DeclareRangedAttack(attack) => Some(attack.attacker),
+            Request::DeclareAreaAttack(attack) => Some(attack.attacker),
+            Request::CastSpell(spell) => Some(spell.caster),
+            Request::DeclareMatrixAction(declaration) => Some(declaration.decker),
+            Request::ApplyDamage(application) => Some(application.target),
+            Request::Heal(application) => Some(application.character_id),
+            Request::Idempotent(_, request) => request.character_id(),
+            _ => None,
+        }
+    }
+
+    // Whether the request only touches the one game it is sent to, which is what lets a batch of them be undone as a unit.  Anything
+    // that registers players, creates or deletes games, or reads a player's own mailbox acts outside the game and stays out of batches.
+    // The requests are named one by one, so that one added later stays out too until someone has checked where it reaches.
+    pub fn batchable(&self) -> bool
+    {
+        matches!(self, Request::AddCharacter(_) | Request::QuickCreateCharacter(..) | Request::GetFullCast | Request::GetNpcCast
+            | Request::GetPcCast | Request::GetCharacter(_) | Request::StartCombat(_) | Request::AddInitiativeRoll(_)
+            | Request::AddInitiativeRolls(_) | Request::OverrideInitiativeRoll(_) | Request::AdjustInitiative(_) | Request::ScheduleEvent(_)
+            | Request::BeginInitiativePhase | Request::QueryInitiativePhase | Request::StartCombatRound | Request::TakeAction(_)
+            | Request::TakeActions(_) | Request::ContinueCombat | Request::SetAutoAdvance(_) | Request::SetVisibility(_)
+            | Request::SetAutoCyclePasses(_) | Request::SetCarryOver(_) | Request::Rest(_) | Request::SitOut(_) | Request::DeclareMovement(_)
+            | Request::DeclareRangedAttack(_) | Request::Engage(_) | Request::Disengage(_) | Request::QueryEngagements
+            | Request::GoFullDefense(_) | Request::DeclareAreaAttack(_) | Request::QueryResistanceTests | Request::ResistanceTestMade(_)
+            | Request::CastSpell(_) | Request::DropSpell(_) | Request::QuerySustainedSpells | Request::OverridePasses(_)
+            | Request::QuerySoakPool(_) | Request::DegradeArmor(_) | Request::AdvanceTurn | Request::AdvancePass | Request::EndCombat
+            | Request::QueryCurrentState | Request::QueryMissingInitiatives | Request::WhoGoesThisTurn | Request::WhatHasYetToHappenThisTurn
+            | Request::WhatHappensNextTurn | Request::AllEventsThisPass | Request::CurrentInitiative | Request::NextInitiative
+            | Request::AllRemainingInitiatives | Request::QueryAllCombatants | Request::QueryRemainingActions(_) | Request::AvailableActions(_)
+            | Request::QueryMovementLedger | Request::BeginEndOfTurn | Request::Chat(_) | Request::QueryChat | Request::DefineRollMacro(_)
+            | Request::RollDice(_) | Request::ApplyDamage(_) | Request::Heal(_) | Request::ApplyDamageEvent(_) | Request::SpendEdge(_)
+            | Request::CombatReport(_) | Request::RoundTimeline | Request::ExportJournal(_) | Request::JournalAfter(_) | Request::Rewind(_)
+            | Request::SaveCheckpoint(_) | Request::RestoreCheckpoint(_) | Request::ListCheckpoints | Request::StageEncounter(_)
+            | Request::QueryStagedEncounters | Request::AnnotateForGm(..) | Request::QueryGmAnnotations | Request::ResyncCombat
+            | Request::LaunchEncounter(_) | Request::PreviewInitiativeOrder | Request::RemindInitiatives | Request::SetInitiativeTimeout(_)
+            | Request::SetCastLimits(_) | Request::NameGame(_) | Request::SetEdition(_) | Request::SetActionLabels(_) | Request::QueryActionLabels
+            | Request::DefineCatalogAction(_) | Request::RemoveCatalogAction(_) | Request::QueryActionCatalog | Request::GetPhase
+            | Request::QueryAllowedRequests | Request::AttachHouseRule(_) | Request::RemoveHouseRule(_) | Request::QueryHouseRules
+            | Request::SetPlayerAbsent(_) | Request::SetAbsencePolicy(_) | Request::RegisterMatrixTarget(_) | Request::JackIn(_)
+            | Request::JackOut(_) | Request::DeclareMatrixAction(_) | Request::QueryMatrix | Request::ChangePlane(_)
+            | Request::UpdateCharacter { .. } | Request::ApproveCharacterUpdate(_))
+    }
+
+    // Whether the request shows its sender is at the table.  A long-poll is only listening, and goes on doing so from a tab nobody is
+    // looking at, so it does not keep a player from lapsing.
+    pub fn counts_as_presence(&self) -> bool
+    {
+        !matches!(self, Request::InboxAfter(_))
+    }
+
+    // Whether the request can use up a character's action, and so might be the one that resolves the turn.
+    pub fn spends_action(&self) -> bool
+    {
+        matches!(self, Request::TakeAction(_) | Request::Engage(_) | Request::Disengage(_) | Request::GoFullDefense(_) 
+            | Request::DeclareAreaAttack(_) | Request::CastSpell(_))
+    }
+
+    // Whether the request only reads the game, or takes it away or copies it whole; these are all an archived game still answers to.
+    pub fn leaves_game_untouched(&self) -> bool
+    {
+        match self
+        {
+            Request::Idempotent(_, request) => request.leaves_game_untouched(),
+            _ => matches!(self, Request::Enumerate(_) | Request::Reconnect(_) | Request::FetchInbox | Request::InboxAfter(_) | Request::Delete | Request::CloneGame
+                | Request::GetFullCast | Request::GetNpcCast | Request::GetPcCast | Request::GetCharacter(_) | Request::QueryInitiativePhase
+                | Request::QueryEngagements | Request::QueryResistanceTests | Request::QuerySustainedSpells | Request::QuerySoakPool(_)
+                | Request::QueryCurrentState | Request::QueryMissingInitiatives | Request::WhoGoesThisTurn | Request::WhatHasYetToHappenThisTurn
+                | Request::WhatHappensNextTurn | Request::AllEventsThisPass | Request::CurrentInitiative | Request::NextInitiative
+                | Request::AllRemainingInitiatives | Request::QueryAllCombatants | Request::QueryRemainingActions(_)
+                | Request::AvailableActions(_) | Request::QueryMovementLedger | Request::QueryChat | Request::CombatReport(_)
+                | Request::RoundTimeline | Request::ExportJournal(_) | Request::JournalAfter(_) | Request::ListCheckpoints
+                | Request::QueryStagedEncounters | Request::QueryGmAnnotations | Request::ResyncCombat | Request::PreviewInitiativeOrder
+                | Request::QueryActionLabels | Request::QueryActionCatalog | Request::GetPhase | Request::QueryAllowedRequests
+                | Request::QueryHouseRules | Request::QueryDeliveryHealth | Request::QueryMatrix),
+        }
+    }
+}
+
+
 pub enum Outcome
 {
     NewPlayer(NewPlayer),
-    Summaries(Vec<(Uuid, String)>),
+    Reconnected(Reconnection),
+    Inbox(Vec<Arc<WhatChanged>>),
+    // Numbered as the inbox numbers them, with what to wait on when there is nothing yet.
+    InboxAfter { notifications: Vec<(usize, Arc<WhatChanged>)>, ready: watch::Receiver<usize> },
+    Summaries(Vec<(Uuid, GameSummary)>),
     JoinedGame(GameState),
     Created(Uuid),
-    CastList(Vec<Arc<Character>>),
+    CastList(Vec<CharacterSummary>),
     Found(Option<Arc<Character>>),
     Destroyed,
+    Archived,
+    AccountDeleted,
     Error(Error),
     CharacterAdded((GameId, Uuid)),
     CombatStarted,
     InitiativePhaseStarted,
     InitiativeRollAdded,
+    InitiativeRollsAdded(Vec<RollResult>),
+    // At least one roll was refused, so none were kept; the results say which.
+    InitiativeRollsRejected(Vec<RollResult>),
     InitiativeStatus(InitiativeState),
     CombatRoundStarted,
     ActionTaken,
-    TurnAdvanced,
+    // Who is up next, if the batch resolved everyone up this turn and the initiative moved on.
+    ActionsTaken { advanced: Option<TurnAdvanced> },
+    AutoAdvanceSet(bool),
+    VisibilitySet,
+    AutoCyclePassesSet(bool),
+    CarryOverSet(bool),
+    Rested,
+    // Who is up next, if marking the character passed over the last of those up this turn.
+    SittingOutSet { advanced: Option<TurnAdvanced> },
+    // Nobody is left to act this pass; the GM may send ContinueCombat to go on to whichever comes next.
+    PassEnded(AfterPass),
+    Moved(Gait),
+    MovementLedger(Vec<MovementRecord>),
+    AttackModifiersAre(AttackModifiers),
+    Engaged(Engagement),
+    Disengaged,
+    EngagementsAre(Vec<Engagement>),
+    OnFullDefense(FullDefenseCost),
+    AreaAttackResolved(AreaResolution),
+    ResistanceTestsAre(Vec<ResistancePrompt>),
+    ResistanceTestCleared(ResistancePrompt),
+    SpellCast(Vec<ResistancePrompt>),
+    SpellDropped(SustainedSpell),
+    SustainedSpellsAre(Vec<SustainedSpell>),
+    PassesOverridden,
+    SoakPoolIs(u8),
+    ArmorDegraded,
+    TurnAdvanced(TurnAdvanced),
+    PassAdvanced,
+    CombatRoundEnded(RoundSummary),
     CombatEnded,
     CurrentStateIs,
     MissingInitiativesFor,
@@ -78,24 +438,380 @@ pub enum Outcome
     InitiativeIs(Option<i8>),
     InitiativesAre(Option<Vec<i8>>),
     AllCombatantsAre,
+    RemainingActionsAre(ActionBudget),
+    AvailableActionsAre(Vec<AvailableAction>),
+    ChatSent(usize),
+    ChatLog(Vec<ChatLine>),
+    RollMacroDefined,
+    Rolled(RollRecord),
+    ActionRolled(RollRecord),
+    DamageApplied,
+    Healed(Recovery),
+    // The tests the event queued, one per target in the order the GM gave.
+    DamageEventApplied(Vec<ResistancePrompt>),
+    EdgeSpent(u8),
+    CombatReportIs(CombatReport),
+    RoundTimelineIs(Vec<RoundTimeline>),
+    JournalEntries(Vec<JournalEntry>),
+    Rewound,
+    CheckpointSaved,
+    CheckpointRestored,
+    Checkpoints(Vec<String>),
+    EncounterStaged,
+    StagedEncountersAre(Vec<StagedEncounter>),
+    GmAnnotationSet,
+    GmAnnotationsAre(Vec<(NoteSubject, GmAnnotation)>),
+    CombatSynced(CombatSync),
+    InitiativePreviewIs(InitiativePreview),
+    RemindersSent(usize),
+    InitiativeTimeoutSet,
+    CastLimitsSet,
+    GameNamed,
+    EditionSet(Edition),
+    ActionLabelsSet,
+    ActionLabelsAre(ActionLabels),
+    CatalogActionDefined,
+    CatalogActionRemoved,
+    ActionCatalogIs(Vec<CatalogAction>),
+    PhaseIs(PhaseState),
+    AllowedRequestsAre(Vec<&'static str>),
+    HouseRuleAttached,
+    HouseRuleRemoved,
+    HouseRulesAre(Vec<(String, HouseRuleEvent)>),
+    DeliveryHealthIs(Vec<(PlayerId, DeliveryRecord)>),
+    PlayerAbsenceSet,
+    AbsencePolicySet,
+    MatrixTargetRegistered(Uuid),
+    JackedIn,
+    JackedOut,
+    MatrixActionResolved(MatrixResolution),
+    MatrixIs(MatrixGrid),
+    PlaneChanged,
+    InitiativeAdjusted(Option<i8>),
+    EventScheduled(TimedEvent),
+    CharacterUpdated(Vec<&'static str>),
+    CharacterUpdateAwaitingApproval(Vec<&'static str>),
+    BatchApplied(Vec<Outcome>),
+    ClocksReviewed,
+}
+
+impl Outcome
+{
+    // A copy of the outcome to answer a replayed request with.  Registering and reconnecting hand over a player's notification channel,
+    // which there is only one of, so those have none.
+    pub fn replay(&self) -> Option<Outcome>
+    {
+        Some(match self
+        {
+            Outcome::NewPlayer(_) | Outcome::Reconnected(_) => return None,
+            Outcome::Inbox(value) => Outcome::Inbox(value.clone()),
+            Outcome::InboxAfter { notifications, ready } => Outcome::InboxAfter { notifications: notifications.clone(), ready: ready.clone() },
+            Outcome::Summaries(value) => Outcome::Summaries(value.clone()),
+            Outcome::JoinedGame(value) => Outcome::JoinedGame(value.clone()),
+            Outcome::Created(value) => Outcome::Created(value.clone()),
+            Outcome::CastList(value) => Outcome::CastList(value.clone()),
+            Outcome::Found(value) => Outcome::Found(value.clone()),
+            Outcome::Destroyed => Outcome::Destroyed,
+            Outcome::Archived => Outcome::Archived,
+            Outcome::AccountDeleted => Outcome::AccountDeleted,
+            Outcome::Error(value) => Outcome::Error(value.clone()),
+            Outcome::CharacterAdded(value) => Outcome::CharacterAdded(value.clone()),
+            Outcome::CombatStarted => Outcome::CombatStarted,
+            Outcome::InitiativePhaseStarted => Outcome::InitiativePhaseStarted,
+            Outcome::InitiativeRollAdded => Outcome::InitiativeRollAdded,
+            Outcome::InitiativeRollsAdded(value) => Outcome::InitiativeRollsAdded(value.clone()),
+            Outcome::InitiativeRollsRejected(value) => Outcome::InitiativeRollsRejected(value.clone()),
+            Outcome::InitiativeStatus(value) => Outcome::InitiativeStatus(value.clone()),
+            Outcome::CombatRoundStarted => Outcome::CombatRoundStarted,
+            Outcome::ActionTaken => Outcome::ActionTaken,
+            Outcome::ActionsTaken { advanced } => Outcome::ActionsTaken { advanced: advanced.clone() },
+            Outcome::AutoAdvanceSet(value) => Outcome::AutoAdvanceSet(value.clone()),
+            Outcome::VisibilitySet => Outcome::VisibilitySet,
+            Outcome::AutoCyclePassesSet(value) => Outcome::AutoCyclePassesSet(value.clone()),
+            Outcome::CarryOverSet(value) => Outcome::CarryOverSet(value.clone()),
+            Outcome::Rested => Outcome::Rested,
+            Outcome::SittingOutSet { advanced } => Outcome::SittingOutSet { advanced: advanced.clone() },
+            Outcome::PassEnded(value) => Outcome::PassEnded(value.clone()),
+            Outcome::Moved(value) => Outcome::Moved(value.clone()),
+            Outcome::MovementLedger(value) => Outcome::MovementLedger(value.clone()),
+            Outcome::AttackModifiersAre(value) => Outcome::AttackModifiersAre(value.clone()),
+            Outcome::Engaged(value) => Outcome::Engaged(value.clone()),
+            Outcome::Disengaged => Outcome::Disengaged,
+            Outcome::EngagementsAre(value) => Outcome::EngagementsAre(value.clone()),
+            Outcome::OnFullDefense(value) => Outcome::OnFullDefense(value.clone()),
+            Outcome::AreaAttackResolved(value) => Outcome::AreaAttackResolved(value.clone()),
+            Outcome::ResistanceTestsAre(value) => Outcome::ResistanceTestsAre(value.clone()),
+            Outcome::ResistanceTestCleared(value) => Outcome::ResistanceTestCleared(value.clone()),
+            Outcome::SpellCast(value) => Outcome::SpellCast(value.clone()),
+            Outcome::SpellDropped(value) => Outcome::SpellDropped(value.clone()),
+            Outcome::SustainedSpellsAre(value) => Outcome::SustainedSpellsAre(value.clone()),
+            Outcome::PassesOverridden => Outcome::PassesOverridden,
+            Outcome::SoakPoolIs(value) => Outcome::SoakPoolIs(value.clone()),
+            Outcome::ArmorDegraded => Outcome::ArmorDegraded,
+            Outcome::TurnAdvanced(value) => Outcome::TurnAdvanced(value.clone()),
+            Outcome::PassAdvanced => Outcome::PassAdvanced,
+            Outcome::CombatRoundEnded(value) => Outcome::CombatRoundEnded(value.clone()),
+            Outcome::CombatEnded => Outcome::CombatEnded,
+            Outcome::CurrentStateIs => Outcome::CurrentStateIs,
+            Outcome::MissingInitiativesFor => Outcome::MissingInitiativesFor,
+            Outcome::MatchingEventsAre(value) => Outcome::MatchingEventsAre(value.clone()),
+            Outcome::MatchingEventsById(value) => Outcome::MatchingEventsById(value.clone()),
+            Outcome::InitiativeIs(value) => Outcome::InitiativeIs(value.clone()),
+            Outcome::InitiativesAre(value) => Outcome::InitiativesAre(value.clone()),
+            Outcome::AllCombatantsAre => Outcome::AllCombatantsAre,
+            Outcome::RemainingActionsAre(value) => Outcome::RemainingActionsAre(value.clone()),
+            Outcome::AvailableActionsAre(value) => Outcome::AvailableActionsAre(value.clone()),
+            Outcome::ChatSent(value) => Outcome::ChatSent(value.clone()),
+            Outcome::ChatLog(value) => Outcome::ChatLog(value.clone()),
+            Outcome::RollMacroDefined => Outcome::RollMacroDefined,
+            Outcome::Rolled(value) => Outcome::Rolled(value.clone()),
+            Outcome::ActionRolled(value) => Outcome::ActionRolled(value.clone()),
+            Outcome::DamageApplied => Outcome::DamageApplied,
+            Outcome::Healed(value) => Outcome::Healed(value.clone()),
+            Outcome::DamageEventApplied(value) => Outcome::DamageEventApplied(value.clone()),
+            Outcome::EdgeSpent(value) => Outcome::EdgeSpent(value.clone()),
+            Outcome::CombatReportIs(value) => Outcome::CombatReportIs(value.clone()),
+            Outcome::RoundTimelineIs(value) => Outcome::RoundTimelineIs(value.clone()),
+            Outcome::JournalEntries(value) => Outcome::JournalEntries(value.clone()),
+            Outcome::Rewound => Outcome::Rewound,
+            Outcome::CheckpointSaved => Outcome::CheckpointSaved,
+            Outcome::CheckpointRestored => Outcome::CheckpointRestored,
+            Outcome::Checkpoints(value) => Outcome::Checkpoints(value.clone()),
+            Outcome::EncounterStaged => Outcome::EncounterStaged,
+            Outcome::StagedEncountersAre(value) => Outcome::StagedEncountersAre(value.clone()),
+            Outcome::GmAnnotationSet => Outcome::GmAnnotationSet,
+            Outcome::GmAnnotationsAre(value) => Outcome::GmAnnotationsAre(value.clone()),
+            Outcome::CombatSynced(value) => Outcome::CombatSynced(value.clone()),
+            Outcome::InitiativePreviewIs(value) => Outcome::InitiativePreviewIs(value.clone()),
+            Outcome::RemindersSent(value) => Outcome::RemindersSent(value.clone()),
+            Outcome::InitiativeTimeoutSet => Outcome::InitiativeTimeoutSet,
+            Outcome::CastLimitsSet => Outcome::CastLimitsSet,
+            Outcome::GameNamed => Outcome::GameNamed,
+            Outcome::EditionSet(value) => Outcome::EditionSet(value.clone()),
+            Outcome::ActionLabelsSet => Outcome::ActionLabelsSet,
+            Outcome::ActionLabelsAre(value) => Outcome::ActionLabelsAre(value.clone()),
+            Outcome::CatalogActionDefined => Outcome::CatalogActionDefined,
+            Outcome::CatalogActionRemoved => Outcome::CatalogActionRemoved,
+            Outcome::ActionCatalogIs(value) => Outcome::ActionCatalogIs(value.clone()),
+            Outcome::PhaseIs(value) => Outcome::PhaseIs(value.clone()),
+            Outcome::AllowedRequestsAre(value) => Outcome::AllowedRequestsAre(value.clone()),
+            Outcome::HouseRuleAttached => Outcome::HouseRuleAttached,
+            Outcome::HouseRuleRemoved => Outcome::HouseRuleRemoved,
+            Outcome::HouseRulesAre(value) => Outcome::HouseRulesAre(value.clone()),
+            Outcome::DeliveryHealthIs(value) => Outcome::DeliveryHealthIs(value.clone()),
+            Outcome::PlayerAbsenceSet => Outcome::PlayerAbsenceSet,
+            Outcome::AbsencePolicySet => Outcome::AbsencePolicySet,
+            Outcome::MatrixTargetRegistered(value) => Outcome::MatrixTargetRegistered(value.clone()),
+            Outcome::JackedIn => Outcome::JackedIn,
+            Outcome::JackedOut => Outcome::JackedOut,
+            Outcome::MatrixActionResolved(value) => Outcome::MatrixActionResolved(value.clone()),
+            Outcome::MatrixIs(value) => Outcome::MatrixIs(value.clone()),
+            Outcome::PlaneChanged => Outcome::PlaneChanged,
+            Outcome::InitiativeAdjusted(value) => Outcome::InitiativeAdjusted(value.clone()),
+            Outcome::EventScheduled(value) => Outcome::EventScheduled(value.clone()),
+            Outcome::CharacterUpdated(value) => Outcome::CharacterUpdated(value.clone()),
+            Outcome::CharacterUpdateAwaitingApproval(value) => Outcome::CharacterUpdateAwaitingApproval(value.clone()),
+            Outcome::BatchApplied(outcomes) => Outcome::BatchApplied(outcomes.iter().map(Outcome::replay).collect::<Option<Vec<Outcome>>>()?),
+            Outcome::ClocksReviewed => Outcome::ClocksReviewed,
+        })
+    }
 }
 
+#[derive(Clone)]
 pub struct InitiativeState
 {
     pub waiting: bool,
     pub remaining: Vec<Uuid>
 }
 
+// The phase a game is in and the phases it could be moved into right now.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseState
+{
+    pub phase: Phase,
+    pub next: Vec<Phase>,
+}
+
 pub struct Roll
 {
     pub character_id: Uuid,
     pub roll: i8,
 }
 
+// How one roll in a submission of several fared.  A roll with no error was good, but it only stands if every other roll was good too.
+#[derive(Clone)]
+pub struct RollResult
+{
+    pub character_id: Uuid,
+    pub error: Option<Error>,
+}
+
+pub struct ChatMessage
+{
+    pub audience: ChatAudience,
+    pub text: String,
+}
+
+// An action may name one of the character's roll macros, which is rolled once the action has been spent.
 pub struct Action
 {
     pub character_id: Uuid,
-    pub action: ActionType
+    pub action: ActionType,
+    pub roll: Option<String>,
+    // An entry in the game's action catalog.  Its cost is spent in place of action, and its name is what the GM is told.
+    pub catalog: Option<String>,
+}
+
+pub struct MacroDefinition
+{
+    pub character_id: Uuid,
+    pub roll_macro: RollMacro,
+}
+
+pub enum RollSpec
+{
+    Macro(String),
+    Pool { label: String, dice: u8 },
+}
+
+pub struct DamageApplication
+{
+    pub source: Option<Uuid>,
+    pub target: Uuid,
+    pub boxes: u8,
+    pub kind: DamageType,
+    // The armor the damage lands on and the attack's armor penetration.  Physical damage that does not beat what is left of the armor is
+    // taken as stun; None applies the damage as given.
+    pub armor: Option<(ArmorTestType, i8)>,
+}
+
+// Marks the character as sitting out their turns for the reason given, or clears the mark with None.
+pub struct SittingOut
+{
+    pub character_id: Uuid,
+    pub reason: Option<SkipReason>,
+}
+
+pub struct HealingApplication
+{
+    pub character_id: Uuid,
+    pub healing: Healing,
+}
+
+pub struct EdgeSpend
+{
+    pub character_id: Uuid,
+    pub points: u8,
+}
+
+// A GM ruling: delta is added to the character's initiative score, and the reason is kept in the journal.
+pub struct InitiativeAdjustment
+{
+    pub character_id: Uuid,
+    pub delta: i8,
+    pub reason: String,
+}
+
+// Something for the initiative order with no character behind it, rounds from now: 0 is the round under way, or the next one if its
+// score has already gone by.
+pub struct EventSchedule
+{
+    pub label: String,
+    pub initiative: i8,
+    pub rounds: u32,
+}
+
+pub struct PlaneChange
+{
+    pub character_id: Uuid,
+    pub plane: Plane,
+}
+
+pub struct HouseRuleScript
+{
+    pub name: String,
+    pub event: HouseRuleEvent,
+    pub source: String,
+}
+
+pub struct DiceRoll
+{
+    pub character_id: Uuid,
+    pub roll: RollSpec,
+}
+
+pub struct Movement
+{
+    pub character_id: Uuid,
+    pub meters: u16,
+    pub sprint_hits: u8,
+}
+
+pub struct MeleeTarget
+{
+    pub character_id: Uuid,
+    pub target_id: Uuid,
+}
+
+pub struct SoakQuery
+{
+    pub character_id: Uuid,
+    pub armor_test: ArmorTestType,
+    pub armor_pen: i8,
+    pub bypass_armor: bool,
+}
+
+pub struct ArmorDamage
+{
+    pub character_id: Uuid,
+    pub armor_index: usize,
+    pub points: i8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameFilter
+{
+    All,
+    Joined,
+    Running, // games the requester is GM of
+    Open,
+    InCombat,
+}
+
+// Pages are zero-indexed; a page_size of None puts everything on page 0.
+pub struct GameQuery
+{
+    pub filter: GameFilter,
+    pub page: usize,
+    pub page_size: Option<usize>,
+}
+
+impl Default for GameQuery
+{
+    fn default() -> Self 
+    {
+        GameQuery { filter: GameFilter::All, page: 0, page_size: None }
+    }
+}
+
+// A passes value of None removes the override and lets the character sheet decide again.
+pub struct PassOverride
+{
+    pub character_id: Uuid,
+    pub passes: Option<usize>,
+}
+
+// While a player is marked absent the GM may roll initiative and take actions for their characters.
+pub struct PlayerAbsence
+{
+    pub player_id: PlayerId,
+    pub absent: bool,
+}
+
+pub struct SpellDrop
+{
+    pub character_id: Uuid,
+    pub spell: String,
 }
 
 pub struct NewPlayer
@@ -104,11 +820,244 @@ pub struct NewPlayer
     pub player_1_receiver: Receiver<Arc<WhatChanged>>
 }
 
+// How a reconnecting client will hear what happens at its games.  One that keeps the channel it is handed reads notifications off it, and
+// that channel closing means the player has gone.  One that drops it - the web front end, which reads the inbox by poll or socket - has
+// everything held in the inbox from the start, and that is not worth telling the GM about.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Delivery
+{
+    Channel,
+    Inbox,
+}
+
+// The games listed are the ones the player still runs or plays in; anything that ended (or was lost to a restart) is simply absent.
+pub struct Reconnection
+{
+    pub player_id: Uuid,
+    pub player_receiver: Receiver<Arc<WhatChanged>>,
+    pub active_games: Vec<GameId>,
+}
+
+#[derive(Clone)]
 pub struct GameState
 {
     pub for_player: Uuid,
 }
 
+// Dispatches the request, turning a panic in handling it into an Unexpected error for whoever sent it, so a bug one table trips over
+// does not take down the loop serving every other.  The game is put back from the copy the runner saved before dispatching, so it is not
+// left partway through whatever change panicked.
+pub fn dispatch_isolated(registry: &mut GameRegistry, hooks: &mut HookChain, authority: Authority, saved: Option<(GameId, &SavedGame)>) -> (Outcome, Option<Notification>)
+{
+    let request_name = authority.request().name();
+    match catch_unwind(AssertUnwindSafe(|| dispatch(registry, hooks, authority)))
+    {
+        Ok(handled) => handled,
+        Err(payload) =>
+        {
+            let cause = payload.downcast_ref::<&str>().map(|cause| String::from(*cause))
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("no message"));
+            error!("Handling {} panicked: {}", request_name, cause);
+            if let Some((game_id, saved)) = saved
+            {
+                registry.restore_game(&game_id, saved.clone());
+            }
+            let message = format!("The server hit a bug handling {} and abandoned it; the game is as it was before the request.", request_name);
+            (Outcome::Error(Error { message, kind: ErrorKind::Unexpected, context: ErrorContext::default() }), None)
+        },
+    }
+}
+
+pub fn dispatch(registry: &mut GameRegistry, hooks: &mut HookChain, authority: Authority) -> (Outcome, Option<Notification>)
+{
+    match authority.request()
+    {
+        Request::Batch(_) => {
+            debug!("Request is a batch.");
+            dispatch_batch(registry, hooks, authority)
+        }
+        Request::Idempotent(..) => {
+            debug!("Request is keyed for replay.");
+            dispatch_idempotent(registry, hooks, authority)
+        }
+        _ => hooks.run(registry, &authority, dispatch_and_settle)
+    }
+}
+
+// Fills in whatever the handler left out of an error's context from the message it answers and the state the game was left in.  A
+// batch keeps the context of the request inside it that failed.  Only the game's GM and players are told what state it is in; anyone
+// else could otherwise watch a table they have no seat at by sending it requests that fail.
+pub fn with_error_context(registry: &mut GameRegistry, outcome: Outcome, player_id: Option<PlayerId>, game_id: Option<GameId>, request: &'static str, character_id: Option<CharacterId>) -> Outcome
+{
+    let Outcome::Error(mut err) = outcome
+    else { return outcome };
+
+    let context = &mut err.context;
+    context.game_id = context.game_id.or(game_id);
+    context.request = context.request.or(Some(request));
+    context.character_id = context.character_id.or(character_id);
+
+    let seated = match (player_id, context.game_id)
+    {
+        (Some(player_id), Some(game_id)) => registry.is_gm(&player_id, &game_id) || registry.game_has_player(&game_id, &player_id),
+        _ => false,
+    };
+    if !seated
+    {
+        context.actual_state = None;
+    }
+    else if context.actual_state.is_none()
+    {
+        context.actual_state = context.game_id.and_then(|game_id| registry.get_mut_game(&game_id)).map(|game| game.current_state());
+    }
+
+    Outcome::Error(err)
+}
+
+// Runs every request in the batch against one game, in order, or none of them: the first failure puts the game back the way it was and
+// nobody hears about the requests that did succeed.  Notifications from a successful batch go out together, in request order.
+fn dispatch_batch(registry: &mut GameRegistry, hooks: &mut HookChain, authority: Authority) -> (Outcome, Option<Notification>)
+{
+    let (role, request) = authority.into_parts();
+    let Request::Batch(requests) = request
+    else { return (Outcome::Error(Error { message: String::from("Expected a batch of requests."), kind: ErrorKind::Unexpected, context: ErrorContext::default() }), None) };
+
+    let game_id = match role {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) => game_id,
+        _ => return (Outcome::Error(Error { message: String::from("Only the game's GM and players may send a batch of requests to it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
+    };
+
+    if let Some((index, unbatchable)) = requests.iter().enumerate().find(|(_, request)| !request.batchable())
+    {
+        return (Outcome::Error(Error { message: String::from(format!("Request {} ({}) cannot be part of a batch.", index, unbatchable.name())), kind: ErrorKind::UnbatchableRequest, context: ErrorContext::default() }), None);
+    }
+
+    let Some(saved) = registry.save_game(&game_id)
+    else { return (Outcome::Error(Error { message: String::from(format!("No matching game for id {}", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None) };
+
+    let mut outcomes = Vec::with_capacity(requests.len());
+    let mut notification: Option<Notification> = None;
+
+    for (index, request) in requests.into_iter().enumerate()
+    {
+        let name = request.name();
+        let (outcome, notify_opt) = hooks.run(registry, &Authority::new(role, request), dispatch_and_settle);
+
+        if let Outcome::Error(err) = outcome
+        {
+            registry.restore_game(&game_id, saved);
+            let message = String::from(format!("Request {} ({}) failed, so nothing in the batch was applied: {}", index, name, err.message));
+            return (Outcome::Error(Error { message, ..err }), None);
+        }
+
+        outcomes.push(outcome);
+        if let Some(next) = notify_opt
+        {
+            match notification.as_mut()
+            {
+                None => notification = Some(next),
+                Some(combined) => {
+                    combined.directed.extend(next.send_to.into_iter().map(|sender| (next.change_type.clone(), sender)));
+                    combined.directed.extend(next.directed);
+                }
+            }
+        }
+    }
+
+    (Outcome::BatchApplied(outcomes), notification)
+}
+
+// Answers a key seen before from the replay cache, and otherwise dispatches the request and remembers its outcome under the key.  Only
+// a registered player can key a request, since the keys are kept per player.
+fn dispatch_idempotent(registry: &mut GameRegistry, hooks: &mut HookChain, authority: Authority) -> (Outcome, Option<Notification>)
+{
+    let (role, request) = authority.into_parts();
+    let Request::Idempotent(key, request) = request
+    else { return (Outcome::Error(Error { message: String::from("Expected a keyed request."), kind: ErrorKind::Unexpected, context: ErrorContext::default() }), None) };
+
+    let (player_id, game_id) = match role
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) | Role::RoleObserver(player_id, game_id) => (player_id, Some(game_id)),
+        Role::RoleRegistered(player_id) => (player_id, None),
+        Role::RoleUnregistered => return (Outcome::Error(Error { message: String::from("Only a registered player may key a request."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None),
+    };
+    if matches!(*request, Request::Idempotent(..) | Request::NewPlayer | Request::Reconnect(_))
+    {
+        return (Outcome::Error(Error { message: String::from(format!("{} requests cannot be keyed.", request.name())), kind: ErrorKind::Unexpected, context: ErrorContext::default() }), None);
+    }
+
+    let name = request.name();
+    match registry.replays().lookup(&player_id, game_id, &key, name)
+    {
+        Replayed::Outcome(outcome) =>
+        {
+            debug!("Replaying the outcome of {} for key {}.", name, key.key);
+            return (outcome, None);
+        },
+        Replayed::KeyReused(first) =>
+        {
+            let message = String::from(format!("The key {} was already used for a different {} request.", key.key, first));
+            return (Outcome::Error(Error { message, kind: ErrorKind::IdempotencyKeyReused, context: ErrorContext::default() }), None);
+        },
+        Replayed::Fresh => {},
+    }
+
+    let (outcome, notification) = dispatch(registry, hooks, Authority::new(role, *request));
+    registry.replays_mut().record(player_id, game_id, key, name, &outcome);
+    (outcome, notification)
+}
+
+// Runs the request, then, if it spent an action in a game with auto-advance on and nobody up is left to resolve, moves the turn on and
+// tells everyone in the same notification.
+fn dispatch_and_settle(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    if let Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) = authority.resource_role()
+    {
+        if registry.is_archived(game_id) && !authority.request().leaves_game_untouched()
+        {
+            let message = String::from(format!("Game {} is archived; it can be read, copied or deleted, but not played.", game_id));
+            return (Outcome::Error(Error { message, kind: ErrorKind::GameArchived, context: ErrorContext::default() }), None);
+        }
+    }
+
+    let (outcome, notification) = dispatch_message2(registry, authority);
+    if !authority.request().spends_action() || matches!(outcome, Outcome::Error(_))
+    {
+        return (outcome, notification);
+    }
+
+    let game_id = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) => *game_id,
+        _ => return (outcome, notification),
+    };
+
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return (outcome, notification) };
+    if !game.advance_if_resolved()
+    {
+        return (outcome, notification);
+    }
+
+    debug!("Everyone up has resolved; auto-advancing the turn in game {}.", game_id);
+    let Some(advanced) = turn_advanced_notification(registry, &game_id)
+    else { return (outcome, notification) };
+
+    let notification = match notification
+    {
+        None => advanced,
+        Some(mut notification) =>
+        {
+            notification.directed.extend(advanced.send_to.into_iter().map(|sender| (Arc::clone(&advanced.change_type), sender)));
+            notification.directed.extend(advanced.directed);
+            notification
+        }
+    };
+
+    (outcome, Some(notification))
+}
+
 pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
 {
     let request = authority.request();
@@ -119,9 +1068,21 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
             debug!("Request is to register as a player.");
             register_player(authority, registry)
         }
-        Request::Enumerate => {
+        Request::Reconnect(delivery) => {
+            debug!("Request is to reconnect a returning player.");
+            (reconnect(authority, registry, *delivery), None)
+        }
+        Request::FetchInbox => {
+            debug!("Request is to drain the player's notification inbox.");
+            (fetch_inbox(authority, registry), None)
+        }
+        Request::InboxAfter(after) => {
+            debug!("Request is for the player's notification inbox after {:?}.", after);
+            (inbox_after(authority, registry, *after), None)
+        }
+        Request::Enumerate(query) => {
             debug!("Request is for a list of running games.");
-            (enumerate(registry), None)
+            (enumerate(registry, query, authority), None)
         }
         Request::New => {
             debug!("Request is for new game.");
@@ -131,6 +1092,18 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
             debug!("Request is to remove game.");
             end_game(authority, registry)
         },
+        Request::Archive => {
+            debug!("Request is to archive a game.");
+            archive_game(authority, registry)
+        },
+        Request::CloneGame => {
+            debug!("Request is to start a new game with this game's table.");
+            clone_game(authority, registry)
+        },
+        Request::DeleteAccount => {
+            debug!("Request is to delete a player's account.");
+            delete_account(authority, registry)
+        },
         Request::JoinGame => {
             debug!("Request is to let a player join a game.");
             join_game(authority, registry)
@@ -139,6 +1112,10 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
             debug!("Request is to add a new character.");
             add_character(character, registry, authority)
         },
+        Request::QuickCreateCharacter(archetype, name) => {
+            debug!("Request is to add a new {} from the archetype templates.", archetype.label());
+            add_character(&archetype.build(name.clone()), registry, authority)
+        },
         Request::GetFullCast => {
             debug!("Request is to get the full cast list.");
             (get_full_cast(registry, authority), None)
@@ -157,12 +1134,106 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
         }
         Request::StartCombat(combatants) => {
             debug!("Request is to start the combat phase.");
-            start_combat(registry, combatants.to_owned(), authority)
+            let (outcome, _) = start_combat(registry, combatants.to_owned(), authority);
+            announce(registry, authority, outcome, WhatChanged::CombatDeclared(combatants.to_owned()))
 
         },
+        Request::StageEncounter(encounter) => {
+            debug!("Request is for the GM to stage an encounter.");
+            (stage_encounter(registry, encounter, authority), None)
+        }
+        Request::QueryStagedEncounters => {
+            debug!("Request is for the list of staged encounters.");
+            (list_staged_encounters(registry, authority), None)
+        }
+        Request::AnnotateForGm(subject, annotation) => {
+            debug!("Request is for the GM to annotate {:?}.", subject);
+            (annotate_for_gm(registry, subject, annotation.clone(), authority), None)
+        }
+        Request::QueryGmAnnotations => {
+            debug!("Request is for the GM's private annotations.");
+            (list_gm_annotations(registry, authority), None)
+        }
+        Request::ResyncCombat => {
+            debug!("Request is for a full sync of the combatants.");
+            (resync_combat(registry, authority), None)
+        }
+        Request::LaunchEncounter(name) => {
+            debug!("Request is to start combat from a staged encounter.");
+            match launch_encounter(registry, name, authority)
+            {
+                Ok(visible) => announce(registry, authority, Outcome::CombatStarted, WhatChanged::CombatDeclared(visible)),
+                Err(outcome) => (outcome, None),
+            }
+        }
+        Request::PreviewInitiativeOrder => {
+            debug!("Request is for a preview of the initiative order.");
+            (preview_initiative_order(registry, authority), None)
+        }
+        Request::RemindInitiatives => {
+            debug!("Request is to remind players of their outstanding initiative rolls.");
+            remind_initiatives(registry, authority)
+        }
+        Request::SetInitiativeTimeout(timeout) => {
+            debug!("Request is for the GM to set or clear how long the initiative phase may stay open.");
+            let outcome = set_initiative_timeout(registry, *timeout, authority);
+            announce(registry, authority, outcome, WhatChanged::InitiativeTimeoutChanged(*timeout))
+        }
+        Request::SetCastLimits(limits) => {
+            debug!("Request is for the GM to set how large the cast may grow and whether names must be unique.");
+            let outcome = set_cast_limits(registry, *limits, authority);
+            announce(registry, authority, outcome, WhatChanged::CastLimitsChanged(*limits))
+        }
+        Request::NameGame(name) => {
+            debug!("Request is for the GM to name the game.");
+            (name_game(registry, name, authority), None)
+        }
+        Request::SetEdition(edition) => {
+            debug!("Request is for the GM to change which edition's rules the game plays by.");
+            let outcome = set_edition(registry, *edition, authority);
+            announce(registry, authority, outcome, WhatChanged::EditionChanged(*edition))
+        }
+        Request::SetActionLabels(labels) => {
+            debug!("Request is for the GM to rename the action types, or go back to the edition's names.");
+            set_action_labels(registry, labels.clone(), authority)
+        }
+        Request::QueryActionLabels => {
+            debug!("Request is for what the game calls each action type.");
+            (query_action_labels(registry, authority), None)
+        }
+        Request::DefineCatalogAction(action) => {
+            debug!("Request is for the GM to add an action to the game's catalog.");
+            change_action_catalog(registry, authority, |game| game.define_catalog_action(action.clone()).map(|_| Outcome::CatalogActionDefined))
+        }
+        Request::RemoveCatalogAction(name) => {
+            debug!("Request is for the GM to take an action out of the game's catalog.");
+            change_action_catalog(registry, authority, |game| game.remove_catalog_action(name).map(|_| Outcome::CatalogActionRemoved))
+        }
+        Request::QueryActionCatalog => {
+            debug!("Request is for the game's catalog of actions.");
+            (query_action_catalog(registry, authority), None)
+        }
+        Request::GetPhase => {
+            debug!("Request is for the game's phase and where it can go from there.");
+            (get_phase(registry, authority), None)
+        }
+        Request::QueryAllowedRequests => {
+            debug!("Request is for the requests the player could make of the game right now.");
+            (query_allowed_requests(registry, authority), None)
+        }
         Request::AddInitiativeRoll(roll) => {
             debug!("Request is to add an initiative roll.");
-            add_init_roll(roll, authority, registry)
+            let (outcome, _) = add_init_roll(roll, authority, registry);
+            announce(registry, authority, outcome, WhatChanged::InitiativeAdded(roll.character_id))
+        },
+        Request::AddInitiativeRolls(rolls) => {
+            debug!("Request is to add {} initiative rolls at once.", rolls.len());
+            add_init_rolls(rolls, authority, registry)
+        },
+        Request::OverrideInitiativeRoll(roll) => {
+            debug!("Request is for the GM to set an initiative roll past the usual bounds.");
+            let outcome = override_init_roll(roll, authority, registry);
+            announce(registry, authority, outcome, WhatChanged::InitiativeAdded(roll.character_id))
         },
         Request::BeginInitiativePhase => {
             debug!("Request is to begin the initiative phase.");
@@ -177,13 +1248,288 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
             debug!("Request is for some character to perform some action.");
             take_action( registry, action, authority)
         }
-        Request::AdvanceTurn => {
-            debug!("Request is to advance to the next event in the pass.");
-            try_advance_turn( registry, authority)
+        Request::SetAutoAdvance(on) =>
+        {
+            debug!("Request is to turn auto-advance {}.", if *on { "on" } else { "off" });
+            (set_auto_advance(registry, *on, authority), None)
         }
-        Request::WhoGoesThisTurn => {
-            debug!("Request is to see who is going this turn.");
-            (list_current_turn_events(registry, authority), None)
+        Request::SetVisibility(options) =>
+        {
+            debug!("Request is to change what the players may see of the GM's side of the fight.");
+            (set_visibility(registry, *options, authority), None)
+        }
+        Request::SetAutoCyclePasses(on) =>
+        {
+            debug!("Request is to turn automatic pass cycling {}.", if *on { "on" } else { "off" });
+            (set_auto_cycle_passes(registry, *on, authority), None)
+        }
+        Request::SetCarryOver(on) =>
+        {
+            debug!("Request is to turn carrying damage and resources between fights {}.", if *on { "on" } else { "off" });
+            (set_carry_over(registry, *on, authority), None)
+        }
+        Request::Rest(characters) =>
+        {
+            debug!("Request is for the GM to rest and repair {} characters.", characters.len());
+            let outcome = rest(registry, characters, authority);
+            announce(registry, authority, outcome, WhatChanged::CharactersRested(characters.clone()))
+        }
+        Request::SitOut(sitting_out) =>
+        {
+            debug!("Request is to mark a character as sitting out their turns: {:?}.", sitting_out.reason);
+            sit_out(registry, sitting_out, authority)
+        }
+        Request::EndCombat =>
+        {
+            debug!("Request is to end the combat.");
+            let outcome = end_combat(registry, authority);
+            announce(registry, authority, outcome, WhatChanged::CombatEnded)
+        }
+        Request::TakeActions(actions) =>
+        {
+            debug!("Request is for one player to take the turns of several tied characters together.");
+            take_actions(registry, actions, authority)
+        }
+        Request::DeclareMovement(movement) => {
+            debug!("Request is for some character to move.");
+            let outcome = declare_movement(registry, movement, authority);
+            announce(registry, authority, outcome, WhatChanged::CharacterMoved(movement.character_id))
+        }
+        Request::DeclareRangedAttack(attack) => {
+            debug!("Request is for the modifiers on a ranged attack.");
+            (ranged_attack(registry, attack, authority), None)
+        }
+        Request::Engage(melee) => {
+            debug!("Request is for a character to engage another in melee.");
+            let outcome = engage(registry, melee, authority);
+            announce(registry, authority, outcome, WhatChanged::Engaged { attacker: melee.character_id, defender: melee.target_id })
+        }
+        Request::Disengage(melee) => {
+            debug!("Request is for a character to break off melee.");
+            let outcome = disengage(registry, melee, authority);
+            announce(registry, authority, outcome, WhatChanged::Disengaged { character: melee.character_id, from: melee.target_id })
+        }
+        Request::QueryEngagements => {
+            debug!("Request is for the list of melee engagements.");
+            (list_engagements(registry, authority), None)
+        }
+        Request::GoFullDefense(character_id) => {
+            debug!("Request is for a character to go on full defense.");
+            let outcome = full_defense(registry, character_id, authority);
+            announce(registry, authority, outcome, WhatChanged::WentOnFullDefense(*character_id))
+        }
+        Request::DeclareAreaAttack(attack) => {
+            debug!("Request is to resolve an area attack.");
+            let outcome = area_attack(registry, attack, authority);
+            announce(registry, authority, outcome, WhatChanged::AreaAttackResolved(attack.attacker))
+        }
+        Request::QueryResistanceTests => {
+            debug!("Request is for the outstanding damage resistance tests.");
+            (resistance_tests(registry, authority), None)
+        }
+        Request::ResistanceTestMade(character_id) => {
+            debug!("Request is to clear a character's damage resistance test.");
+            let outcome = resistance_test_made(registry, character_id, authority);
+            announce(registry, authority, outcome, WhatChanged::ResistanceTestMade(*character_id))
+        }
+        Request::CastSpell(declaration) => {
+            debug!("Request is for a character to cast a spell.");
+            cast_spell(registry, declaration, authority)
+        }
+        Request::DropSpell(drop) => {
+            debug!("Request is for a character to drop a sustained spell.");
+            let outcome = drop_spell(registry, drop, authority);
+            announce(registry, authority, outcome, WhatChanged::SpellDropped(drop.character_id))
+        }
+        Request::QuerySustainedSpells => {
+            debug!("Request is for the list of sustained spells.");
+            (list_sustained_spells(registry, authority), None)
+        }
+        Request::OverridePasses(pass_override) => {
+            debug!("Request is for the GM to override a combatant's initiative passes.");
+            let outcome = override_passes(registry, pass_override, authority);
+            announce(registry, authority, outcome, WhatChanged::PassesOverridden(pass_override.character_id))
+        }
+        Request::QuerySoakPool(query) => {
+            debug!("Request is for a character's suggested soak pool.");
+            (soak_pool(registry, query, authority), None)
+        }
+        Request::DegradeArmor(damage) => {
+            debug!("Request is to degrade a character's armor.");
+            let outcome = degrade_armor(registry, damage, authority);
+            announce(registry, authority, outcome, WhatChanged::ArmorDegraded(damage.character_id))
+        }
+        Request::AdvanceTurn => {
+            debug!("Request is to advance to the next event in the pass.");
+            try_advance_turn( registry, authority)
+        }
+        Request::AdvancePass => {
+            debug!("Request is to begin the next initiative pass.");
+            try_advance_pass(registry, authority)
+        }
+        Request::ContinueCombat => {
+            debug!("Request is to carry on into the next pass or initiative phase, whichever comes next.");
+            continue_combat(registry, authority)
+        }
+        Request::Chat(message) => {
+            debug!("Request is to send a chat message.");
+            send_chat(registry, message, authority)
+        }
+        Request::QueryChat => {
+            debug!("Request is for the chat messages the player can see.");
+            (chat_log(registry, authority), None)
+        }
+        Request::DefineRollMacro(definition) => {
+            debug!("Request is to define a roll macro for a character.");
+            (define_roll_macro(registry, definition, authority), None)
+        }
+        Request::RollDice(dice_roll) => {
+            debug!("Request is to roll dice for a character.");
+            match roll_dice(registry, dice_roll, authority)
+            {
+                Outcome::Rolled(record) => announce(registry, authority, Outcome::Rolled(record.clone()), WhatChanged::DiceRolled(record)),
+                outcome => (outcome, None),
+            }
+        }
+        Request::ApplyDamage(damage) => {
+            debug!("Request is to apply damage to a character.");
+            match apply_damage(registry, damage, authority)
+            {
+                Outcome::Error(err) => (Outcome::Error(err), None),
+                outcome => (outcome, wounds_notification(registry, authority, &damage.target, |character, wounds| WhatChanged::CharacterDamaged { character, wounds })),
+            }
+        }
+        Request::Heal(application) => {
+            debug!("Request is to heal a character.");
+            match heal(registry, application, authority)
+            {
+                Outcome::Error(err) => (Outcome::Error(err), None),
+                outcome => (outcome, wounds_notification(registry, authority, &application.character_id, |character, wounds| WhatChanged::CharacterHealed { character, wounds })),
+            }
+        }
+        Request::ApplyDamageEvent(event) => {
+            debug!("Request is for the GM to apply one damage event to several targets.");
+            match apply_damage_event(registry, event, authority)
+            {
+                Outcome::DamageEventApplied(prompts) =>
+                {
+                    let notification = damage_event_notification(registry, authority, event.source, &prompts);
+                    (Outcome::DamageEventApplied(prompts), notification)
+                },
+                outcome => (outcome, None),
+            }
+        }
+        Request::SpendEdge(spend) => {
+            debug!("Request is to spend a character's Edge.");
+            let outcome = spend_edge(registry, spend, authority);
+            announce(registry, authority, outcome, WhatChanged::EdgeSpent(spend.character_id))
+        }
+        Request::CombatReport(scope) => {
+            debug!("Request is for the combat statistics report.");
+            (combat_report(registry, *scope, authority), None)
+        }
+        Request::RoundTimeline => {
+            debug!("Request is for the round-by-round timeline of the combat.");
+            (round_timeline(registry, authority), None)
+        }
+        Request::JournalAfter(sequence) => {
+            debug!("Request is for the journal entries after {:?}.", sequence);
+            (journal_after(registry, *sequence, authority), None)
+        }
+        Request::ExportJournal(filter) => {
+            debug!("Request is to export the game journal.");
+            (export_journal(registry, filter, authority), None)
+        }
+        Request::Rewind(target) => {
+            debug!("Request is for the GM to rewind the initiative order.");
+            rewind(registry, *target, authority)
+        }
+        Request::SaveCheckpoint(name) => {
+            debug!("Request is for the GM to save a checkpoint.");
+            (save_checkpoint(registry, name, authority), None)
+        }
+        Request::RestoreCheckpoint(name) => {
+            debug!("Request is for the GM to restore a checkpoint.");
+            let outcome = restore_checkpoint(registry, name, authority);
+            announce(registry, authority, outcome, WhatChanged::CheckpointRestored(name.clone()))
+        }
+        Request::ListCheckpoints => {
+            debug!("Request is for the list of saved checkpoints.");
+            (list_checkpoints(registry, authority), None)
+        }
+        Request::AttachHouseRule(script) => {
+            debug!("Request is for the GM to attach a house rule script.");
+            (attach_house_rule(registry, script, authority), None)
+        }
+        Request::RemoveHouseRule(name) => {
+            debug!("Request is for the GM to remove a house rule script.");
+            (remove_house_rule(registry, name, authority), None)
+        }
+        Request::QueryHouseRules => {
+            debug!("Request is for the list of house rules.");
+            (list_house_rules(registry, authority), None)
+        }
+        Request::QueryDeliveryHealth => {
+            debug!("Request is for how notifications to the table's players have been getting through.");
+            (delivery_health(registry, authority), None)
+        }
+        Request::SetPlayerAbsent(absence) => {
+            debug!("Request is for the GM to mark a player as away from, or back at, the table.");
+            let outcome = set_player_absent(registry, absence, authority);
+            announce(registry, authority, outcome, WhatChanged::PlayerAbsent { player: absence.player_id, absent: absence.absent })
+        }
+        Request::SetAbsencePolicy(policy) => {
+            debug!("Request is for the GM to set or clear what happens to players who keep the table waiting.");
+            set_absence_policy(registry, *policy, authority)
+        }
+        Request::RegisterMatrixTarget(target) => {
+            debug!("Request is for the GM to register a Matrix device or host.");
+            (register_matrix_target(registry, target, authority), None)
+        }
+        Request::JackIn(character_id) => {
+            debug!("Request is for a character to jack into the Matrix.");
+            let outcome = jack_in(registry, character_id, authority);
+            announce(registry, authority, outcome, WhatChanged::JackedIn(*character_id))
+        }
+        Request::JackOut(character_id) => {
+            debug!("Request is for a character to jack out of the Matrix.");
+            let outcome = jack_out(registry, character_id, authority);
+            announce(registry, authority, outcome, WhatChanged::JackedOut(*character_id))
+        }
+        Request::DeclareMatrixAction(declaration) => {
+            debug!("Request is for a decker to take a Matrix action.");
+            let outcome = matrix_action(registry, declaration, authority);
+            announce(registry, authority, outcome, WhatChanged::MatrixActionTaken { decker: declaration.decker, target: declaration.target })
+        }
+        Request::QueryMatrix => {
+            debug!("Request is for the state of the Matrix.");
+            (matrix_state(registry, authority), None)
+        }
+        Request::UpdateCharacter { id, patch } => {
+            debug!("Request is to edit a character sheet.");
+            update_character(registry, id, patch, authority)
+        }
+        Request::ApproveCharacterUpdate(character_id) => {
+            debug!("Request is for the GM to approve a character edit.");
+            approve_character_update(registry, character_id, authority)
+        }
+        Request::AdjustInitiative(adjustment) => {
+            debug!("Request is for the GM to adjust a character's initiative.");
+            let outcome = adjust_initiative(registry, adjustment, authority);
+            announce(registry, authority, outcome, WhatChanged::InitiativeAdjusted { character: adjustment.character_id, delta: adjustment.delta })
+        }
+        Request::ScheduleEvent(schedule) => {
+            debug!("Request is for the GM to schedule a timed event.");
+            (schedule_event(registry, schedule, authority), None)
+        }
+        Request::ChangePlane(change) => {
+            debug!("Request is for a character to change planes.");
+            let outcome = change_plane(registry, change, authority);
+            announce(registry, authority, outcome, WhatChanged::PlaneChanged { character: change.character_id, plane: change.plane })
+        }
+        Request::WhoGoesThisTurn => {
+            debug!("Request is to see who is going this turn.");
+            (list_current_turn_events(registry, authority), None)
         }
         Request::WhatHasYetToHappenThisTurn => {
             debug!("Request is to see who has yet to go.");
@@ -209,8 +1555,45 @@ pub fn dispatch_message2(registry: &mut GameRegistry, authority: &Authority) ->
             debug!("Request is to get any initiatives that have not been fully resolved.");
             (remaining_initiatives_are(registry, authority), None)
         }
-        _ => (Outcome::Error(Error { message: String::from("Not Yet Implemented"), kind: ErrorKind::InvalidStateAction }), None)
+        Request::QueryRemainingActions(character_id) => {
+            debug!("Request is to see what actions a character has left this pass.");
+            (remaining_actions(character_id, registry, authority), None)
+        }
+        Request::AvailableActions(character_id) => {
+            debug!("Request is to list what a character could legally do right now.");
+            (available_actions(character_id, registry, authority), None)
+        }
+        Request::QueryMovementLedger => {
+            debug!("Request is for the movement ledger of the current combat turn.");
+            (movement_ledger(registry, authority), None)
+        }
+        _ => (Outcome::Error(Error { message: String::from("Not Yet Implemented"), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() }), None)
+    }
+}
+
+// Broadcasts the change to everyone in the requester's game, provided the request actually changed something.
+fn announce(registry: &GameRegistry, authority: &Authority, outcome: Outcome, change: WhatChanged) -> (Outcome, Option<Notification>)
+{
+    if let Outcome::Error(_) = outcome
+    {
+        return (outcome, None);
     }
+
+    let game_id = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => game_id,
+        _ => return (outcome, None),
+    };
+
+    (outcome, Some(table_notification(registry, game_id, change)))
+}
+
+fn table_notification(registry: &GameRegistry, game_id: &GameId, change: WhatChanged) -> Notification
+{
+    let senders = registry.players_by_game(game_id)
+        .map_or(Vec::new(), |players| players.iter().filter_map(|player_id| registry.get_player_sender(player_id)).collect());
+
+    Notification { game_id: Some(*game_id), change_type: Arc::from(change), send_to: senders, directed: Vec::new() }
 }
 
 fn register_player(authority: &Authority, player_directory: &mut GameRegistry) -> (Outcome, Option<Notification>)
@@ -225,7 +1608,7 @@ fn register_player(authority: &Authority, player_directory: &mut GameRegistry) -
                 player_id = Uuid::new_v4();
             }
         
-            let (player_sender, player_receiver) = channel(32);
+            let (player_sender, player_receiver) = channel(player_directory.player_channel_capacity());
             let player_info = NewPlayer{ player_id, player_1_receiver: player_receiver };   
         
             match player_directory.register_player(player_id, player_sender)
@@ -235,24 +1618,124 @@ fn register_player(authority: &Authority, player_directory: &mut GameRegistry) -
             }
         },
         _ => {
-            (Outcome::Error(Error { message: String::from("Player is already registered."), kind: ErrorKind::InvalidStateAction }), None)
+            (Outcome::Error(Error { message: String::from("Player is already registered."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() }), None)
         }
     }
     // return Outcome::NewPlayer(player_info);
 }
 
-fn enumerate(running_games: &mut GameRegistry ) -> Outcome
+// A returning client already has a player id.  If the runner still knows the player their notifications are rerouted to the new channel;
+// if it doesn't (the runner has restarted since) the player is registered again under the same id.
+fn reconnect(authority: &Authority, player_directory: &mut GameRegistry, delivery: Delivery) -> Outcome
 {
+    let player_id = match authority.resource_role()
+    {
+        Role::RoleGM(player_id, _) | Role::RolePlayer(player_id, _) | Role::RoleObserver(player_id, _) | Role::RoleRegistered(player_id) => *player_id,
+        Role::RoleUnregistered => {
+            return Outcome::Error(Error { message: String::from("A player id is required to reconnect."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() });
+        }
+    };
 
-    let games = running_games.enumerate_games();
+    let (player_sender, player_receiver) = channel(player_directory.player_channel_capacity());
 
-    let mut enumeration = Vec::<(Uuid, String)>::with_capacity(games.len());
-    
-    for id in games
+    let registered = if player_directory.is_registered(&player_id)
+    {
+        player_directory.replace_player_sender(&player_id, player_sender)
+    }
+    else
+    {
+        debug!("Player {} is unknown to the runner; registering them again.", player_id);
+        player_directory.register_player(player_id, player_sender)
+    };
+
+    if registered.and_then(|_| player_directory.set_inbox_only(&player_id, delivery == Delivery::Inbox)).is_err()
+    {
+        return Outcome::Error(Error { message: String::from("The player could not be reconnected."), kind: ErrorKind::Unexpected, context: ErrorContext::default() });
+    }
+
+    let mut active_games: Vec<GameId> = player_directory.enumerate_games().into_iter()
+        .filter(|game_id| player_directory.gm_id(game_id) == Some(&player_id) || player_directory.player_in_game(player_id, *game_id))
+        .collect();
+    active_games.sort();
+
+    Outcome::Reconnected(Reconnection { player_id, player_receiver, active_games })
+}
+
+fn fetch_inbox(authority: &Authority, player_directory: &mut GameRegistry) -> Outcome
+{
+    match authority.resource_role()
+    {
+        Role::RoleGM(player_id, _) | Role::RolePlayer(player_id, _) | Role::RoleObserver(player_id, _) | Role::RoleRegistered(player_id) => 
+        {
+            match player_directory.drain_inbox(player_id)
+            {
+                Some(notifications) => Outcome::Inbox(notifications),
+                None => Outcome::Error(Error { message: String::from("The player id is not registered."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }),
+            }
+        }
+        Role::RoleUnregistered => 
+        {
+            Outcome::Error(Error { message: String::from("Only registered players have an inbox."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() })
+        }
+    }
+}
+
+// Nothing is drained, so a poll whose answer went astray can ask again from the same number.  The poll is made through a game, and only
+// someone at that table may make it; what they are shown is what was held about that table.
+fn inbox_after(authority: &Authority, player_directory: &GameRegistry, after: Option<usize>) -> Outcome
+{
+    match authority.resource_role()
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) | Role::RoleObserver(player_id, game_id) =>
+        {
+            match player_directory.inbox_after(player_id, game_id, after)
+            {
+                Some((notifications, ready)) => Outcome::InboxAfter { notifications, ready },
+                None => Outcome::Error(Error { message: String::from("The player id is not registered."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }),
+            }
+        }
+        Role::RoleRegistered(_) | Role::RoleUnregistered =>
+        {
+            Outcome::Error(Error { message: String::from("Only players at the table may wait on its events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() })
+        }
+    }
+}
+
+fn enumerate(running_games: &mut GameRegistry, query: &GameQuery, authority: &Authority) -> Outcome
+{
+    let player_id = match authority.resource_role()
+    {
+        Role::RoleGM(player_id, _) | Role::RolePlayer(player_id, _) | Role::RoleObserver(player_id, _) | Role::RoleRegistered(player_id) => Some(*player_id),
+        Role::RoleUnregistered => None,
+    };
+
+    if player_id.is_none() && (query.filter == GameFilter::Joined || query.filter == GameFilter::Running)
     {
-        enumeration.push((id, String::from("")));
+        return Outcome::Error(Error { message: String::from("Only registered players may list the games they are part of."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() });
     }
 
+    // The public listings are paged straight off the lobby index.  Joined and Running only ever look at the games the player sits at.
+    let page_size = query.page_size.unwrap_or(usize::MAX).max(1);
+    let enumeration = match (query.filter, player_id)
+    {
+        (GameFilter::All, _) => running_games.lobby().page(None, query.page, page_size),
+        (GameFilter::Open, _) => running_games.lobby().page(Some(false), query.page, page_size),
+        (GameFilter::InCombat, _) => running_games.lobby().page(Some(true), query.page, page_size),
+        (GameFilter::Joined | GameFilter::Running, Some(player_id)) =>
+        {
+            let seated = if query.filter == GameFilter::Joined { running_games.games_by_player(player_id) } else { None };
+            let mut game_ids = running_games.games_run_by(player_id).into_iter().chain(seated).flatten().copied().collect::<Vec<GameId>>();
+            game_ids.sort();
+            game_ids.dedup();
+            game_ids.into_iter()
+                .filter_map(|game_id| running_games.lobby().get(&game_id).map(|summary| (game_id, summary.clone())))
+                .skip(query.page.saturating_mul(page_size))
+                .take(page_size)
+                .collect()
+        },
+        (GameFilter::Joined | GameFilter::Running, None) => Vec::new(),
+    };
+
     return Outcome::Summaries(enumeration);
 }
 
@@ -263,7 +1746,7 @@ fn new_game(authority: &Authority, running_games: &mut GameRegistry) -> Outcome
     {
         Role::RoleUnregistered => {
             debug!("Requester was categorized as RoleUnregistered: cannot create new game.");
-            Outcome::Error(Error {message: String::from("User must be registered before a game may be created."), kind: ErrorKind::InvalidStateAction})
+            Outcome::Error(Error {message: String::from("User must be registered before a game may be created."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default()})
         },
         Role::RoleRegistered(player_id) | Role::RolePlayer(player_id, _) | Role::RoleGM(player_id, _) | Role::RoleObserver(player_id, _) => {
             debug!("Requester has been identified has registered.");
@@ -276,7 +1759,7 @@ fn new_game(authority: &Authority, running_games: &mut GameRegistry) -> Outcome
                 }
                 Err(()) => {
                     debug!("Outcome of new_game() was unsuccessful.");
-                    Outcome::Error(Error { message: String::from("Unexpected error: a new game could not be created."), kind: ErrorKind::Unexpected })
+                    Outcome::Error(Error { message: String::from("Unexpected error: a new game could not be created."), kind: ErrorKind::Unexpected, context: ErrorContext::default() })
                 }
             }
             
@@ -285,6 +1768,29 @@ fn new_game(authority: &Authority, running_games: &mut GameRegistry) -> Outcome
 
 }
 
+// A rematch: the same cast and players in a new game, set up as this one is but with no fight under way.  Everyone at the table is told
+// where the new game is; this one carries on untouched.
+fn clone_game(authority: &Authority, registry: &mut GameRegistry) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, source_id) = authority.resource_role()
+    else { return (Outcome::Error(Error { message: String::from("Only the game's GM may start a rematch."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None) };
+
+    let Some(rematch) = registry.get_game(source_id).map(|game| game.rematch())
+    else { return (Outcome::Error(Error { message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None) };
+
+    let mut game_id = Uuid::new_v4();
+    while registry.is_game(&game_id)
+    {
+        game_id = Uuid::new_v4();
+    }
+
+    match registry.clone_game(source_id, game_id, rematch)
+    {
+        Ok(()) => announce(registry, authority, Outcome::Created(game_id), WhatChanged::GameCloned(game_id)),
+        Err(()) => (Outcome::Error(Error { message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None),
+    }
+}
+
 fn end_game(authority: &Authority, directory: &mut GameRegistry) -> (Outcome, Option<Notification>)
 {
 
@@ -302,25 +1808,125 @@ fn end_game(authority: &Authority, directory: &mut GameRegistry) -> (Outcome, Op
                         .filter(|opt| opt.is_some())
                         .map(|vec| vec.unwrap())
                         .collect();
-                    let notification = Notification { change_type: Arc::from(WhatChanged::GameEnded), send_to: senders };
+                    let notification = Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::GameEnded), send_to: senders, directed: Vec::new() };
                     // let to_notify = directory.players_by_game(game);
                     (Outcome::Destroyed, Some(notification))
                 },
                 Err(_) => 
                 {
                     (Outcome::Error(
-                    Error{ message: String::from(format!("No game by ID {} exists.", game_id)), kind: ErrorKind::NoMatchingGame }), None)
+                    Error{ message: String::from(format!("No game by ID {} exists.", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None)
                 }
             }
         }
         _ => 
         {
-            (Outcome::Error(Error { message: String::from("The action requested (Delete Game) may only be initiated by the game's GM."), kind: ErrorKind::UnauthorizedAction }), None)
+            if let Some(game_id) = names_missing_game(directory, authority)
+            {
+                return (Outcome::Error(Error{ message: String::from(format!("No game by ID {} exists.", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None);
+            }
+            (Outcome::Error(Error { message: String::from("The action requested (Delete Game) may only be initiated by the game's GM."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
         }
     }
     
 }
 
+// Everyone at the table is told, as they are when a game is deleted; they can still read its reports and journal afterwards.
+fn archive_game(authority: &Authority, registry: &mut GameRegistry) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else 
+    {
+        if let Some(game_id) = names_missing_game(registry, authority)
+        {
+            return (Outcome::Error(Error{ message: String::from(format!("No game by ID {} exists.", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None);
+        }
+        return (Outcome::Error(Error { message: String::from("Only the game's GM may archive it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None);
+    };
+
+    if registry.archive_game(game_id).is_err()
+    {
+        return (Outcome::Error(Error{ message: String::from(format!("No game by ID {} exists.", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None);
+    }
+
+    (Outcome::Archived, Some(table_notification(registry, game_id, WhatChanged::GameArchived)))
+}
+
+// A game id the registry has no record of leaves the caller an observer of nothing.  Deleting or archiving it is answered with the game
+// being missing rather than with the caller not being its GM.
+fn names_missing_game(registry: &GameRegistry, authority: &Authority) -> Option<GameId>
+{
+    match authority.resource_role()
+    {
+        Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) if registry.get_game(game_id).is_none() => Some(*game_id),
+        _ => None,
+    }
+}
+
+// The player goes from every game they are in.  Games they run end outright, as though they had deleted each one; in the rest their
+// characters are anonymized or dropped and their chat purged, and the players left at the table are told they have gone.
+fn delete_account(authority: &Authority, registry: &mut GameRegistry) -> (Outcome, Option<Notification>)
+{
+    let player_id = match authority.resource_role()
+    {
+        Role::RoleGM(player_id, _) | Role::RolePlayer(player_id, _) | Role::RoleObserver(player_id, _) | Role::RoleRegistered(player_id) => *player_id,
+        Role::RoleUnregistered => 
+        {
+            return (Outcome::Error(Error { message: String::from("Only a registered player has an account to delete."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() }), None);
+        }
+    };
+
+    // A GM's own games are not in their list of games played, so they are picked out of the directory separately.
+    let mut game_ids: HashSet<GameId> = registry.games_by_player(player_id).cloned().unwrap_or_default();
+    game_ids.extend(registry.enumerate_games().into_iter().filter(|game_id| registry.gm_id(game_id) == Some(&player_id)));
+    let placeholder = registry.intern_name("Departed runner");
+    let mut ended = Vec::new();
+    let mut departed_from = Vec::new();
+
+    for game_id in game_ids
+    {
+        if registry.is_gm(&player_id, &game_id)
+        {
+            if let Ok(game_entry) = registry.delete_game(game_id)
+            {
+                ended.extend(game_entry.players.into_iter().filter(|other| *other != player_id));
+            }
+            continue;
+        }
+
+        let characters = registry.characters_by_player(&game_id, &player_id).cloned().unwrap_or_default();
+        if let Some(game) = registry.get_mut_game(&game_id)
+        {
+            game.forget_player(player_id, &characters, placeholder.clone());
+        }
+        departed_from.push(game_id);
+    }
+
+    if registry.unregister_player(player_id).is_err()
+    {
+        return (Outcome::Error(Error { message: String::from(format!("Player {} is not registered.", player_id)), kind: ErrorKind::UnknownId, context: ErrorContext::default() }), None);
+    }
+
+    let mut remaining: Vec<PlayerId> = departed_from.iter()
+        .filter_map(|game_id| registry.players_by_game(game_id))
+        .flat_map(|players| players.iter().copied())
+        .collect();
+    remaining.sort();
+    remaining.dedup();
+
+    let game_ended = Arc::from(WhatChanged::GameEnded);
+    // The player leaves several games at once, so this is not about any one of them.
+    let notification = Notification 
+    { 
+        game_id: None,
+        change_type: Arc::from(WhatChanged::PlayerDeparted(player_id)), 
+        send_to: remaining.iter().filter_map(|other| registry.get_player_sender(other)).collect(),
+        directed: ended.iter().filter_map(|other| registry.get_player_sender(other)).map(|sender| (Arc::clone(&game_ended), sender)).collect(),
+    };
+
+    (Outcome::AccountDeleted, Some(notification))
+}
+
 fn join_game(authority: &Authority, game_directory: &mut GameRegistry) -> (Outcome, Option<Notification>)
 {
     debug!("Starting join_game()");
@@ -342,14 +1948,15 @@ fn join_game(authority: &Authority, game_directory: &mut GameRegistry) -> (Outco
 
             debug!("List of players to notify created.");
 
+            let name = game_directory.player_name(player_id).unwrap_or_else(|| Name::from(""));
             if game_directory.join_game(*player_id, *game_id).is_ok()
             {
                 debug!("join_game() call successful.");
                 let notification = match opt_senders 
                 {
                     Some(senders) => {
-                        Some(Notification{ change_type: Arc::from(WhatChanged::NewPlayer(PlayerJoined { name: String::from(""), 
-                        player_id: *player_id })), send_to: senders})
+                        Some(Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::NewPlayer(PlayerJoined { name, 
+                        player_id: *player_id })), send_to: senders, directed: Vec::new() })
                     }, 
                     None => None
                 };
@@ -357,14 +1964,14 @@ fn join_game(authority: &Authority, game_directory: &mut GameRegistry) -> (Outco
             }
             else {
                 debug!("join_game() call failed.");
-                (Outcome::Error(Error { message: String::from(format!("No matching game for id {}", game_id)), kind: ErrorKind::NoMatchingGame }), None)
+                (Outcome::Error(Error { message: String::from(format!("No matching game for id {}", game_id)), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default() }), None)
             }
             
         },
         Role::RoleUnregistered | Role::RoleRegistered(_) =>
         {
             debug!("Authority categorized the player as unregistered.");
-            (Outcome::Error(Error { message: String::from("User must be registered or provide the game ID before they may join a game."), kind: ErrorKind::UnknownId }), None)
+            (Outcome::Error(Error { message: String::from("User must be registered or provide the game ID before they may join a game."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }), None)
         }
     }
 }
@@ -383,30 +1990,37 @@ fn add_character(character: &Character, registry: &mut GameRegistry, authority:
                     .map(|player_id| registry.get_player_sender(player_id)).filter(|opt| opt.is_some())
                     .map(|opt| opt.unwrap()).collect::<Vec<Sender<Arc<WhatChanged>>>>());
 
-            if let Some(char_id) = registry.add_character(player_id, game_id, character.clone())
+            match registry.add_character(player_id, game_id, character.clone())
             {
-                debug!("add_character successful, character id is {}", char_id);
-                let notification = match senders
-                {
-                    Some(sender_list) => {
-                        Some(
-                        Notification{ change_type: Arc::from(WhatChanged::NewCharacter(NewCharacter{ player_id: *player_id, character_id: char_id, metatype: character.metatype })), 
-                        send_to: sender_list })
-                    },
-                    None => {None}
-                };
+                Ok(char_id) => {
+                    debug!("add_character successful, character id is {}", char_id);
+                    let notification = match senders
+                    {
+                        Some(sender_list) => {
+                            Some(
+                            Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::NewCharacter(NewCharacter{ player_id: *player_id, character_id: char_id, metatype: character.metatype })), 
+                            send_to: sender_list, directed: Vec::new() })
+                        },
+                        None => {None}
+                    };
 
-                (Outcome::CharacterAdded((*game_id, char_id)), notification)
-            }
-            else 
-            {
-                debug!("add_character failed - there is no game by the provided id {}", game_id);
-                (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::UnauthorizedAction}), None)
+                    (Outcome::CharacterAdded((*game_id, char_id)), notification)
+                },
+                Err(CastRefusal::NoSuchGame) => {
+                    debug!("add_character failed - there is no game by the provided id {}", game_id);
+                    (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+                },
+                Err(CastRefusal::PlayerLimitReached(limit)) => 
+                    (Outcome::Error(Error {message: String::from(format!("Players may bring at most {} characters to this game.", limit)), kind: ErrorKind::CharacterLimitReached, context: ErrorContext::default()}), None),
+                Err(CastRefusal::CastFull(limit)) => 
+                    (Outcome::Error(Error {message: String::from(format!("The cast is full at {} characters.", limit)), kind: ErrorKind::CastFull, context: ErrorContext::default()}), None),
+                Err(CastRefusal::DuplicateName(name)) => 
+                    (Outcome::Error(Error {message: String::from(format!("A character named {} is already in the cast.", name)), kind: ErrorKind::DuplicateCharacterName, context: ErrorContext::default()}), None),
             }
         }, 
         _ => {
             debug!("The authority ResourceRole is not sufficient to add a player.");
-            return (Outcome::Error(Error { message: String::from("Observers may not create characters in a game."), kind: ErrorKind::UnauthorizedAction }), None)
+            return (Outcome::Error(Error { message: String::from("Observers may not create characters in a game."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
         }
     }
     
@@ -419,14 +2033,14 @@ fn get_full_cast(registry: &mut GameRegistry, authority: &Authority) -> Outcome
         Role::RoleGM(_, game_id) => {
             if let Some(game) = registry.get_game(game_id)
             {
-                Outcome::CastList(game.get_cast())
+                Outcome::CastList(game.get_cast().into_iter().map(|character| game.summarize(character, true)).collect())
             }
             else
             {
-                Outcome::Error(Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId})
+                Outcome::Error(Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()})
             }
         }
-        _ => Outcome::Error(Error { message: String::from("Only GMs may request the full character roster."), kind: ErrorKind::InvalidStateAction })
+        _ => Outcome::Error(Error { message: String::from("Only GMs may request the full character roster."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() })
     }
     
 }
@@ -438,14 +2052,14 @@ fn get_npcs(registry: &mut GameRegistry, authority: &Authority) -> Outcome
         Role::RoleGM(_, game_id) => {
             if let Some(game) = registry.get_game(game_id)
             {
-                Outcome::CastList(game.get_npcs())
+                Outcome::CastList(game.get_npcs().into_iter().map(|character| game.summarize(character, true)).collect())
             }
             else
             {
-                Outcome::Error( Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId})
+                Outcome::Error( Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()})
             }
         }
-        _ => Outcome::Error(Error {message: String::from("Only GMs may request the NPC character roster."), kind: ErrorKind::InvalidStateAction })
+        _ => Outcome::Error(Error {message: String::from("Only GMs may request the NPC character roster."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() })
     }
     
 }
@@ -454,17 +2068,35 @@ fn get_pcs(registry: &mut GameRegistry, authority: &Authority) -> Outcome
 {
     match authority.resource_role()
     {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) => {
+        Role::RoleGM(_, game_id) => {
+            if let Some(game) = registry.get_game(game_id)
+            {
+                Outcome::CastList(game.get_pcs().into_iter().map(|character| game.summarize(character, true)).collect())
+            }
+            else
+            {
+                Outcome::Error( Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()})
+            }
+        }
+        // Players get the full picture of their own characters and what the table can see of everyone else's.
+        Role::RolePlayer(player_id, game_id) => {
             if let Some(game) = registry.get_game(game_id)
             {
-                Outcome::CastList(game.get_pcs())
+                let owned = registry.characters_by_player(game_id, player_id);
+                Outcome::CastList(game.get_pcs().into_iter()
+                    .filter(|character| !game.is_hidden(&character.id))
+                    .map(|character| {
+                        let own = owned.map_or(false, |owned| owned.contains(&character.id));
+                        game.summarize(character, own)
+                    })
+                    .collect())
             }
             else
             {
-                Outcome::Error( Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId})
+                Outcome::Error( Error { message: String::from("The game identifier provided does not resolve to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()})
             }
         }
-        _ => Outcome::Error(Error {message: String::from("Only active participants in the game may get the player roster."), kind: ErrorKind::InvalidStateAction })
+        _ => Outcome::Error(Error {message: String::from("Only active participants in the game may get the player roster."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() })
     }
     
 }
@@ -486,12 +2118,12 @@ fn get_char(char_id: &CharacterId, registry: &GameRegistry, authority: &Authorit
                     }
                     else
                     {
-                        return Outcome::Error(Error { message: String::from("Player ID is not an owner of the character."), kind: ErrorKind::UnknownId });
+                        return Outcome::Error(Error { message: String::from("Player ID is not an owner of the character."), kind: ErrorKind::UnknownId, context: ErrorContext::default() });
                     }
                 },
                 None =>
                 {
-                    Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId })
+                    Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default() })
                 }
             }
         }
@@ -500,57 +2132,489 @@ fn get_char(char_id: &CharacterId, registry: &GameRegistry, authority: &Authorit
             match registry.get_game(&game_id)
             {
                 Some(game) => {Outcome::Found(game.get_cast_by_id(&char_id))}
-                None => {Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId })}
+                None => {Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default() })}
             }
         }
         _ =>
         {
-            Outcome::Error(Error{ message: String::from("Cannot get character for a game or player that does not exist."), kind: ErrorKind::NotGamePlayer })
+            Outcome::Error(Error{ message: String::from("Cannot get character for a game or player that does not exist."), kind: ErrorKind::NotGamePlayer, context: ErrorContext::default() })
         }
     }
 }
 
-fn start_combat(game_registry: &mut GameRegistry, combatants: Vec<CharacterId>, authority: &Authority) -> (Outcome, Option<Notification>)
+// NPC scores are in the preview, so it is for the GM only.
+fn preview_initiative_order(registry: &mut GameRegistry, authority: &Authority) -> Outcome
 {
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
 
-    let response: Outcome;
-
-    match authority.resource_role()
-    {
-        Role::RoleGM(_, game_id) => {
-            if let Some(game) = game_registry.get_mut_game(game_id)
-            {
-                if let Err(result) = game.add_combatants(combatants)
-                {
-                    match result.kind
-                    {
-                        crate::tracker::game::ErrorKind::UnknownCastId => {
-                            response = Outcome::Error
-                            (
-                                Error 
-                                { 
-                                    message: result.msg, 
-                                    kind: ErrorKind::NoSuchCharacter 
-                                }
-                            );
-                        },
-                        _ => {unreachable!()},
-                    }
-                }
-                else 
-                {
-                    
-                    response = Outcome::CombatStarted;
-                }
-            }
-            else
+            match game.preview_initiative_order()
             {
-                response = Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId});
+                Ok(preview) => Outcome::InitiativePreviewIs(preview),
+                Err(err) => action_error(err),
             }
-        },
-        _ => {response = Outcome::Error(Error { message: String::from("Only the Game GM may initiate combat."), kind: ErrorKind::UnauthorizedAction })}
-    }
-
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may preview the initiative order."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// Each player who still owes a roll gets one reminder listing their characters.  The GM's own NPCs are the GM's problem.
+fn remind_initiatives(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let game_id = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => *game_id,
+        _ => return (Outcome::Error(Error {message: String::from("Only the game's GM may send initiative reminders."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+    };
+
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    let outstanding = match game.preview_initiative_order()
+    {
+        Ok(preview) => preview.outstanding,
+        Err(err) => return (action_error(err), None),
+    };
+
+    let reminders = initiative_reminders(registry, &game_id, outstanding);
+    let reminded = reminders.directed.len();
+
+    (Outcome::RemindersSent(reminded), Some(reminders))
+}
+
+// Nothing is broadcast - every reminder is directed, to the player owing the rolls.
+fn initiative_reminders(registry: &GameRegistry, game_id: &GameId, outstanding: Vec<CharacterId>) -> Notification
+{
+    let mut owed: HashMap<PlayerId, Vec<CharacterId>> = HashMap::new();
+    for character_id in outstanding
+    {
+        if let Some(player_id) = registry.players_by_character(game_id, &character_id).filter(|player_id| !registry.is_gm(player_id, game_id))
+        {
+            owed.entry(*player_id).or_default().push(character_id);
+        }
+    }
+
+    let directed = owed.into_iter()
+        .filter_map(|(player_id, characters)| registry.get_player_sender(&player_id)
+            .map(|sender| (Arc::from(WhatChanged::InitiativeReminder { characters }), sender)))
+        .collect::<Vec<(Arc<WhatChanged>, Sender<Arc<WhatChanged>>)>>();
+
+    Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::InitiativeReminder { characters: Vec::new() }), send_to: Vec::new(), directed }
+}
+
+fn set_initiative_timeout(registry: &mut GameRegistry, timeout: Option<Duration>, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may set the initiative timeout."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(deadline) = registry.initiative_deadline_mut(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    deadline.set_timeout(timeout);
+    Outcome::InitiativeTimeoutSet
+}
+
+fn set_cast_limits(registry: &mut GameRegistry, limits: CastLimits, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may limit the cast."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    match registry.set_cast_limits(game_id, limits)
+    {
+        Ok(()) => Outcome::CastLimitsSet,
+        Err(()) => Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}),
+    }
+}
+
+fn name_game(registry: &mut GameRegistry, name: &str, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may name it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    match registry.set_game_name(game_id, name.trim())
+    {
+        Ok(()) => Outcome::GameNamed,
+        Err(()) => Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}),
+    }
+}
+
+fn set_edition(registry: &mut GameRegistry, edition: Edition, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may change which rules the game plays by."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    game.set_edition(edition);
+    Outcome::EditionSet(edition)
+}
+
+// The table is told the names now in force, which are the edition's own once the GM clears theirs.
+fn set_action_labels(registry: &mut GameRegistry, labels: Option<ActionLabels>, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return (Outcome::Error(Error {message: String::from("Only the game's GM may rename the action types."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    game.set_action_labels(labels);
+    let in_force = game.action_labels();
+
+    (Outcome::ActionLabelsSet, Some(table_notification(registry, game_id, WhatChanged::ActionLabelsChanged(in_force))))
+}
+
+fn query_action_labels(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::ActionLabelsAre(game.action_labels())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may ask what the action types are called."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// The table is sent the whole catalog after every change, as it is with the action labels.
+fn change_action_catalog<F>(registry: &mut GameRegistry, authority: &Authority, change: F) -> (Outcome, Option<Notification>)
+where F: FnOnce(&mut Game) -> Result<Outcome, GameError>
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return (Outcome::Error(Error {message: String::from("Only the game's GM may change the action catalog."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    match change(game)
+    {
+        Ok(outcome) =>
+        {
+            let catalog = game.action_catalog();
+            (outcome, Some(table_notification(registry, game_id, WhatChanged::ActionCatalogChanged(catalog))))
+        },
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn query_action_catalog(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::ActionCatalogIs(game.action_catalog())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may see the action catalog."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn get_phase(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::PhaseIs(PhaseState { phase: game.phase(), next: game.allowed_transitions() })
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may ask after a game's phase."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// Observers may ask, but are never allowed anything.
+fn query_allowed_requests(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let (player_id, game_id, gm) = match authority.resource_role() {
+        Role::RoleGM(player_id, game_id) => (player_id, game_id, true),
+        Role::RolePlayer(player_id, game_id) | Role::RoleObserver(player_id, game_id) => (player_id, game_id, false),
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may ask what they can do in a game."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    };
+
+    let Some(game) = registry.get_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    let owned = registry.characters_by_player(game_id, player_id).cloned().unwrap_or_default();
+    Outcome::AllowedRequestsAre(AllowedRequests::of(game).for_player(gm, &owned))
+}
+
+// Runs after every message to a game.  Whatever it did to the combatants goes out as a delta - or now and then a full sync - the GM's view
+// to the GM and the table's to everyone else, rather than each client fetching the whole fight again.
+pub fn sync_combatants(registry: &mut GameRegistry, game_id: Option<GameId>) -> Option<Notification>
+{
+    let game_id = game_id?;
+    let game = registry.get_game(&game_id)?;
+    let (gm_states, table_states) = (game.combatant_states(true), game.combatant_states(false));
+
+    let sync = registry.combat_sync_mut(&game_id)?;
+    let (gm_sync, table_sync) = (sync.gm.update(gm_states), sync.table.update(table_states));
+
+    let gm_change = gm_sync.map(|gm_sync| Arc::new(WhatChanged::CombatantsSynced(gm_sync)));
+    let gm_sender = registry.gm_sender(&game_id);
+
+    match table_sync
+    {
+        Some(table_sync) =>
+        {
+            let gm_id = registry.gm_id(&game_id);
+            let senders = registry.players_by_game(&game_id).map_or(Vec::new(), |players| players.iter()
+                .filter(|player_id| Some(*player_id) != gm_id)
+                .filter_map(|player_id| registry.get_player_sender(player_id)).collect());
+            let directed = gm_change.zip(gm_sender).into_iter().collect();
+            Some(Notification { game_id: Some(game_id), change_type: Arc::new(WhatChanged::CombatantsSynced(table_sync)), send_to: senders, directed })
+        },
+        None => gm_change.map(|change| Notification { game_id: Some(game_id), change_type: change, send_to: gm_sender.into_iter().collect(), directed: Vec::new() }),
+    }
+}
+
+// Runs after every message to a game, ahead of sync_combatants.  The GM hears of every trigger that fired; the rest of the table hears only
+// of reinforcements they can see arriving.
+pub fn announce_triggers(registry: &mut GameRegistry, game_id: Option<GameId>) -> Option<Notification>
+{
+    let game_id = game_id?;
+    let fired = registry.get_mut_game(&game_id)?.take_fired_triggers();
+    if fired.is_empty()
+    {
+        return None;
+    }
+
+    let game = registry.get_game(&game_id)?;
+    let arrived = fired.iter()
+        .flat_map(|trigger| match &trigger.action
+        {
+            TriggerAction::Reinforcements(characters) => characters.clone(),
+            TriggerAction::Prompt(_) => Vec::new(),
+        })
+        .filter(|character| !game.is_hidden(character))
+        .collect::<Vec<CharacterId>>();
+    let gm_sender = registry.gm_sender(&game_id);
+
+    if arrived.is_empty()
+    {
+        return gm_sender.map(|sender| Notification { game_id: Some(game_id), change_type: Arc::new(WhatChanged::TriggersFired(fired)), send_to: vec![sender], directed: Vec::new() });
+    }
+
+    let gm_id = registry.gm_id(&game_id);
+    let senders = registry.players_by_game(&game_id).map_or(Vec::new(), |players| players.iter()
+        .filter(|player_id| Some(*player_id) != gm_id)
+        .filter_map(|player_id| registry.get_player_sender(player_id)).collect());
+    let directed = gm_sender.map(|sender| (Arc::new(WhatChanged::TriggersFired(fired)), sender)).into_iter().collect();
+    Some(Notification { game_id: Some(game_id), change_type: Arc::new(WhatChanged::ReinforcementsArrived(arrived)), send_to: senders, directed })
+}
+
+// Runs ahead of every message, after review_absences.  Once the game's initiative phase has been open past its timeout, the server rolls
+// for every NPC still to roll - any character the GM owns, or that nobody does - and reminds the players who are still holding things up.
+pub fn review_initiative_deadline(registry: &mut GameRegistry, game_id: Option<GameId>) -> Vec<Notification>
+{
+    let Some(game_id) = game_id
+    else { return Vec::new() };
+    if !registry.initiative_deadline(&game_id).map_or(false, |deadline| deadline.passed(Instant::now()))
+    {
+        return Vec::new();
+    }
+    if let Some(deadline) = registry.initiative_deadline_mut(&game_id)
+    {
+        deadline.clear();
+    }
+
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return Vec::new() };
+    let Ok(preview) = game.preview_initiative_order()
+    else { return Vec::new() };
+
+    let (npcs, pcs): (Vec<CharacterId>, Vec<CharacterId>) = preview.outstanding.into_iter()
+        .partition(|char_id| registry.players_by_character(&game_id, char_id).map_or(true, |owner| registry.is_gm(owner, &game_id)));
+    debug!("Initiative in game {} has run past its timeout; rolling for {} NPCs and reminding {} PCs.", game_id, npcs.len(), pcs.len());
+
+    let mut rng = rand::thread_rng();
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return Vec::new() };
+    let rolled = npcs.into_iter().filter(|char_id| game.roll_initiative(&mut rng, *char_id).is_ok()).collect::<Vec<CharacterId>>();
+
+    let mut notifications = rolled.into_iter()
+        .map(|char_id| table_notification(registry, &game_id, WhatChanged::InitiativeAdded(char_id)))
+        .collect::<Vec<Notification>>();
+    if !pcs.is_empty()
+    {
+        notifications.push(initiative_reminders(registry, &game_id, pcs));
+    }
+
+    notifications
+}
+
+fn stage_encounter(registry: &mut GameRegistry, encounter: &StagedEncounter, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.stage_encounter(encounter.clone())
+            {
+                Ok(_) => Outcome::EncounterStaged,
+                Err(err) => action_error(err),
+            }
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may stage an encounter."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// Nothing is announced: the table is not even told that the GM has made a note.
+fn annotate_for_gm(registry: &mut GameRegistry, subject: &NoteSubject, annotation: Option<GmAnnotation>, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may keep private notes."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match subject
+    {
+        NoteSubject::Character(character_id) if game.get_cast_by_id(character_id).is_none() =>
+            return Outcome::Error(Error {message: String::from(format!("The id {} does not match any cast member.", character_id)), kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}),
+        NoteSubject::Encounter(name) if !game.staged_encounters().iter().any(|encounter| encounter.name == *name) =>
+            return Outcome::Error(Error {message: String::from(format!("No encounter named {} has been staged.", name)), kind: ErrorKind::NoSuchEncounter, context: ErrorContext::default()}),
+        _ => {},
+    }
+
+    let Some(notes) = registry.gm_notes_mut(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    notes.annotate(subject.clone(), annotation);
+    Outcome::GmAnnotationSet
+}
+
+fn list_gm_annotations(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may read their private notes."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(notes) = registry.gm_notes(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    Outcome::GmAnnotationsAre(notes.all())
+}
+
+// The GM gets their own view back; players and observers get the table's.
+fn resync_combat(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let (game_id, gm_view) = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) => (game_id, true),
+        Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => (game_id, false),
+        _ => return Outcome::Error(Error {message: String::from("Only the game's GM, players and observers may sync its combatants."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}),
+    };
+
+    let Some(sync) = registry.combat_sync(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    Outcome::CombatSynced(if gm_view { sync.gm.full() } else { sync.table.full() })
+}
+
+fn list_staged_encounters(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::StagedEncountersAre(game.staged_encounters())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may see the staged encounters."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// On success, returns the combatants the players are allowed to know about.
+fn launch_encounter(registry: &mut GameRegistry, name: &str, authority: &Authority) -> Result<Vec<CharacterId>, Outcome>
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Err(Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()})) };
+
+            match game.launch_encounter(name)
+            {
+                Ok(encounter) => Ok(encounter.combatants.into_iter().filter(|id| !encounter.hidden.contains(id)).collect()),
+                Err(err) => Err(action_error(err)),
+            }
+        }
+        _ =>
+        {
+            Err(Outcome::Error(Error {message: String::from("Only the Game GM may initiate combat."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}))
+        }
+    }
+}
+
+fn start_combat(game_registry: &mut GameRegistry, combatants: Vec<CharacterId>, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+
+    let response: Outcome;
+
+    match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) => {
+            if let Some(game) = game_registry.get_mut_game(game_id)
+            {
+                if let Err(result) = game.add_combatants(combatants)
+                {
+                    match result.kind
+                    {
+                        crate::tracker::game::ErrorKind::UnknownCastId => {
+                            response = Outcome::Error
+                            (
+                                Error 
+                                { 
+                                    message: result.msg, 
+                                    kind: ErrorKind::NoSuchCharacter, 
+                                    context: ErrorContext::default() 
+                                }
+                            );
+                        },
+                        _ => {unreachable!()},
+                    }
+                }
+                else 
+                {
+                    
+                    response = Outcome::CombatStarted;
+                }
+            }
+            else
+            {
+                response = Outcome::Error(Error { message: String::from("Provided ID does not map to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()});
+            }
+        },
+        _ => {response = Outcome::Error(Error { message: String::from("Only the Game GM may initiate combat."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() })}
+    }
+
     return (response, None);
 
 }
@@ -575,8 +2639,23 @@ fn try_initiative_phase(registry: &mut GameRegistry, authority: &Authority) -> (
                             .map(|player_sender_opt| player_sender_opt.unwrap())
                             .collect::<Vec<Sender<Arc<WhatChanged>>>>();
                         
+                        // Whoever the absence policy passed over last round starts this one in control of their characters again.
+                        let fallback = registry.absence_watch(game_id).and_then(|watch| watch.policy()).map(|policy| policy.fallback);
+                        let restored = registry.absence_watch_mut(game_id).map_or(Vec::new(), |watch| watch.new_round(Instant::now()));
+                        let directed = restore_control(registry, game_id, restored, fallback).into_iter()
+                            .flat_map(|notification| {
+                                let change = notification.change_type;
+                                notification.send_to.into_iter().map(move |sender| (Arc::clone(&change), sender))
+                            })
+                            .collect();
+
+                        if let Some(deadline) = registry.initiative_deadline_mut(game_id)
+                        {
+                            deadline.phase_opened(Instant::now());
+                        }
+
                         debug!("Non-error returned from game.start_initiative_phase()");
-                        (Outcome::InitiativePhaseStarted, Some(Notification { change_type: Arc::from(WhatChanged::StartingInitiativePhase), send_to: senders }))
+                        (Outcome::InitiativePhaseStarted, Some(Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::StartingInitiativePhase), send_to: senders, directed }))
                     },
                     Err(game_err) => {
                         let runner_err: Error;
@@ -584,15 +2663,15 @@ fn try_initiative_phase(registry: &mut GameRegistry, authority: &Authority) -> (
                         {
                             crate::tracker::game::ErrorKind::InvalidStateAction => 
                             {
-                                runner_err = Error {kind: ErrorKind::InvalidStateAction, message: game_err.msg}
+                                runner_err = Error {kind: ErrorKind::InvalidStateAction, context: ErrorContext::from_game_error(&game_err), message: game_err.msg}
                             },
                             crate::tracker::game::ErrorKind::UnknownCastId => 
                             {
-                                runner_err = Error {kind: ErrorKind::NoSuchCharacter, message: game_err.msg}
+                                runner_err = Error {kind: ErrorKind::NoSuchCharacter, message: game_err.msg, context: ErrorContext::default()}
                             }
                             crate::tracker::game::ErrorKind::UnresolvedCombatant => 
                             {
-                                runner_err = Error {kind: ErrorKind::UnresolvedCombatant, message: game_err.msg}
+                                runner_err = Error {kind: ErrorKind::UnresolvedCombatant, message: game_err.msg, context: ErrorContext::default()}
                             },
                             _ => {unreachable!()}
                         }
@@ -603,11 +2682,11 @@ fn try_initiative_phase(registry: &mut GameRegistry, authority: &Authority) -> (
             }
             else 
             {
-                (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}), None)
+                (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None)
             }
         },
         _ => {
-            (Outcome::Error(Error {message: String::from("Only the GM may begin initiative."), kind: ErrorKind::UnauthorizedAction}), None)
+            (Outcome::Error(Error {message: String::from("Only the GM may begin initiative."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
         }
     }
     
@@ -622,7 +2701,16 @@ fn add_init_roll(roll: &Roll, authority: &Authority, registry: &mut GameRegistry
         Role::RoleGM(player_id, game_id)=> 
         {
             debug!("Authority found for player {} on game {} is RoleGM - setting roll with no further checks.", player_id, game_id);
-            set_init_roll(registry, game_id, roll)
+            let proxy_for = registry.absent_owner(game_id, &roll.character_id);
+            let (outcome, notification) = set_init_roll(registry, game_id, roll);
+            if let (Outcome::InitiativeRollAdded, Some(absent_player)) = (&outcome, proxy_for)
+            {
+                if let Some(game) = registry.get_mut_game(game_id)
+                {
+                    game.record_proxy(roll.character_id, absent_player);
+                }
+            }
+            (outcome, notification)
         },
         Role::RolePlayer(player_id, game_id) => {
             debug!("Authority found for player {} on game {} is RolePlayer - checking ownership first.", player_id, game_id);
@@ -632,300 +2720,1986 @@ fn add_init_roll(roll: &Roll, authority: &Authority, registry: &mut GameRegistry
                     set_init_roll(registry, game_id, roll)
                 }
                 else {
-                    (Outcome::Error(Error { message: String::from("A player may only set the initiative of a character they own."), kind: ErrorKind::UnauthorizedAction }), None)    
+                    (Outcome::Error(Error { message: String::from("A player may only set the initiative of a character they own."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)    
                 }
             }
             else {
-                (Outcome::Error(Error { message: String::from("A player may only set the initiative of a character they own."), kind: ErrorKind::UnauthorizedAction }), None)
+                (Outcome::Error(Error { message: String::from("A player may only set the initiative of a character they own."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
             }
         }, 
-        _ => (Outcome::Error(Error { message: String::from("Only players and the GM may roll for initiative."), kind: ErrorKind::UnauthorizedAction}), None)
+        _ => (Outcome::Error(Error { message: String::from("Only players and the GM may roll for initiative."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+    }
+
+}
+
+// Every roll is tried, so a client hears about all the bad ones at once rather than only the first; if any was refused, the game is put
+// back as it was and nobody is told anything.  Each roll is held to the same ownership rules as one sent on its own.
+fn add_init_rolls(rolls: &[Roll], authority: &Authority, registry: &mut GameRegistry) -> (Outcome, Option<Notification>)
+{
+    let game_id = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) => game_id,
+        _ => return (Outcome::Error(Error { message: String::from("Only players and the GM may roll for initiative."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None),
+    };
+
+    let Some(saved) = registry.save_game(game_id)
+    else { return (Outcome::Error(Error { message: String::from("No game found by provided ID."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }), None) };
+
+    let mut results = Vec::with_capacity(rolls.len());
+    for (index, roll) in rolls.iter().enumerate()
+    {
+        let error = if rolls[..index].iter().any(|earlier| earlier.character_id == roll.character_id)
+        {
+            Some(Error { message: String::from("This character was already rolled for earlier in the same submission."), kind: ErrorKind::DuplicateRoll, context: ErrorContext::default() })
+        }
+        else
+        {
+            match add_init_roll(roll, authority, registry)
+            {
+                (Outcome::Error(err), _) => Some(err),
+                _ => None,
+            }
+        };
+        results.push(RollResult { character_id: roll.character_id, error });
+    }
+
+    if results.iter().any(|result| result.error.is_some())
+    {
+        registry.restore_game(game_id, saved);
+        return (Outcome::InitiativeRollsRejected(results), None);
+    }
+
+    let changes = rolls.iter().map(|roll| Arc::new(WhatChanged::InitiativeAdded(roll.character_id))).collect();
+    (Outcome::InitiativeRollsAdded(results), Some(table_notification(registry, game_id, WhatChanged::Composite(changes))))
+}
+
+fn override_init_roll(roll: &Roll, authority: &Authority, registry: &mut GameRegistry) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error { message: String::from("Only the GM may set an initiative roll the sheet could not produce."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }) };
+
+    let proxy_for = registry.absent_owner(game_id, &roll.character_id);
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error { message: String::from("No game found by provided ID."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }) };
+
+    match game.override_initiative_roll(roll.character_id, roll.roll)
+    {
+        Ok(()) => {
+            if let Some(absent_player) = proxy_for
+            {
+                game.record_proxy(roll.character_id, absent_player);
+            }
+            Outcome::InitiativeRollAdded
+        },
+        Err(err) => action_error(err),
+    }
+}
+
+fn set_init_roll(registry: &mut GameRegistry, game_id: &Uuid, roll: &Roll) -> (Outcome, Option<Notification>) {
+    debug!("Starting set_init_roll()");
+    if let Some(game) = registry.get_mut_game(game_id)
+    {
+        debug!("Game exists for id {}", game_id);
+        match game.accept_initiative_roll(roll.character_id, roll.roll)
+        {
+            Ok(_) => {
+                debug!("Initiative added.");
+                (Outcome::InitiativeRollAdded, None)
+            },
+            Err(GameError{kind: GameErrorKind::InvalidStateAction, ..}) => {
+                debug!("Initiative add failed: Game is not in initiative phase.");
+                (Outcome::Error(Error {message: String::from("The game is not in the initiatve state."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::expecting("Initiative Rolls")}), None)
+            }
+            Err(GameError{kind: GameErrorKind::UnknownCastId, ..}) => {
+                debug!("Initiative add failed: Character ID is not part of the combat group.");
+                (Outcome::Error(Error { message: String::from("The character ID provided is not registered as part of combat."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }), None)
+            }
+            Err(GameError{kind: GameErrorKind::InitiativeOutOfRange, msg, ..}) => {
+                debug!("Initiative add failed: {}", msg);
+                (Outcome::Error(Error { message: msg, kind: ErrorKind::InitiativeOutOfRange, context: ErrorContext::default() }), None)
+            }
+            _ => {
+                debug!("Unexpected error during initiative set.");
+                (Outcome::Error(Error { message: String::from("Unexpected error type returned from initiative add."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default()}), None)
+            }
+        }
+
+    
+    }
+    else
+    {
+        return (Outcome::Error(Error { message: String::from("No game found by provided ID."), kind: ErrorKind::UnknownId, context: ErrorContext::default() }), None)
+    }
+}
+
+
+fn try_begin_combat(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    debug!("Starting try_begin_combat");
+    match authority.resource_role()
+    {
+        Role::RoleGM(player_id, game_id) => {
+            debug!("Authority for {} in game {} is RoleGM", player_id, game_id);
+
+            let Some(game) = registry.get_mut_game(game_id) 
+            else {
+                debug!("Game not found for game id {}", game_id);
+                return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None)
+            };
+            if let Err(err) = game.start_combat_rounds()
+            {
+                debug!("Unable to start combat round: {}", err.msg);
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::InvalidStateAction => {
+                        (Outcome::Error(Error{ message: err.msg, kind: ErrorKind::InvalidStateAction, context: ErrorContext::expecting("Initiative Rolls") }), None)
+                    },
+                    _ => {unreachable!()}
+                }
+            }
+            else 
+            {
+                debug!("Combat round started.");
+                let (up, on_deck) = (game.currently_up().unwrap_or_default(), game.on_deck().unwrap_or_default());
+                let full_order = game.initiative_order().into_iter()
+                    .filter(|(id, _)| game.timed_event(id).is_none())
+                    .map(|(character, initiative)| InitiativeEntry { character, initiative: Some(initiative) })
+                    .collect::<Vec<InitiativeEntry>>();
+                let player_order = full_order.iter()
+                    .filter(|entry| !game.is_hidden(&entry.character))
+                    .map(|entry| match game.get_cast_by_id(&entry.character)
+                    {
+                        Some(character) if !character.player_character => InitiativeEntry { character: entry.character, initiative: None },
+                        _ => *entry,
+                    })
+                    .collect::<Vec<InitiativeEntry>>();
+
+                let gm_sender = registry.gm_sender(game_id);
+                let senders = registry.players_by_game(game_id).map_or(Vec::new(), |players| players.iter()
+                    .filter(|player_id| !registry.is_gm(player_id, game_id))
+                    .filter_map(|player_id| registry.get_player_sender(player_id))
+                    .collect::<Vec<Sender<Arc<WhatChanged>>>>());
+                let mut directed = turn_prompts(registry, game_id, up, on_deck);
+                directed.extend(timed_event_notices(registry, game_id));
+                if let Some(gm_sender) = gm_sender
+                {
+                    directed.push((Arc::from(WhatChanged::CombatStarted(full_order)), gm_sender));
+                }
+                (Outcome::CombatRoundStarted, Some(Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::CombatStarted(player_order)), send_to: senders, directed }))
+            }
+        }
+        _ => (Outcome::Error(Error {message: String::from("Only the game's GM may initiate combat."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+    }
+}
+
+// Directed prompts for the owners of whoever is acting now and whoever is on deck, so clients can alert the right people.
+fn turn_prompts(registry: &GameRegistry, game_id: &GameId, up: Vec<CharacterId>, on_deck: Vec<CharacterId>) 
+    -> Vec<(Arc<WhatChanged>, Sender<Arc<WhatChanged>>)>
+{
+    let prompt = |character: CharacterId, change: WhatChanged| 
+        registry.players_by_character(game_id, &character)
+            .and_then(|player_id| registry.get_player_sender(player_id))
+            .map(|sender| (Arc::new(change), sender));
+
+    up.into_iter().filter_map(|character| prompt(character, WhatChanged::YourTurn { character }))
+        .chain(on_deck.into_iter().filter_map(|character| prompt(character, WhatChanged::UpNext { character })))
+        .collect()
+}
+
+// The one-step answer to PassEnded: starts the next pass if anyone has one left, or the next initiative phase if the round is over.
+fn continue_combat(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return (Outcome::Error(Error { message: String::from("Only the game's GM may move the combat on."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None) };
+
+    let Some(game) = registry.get_game(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    match game.after_pass()
+    {
+        Some(AfterPass::NextPass) => try_advance_pass(registry, authority),
+        Some(AfterPass::NextRound) => try_initiative_phase(registry, authority),
+        None => (Outcome::Error(Error { message: String::from("The current pass is not over yet."), kind: ErrorKind::CannotAdvanceTurn, context: ErrorContext::default() }), None),
+    }
+}
+
+// Starting the next pass tells everyone who is up; once there are no passes left the round is over and everyone gets the summary.
+fn try_advance_pass(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let (game, game_id) = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None)};
+            (game, game_id)
+        }
+        _ => return (Outcome::Error(Error { message: String::from("Only the game's GM may advance the pass."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
+    };
+
+    match game.next_initiative_pass()
+    {
+        Ok(()) => 
+        {
+            let (up, on_deck) = (game.currently_up().unwrap_or_default(), game.on_deck().unwrap_or_default());
+            let directed = turn_prompts(registry, game_id, up, on_deck);
+            let (outcome, notification) = announce(registry, authority, Outcome::PassAdvanced, WhatChanged::PassAdvanced);
+            (outcome, notification.map(|mut notification| { notification.directed = directed; notification }))
+        },
+        Err(GameError{kind: GameErrorKind::EndOfInitiativePass, ..}) =>
+        {
+            let summary = RoundSummary 
+            { 
+                passes: game.current_pass(), 
+                combatants: game.get_combatants(), 
+                outstanding_resistance_tests: game.pending_resistance_tests().len() 
+            };
+            announce(registry, authority, Outcome::CombatRoundEnded(summary.clone()), WhatChanged::RoundEnded(summary))
+        },
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn save_checkpoint(registry: &mut GameRegistry, name: &str, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            game.save_checkpoint(name);
+            Outcome::CheckpointSaved
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may save a checkpoint."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn restore_checkpoint(registry: &mut GameRegistry, name: &str, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.restore_checkpoint(name)
+            {
+                Ok(_) =>
+                {
+                    registry.drop_missing_characters(game_id);
+                    Outcome::CheckpointRestored
+                },
+                Err(err) => action_error(err),
+            }
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may restore a checkpoint."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn list_checkpoints(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::Checkpoints(game.checkpoints())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may see the saved checkpoints."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn attach_house_rule(registry: &mut GameRegistry, script: &HouseRuleScript, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.attach_house_rule(&script.name, script.event, &script.source)
+            {
+                Ok(_) => Outcome::HouseRuleAttached,
+                Err(err) => action_error(err),
+            }
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may attach a house rule."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn remove_house_rule(registry: &mut GameRegistry, name: &str, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.remove_house_rule(name)
+            {
+                Ok(_) => Outcome::HouseRuleRemoved,
+                Err(err) => action_error(err),
+            }
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may remove a house rule."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn list_house_rules(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::HouseRulesAre(game.house_rules())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may see the house rules."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// After a rewind everyone is told, and whoever is up (or on deck) again is prompted again.
+fn rewind(registry: &mut GameRegistry, target: RewindTarget, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let (game, game_id) = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None)};
+            (game, game_id)
+        }
+        _ => return (Outcome::Error(Error { message: String::from("Only the game's GM may rewind the initiative order."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
+    };
+
+    match game.rewind(target)
+    {
+        Ok(()) => 
+        {
+            let (up, on_deck) = (game.currently_up().unwrap_or_default(), game.on_deck().unwrap_or_default());
+            registry.drop_missing_characters(game_id);
+            let directed = turn_prompts(registry, game_id, up, on_deck);
+            let (outcome, notification) = announce(registry, authority, Outcome::Rewound, WhatChanged::Rewound(target));
+            (outcome, notification.map(|mut notification| { notification.directed = directed; notification }))
+        },
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn set_auto_advance(registry: &mut GameRegistry, on: bool, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may change how turns advance."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    game.set_auto_advance(on);
+    Outcome::AutoAdvanceSet(on)
+}
+
+fn set_visibility(registry: &mut GameRegistry, options: VisibilityOptions, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may change what the players can see."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    game.set_visibility(options);
+    Outcome::VisibilitySet
+}
+
+// For the GM to tell a player who has stopped hearing the game apart from one who is just quiet.
+fn delivery_health(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may see how notifications are being delivered."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(players) = registry.players_by_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    let mut health: Vec<(PlayerId, DeliveryRecord)> = players.iter()
+        .filter_map(|player_id| registry.delivery_record(player_id).map(|record| (*player_id, record)))
+        .collect();
+    health.sort_by_key(|(player_id, _)| *player_id);
+
+    Outcome::DeliveryHealthIs(health)
+}
+
+// Setting a policy, or clearing it, gives every player a fresh timeout and hands back any control the old one had taken.
+fn set_absence_policy(registry: &mut GameRegistry, policy: Option<AbsencePolicy>, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return (Outcome::Error(Error {message: String::from("Only the game's GM may set the absence policy."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None) };
+
+    let Some(watch) = registry.absence_watch_mut(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+    let fallback = watch.policy().map(|old| old.fallback);
+    let restored = watch.set_policy(policy, Instant::now());
+
+    let (outcome, notification) = announce(registry, authority, Outcome::AbsencePolicySet, WhatChanged::AbsencePolicyChanged(policy));
+    let restorations = restore_control(registry, game_id, restored, fallback);
+
+    (outcome, notification.map(|mut notification| {
+        for restoration in restorations
+        {
+            let change = restoration.change_type;
+            notification.directed.extend(restoration.send_to.into_iter().map(|sender| (Arc::clone(&change), sender)));
+        }
+        notification
+    }))
+}
+
+// Runs ahead of every message.  Whoever sent it is plainly at the table, so any of their games that had taken control from them gives
+// it back; then, in the game the message is for, anyone the table has waited on past the policy's timeout lapses.  Only action rounds
+// are watched - the GM can already roll initiative for anyone.
+pub fn review_absences(registry: &mut GameRegistry, player_id: Option<PlayerId>, game_id: Option<GameId>) -> Vec<Notification>
+{
+    let now = Instant::now();
+    let mut notifications = Vec::new();
+
+    if let Some(player_id) = player_id
+    {
+        let game_ids: Vec<GameId> = registry.games_by_player(player_id).map_or(Vec::new(), |games| games.iter().copied().collect());
+        for game_id in game_ids
+        {
+            let Some(watch) = registry.absence_watch_mut(&game_id)
+            else { continue };
+            if watch.heard_from(player_id, now)
+            {
+                let fallback = watch.policy().map(|policy| policy.fallback);
+                debug!("Player {} is back; restoring control of their characters in game {}.", player_id, game_id);
+                notifications.extend(restore_control(registry, &game_id, vec![player_id], fallback));
+            }
+        }
+    }
+
+    if let Some(game_id) = game_id
+    {
+        notifications.extend(lapse_overdue_players(registry, &game_id, now));
+    }
+
+    notifications
+}
+
+// What the runner's clock asks for: every game's initiative deadline and absence policy looked at as though the game had just been sent
+// a message.  Returns the games anything came due in, for republishing, along with what their tables are to be told.
+pub fn review_clocks(registry: &mut GameRegistry) -> (Vec<GameId>, Vec<Notification>)
+{
+    let mut due = Vec::new();
+    let mut notifications = Vec::new();
+
+    for game_id in registry.enumerate_games()
+    {
+        let mut notices = review_absences(registry, None, Some(game_id));
+        notices.extend(review_initiative_deadline(registry, Some(game_id)));
+        if notices.is_empty()
+        {
+            continue;
+        }
+
+        notices.extend(sync_combatants(registry, Some(game_id)));
+        notifications.extend(notices);
+        due.push(game_id);
+    }
+
+    (due, notifications)
+}
+
+fn lapse_overdue_players(registry: &mut GameRegistry, game_id: &GameId, now: Instant) -> Vec<Notification>
+{
+    let Some(policy) = registry.absence_watch(game_id).and_then(|watch| watch.policy())
+    else { return Vec::new() };
+    let Some(game) = registry.get_game(game_id)
+    else { return Vec::new() };
+
+    // A player the GM already marked absent is in the GM's hands as it is.
+    let mut overdue = game.waiting_for().unwrap_or_default().iter()
+        .filter_map(|char_id| registry.players_by_character(game_id, char_id))
+        .filter(|player_id| !registry.is_gm(player_id, game_id) && !registry.is_absent(game_id, player_id))
+        .filter(|player_id| registry.absence_watch(game_id).map_or(false, |watch| watch.overdue(player_id, now)))
+        .copied()
+        .collect::<Vec<PlayerId>>();
+    overdue.sort();
+    overdue.dedup();
+
+    let mut notifications = Vec::new();
+    for player_id in overdue
+    {
+        debug!("Player {} has kept game {} waiting past its timeout.", player_id, game_id);
+        if let Some(watch) = registry.absence_watch_mut(game_id)
+        {
+            watch.lapse(player_id);
+        }
+        if policy.fallback == AbsentFallback::DelegateToGm
+        {
+            let _ = registry.set_absent(game_id, player_id, true);
+        }
+        notifications.push(table_notification(registry, game_id, WhatChanged::PlayerAbsent { player: player_id, absent: true }));
+    }
+
+    if policy.fallback == AbsentFallback::Skip
+    {
+        notifications.extend(skip_lapsed_turns(registry, game_id));
+    }
+
+    notifications
+}
+
+// Passes over every character up this turn whose player has lapsed, then moves the turn on if auto-advance is set and that was the last
+// of them.
+fn skip_lapsed_turns(registry: &mut GameRegistry, game_id: &GameId) -> Vec<Notification>
+{
+    let Some(game) = registry.get_game(game_id)
+    else { return Vec::new() };
+    let to_skip = game.waiting_for().unwrap_or_default().into_iter()
+        .filter(|char_id| registry.players_by_character(game_id, char_id)
+            .map_or(false, |player_id| registry.absence_watch(game_id).map_or(false, |watch| watch.is_lapsed(player_id))))
+        .collect::<Vec<CharacterId>>();
+    if to_skip.is_empty()
+    {
+        return Vec::new();
+    }
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Vec::new() };
+    let skipped = to_skip.into_iter().filter(|char_id| game.skip_turn(*char_id).is_ok()).collect::<Vec<CharacterId>>();
+    let advanced = game.advance_if_resolved();
+
+    let mut notifications = skipped.into_iter()
+        .map(|char_id| table_notification(registry, game_id, WhatChanged::TurnSkipped(char_id)))
+        .collect::<Vec<Notification>>();
+    if advanced
+    {
+        notifications.extend(turn_advanced_notification(registry, game_id));
+    }
+
+    notifications
+}
+
+// Gives back control the absence policy took: lifts the absent mark if it set one, and tells the table.
+fn restore_control(registry: &mut GameRegistry, game_id: &GameId, players: Vec<PlayerId>, fallback: Option<AbsentFallback>) -> Vec<Notification>
+{
+    players.into_iter()
+        .map(|player_id| {
+            if fallback == Some(AbsentFallback::DelegateToGm)
+            {
+                let _ = registry.set_absent(game_id, player_id, false);
+            }
+            table_notification(registry, game_id, WhatChanged::PlayerAbsent { player: player_id, absent: false })
+        })
+        .collect()
+}
+
+fn set_player_absent(registry: &mut GameRegistry, absence: &PlayerAbsence, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may mark a player as absent."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    match registry.set_absent(game_id, absence.player_id, absence.absent)
+    {
+        Ok(()) => Outcome::PlayerAbsenceSet,
+        Err(()) => Outcome::Error(Error {message: String::from("The player is not seated at this game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()}),
+    }
+}
+
+fn set_auto_cycle_passes(registry: &mut GameRegistry, on: bool, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may change how passes advance."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    game.set_auto_cycle_passes(on);
+    Outcome::AutoCyclePassesSet(on)
+}
+
+fn set_carry_over(registry: &mut GameRegistry, on: bool, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may change what carries over between fights."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    game.set_carry_over(on);
+    Outcome::CarryOverSet(on)
+}
+
+fn rest(registry: &mut GameRegistry, characters: &[CharacterId], authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may call a rest."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.rest(characters)
+    {
+        Ok(()) => Outcome::Rested,
+        Err(err) => Outcome::Error(Error {message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}),
+    }
+}
+
+// The GM may mark anyone; a player only their own characters.  If that passes over the last of those up, everyone hears where the turn
+// went; otherwise the table hears of the mark.
+fn sit_out(registry: &mut GameRegistry, sitting_out: &SittingOut, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let (game_id, game) = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) => match registry.get_mut_game(game_id)
+        {
+            Some(game) => (*game_id, game),
+            None => return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None),
+        },
+        Role::RolePlayer(_, game_id) => match owned_character_game(registry, &sitting_out.character_id, authority)
+        {
+            Ok(game) => (*game_id, game),
+            Err(outcome) => return (outcome, None),
+        },
+        _ => return (Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to act on."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None),
+    };
+
+    match game.sit_out(sitting_out.character_id, sitting_out.reason)
+    {
+        Ok(true) =>
+        {
+            let advanced = turn_advanced(game, matches!(authority.resource_role(), Role::RoleGM(..)));
+            (Outcome::SittingOutSet { advanced: Some(advanced) }, turn_advanced_notification(registry, &game_id))
+        },
+        Ok(false) => (Outcome::SittingOutSet { advanced: None }, 
+            Some(table_notification(registry, &game_id, WhatChanged::SittingOut { character: sitting_out.character_id, reason: sitting_out.reason }))),
+        Err(err) => (action_error(err), None),
+    }
+}
+
+// Tells the table of every turn passed over since the last message because its character was down, delaying or away.  Only the GM hears
+// of hidden characters.
+pub fn announce_skipped_turns(registry: &mut GameRegistry, game_id: Option<GameId>) -> Vec<Notification>
+{
+    let Some(game_id) = game_id
+    else { return Vec::new() };
+    let Some(skipped) = registry.get_mut_game(&game_id).map(|game| game.take_skipped_turns())
+    else { return Vec::new() };
+    let Some(game) = registry.get_game(&game_id)
+    else { return Vec::new() };
+
+    skipped.into_iter()
+        .filter_map(|(character, _)| if game.is_hidden(&character)
+        {
+            registry.gm_sender(&game_id)
+                .map(|sender| Notification { game_id: Some(game_id), change_type: Arc::new(WhatChanged::TurnSkipped(character)), send_to: vec![sender], directed: Vec::new() })
+        }
+        else
+        {
+            Some(table_notification(registry, &game_id, WhatChanged::TurnSkipped(character)))
+        })
+        .collect()
+}
+
+// Unless the table carries damage and resources over, the player characters who fought are rested as the combat ends.  There has to be a
+// combat to end.
+fn end_combat(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may end the combat."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.end_combat()
+    {
+        Ok(()) => Outcome::CombatEnded,
+        Err(err) => action_error(err),
+    }
+}
+
+// A player running several characters tied on the same initiative - a GM's squad of grunts, say - resolves all of their turns in one go.
+// If that leaves nobody unresolved, the turn advances without waiting on the GM and everyone hears about it.
+fn take_actions(registry: &mut GameRegistry, actions: &Vec<Action>, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let (player_id, game_id) = match authority.resource_role()
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => (player_id, game_id),
+        _ => return (Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to act on."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+    };
+
+    let owned = registry.characters_by_player(game_id, player_id);
+    if !actions.iter().all(|action| owned.map_or(false, |chars| chars.contains(&action.character_id)))
+    {
+        return (Outcome::Error(Error {message: String::from("Only the owner of a character may take an action for it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None);
+    }
+
+    if actions.iter().any(|action| action.roll.is_some())
+    {
+        return (Outcome::Error(Error {message: String::from("Roll macros cannot be used in a batch of turns; take that action on its own."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default()}), None);
+    }
+    // A catalog action names its own type, journals its name and spends ammunition, none of which a batch of turns does.
+    if actions.iter().any(|action| action.catalog.is_some())
+    {
+        return (Outcome::Error(Error {message: String::from("Catalog actions cannot be used in a batch of turns; take that action on its own."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default()}), None);
+    }
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    let turns = actions.iter().map(|action| (action.character_id, action.action)).collect::<Vec<(CharacterId, ActionType)>>();
+    match game.take_turns(&turns)
+    {
+        Ok(false) =>
+        {
+            let notification = registry.gm_sender(game_id)
+                .map(|sender| Notification { game_id: Some(*game_id), change_type: Arc::from(WhatChanged::PlayerActed), send_to: vec![sender], directed: Vec::new() });
+            (Outcome::ActionsTaken { advanced: None }, notification)
+        },
+        Ok(true) =>
+        {
+            // Answered in the same view the sender would be notified in, so a player never learns NPC scores from their own reply.
+            let advanced = turn_advanced(game, matches!(authority.resource_role(), Role::RoleGM(..)));
+            (Outcome::ActionsTaken { advanced: Some(advanced) }, turn_advanced_notification(registry, game_id))
+        },
+        Err(err) => (action_error(err), None),
+    }
+}
+
+// Who is up and on deck once the turn has moved on, with their scores.  The players' view leaves out hidden characters and withholds
+// NPC scores, the same as the order announced when the round starts.
+fn turn_advanced(game: &Game, gm_view: bool) -> TurnAdvanced
+{
+    let entries = |characters: Vec<CharacterId>, initiative: Option<i8>| characters.into_iter()
+        .filter(|character| game.timed_event(character).is_none())
+        .filter(|character| gm_view || !game.is_hidden(character))
+        .map(|character| match game.get_cast_by_id(&character)
+        {
+            Some(cast) if !gm_view && !cast.player_character => InitiativeEntry { character, initiative: None },
+            _ => InitiativeEntry { character, initiative },
+        })
+        .collect::<Vec<InitiativeEntry>>();
+
+    TurnAdvanced
+    {
+        pass: game.current_pass(),
+        up: entries(game.currently_up().unwrap_or_default(), game.get_current_init()),
+        on_deck: entries(game.on_deck().unwrap_or_default(), game.get_next_init()),
+        events_up: timed_events(game, game.currently_up()),
+        events_on_deck: timed_events(game, game.on_deck()),
+    }
+}
+
+fn timed_events(game: &Game, ids: Option<Vec<Uuid>>) -> Vec<TimedEvent>
+{
+    ids.unwrap_or_default().iter().filter_map(|id| game.timed_event(id)).cloned().collect()
+}
+
+// Everyone at the table, the GM included, hears each event that has just come up.
+fn timed_event_notices(registry: &GameRegistry, game_id: &GameId) -> Vec<(Arc<WhatChanged>, Sender<Arc<WhatChanged>>)>
+{
+    let Some(game) = registry.get_game(game_id)
+    else { return Vec::new() };
+    let senders = registry.players_by_game(game_id).map_or(Vec::new(), |players| players.iter()
+        .filter_map(|player_id| registry.get_player_sender(player_id))
+        .collect::<Vec<Sender<Arc<WhatChanged>>>>());
+
+    timed_events(game, game.currently_up()).into_iter()
+        .flat_map(|event| { let change = Arc::new(WhatChanged::TimedEventUp(event)); senders.iter().map(move |sender| (Arc::clone(&change), sender.clone())) })
+        .collect()
+}
+
+// The players' view goes to the owners of every combatant, the GM's view to the GM - along with what comes next if that was the end of
+// the pass - and each owner whose character is now up or on
+// deck gets their prompt as well.
+fn turn_advanced_notification(registry: &GameRegistry, game_id: &GameId) -> Option<Notification>
+{
+    let game = registry.get_game(game_id)?;
+    let (up, on_deck) = (game.currently_up().unwrap_or_default(), game.on_deck().unwrap_or_default());
+
+    let mut owners = game.get_combatants().iter()
+        .filter_map(|char_id| registry.players_by_character(game_id, char_id))
+        .filter(|player_id| !registry.is_gm(player_id, game_id))
+        .copied()
+        .collect::<Vec<PlayerId>>();
+    owners.sort();
+    owners.dedup();
+    let senders = owners.iter().filter_map(|player_id| registry.get_player_sender(player_id)).collect::<Vec<Sender<Arc<WhatChanged>>>>();
+
+    let mut directed = turn_prompts(registry, game_id, up, on_deck);
+    directed.extend(timed_event_notices(registry, game_id));
+    if let Some(gm_sender) = registry.gm_sender(game_id)
+    {
+        directed.push((Arc::new(WhatChanged::TurnAdvanced(turn_advanced(game, true))), gm_sender.clone()));
+        if let Some(next) = game.after_pass()
+        {
+            directed.push((Arc::new(WhatChanged::PassEnded(next)), gm_sender));
+        }
+    }
+
+    Some(Notification { game_id: Some(*game_id), change_type: Arc::new(WhatChanged::TurnAdvanced(turn_advanced(game, false))), send_to: senders, directed })
+}
+
+pub fn try_advance_turn(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+
+    let (game, game_id) = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()}), None)};
+            (game, game_id)
+        }
+        _ => return (Outcome::Error(Error { message: String::from("Only the game's GM may advance the turn."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() }), None)
+    };
+
+    match game.advance_round()
+    {
+        Ok(()) => {
+            let advanced = turn_advanced(game, true);
+            (Outcome::TurnAdvanced(advanced), turn_advanced_notification(registry, game_id))
+        }, 
+        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::InvalidStateAction, ..}) => {
+            (Outcome::Error(Error{message: msg, kind: ErrorKind::InvalidStateAction, context: ErrorContext::expecting("Initiative Pass")}), None)
+        }, 
+        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::UnresolvedCombatant, ..}) => {
+            (Outcome::Error(Error{message: msg, kind: ErrorKind::CannotAdvanceTurn, context: ErrorContext::default()}), None)
+        },
+        // The last of the pass has resolved: tell the GM what comes next rather than just refusing.
+        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::EndOfInitiative, ..}) => {
+            match game.after_pass()
+            {
+                Some(next) => (Outcome::PassEnded(next), turn_advanced_notification(registry, game_id)),
+                None => (Outcome::Error(Error{message: msg, kind: ErrorKind::NoEventsLeft, context: ErrorContext::default()}), None),
+            }
+        },
+        _ => unreachable!("The other game ErrorKind types should not exist.")
+    }
+}
+
+fn take_action(registry: &mut GameRegistry, action: &Action, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    debug!("Started take_action()");
+    let (game, game_id, proxy_for) = match authority.resource_role() 
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => {
+            debug!("Authority for player {} on game {} is RoleGM or RolePlayer", player_id, game_id);
+            let owns = registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(&action.character_id));
+            // The GM may also act for a character whose player is away, so one empty seat does not hold up the table.
+            let proxy_for = match authority.resource_role()
+            {
+                Role::RoleGM(..) if !owns => registry.absent_owner(game_id, &action.character_id),
+                _ => None,
+            };
+            if owns || proxy_for.is_some()
+            {
+                debug!("Player {} owns character {} or stands in for its owner, and may take action.", player_id, action.character_id);
+                let Some(game) = registry.get_mut_game(game_id)
+                else {return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None)};
+                (game, game_id, proxy_for)
+            }
+            else {
+                debug!("Player {} does not own character {} and may not take the action.", player_id, action.character_id);
+                return (Outcome::Error(Error {message: String::from("Only the owner of a character may take an action for it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None);
+            }
+        }
+        _ => return (Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to act on."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
+    };
+
+    debug!("Game found.  Attempting to take the action.");
+
+    // Check the macro before the action is spent, so a typo does not cost the character their action.
+    if let Some(name) = &action.roll
+    {
+        if !game.has_roll_macro(action.character_id, name)
+        {
+            return (Outcome::Error(Error{message: String::from(format!("The character has no roll macro named {}.", name)), kind: ErrorKind::NoSuchRollMacro, context: ErrorContext::default()}), None);
+        }
+    }
+
+    let taken = match &action.catalog
+    {
+        Some(name) => game.take_catalog_action(action.character_id, name).map(|cataloged| Some(cataloged.name)),
+        None => game.take_action(action.character_id, action.action).map(|_| None),
+    };
+    if let (Ok(_), Some(player_id)) = (&taken, proxy_for)
+    {
+        game.record_proxy(action.character_id, player_id);
+    }
+
+    match taken
+    {
+        Ok(_) if action.roll.is_some() => 
+        {
+            let name = action.roll.as_deref().unwrap_or_default();
+            let record = match game.roll_macro(&mut rand::thread_rng(), action.character_id, name)
+            {
+                Ok(record) => record,
+                Err(err) => return (action_error(err), None),
+            };
+
+            let directed = registry.gm_sender(game_id).map(|sender| (Arc::from(WhatChanged::PlayerActed), sender)).into_iter().collect();
+            let (outcome, notification) = announce(registry, authority, Outcome::ActionRolled(record.clone()), WhatChanged::DiceRolled(record));
+            (outcome, notification.map(|mut notification| { notification.directed = directed; notification }))
+        },
+        Ok(name) => 
+        {
+            debug!("Action successful.  Gathering players to notify...");
+            let change = match name
+            {
+                Some(name) => WhatChanged::NamedActionTaken { character: action.character_id, name },
+                None => WhatChanged::PlayerActed,
+            };
+            let notification = registry.gm_sender(game_id)
+                .map(|sender| {
+                    let mut senders = Vec::with_capacity(1);
+                    senders.push(sender);
+                    Notification { game_id: Some(*game_id), change_type: Arc::from(change), send_to:  senders, directed: Vec::new() }
+                });
+            (Outcome::ActionTaken, notification)
+        },
+        Err(err) => 
+        {
+            debug!("Action unsuccessful.  Categorizing error for message: {}", err.msg);
+            match err.kind
+            {
+                crate::tracker::game::ErrorKind::InvalidStateAction => {
+                    (Outcome::Error(Error{message: err.msg, kind: ErrorKind::InvalidStateAction, context: ErrorContext::expecting("Initiative Pass")}), None)
+                },
+                crate::tracker::game::ErrorKind::UnknownCastId => 
+                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}), None)},
+                crate::tracker::game::ErrorKind::EndOfInitiative => 
+                    {(Outcome::Error(Error{message:err.msg, kind: ErrorKind::CannotAdvanceTurn, context: ErrorContext::default()}), None)},
+                crate::tracker::game::ErrorKind::NoAction => 
+                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoActionLeft, context: ErrorContext::default()}), None)},
+                crate::tracker::game::ErrorKind::UnresolvedCombatant => 
+                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NotCharactersTurn, context: ErrorContext::default()}), None)},
+                crate::tracker::game::ErrorKind::UnknownCatalogAction => 
+                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCatalogAction, context: ErrorContext::default()}), None)},
+                crate::tracker::game::ErrorKind::OutOfAmmo => 
+                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::OutOfAmmo, context: ErrorContext::default()}), None)},
+                _ => {unreachable!("Should not be called.")}
+            }
+        },
+    }
+}
+fn list_current_turn_events(game_registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    let game = match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
+        {
+            let Some(game) = game_registry.get_game(game_id)
+            else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            game
+        },
+        _ => {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    };
+
+    Outcome::MatchingEventsAre(game.currently_up())
+}
+
+fn list_unresolved_events(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let game = match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
+        {
+            let Some(game) = registry.get_game(game_id)
+            else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            game
+        },
+        _ => {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    };
+
+    Outcome::MatchingEventsAre(game.waiting_for())
+}
+
+fn list_next_turn_events(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::MatchingEventsAre(game.on_deck())
+        },
+        _ => 
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    }
+    
+}
+
+fn list_all_events_by_id_this_pass(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::MatchingEventsById(game.collect_all_remaining_events())
+        }
+        _ => 
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    }
+    
+}
+
+fn next_initiative(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::InitiativeIs(game.get_next_init())
+        }
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    }
+}
+
+fn current_initiative(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::InitiativeIs(game.get_current_init())
+        }
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    }
+}
+
+fn remaining_initiatives_are(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::InitiativesAre(game.get_all_remaining_initiatives())
+        }
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    }
+    
+}
+
+fn remaining_actions(char_id: &CharacterId, registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let game = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => 
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            game
+        },
+        Role::RolePlayer(player_id, game_id) =>
+        {
+            if !registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(char_id))
+            {
+                return Outcome::Error(Error { message: String::from("Player ID is not an owner of the character."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() });
+            }
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            game
+        },
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only the GM or the character's owner may view its remaining actions."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    };
+
+    match game.remaining_actions(*char_id)
+    {
+        Ok(budget) => Outcome::RemainingActionsAre(budget),
+        Err(err) => Outcome::Error(Error { message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default() }),
+    }
+}
+
+// The same people who may see a character's remaining actions may ask what it could do with them.
+fn available_actions(char_id: &CharacterId, registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    let game_id = match authority.resource_role() {
+        Role::RoleGM(_, game_id) => game_id,
+        Role::RolePlayer(player_id, game_id) if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(char_id)) => game_id,
+        Role::RolePlayer(..) =>
+        {
+            return Outcome::Error(Error { message: String::from("Player ID is not an owner of the character."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default() });
+        },
+        _ =>
+        {
+            return Outcome::Error(Error {message: String::from("Only the GM or the character's owner may see what it can do."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+        }
+    };
+
+    let Some(game) = registry.get_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.available_actions(*char_id)
+    {
+        Ok(actions) => Outcome::AvailableActionsAre(actions),
+        Err(err) => Outcome::Error(Error { message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default() }),
+    }
+}
+
+fn declare_movement(registry: &mut GameRegistry, movement: &Movement, authority: &Authority) -> Outcome
+{
+    debug!("Started declare_movement()");
+    let game = match authority.resource_role() 
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => {
+            if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(&movement.character_id))
+            {
+                let Some(game) = registry.get_mut_game(game_id)
+                else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()})};
+                game
+            }
+            else {
+                debug!("Player {} does not own character {} and may not move it.", player_id, movement.character_id);
+                return Outcome::Error(Error {message: String::from("Only the owner of a character may move it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+            }
+        }
+        _ => return Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to move."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+    };
+
+    match game.declare_movement(movement.character_id, movement.meters, movement.sprint_hits)
+    {
+        Ok(gait) => Outcome::Moved(gait),
+        Err(err) => 
+        {
+            debug!("Movement rejected: {}", err.msg);
+            match err.kind
+            {
+                GameErrorKind::InvalidStateAction => Outcome::Error(Error{context: ErrorContext::from_game_error(&err), message: err.msg, kind: ErrorKind::InvalidStateAction}),
+                GameErrorKind::UnknownCastId => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}),
+                GameErrorKind::UnresolvedCombatant => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NotCharactersTurn, context: ErrorContext::default()}),
+                GameErrorKind::ExceedsMovementRate => Outcome::Error(Error{message: err.msg, kind: ErrorKind::ExceedsMovementRate, context: ErrorContext::default()}),
+                _ => {unreachable!("Should not be called.")}
+            }
+        }
+    }
+}
+
+fn movement_ledger(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::MovementLedger(game.movement_ledger())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the GM may view the movement ledger."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn ranged_attack(registry: &GameRegistry, attack: &RangedAttack, authority: &Authority) -> Outcome
+{
+    debug!("Started ranged_attack()");
+    let game = match authority.resource_role() 
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => {
+            if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(&attack.attacker))
+            {
+                let Some(game) = registry.get_game(game_id)
+                else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()})};
+                game
+            }
+            else {
+                return Outcome::Error(Error {message: String::from("Only the owner of a character may declare its attacks."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()});
+            }
+        }
+        _ => return Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to attack with."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+    };
+
+    match game.ranged_attack_modifiers(attack)
+    {
+        Ok(modifiers) => Outcome::AttackModifiersAre(modifiers),
+        Err(err) => match err.kind
+        {
+            GameErrorKind::InvalidStateAction => Outcome::Error(Error{context: ErrorContext::from_game_error(&err), message: err.msg, kind: ErrorKind::InvalidStateAction}),
+            GameErrorKind::UnknownCastId => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}),
+            _ => {unreachable!("Should not be called.")}
+        }
+    }
+}
+
+fn engage(registry: &mut GameRegistry, melee: &MeleeTarget, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &melee.character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.engage(melee.character_id, melee.target_id)
+    {
+        Ok(engagement) => Outcome::Engaged(engagement),
+        Err(err) => action_error(err),
+    }
+}
+
+fn disengage(registry: &mut GameRegistry, melee: &MeleeTarget, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &melee.character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.disengage(melee.character_id, melee.target_id)
+    {
+        Ok(()) => Outcome::Disengaged,
+        Err(err) => action_error(err),
+    }
+}
+
+fn full_defense(registry: &mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.go_full_defense(*character_id)
+    {
+        Ok(cost) => Outcome::OnFullDefense(cost),
+        Err(err) => action_error(err),
+    }
+}
+
+// The GM marks who is caught in the blast, so area attacks are declared by the GM on the attacker's behalf.
+fn area_attack(registry: &mut GameRegistry, attack: &AreaAttack, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.resolve_area_attack(attack)
+            {
+                Ok(resolution) => Outcome::AreaAttackResolved(resolution),
+                Err(err) => action_error(err),
+            }
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the GM may resolve an area attack."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// The GM sees every outstanding test; players only see the ones owed by their own characters.
+fn resistance_tests(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::ResistanceTestsAre(game.pending_resistance_tests())
+        }
+        Role::RolePlayer(player_id, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            let owned = registry.characters_by_player(game_id, player_id);
+            Outcome::ResistanceTestsAre(game.pending_resistance_tests().into_iter()
+                .filter(|prompt| owned.map_or(false, |chars| chars.contains(&prompt.character_id)))
+                .collect())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the GM and players may view resistance tests."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn resistance_test_made(registry: &mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.resistance_test_made(*character_id)
+    {
+        Ok(prompt) => Outcome::ResistanceTestCleared(prompt),
+        Err(err) => action_error(err),
+    }
+}
+
+// Everyone hears about the spell; the GM is also told directly about the caster's drain test so it doesn't get lost among everything
+// else happening that turn.
+fn cast_spell(registry: &mut GameRegistry, declaration: &SpellDeclaration, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let game = match owned_character_game(registry, &declaration.caster, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return (outcome, None),
+    };
+
+    match game.cast_spell(declaration)
+    {
+        Ok(prompts) =>
+        {
+            let (outcome, notification) = announce(registry, authority, Outcome::SpellCast(prompts), WhatChanged::SpellCast(declaration.caster));
+            let game_id = match authority.resource_role() { Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) => Some(game_id), _ => None };
+            let notification = notification.map(|mut notification| {
+                if let Some(gm_sender) = game_id.and_then(|game_id| registry.gm_sender(game_id))
+                {
+                    notification.directed.push((Arc::from(WhatChanged::DrainTestPending(declaration.caster)), gm_sender));
+                }
+                notification
+            });
+            (outcome, notification)
+        }
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn drop_spell(registry: &mut GameRegistry, drop: &SpellDrop, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &drop.character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.drop_spell(drop.character_id, &drop.spell)
+    {
+        Ok(spell) => Outcome::SpellDropped(spell),
+        Err(err) => action_error(err),
+    }
+}
+
+fn register_matrix_target(registry: &mut GameRegistry, target: &MatrixTarget, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::MatrixTargetRegistered(game.register_matrix_target(target.clone()))
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may register Matrix devices and hosts."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn jack_in(registry: &mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.jack_in(*character_id)
+    {
+        Ok(_) => Outcome::JackedIn,
+        Err(err) => action_error(err),
+    }
+}
+
+fn jack_out(registry: &mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.jack_out(*character_id)
+    {
+        Ok(_) => Outcome::JackedOut,
+        Err(err) => action_error(err),
+    }
+}
+
+fn matrix_action(registry: &mut GameRegistry, declaration: &MatrixActionDeclaration, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &declaration.decker, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.declare_matrix_action(&mut rand::thread_rng(), declaration)
+    {
+        Ok(resolution) => Outcome::MatrixActionResolved(resolution),
+        Err(err) => action_error(err),
+    }
+}
+
+fn matrix_state(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::MatrixIs(game.matrix().clone())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// The owner may edit their own character, and the GM any character in the game; the GM's edits need nobody's approval.
+fn update_character(registry: &mut GameRegistry, character_id: &CharacterId, patch: &CharacterPatch, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let (game_id, gm_approved) = match authority.resource_role()
+    {
+        Role::RoleGM(_, game_id) => (*game_id, true),
+        Role::RolePlayer(player_id, game_id) if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(character_id)) => (*game_id, false),
+        Role::RolePlayer(..) => return (Outcome::Error(Error {message: String::from("Only the owner of a character may edit it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None),
+        _ => return (Outcome::Error(Error {message: String::from("Unregistered or observing players have no character to edit."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None),
+    };
+
+    let mut patch = patch.clone();
+    patch.name = patch.name.map(|name| registry.intern_name(&name));
+
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    match game.update_character(*character_id, patch, gm_approved)
+    {
+        Ok(PatchOutcome::Applied(fields)) => 
+            announce(registry, authority, Outcome::CharacterUpdated(fields.clone()), WhatChanged::CharacterUpdated { character: *character_id, fields }),
+        Ok(PatchOutcome::AwaitingApproval(fields)) =>
+        {
+            let notification = registry.gm_sender(&game_id).map(|sender| Notification 
+            { 
+                game_id: Some(game_id), change_type: Arc::from(WhatChanged::CharacterUpdatePending(*character_id)), send_to: vec![sender], directed: Vec::new() 
+            });
+            (Outcome::CharacterUpdateAwaitingApproval(fields), notification)
+        },
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn approve_character_update(registry: &mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> (Outcome, Option<Notification>)
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return (Outcome::Error(Error {message: String::from("Only the game's GM may approve a character edit."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+
+    match game.approve_character_update(*character_id)
+    {
+        Ok(fields) => announce(registry, authority, Outcome::CharacterUpdated(fields.clone()), WhatChanged::CharacterUpdated { character: *character_id, fields }),
+        Err(err) => (action_error(err), None),
+    }
+}
+
+fn adjust_initiative(registry: &mut GameRegistry, adjustment: &InitiativeAdjustment, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may adjust a character's initiative."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.adjust_initiative(adjustment.character_id, adjustment.delta, adjustment.reason.clone())
+    {
+        Ok(initiative) => Outcome::InitiativeAdjusted(initiative),
+        Err(err) => action_error(err),
+    }
+}
+
+// Only the GM hears of an event when it is scheduled; the table finds out when it comes up.
+fn schedule_event(registry: &mut GameRegistry, schedule: &EventSchedule, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may schedule an event."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.schedule_event(schedule.label.clone(), schedule.initiative, schedule.rounds)
+    {
+        Ok(event) => Outcome::EventScheduled(event),
+        Err(err) => action_error(err),
+    }
+}
+
+fn change_plane(registry: &mut GameRegistry, change: &PlaneChange, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &change.character_id, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    match game.change_plane(change.character_id, change.plane)
+    {
+        Ok(_) => Outcome::PlaneChanged,
+        Err(err) => action_error(err),
+    }
+}
+
+fn list_sustained_spells(registry: &GameRegistry, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::SustainedSpellsAre(game.sustained_spells())
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+// The GM marks damage on anyone; a player marks it on their own characters.
+fn apply_damage(registry: &mut GameRegistry, damage: &DamageApplication, authority: &Authority) -> Outcome
+{
+    let game = match gm_or_owner_game(registry, &damage.target, authority)
+    {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
+
+    let kind = match damage.armor
+    {
+        Some((armor_test, armor_pen)) => match game.damage_against_armor(damage.target, damage.boxes, damage.kind, armor_test, armor_pen)
+        {
+            Ok(kind) => kind,
+            Err(err) => return action_error(err),
+        },
+        None => damage.kind,
+    };
+
+    match game.apply_damage(damage.source, damage.target, damage.boxes, kind)
+    {
+        Ok(_) => Outcome::DamageApplied,
+        Err(err) => action_error(err),
     }
-
 }
 
-fn set_init_roll(registry: &mut GameRegistry, game_id: &Uuid, roll: &Roll) -> (Outcome, Option<Notification>) {
-    debug!("Starting set_init_roll()");
-    if let Some(game) = registry.get_mut_game(game_id)
+fn heal(registry: &mut GameRegistry, application: &HealingApplication, authority: &Authority) -> Outcome
+{
+    let game = match gm_or_owner_game(registry, &application.character_id, authority)
     {
-        debug!("Game exists for id {}", game_id);
-        match game.accept_initiative_roll(roll.character_id, roll.roll)
-        {
-            Ok(_) => {
-                debug!("Initiative added.");
-                (Outcome::InitiativeRollAdded, None)
-            },
-            Err(GameError{kind: GameErrorKind::InvalidStateAction, ..}) => {
-                debug!("Initiative add failed: Game is not in initiative phase.");
-                (Outcome::Error(Error {message: String::from("The game is not in the initiatve state."), kind: ErrorKind::InvalidStateAction}), None)
-            }
-            Err(GameError{kind: GameErrorKind::UnknownCastId, ..}) => {
-                debug!("Initiative add failed: Character ID is not part of the combat group.");
-                (Outcome::Error(Error { message: String::from("The character ID provided is not registered as part of combat."), kind: ErrorKind::UnknownId }), None)
-            }
-            _ => {
-                debug!("Unexpected error during initiative set.");
-                (Outcome::Error(Error { message: String::from("Unexpected error type returned from initiative add."), kind: ErrorKind::InvalidStateAction}), None)
-            }
-        }
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
 
-    
+    match game.heal(&mut rand::thread_rng(), application.character_id, application.healing)
+    {
+        Ok(recovery) => Outcome::Healed(recovery),
+        Err(err) => action_error(err),
     }
-    else
+}
+
+fn apply_damage_event(registry: &mut GameRegistry, event: &DamageEvent, authority: &Authority) -> Outcome
+{
+    let Role::RoleGM(_, game_id) = authority.resource_role()
+    else { return Outcome::Error(Error {message: String::from("Only the game's GM may apply damage."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}) };
+    let Some(game) = registry.get_mut_game(game_id)
+    else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+    match game.apply_damage_event(event)
     {
-        return (Outcome::Error(Error { message: String::from("No game found by provided ID."), kind: ErrorKind::UnknownId }), None)
+        Ok(prompts) => Outcome::DamageEventApplied(prompts),
+        Err(err) => action_error(err),
     }
 }
 
+// One notification for the whole event rather than one per target.  The GM hears every target; the players hear the ones they can see.
+fn damage_event_notification(registry: &GameRegistry, authority: &Authority, source: Uuid, prompts: &[ResistancePrompt]) -> Option<Notification>
+{
+    let Role::RoleGM(gm_id, game_id) = authority.resource_role()
+    else { return None };
+    let game = registry.get_game(game_id)?;
 
-fn try_begin_combat(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+    let targets = prompts.iter().map(|prompt| prompt.character_id).collect::<Vec<CharacterId>>();
+    let seen = targets.iter().copied().filter(|target| !game.is_hidden(target)).collect::<Vec<CharacterId>>();
+    let senders = registry.players_by_game(game_id).map_or(Vec::new(), |players| players.iter()
+        .filter(|player_id| *player_id != gm_id)
+        .filter_map(|player_id| registry.get_player_sender(player_id))
+        .collect());
+    let directed = registry.gm_sender(game_id).map(|sender| (Arc::new(WhatChanged::DamageEventApplied { source, targets }), sender)).into_iter().collect();
+
+    Some(Notification { game_id: Some(*game_id), change_type: Arc::new(WhatChanged::DamageEventApplied { source, targets: seen }), send_to: senders, directed })
+}
+
+// Damage or healing: the GM is told the target's exact boxes.  The players are told whatever the game's visibility options let them see,
+// and nothing at all when that is nothing.
+fn wounds_notification(registry: &GameRegistry, authority: &Authority, target: &CharacterId, change: fn(CharacterId, WoundView) -> WhatChanged) 
+    -> Option<Notification>
 {
-    debug!("Starting try_begin_combat");
-    match authority.resource_role()
+    let (Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id)) = authority.resource_role()
+    else { return None };
+    let game = registry.get_game(game_id)?;
+
+    let gm_view = change(*target, game.wounds_seen_by(target, true)?);
+    let gm_sender = registry.gm_sender(game_id);
+    let Some(wounds) = game.wounds_seen_by(target, false)
+    else { return Some(Notification { game_id: Some(*game_id), change_type: Arc::new(gm_view), send_to: gm_sender.into_iter().collect(), directed: Vec::new() }) };
+
+    let senders = registry.players_by_game(game_id).map_or(Vec::new(), |players| players.iter()
+        .filter(|player_id| !registry.is_gm(player_id, game_id))
+        .filter_map(|player_id| registry.get_player_sender(player_id))
+        .collect());
+    let directed = gm_sender.map(|sender| (Arc::new(gm_view), sender)).into_iter().collect();
+
+    Some(Notification { game_id: Some(*game_id), change_type: Arc::new(change(*target, wounds)), send_to: senders, directed })
+}
+
+fn spend_edge(registry: &mut GameRegistry, spend: &EdgeSpend, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &spend.character_id, authority)
     {
-        Role::RoleGM(player_id, game_id) => {
-            debug!("Authority for {} in game {} is RoleGM", player_id, game_id);
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
 
-            let Some(game) = registry.get_mut_game(game_id) 
-            else {
-                debug!("Game not found for game id {}", game_id);
-                return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}), None)
-            };
-            if let Err(err) = game.start_combat_rounds()
-            {
-                debug!("Unable to start combat round: {}", err.msg);
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::InvalidStateAction => {
-                        (Outcome::Error(Error{ message: err.msg, kind: ErrorKind::InvalidStateAction }), None)
-                    },
-                    _ => {unreachable!()}
-                }
-            }
-            else 
-            {
-                debug!("Combat round started.");
-                let senders = game.get_combatants().iter().map(|char_id| registry.players_by_character(game_id, char_id))
-                    .filter(|player_id| player_id.is_some()).map(|player_id| player_id.unwrap())
-                    .map(|player_id| registry.get_player_sender(player_id)).map(|sender| sender.unwrap())
-                    .collect::<Vec<Sender<Arc<WhatChanged>>>>();
-                (Outcome::CombatRoundStarted, Some(Notification{ change_type: Arc::from(WhatChanged::CombatStarted), send_to: senders }))
-            }
+    match game.spend_edge(spend.character_id, spend.points)
+    {
+        Ok(remaining) => Outcome::EdgeSpent(remaining),
+        Err(err) => action_error(err),
+    }
+}
+
+fn combat_report(registry: &GameRegistry, scope: ReportScope, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::CombatReportIs(game.combat_report(scope))
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may view the combat report."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
-        _ => (Outcome::Error(Error {message: String::from("Only the game's GM may initiate combat."), kind: ErrorKind::UnauthorizedAction}), None)
     }
 }
 
-pub fn try_advance_turn(registry: &mut GameRegistry, authority: &Authority) -> (Outcome, Option<Notification>)
+fn round_timeline(registry: &GameRegistry, authority: &Authority) -> Outcome
 {
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::RoundTimelineIs(game.round_timeline(matches!(authority.resource_role(), Role::RoleGM(..))))
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may view the combat timeline."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
 
-    let (game, game_id) = match authority.resource_role() {
-        Role::RoleGM(_, game_id) => {
-            let Some(game) = registry.get_mut_game(game_id)
-            else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::UnknownId}), None)};
-            (game, game_id)
+// Anyone seated may follow the journal, but only the GM reads all of it.
+fn journal_after(registry: &GameRegistry, sequence: Option<usize>, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) | Role::RoleObserver(player_id, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::JournalEntries(game.journal_after(sequence, *player_id, matches!(authority.resource_role(), Role::RoleGM(..))))
         }
-        _ => return (Outcome::Error(Error { message: String::from("Only the game's GM may advance the turn."), kind: ErrorKind::UnauthorizedAction }), None)
-    };
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only registered players and observers may follow the journal."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
 
-    match game.advance_round()
+// The journal holds every whisper, so only the GM may export it whole.
+fn export_journal(registry: &GameRegistry, filter: &JournalFilter, authority: &Authority) -> Outcome
+{
+    match authority.resource_role() {
+        Role::RoleGM(_, game_id) =>
+        {
+            let Some(game) = registry.get_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::JournalEntries(game.journal_entries(filter))
+        }
+        _ =>
+        {
+            Outcome::Error(Error {message: String::from("Only the game's GM may export the journal."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
+        }
+    }
+}
+
+fn define_roll_macro(registry: &mut GameRegistry, definition: &MacroDefinition, authority: &Authority) -> Outcome
+{
+    let game = match owned_character_game(registry, &definition.character_id, authority)
     {
-        Ok(()) => {
+        Ok(game) => game,
+        Err(outcome) => return outcome,
+    };
 
-            let senders = game.get_combatants().iter()
-                            .map(|char_id| registry.players_by_character(game_id, char_id))
-                            .filter(|player_id_opt| player_id_opt.is_some())
-                            .map(|player_id_opt| player_id_opt.unwrap())
-                            .map(|player_id| registry.get_player_sender(player_id))
-                            .map(|player_sender_opt| player_sender_opt.unwrap())
-                            .collect::<Vec<Sender<Arc<WhatChanged>>>>();
-            (Outcome::TurnAdvanced, Some(Notification { change_type: Arc::from(WhatChanged::TurnAdvanced), send_to: senders }))
-        }, 
-        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::InvalidStateAction}) => {
-            (Outcome::Error(Error{message: msg, kind: ErrorKind::InvalidStateAction}), None)
-        }, 
-        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::UnresolvedCombatant}) => {
-            (Outcome::Error(Error{message: msg, kind: ErrorKind::CannotAdvanceTurn}), None)
-        },
-        Err(GameError{msg, kind: crate::tracker::game::ErrorKind::EndOfInitiative}) => {
-            (Outcome::Error(Error{message: msg, kind: ErrorKind::NoEventsLeft}), None)
-        },
-        _ => unreachable!("The other game ErrorKind types should not exist.")
+    match game.define_roll_macro(definition.character_id, definition.roll_macro.clone())
+    {
+        Ok(_) => Outcome::RollMacroDefined,
+        Err(err) => action_error(err),
     }
 }
 
-fn take_action(registry: &mut GameRegistry, action: &Action, authority: &Authority) -> (Outcome, Option<Notification>)
+fn roll_dice(registry: &mut GameRegistry, dice_roll: &DiceRoll, authority: &Authority) -> Outcome
 {
-    debug!("Started take_action()");
-    let (game, game_id, _) = match authority.resource_role() 
+    let game = match owned_character_game(registry, &dice_roll.character_id, authority)
     {
-        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => {
-            debug!("Authority for player {} on game {} is RoleGM or RolePlayer", player_id, game_id);
-            if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(&action.character_id))
-            {
-                debug!("Player {} owns character {} and may take action.", player_id, action.character_id);
-                let Some(game) = registry.get_mut_game(game_id)
-                else {return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}), None)};
-                (game, game_id, player_id)
-            }
-            else {
-                debug!("Player {} does not own character {} and may not take the action.", player_id, action.character_id);
-                return (Outcome::Error(Error {message: String::from("Only the owner of a character may take an action for it."), kind: ErrorKind::UnauthorizedAction}), None);
-            }
-        }
-        _ => return (Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to act on."), kind: ErrorKind::UnauthorizedAction}), None)
+        Ok(game) => game,
+        Err(outcome) => return outcome,
     };
 
-    debug!("Game found.  Attempting to take the action.");
+    let mut rng = rand::thread_rng();
+    let rolled = match &dice_roll.roll
+    {
+        RollSpec::Macro(name) => game.roll_macro(&mut rng, dice_roll.character_id, name),
+        RollSpec::Pool { label, dice } => game.roll_dice(&mut rng, dice_roll.character_id, label, *dice),
+    };
 
-    match game.take_action(action.character_id, action.action)
+    match rolled
     {
-        Ok(_) => 
-        {
-            debug!("Action successful.  Gathering players to notify...");
-            let notification = registry.gm_sender(game_id)
-                .map(|sender| {
-                    let mut senders = Vec::with_capacity(1);
-                    senders.push(sender);
-                    Notification { change_type: Arc::from(WhatChanged::PlayerActed), send_to:  senders}
-                });
-            (Outcome::ActionTaken, notification)
-        },
-        Err(err) => 
-        {
-            debug!("Action unsuccessful.  Categorizing error for message: {}", err.msg);
-            match err.kind
-            {
-                crate::tracker::game::ErrorKind::InvalidStateAction => {
-                    (Outcome::Error(Error{message: err.msg, kind: ErrorKind::InvalidStateAction}), None)
-                },
-                crate::tracker::game::ErrorKind::UnknownCastId => 
-                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCharacter}), None)},
-                crate::tracker::game::ErrorKind::EndOfInitiative => 
-                    {(Outcome::Error(Error{message:err.msg, kind: ErrorKind::CannotAdvanceTurn}), None)},
-                crate::tracker::game::ErrorKind::NoAction => 
-                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoActionLeft}), None)},
-                crate::tracker::game::ErrorKind::UnresolvedCombatant => 
-                    {(Outcome::Error(Error{message: err.msg, kind: ErrorKind::NotCharactersTurn}), None)},
-                _ => {unreachable!("Should not be called.")}
-            }
-        },
+        Ok(record) => Outcome::Rolled(record),
+        Err(err) => action_error(err),
     }
 }
-fn list_current_turn_events(game_registry: &mut GameRegistry, authority: &Authority) -> Outcome
+
+// Table talk goes to everyone in the game; a whisper goes to its target and, since the GM reads everything, to the GM as well.
+fn send_chat(registry: &mut GameRegistry, message: &ChatMessage, authority: &Authority) -> (Outcome, Option<Notification>)
 {
-    let game = match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
-        {
-            let Some(game) = game_registry.get_game(game_id)
-            else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            game
-        },
-        _ => {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
-        }
+    let (player_id, game_id) = match authority.resource_role() {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => (*player_id, *game_id),
+        _ => return (Outcome::Error(Error {message: String::from("Only the game's GM and players may chat in it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}), None)
     };
 
-    Outcome::MatchingEventsAre(game.currently_up())
-}
+    if message.text.trim().is_empty()
+    {
+        return (Outcome::Error(Error {message: String::from("A chat message must have some text in it."), kind: ErrorKind::EmptyMessage, context: ErrorContext::default()}), None);
+    }
 
-fn list_unresolved_events(registry: &GameRegistry, authority: &Authority) -> Outcome
-{
-    let game = match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
+    if let ChatAudience::Player(target) = message.audience
+    {
+        if !registry.game_has_player(&game_id, &target)
         {
-            let Some(game) = registry.get_game(game_id)
-            else {return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            game
-        },
-        _ => {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            return (Outcome::Error(Error {message: String::from("The whisper's target is not a player in this game."), kind: ErrorKind::UnknownId, context: ErrorContext::default()}), None);
         }
+    }
+
+    let gm_id = registry.gm_id(&game_id).copied();
+    let recipients: Vec<PlayerId> = match message.audience
+    {
+        ChatAudience::Table => registry.players_by_game(&game_id).map_or(Vec::new(), |players| players.iter().copied().collect()),
+        ChatAudience::Gm => gm_id.into_iter().collect(),
+        ChatAudience::Player(target) => gm_id.into_iter().chain(std::iter::once(target)).collect(),
     };
+    let senders = recipients.iter()
+        .filter(|recipient| **recipient != player_id)
+        .filter_map(|recipient| registry.get_player_sender(recipient))
+        .collect::<Vec<Sender<Arc<WhatChanged>>>>();
 
-    Outcome::MatchingEventsAre(game.waiting_for())
+    let line = ChatLine { from: player_id, audience: message.audience, text: message.text.clone() };
+    let Some(game) = registry.get_mut_game(&game_id)
+    else { return (Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}), None) };
+    let sequence = game.record_chat(line.clone());
+
+    (Outcome::ChatSent(sequence), Some(Notification { game_id: Some(game_id), change_type: Arc::from(WhatChanged::Chat(line)), send_to: senders, directed: Vec::new() }))
 }
 
-fn list_next_turn_events(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+fn chat_log(registry: &GameRegistry, authority: &Authority) -> Outcome
 {
     match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) => 
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) | Role::RoleObserver(player_id, game_id) =>
         {
             let Some(game) = registry.get_game(game_id)
-            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            Outcome::MatchingEventsAre(game.on_deck())
-        },
-        _ => 
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::ChatLog(game.chat_visible_to(*player_id, registry.is_gm(player_id, game_id)))
+        }
+        _ =>
         {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            Outcome::Error(Error {message: String::from("Only registered players and observers may read the game's chat."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
     }
-    
 }
 
-fn list_all_events_by_id_this_pass(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+fn override_passes(registry: &mut GameRegistry, pass_override: &PassOverride, authority: &Authority) -> Outcome
 {
     match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        Role::RoleGM(_, game_id) =>
         {
-            let Some(game) = registry.get_game(game_id)
-            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            Outcome::MatchingEventsById(game.collect_all_remaining_events())
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.override_initiative_passes(pass_override.character_id, pass_override.passes)
+            {
+                Ok(_) => Outcome::PassesOverridden,
+                Err(err) => action_error(err),
+            }
         }
-        _ => 
+        _ =>
         {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            Outcome::Error(Error {message: String::from("Only the GM may override a combatant's initiative passes."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
     }
-    
 }
 
-fn next_initiative(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+fn soak_pool(registry: &GameRegistry, query: &SoakQuery, authority: &Authority) -> Outcome
 {
-
     match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) =>
         {
             let Some(game) = registry.get_game(game_id)
-            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            Outcome::InitiativeIs(game.get_next_init())
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.soak_pool(query.character_id, query.armor_test, query.armor_pen, query.bypass_armor)
+            {
+                Ok(pool) => Outcome::SoakPoolIs(pool),
+                Err(err) => action_error(err),
+            }
         }
         _ =>
         {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            Outcome::Error(Error {message: String::from("Only the GM and players may ask for a soak pool."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
     }
 }
 
-fn current_initiative(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+fn degrade_armor(registry: &mut GameRegistry, damage: &ArmorDamage, authority: &Authority) -> Outcome
 {
     match authority.resource_role() {
-        Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
+        Role::RoleGM(_, game_id) =>
         {
-            let Some(game) = registry.get_game(game_id)
-            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            Outcome::InitiativeIs(game.get_current_init())
+            let Some(game) = registry.get_mut_game(game_id)
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+
+            match game.degrade_armor(damage.character_id, damage.armor_index, damage.points)
+            {
+                Ok(_) => Outcome::ArmorDegraded,
+                Err(err) => action_error(err),
+            }
         }
         _ =>
         {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            Outcome::Error(Error {message: String::from("Only the GM may degrade a character's armor."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
     }
 }
 
-fn remaining_initiatives_are(registry: &mut GameRegistry, authority: &Authority) -> Outcome
+fn list_engagements(registry: &GameRegistry, authority: &Authority) -> Outcome
 {
     match authority.resource_role() {
         Role::RoleGM(_, game_id) | Role::RolePlayer(_, game_id) | Role::RoleObserver(_, game_id) =>
         {
             let Some(game) = registry.get_game(game_id)
-            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame}) };
-            Outcome::InitiativesAre(game.get_all_remaining_initiatives())
+            else { return Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}) };
+            Outcome::EngagementsAre(game.engagements())
         }
         _ =>
         {
-            return Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction});
+            Outcome::Error(Error {message: String::from("Only registered players and observers may view game events."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()})
         }
     }
-    
+}
+
+// Resolves the game for a request that acts on behalf of a character, provided the requester owns that character.
+fn owned_character_game<'a>(registry: &'a mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Result<&'a mut Game, Outcome>
+{
+    match authority.resource_role() 
+    {
+        Role::RoleGM(player_id, game_id) | Role::RolePlayer(player_id, game_id) => {
+            if registry.characters_by_player(game_id, player_id).map_or(false, |chars| chars.contains(character_id))
+            {
+                registry.get_mut_game(game_id)
+                    .ok_or(Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}))
+            }
+            else {
+                debug!("Player {} does not own character {}.", player_id, character_id);
+                Err(Outcome::Error(Error {message: String::from("Only the owner of a character may act for it."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}))
+            }
+        }
+        _ => Err(Outcome::Error(Error{message: String::from("Unregistered or observing players have no character to act on."), kind: ErrorKind::UnauthorizedAction, context: ErrorContext::default()}))
+    }
+}
+
+// As owned_character_game(), but the GM may act on any character in their game.
+fn gm_or_owner_game<'a>(registry: &'a mut GameRegistry, character_id: &CharacterId, authority: &Authority) -> Result<&'a mut Game, Outcome>
+{
+    if let Role::RoleGM(_, game_id) = authority.resource_role()
+    {
+        return registry.get_mut_game(game_id)
+            .ok_or(Outcome::Error(Error {message: String::from("The game ID does not resolve to a running game."), kind: ErrorKind::NoMatchingGame, context: ErrorContext::default()}));
+    }
+
+    owned_character_game(registry, character_id, authority)
+}
+
+// Maps the errors Game produces for action-taking into runner errors, the same way take_action() does.
+fn action_error(err: GameError) -> Outcome
+{
+    debug!("Action unsuccessful: {}", err.msg);
+    match err.kind
+    {
+        GameErrorKind::InvalidStateAction => Outcome::Error(Error{context: ErrorContext::from_game_error(&err), message: err.msg, kind: ErrorKind::InvalidStateAction}),
+        GameErrorKind::UnknownCastId => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCharacter, context: ErrorContext::default()}),
+        GameErrorKind::EndOfInitiative => Outcome::Error(Error{message: err.msg, kind: ErrorKind::CannotAdvanceTurn, context: ErrorContext::default()}),
+        GameErrorKind::NoAction => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoActionLeft, context: ErrorContext::default()}),
+        GameErrorKind::UnresolvedCombatant => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NotCharactersTurn, context: ErrorContext::default()}),
+        GameErrorKind::UnknownRollMacro => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchRollMacro, context: ErrorContext::default()}),
+        GameErrorKind::NoEdgeLeft => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoEdgeLeft, context: ErrorContext::default()}),
+        GameErrorKind::NothingToRewind => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NothingToRewind, context: ErrorContext::default()}),
+        GameErrorKind::UnknownCheckpoint => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCheckpoint, context: ErrorContext::default()}),
+        GameErrorKind::UnknownEncounter => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchEncounter, context: ErrorContext::default()}),
+        GameErrorKind::InvalidHouseRule => Outcome::Error(Error{message: err.msg, kind: ErrorKind::InvalidHouseRule, context: ErrorContext::default()}),
+        GameErrorKind::UnknownHouseRule => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchHouseRule, context: ErrorContext::default()}),
+        GameErrorKind::UnknownMatrixTarget => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchMatrixTarget, context: ErrorContext::default()}),
+        GameErrorKind::InvalidPatch => Outcome::Error(Error{message: err.msg, kind: ErrorKind::InvalidPatch, context: ErrorContext::default()}),
+        GameErrorKind::InitiativeOutOfRange => Outcome::Error(Error{message: err.msg, kind: ErrorKind::InitiativeOutOfRange, context: ErrorContext::default()}),
+        GameErrorKind::InvalidCatalogAction => Outcome::Error(Error{message: err.msg, kind: ErrorKind::InvalidCatalogAction, context: ErrorContext::default()}),
+        GameErrorKind::UnknownCatalogAction => Outcome::Error(Error{message: err.msg, kind: ErrorKind::NoSuchCatalogAction, context: ErrorContext::default()}),
+        GameErrorKind::OutOfAmmo => Outcome::Error(Error{message: err.msg, kind: ErrorKind::OutOfAmmo, context: ErrorContext::default()}),
+        _ => Outcome::Error(Error{message: err.msg, kind: ErrorKind::Unexpected, context: ErrorContext::default()}),
+    }
 }
@@ -0,0 +1,158 @@
+use tracing::debug;
+
+use super::{Error, registry::GameRegistry, authority::Authority, dispatcher::Outcome, notifier::Notification};
+
+// Extension point around the dispatcher.  Anything that cuts across requests - auditing, house rules, achievements - registers a hook
+// with the runner instead of growing another arm in dispatch_message2.  Hooks see every request, batched ones included, in the order
+// they were registered.
+
+pub enum Verdict
+{
+    Proceed,
+    // Stop the request before it touches the game; the error goes back to the sender in place of an outcome.
+    Veto(Error),
+}
+
+pub trait DispatchHook: Send
+{
+    fn name(&self) -> &'static str;
+
+    // Called before the request is dispatched.  The registry is read-only here: a hook decides, the dispatcher acts.
+    fn before(&mut self, _registry: &GameRegistry, _authority: &Authority) -> Verdict
+    {
+        Verdict::Proceed
+    }
+
+    // Called with the dispatcher's outcome, which the hook may pass on as-is or replace.  Errors come through here too.
+    fn after(&mut self, _registry: &GameRegistry, _authority: &Authority, outcome: Outcome) -> Outcome
+    {
+        outcome
+    }
+}
+
+pub struct HookChain
+{
+    hooks: Vec<Box<dyn DispatchHook>>,
+}
+
+impl HookChain
+{
+    pub fn new() -> HookChain
+    {
+        HookChain { hooks: Vec::new() }
+    }
+
+    pub fn register(&mut self, hook: Box<dyn DispatchHook>)
+    {
+        debug!("Registering dispatch hook {}.", hook.name());
+        self.hooks.push(hook);
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.hooks.len()
+    }
+
+    // The first veto wins and the later hooks never see the request.  A vetoed request sends no notifications.
+    pub fn run<F>(&mut self, registry: &mut GameRegistry, authority: &Authority, dispatch: F) -> (Outcome, Option<Notification>)
+        where F: FnOnce(&mut GameRegistry, &Authority) -> (Outcome, Option<Notification>)
+    {
+        for hook in self.hooks.iter_mut()
+        {
+            if let Verdict::Veto(err) = hook.before(registry, authority)
+            {
+                debug!("Hook {} vetoed {}: {}", hook.name(), authority.request().name(), err.message);
+                return (Outcome::Error(err), None);
+            }
+        }
+
+        let (mut outcome, notification) = dispatch(registry, authority);
+
+        for hook in self.hooks.iter_mut()
+        {
+            outcome = hook.after(registry, authority, outcome);
+        }
+
+        (outcome, notification)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    use tokio::sync::{mpsc::channel, oneshot};
+    use uuid::Uuid;
+
+    use crate::gamerunner::{ErrorKind, Error, ErrorContext, game_runner_with_hooks, dispatcher::{Message, Outcome, Request, ChatMessage}, registry::GameRegistry, authority::Authority};
+    use crate::tracker::journal::ChatAudience;
+
+    use super::{DispatchHook, HookChain, Verdict};
+
+    struct NoChat;
+
+    impl DispatchHook for NoChat
+    {
+        fn name(&self) -> &'static str { "no-chat" }
+
+        fn before(&mut self, _registry: &GameRegistry, authority: &Authority) -> Verdict
+        {
+            match authority.request()
+            {
+                Request::Chat(_) => Verdict::Veto(Error { message: String::from("Chat is disabled at this table."), kind: ErrorKind::Vetoed, context: ErrorContext::default() }),
+                _ => Verdict::Proceed,
+            }
+        }
+    }
+
+    struct Counter(Arc<AtomicUsize>);
+
+    impl DispatchHook for Counter
+    {
+        fn name(&self) -> &'static str { "counter" }
+
+        fn after(&mut self, _registry: &GameRegistry, _authority: &Authority, outcome: Outcome) -> Outcome
+        {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            outcome
+        }
+    }
+
+    async fn send(runner: &tokio::sync::mpsc::Sender<Message>, player_id: Option<Uuid>, game_id: Option<Uuid>, msg: Request) -> Outcome
+    {
+        let (reply_channel, reply) = oneshot::channel();
+        assert!(runner.send(Message { player_id, game_id, reply_channel, msg }).await.is_ok());
+        reply.await.unwrap()
+    }
+
+    #[tokio::test]
+    pub async fn a_hook_can_veto_a_request_and_observers_see_everything_that_was_dispatched()
+    {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let mut hooks = HookChain::new();
+        hooks.register(Box::new(NoChat));
+        hooks.register(Box::new(Counter(seen.clone())));
+
+        let (runner, receiver) = channel(4);
+        tokio::spawn(async move { game_runner_with_hooks(receiver, hooks).await; });
+
+        let gm = match send(&runner, None, None, Request::NewPlayer).await
+        {
+            Outcome::NewPlayer(player) => player.player_id,
+            _ => panic!("Registration should return the new player."),
+        };
+        let game_id = match send(&runner, Some(gm), Some(Uuid::new_v4()), Request::New).await
+        {
+            Outcome::Created(game_id) => game_id,
+            _ => panic!("The game should have been created."),
+        };
+
+        let chat = Request::Chat(ChatMessage { audience: ChatAudience::Table, text: String::from("Anyone there?") });
+        assert!(matches!(send(&runner, Some(gm), Some(game_id), chat).await, Outcome::Error(Error { kind: ErrorKind::Vetoed, .. })));
+        assert!(matches!(send(&runner, Some(gm), Some(game_id), Request::QueryChat).await, Outcome::ChatLog(log) if log.is_empty()));
+
+        // The vetoed chat never reached the dispatcher, so the counter only saw the other three requests.
+        assert_eq!(3, seen.load(Ordering::SeqCst));
+    }
+}
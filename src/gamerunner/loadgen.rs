@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::{mpsc::Sender, oneshot::channel};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::tracker::{character::{Character, Metatypes}, journal::ChatAudience};
+
+use super::{CharacterId, GameId, PlayerId, dispatcher::{Message, Outcome, Request, ChatMessage}};
+
+// Synthetic traffic for the runner loop, shared by the criterion benchmarks and the load-test binary.  Games are seeded the way a real
+// table would be (GM, players joining, characters, combat declared, initiative phase open) and then every player hammers the runner
+// with the mix of queries and table chat a client produces while a fight is going on.
+
+pub struct LoadProfile
+{
+    pub games: usize,
+    pub players_per_game: usize,
+    pub requests_per_player: usize,
+}
+
+pub struct SeededGame
+{
+    pub game_id: GameId,
+    pub gm: PlayerId,
+    pub players: Vec<(PlayerId, CharacterId)>,
+}
+
+pub struct LoadReport
+{
+    pub requests: usize,
+    pub errors: usize,
+    pub elapsed: Duration,
+    pub latencies: Vec<Duration>,
+}
+
+impl LoadReport
+{
+    pub fn throughput(&self) -> f64
+    {
+        self.requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    // Nearest-rank percentile over the recorded round trips; p is in 0.0..=100.0.
+    pub fn percentile(&self, p: f64) -> Duration
+    {
+        if self.latencies.is_empty()
+        {
+            return Duration::ZERO;
+        }
+
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+pub async fn send(runner: &Sender<Message>, player_id: Option<PlayerId>, game_id: Option<GameId>, request: Request) -> Result<Outcome, String>
+{
+    let (reply_channel, reply) = channel::<Outcome>();
+    let msg = Message { player_id, game_id, reply_channel, msg: request };
+
+    if runner.send(msg).await.is_err()
+    {
+        return Err(String::from("The game runner has shut down."));
+    }
+
+    reply.await.map_err(|_| String::from("The game runner dropped the reply channel."))
+}
+
+// Registers a player and keeps their notification channel drained, as a connected client would; otherwise every broadcast would pile up
+// in an inbox and the numbers would measure that instead.
+async fn connected_player(runner: &Sender<Message>) -> Result<PlayerId, String>
+{
+    match send(runner, None, None, Request::NewPlayer).await?
+    {
+        Outcome::NewPlayer(mut new_player) =>
+        {
+            tokio::spawn(async move { while new_player.player_1_receiver.recv().await.is_some() {} });
+            Ok(new_player.player_id)
+        },
+        _ => Err(String::from("Registering a player did not return a player id.")),
+    }
+}
+
+pub async fn seed_game(runner: &Sender<Message>, players: usize) -> Result<SeededGame, String>
+{
+    let gm = connected_player(runner).await?;
+    let game_id = match send(runner, Some(gm), Some(Uuid::new_v4()), Request::New).await?
+    {
+        Outcome::Created(game_id) => game_id,
+        _ => return Err(String::from("Creating a game did not return its id.")),
+    };
+
+    let mut seated = Vec::with_capacity(players);
+    for seat in 0..players
+    {
+        let player_id = connected_player(runner).await?;
+        if let Outcome::Error(err) = send(runner, Some(player_id), Some(game_id), Request::JoinGame).await?
+        {
+            return Err(err.message);
+        }
+
+        let character = Character::new_pc(Metatypes::Human, String::from(format!("Runner {}", seat)));
+        match send(runner, Some(player_id), Some(game_id), Request::AddCharacter(character)).await?
+        {
+            Outcome::CharacterAdded((_, character_id)) => seated.push((player_id, character_id)),
+            Outcome::Error(err) => return Err(err.message),
+            _ => return Err(String::from("Adding a character did not return its id.")),
+        }
+    }
+
+    let combatants = seated.iter().map(|(_, character_id)| *character_id).collect();
+    for request in [Request::StartCombat(combatants), Request::BeginInitiativePhase]
+    {
+        if let Outcome::Error(err) = send(runner, Some(gm), Some(game_id), request).await?
+        {
+            return Err(err.message);
+        }
+    }
+
+    debug!("Seeded game {} with {} players.", game_id, players);
+    Ok(SeededGame { game_id, gm, players: seated })
+}
+
+// The request a client would plausibly send as its nth call: mostly polling, with table chat mixed in to exercise notification fan-out.
+pub fn synthetic_request(n: usize, character_id: CharacterId) -> Request
+{
+    match n % 5
+    {
+        0 => Request::GetPcCast,
+        1 => Request::GetCharacter(character_id),
+        2 => Request::QueryChat,
+        3 => Request::QueryEngagements,
+        _ => Request::Chat(ChatMessage { audience: ChatAudience::Table, text: String::from(format!("Synthetic message {}", n)) }),
+    }
+}
+
+// Every player in every game runs concurrently, each waiting for one reply before sending the next request.
+pub async fn run_load(runner: &Sender<Message>, games: &[SeededGame], requests_per_player: usize) -> LoadReport
+{
+    let started = Instant::now();
+    let mut clients = Vec::new();
+
+    for game in games
+    {
+        for (player_id, character_id) in game.players.iter().copied()
+        {
+            let runner = runner.clone();
+            let game_id = game.game_id;
+
+            clients.push(tokio::spawn(async move {
+                let mut latencies = Vec::with_capacity(requests_per_player);
+                let mut errors = 0;
+
+                for n in 0..requests_per_player
+                {
+                    let sent = Instant::now();
+                    match send(&runner, Some(player_id), Some(game_id), synthetic_request(n, character_id)).await
+                    {
+                        Ok(Outcome::Error(_)) | Err(_) => errors += 1,
+                        Ok(_) => {},
+                    }
+                    latencies.push(sent.elapsed());
+                }
+
+                (latencies, errors)
+            }));
+        }
+    }
+
+    let mut report = LoadReport { requests: 0, errors: 0, elapsed: Duration::ZERO, latencies: Vec::new() };
+    for client in clients
+    {
+        if let Ok((latencies, errors)) = client.await
+        {
+            report.requests += latencies.len();
+            report.errors += errors;
+            report.latencies.extend(latencies);
+        }
+    }
+    report.elapsed = started.elapsed();
+
+    report
+}
+
+#[cfg(test)]
+mod tests
+{
+    use tokio::sync::mpsc::channel;
+
+    use crate::gamerunner::game_runner;
+
+    use super::{seed_game, run_load};
+
+    #[tokio::test]
+    pub async fn a_small_load_run_completes_every_request_without_errors()
+    {
+        let (runner, receiver) = channel(16);
+        tokio::spawn(async move { game_runner(receiver).await; });
+
+        let games = vec![seed_game(&runner, 3).await.unwrap(), seed_game(&runner, 2).await.unwrap()];
+        let report = run_load(&runner, &games, 10).await;
+
+        assert_eq!(50, report.requests);
+        assert_eq!(0, report.errors);
+        assert!(report.percentile(50.0) <= report.percentile(99.0));
+    }
+}
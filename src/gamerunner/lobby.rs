@@ -0,0 +1,182 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use crate::tracker::names::Name;
+
+use super::{GameId, cast_limits::CastLimits};
+
+// What the lobby shows of a game.  The registry keeps one per game up to date as players come and go and messages reach it, so listing
+// games reads these rather than visiting every game.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GameSummary
+{
+    pub name: Name,
+    pub gm_name: Name,
+    // Everyone seated at the game, the GM included.
+    pub players: usize,
+    // Characters brought by the players; whatever the GM added is not counted.
+    pub characters: usize,
+    pub phase: String,
+    pub in_combat: bool,
+    // Anyone may join a game, so these are all that stand between a newcomer and the table: how many characters they may bring, and
+    // under what names.
+    pub cast_limits: CastLimits,
+    pub last_activity: Instant,
+}
+
+// Kept in game id order, so pages of a listing come out the same from one request to the next.
+#[derive(Default)]
+pub struct LobbyIndex
+{
+    summaries: BTreeMap<GameId, GameSummary>,
+    order: ListingOrder,
+    // Moves on whenever a game is added or removed, so anything mirroring the lobby can tell its set of games has changed even when a
+    // deletion and a creation leave the count where it was.
+    generation: u64,
+}
+
+impl LobbyIndex
+{
+    pub fn record(&mut self, game_id: GameId, summary: GameSummary)
+    {
+        self.order.place(game_id, summary.in_combat);
+        if self.summaries.insert(game_id, summary).is_none()
+        {
+            self.generation += 1;
+        }
+    }
+
+    pub fn remove(&mut self, game_id: &GameId)
+    {
+        self.order.remove(game_id);
+        if self.summaries.remove(game_id).is_some()
+        {
+            self.generation += 1;
+        }
+    }
+
+    pub fn generation(&self) -> u64
+    {
+        self.generation
+    }
+
+    pub fn get(&self, game_id: &GameId) -> Option<&GameSummary>
+    {
+        self.summaries.get(game_id)
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.summaries.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.summaries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&GameId, &GameSummary)>
+    {
+        self.summaries.iter()
+    }
+
+    // One page of the public listing: every game when in_combat is None, otherwise only those in or out of a fight.
+    pub fn page(&self, in_combat: Option<bool>, page: usize, page_size: usize) -> Vec<(GameId, GameSummary)>
+    {
+        self.order.page(in_combat, page, page_size).iter()
+            .filter_map(|game_id| self.summaries.get(game_id).map(|summary| (*game_id, summary.clone())))
+            .collect()
+    }
+}
+
+// The game ids of the public listings, each in id order, so that a page is a slice off the front rather than a walk past every game on
+// the pages before it.  A game only moves between lists when a fight starts or ends.
+#[derive(Default, Clone)]
+pub struct ListingOrder
+{
+    all: Vec<GameId>,
+    open: Vec<GameId>,
+    in_combat: Vec<GameId>,
+}
+
+impl ListingOrder
+{
+    pub fn place(&mut self, game_id: GameId, in_combat: bool)
+    {
+        insert_sorted(&mut self.all, game_id);
+        let (into, out_of) = if in_combat { (&mut self.in_combat, &mut self.open) } else { (&mut self.open, &mut self.in_combat) };
+        remove_sorted(out_of, &game_id);
+        insert_sorted(into, game_id);
+    }
+
+    pub fn remove(&mut self, game_id: &GameId)
+    {
+        for ids in [&mut self.all, &mut self.open, &mut self.in_combat]
+        {
+            remove_sorted(ids, game_id);
+        }
+    }
+
+    pub fn page(&self, in_combat: Option<bool>, page: usize, page_size: usize) -> &[GameId]
+    {
+        let ids = match in_combat
+        {
+            None => &self.all,
+            Some(false) => &self.open,
+            Some(true) => &self.in_combat,
+        };
+        let start = page.saturating_mul(page_size).min(ids.len());
+        &ids[start..start.saturating_add(page_size).min(ids.len())]
+    }
+}
+
+fn insert_sorted(ids: &mut Vec<GameId>, game_id: GameId)
+{
+    if let Err(at) = ids.binary_search(&game_id)
+    {
+        ids.insert(at, game_id);
+    }
+}
+
+fn remove_sorted(ids: &mut Vec<GameId>, game_id: &GameId)
+{
+    if let Ok(at) = ids.binary_search(game_id)
+    {
+        ids.remove(at);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use super::ListingOrder;
+
+    #[test]
+    pub fn listing_order_pages_each_listing_in_id_order_and_moves_games_as_fights_start_and_end()
+    {
+        let mut ids = (0..5).map(|_| Uuid::new_v4()).collect::<Vec<Uuid>>();
+        let mut order = ListingOrder::default();
+        for (index, game_id) in ids.iter().enumerate()
+        {
+            order.place(*game_id, index % 2 == 0);
+        }
+        ids.sort();
+
+        assert_eq!(&ids[0..2], order.page(None, 0, 2));
+        assert_eq!(&ids[4..5], order.page(None, 2, 2));
+        assert!(order.page(None, 3, 2).is_empty());
+        assert!(order.page(None, usize::MAX, usize::MAX).is_empty());
+        assert_eq!(5, order.page(Some(false), 0, usize::MAX).len() + order.page(Some(true), 0, usize::MAX).len());
+
+        let moved = order.page(Some(true), 0, 1)[0];
+        order.place(moved, false);
+        assert!(!order.page(Some(true), 0, usize::MAX).contains(&moved));
+        assert!(order.page(Some(false), 0, usize::MAX).contains(&moved));
+        assert_eq!(5, order.page(None, 0, usize::MAX).len());
+
+        order.remove(&moved);
+        assert!(!order.page(None, 0, usize::MAX).contains(&moved));
+        assert!(!order.page(Some(false), 0, usize::MAX).contains(&moved));
+    }
+}
@@ -1,10 +1,15 @@
-use log::{debug, error};
-use tokio::sync::mpsc::{Receiver};
+use std::{sync::Arc, time::Duration};
+
+use tracing::{debug, error, warn, info_span, Instrument};
+use tokio::{sync::{Mutex, oneshot, mpsc::{Receiver, Sender}}, time::MissedTickBehavior};
 use uuid::Uuid;
 
-use crate::gamerunner::{registry::GameRegistry, authority::authorize};
-use notifier::{/*into_notification, notify_players,*/ WhatChanged};
-use dispatcher::dispatch_message2;
+use crate::gamerunner::{registry::{GameRegistry, SavedGame}, authority::authorize};
+use crate::tracker::game::{GameError, TimedEvent};
+use notifier::{/*into_notification, notify_players,*/ WhatChanged, InitiativeEntry, coalesce};
+use dispatcher::{dispatch_isolated, with_error_context, announce_triggers, announce_skipped_turns, sync_combatants, review_absences, review_initiative_deadline, review_clocks, Request, Outcome};
+use hooks::HookChain;
+use snapshot::QuerySnapshots;
 
 use self::dispatcher::Message;
 
@@ -12,63 +17,281 @@ pub mod registry;
 pub mod authority;
 pub mod dispatcher;
 pub mod notifier;
+pub mod loadgen;
+pub mod hooks;
+pub mod simulation;
+pub mod absence;
+pub mod deadline;
+pub mod notes;
+pub mod cast_limits;
+pub mod sync;
+pub mod lobby;
+pub mod snapshot;
+pub mod replay;
+pub mod allowed;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub async fn game_runner(message_queue: Receiver<Message>)
+{
+    game_runner_with_hooks(message_queue, HookChain::new()).await;
+}
+
+pub async fn game_runner_with_hooks(message_queue: Receiver<Message>, hooks: HookChain)
+{
+    game_runner_configured(message_queue, hooks, PLAYER_CHANNEL_CAPACITY, QuerySnapshots::default()).await;
+}
+
+// Every message finished with is republished to the snapshots, for the read-only queries that do not come through here.
+pub async fn game_runner_configured(message_queue: Receiver<Message>, hooks: HookChain, player_channel_capacity: usize, snapshots: QuerySnapshots)
+{
+    run_shared(RunnerState::new(message_queue, hooks, player_channel_capacity), snapshots).await;
+}
+
+// Everything the runner works on, kept apart from the task running it so that a supervisor can hand it to a new task when the runner
+// panics.  The runner holds the lock for as long as it runs; the lock does not poison, and is let go as the panicking task unwinds.
+pub struct RunnerState
+{
+    message_queue: Receiver<Message>,
+    directory: GameRegistry,
+    hooks: HookChain,
+    // The game the message being handled was sent to, and the game as it stood before the message was dispatched to it - None for a
+    // read, which has nothing to undo.  Still set when a runner starts means the last one panicked partway through it.
+    in_flight: Option<(GameId, Option<SavedGame>)>,
+}
+
+pub type SharedRunner = Arc<Mutex<RunnerState>>;
+
+impl RunnerState
+{
+    pub fn new(message_queue: Receiver<Message>, hooks: HookChain, player_channel_capacity: usize) -> SharedRunner
+    {
+        let directory = GameRegistry::with_player_channel_capacity(player_channel_capacity);
+        Arc::new(Mutex::new(RunnerState { message_queue, directory, hooks, in_flight: None }))
+    }
+}
 
-pub async fn game_runner(mut message_queue: Receiver<Message>)
+// Runs until the queue closes, on whatever registry the last runner left behind.  The games themselves carry on as they were, except
+// the one a panic interrupted, which is put back as it was before the message that panicked; the message is lost, as its sender was told
+// when the reply channel closed.
+pub async fn run_shared(state: SharedRunner, snapshots: QuerySnapshots)
 {
-    debug!("Game runner redux started.");
+    let mut state = state.lock().await;
+    let RunnerState { message_queue, directory, hooks, in_flight } = &mut *state;
+    debug!("Game runner redux started with {} dispatch hooks and {} games.", hooks.len(), directory.lobby().len());
 
-    let mut directory = GameRegistry::new();
+    if let Some((game_id, saved)) = in_flight.take()
+    {
+        match saved
+        {
+            Some(saved) =>
+            {
+                warn!("The game runner was restarted after panicking on a message to game {}; the game is put back as it was before it.", game_id);
+                directory.restore_game(&game_id, saved);
+            },
+            None => warn!("The game runner was restarted after panicking on a message to game {}, which had nothing saved to put back.", game_id),
+        }
+        snapshots.republish_all(directory);
+    }
 
     while let Some(message) = message_queue.recv().await
     {
         let (channel, player_id_opt, game_id_opt, request) = 
             (message.reply_channel, message.player_id, message.game_id, message.msg);
 
-        let mut_directory = &mut directory;
-        let authority = authorize(player_id_opt, game_id_opt, request, mut_directory);
-        // let (channel, game_id) = (message.reply_channel, message.game_id);
-        let (response, notify_opt) = dispatch_message2(mut_directory, &authority);
+        // Everything logged while handling this message carries the game, player and request, so one table's traffic can be pulled
+        // out of the rest; the span's close event gives the time spent on it.
+        let span = info_span!("dispatch", request = request.name(), game_id = ?game_id_opt, player_id = ?player_id_opt);
+        let (request_name, character_id) = (request.name(), request.character_id());
+        let heard_from = player_id_opt.filter(|_| request.counts_as_presence());
+        let read_only = request.leaves_game_untouched();
 
-        if let Some(notification) = notify_opt // = into_notification(&directory,&response, &authority)
+        if let Request::ReviewClocks = request
         {
-            let (message, sender_list) = (notification.change_type, notification.send_to);
+            let notifications = span.in_scope(|| {
+                let (due, notifications) = review_clocks(directory);
+                // A clock going off at an idle table is no sign of life there, so it does not count as activity.
+                for game_id in due
+                {
+                    directory.refresh_summary(&game_id, false);
+                    snapshots.republish(directory, Some(game_id));
+                }
+                notifications
+            });
+
+            async {
+                deliver(directory, coalesce(notifications)).await;
+                let _ = channel.send(Outcome::ClocksReviewed);
+            }.instrument(span).await;
+            continue;
+        }
+
+        *in_flight = game_id_opt.map(|game_id| (game_id, None));
+
+        let (review_notices, response, notify_opt) = span.in_scope(|| {
+            let mut_directory = &mut *directory;
+            let mut review_notices = review_absences(mut_directory, heard_from, game_id_opt);
+            review_notices.extend(review_initiative_deadline(mut_directory, game_id_opt));
+            // Saved after the clocks are reviewed, whose changes stand whatever becomes of the message.
+            if let Some((game_id, saved)) = in_flight.as_mut().filter(|_| !read_only)
+            {
+                *saved = mut_directory.save_game(game_id);
+            }
+            let authority = authorize(player_id_opt, game_id_opt, request, mut_directory);
+            let saved = in_flight.as_ref().and_then(|(game_id, saved)| saved.as_ref().map(|saved| (*game_id, saved)));
+            let (response, notify_opt) = dispatch_isolated(mut_directory, hooks, authority, saved);
+            let response = with_error_context(mut_directory, response, player_id_opt, game_id_opt, request_name, character_id);
+            let fired = announce_triggers(mut_directory, game_id_opt);
+            let skipped = announce_skipped_turns(mut_directory, game_id_opt);
+            // Building the combatant views is the dearest part of a message, and a read cannot have changed them; the clocks reviewed
+            // beforehand can, though, so a read that set one off still syncs.
+            let synced = if read_only && review_notices.is_empty() { None } else { sync_combatants(mut_directory, game_id_opt) };
+            if let Some(game_id) = game_id_opt
+            {
+                mut_directory.refresh_summary(&game_id, !read_only && !matches!(response, Outcome::Error(_)));
+            }
+            snapshots.republish(mut_directory, game_id_opt);
+            (review_notices, response, notify_opt.into_iter().chain(fired).chain(skipped).chain(synced))
+        });
+
+        async {
+            let notifications = review_notices.into_iter().chain(notify_opt).collect();
+            deliver(directory, coalesce(notifications)).await;
 
-            for sender in sender_list
+            if channel.send(response).is_err()
             {
-                // The sender's error variant is ignored.  If the send request errors out, that means that the recipient's channel has closed or 
-                // broken, and we really cannot fix that.  Right now, we do not provide a way to establish a new channel - but even when we do, 
-                // establishing a new channel will be at the discretion of the consumer.  We will just ignore the error and continue operating, 
-                // at least until we make this more robust.
-                sender.send(message.clone()).await;
+                error!("The return channel has dropped.");
             }
+        }.instrument(span).await;
+        *in_flight = None;
+    }
+}
+
+// Asks the runner to look over every game's clocks at each interval, for as long as it is taking messages.  A review that finds the queue
+// full waits its turn like anything else; ticks missed meanwhile are not made up.
+pub async fn run_review_clock(runner: Sender<Message>, interval: Duration)
+{
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop
+    {
+        ticks.tick().await;
+        let (reply_channel, reply) = oneshot::channel::<Outcome>();
+        if runner.send(Message { player_id: None, game_id: None, reply_channel, msg: Request::ReviewClocks }).await.is_err()
+        {
+            debug!("The game runner's queue has closed; the review clock has stopped.");
+            return;
         }
+        let _ = reply.await;
+    }
+}
 
-        if channel.send(response).is_err()
+async fn deliver(directory: &mut GameRegistry, deliveries: Vec<(Option<GameId>, Arc<WhatChanged>, Sender<Arc<WhatChanged>>)>)
+{
+    for (game_id, message, sender) in deliveries
+    {
+        // A closed channel means the recipient has gone offline.  Hold the notification in their inbox until they come back for it.
+        if let Err(failed) = sender.send(message).await
         {
-            error!("The return channel has dropped.");
+            match directory.player_for_sender(&sender)
+            {
+                Some(player_id) =>
+                {
+                    debug!("Player {} is offline; notification stored in their inbox.", player_id);
+                    let _ = directory.store_in_inbox(&player_id, game_id, failed.0);
+                    if directory.record_failed_delivery(&player_id)
+                    {
+                        report_unreachable(directory, player_id).await;
+                    }
+                },
+                None => error!("A notification was addressed to a channel no registered player holds; it has been dropped."),
+            }
         }
     }
 }
 
+// Tells the GM of every game the player is in that the player's channel has gone dead.  A GM who cannot be reached either gets it in
+// their inbox, and that failure is counted but not itself reported.
+async fn report_unreachable(directory: &mut GameRegistry, player_id: PlayerId)
+{
+    warn!("Player {}'s notification channel has closed.", player_id);
+    let game_ids: Vec<GameId> = directory.games_by_player(player_id).map_or(Vec::new(), |games| games.iter().copied().collect());
+
+    for game_id in game_ids
+    {
+        let Some(gm_id) = directory.gm_id(&game_id).copied()
+        else { continue };
+        let Some(gm_sender) = directory.gm_sender(&game_id)
+        else { continue };
+        if gm_id == player_id
+        {
+            continue;
+        }
+
+        if let Err(failed) = gm_sender.send(Arc::new(WhatChanged::PlayerUnreachable(player_id))).await
+        {
+            let _ = directory.store_in_inbox(&gm_id, Some(game_id), failed.0);
+            directory.record_failed_delivery(&gm_id);
+        }
+    }
+}
+// Notifications a player's channel holds unread before sends to it have to wait.
+pub const PLAYER_CHANNEL_CAPACITY: usize = 32;
 
 type PlayerId = Uuid;
 type GameId = Uuid;
 type CharacterId = Uuid;
 
+#[derive(Clone)]
 pub struct Error
 {
     pub message: String,
     pub kind: ErrorKind,
+    pub context: ErrorContext,
+}
+
+// What an error was about, for clients that want to point at the offending game or character and tests that want to check precisely.
+// Handlers fill in what only they know - mostly the state they needed the game in - and the runner adds the rest from the message.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ErrorContext
+{
+    pub game_id: Option<GameId>,
+    pub character_id: Option<CharacterId>,
+    pub request: Option<&'static str>,
+    pub expected_state: Option<String>,
+    pub actual_state: Option<String>,
 }
 
+impl ErrorContext
+{
+    pub fn expecting(state: &str) -> ErrorContext
+    {
+        ErrorContext { expected_state: Some(String::from(state)), ..ErrorContext::default() }
+    }
+
+    // Carries over the phase a game said it needed to be in, when that was why it refused.
+    pub fn from_game_error(err: &GameError) -> ErrorContext
+    {
+        ErrorContext { expected_state: err.expected.clone(), ..ErrorContext::default() }
+    }
+}
+
+// Who is acting now and who is on deck once the turn moves on, each with their initiative, so nobody has to ask again.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TurnAdvanced
 {
-    pub up: Vec<Uuid>,
-    pub on_deck: Vec<Uuid>,
+    // Changes when the turn rolled over into the next pass by itself.
+    pub pass: usize,
+    pub up: Vec<InitiativeEntry>,
+    pub on_deck: Vec<InitiativeEntry>,
+    // Timed events the GM scheduled sit in the order without a character, so they are listed apart.
+    pub events_up: Vec<TimedEvent>,
+    pub events_on_deck: Vec<TimedEvent>,
 }
 
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum ErrorKind
 {
     NotGameOwner,
@@ -83,6 +306,29 @@ pub enum ErrorKind
     NoEventsLeft,
     UnresolvedCombatant, 
     UnauthorizedAction,
+    ExceedsMovementRate,
+    EmptyMessage,
+    NoSuchRollMacro,
+    NoEdgeLeft,
+    NothingToRewind,
+    NoSuchCheckpoint,
+    NoSuchEncounter,
+    CharacterLimitReached,
+    CastFull,
+    DuplicateCharacterName,
+    InvalidHouseRule,
+    NoSuchHouseRule,
+    NoSuchMatrixTarget,
+    InvalidPatch,
+    InitiativeOutOfRange,
+    InvalidCatalogAction,
+    NoSuchCatalogAction,
+    OutOfAmmo,
+    DuplicateRoll,
+    UnbatchableRequest,
+    IdempotencyKeyReused,
+    Vetoed,
+    GameArchived,
     Unexpected,
 }
 
@@ -91,110 +337,49 @@ mod tests
 {
     use core::panic;
     use std::collections::HashMap;
+    use std::time::Duration;
 
 
-    use log::debug;
-    use tokio::sync::oneshot::{Sender as OneShotSender, Receiver};
+    use tracing::debug;
+    use tokio::sync::oneshot::Receiver;
     use tokio::sync::oneshot::channel;
     use tokio::sync::mpsc::channel as mpsc_channel;
     use tokio::sync::mpsc::Sender;
     use uuid::Uuid;
     
 
-    use crate::gamerunner::dispatcher::Action;
-    use crate::gamerunner::{game_runner, dispatcher::{Outcome, Request}};
+    use crate::gamerunner::dispatcher::{Action, ChatMessage, MacroDefinition, DiceRoll, RollSpec, DamageApplication, HealingApplication, HouseRuleScript, PlayerAbsence, EventSchedule, SittingOut, PhaseState};
+    use crate::gamerunner::absence::{AbsencePolicy, AbsentFallback};
+    use crate::tracker::{combat::{DamageType, DamageEvent}, gear::ArmorTestType, report::ReportScope, journal::{JournalEvent, JournalEventKind, JournalFilter}, encounter::{StagedEncounter, ScriptedTrigger, TriggerAction}, house_rules::HouseRuleEvent, rules::{Edition, ActionLabels, Healing, Recovery}, archetypes::Archetype};
+    use crate::tracker::character::{RollMacro, CharacterPatch, InitiativeFormula};
+    use crate::tracker::journal::ChatAudience;
+    use crate::tracker::catalog::{CatalogAction, Ammunition};
+    use crate::gamerunner::{dispatcher::{Outcome, Request}, ErrorContext};
     use crate::tracker::character::Character;
     use crate::tracker::character::Metatypes;
-    use crate::tracker::game::ActionType;
+    use crate::tracker::game::{ActionType, AfterPass, AvailableAction, VisibilityOptions, WoundDisclosure, TurnState, TimedEvent, SkipReason, Phase};
+    use crate::tracker::character::{WoundTier, WoundView};
     use crate::gamerunner::WhatChanged;
+    use crate::gamerunner::notifier::InitiativeEntry;
 
     use super::CharacterId;
     use super::ErrorKind;
     use super::GameId;
     use super::Message;
     use super::PlayerId;
-    use super::dispatcher::NewPlayer;
+    use super::dispatcher::{NewPlayer, Delivery};
     use super::dispatcher::Roll;
-
-    pub fn init() -> Sender<Message> {
-        let _ = env_logger::builder().is_test(true).try_init();
-        debug!("Logger should be active.");
-
-        debug!("Created multi-producer, single consumer channel");
-        let (sender, receiver) = mpsc_channel(1);
-
-        debug!("About to start game runner.");
-        tokio::spawn(async {game_runner(receiver).await;});
-
-        debug!("Runner started, returning.");
-        return sender;
-    }
-
-    pub async fn add_new_game(game_input_channel: &Sender<Message>) -> (PlayerId, GameId)
-    {
-        debug!("Starting add_new_game");
-        let gm: PlayerId;
-
-        let (mut game_sender, mut game_receiver) = channel();
-        let msg = Message { player_id: None, game_id: None, reply_channel: game_sender, msg: Request::NewPlayer};
-
-        debug!("Message to register new player done and sending.");
-
-        assert!(game_input_channel.send(msg).await.is_ok());
-        gm = match game_receiver.await {
-            Ok(Outcome::NewPlayer(player_id)) => player_id.player_id, 
-            _ => panic!("Should have received a NewPlayer object with the id and messaging channel.")
-        };
-
-        debug!("Message to register new player has been sent and OK received from response channel.  Player id: {}", gm);
-
-        (game_sender, game_receiver) = channel();
-        let msg = Message { player_id: Some(gm), game_id: Some(Uuid::new_v4()), reply_channel: game_sender, msg: Request::New };
-
-        debug!("Message to create new game done and about to send.");
-
-        match game_input_channel.send(msg).await {
-            Ok(_) => {
-                match game_receiver.await
-                {
-                    Ok(Outcome::Created(id)) => {
-                        debug!("Message has been accepted and new game {} has been created.", id);
-                        return (gm, id)
-                    },
-                    Ok(_) | Err(_) => {
-                        debug!("Message has been rejected.");
-                        panic!{"The oneshot channel closed while waiting for reply."}
-                    },
-                }
-            },
-            Err(_) => panic!("Game input channel closed while waiting for reply."),
-        }
-    }
-
-    pub async fn player_join_game(game_input_channel: &Sender<Message>, game_id: Uuid) -> NewPlayer
-    {
-        let (game_sender, game_receiver) = channel();
-        
-        let msg = Message {player_id: None, game_id: Some(game_id), reply_channel: game_sender, msg: Request::NewPlayer};
-
-        if let Err(_) = game_input_channel.send(msg).await 
-        {
-            panic!("The game runner input channel closed prematurely.");
-        };
-
-        return match game_receiver.await
-        {
-            Ok(from_game) =>
-            {
-                match from_game
-                {
-                    Outcome::NewPlayer(player_state) => player_state,
-                    _ => panic!("Was expecting NewPlayer registration confirmation.")
-                }
-            }
-            Err(_) => panic!("Game input channel has closed.")
-        }
-    }
+    use super::replay::IdempotencyKey;
+    use super::dispatcher::{GameQuery, GameFilter};
+    use super::notes::{NoteSubject, GmAnnotation};
+    use super::sync::CombatSync;
+    use super::snapshot::QuerySnapshots;
+    use super::hooks::{HookChain, DispatchHook, Verdict};
+    use super::registry::GameRegistry;
+    use super::authority::Authority;
+    use super::{game_runner_configured, run_shared, RunnerState, PLAYER_CHANNEL_CAPACITY};
+    use super::cast_limits::CastLimits;
+    use super::testing::{TestTable, init, add_new_game, player_join_game, create_character, create_and_add_char};
 
     #[tokio::test]
     pub async fn if_a_person_wishes_to_play_they_must_register_as_a_player_to_get_a_player_id()
@@ -288,69 +473,6 @@ mod tests
     }
 
 
-    pub fn create_character() -> Character
-    {
-        let names: [&str; 5] = ["Matrox", "El See-Dee", "BusShock", "Junkyard", "Lo Hax"];
-        let metatypes = [Metatypes::Dwarf, Metatypes::Elf, Metatypes::Human, Metatypes::Orc, Metatypes::Troll];
-
-        if rand::random::<usize>() % 2 == 1 {
-            return Character::new_npc(metatypes[rand::random::<usize>() % 5], String::from(names[rand::random::<usize>() % 5]));
-        }
-
-        return Character::new_pc(metatypes[rand::random::<usize>() % 5], String::from(names[rand::random::<usize>() % 5]));
-        
-    }
-
-    async fn create_and_add_char(game_input_channel: &Sender<Message>, game_id: Uuid) -> (PlayerId, CharacterId)
-    {
-        debug!("Starting create_and_add_char()");
-
-        let mut game_sender: OneShotSender<Outcome>;
-        let mut game_receiver: Receiver<Outcome>;
-
-        (game_sender, game_receiver) = channel::<Outcome>();
-        
-        debug!("Adding a player.");
-        let mut msg = Message {player_id: None, game_id: None, reply_channel: game_sender, msg: Request::NewPlayer};
-        assert!(game_input_channel.send(msg).await.is_ok());
-
-        let player_id = match game_receiver.await {
-            Ok(Outcome::NewPlayer(player)) => player.player_id, 
-            _ => panic!("Attempt to create new player has failed.")
-        };
-
-        debug!("Player {} added.", player_id);
-        debug!("Player sending request to join game {}", game_id);
-
-        (game_sender, game_receiver) = channel::<Outcome>();
-        msg = Message {player_id: Some(player_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::JoinGame};
-        assert!(game_input_channel.send(msg).await.is_ok());
-
-        match game_receiver.await {
-            Ok(Outcome::JoinedGame(state)) => {assert_eq!(player_id, state.for_player)}
-            _ => panic!("Attempt to join game failed.")
-        }
-
-        (game_sender, game_receiver) = channel::<Outcome>();
-        let character = create_character();
-
-        msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::AddCharacter(character) };
-        let send_state = game_input_channel.send(msg).await;
-        assert!(send_state.is_ok());
-
-        let response = game_receiver.await;
-        assert!(response.is_ok());
-
-        match response
-        {
-            Ok(Outcome::CharacterAdded((_, character_id))) => {
-                    return (player_id, character_id);
-            },
-            Ok(_) => {panic!("Should have received CharacterAdded outcome - interface changed.")}
-            Err(_) => {panic!("Channel closed.")}
-        }
-    }
-
     
 
     #[tokio::test]
@@ -359,7 +481,7 @@ mod tests
         let game_input_channel = init();
         let (game_sender, game_receiver) = channel();
 
-        let msg = Message{ player_id: None, game_id: Some(Uuid::new_v4()), reply_channel: game_sender, msg: Request::Enumerate };
+        let msg = Message{ player_id: None, game_id: Some(Uuid::new_v4()), reply_channel: game_sender, msg: Request::Enumerate(GameQuery::default()) };
         assert!(game_input_channel.send(msg).await.is_ok());
 
         match game_receiver.await
@@ -410,7 +532,7 @@ mod tests
 
         let (game_sender, game_receiver) = channel();
 
-        let msg = Message{ player_id: None, game_id: Some(Uuid::new_v4()), reply_channel: game_sender, msg: Request::Enumerate };
+        let msg = Message{ player_id: None, game_id: Some(Uuid::new_v4()), reply_channel: game_sender, msg: Request::Enumerate(GameQuery::default()) };
         assert!(game_input_channel.send(msg).await.is_ok());
 
         match game_receiver.await
@@ -1048,35 +1170,10 @@ mod tests
 
     async fn construct_combat_ready_game() -> (Sender<Message>, PlayerId, GameId, HashMap<PlayerId, CharacterId>)
     {
-        debug!("Started construct_combat_ready_game()");
-        let game_input_channel = init();
-        let (game_sender, _game_receiver) = channel::<Outcome>();
-
-        let (gm, game_id) = add_new_game(&game_input_channel).await;
-
-        debug!("GM {} has created game {}", gm, game_id);
-
-        let (player1, character1) = create_and_add_char(&game_input_channel, game_id).await;
-        let (player2, character2) = create_and_add_char(&game_input_channel, game_id).await;
-        let (player3, character3) = create_and_add_char(&game_input_channel, game_id).await;
-        let (player4, character4) = create_and_add_char(&game_input_channel, game_id).await;
-        let combatants = vec![character1, character2, character3, character4];
-
-        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombat(combatants.clone()) };
-
-        assert!(game_input_channel.send(msg).await.is_ok());
-
-        let (game_sender, _game_receiver) = channel::<Outcome>();
-        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::BeginInitiativePhase };
-        assert!(game_input_channel.send(msg).await.is_ok());
-
-        let mut player_character_map = HashMap::<Uuid, Uuid>::new();
-        player_character_map.insert(player1, character1);
-        player_character_map.insert(player2, character2);
-        player_character_map.insert(player3, character3);
-        player_character_map.insert(player4, character4);
+        let table = TestTable::new().with_players(4).combat_ready().await;
+        let player_character_map = table.player_characters();
 
-        return (game_input_channel, gm, game_id, player_character_map);
+        (table.runner, table.gm, table.game_id, player_character_map)
     }
 
     #[tokio::test]
@@ -1296,7 +1393,7 @@ mod tests
         assert!(game_input_channel.send(msg).await.is_ok());
 
         (game_sender, _game_receiver) = channel::<Outcome>();
-        msg = Message { player_id: Some(player2), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id: character2, action: ActionType::Complex })};
+        msg = Message { player_id: Some(player2), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id: character2, action: ActionType::Complex, roll: None, catalog: None })};
         assert!(game_input_channel.send(msg).await.is_ok());
 
         (game_sender, _game_receiver) = channel::<Outcome>();
@@ -1304,7 +1401,7 @@ mod tests
         assert!(game_input_channel.send(msg).await.is_ok());
 
         (game_sender, _game_receiver) = channel::<Outcome>();
-        msg = Message { player_id: Some(player1), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id: character1, action: ActionType::Complex })};
+        msg = Message { player_id: Some(player1), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id: character1, action: ActionType::Complex, roll: None, catalog: None })};
         assert!(game_input_channel.send(msg).await.is_ok());
 
         (game_sender, _game_receiver) = channel::<Outcome>();
@@ -1368,7 +1465,7 @@ mod tests
         
         (game_owned_sender, our_receiver) = channel::<Outcome>();
         msg = Message{ player_id: Some(**players.get(1).unwrap()), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::TakeAction
-            (Action{character_id: *player_char_map.get(players.get(1).unwrap()).unwrap(), action: ActionType::Complex})};
+            (Action{character_id: *player_char_map.get(players.get(1).unwrap()).unwrap(), action: ActionType::Complex, roll: None, catalog: None})};
         
         assert!(sender.send(msg).await.is_ok());
 
@@ -1426,7 +1523,7 @@ mod tests
 
         (game_owned_sender, our_receiver) = channel::<Outcome>();
         msg = Message{ player_id: Some(**players.get(2).unwrap()), game_id: Some(game_id), reply_channel: game_owned_sender, 
-            msg: Request::TakeAction(Action{ character_id: *player_char_map.get(players.get(2).unwrap()).unwrap(), action: ActionType::Free })};
+            msg: Request::TakeAction(Action{ character_id: *player_char_map.get(players.get(2).unwrap()).unwrap(), action: ActionType::Free, roll: None, catalog: None })};
         assert!(sender.send(msg).await.is_ok());
         
         match our_receiver.await
@@ -1490,7 +1587,7 @@ mod tests
 
         (game_owned_sender, our_receiver) = channel::<Outcome>();
         msg = Message{ player_id: Some(**player3), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::TakeAction
-            (Action{ character_id: *character3, action: ActionType::Complex })};
+            (Action{ character_id: *character3, action: ActionType::Complex, roll: None, catalog: None })};
         assert!(sender.send(msg).await.is_ok());
 
         match our_receiver.await
@@ -1510,5 +1607,2490 @@ mod tests
         }
     }
 
-    
-}
\ No newline at end of file
+    #[tokio::test]
+    pub async fn a_player_may_query_the_remaining_actions_of_their_own_character_only()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let players = player_char_map.keys().collect::<Vec<&PlayerId>>();
+
+        for (roll, player) in players.iter().enumerate()
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(**player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(player).unwrap(), roll: 10 + roll as i8 }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(our_receiver.await.is_ok());
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StartCombatRound};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(our_receiver.await.is_ok());
+
+        let player1 = players.get(0).unwrap();
+        let player2 = players.get(1).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(**player1), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::QueryRemainingActions(*player_char_map.get(player1).unwrap())};
+        assert!(sender.send(msg).await.is_ok());
+
+        match our_receiver.await
+        {
+            Ok(Outcome::RemainingActionsAre(budget)) => 
+            {
+                assert_eq!(budget.free, 1);
+                assert_eq!(budget.simple, 2);
+                assert_eq!(budget.complex, 1);
+            },
+            Ok(_) => {panic!("The outcome should have been RemainingActionsAre.");},
+            Err(_) => {panic!("The one-shot receiver dropped.");},
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(**player2), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::QueryRemainingActions(*player_char_map.get(player1).unwrap())};
+        assert!(sender.send(msg).await.is_ok());
+
+        match our_receiver.await
+        {
+            Ok(Outcome::Error(err)) => {assert!(err.kind == ErrorKind::UnauthorizedAction);},
+            Ok(_) => {panic!("The outcome should have been an error.");},
+            Err(_) => {panic!("The one-shot receiver dropped.");},
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_player_may_ask_what_their_own_character_can_do_and_is_told_to_roll_during_initiative()
+    {
+        let (sender, _gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let players = player_char_map.keys().collect::<Vec<&PlayerId>>();
+        let (player1, player2) = (**players.get(0).unwrap(), **players.get(1).unwrap());
+        let character = *player_char_map.get(&player1).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player1), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AvailableActions(character)};
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::AvailableActionsAre(actions)) => 
+            {
+                assert!(actions.contains(&AvailableAction::RollInitiative));
+                assert!(!actions.contains(&AvailableAction::Simple));
+            },
+            _ => panic!("The outcome should have been AvailableActionsAre."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player2), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AvailableActions(character)};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnauthorizedAction));
+    }
+
+    #[tokio::test]
+    pub async fn only_the_gm_may_switch_a_game_to_the_simplified_rules_and_the_table_is_told()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let player = *player_char_map.keys().next().unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetEdition(Edition::Anarchy)};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnauthorizedAction));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetEdition(Edition::Anarchy)};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::EditionSet(Edition::Anarchy))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) =>
+                assert!(notifications.iter().any(|change| matches!(change.as_ref(), WhatChanged::EditionChanged(Edition::Anarchy)))),
+            _ => panic!("Should have received the player's inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_gm_may_rename_the_action_types_for_the_whole_table_and_anyone_seated_may_ask_what_they_are()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let player = *player_char_map.keys().next().unwrap();
+        let labels = ActionLabels { free: String::from("Reaction"), simple: String::from("Bonus action"), complex: String::from("Action") };
+
+        for request in [Request::SetEdition(Edition::Generic), Request::SetActionLabels(Some(labels.clone()))]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: request};
+            assert!(sender.send(msg).await.is_ok());
+            assert!(!matches!(our_receiver.await, Ok(Outcome::Error(_)) | Err(_)));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetActionLabels(None)};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnauthorizedAction));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::QueryActionLabels};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ActionLabelsAre(named)) if named == labels));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) =>
+                assert!(notifications.iter().any(|change| matches!(change.as_ref(), WhatChanged::ActionLabelsChanged(named) if *named == labels))),
+            _ => panic!("Should have received the player's inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_player_following_the_journal_is_given_only_the_entries_after_the_last_one_they_saw()
+    {
+        let (sender, _gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let player = *player_char_map.keys().next().unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::JournalAfter(None)};
+        assert!(sender.send(msg).await.is_ok());
+        let last = match our_receiver.await
+        {
+            Ok(Outcome::JournalEntries(entries)) =>
+            {
+                assert!(entries.iter().any(|entry| matches!(entry.event, JournalEvent::CombatStarted(_))));
+                entries.last().unwrap().sequence
+            },
+            _ => panic!("Should have received the journal entries."),
+        };
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::JournalAfter(Some(last))};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::JournalEntries(entries)) if entries.is_empty()));
+    }
+
+    #[tokio::test]
+    pub async fn gm_annotations_are_only_readable_by_the_gm_and_never_reach_the_players()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let (player, character) = player_char_map.iter().next().map(|(player, character)| (*player, *character)).unwrap();
+        let subject = NoteSubject::Character(character);
+        let annotation = GmAnnotation { notes: String::from("Owes the Tamanous."), true_name: Some(String::from("Jack Carver")), ..GmAnnotation::default() };
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Inbox(_))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AnnotateForGm(subject.clone(), Some(annotation.clone())) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::GmAnnotationSet)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AnnotateForGm(NoteSubject::Encounter(String::from("never staged")), Some(annotation.clone())) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::NoSuchEncounter));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::QueryGmAnnotations };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::GmAnnotationsAre(annotations)) => assert_eq!(vec![(subject.clone(), annotation.clone())], annotations),
+            _ => panic!("The GM should have received their annotations."),
+        }
+
+        for request in [Request::QueryGmAnnotations, Request::AnnotateForGm(subject.clone(), None)]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: request };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnauthorizedAction));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => assert!(notifications.is_empty()),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_character_a_restored_checkpoint_takes_out_of_the_cast_no_longer_counts_against_its_player()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (player, _) = *table.players.first().unwrap();
+
+        let limits = CastLimits { per_player: Some(2), ..CastLimits::default() };
+        assert!(matches!(table.send(table.gm, Request::SetCastLimits(limits)).await, Outcome::CastLimitsSet));
+        assert!(matches!(table.send(table.gm, Request::SaveCheckpoint(String::from("before the newcomer"))).await, Outcome::CheckpointSaved));
+        let newcomer = match table.send(player, Request::AddCharacter(create_character())).await
+        {
+            Outcome::CharacterAdded((_, character_id)) => character_id,
+            _ => panic!("The player's second character should have been added."),
+        };
+        assert!(matches!(table.send(player, Request::AddCharacter(create_character())).await, 
+            Outcome::Error(err) if err.kind == ErrorKind::CharacterLimitReached));
+
+        assert!(matches!(table.send(table.gm, Request::RestoreCheckpoint(String::from("before the newcomer"))).await, Outcome::CheckpointRestored));
+        assert!(matches!(table.send(player, Request::GetCharacter(newcomer)).await, Outcome::Found(None) | Outcome::Error(_)));
+        assert!(matches!(table.send(player, Request::AddCharacter(create_character())).await, Outcome::CharacterAdded(_)));
+    }
+
+    #[tokio::test]
+    pub async fn a_player_at_the_cast_limit_is_refused_another_character_and_the_table_hears_the_limit()
+    {
+        let sender = init();
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (player, _) = create_and_add_char(&sender, game_id).await;
+        let limits = CastLimits { per_player: Some(1), ..CastLimits::default() };
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetCastLimits(limits) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnauthorizedAction));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetCastLimits(limits) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CastLimitsSet)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AddCharacter(create_character()) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::CharacterLimitReached));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AddCharacter(create_character()) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CharacterAdded(_))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::CastLimitsChanged(announced) if *announced == limits))),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_quick_created_character_joins_the_cast_with_its_archetypes_sheet()
+    {
+        let sender = init();
+        let (_, game_id) = add_new_game(&sender).await;
+        let (player, _) = create_and_add_char(&sender, game_id).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::QuickCreateCharacter(Archetype::StreetSamurai, String::from("Razor")) };
+        assert!(sender.send(msg).await.is_ok());
+        let character_id = match our_receiver.await
+        {
+            Ok(Outcome::CharacterAdded((_, character_id))) => character_id,
+            _ => panic!("Quick creating a character should add it to the cast."),
+        };
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::GetCharacter(character_id) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Found(Some(character))) => 
+            {
+                assert_eq!("Razor", &*character.name);
+                assert!(character.player_character);
+                assert_eq!(2, character.extra_initiative_passes());
+            },
+            _ => panic!("The quick created character should be in the cast."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn an_initiative_roll_the_sheets_formula_cannot_produce_is_refused()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let (player, character_id) = player_char_map.iter().next().map(|(player, character)| (*player, *character)).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::UpdateCharacter { id: character_id, patch: CharacterPatch { initiative: InitiativeFormula::parse("8 + 3d6"), ..Default::default() } } };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CharacterUpdated(_))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id, roll: 3 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::InitiativeOutOfRange));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id, roll: 11 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+    }
+
+    #[tokio::test]
+    pub async fn only_the_gm_may_put_in_an_initiative_roll_past_the_editions_bounds()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let (player, character_id) = player_char_map.iter().next().map(|(player, character)| (*player, *character)).unwrap();
+
+        for (sent_by, request, refusal) in [(player, Request::AddInitiativeRoll(Roll{ character_id, roll: 41 }), ErrorKind::InitiativeOutOfRange), 
+            (player, Request::AddInitiativeRoll(Roll{ character_id, roll: -1 }), ErrorKind::InitiativeOutOfRange),
+            (player, Request::OverrideInitiativeRoll(Roll{ character_id, roll: 41 }), ErrorKind::UnauthorizedAction)]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(sent_by), game_id: Some(game_id), reply_channel: game_owned_sender, msg: request };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::Error(err)) if err.kind == refusal));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::OverrideInitiativeRoll(Roll{ character_id, roll: 41 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+    }
+
+    #[tokio::test]
+    pub async fn errors_name_the_game_character_request_and_states_involved()
+    {
+        let sender = init();
+        let (_, game_id) = add_new_game(&sender).await;
+        let (player, character_id) = create_and_add_char(&sender, game_id).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id, roll: 9 }) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Error(err)) => 
+            {
+                assert!(err.kind == ErrorKind::InvalidStateAction);
+                assert_eq!(ErrorContext { game_id: Some(game_id), character_id: Some(character_id), request: Some("AddInitiativeRoll"), 
+                    expected_state: Some(String::from("Initiative Rolls")), actual_state: Some(String::from("PreCombat")) }, err.context);
+            },
+            _ => panic!("A roll outside the initiative phase should be refused."),
+        }
+
+        let outsider = player_join_game(&sender, game_id).await.player_id;
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(outsider), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id, roll: 9 }) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Error(err)) => 
+            {
+                assert_eq!(Some(game_id), err.context.game_id);
+                assert_eq!(None, err.context.actual_state);
+            },
+            _ => panic!("Someone without a seat at the game should be refused."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::Batch(vec![Request::New]) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Error(err)) => 
+            {
+                assert_eq!(Some("Batch"), err.context.request);
+                assert_eq!(None, err.context.expected_state);
+            },
+            _ => panic!("An unbatchable request should fail the batch."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_change_to_one_combatant_sends_only_that_combatant_and_a_resync_matches_it()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let (player, character) = player_char_map.iter().next().map(|(player, character)| (*player, *character)).unwrap();
+
+        for reader in [gm, player]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(reader), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::Inbox(_))));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll { character_id: character, roll: 12 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+
+        let mut sequence = 0;
+        for reader in [gm, player]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(reader), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+            let Ok(Outcome::Inbox(notifications)) = our_receiver.await
+            else { panic!("Should have received an inbox.") };
+
+            let deltas = notifications.iter().filter_map(|notification| match notification.as_ref()
+            {
+                WhatChanged::CombatantsSynced(CombatSync::Delta { sequence, changed, removed }) => Some((*sequence, changed.clone(), removed.clone())),
+                _ => None,
+            }).collect::<Vec<_>>();
+            assert_eq!(1, deltas.len());
+            let (delta_sequence, changed, removed) = deltas.into_iter().next().unwrap();
+            assert_eq!(vec![character], changed.iter().map(|state| state.character).collect::<Vec<CharacterId>>());
+            assert_eq!(TurnState::Waiting, changed[0].turn);
+            assert!(removed.is_empty());
+            sequence = delta_sequence;
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::ResyncCombat };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::CombatSynced(CombatSync::Full { sequence: full_sequence, combatants })) => 
+            {
+                assert_eq!(sequence, full_sequence);
+                assert_eq!(4, combatants.len());
+                assert_eq!(1, combatants.iter().filter(|state| state.turn == TurnState::Waiting).count());
+            },
+            _ => panic!("The player should have received a full sync."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn enumerating_games_can_be_filtered_to_those_a_player_runs_and_paged()
+    {
+        let game_input_channel = init();
+
+        let (gm, first_game) = add_new_game(&game_input_channel).await;
+        let (_other_gm, _other_game) = add_new_game(&game_input_channel).await;
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::New };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        let second_game = match game_receiver.await {
+            Ok(Outcome::Created(game_id)) => game_id,
+            _ => panic!("Should have received a Created outcome."),
+        };
+
+        let mut expected = vec![first_game, second_game];
+        expected.sort();
+
+        let mut paged = Vec::new();
+        for page in 0..3
+        {
+            let (game_sender, game_receiver) = channel();
+            let query = GameQuery { filter: GameFilter::Running, page, page_size: Some(1) };
+            let msg = Message { player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+
+            match game_receiver.await
+            {
+                Ok(Outcome::Summaries(summaries)) => paged.extend(summaries.into_iter().map(|(id, _)| id)),
+                _ => panic!("Should have received an Outcome::Summaries."),
+            }
+        }
+        assert_eq!(expected, paged);
+
+        let (game_sender, game_receiver) = channel();
+        let query = GameQuery { filter: GameFilter::Joined, page: 0, page_size: None };
+        let msg = Message { player_id: None, game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        match game_receiver.await
+        {
+            Ok(Outcome::Error(err)) => assert!(err.kind == ErrorKind::UnauthorizedAction),
+            _ => panic!("An unregistered requester has no games of their own to list."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_named_game_is_listed_by_name_and_only_a_change_marks_it_active()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (player, _) = table.players[0];
+        let listed = |outcome: Outcome| match outcome
+        {
+            Outcome::Summaries(summaries) => summaries.into_iter().find(|(id, _)| *id == table.game_id).map(|(_, summary)| summary).unwrap(),
+            _ => panic!("Should have received an Outcome::Summaries."),
+        };
+        let everything = || Request::Enumerate(GameQuery { filter: GameFilter::All, page: 0, page_size: None });
+
+        assert!(matches!(table.send(player, Request::NameGame(String::from("Not mine"))).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::NameGame(String::from("Harbour Job"))).await, Outcome::GameNamed));
+        let named = listed(table.send(player, everything()).await);
+        assert_eq!("Harbour Job", &*named.name);
+
+        assert!(matches!(table.send(player, Request::GetPhase).await, Outcome::PhaseIs(_)));
+        assert!(matches!(table.send(player, Request::NameGame(String::from("Still not mine"))).await, Outcome::Error(_)));
+        assert_eq!(named.last_activity, listed(table.send(player, everything()).await).last_activity);
+
+        assert!(matches!(table.send(table.gm, Request::NameGame(String::from("Harbour Job, Take Two"))).await, Outcome::GameNamed));
+        assert!(listed(table.send(player, everything()).await).last_activity > named.last_activity);
+    }
+
+    #[tokio::test]
+    pub async fn the_lobby_listing_reports_each_games_seats_and_phase_as_they_change()
+    {
+        let (game_input_channel, _, game_id, _) = construct_combat_ready_game().await;
+
+        for (filter, listed) in [(GameFilter::InCombat, true), (GameFilter::Open, false)]
+        {
+            let (game_sender, game_receiver) = channel();
+            let query = GameQuery { filter, page: 0, page_size: None };
+            let msg = Message { player_id: None, game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+
+            match game_receiver.await
+            {
+                Ok(Outcome::Summaries(summaries)) => 
+                {
+                    let summary = summaries.iter().find(|(id, _)| *id == game_id).map(|(_, summary)| summary);
+                    assert_eq!(listed, summary.is_some());
+                    if let Some(summary) = summary
+                    {
+                        assert_eq!(5, summary.players);
+                        assert_eq!(4, summary.characters);
+                        assert_eq!(CastLimits::default(), summary.cast_limits);
+                        assert_eq!("Initiative Rolls", summary.phase);
+                        assert!(summary.in_combat);
+                    }
+                },
+                _ => panic!("Should have received an Outcome::Summaries."),
+            }
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_query_snapshots_follow_each_game_without_asking_the_runner()
+    {
+        let snapshots = QuerySnapshots::default();
+        let (sender, receiver) = mpsc_channel(1);
+        let runner_snapshots = snapshots.clone();
+        tokio::spawn(async move { game_runner_configured(receiver, HookChain::new(), PLAYER_CHANNEL_CAPACITY, runner_snapshots).await; });
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (player, character) = create_and_add_char(&sender, game_id).await;
+        assert!(matches!(snapshots.game(&game_id), Some(snapshot) if !snapshot.summary.in_combat && snapshot.combatants.is_empty()));
+        let snapshot = snapshots.game(&game_id).unwrap();
+        assert!(snapshot.players.contains(&gm) && snapshot.players.contains(&player) && snapshot.players.len() == 2);
+        assert!(matches!(&snapshot.cast[..], [member] if member.character == character && member.turn == TurnState::OutOfCombat));
+        assert_eq!(Some(vec![game_id]), snapshots.list(GameFilter::Open, 0, None).map(|games| games.into_iter().map(|(id, _)| id).collect()));
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombat(vec![character]) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::CombatStarted)));
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::BeginInitiativePhase };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::InitiativePhaseStarted)));
+
+        let snapshot = snapshots.game(&game_id).unwrap();
+        assert!(snapshot.summary.in_combat);
+        assert_eq!(Some(&vec!["AddInitiativeRoll", "AddInitiativeRolls"]), snapshot.allowed.characters.get(&character));
+        assert_eq!(vec![character], snapshot.combatants.iter().map(|combatant| combatant.character).collect::<Vec<CharacterId>>());
+        assert!(matches!(snapshots.list(GameFilter::Open, 0, None), Some(games) if games.is_empty()));
+        assert!(snapshots.list(GameFilter::Joined, 0, None).is_none());
+        assert!(snapshot.order.is_empty());
+
+        for request in [Request::OverrideInitiativeRoll(Roll { character_id: character, roll: 10 }), Request::StartCombatRound]
+        {
+            let (game_sender, game_receiver) = channel();
+            let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: request };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(!matches!(game_receiver.await, Ok(Outcome::Error(_))));
+        }
+        let snapshot = snapshots.game(&game_id).unwrap();
+        assert_eq!((1, 0), (snapshot.round, snapshot.pass));
+        assert!(matches!(&snapshot.order[..], [entry] if entry.character == character && entry.acting));
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Delete };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(game_receiver.await.is_ok());
+        assert!(snapshots.game(&game_id).is_none());
+    }
+
+    #[tokio::test]
+    pub async fn a_player_joining_from_the_lobby_takes_a_seat_the_snapshots_show_and_a_missing_game_turns_them_away()
+    {
+        let snapshots = QuerySnapshots::default();
+        let (sender, receiver) = mpsc_channel(1);
+        let runner_snapshots = snapshots.clone();
+        tokio::spawn(async move { game_runner_configured(receiver, HookChain::new(), PLAYER_CHANNEL_CAPACITY, runner_snapshots).await; });
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        let player = player_join_game(&sender, game_id).await.player_id;
+        assert!(snapshots.seats(&game_id, &gm));
+        assert!(!snapshots.seats(&game_id, &player));
+
+        let sender = &sender;
+        let join = |game_id| async move
+        {
+            let (reply_channel, reply) = channel();
+            assert!(sender.send(Message { player_id: Some(player), game_id: Some(game_id), reply_channel, msg: Request::JoinGame }).await.is_ok());
+            reply.await
+        };
+        assert!(matches!(join(Uuid::new_v4()).await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::UnknownId || err.kind == ErrorKind::NoMatchingGame));
+        assert!(!snapshots.seats(&game_id, &player));
+
+        assert!(matches!(join(game_id).await, Ok(Outcome::JoinedGame(state)) if state.for_player == player));
+        assert!(snapshots.seats(&game_id, &player));
+        assert!(!snapshots.seats(&Uuid::new_v4(), &player));
+    }
+
+    #[test]
+    pub fn the_query_snapshots_notice_a_game_deleted_and_another_created_even_when_the_count_is_unchanged()
+    {
+        let mut registry = GameRegistry::new();
+        let gm = PlayerId::new_v4();
+        let (gm_sender, _gm_receiver) = mpsc_channel(32);
+        assert!(registry.register_player(gm, gm_sender).is_ok());
+
+        let (first, second) = (GameId::new_v4(), GameId::new_v4());
+        assert!(registry.new_game(gm, first, crate::tracker::game::Game::new()).is_ok());
+        let snapshots = QuerySnapshots::default();
+        snapshots.republish(&registry, None);
+        assert!(snapshots.game(&first).is_some());
+
+        // Neither game is the one the message was for, as with a departing GM's games going and a clone arriving in the same message.
+        assert!(registry.delete_game(first).is_ok());
+        assert!(registry.new_game(gm, second, crate::tracker::game::Game::new()).is_ok());
+        snapshots.republish(&registry, None);
+
+        assert!(snapshots.game(&first).is_none());
+        assert!(snapshots.game(&second).is_some());
+        assert_eq!(Some(vec![second]), snapshots.list(GameFilter::All, 0, None).map(|games| games.into_iter().map(|(id, _)| id).collect()));
+    }
+
+    // Panics once the game has been deleted, so that there is something to put back.
+    struct PanicOnDelete;
+
+    impl DispatchHook for PanicOnDelete
+    {
+        fn name(&self) -> &'static str
+        {
+            "panic on delete"
+        }
+
+        fn after(&mut self, _registry: &GameRegistry, authority: &Authority, outcome: Outcome) -> Outcome
+        {
+            if authority.request().name() == "Delete"
+            {
+                panic!("Deletes are not allowed here.");
+            }
+            outcome
+        }
+    }
+
+    // A panic whose payload panics again as it is dropped, which happens after catch_unwind has returned and so takes the runner down
+    // with it: the one way left for a test to make the runner panic.
+    struct PanicsWhenDropped;
+
+    impl Drop for PanicsWhenDropped
+    {
+        fn drop(&mut self)
+        {
+            panic!("The panic payload was dropped.");
+        }
+    }
+
+    struct PanicHarderOnDelete;
+
+    impl DispatchHook for PanicHarderOnDelete
+    {
+        fn name(&self) -> &'static str
+        {
+            "panic harder on delete"
+        }
+
+        fn after(&mut self, _registry: &GameRegistry, authority: &Authority, outcome: Outcome) -> Outcome
+        {
+            if authority.request().name() == "Delete"
+            {
+                std::panic::panic_any(PanicsWhenDropped);
+            }
+            outcome
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_request_that_panics_gets_an_unexpected_error_and_its_game_is_put_back()
+    {
+        let (sender, receiver) = mpsc_channel(1);
+        let mut hooks = HookChain::new();
+        hooks.register(Box::new(PanicOnDelete));
+        tokio::spawn(run_shared(RunnerState::new(receiver, hooks, PLAYER_CHANNEL_CAPACITY), QuerySnapshots::default()));
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Delete };
+        assert!(sender.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::Error(err)) => assert!(err.kind == ErrorKind::Unexpected && err.context.game_id == Some(game_id)),
+            _ => panic!("Should have received an Unexpected error."),
+        }
+
+        let (player, _) = create_and_add_char(&sender, game_id).await;
+        for (player_id, filter) in [(gm, GameFilter::Running), (player, GameFilter::Joined)]
+        {
+            let (game_sender, game_receiver) = channel();
+            let query = GameQuery { filter, page: 0, page_size: None };
+            let msg = Message { player_id: Some(player_id), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(game_receiver.await, Ok(Outcome::Summaries(games)) if games.len() == 1 && games[0].0 == game_id));
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_runner_restarted_after_a_panic_picks_up_the_same_queue_and_games()
+    {
+        let (sender, receiver) = mpsc_channel(1);
+        let mut hooks = HookChain::new();
+        hooks.register(Box::new(PanicHarderOnDelete));
+        let state = RunnerState::new(receiver, hooks, PLAYER_CHANNEL_CAPACITY);
+        let runner = tokio::spawn(run_shared(state.clone(), QuerySnapshots::default()));
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Delete };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(game_receiver.await.is_err());
+        assert!(runner.await.unwrap_err().is_panic());
+
+        let snapshots = QuerySnapshots::default();
+        tokio::spawn(run_shared(state, snapshots.clone()));
+        let (game_sender, game_receiver) = channel();
+        let query = GameQuery { filter: GameFilter::Running, page: 0, page_size: None };
+        let msg = Message { player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Summaries(games)) if games.len() == 1 && games[0].0 == game_id));
+        assert!(snapshots.game(&game_id).is_some());
+    }
+
+    #[tokio::test]
+    pub async fn a_restarted_runner_picks_up_the_same_queue_and_games()
+    {
+        let (sender, receiver) = mpsc_channel(1);
+        let state = RunnerState::new(receiver, HookChain::new(), PLAYER_CHANNEL_CAPACITY);
+        let runner = tokio::spawn(run_shared(state.clone(), QuerySnapshots::default()));
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        runner.abort();
+        assert!(runner.await.unwrap_err().is_cancelled());
+
+        let snapshots = QuerySnapshots::default();
+        tokio::spawn(run_shared(state, snapshots.clone()));
+        let (game_sender, game_receiver) = channel();
+        let query = GameQuery { filter: GameFilter::Running, page: 0, page_size: None };
+        let msg = Message { player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Summaries(games)) if games.len() == 1 && games[0].0 == game_id));
+        assert!(snapshots.game(&game_id).is_some());
+    }
+
+    #[tokio::test]
+    pub async fn a_restarted_runner_puts_the_game_it_was_handling_back_as_it_was_before_the_message()
+    {
+        let (sender, receiver) = mpsc_channel(1);
+        let state = RunnerState::new(receiver, HookChain::new(), PLAYER_CHANNEL_CAPACITY);
+        let runner = tokio::spawn(run_shared(state.clone(), QuerySnapshots::default()));
+
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (player, character) = create_and_add_char(&sender, game_id).await;
+        runner.abort();
+        assert!(runner.await.unwrap_err().is_cancelled());
+
+        // As a runner that panicked after unseating the table for a delete, but before it could reply, would have left things.
+        {
+            let mut state = state.lock().await;
+            let saved = state.directory.save_game(&game_id);
+            state.in_flight = Some((game_id, saved));
+            assert!(state.directory.delete_game(game_id).is_ok());
+        }
+
+        let snapshots = QuerySnapshots::default();
+        tokio::spawn(run_shared(state, snapshots.clone()));
+        for (player_id, filter) in [(gm, GameFilter::Running), (player, GameFilter::Joined)]
+        {
+            let (game_sender, game_receiver) = channel();
+            let query = GameQuery { filter, page: 0, page_size: None };
+            let msg = Message { player_id: Some(player_id), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(query) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(game_receiver.await, Ok(Outcome::Summaries(games)) if games.len() == 1 && games[0].0 == game_id));
+        }
+        assert!(matches!(snapshots.game(&game_id), Some(snapshot) if snapshot.cast.iter().any(|member| member.character == character)));
+    }
+
+    #[tokio::test]
+    pub async fn a_keyed_request_sent_twice_is_applied_once_and_answered_the_same_both_times()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (player, _) = table.players[0];
+        let chat = || Request::Idempotent(IdempotencyKey::new("hoi-1", 1),
+            Box::new(Request::Chat(ChatMessage { audience: ChatAudience::Table, text: String::from("Hoi, chummer.") })));
+
+        let Outcome::ChatSent(first) = table.send(player, chat()).await
+        else { panic!("Should have received ChatSent for the first send.") };
+        assert!(matches!(table.send(player, chat()).await, Outcome::ChatSent(replayed) if replayed == first));
+        assert!(matches!(table.send(player, Request::QueryChat).await, Outcome::ChatLog(lines) if lines.len() == 1));
+
+        let reused = Request::Idempotent(IdempotencyKey::new("hoi-1", 1), Box::new(Request::QueryChat));
+        assert!(matches!(table.send(player, reused).await, Outcome::Error(err) if err.kind == ErrorKind::IdempotencyKeyReused));
+        let changed = Request::Idempotent(IdempotencyKey::new("hoi-1", 2),
+            Box::new(Request::Chat(ChatMessage { audience: ChatAudience::Table, text: String::from("Hoi, chummers.") })));
+        assert!(matches!(table.send(player, changed).await, Outcome::Error(err) if err.kind == ErrorKind::IdempotencyKeyReused));
+    }
+
+    #[tokio::test]
+    pub async fn a_key_stands_only_for_the_game_it_was_sent_to_and_a_refusal_is_not_replayed()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (_, character) = table.players[0];
+        let begin = || Request::Idempotent(IdempotencyKey::new("begin-1", 1), Box::new(Request::BeginInitiativePhase));
+
+        assert!(matches!(table.send(table.gm, begin()).await, Outcome::Error(err) if err.kind != ErrorKind::IdempotencyKeyReused));
+        assert!(matches!(table.send(table.gm, Request::StartCombat(vec![character])).await, Outcome::CombatStarted));
+        assert!(matches!(table.send(table.gm, begin()).await, Outcome::InitiativePhaseStarted));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(table.gm), game_id: Some(Uuid::new_v4()), reply_channel, msg: Request::New };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Created(second_game)) = reply.await
+        else { panic!("Should have created a second game.") };
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(table.gm), game_id: Some(second_game), reply_channel, msg: begin() };
+        assert!(table.runner.send(msg).await.is_ok());
+        assert!(matches!(reply.await, Ok(Outcome::Error(err)) if err.kind != ErrorKind::IdempotencyKeyReused));
+    }
+
+    #[tokio::test]
+    pub async fn one_damage_event_prompts_every_target_and_tells_each_player_once()
+    {
+        let table = TestTable::new().with_players(3).combat_ready().await;
+        let (player, first) = table.players[0];
+        let targets = vec![table.players[2].1, first, table.players[1].1];
+
+        let event = DamageEvent { source: first, targets: targets.clone(), armor_test: ArmorTestType::Impact, armor_pen: 0 };
+        match table.send(table.gm, Request::ApplyDamageEvent(event)).await
+        {
+            Outcome::DamageEventApplied(prompts) => assert_eq!(targets, prompts.iter().map(|prompt| prompt.character_id).collect::<Vec<CharacterId>>()),
+            _ => panic!("Should have received DamageEventApplied."),
+        }
+
+        let event = DamageEvent { source: first, targets: vec![first], armor_test: ArmorTestType::Impact, armor_pen: 0 };
+        assert!(matches!(table.send(player, Request::ApplyDamageEvent(event)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(player), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        let heard = inbox.iter().flat_map(|change| change.parts()).filter_map(|change| match change
+        {
+            WhatChanged::DamageEventApplied { targets, .. } => Some(targets.clone()),
+            _ => None,
+        }).collect::<Vec<Vec<CharacterId>>>();
+        assert_eq!(vec![targets], heard);
+    }
+
+    #[tokio::test]
+    pub async fn a_player_whose_actions_move_the_turn_on_is_answered_without_npc_scores()
+    {
+        let table = TestTable::new().with_players(1).with_character(|| Character::new_pc(Metatypes::Human, String::from("Razor"))).seated().await;
+        let (player, character_id) = table.players[0];
+        let npc = match table.send(table.gm, Request::AddCharacter(Character::new_npc(Metatypes::Orc, String::from("Ganger")))).await
+        {
+            Outcome::CharacterAdded((_, npc)) => npc,
+            _ => panic!("The GM's NPC should have been added."),
+        };
+
+        assert!(matches!(table.send(table.gm, Request::StartCombat(vec![character_id, npc])).await, Outcome::CombatStarted));
+        assert!(matches!(table.send(table.gm, Request::BeginInitiativePhase).await, Outcome::InitiativePhaseStarted));
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 20 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id: npc, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+
+        let catalog = vec![Action { character_id, action: ActionType::Free, roll: None, catalog: Some(String::from("Burst fire")) }];
+        assert!(matches!(table.send(player, Request::TakeActions(catalog)).await, Outcome::Error(err) if err.kind == ErrorKind::InvalidStateAction));
+
+        let actions = vec![Action { character_id, action: ActionType::Complex, roll: None, catalog: None }];
+        match table.send(player, Request::TakeActions(actions)).await
+        {
+            Outcome::ActionsTaken { advanced: Some(advanced) } => assert_eq!(vec![InitiativeEntry { character: npc, initiative: None }], advanced.up),
+            _ => panic!("The player's only character has acted, so the turn should have moved on to the NPC."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_player_who_sits_out_and_moves_the_turn_on_is_answered_without_npc_scores()
+    {
+        let table = TestTable::new().with_players(1).with_character(|| Character::new_pc(Metatypes::Human, String::from("Razor"))).seated().await;
+        let (player, character_id) = table.players[0];
+        let npc = match table.send(table.gm, Request::AddCharacter(Character::new_npc(Metatypes::Orc, String::from("Ganger")))).await
+        {
+            Outcome::CharacterAdded((_, npc)) => npc,
+            _ => panic!("The GM's NPC should have been added."),
+        };
+
+        assert!(matches!(table.send(table.gm, Request::StartCombat(vec![character_id, npc])).await, Outcome::CombatStarted));
+        assert!(matches!(table.send(table.gm, Request::BeginInitiativePhase).await, Outcome::InitiativePhaseStarted));
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 20 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id: npc, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+
+        match table.send(player, Request::SitOut(SittingOut { character_id, reason: Some(SkipReason::Delaying) })).await
+        {
+            Outcome::SittingOutSet { advanced: Some(advanced) } => assert_eq!(vec![InitiativeEntry { character: npc, initiative: None }], advanced.up),
+            _ => panic!("The player's only character sat out its turn, so the turn should have moved on to the NPC."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_timed_event_takes_its_own_turn_and_the_table_hears_when_it_comes_up()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        let schedule = EventSchedule { label: String::from("Bomb timer"), initiative: 20, rounds: 0 };
+        assert!(matches!(table.send(player, Request::ScheduleEvent(schedule)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        let schedule = EventSchedule { label: String::from("Bomb timer"), initiative: 20, rounds: 0 };
+        let bomb = match table.send(table.gm, Request::ScheduleEvent(schedule)).await
+        {
+            Outcome::EventScheduled(event) => event,
+            _ => panic!("Should have received EventScheduled."),
+        };
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+
+        match table.send(table.gm, Request::AdvanceTurn).await
+        {
+            Outcome::TurnAdvanced(advanced) =>
+            {
+                assert_eq!(vec![InitiativeEntry { character: character_id, initiative: Some(10) }], advanced.up);
+                assert!(advanced.events_up.is_empty());
+            },
+            _ => panic!("The bomb timer has nobody to wait on, so the turn should have moved on."),
+        }
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(player), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        let heard = inbox.iter().flat_map(|change| change.parts()).filter_map(|change| match change
+        {
+            WhatChanged::TimedEventUp(event) => Some(event.clone()),
+            _ => None,
+        }).collect::<Vec<TimedEvent>>();
+        assert_eq!(vec![bomb], heard);
+    }
+
+    #[tokio::test]
+    pub async fn an_action_taken_from_the_catalog_is_named_to_the_gm()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        let reload = CatalogAction { name: String::from("Reload"), cost: ActionType::Simple, description: String::from("Swap in a fresh clip."),
+            ammunition: Ammunition::Untouched };
+        assert!(matches!(table.send(player, Request::DefineCatalogAction(reload.clone())).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::DefineCatalogAction(reload.clone())).await, Outcome::CatalogActionDefined));
+        assert!(matches!(table.send(player, Request::QueryActionCatalog).await, Outcome::ActionCatalogIs(catalog) if catalog == vec![reload.clone()]));
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+
+        let take = |catalog: &str| Request::TakeAction(Action { character_id, action: ActionType::Free, roll: None, catalog: Some(String::from(catalog)) });
+        assert!(matches!(table.send(player, take("Suppressive fire")).await, Outcome::Error(err) if err.kind == ErrorKind::NoSuchCatalogAction));
+        assert!(matches!(table.send(player, take("Reload")).await, Outcome::ActionTaken));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(table.gm), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the GM's inbox.") };
+        assert!(inbox.iter().flat_map(|change| change.parts())
+            .any(|change| matches!(change, WhatChanged::NamedActionTaken { character, name } if *character == character_id && name == "Reload")));
+    }
+
+    #[tokio::test]
+    pub async fn a_cataloged_attack_with_an_empty_gun_is_refused_until_it_is_reloaded()
+    {
+        let table = TestTable::new().with_players(1).with_character(|| Archetype::Decker.build(String::from("Dodger"))).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        let mag_dump = CatalogAction { name: String::from("Empty the clip"), cost: ActionType::Simple, description: String::from("Everything at once."),
+            ammunition: Ammunition::Fires(30) };
+        let reload = CatalogAction { name: String::from("Reload"), cost: ActionType::Free, description: String::from("Swap in a fresh clip."),
+            ammunition: Ammunition::Reloads };
+        assert!(matches!(table.send(table.gm, Request::DefineCatalogAction(mag_dump)).await, Outcome::CatalogActionDefined));
+        assert!(matches!(table.send(table.gm, Request::DefineCatalogAction(reload)).await, Outcome::CatalogActionDefined));
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+
+        let take = |catalog: &str| Request::TakeAction(Action { character_id, action: ActionType::Free, roll: None, catalog: Some(String::from(catalog)) });
+        assert!(matches!(table.send(player, take("Empty the clip")).await, Outcome::ActionTaken));
+        assert!(matches!(table.send(player, take("Empty the clip")).await, Outcome::Error(err) if err.kind == ErrorKind::OutOfAmmo));
+        assert!(matches!(table.send(player, take("Reload")).await, Outcome::ActionTaken));
+        assert!(matches!(table.send(player, take("Empty the clip")).await, Outcome::ActionTaken));
+    }
+
+    #[tokio::test]
+    pub async fn the_gm_rests_the_table_and_ends_the_fight_and_the_players_hear_of_both()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        assert!(matches!(table.send(player, Request::SetCarryOver(true)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::SetCarryOver(true)).await, Outcome::CarryOverSet(true)));
+
+        assert!(matches!(table.send(player, Request::Rest(vec![character_id])).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::Rest(vec![character_id, Uuid::new_v4()])).await, 
+            Outcome::Error(err) if err.kind == ErrorKind::NoSuchCharacter));
+        assert!(matches!(table.send(table.gm, Request::Rest(vec![character_id])).await, Outcome::Rested));
+
+        assert!(matches!(table.send(player, Request::EndCombat).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::EndCombat).await, Outcome::CombatEnded));
+        assert!(matches!(table.send(table.gm, Request::EndCombat).await, Outcome::Error(err) if err.kind == ErrorKind::InvalidStateAction));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(player), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        let heard = inbox.iter().flat_map(|change| change.parts()).collect::<Vec<&WhatChanged>>();
+        assert!(heard.iter().any(|change| matches!(change, WhatChanged::CharactersRested(rested) if *rested == vec![character_id])));
+        assert_eq!(1, heard.iter().filter(|change| matches!(change, WhatChanged::CombatEnded)).count());
+    }
+
+    #[tokio::test]
+    pub async fn the_gm_heals_a_character_and_the_table_hears_the_wounds_that_remain()
+    {
+        let table = TestTable::new().with_players(2).with_character(|| Archetype::StreetSamurai.build(String::from("Razor"))).seated().await;
+        let ((player, character_id), (_, other_character)) = (table.players[0], table.players[1]);
+
+        let damage = DamageApplication { source: None, target: character_id, boxes: 5, kind: DamageType::Physical, armor: None };
+        assert!(matches!(table.send(table.gm, Request::ApplyDamage(damage)).await, Outcome::DamageApplied));
+
+        let first_aid = |character_id| Request::Heal(HealingApplication { character_id, healing: Healing::FirstAid { hits: 4, rating: 3, kind: DamageType::Physical } });
+        assert!(matches!(table.send(player, first_aid(other_character)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, first_aid(Uuid::new_v4())).await, Outcome::Error(err) if err.kind == ErrorKind::NoSuchCharacter));
+        assert!(matches!(table.send(table.gm, first_aid(character_id)).await, Outcome::Healed(Recovery { physical: 3, stun: 0 })));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(player), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        assert!(inbox.iter().flat_map(|change| change.parts()).any(|change| matches!(change, 
+            WhatChanged::CharacterHealed { character, wounds: WoundView::Exact(monitor) } if *character == character_id && monitor.physical_filled == 2)));
+    }
+
+    #[tokio::test]
+    pub async fn a_player_marks_damage_on_their_own_character_and_their_armor_turns_what_it_stops_to_stun()
+    {
+        let table = TestTable::new().with_players(2).with_character(|| Archetype::StreetSamurai.build(String::from("Razor"))).seated().await;
+        let ((player, character_id), (_, other_character)) = (table.players[0], table.players[1]);
+
+        // The armor jacket stops 8 ballistic; six boxes from a pistol with no penetration only bruise, but the same with AP -3 gets through.
+        let shot = |target, armor_pen| Request::ApplyDamage(DamageApplication { source: None, target, boxes: 6, kind: DamageType::Physical, 
+            armor: Some((ArmorTestType::Ballistic, armor_pen)) });
+        assert!(matches!(table.send(player, shot(other_character, 0)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(player, shot(character_id, 0)).await, Outcome::DamageApplied));
+        assert!(matches!(table.send(player, shot(character_id, -3)).await, Outcome::DamageApplied));
+
+        let Outcome::Found(Some(razor)) = table.send(player, Request::GetCharacter(character_id)).await
+        else { panic!("The player should be able to look up their own character.") };
+        assert_eq!((6, 6), (razor.physical_track_filled, razor.stun_track_filled));
+    }
+
+    #[tokio::test]
+    pub async fn a_delaying_character_is_passed_over_and_the_table_is_told_instead_of_waiting_on_them()
+    {
+        let table = TestTable::new().with_players(2).combat_ready().await;
+        let ((first, first_character), (second, second_character)) = (table.players[0], table.players[1]);
+
+        let delay = |character_id| Request::SitOut(SittingOut { character_id, reason: Some(SkipReason::Delaying) });
+        assert!(matches!(table.send(first, delay(second_character)).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(second, delay(second_character)).await, Outcome::SittingOutSet { advanced: None }));
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id: first_character, roll: 8 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id: second_character, roll: 15 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+        assert!(matches!(table.send(table.gm, Request::WhoGoesThisTurn).await, Outcome::MatchingEventsAre(Some(up)) if up == vec![first_character]));
+
+        // The GM can sit the last of them out too, which moves the turn on by itself.
+        assert!(matches!(table.send(table.gm, Request::SitOut(SittingOut { character_id: first_character, reason: Some(SkipReason::Absent) })).await, 
+            Outcome::SittingOutSet { advanced: Some(_) }));
+
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(first), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        let skipped = inbox.iter().flat_map(|change| change.parts()).filter_map(|change| match change
+        {
+            WhatChanged::TurnSkipped(character) => Some(*character),
+            _ => None,
+        }).collect::<Vec<CharacterId>>();
+        assert_eq!(vec![second_character, first_character], skipped);
+    }
+
+    #[tokio::test]
+    pub async fn the_table_can_ask_which_phase_the_game_is_in_and_where_it_can_go_next()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        let phase = |phase: Phase, next: Vec<Phase>| PhaseState { phase, next };
+        assert!(matches!(table.send(player, Request::GetPhase).await, Outcome::PhaseIs(state) if state == phase(Phase::Initiative, vec![Phase::PreCombat])));
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(player, Request::GetPhase).await,
+            Outcome::PhaseIs(state) if state == phase(Phase::Initiative, vec![Phase::ActionRound, Phase::PreCombat])));
+
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+        assert!(matches!(table.send(table.gm, Request::GetPhase).await, Outcome::PhaseIs(state) if state.phase == Phase::ActionRound));
+    }
+
+    #[tokio::test]
+    pub async fn each_player_is_told_only_the_requests_the_game_would_take_from_them_now()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        assert!(matches!(table.send(player, Request::QueryAllowedRequests).await, Outcome::AllowedRequestsAre(allowed) if allowed == vec!["AddInitiativeRoll", "AddInitiativeRolls"]));
+        assert!(matches!(table.send(table.gm, Request::QueryAllowedRequests).await,
+            Outcome::AllowedRequestsAre(allowed) if allowed == vec!["OverrideInitiativeRoll", "EndCombat"]));
+
+        assert!(matches!(table.send(table.gm, Request::OverrideInitiativeRoll(Roll { character_id, roll: 10 })).await, Outcome::InitiativeRollAdded));
+        assert!(matches!(table.send(player, Request::QueryAllowedRequests).await, Outcome::AllowedRequestsAre(allowed) if allowed.is_empty()));
+        assert!(matches!(table.send(table.gm, Request::QueryAllowedRequests).await,
+            Outcome::AllowedRequestsAre(allowed) if allowed == vec!["OverrideInitiativeRoll", "StartCombatRound", "EndCombat"]));
+
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+        assert!(matches!(table.send(player, Request::QueryAllowedRequests).await, Outcome::AllowedRequestsAre(allowed) if allowed.contains(&"TakeAction")));
+        assert!(matches!(table.send(table.gm, Request::QueryAllowedRequests).await, Outcome::AllowedRequestsAre(allowed) if allowed == vec!["EndCombat"]));
+    }
+
+    #[tokio::test]
+    pub async fn rolls_sent_together_are_all_kept_or_all_refused_with_a_result_for_each()
+    {
+        let table = TestTable::new().with_players(2).combat_ready().await;
+        let ((mork, morks_character), (_, elfies_character)) = (table.players[0], table.players[1]);
+        let rolls = || vec![Roll { character_id: morks_character, roll: 10 }, Roll { character_id: elfies_character, roll: 12 }];
+
+        match table.send(mork, Request::AddInitiativeRolls(rolls())).await
+        {
+            Outcome::InitiativeRollsRejected(results) =>
+            {
+                assert_eq!(vec![morks_character, elfies_character], results.iter().map(|result| result.character_id).collect::<Vec<_>>());
+                assert!(results[0].error.is_none());
+                assert!(matches!(&results[1].error, Some(err) if err.kind == ErrorKind::UnauthorizedAction));
+            },
+            _ => panic!("A roll for someone else's character should have sunk the whole submission."),
+        }
+        assert!(matches!(table.send(mork, Request::QueryAllowedRequests).await, Outcome::AllowedRequestsAre(allowed) if allowed.contains(&"AddInitiativeRoll")));
+
+        let twice = vec![Roll { character_id: morks_character, roll: 10 }, Roll { character_id: morks_character, roll: 11 }];
+        assert!(matches!(table.send(table.gm, Request::AddInitiativeRolls(twice)).await,
+            Outcome::InitiativeRollsRejected(results) if matches!(&results[1].error, Some(err) if err.kind == ErrorKind::DuplicateRoll)));
+
+        assert!(matches!(table.send(table.gm, Request::AddInitiativeRolls(rolls())).await,
+            Outcome::InitiativeRollsAdded(results) if results.iter().all(|result| result.error.is_none())));
+        assert!(matches!(table.send(table.gm, Request::StartCombatRound).await, Outcome::CombatRoundStarted));
+    }
+
+    #[tokio::test]
+    pub async fn an_archived_game_can_still_be_read_but_not_played()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, character_id) = table.players[0];
+
+        assert!(matches!(table.send(player, Request::Archive).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(table.gm, Request::Archive).await, Outcome::Archived));
+
+        assert!(matches!(table.send(player, Request::AddInitiativeRoll(Roll { character_id, roll: 10 })).await, 
+            Outcome::Error(err) if err.kind == ErrorKind::GameArchived));
+        assert!(matches!(table.send(table.gm, Request::Idempotent(IdempotencyKey::new("end-1", 1), Box::new(Request::EndCombat))).await, 
+            Outcome::Error(err) if err.kind == ErrorKind::GameArchived));
+        assert!(matches!(table.send(player, Request::GetPhase).await, Outcome::PhaseIs(_)));
+        assert!(matches!(table.send(table.gm, Request::Delete).await, Outcome::Destroyed));
+    }
+
+    #[tokio::test]
+    pub async fn deleting_or_archiving_a_game_that_is_not_there_says_so_rather_than_that_the_caller_is_not_its_gm()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let missing_game = Uuid::new_v4();
+
+        for request in [Request::Archive, Request::Delete]
+        {
+            let (reply_channel, reply) = channel();
+            let msg = Message { player_id: Some(table.gm), game_id: Some(missing_game), reply_channel, msg: request };
+            assert!(table.runner.send(msg).await.is_ok());
+            assert!(matches!(reply.await, Ok(Outcome::Error(err)) if err.kind == ErrorKind::NoMatchingGame));
+        }
+
+        let (player, _) = table.players[0];
+        assert!(matches!(table.send(player, Request::Archive).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+        assert!(matches!(table.send(player, Request::Delete).await, Outcome::Error(err) if err.kind == ErrorKind::UnauthorizedAction));
+    }
+
+    #[tokio::test]
+    pub async fn a_returning_player_is_reconnected_under_their_old_id_and_told_which_games_are_active()
+    {
+        let game_input_channel = init();
+        let (gm, game_id) = add_new_game(&game_input_channel).await;
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::Reconnect(Delivery::Channel) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        match game_receiver.await
+        {
+            Ok(Outcome::Reconnected(reconnection)) => 
+            {
+                assert_eq!(gm, reconnection.player_id);
+                assert_eq!(vec![game_id], reconnection.active_games);
+            },
+            _ => panic!("Should have received a Reconnected outcome."),
+        }
+
+        // A player id from before a restart is unknown to the runner, but is taken back all the same.
+        let forgotten_player = Uuid::new_v4();
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(forgotten_player), game_id: None, reply_channel: game_sender, msg: Request::Reconnect(Delivery::Channel) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        match game_receiver.await
+        {
+            Ok(Outcome::Reconnected(reconnection)) => 
+            {
+                assert_eq!(forgotten_player, reconnection.player_id);
+                assert!(reconnection.active_games.is_empty());
+            },
+            _ => panic!("Should have received a Reconnected outcome."),
+        }
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message { player_id: Some(forgotten_player), game_id: Some(game_id), reply_channel: game_sender, msg: Request::JoinGame };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::JoinedGame(_))));
+    }
+
+    #[tokio::test]
+    pub async fn notifications_for_a_player_whose_channel_has_closed_wait_in_their_inbox()
+    {
+        let game_input_channel = init();
+        // add_new_game drops the GM's notification receiver, so the GM is effectively offline from here on.
+        let (gm, game_id) = add_new_game(&game_input_channel).await;
+
+        let player_state = player_join_game(&game_input_channel, game_id).await;
+        let (game_sender, game_receiver) = channel();
+        let msg = Message {player_id: Some(player_state.player_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::JoinGame};
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(game_receiver.await.is_ok());
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message {player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::FetchInbox};
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        match game_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+            {
+                assert_eq!(1, notifications.len());
+                assert!(matches!(notifications.get(0).unwrap().as_ref(), WhatChanged::NewPlayer(_)));
+            },
+            _ => panic!("Should have received the GM's inbox."),
+        }
+
+        let (game_sender, game_receiver) = channel();
+        let msg = Message {player_id: Some(gm), game_id: None, reply_channel: game_sender, msg: Request::FetchInbox};
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Inbox(notifications)) if notifications.is_empty()));
+    }
+
+    #[tokio::test]
+    pub async fn when_a_combat_round_starts_the_acting_and_on_deck_players_are_told_directly()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+
+        for (roll, player) in players.iter().enumerate()
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(*player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(player).unwrap(), roll: 10 + roll as i8 }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(our_receiver.await.is_ok());
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StartCombatRound};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        // None of the players have a live channel, so their directed messages land in their inboxes.
+        let inbox_of = |player: PlayerId| {
+            let sender = sender.clone();
+            async move {
+                let (game_owned_sender, our_receiver) = channel::<Outcome>();
+                let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+                assert!(sender.send(msg).await.is_ok());
+                match our_receiver.await
+                {
+                    Ok(Outcome::Inbox(notifications)) => notifications,
+                    _ => panic!("Should have received an inbox."),
+                }
+            }
+        };
+
+        let (first, second, last) = (*players.get(3).unwrap(), *players.get(2).unwrap(), *players.get(0).unwrap());
+
+        let first_inbox = inbox_of(first).await;
+        assert!(first_inbox.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::YourTurn { character } if character == player_char_map.get(&first).unwrap())));
+
+        let second_inbox = inbox_of(second).await;
+        assert!(second_inbox.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::UpNext { character } if character == player_char_map.get(&second).unwrap())));
+
+        let last_inbox = inbox_of(last).await;
+        assert!(!last_inbox.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::YourTurn { .. } | WhatChanged::UpNext { .. })));
+    }
+
+    #[tokio::test]
+    pub async fn adding_an_initiative_roll_is_announced_to_everyone_in_the_game()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (roller, bystander) = (*players.get(0).unwrap(), *players.get(1).unwrap());
+        let character = *player_char_map.get(&roller).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(roller), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id: character, roll: 12 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+
+        for player in [gm, bystander]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+
+            match our_receiver.await
+            {
+                Ok(Outcome::Inbox(notifications)) => 
+                {
+                    assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::InitiativeAdded(id) if *id == character)));
+                },
+                _ => panic!("Should have received an inbox."),
+            }
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_gm_gets_the_full_initiative_order_at_round_start_and_a_summary_when_the_round_ends()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+
+        for (roll, player) in players.iter().enumerate()
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(*player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(player).unwrap(), roll: 10 + roll as i8 }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(our_receiver.await.is_ok());
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StartCombatRound};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        for player in players.iter().rev()
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(*player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::TakeAction(Action{character_id: *player_char_map.get(player).unwrap(), action: ActionType::Complex, roll: None, catalog: None}) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::ActionTaken)));
+
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AdvanceTurn};
+            assert!(sender.send(msg).await.is_ok());
+            assert!(our_receiver.await.is_ok());
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AdvancePass};
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::CombatRoundEnded(summary)) => assert_eq!(4, summary.combatants.len()),
+            _ => panic!("With nobody holding extra passes the round should have ended."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+            {
+                let order = notifications.iter().find_map(|msg| match msg.as_ref() { WhatChanged::CombatStarted(order) => Some(order.clone()), _ => None })
+                    .expect("The GM should have been sent the initiative order.");
+                let expected = players.iter().rev().map(|player| *player_char_map.get(player).unwrap()).collect::<Vec<CharacterId>>();
+                assert_eq!(expected, order.iter().map(|entry| entry.character).collect::<Vec<CharacterId>>());
+                assert!(order.iter().all(|entry| entry.initiative.is_some()));
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::RoundEnded(_))));
+            },
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_gm_whisper_reaches_only_its_target_and_is_kept_in_the_game_journal()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (target, bystander) = (*players.get(0).unwrap(), *players.get(1).unwrap());
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::Chat(ChatMessage{ audience: ChatAudience::Player(target), text: String::from("You hear something behind the dumpster.") }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ChatSent(_))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(target), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::Chat(ChatMessage{ audience: ChatAudience::Table, text: String::from("   ") }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::EmptyMessage, ..}))));
+
+        for (player, expected) in [(target, 1), (bystander, 0)]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+            match our_receiver.await
+            {
+                Ok(Outcome::Inbox(notifications)) => 
+                    assert_eq!(expected, notifications.iter().filter(|msg| matches!(msg.as_ref(), WhatChanged::Chat(_))).count()),
+                _ => panic!("Should have received an inbox."),
+            }
+
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::QueryChat };
+            assert!(sender.send(msg).await.is_ok());
+            match our_receiver.await
+            {
+                Ok(Outcome::ChatLog(lines)) => assert_eq!(expected, lines.len()),
+                _ => panic!("Should have received the chat log."),
+            }
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_roll_macro_invoked_by_name_is_rolled_and_announced_to_the_table()
+    {
+        let (sender, _, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (roller, bystander) = (*players.get(0).unwrap(), *players.get(1).unwrap());
+        let character_id = *player_char_map.get(&roller).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(roller), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::DefineRollMacro(MacroDefinition{ character_id, roll_macro: RollMacro::parse("Ares Predator attack: 14d6").unwrap() }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::RollMacroDefined)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(roller), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::RollDice(DiceRoll{ character_id, roll: RollSpec::Macro(String::from("Ares Predator attack")) }) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Rolled(record)) => assert_eq!(14, record.roll.dice.len()),
+            _ => panic!("The macro should have been rolled."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(roller), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{ character_id, action: ActionType::Free, roll: Some(String::from("Uzi attack")), catalog: None }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::NoSuchRollMacro, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(bystander), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::DiceRolled(record) if record.character == character_id))),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_combat_report_credits_damage_the_gm_applies_to_both_attacker_and_target()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (attacker, target) = (*player_char_map.get(players.get(0).unwrap()).unwrap(), *player_char_map.get(players.get(1).unwrap()).unwrap());
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::ApplyDamage(DamageApplication{ source: Some(attacker), target, boxes: 5, kind: DamageType::Physical, armor: None }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::DamageApplied)));
+
+        // A player may mark damage on their own character, but the attacker's player may not mark it on the target.
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(*players.get(0).unwrap()), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::ApplyDamage(DamageApplication{ source: None, target, boxes: 5, kind: DamageType::Physical, armor: None }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(*players.get(2).unwrap()), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::CombatReport(ReportScope::LastCombat) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::CombatReportIs(report)) => 
+            {
+                assert_eq!(1, report.combats);
+                assert_eq!(4, report.combatants.len());
+                assert_eq!(5, report.combatants.iter().find(|tally| tally.character == attacker).unwrap().damage_dealt);
+                assert_eq!(5, report.combatants.iter().find(|tally| tally.character == target).unwrap().damage_taken);
+            },
+            _ => panic!("Should have received the combat report."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn only_the_gm_may_export_the_journal_and_may_narrow_it_by_kind()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+        let player = *player_char_map.keys().next().unwrap();
+        let filter = JournalFilter { kinds: vec![JournalEventKind::CombatStarted], ..Default::default() };
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::ExportJournal(filter.clone()) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::ExportJournal(filter) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::JournalEntries(entries)) => 
+            {
+                assert_eq!(1, entries.len());
+                assert!(matches!(&entries.get(0).unwrap().event, JournalEvent::CombatStarted(combatants) if combatants.len() == 4));
+            },
+            _ => panic!("The GM should have received the journal."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn launching_a_staged_encounter_declares_combat_without_revealing_hidden_combatants()
+    {
+        let sender = init();
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (_, visible) = create_and_add_char(&sender, game_id).await;
+        let (_, lurker) = create_and_add_char(&sender, game_id).await;
+        let (bystander, _) = create_and_add_char(&sender, game_id).await;
+
+        let mut encounter = StagedEncounter::new("ambush");
+        encounter.combatants = vec![visible, lurker];
+        encounter.hidden.push(lurker);
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StageEncounter(encounter) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::EncounterStaged)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::LaunchEncounter(String::from("ambush")) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CombatStarted)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(bystander), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::CombatDeclared(ids) if *ids == vec![visible]))),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn an_encounters_triggers_bring_in_reinforcements_and_prompt_the_gm_when_the_round_begins()
+    {
+        let table = TestTable::new().with_players(3).seated().await;
+        let (bystander, first) = table.players[0];
+        let late = table.players[1].1;
+
+        let mut encounter = StagedEncounter::new("ambush");
+        encounter.combatants = vec![first];
+        let backup = ScriptedTrigger { round: 1, pass: 1, action: TriggerAction::Reinforcements(vec![late]) };
+        let sirens = ScriptedTrigger { round: 1, pass: 1, action: TriggerAction::Prompt(String::from("Sirens in the distance")) };
+        encounter.triggers = vec![backup.clone(), sirens.clone()];
+        assert!(matches!(table.send(table.gm, Request::StageEncounter(encounter)).await, Outcome::EncounterStaged));
+        assert!(matches!(table.send(table.gm, Request::LaunchEncounter(String::from("ambush"))).await, Outcome::CombatStarted));
+        assert!(matches!(table.send(table.gm, Request::BeginInitiativePhase).await, Outcome::InitiativePhaseStarted));
+
+        let mut inboxes = Vec::new();
+        for player_id in [table.gm, bystander]
+        {
+            let (reply_channel, reply) = channel();
+            let msg = Message { player_id: Some(player_id), game_id: None, reply_channel, msg: Request::FetchInbox };
+            assert!(table.runner.send(msg).await.is_ok());
+            match reply.await
+            {
+                Ok(Outcome::Inbox(inbox)) => inboxes.push(inbox),
+                _ => panic!("Should have received an inbox."),
+            }
+        }
+
+        let (gm_inbox, table_inbox) = (&inboxes[0], &inboxes[1]);
+        assert!(gm_inbox.iter().flat_map(|change| change.parts()).any(|change| matches!(change, WhatChanged::TriggersFired(fired) if *fired == vec![backup.clone(), sirens.clone()])));
+        assert!(table_inbox.iter().flat_map(|change| change.parts()).any(|change| matches!(change, WhatChanged::ReinforcementsArrived(arrived) if *arrived == vec![late])));
+        assert!(!table_inbox.iter().flat_map(|change| change.parts()).any(|change| matches!(change, WhatChanged::TriggersFired(_))));
+    }
+
+    #[tokio::test]
+    pub async fn initiative_reminders_go_only_to_players_who_have_not_rolled()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (rolled, slacker) = (*players.get(0).unwrap(), *players.get(1).unwrap());
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(rolled), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(&rolled).unwrap(), roll: 9 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(our_receiver.await.is_ok());
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::RemindInitiatives };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::RemindersSent(3))));
+
+        for (player, reminded) in [(rolled, false), (slacker, true)]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+            match our_receiver.await
+            {
+                Ok(Outcome::Inbox(notifications)) => assert_eq!(reminded, notifications.iter().any(|msg| matches!(msg.as_ref(), 
+                    WhatChanged::InitiativeReminder { characters } if *characters == vec![*player_char_map.get(&player).unwrap()]))),
+                _ => panic!("Should have received an inbox."),
+            }
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_batch_applies_every_request_in_order_and_reports_each_outcome()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let rolls = player_char_map.values().map(|character_id| Request::AddInitiativeRoll(Roll { character_id: *character_id, roll: 12 })).collect();
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Batch(rolls) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        match game_receiver.await
+        {
+            Ok(Outcome::BatchApplied(outcomes)) => {
+                assert_eq!(4, outcomes.len());
+                assert!(outcomes.iter().all(|outcome| matches!(outcome, Outcome::InitiativeRollAdded)));
+            },
+            _ => panic!("The batch should have been applied in full."),
+        }
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombatRound };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(!matches!(game_receiver.await, Ok(Outcome::Error(_))));
+    }
+
+    #[tokio::test]
+    pub async fn when_any_request_in_a_batch_fails_none_of_the_batch_is_applied()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+        let characters = player_char_map.values().copied().collect::<Vec<CharacterId>>();
+
+        let doomed = vec![
+            Request::AddInitiativeRoll(Roll { character_id: characters[0], roll: 12 }),
+            Request::AddInitiativeRoll(Roll { character_id: Uuid::new_v4(), roll: 12 }),
+        ];
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Batch(doomed) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(_))));
+
+        // Everyone but the first character rolls; if the first roll had survived the failed batch the round could now start.
+        let rest = characters[1..].iter().map(|character_id| Request::AddInitiativeRoll(Roll { character_id: *character_id, roll: 12 })).collect();
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Batch(rest) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::BatchApplied(_))));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombatRound };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(super::Error { kind: ErrorKind::InvalidStateAction, .. }))));
+    }
+
+    #[tokio::test]
+    pub async fn a_failed_batch_also_takes_back_what_it_changed_outside_the_game_itself()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (player, _) = *table.players.first().unwrap();
+
+        // The cast limits are kept beside the game rather than in it; a rollback that only put the game back would leave the player capped.
+        let limits = CastLimits { per_player: Some(1), ..CastLimits::default() };
+        let doomed = vec![Request::SetCastLimits(limits), Request::AddInitiativeRoll(Roll { character_id: Uuid::new_v4(), roll: 12 })];
+        assert!(matches!(table.send(table.gm, Request::Batch(doomed)).await, Outcome::Error(_)));
+
+        assert!(matches!(table.send(player, Request::AddCharacter(create_character())).await, Outcome::CharacterAdded(_)));
+    }
+
+    #[tokio::test]
+    pub async fn requests_that_reach_outside_the_game_are_refused_in_a_batch()
+    {
+        let (game_input_channel, gm_id, game_id, _) = construct_combat_ready_game().await;
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::Batch(vec![Request::QueryChat, Request::Delete]) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(super::Error { kind: ErrorKind::UnbatchableRequest, .. }))));
+    }
+
+    #[tokio::test]
+    pub async fn only_the_gm_may_attach_house_rules_and_broken_scripts_are_refused()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+        let player_id = *player_char_map.keys().next().unwrap();
+        let script = |source: &str| Request::AttachHouseRule(HouseRuleScript { name: String::from("wounds"), event: HouseRuleEvent::Initiative, 
+            source: String::from(source) });
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: game_sender, msg: script("roll -= 1;") };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(super::Error { kind: ErrorKind::UnauthorizedAction, .. }))));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: script("roll -= ;") };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(super::Error { kind: ErrorKind::InvalidHouseRule, .. }))));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: script("roll -= 1;") };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::HouseRuleAttached)));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::QueryHouseRules };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::HouseRulesAre(rules)) => assert_eq!(vec![(String::from("wounds"), HouseRuleEvent::Initiative)], rules),
+            _ => panic!("The GM should be able to list the house rules."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn advancing_the_turn_returns_who_is_up_and_on_deck_with_their_initiatives()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+        let mut order = player_char_map.iter().map(|(player, character)| (*player, *character)).collect::<Vec<(PlayerId, CharacterId)>>();
+        order.sort();
+
+        for (roll, (_, character_id)) in [20, 15, 10, 5].into_iter().zip(order.iter())
+        {
+            let (game_sender, game_receiver) = channel::<Outcome>();
+            let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::AddInitiativeRoll(Roll { character_id: *character_id, roll }) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+            assert!(game_receiver.await.is_ok());
+        }
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombatRound };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        let (player_id, character_id) = order[0];
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id, action: ActionType::Complex, roll: None, catalog: None }) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::ActionTaken)));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::AdvanceTurn };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::TurnAdvanced(advanced)) =>
+            {
+                assert_eq!(vec![InitiativeEntry { character: order[1].1, initiative: Some(15) }], advanced.up);
+                assert_eq!(vec![InitiativeEntry { character: order[2].1, initiative: Some(10) }], advanced.on_deck);
+            },
+            _ => panic!("Advancing the turn should say who is now up and on deck."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_last_advance_of_a_round_offers_the_next_initiative_phase_and_continuing_starts_it()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+        let mut order = player_char_map.iter().map(|(player, character)| (*player, *character)).collect::<Vec<(PlayerId, CharacterId)>>();
+        order.sort();
+
+        for (roll, (_, character_id)) in [20, 15, 10, 5].into_iter().zip(order.iter())
+        {
+            let (game_sender, game_receiver) = channel::<Outcome>();
+            let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::AddInitiativeRoll(Roll { character_id: *character_id, roll }) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+            assert!(game_receiver.await.is_ok());
+        }
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::StartCombatRound };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        let mut last = None;
+        for (player_id, character_id) in order
+        {
+            let (game_sender, game_receiver) = channel::<Outcome>();
+            let msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::TakeAction(Action { character_id, action: ActionType::Complex, roll: None, catalog: None }) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+            assert!(matches!(game_receiver.await, Ok(Outcome::ActionTaken)));
+
+            let (game_sender, game_receiver) = channel::<Outcome>();
+            let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::AdvanceTurn };
+            assert!(game_input_channel.send(msg).await.is_ok());
+            last = game_receiver.await.ok();
+        }
+        assert!(matches!(last, Some(Outcome::PassEnded(AfterPass::NextRound))));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::ContinueCombat };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::InitiativePhaseStarted)));
+    }
+
+    #[tokio::test]
+    pub async fn a_deleted_player_leaves_an_anonymous_fighter_behind_and_a_deleted_gm_ends_their_game()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+        let (leaver, character_id) = player_char_map.iter().map(|(player, character)| (*player, *character)).next().unwrap();
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(leaver), game_id: None, reply_channel: game_sender, msg: Request::DeleteAccount };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::AccountDeleted)));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::GetCharacter(character_id) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::Found(Some(character))) => assert_eq!("Departed runner", &*character.name),
+            _ => panic!("A character still in the fight should stay in the cast under the placeholder name."),
+        }
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: None, reply_channel: game_sender, msg: Request::DeleteAccount };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::AccountDeleted)));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: None, reply_channel: game_sender, msg: Request::DeleteAccount };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Error(_))));
+
+        let remaining = *player_char_map.keys().find(|player| **player != leaver).unwrap();
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(remaining), game_id: None, reply_channel: game_sender, msg: Request::Enumerate(GameQuery::default()) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::Summaries(games)) => assert!(games.iter().all(|(listed, _)| *listed != game_id)),
+            _ => panic!("The game list should come back without the deleted GM's game."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_cloned_game_seats_the_same_players_with_their_characters_ready_for_a_new_fight()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::CloneGame };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        let rematch_id = match game_receiver.await
+        {
+            Ok(Outcome::Created(rematch_id)) => rematch_id,
+            _ => panic!("Cloning a game should hand back the new game's id."),
+        };
+        assert_ne!(game_id, rematch_id);
+
+        for (player_id, character_id) in player_char_map.iter()
+        {
+            let (game_sender, game_receiver) = channel::<Outcome>();
+            let msg = Message { player_id: Some(*player_id), game_id: Some(rematch_id), reply_channel: game_sender, msg: Request::GetCharacter(*character_id) };
+            assert!(game_input_channel.send(msg).await.is_ok());
+            assert!(matches!(game_receiver.await, Ok(Outcome::Found(Some(_)))));
+        }
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(rematch_id), reply_channel: game_sender, msg: Request::StartCombat(player_char_map.values().copied().collect()) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::CombatStarted)));
+    }
+
+    #[tokio::test]
+    pub async fn players_whose_channels_have_closed_are_reported_to_the_gm_and_counted_until_they_reconnect()
+    {
+        let (game_input_channel, gm_id, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: None, reply_channel: game_sender, msg: Request::FetchInbox };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+            {
+                let reported = notifications.iter().filter(|notification| matches!(notification.as_ref(), WhatChanged::PlayerUnreachable(_))).count();
+                assert_eq!(player_char_map.len(), reported);
+            },
+            _ => panic!("Should have received the GM's inbox."),
+        }
+
+        let player_id = *player_char_map.keys().next().unwrap();
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player_id), game_id: None, reply_channel: game_sender, msg: Request::Reconnect(Delivery::Channel) };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        assert!(matches!(game_receiver.await, Ok(Outcome::Reconnected(_))));
+
+        let (game_sender, game_receiver) = channel::<Outcome>();
+        let msg = Message { player_id: Some(gm_id), game_id: Some(game_id), reply_channel: game_sender, msg: Request::QueryDeliveryHealth };
+        assert!(game_input_channel.send(msg).await.is_ok());
+        match game_receiver.await
+        {
+            Ok(Outcome::DeliveryHealthIs(health)) =>
+            {
+                for (player, record) in health.iter().filter(|(player, _)| player_char_map.contains_key(player))
+                {
+                    assert!(record.failed > 0);
+                    assert_eq!(*player != player_id, record.unreachable);
+                }
+            },
+            _ => panic!("The GM should be able to see how notifications are getting through."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn players_who_read_only_their_inbox_are_not_reported_to_the_gm_as_unreachable()
+    {
+        let table = TestTable::new().with_players(2).combat_ready().await;
+        for (player, _) in table.players.iter()
+        {
+            assert!(matches!(table.send(*player, Request::Reconnect(Delivery::Inbox)).await, Outcome::Reconnected(_)));
+        }
+        assert!(matches!(table.send(table.gm, Request::FetchInbox).await, Outcome::Inbox(_)));
+
+        assert!(matches!(table.send(table.gm, Request::RemindInitiatives).await, Outcome::RemindersSent(2)));
+
+        let Outcome::Inbox(gm_inbox) = table.send(table.gm, Request::FetchInbox).await
+        else { panic!("Should have received the GM's inbox.") };
+        assert!(!gm_inbox.iter().any(|notification| matches!(notification.as_ref(), WhatChanged::PlayerUnreachable(_))));
+        let (player, _) = table.players[0];
+        let Outcome::Inbox(player_inbox) = table.send(player, Request::FetchInbox).await
+        else { panic!("Should have received the player's inbox.") };
+        assert!(player_inbox.iter().any(|notification| matches!(notification.as_ref(), WhatChanged::InitiativeReminder { .. })));
+
+        let Outcome::DeliveryHealthIs(health) = table.send(table.gm, Request::QueryDeliveryHealth).await
+        else { panic!("The GM should be able to see how notifications are getting through.") };
+        for (_, record) in health.iter().filter(|(player, _)| table.players.iter().any(|(seated, _)| seated == player))
+        {
+            assert!(record.inbox_only && !record.unreachable);
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_gm_may_roll_and_act_only_for_characters_whose_players_are_absent_and_the_journal_says_so()
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        let (absentee, present) = (*players.get(3).unwrap(), *players.get(2).unwrap());
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(absentee), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::SetPlayerAbsent(PlayerAbsence{ player_id: absentee, absent: true }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::SetPlayerAbsent(PlayerAbsence{ player_id: absentee, absent: true }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::PlayerAbsenceSet)));
+
+        for (roll, player) in players.iter().enumerate()
+        {
+            let roller = if *player == absentee { gm } else { *player };
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(roller), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(player).unwrap(), roll: 10 + roll as i8 }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StartCombatRound};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id: *player_char_map.get(&present).unwrap(), action: ActionType::Complex, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id: *player_char_map.get(&absentee).unwrap(), action: ActionType::Complex, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ActionTaken)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::ExportJournal(JournalFilter { kinds: vec![JournalEventKind::GmProxy], ..Default::default() }) };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::JournalEntries(entries)) => 
+            {
+                assert_eq!(2, entries.len());
+                assert!(entries.iter().all(|entry| matches!(entry.event, JournalEvent::GmProxy{ player, .. } if player == absentee)));
+            },
+            _ => panic!("The GM should have received the journal."),
+        }
+    }
+
+    async fn start_round_with_absence_policy(policy: AbsencePolicy) -> (Sender<Message>, PlayerId, GameId, HashMap<PlayerId, CharacterId>, Vec<PlayerId>)
+    {
+        let (sender, gm, game_id, player_char_map) = construct_combat_ready_game().await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::SetAbsencePolicy(Some(policy)) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::AbsencePolicySet)));
+
+        let mut players = player_char_map.keys().copied().collect::<Vec<PlayerId>>();
+        players.sort();
+        for (roll, player) in players.iter().enumerate()
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(*player), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::AddInitiativeRoll(Roll{ character_id: *player_char_map.get(player).unwrap(), roll: 10 + roll as i8 }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::StartCombatRound};
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::CombatRoundStarted)));
+
+        (sender, gm, game_id, player_char_map, players)
+    }
+
+    #[tokio::test]
+    pub async fn the_review_clock_lapses_a_player_at_a_table_nobody_is_sending_anything_to()
+    {
+        let (sender, _, _, _, players) = start_round_with_absence_policy(AbsencePolicy { timeout: Duration::from_millis(100), fallback: AbsentFallback::DelegateToGm }).await;
+        let first_up = *players.get(3).unwrap();
+        tokio::spawn(super::run_review_clock(sender.clone(), Duration::from_millis(50)));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // Fetching an inbox names no game, so nothing but the clock can have lapsed the player.
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(first_up), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::PlayerAbsent{ player, absent: true } if *player == first_up))),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn a_player_who_only_polls_for_events_still_lapses()
+    {
+        let (sender, gm, game_id, player_char_map, players) = start_round_with_absence_policy(AbsencePolicy { timeout: Duration::from_millis(200), fallback: AbsentFallback::DelegateToGm }).await;
+        let first_up = *players.get(3).unwrap();
+        let character_id = *player_char_map.get(&first_up).unwrap();
+
+        for _ in 0..5
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(first_up), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::InboxAfter(None) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::InboxAfter { .. })));
+            tokio::time::sleep(Duration::from_millis(60)).await;
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id, action: ActionType::Simple, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ActionTaken)));
+    }
+
+    #[tokio::test]
+    pub async fn a_long_poll_reads_the_inbox_without_draining_it_and_is_woken_by_what_arrives_next()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, _) = table.players[0];
+
+        let Outcome::InboxAfter { notifications, .. } = table.send(player, Request::InboxAfter(None)).await
+        else { panic!("A player at the table should be able to read their inbox.") };
+        assert!(!notifications.is_empty());
+        let last = notifications.last().map(|(sequence, _)| *sequence);
+
+        let Outcome::InboxAfter { notifications, mut ready } = table.send(player, Request::InboxAfter(last)).await
+        else { panic!("A player at the table should be able to read their inbox.") };
+        assert!(notifications.is_empty());
+
+        assert!(matches!(table.send(table.gm, Request::RemindInitiatives).await, Outcome::RemindersSent(1)));
+        assert!(tokio::time::timeout(Duration::from_secs(1), ready.changed()).await.is_ok());
+        let Outcome::InboxAfter { notifications, .. } = table.send(player, Request::InboxAfter(last)).await
+        else { panic!("A player at the table should be able to read their inbox.") };
+        assert!(matches!(notifications[..], [(_, ref change)] if matches!(change.as_ref(), WhatChanged::InitiativeReminder { .. })));
+
+        // Still there for a FetchInbox, which is what drains it.
+        let Outcome::Inbox(drained) = table.send(player, Request::FetchInbox).await
+        else { panic!("A registered player should have an inbox.") };
+        assert!(drained.iter().any(|change| matches!(change.as_ref(), WhatChanged::InitiativeReminder { .. })));
+    }
+
+    #[tokio::test]
+    pub async fn a_long_poll_through_one_game_is_not_shown_what_happened_at_the_players_other_game()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player, _) = table.players[0];
+        let (_, other_game) = add_new_game(&table.runner).await;
+
+        let (reply_channel, reply) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player), game_id: Some(other_game), reply_channel, msg: Request::JoinGame };
+        assert!(table.runner.send(msg).await.is_ok());
+        assert!(matches!(reply.await, Ok(Outcome::JoinedGame(_))));
+
+        let Outcome::InboxAfter { notifications, .. } = table.send(player, Request::InboxAfter(None)).await
+        else { panic!("A player at the table should be able to read their inbox.") };
+        let last = notifications.last().map(|(sequence, _)| *sequence);
+
+        // Someone else sitting down at the other game is news there, and only there.
+        let (newcomer, _) = create_and_add_char(&table.runner, other_game).await;
+        let Outcome::InboxAfter { notifications, .. } = table.send(player, Request::InboxAfter(last)).await
+        else { panic!("A player at the table should be able to read their inbox.") };
+        assert!(notifications.is_empty());
+
+        let (reply_channel, reply) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player), game_id: Some(other_game), reply_channel, msg: Request::InboxAfter(last) };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::InboxAfter { notifications, .. }) = reply.await
+        else { panic!("A player at the other table should be able to read their inbox there.") };
+        assert!(notifications.iter().any(|(_, change)| matches!(change.as_ref(), WhatChanged::NewPlayer(joined) if joined.player_id == newcomer)));
+    }
+
+    #[tokio::test]
+    pub async fn a_player_who_keeps_the_table_waiting_is_delegated_to_the_gm_until_they_are_heard_from()
+    {
+        let (sender, gm, game_id, player_char_map, players) = start_round_with_absence_policy(AbsencePolicy { timeout: Duration::from_millis(200), fallback: AbsentFallback::DelegateToGm }).await;
+        let first_up = *players.get(3).unwrap();
+        let character_id = *player_char_map.get(&first_up).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id, action: ActionType::Simple, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id, action: ActionType::Simple, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ActionTaken)));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(first_up), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::PlayerAbsent{ player, absent: true } if *player == first_up))),
+            _ => panic!("Should have received an inbox."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id, action: ActionType::Simple, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::Error(super::Error{kind: ErrorKind::UnauthorizedAction, ..}))));
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(first_up), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::TakeAction(Action{character_id, action: ActionType::Simple, roll: None, catalog: None}) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::ActionTaken)));
+    }
+
+    #[tokio::test]
+    pub async fn with_the_skip_fallback_a_lapsed_players_turn_is_passed_over_for_the_rest_of_the_round()
+    {
+        let (sender, gm, game_id, player_char_map, players) = start_round_with_absence_policy(AbsencePolicy { timeout: Duration::ZERO, fallback: AbsentFallback::Skip }).await;
+        let (first_up, bystander) = (*players.get(3).unwrap(), *players.get(0).unwrap());
+        let character_id = *player_char_map.get(&first_up).unwrap();
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::AdvanceTurn};
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::TurnAdvanced(advanced)) => assert!(advanced.up.iter().all(|entry| entry.character != character_id)),
+            _ => panic!("With the skipped character resolved the GM should be able to move the turn on."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(bystander), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+                assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::TurnSkipped(skipped) if *skipped == character_id))),
+            _ => panic!("Should have received an inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn players_are_told_how_hurt_an_npc_looks_while_the_gm_is_told_its_exact_boxes()
+    {
+        let sender = init();
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (player, _) = create_and_add_char(&sender, game_id).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddCharacter(Character::new_npc(Metatypes::Orc, String::from("Ganger"))) };
+        assert!(sender.send(msg).await.is_ok());
+        let ganger = match our_receiver.await
+        {
+            Ok(Outcome::CharacterAdded((_, character_id))) => character_id,
+            _ => panic!("The GM should have been able to add an NPC."),
+        };
+
+        for options in [VisibilityOptions::default(), VisibilityOptions { npc_wounds: WoundDisclosure::Hidden }]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::SetVisibility(options) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::VisibilitySet)));
+
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+                msg: Request::ApplyDamage(DamageApplication{ source: None, target: ganger, boxes: 1, kind: DamageType::Physical, armor: None }) };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(matches!(our_receiver.await, Ok(Outcome::DamageApplied)));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+            {
+                let seen = notifications.iter().filter_map(|msg| match msg.as_ref()
+                {
+                    WhatChanged::CharacterDamaged { character, wounds } if *character == ganger => Some(*wounds),
+                    _ => None,
+                }).collect::<Vec<WoundView>>();
+                assert_eq!(vec![WoundView::Tier(WoundTier::LightlyHurt)], seen);
+            },
+            _ => panic!("Should have received an inbox."),
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::Inbox(notifications)) => 
+            {
+                let boxes = notifications.iter().filter_map(|msg| match msg.as_ref()
+                {
+                    WhatChanged::CharacterDamaged { wounds: WoundView::Exact(monitor), .. } => Some(monitor.physical_filled),
+                    _ => None,
+                }).collect::<Vec<i8>>();
+                assert_eq!(vec![1, 2], boxes);
+            },
+            _ => panic!("Should have received the GM's inbox."),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn once_initiative_has_been_open_past_its_timeout_the_npcs_are_rolled_for_and_the_players_still_to_roll_are_reminded()
+    {
+        let sender = init();
+        let (gm, game_id) = add_new_game(&sender).await;
+        let (prompt, prompt_char) = create_and_add_char(&sender, game_id).await;
+        let (straggler, straggler_char) = create_and_add_char(&sender, game_id).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddCharacter(Character::new_npc(Metatypes::Troll, String::from("Bouncer"))) };
+        assert!(sender.send(msg).await.is_ok());
+        let bouncer = match our_receiver.await
+        {
+            Ok(Outcome::CharacterAdded((_, character_id))) => character_id,
+            _ => panic!("The GM should have been able to add an NPC."),
+        };
+
+        for request in [Request::SetInitiativeTimeout(Some(Duration::from_millis(200))), Request::StartCombat(vec![prompt_char, straggler_char, bouncer]), 
+            Request::BeginInitiativePhase]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: request };
+            assert!(sender.send(msg).await.is_ok());
+            assert!(!matches!(our_receiver.await, Ok(Outcome::Error(_))));
+        }
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(prompt), game_id: Some(game_id), reply_channel: game_owned_sender, 
+            msg: Request::AddInitiativeRoll(Roll{ character_id: prompt_char, roll: 12 }) };
+        assert!(sender.send(msg).await.is_ok());
+        assert!(matches!(our_receiver.await, Ok(Outcome::InitiativeRollAdded)));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let (game_owned_sender, our_receiver) = channel::<Outcome>();
+        let msg = Message{ player_id: Some(gm), game_id: Some(game_id), reply_channel: game_owned_sender, msg: Request::PreviewInitiativeOrder };
+        assert!(sender.send(msg).await.is_ok());
+        match our_receiver.await
+        {
+            Ok(Outcome::InitiativePreviewIs(preview)) =>
+            {
+                assert!(preview.order.iter().any(|(character_id, _)| *character_id == bouncer));
+                assert_eq!(vec![straggler_char], preview.outstanding);
+            },
+            _ => panic!("The GM should have been shown the initiative order."),
+        }
+
+        for (player, reminded) in [(prompt, Vec::new()), (straggler, vec![straggler_char])]
+        {
+            let (game_owned_sender, our_receiver) = channel::<Outcome>();
+            let msg = Message{ player_id: Some(player), game_id: None, reply_channel: game_owned_sender, msg: Request::FetchInbox };
+            assert!(sender.send(msg).await.is_ok());
+            match our_receiver.await
+            {
+                Ok(Outcome::Inbox(notifications)) =>
+                {
+                    let reminders = notifications.iter().filter_map(|msg| match msg.as_ref()
+                    {
+                        WhatChanged::InitiativeReminder { characters } => Some(characters.clone()),
+                        _ => None,
+                    }).flatten().collect::<Vec<CharacterId>>();
+                    assert_eq!(reminded, reminders);
+                    assert!(notifications.iter().any(|msg| matches!(msg.as_ref(), WhatChanged::InitiativeAdded(character_id) if *character_id == bouncer)));
+                },
+                _ => panic!("Should have received an inbox."),
+            }
+        }
+    }
+
+    #[tokio::test]
+    pub async fn the_review_clock_fires_an_initiative_deadline_at_a_table_nobody_is_sending_anything_to()
+    {
+        let table = TestTable::new().with_players(1).seated().await;
+        let (player, character_id) = table.players[0];
+        tokio::spawn(super::run_review_clock(table.runner.clone(), Duration::from_millis(50)));
+
+        for request in [Request::SetInitiativeTimeout(Some(Duration::from_millis(100))), Request::StartCombat(vec![character_id]), Request::BeginInitiativePhase]
+        {
+            assert!(!matches!(table.send(table.gm, request).await, Outcome::Error(_)));
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // Fetching an inbox names no game, so nothing but the clock can have looked at the deadline.
+        let (reply_channel, reply) = channel();
+        let msg = Message { player_id: Some(player), game_id: None, reply_channel, msg: Request::FetchInbox };
+        assert!(table.runner.send(msg).await.is_ok());
+        let Ok(Outcome::Inbox(inbox)) = reply.await
+        else { panic!("Should have received the player's inbox.") };
+        assert!(inbox.iter().flat_map(|change| change.parts())
+            .any(|change| matches!(change, WhatChanged::InitiativeReminder { characters } if *characters == vec![character_id])));
+    }
+}
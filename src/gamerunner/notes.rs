@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use super::CharacterId;
+
+// What the GM keeps to themselves about the cast and the staged encounters: notes, who someone really is, numbers the sheet does not
+// show.  None of it lives on the Game or its characters, so nothing that reads the game for a player can pick it up; the only way out is
+// the GM's own query.
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum NoteSubject
+{
+    Character(CharacterId),
+    // A staged encounter, by name.
+    Encounter(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct GmAnnotation
+{
+    pub notes: String,
+    pub true_name: Option<String>,
+    pub secret_stats: HashMap<String, i8>,
+}
+
+#[derive(Default, Clone)]
+pub struct GmNotes
+{
+    annotations: HashMap<NoteSubject, GmAnnotation>,
+}
+
+impl GmNotes
+{
+    // Replaces whatever the GM had on the subject; None clears it.  Returns what was there before.
+    pub fn annotate(&mut self, subject: NoteSubject, annotation: Option<GmAnnotation>) -> Option<GmAnnotation>
+    {
+        match annotation
+        {
+            Some(annotation) => self.annotations.insert(subject, annotation),
+            None => self.annotations.remove(&subject),
+        }
+    }
+
+    pub fn get(&self, subject: &NoteSubject) -> Option<&GmAnnotation>
+    {
+        self.annotations.get(subject)
+    }
+
+    // Characters first, then encounters, each in a stable order.
+    pub fn all(&self) -> Vec<(NoteSubject, GmAnnotation)>
+    {
+        let mut annotations = self.annotations.iter().map(|(subject, annotation)| (subject.clone(), annotation.clone())).collect::<Vec<_>>();
+        annotations.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        annotations
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use super::{GmNotes, GmAnnotation, NoteSubject};
+
+    #[test]
+    pub fn annotating_a_subject_replaces_the_last_note_and_none_clears_it()
+    {
+        let mut notes = GmNotes::default();
+        let fixer = NoteSubject::Character(Uuid::new_v4());
+        let ambush = NoteSubject::Encounter(String::from("Ambush"));
+
+        assert!(notes.annotate(ambush.clone(), Some(GmAnnotation { notes: String::from("Two snipers on the roof."), ..GmAnnotation::default() })).is_none());
+        assert!(notes.annotate(fixer.clone(), Some(GmAnnotation { notes: String::from("Sold them out."), ..GmAnnotation::default() })).is_none());
+        let replaced = notes.annotate(fixer.clone(), Some(GmAnnotation { true_name: Some(String::from("Mr. Johnson")), ..GmAnnotation::default() }));
+        assert_eq!("Sold them out.", replaced.unwrap().notes);
+
+        assert_eq!(vec![fixer.clone(), ambush.clone()], notes.all().into_iter().map(|(subject, _)| subject).collect::<Vec<NoteSubject>>());
+        assert_eq!(Some(String::from("Mr. Johnson")), notes.get(&fixer).unwrap().true_name.clone());
+
+        assert!(notes.annotate(ambush.clone(), None).is_some());
+        assert!(notes.get(&ambush).is_none());
+    }
+}
@@ -1,17 +1,27 @@
 use std::sync::Arc;
+use std::time::Duration;
 
+use serde::{Serialize, Deserialize};
 use tokio::sync::mpsc::Sender as MpscSender;
-use crate::tracker::character::Metatypes;
+use uuid::Uuid;
+use crate::tracker::{character::{Metatypes, WoundView}, game::{RewindTarget, AfterPass, TimedEvent, SkipReason}, encounter::ScriptedTrigger, magic::Plane, journal::{ChatLine, RollRecord}, names::Name, rules::{Edition, ActionLabels}, catalog::CatalogAction};
 
-use super::{PlayerId, CharacterId};
+use super::{PlayerId, CharacterId, GameId, TurnAdvanced, absence::AbsencePolicy, cast_limits::CastLimits, sync::CombatSync};
 
+type Recipient = MpscSender<Arc<WhatChanged>>;
+
+// change_type goes to everyone in send_to; directed messages go only to the one channel paired with them.  game_id is the game it is
+// about, which is what a player's inbox files it under; a change about no one game, such as a player leaving all of theirs, has none.
 pub struct Notification
 {
+    pub game_id: Option<GameId>,
     pub change_type: Arc<WhatChanged>, 
     pub send_to: Vec<MpscSender<Arc<WhatChanged>>>,
+    pub directed: Vec<(Arc<WhatChanged>, MpscSender<Arc<WhatChanged>>)>,
 }
 
 // #[derive(Clone)]
+#[derive(Debug)]
 pub enum WhatChanged
 {
     NewPlayer(PlayerJoined),
@@ -19,26 +29,337 @@ pub enum WhatChanged
     StartingInitiativePhase,
     StartingCombatRound,
     PlayerActed,
+    // A character took an action from the game's catalog; the GM is told which.
+    NamedActionTaken { character: CharacterId, name: String },
+    TurnAdvanced(TurnAdvanced),
+    PassEnded(AfterPass),
+    PassAdvanced,
+    RoundAdvanced,
+    CombatStarted(Vec<InitiativeEntry>),
+    UpNext { character: CharacterId },
+    YourTurn { character: CharacterId },
+    CombatEnded,
+    // The GM rested these characters: wounds healed, armor mended, guns reloaded and Edge back.
+    CharactersRested(Vec<CharacterId>),
+    GameEnded,
+    // The game is kept for reading but will take no more play.
+    GameArchived,
+    DrainTestPending(CharacterId),
+    CombatDeclared(Vec<CharacterId>),
+    InitiativeAdded(CharacterId),
+    InitiativeAdjusted { character: CharacterId, delta: i8 },
+    // A timed event the GM scheduled has come up in the order.
+    TimedEventUp(TimedEvent),
+    // The GM's view: the encounter's triggers that fired as the round or pass began.
+    TriggersFired(Vec<ScriptedTrigger>),
+    ReinforcementsArrived(Vec<CharacterId>),
+    CharacterMoved(CharacterId),
+    Engaged { attacker: CharacterId, defender: CharacterId },
+    Disengaged { character: CharacterId, from: CharacterId },
+    WentOnFullDefense(CharacterId),
+    AreaAttackResolved(CharacterId),
+    // Everyone the event hit, in the order their resistance tests were queued.
+    DamageEventApplied { source: CharacterId, targets: Vec<CharacterId> },
+    ResistanceTestMade(CharacterId),
+    SpellCast(CharacterId),
+    SpellDropped(CharacterId),
+    PassesOverridden(CharacterId),
+    ArmorDegraded(CharacterId),
+    RoundEnded(RoundSummary),
+    Chat(ChatLine),
+    DiceRolled(RollRecord),
+    CharacterDamaged { character: CharacterId, wounds: WoundView },
+    CharacterHealed { character: CharacterId, wounds: WoundView },
+    EdgeSpent(CharacterId),
+    Rewound(RewindTarget),
+    CheckpointRestored(String),
+    InitiativeReminder { characters: Vec<CharacterId> },
+    InitiativeTimeoutChanged(Option<Duration>),
+    EditionChanged(Edition),
+    ActionLabelsChanged(ActionLabels),
+    ActionCatalogChanged(Vec<CatalogAction>),
+    CastLimitsChanged(CastLimits),
+    CombatantsSynced(CombatSync),
+    JackedIn(CharacterId),
+    JackedOut(CharacterId),
+    MatrixActionTaken { decker: CharacterId, target: Uuid },
+    PlaneChanged { character: CharacterId, plane: Plane },
+    CharacterUpdated { character: CharacterId, fields: Vec<&'static str> },
+    CharacterUpdatePending(CharacterId),
+    PlayerDeparted(PlayerId),
+    GameCloned(GameId),
+    PlayerUnreachable(PlayerId),
+    PlayerAbsent { player: PlayerId, absent: bool },
+    AbsencePolicyChanged(Option<AbsencePolicy>),
+    TurnSkipped(CharacterId),
+    SittingOut { character: CharacterId, reason: Option<SkipReason> },
+    // Several changes for the same recipient out of one message, in the order they happened.
+    Composite(Vec<Arc<WhatChanged>>),
+}
+
+// The kinds of WhatChanged, named the same, which is what goes over the wire when a client is told of a change.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ChangeKind
+{
+    NewPlayer,
+    NewCharacter,
+    StartingInitiativePhase,
+    StartingCombatRound,
+    PlayerActed,
+    NamedActionTaken,
     TurnAdvanced,
+    PassEnded,
     PassAdvanced,
     RoundAdvanced,
     CombatStarted,
     UpNext,
     YourTurn,
     CombatEnded,
+    CharactersRested,
     GameEnded,
+    GameArchived,
+    DrainTestPending,
+    CombatDeclared,
+    InitiativeAdded,
+    InitiativeAdjusted,
+    TimedEventUp,
+    TriggersFired,
+    ReinforcementsArrived,
+    CharacterMoved,
+    Engaged,
+    Disengaged,
+    WentOnFullDefense,
+    AreaAttackResolved,
+    DamageEventApplied,
+    ResistanceTestMade,
+    SpellCast,
+    SpellDropped,
+    PassesOverridden,
+    ArmorDegraded,
+    RoundEnded,
+    Chat,
+    DiceRolled,
+    CharacterDamaged,
+    CharacterHealed,
+    EdgeSpent,
+    Rewound,
+    CheckpointRestored,
+    InitiativeReminder,
+    InitiativeTimeoutChanged,
+    EditionChanged,
+    ActionLabelsChanged,
+    ActionCatalogChanged,
+    CastLimitsChanged,
+    CombatantsSynced,
+    JackedIn,
+    JackedOut,
+    MatrixActionTaken,
+    PlaneChanged,
+    CharacterUpdated,
+    CharacterUpdatePending,
+    PlayerDeparted,
+    GameCloned,
+    PlayerUnreachable,
+    PlayerAbsent,
+    AbsencePolicyChanged,
+    TurnSkipped,
+    SittingOut,
+    Composite,
+}
+
+impl WhatChanged
+{
+    // Which kind of change this is, without what it carried, for telling a client what it is being handed.
+    pub fn kind(&self) -> ChangeKind
+    {
+        match self
+        {
+            WhatChanged::NewPlayer(_) => ChangeKind::NewPlayer,
+            WhatChanged::NewCharacter(_) => ChangeKind::NewCharacter,
+            WhatChanged::StartingInitiativePhase => ChangeKind::StartingInitiativePhase,
+            WhatChanged::StartingCombatRound => ChangeKind::StartingCombatRound,
+            WhatChanged::PlayerActed => ChangeKind::PlayerActed,
+            WhatChanged::NamedActionTaken { .. } => ChangeKind::NamedActionTaken,
+            WhatChanged::TurnAdvanced(_) => ChangeKind::TurnAdvanced,
+            WhatChanged::PassEnded(_) => ChangeKind::PassEnded,
+            WhatChanged::PassAdvanced => ChangeKind::PassAdvanced,
+            WhatChanged::RoundAdvanced => ChangeKind::RoundAdvanced,
+            WhatChanged::CombatStarted(_) => ChangeKind::CombatStarted,
+            WhatChanged::UpNext { .. } => ChangeKind::UpNext,
+            WhatChanged::YourTurn { .. } => ChangeKind::YourTurn,
+            WhatChanged::CombatEnded => ChangeKind::CombatEnded,
+            WhatChanged::CharactersRested(_) => ChangeKind::CharactersRested,
+            WhatChanged::GameEnded => ChangeKind::GameEnded,
+            WhatChanged::GameArchived => ChangeKind::GameArchived,
+            WhatChanged::DrainTestPending(_) => ChangeKind::DrainTestPending,
+            WhatChanged::CombatDeclared(_) => ChangeKind::CombatDeclared,
+            WhatChanged::InitiativeAdded(_) => ChangeKind::InitiativeAdded,
+            WhatChanged::InitiativeAdjusted { .. } => ChangeKind::InitiativeAdjusted,
+            WhatChanged::TimedEventUp(_) => ChangeKind::TimedEventUp,
+            WhatChanged::TriggersFired(_) => ChangeKind::TriggersFired,
+            WhatChanged::ReinforcementsArrived(_) => ChangeKind::ReinforcementsArrived,
+            WhatChanged::CharacterMoved(_) => ChangeKind::CharacterMoved,
+            WhatChanged::Engaged { .. } => ChangeKind::Engaged,
+            WhatChanged::Disengaged { .. } => ChangeKind::Disengaged,
+            WhatChanged::WentOnFullDefense(_) => ChangeKind::WentOnFullDefense,
+            WhatChanged::AreaAttackResolved(_) => ChangeKind::AreaAttackResolved,
+            WhatChanged::DamageEventApplied { .. } => ChangeKind::DamageEventApplied,
+            WhatChanged::ResistanceTestMade(_) => ChangeKind::ResistanceTestMade,
+            WhatChanged::SpellCast(_) => ChangeKind::SpellCast,
+            WhatChanged::SpellDropped(_) => ChangeKind::SpellDropped,
+            WhatChanged::PassesOverridden(_) => ChangeKind::PassesOverridden,
+            WhatChanged::ArmorDegraded(_) => ChangeKind::ArmorDegraded,
+            WhatChanged::RoundEnded(_) => ChangeKind::RoundEnded,
+            WhatChanged::Chat(_) => ChangeKind::Chat,
+            WhatChanged::DiceRolled(_) => ChangeKind::DiceRolled,
+            WhatChanged::CharacterDamaged { .. } => ChangeKind::CharacterDamaged,
+            WhatChanged::CharacterHealed { .. } => ChangeKind::CharacterHealed,
+            WhatChanged::EdgeSpent(_) => ChangeKind::EdgeSpent,
+            WhatChanged::Rewound(_) => ChangeKind::Rewound,
+            WhatChanged::CheckpointRestored(_) => ChangeKind::CheckpointRestored,
+            WhatChanged::InitiativeReminder { .. } => ChangeKind::InitiativeReminder,
+            WhatChanged::InitiativeTimeoutChanged(_) => ChangeKind::InitiativeTimeoutChanged,
+            WhatChanged::EditionChanged(_) => ChangeKind::EditionChanged,
+            WhatChanged::ActionLabelsChanged(_) => ChangeKind::ActionLabelsChanged,
+            WhatChanged::ActionCatalogChanged(_) => ChangeKind::ActionCatalogChanged,
+            WhatChanged::CastLimitsChanged(_) => ChangeKind::CastLimitsChanged,
+            WhatChanged::CombatantsSynced(_) => ChangeKind::CombatantsSynced,
+            WhatChanged::JackedIn(_) => ChangeKind::JackedIn,
+            WhatChanged::JackedOut(_) => ChangeKind::JackedOut,
+            WhatChanged::MatrixActionTaken { .. } => ChangeKind::MatrixActionTaken,
+            WhatChanged::PlaneChanged { .. } => ChangeKind::PlaneChanged,
+            WhatChanged::CharacterUpdated { .. } => ChangeKind::CharacterUpdated,
+            WhatChanged::CharacterUpdatePending(_) => ChangeKind::CharacterUpdatePending,
+            WhatChanged::PlayerDeparted(_) => ChangeKind::PlayerDeparted,
+            WhatChanged::GameCloned(_) => ChangeKind::GameCloned,
+            WhatChanged::PlayerUnreachable(_) => ChangeKind::PlayerUnreachable,
+            WhatChanged::PlayerAbsent { .. } => ChangeKind::PlayerAbsent,
+            WhatChanged::AbsencePolicyChanged(_) => ChangeKind::AbsencePolicyChanged,
+            WhatChanged::TurnSkipped(_) => ChangeKind::TurnSkipped,
+            WhatChanged::SittingOut { .. } => ChangeKind::SittingOut,
+            WhatChanged::Composite(_) => ChangeKind::Composite,
+        }
+    }
+
+    // The changes this stands for: a composite's parts, or just itself.
+    pub fn parts(&self) -> Vec<&WhatChanged>
+    {
+        match self
+        {
+            WhatChanged::Composite(changes) => changes.iter().flat_map(|change| change.parts()).collect(),
+            _ => vec![self],
+        }
+    }
+}
+
+// Everything one message set off, as one delivery per recipient and game: a player owed several changes - a batch of NPCs added, everyone
+// caught in a blast - gets them together rather than woken once for each.  Changes about different games are kept apart, so each can be
+// filed under its own game if the player has to have it held for them.
+pub fn coalesce(notifications: Vec<Notification>) -> Vec<(Option<GameId>, Arc<WhatChanged>, Recipient)>
+{
+    let mut deliveries: Vec<(Option<GameId>, Vec<Arc<WhatChanged>>, Recipient)> = Vec::new();
+
+    for notification in notifications
+    {
+        let (game_id, broadcast) = (notification.game_id, notification.change_type);
+        let addressed = notification.send_to.into_iter().map(|sender| (Arc::clone(&broadcast), sender)).chain(notification.directed);
+        for (change, sender) in addressed
+        {
+            match deliveries.iter_mut().find(|(about, _, recipient)| *about == game_id && recipient.same_channel(&sender))
+            {
+                Some((_, changes, _)) => changes.push(change),
+                None => deliveries.push((game_id, vec![change], sender)),
+            }
+        }
+    }
+
+    deliveries.into_iter()
+        .map(|(game_id, mut changes, sender)| match changes.len()
+        {
+            1 => (game_id, changes.remove(0), sender),
+            _ => (game_id, Arc::new(WhatChanged::Composite(changes)), sender),
+        })
+        .collect()
+}
+
+// Players are shown where an NPC sits in the order, but only the GM sees the NPC's actual score.  NPCs staged as hidden are left out
+// of the players' order altogether.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InitiativeEntry
+{
+    pub character: CharacterId,
+    pub initiative: Option<i8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RoundSummary
+{
+    pub passes: usize,
+    pub combatants: Vec<CharacterId>,
+    pub outstanding_resistance_tests: usize,
 }
 
+#[derive(Debug)]
 pub struct PlayerJoined
 {
-    pub name: String,
+    pub name: Name,
     pub player_id: PlayerId,
 }
 
+#[derive(Debug)]
 pub struct NewCharacter
 {
     pub player_id: PlayerId,
     pub character_id: CharacterId,
     pub metatype: Metatypes,
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc::channel;
+    use uuid::Uuid;
+
+    use super::{coalesce, Notification, WhatChanged};
+
+    #[test]
+    pub fn a_recipient_owed_several_changes_gets_one_composite_in_order()
+    {
+        let (gm, _gm_inbox) = channel::<Arc<WhatChanged>>(8);
+        let (player, _player_inbox) = channel::<Arc<WhatChanged>>(8);
+        let game_id = Some(Uuid::new_v4());
+        let notifications = vec![
+            Notification { game_id, change_type: Arc::new(WhatChanged::PlayerActed), send_to: vec![gm.clone(), player.clone()], directed: Vec::new() },
+            Notification { game_id, change_type: Arc::new(WhatChanged::PassAdvanced), send_to: Vec::new(), directed: vec![(Arc::new(WhatChanged::RoundAdvanced), gm.clone())] },
+        ];
+
+        let deliveries = coalesce(notifications);
+
+        assert_eq!(2, deliveries.len());
+        let (_, to_gm, _) = deliveries.iter().find(|(_, _, sender)| sender.same_channel(&gm)).unwrap();
+        assert!(matches!(to_gm.parts()[..], [WhatChanged::PlayerActed, WhatChanged::RoundAdvanced]));
+        assert!(matches!(to_gm.as_ref(), WhatChanged::Composite(_)));
+        let (_, to_player, _) = deliveries.iter().find(|(_, _, sender)| sender.same_channel(&player)).unwrap();
+        assert!(matches!(to_player.as_ref(), WhatChanged::PlayerActed));
+    }
+
+    #[test]
+    pub fn changes_about_different_games_are_delivered_apart_even_to_the_same_player()
+    {
+        let (player, _player_inbox) = channel::<Arc<WhatChanged>>(8);
+        let (first, second) = (Some(Uuid::new_v4()), Some(Uuid::new_v4()));
+        let notifications = vec![
+            Notification { game_id: first, change_type: Arc::new(WhatChanged::PlayerActed), send_to: vec![player.clone()], directed: Vec::new() },
+            Notification { game_id: second, change_type: Arc::new(WhatChanged::PassAdvanced), send_to: vec![player.clone()], directed: Vec::new() },
+        ];
+
+        let deliveries = coalesce(notifications);
+
+        assert_eq!(2, deliveries.len());
+        assert!(matches!(&deliveries[0], (game_id, change, _) if *game_id == first && matches!(change.as_ref(), WhatChanged::PlayerActed)));
+        assert!(matches!(&deliveries[1], (game_id, change, _) if *game_id == second && matches!(change.as_ref(), WhatChanged::PassAdvanced)));
+    }
+}
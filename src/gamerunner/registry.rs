@@ -1,14 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::collections::hash_map::Entry as MapEntry;
 use std::sync::Arc;
-use log::debug;
-use tokio::sync::mpsc::Sender;
+use std::time::Instant;
+use tracing::debug;
+use tokio::sync::{mpsc::Sender, watch};
 use uuid::Uuid;
 
 use crate::tracker::character::Character;
+use crate::tracker::names::{Name, NameTable};
 use crate::tracker::game::Game;
 
-use super::{WhatChanged, CharacterId};
+use super::{WhatChanged, CharacterId, PLAYER_CHANNEL_CAPACITY, absence::AbsenceWatch, deadline::InitiativeDeadline, notes::GmNotes, cast_limits::{CastLimits, CastRefusal}, sync::CombatSyncState, lobby::{LobbyIndex, GameSummary}, replay::ReplayCache};
 
 type PlayerId = Uuid;
 type GameId = Uuid;
@@ -16,23 +18,75 @@ type GameId = Uuid;
 pub struct PlayerDirectoryEntry
 {
     pub player_id: Uuid,
-    pub player_name: String,
+    pub player_name: Name,
     pub player_games: HashSet<GameId>,
+    // The games the player is GM of, which player_games leaves out.
+    pub gm_games: HashSet<GameId>,
     pub player_characters: HashMap<GameId, HashSet<CharacterId>>,
-    pub player_sender: Sender<Arc<WhatChanged>>
+    pub player_sender: Sender<Arc<WhatChanged>>,
+    // Numbered in the order they came, counting on through whatever has been drained or pushed out, so a long-poll can ask for what
+    // came after the last one it saw.  Each is filed under the game it is about, if it is about just the one, so a game's long-poll is
+    // not handed what happened at the player's other tables.
+    pub inbox: VecDeque<(usize, Option<GameId>, Arc<WhatChanged>)>,
+    pub inbox_sequence: usize,
+    // Holds the next sequence number, moved on as the inbox takes something, so every long-poll and socket waiting on the player wakes.
+    pub inbox_ready: watch::Sender<usize>,
+    pub deliveries: DeliveryRecord,
 }
 
+// How notifications to a player have been getting on.  A channel whose receiver has gone stays gone, so the first failed send marks it
+// unreachable until the player reconnects with a new one.  A player whose client reads only the inbox never held the channel at all;
+// their notifications going to the inbox is how they get them, not a failure.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DeliveryRecord
+{
+    pub failed: usize,
+    pub unreachable: bool,
+    pub inbox_only: bool,
+}
+
+// Notifications held for a player with no live channel.  Past this many the oldest are dropped - by then the player has missed enough
+// that the current game state is more use to them than the backlog.
+pub const INBOX_CAPACITY: usize = 64;
+
+#[derive(Clone)]
 pub struct GameDirectoryEntry
 {
     pub game: Game,
     pub gm: Uuid,
     pub players: HashSet<PlayerId>,
+    // Players the GM has marked as away from the table; the GM may roll and act for their characters.
+    pub absent: HashSet<PlayerId>,
+    pub absence_watch: AbsenceWatch,
+    pub initiative_deadline: InitiativeDeadline,
+    // Kept here rather than on the game so that nothing handed to a player can carry it.
+    pub gm_notes: GmNotes,
+    pub cast_limits: CastLimits,
+    // What the GM and the table were last sent of the combatants, for diffing the next change against.
+    pub combat_sync: CombatSyncState,
+    // Put away by the GM: kept for its reports and journal, but out of the lobby and closed to anything that would change it.
+    pub archived: bool,
+    // What the lobby calls the game; empty until the GM names it.
+    pub name: Name,
 }
 
+#[derive(Clone)]
+pub struct SavedGame
+{
+    entry: GameDirectoryEntry,
+    characters: HashMap<PlayerId, Option<HashSet<CharacterId>>>,
+}
+
+// Owned outright by the one runner task, which handles every message in turn, so none of these maps is behind a lock.  Splitting them
+// by game only pays once games run on tasks of their own; until then there is nothing else to contend with.
 pub struct GameRegistry
 {
     games: HashMap<GameId, GameDirectoryEntry>,
-    players: HashMap<PlayerId, PlayerDirectoryEntry>
+    players: HashMap<PlayerId, PlayerDirectoryEntry>,
+    names: NameTable,
+    lobby: LobbyIndex,
+    replays: ReplayCache,
+    player_channel_capacity: usize,
 }
 
 impl <'a> GameRegistry
@@ -40,7 +94,18 @@ impl <'a> GameRegistry
 
     pub fn new() -> GameRegistry
     {
-        GameRegistry { games: HashMap::new(), players: HashMap::new() }
+        GameRegistry::with_player_channel_capacity(PLAYER_CHANNEL_CAPACITY)
+    }
+
+    pub fn with_player_channel_capacity(player_channel_capacity: usize) -> GameRegistry
+    {
+        GameRegistry { games: HashMap::new(), players: HashMap::new(), names: NameTable::new(), lobby: LobbyIndex::default(), replays: ReplayCache::default(), player_channel_capacity }
+    }
+
+    // How many notifications a player's channel holds before the runner has to wait on the player to read them.
+    pub fn player_channel_capacity(&self) -> usize
+    {
+        self.player_channel_capacity
     }
 
     pub fn new_game(&'a mut self, player_id: PlayerId, game_id: GameId, game: Game) -> Result<(),()>
@@ -49,9 +114,13 @@ impl <'a> GameRegistry
         if self.players.contains_key(&player_id)
         {
             debug!("Player id {} is registered as a player.", player_id);
-            let mut directory_entry = GameDirectoryEntry{ game, gm: player_id, players: HashSet::new() };
+            let mut directory_entry = GameDirectoryEntry{ game, gm: player_id, players: HashSet::new(), absent: HashSet::new(), absence_watch: AbsenceWatch::default(),
+                initiative_deadline: InitiativeDeadline::default(), gm_notes: GmNotes::default(), cast_limits: CastLimits::default(),
+                combat_sync: CombatSyncState::default(), archived: false, name: self.names.intern("") };
             directory_entry.players.insert(player_id);
             self.games.insert(game_id, directory_entry);
+            self.players.entry(player_id).and_modify(|gm| { gm.gm_games.insert(game_id); });
+            self.refresh_summary(&game_id, true);
             Ok(())
         }
         else
@@ -61,6 +130,44 @@ impl <'a> GameRegistry
         }
     }
 
+    // Seats the same GM and players at a new game, each player keeping the characters they had in the source game.
+    pub fn clone_game(&mut self, source_id: &GameId, game_id: GameId, game: Game) -> Result<(), ()>
+    {
+        let Some(source) = self.games.get(source_id)
+        else { return Err(()) };
+
+        let players = source.players.clone();
+        let gm = source.gm;
+        // The rematch keeps the cast and the staged encounters, so the GM's notes on them still apply.
+        let gm_notes = source.gm_notes.clone();
+        let cast_limits = source.cast_limits;
+        let name = source.name.clone();
+        self.games.insert(game_id, GameDirectoryEntry { game, gm, players: players.clone(), absent: HashSet::new(), absence_watch: AbsenceWatch::default(),
+            initiative_deadline: InitiativeDeadline::default(), gm_notes, cast_limits, combat_sync: CombatSyncState::default(), archived: false, name });
+
+        if let Some(gm_entry) = self.players.get_mut(&gm)
+        {
+            gm_entry.gm_games.insert(game_id);
+        }
+        for player_id in players
+        {
+            if let Some(player_entry) = self.players.get_mut(&player_id)
+            {
+                if player_entry.player_games.contains(source_id)
+                {
+                    player_entry.player_games.insert(game_id);
+                }
+                if let Some(characters) = player_entry.player_characters.get(source_id).cloned()
+                {
+                    player_entry.player_characters.insert(game_id, characters);
+                }
+            }
+        }
+        self.refresh_summary(&game_id, true);
+
+        Ok(())
+    }
+
     pub fn get_mut_game(&'a mut self, id: &GameId) -> Option<&'a mut Game>
     {
         if let Some(dir_entry) = self.games.get_mut(id)
@@ -82,6 +189,7 @@ impl <'a> GameRegistry
 
     pub fn register_player(&mut self, player_id: PlayerId, player_comm_channel: Sender<Arc<WhatChanged>>) -> Result<(), ()>
     {
+        let player_name = self.names.intern("");
         match self.players.entry(player_id)
         {
             MapEntry::Occupied(_) => Err(()),
@@ -89,10 +197,14 @@ impl <'a> GameRegistry
             {
                 vacant.insert(PlayerDirectoryEntry 
                 {
-                    player_name: String::from(""),  
-                    player_id, player_games: HashSet::new(), 
+                    player_name,
+                    player_id, player_games: HashSet::new(), gm_games: HashSet::new(),
                     player_characters: HashMap::new(), 
-                    player_sender: player_comm_channel 
+                    player_sender: player_comm_channel,
+                    inbox: VecDeque::new(),
+                    inbox_sequence: 0,
+                    inbox_ready: watch::channel(0).0,
+                    deliveries: DeliveryRecord::default(),
                 });
                 Ok(())
             },
@@ -110,6 +222,7 @@ impl <'a> GameRegistry
 
             game_dir.players.insert(player_id);
             player_dir.player_games.insert(game_id);
+            self.refresh_summary(&game_id, true);
 
             Ok(())
         }
@@ -121,6 +234,92 @@ impl <'a> GameRegistry
         }
     }
 
+    // Swaps in a fresh notification channel for a player whose old one went away with their connection.
+    pub fn replace_player_sender(&mut self, player_id: &PlayerId, player_comm_channel: Sender<Arc<WhatChanged>>) -> Result<(), ()>
+    {
+        let player_entry = self.players.get_mut(player_id).ok_or(())?;
+        player_entry.player_sender = player_comm_channel;
+        player_entry.deliveries.unreachable = false;
+
+        Ok(())
+    }
+
+    pub fn set_inbox_only(&mut self, player_id: &PlayerId, inbox_only: bool) -> Result<(), ()>
+    {
+        let player_entry = self.players.get_mut(player_id).ok_or(())?;
+        player_entry.deliveries.inbox_only = inbox_only;
+
+        Ok(())
+    }
+
+    // Counts a notification that could not be sent to the player.  Returns true the first time the player's current channel fails, which
+    // is when it is worth telling anyone.  An inbox-only player's channel was never meant to be read, so nothing is counted for them.
+    pub fn record_failed_delivery(&mut self, player_id: &PlayerId) -> bool
+    {
+        let Some(player_entry) = self.players.get_mut(player_id)
+        else { return false };
+        if player_entry.deliveries.inbox_only
+        {
+            return false;
+        }
+
+        player_entry.deliveries.failed += 1;
+        !std::mem::replace(&mut player_entry.deliveries.unreachable, true)
+    }
+
+    pub fn delivery_record(&self, player_id: &PlayerId) -> Option<DeliveryRecord>
+    {
+        self.players.get(player_id).map(|entry| entry.deliveries)
+    }
+
+    pub fn player_for_sender(&self, sender: &Sender<Arc<WhatChanged>>) -> Option<PlayerId>
+    {
+        self.players.values().find(|entry| entry.player_sender.same_channel(sender)).map(|entry| entry.player_id)
+    }
+
+    // A composite is unpacked, so the inbox holds - and its capacity counts - one change per entry.
+    pub fn store_in_inbox(&mut self, player_id: &PlayerId, game_id: Option<GameId>, notification: Arc<WhatChanged>) -> Result<(), ()>
+    {
+        if let WhatChanged::Composite(changes) = notification.as_ref()
+        {
+            return changes.iter().try_for_each(|change| self.store_in_inbox(player_id, game_id, Arc::clone(change)));
+        }
+
+        let player_entry = self.players.get_mut(player_id).ok_or(())?;
+
+        if player_entry.inbox.len() >= INBOX_CAPACITY
+        {
+            player_entry.inbox.pop_front();
+        }
+        player_entry.inbox.push_back((player_entry.inbox_sequence, game_id, notification));
+        player_entry.inbox_sequence += 1;
+        // Stored whether or not anyone is subscribed; a poll subscribed when it read the inbox sees the change even if it is not waiting yet.
+        player_entry.inbox_ready.send_replace(player_entry.inbox_sequence);
+
+        Ok(())
+    }
+
+    pub fn drain_inbox(&mut self, player_id: &PlayerId) -> Option<Vec<Arc<WhatChanged>>>
+    {
+        let player_entry = self.players.get_mut(player_id)?;
+
+        Some(player_entry.inbox.drain(..).map(|(_, _, notification)| notification).collect())
+    }
+
+    // What the inbox holds about the given game after the given number, left in place, along with what to wait on for more.  Notices
+    // about no game in particular are shown whichever game asks.  The receiver is subscribed here, alongside the read, so nothing stored
+    // after the read can slip past it.
+    pub fn inbox_after(&self, player_id: &PlayerId, game_id: &GameId, after: Option<usize>) -> Option<(Vec<(usize, Arc<WhatChanged>)>, watch::Receiver<usize>)>
+    {
+        let player_entry = self.players.get(player_id)?;
+        let held = player_entry.inbox.iter()
+            .filter(|(sequence, about, _)| after.map_or(true, |after| *sequence > after) && about.map_or(true, |about| about == *game_id))
+            .map(|(sequence, _, notification)| (*sequence, Arc::clone(notification)))
+            .collect();
+
+        Some((held, player_entry.inbox_ready.subscribe()))
+    }
+
     pub fn get_player_sender(&self, player_id: &PlayerId) -> Option<Sender<Arc<WhatChanged>>>
     {
         if let Some(players) = self.players.get(&player_id)
@@ -178,11 +377,18 @@ impl <'a> GameRegistry
         Some(&player_entry.player_games)
     }
 
-    pub fn player_name(&self, player_id: &PlayerId) -> Option<&str>
+    pub fn games_run_by(&self, player_id: PlayerId) -> Option<&HashSet<GameId>>
+    {
+        let player_entry = self.players.get(&player_id)?;
+
+        Some(&player_entry.gm_games)
+    }
+
+    pub fn player_name(&self, player_id: &PlayerId) -> Option<Name>
     {
         let player_entry = self.players.get(player_id)?;
 
-        Some(player_entry.player_name.as_str())
+        Some(player_entry.player_name.clone())
     }
 
     pub fn players_by_game(&self, game_id: &GameId) -> Option<&HashSet<PlayerId>>
@@ -205,6 +411,7 @@ impl <'a> GameRegistry
             {
                 let removed_player = game_entry.get_mut().players.remove(&player_id);
                 let removed_game = player_entry.get_mut().player_games.remove(&game_id);
+                self.refresh_summary(&game_id, true);
                 if !removed_player || !removed_game
                 {
                     return Err(());
@@ -240,16 +447,19 @@ impl <'a> GameRegistry
     {
         if let Some(game) = self.games.remove(&game_id)
         {
+            self.lobby.remove(&game_id);
             for player in game.players.iter()
             {
                 match self.players.entry(*player)
                 {
                     MapEntry::Occupied(mut player_entry) => {
                         player_entry.get_mut().player_games.remove(&game_id);
+                        player_entry.get_mut().gm_games.remove(&game_id);
                     },
                     MapEntry::Vacant(_) => {}
                 }
             }
+            self.names.prune();
 
             Ok(game)
         }
@@ -259,6 +469,21 @@ impl <'a> GameRegistry
         }
     }
 
+    pub fn archive_game(&mut self, game_id: &GameId) -> Result<(), ()>
+    {
+        let Some(entry) = self.games.get_mut(game_id)
+        else { return Err(()) };
+
+        entry.archived = true;
+        self.lobby.remove(game_id);
+        Ok(())
+    }
+
+    pub fn is_archived(&self, game_id: &GameId) -> bool
+    {
+        self.games.get(game_id).map_or(false, |entry| entry.archived)
+    }
+
     pub fn unregister_player(&mut self, player_id: PlayerId) -> Result<(), ()>
     {
         if let Some(player) = self.players.remove(&player_id)
@@ -275,7 +500,10 @@ impl <'a> GameRegistry
                     },
                     MapEntry::Vacant(_) => {}
                 }
+                self.refresh_summary(&game_id, true);
             }
+            self.names.prune();
+            self.replays.forget(&player_id);
 
             Ok(())
         }
@@ -310,16 +538,149 @@ impl <'a> GameRegistry
         }
     }
 
-    pub fn add_character(&mut self, player_id: &PlayerId, game_id: &GameId, character: Character) -> Option<CharacterId>
+    // Everything a batch can change about one game - its directory entry whole, and the characters each of its players holds in it - so
+    // that a batch that fails part way through can be put back exactly as it was found.
+    pub fn save_game(&self, game_id: &GameId) -> Option<SavedGame>
+    {
+        let entry = self.games.get(game_id)?.clone();
+        let characters = entry.players.iter().chain(std::iter::once(&entry.gm))
+            .filter_map(|player_id| Some((*player_id, self.players.get(player_id)?.player_characters.get(game_id).cloned())))
+            .collect();
+
+        Some(SavedGame { entry, characters })
+    }
+
+    // A player who picked up characters since the save, without having held any in the game then, is left holding none.
+    pub fn restore_game(&mut self, game_id: &GameId, saved: SavedGame)
+    {
+        let SavedGame { entry, characters } = saved;
+        let since: Vec<PlayerId> = self.games.get(game_id).map_or_else(Vec::new, |current| current.players.iter().copied().collect());
+
+        for player_id in since.iter().filter(|player_id| !characters.contains_key(player_id))
+        {
+            if let Some(player_entry) = self.players.get_mut(player_id)
+            {
+                player_entry.player_characters.remove(game_id);
+            }
+        }
+        for (player_id, held) in characters
+        {
+            if let Some(player_entry) = self.players.get_mut(&player_id)
+            {
+                match held
+                {
+                    Some(held) => { player_entry.player_characters.insert(*game_id, held); },
+                    None => { player_entry.player_characters.remove(game_id); },
+                }
+            }
+        }
+
+        // Seats are put back as they were too, for a restore after a panic that may have got as far as unseating the table.
+        for player_id in since.iter().filter(|player_id| !entry.players.contains(player_id))
+        {
+            if let Some(player_entry) = self.players.get_mut(player_id)
+            {
+                player_entry.player_games.remove(game_id);
+            }
+        }
+        for player_id in entry.players.iter()
+        {
+            if let Some(player_entry) = self.players.get_mut(player_id)
+            {
+                if *player_id == entry.gm { player_entry.gm_games.insert(*game_id); } else { player_entry.player_games.insert(*game_id); }
+            }
+        }
+
+        self.games.insert(*game_id, entry);
+        self.refresh_summary(game_id, false);
+    }
+
+    // Lets go of any player's claim to a character the game's cast no longer has - one a checkpoint or rewind took back out of the cast.
+    // A character one brought back in is claimed by nobody, and so is the GM's to run.
+    pub fn drop_missing_characters(&mut self, game_id: &GameId)
+    {
+        let Some(entry) = self.games.get(game_id)
+        else { return };
+
+        for player_id in entry.players.iter()
+        {
+            if let Some(characters) = self.players.get_mut(player_id).and_then(|player_entry| player_entry.player_characters.get_mut(game_id))
+            {
+                characters.retain(|character_id| entry.game.get_cast_by_id(character_id).is_some());
+            }
+        }
+    }
+
+    // Brings the game's lobby summary up to date, marking it active now if touched says something at the table changed.  The runner calls
+    // this after every message to a game, touching it only for one that changed the game; the registry itself after anything that seats
+    // or unseats a player.
+    pub fn refresh_summary(&mut self, game_id: &GameId, touched: bool)
+    {
+        let unnamed = self.names.intern("");
+        let Some(entry) = self.games.get(game_id).filter(|entry| !entry.archived)
+        else { return };
+
+        let gm_name = self.players.get(&entry.gm).map_or(unnamed, |gm| gm.player_name.clone());
+        let characters = entry.players.iter().filter(|player_id| **player_id != entry.gm)
+            .filter_map(|player_id| self.players.get(player_id)?.player_characters.get(game_id).map(|characters| characters.len()))
+            .sum();
+        let last_activity = match self.lobby.get(game_id)
+        {
+            Some(summary) if !touched => summary.last_activity,
+            _ => Instant::now(),
+        };
+        let summary = GameSummary { name: entry.name.clone(), gm_name, players: entry.players.len(), characters, phase: entry.game.current_state(),
+            in_combat: entry.game.is_in_combat(), cast_limits: entry.cast_limits, last_activity };
+        self.lobby.record(*game_id, summary);
+    }
+
+    pub fn set_game_name(&mut self, game_id: &GameId, name: &str) -> Result<(), ()>
+    {
+        let name = self.names.intern(name);
+        let Some(entry) = self.games.get_mut(game_id)
+        else { return Err(()) };
+
+        entry.name = name;
+        Ok(())
+    }
+
+    pub fn lobby(&self) -> &LobbyIndex
+    {
+        &self.lobby
+    }
+
+    pub fn replays(&self) -> &ReplayCache
+    {
+        &self.replays
+    }
+
+    pub fn replays_mut(&mut self) -> &mut ReplayCache
+    {
+        &mut self.replays
+    }
+
+    pub fn intern_name(&mut self, name: &str) -> Name
+    {
+        self.names.intern(name)
+    }
+
+    // Holds the character to the game's cast limits before adding it; nothing is added when it falls foul of one.  The character's name
+    // is swapped for the shared copy, so every NPC stamped out under the same name points at one string.
+    pub fn add_character(&mut self, player_id: &PlayerId, game_id: &GameId, mut character: Character) -> Result<CharacterId, CastRefusal>
     {
         match (self.players.get_mut(player_id), self.games.get_mut(game_id)) {
             (Some(player_entry), Some(game)) => {
+                let owned = if game.gm == *player_id { None } 
+                    else { Some(player_entry.player_characters.get(game_id).map_or(0, |characters| characters.len())) };
+                game.cast_limits.admit(&character, &game.game.get_cast(), owned)?;
+
+                character.name = self.names.intern(&character.name);
                 let character_id = game.game.add_cast_member(character);
                 player_entry.player_characters.entry(*game_id).or_insert(HashSet::new()).insert(character_id.clone());
-                Some(character_id)
+                Ok(character_id)
             }, 
             _ => {
-                None
+                Err(CastRefusal::NoSuchGame)
             }, 
         }
         // if let Some(player_entry) = self.players.get_mut(player_id)
@@ -351,6 +712,92 @@ impl <'a> GameRegistry
         ).map(|p| p.0)   
     }
 
+    pub fn set_absent(&mut self, game_id: &GameId, player_id: PlayerId, absent: bool) -> Result<(), ()>
+    {
+        let entry = self.games.get_mut(game_id).ok_or(())?;
+        if !entry.players.contains(&player_id)
+        {
+            return Err(());
+        }
+
+        if absent
+        {
+            entry.absent.insert(player_id);
+        }
+        else
+        {
+            entry.absent.remove(&player_id);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_absent(&self, game_id: &GameId, player_id: &PlayerId) -> bool
+    {
+        self.games.get(game_id).map_or(false, |entry| entry.absent.contains(player_id))
+    }
+
+    // The absent player a character belongs to, if it belongs to one - which is when the GM may stand in for it.
+    pub fn absent_owner(&self, game_id: &GameId, char_id: &CharacterId) -> Option<PlayerId>
+    {
+        self.players_by_character(game_id, char_id).copied().filter(|owner| self.is_absent(game_id, owner))
+    }
+
+    pub fn absence_watch(&self, game_id: &GameId) -> Option<&AbsenceWatch>
+    {
+        self.games.get(game_id).map(|entry| &entry.absence_watch)
+    }
+
+    pub fn absence_watch_mut(&mut self, game_id: &GameId) -> Option<&mut AbsenceWatch>
+    {
+        self.games.get_mut(game_id).map(|entry| &mut entry.absence_watch)
+    }
+
+    pub fn cast_limits(&self, game_id: &GameId) -> Option<CastLimits>
+    {
+        self.games.get(game_id).map(|entry| entry.cast_limits)
+    }
+
+    // Only governs characters added from now on; a cast already past a new limit keeps everyone in it.
+    pub fn set_cast_limits(&mut self, game_id: &GameId, limits: CastLimits) -> Result<(), ()>
+    {
+        let Some(entry) = self.games.get_mut(game_id)
+        else { return Err(()) };
+
+        entry.cast_limits = limits;
+        Ok(())
+    }
+
+    pub fn combat_sync(&self, game_id: &GameId) -> Option<&CombatSyncState>
+    {
+        self.games.get(game_id).map(|entry| &entry.combat_sync)
+    }
+
+    pub fn combat_sync_mut(&mut self, game_id: &GameId) -> Option<&mut CombatSyncState>
+    {
+        self.games.get_mut(game_id).map(|entry| &mut entry.combat_sync)
+    }
+
+    pub fn initiative_deadline(&self, game_id: &GameId) -> Option<&InitiativeDeadline>
+    {
+        self.games.get(game_id).map(|entry| &entry.initiative_deadline)
+    }
+
+    pub fn initiative_deadline_mut(&mut self, game_id: &GameId) -> Option<&mut InitiativeDeadline>
+    {
+        self.games.get_mut(game_id).map(|entry| &mut entry.initiative_deadline)
+    }
+
+    pub fn gm_notes(&self, game_id: &GameId) -> Option<&GmNotes>
+    {
+        self.games.get(game_id).map(|entry| &entry.gm_notes)
+    }
+
+    pub fn gm_notes_mut(&mut self, game_id: &GameId) -> Option<&mut GmNotes>
+    {
+        self.games.get_mut(game_id).map(|entry| &mut entry.gm_notes)
+    }
+
     pub fn is_gm(&self, player_id: &PlayerId, game_id: &GameId) -> bool
     {
         match self.games.get(game_id)
@@ -375,11 +822,11 @@ pub mod tests
 
     use crate::{tracker::{game::Game, character::Character}, gamerunner::{WhatChanged, PlayerId, CharacterId}};
 
-    use super::GameRegistry;
+    use super::{GameRegistry, INBOX_CAPACITY, CastLimits, CastRefusal};
 
     pub fn init()
     {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
     }
 
 
@@ -589,7 +1036,7 @@ pub mod tests
         assert!(registry.register_player(player_1, player_sender).is_ok());
         assert!(registry.join_game(player_1, game_1).is_ok());
     
-        let char_id: Option<CharacterId> = registry.add_character(&player_1, &game_1, mork);
+        let char_id: Option<CharacterId> = registry.add_character(&player_1, &game_1, mork).ok();
 
         assert!(char_id.is_some());
         assert_eq!(1, registry.get_game(&game_1).unwrap().cast_size());
@@ -621,8 +1068,8 @@ pub mod tests
         assert!(registry.join_game(player_1, game_1).is_ok());
         assert!(registry.join_game(player_1, game_2).is_ok());
 
-        let dorf_id = registry.add_character(&player_1, &game_1, dorf);
-        let mork_id = registry.add_character(&player_1, &game_2, mork);
+        let dorf_id = registry.add_character(&player_1, &game_1, dorf).ok();
+        let mork_id = registry.add_character(&player_1, &game_2, mork).ok();
 
         let mut chars = registry.characters_by_player(&game_1, &player_1);
         assert!(chars.is_some());
@@ -635,6 +1082,40 @@ pub mod tests
         assert!(chars.unwrap().contains(&mork_id.unwrap()));
     }
 
+    #[test]
+    pub fn cast_limits_refuse_characters_without_adding_them_and_spare_the_gm_the_player_limit()
+    {
+        init();
+        let mut registry = GameRegistry::new();
+        let gm = PlayerId::new_v4();
+        let (gm_sender, _) = channel(32);
+        let player_1 = Uuid::new_v4();
+        let (player_sender, _) = channel(32);
+        let game_1 = Uuid::new_v4();
+
+        assert!(registry.register_player(gm, gm_sender).is_ok());
+        assert!(registry.register_player(player_1, player_sender).is_ok());
+        assert!(registry.new_game(gm, game_1, Game::new()).is_ok());
+        assert!(registry.join_game(player_1, game_1).is_ok());
+        assert!(registry.set_cast_limits(&game_1, CastLimits { per_player: Some(1), cast: Some(3), unique_names: true }).is_ok());
+
+        assert!(registry.add_character(&player_1, &game_1, Character::new_pc(crate::tracker::character::Metatypes::Orc, String::from("Mork"))).is_ok());
+        assert_eq!(Err(CastRefusal::PlayerLimitReached(1)), 
+            registry.add_character(&player_1, &game_1, Character::new_pc(crate::tracker::character::Metatypes::Elf, String::from("Elfie"))));
+        assert_eq!(Err(CastRefusal::DuplicateName(String::from("MORK"))), 
+            registry.add_character(&gm, &game_1, Character::new_npc(crate::tracker::character::Metatypes::Orc, String::from("MORK"))));
+
+        assert!(registry.add_character(&gm, &game_1, Character::new_npc(crate::tracker::character::Metatypes::Dwarf, String::from("Ganger 1"))).is_ok());
+        assert!(registry.add_character(&gm, &game_1, Character::new_npc(crate::tracker::character::Metatypes::Dwarf, String::from("Ganger 2"))).is_ok());
+        assert_eq!(Err(CastRefusal::CastFull(3)), 
+            registry.add_character(&gm, &game_1, Character::new_npc(crate::tracker::character::Metatypes::Dwarf, String::from("Ganger 3"))));
+
+        assert_eq!(3, registry.get_game(&game_1).unwrap().cast_size());
+        assert_eq!(1, registry.characters_by_player(&game_1, &player_1).unwrap().len());
+        assert_eq!(Err(CastRefusal::NoSuchGame), 
+            registry.add_character(&gm, &Uuid::new_v4(), Character::new_npc(crate::tracker::character::Metatypes::Dwarf, String::from("Ganger 4"))));
+    }
+
     #[test]
     pub fn a_player_may_enumerate_the_games_they_are_in()
     {
@@ -1305,4 +1786,139 @@ pub mod tests
         
     }
 
-}
\ No newline at end of file
+    #[test]
+    pub fn a_players_inbox_keeps_only_the_most_recent_notifications_and_empties_when_drained()
+    {
+        let mut registry = GameRegistry::new();
+        let player_id = PlayerId::new_v4();
+        let (player_sender, _) = channel(32);
+
+        assert!(registry.register_player(player_id, player_sender.clone()).is_ok());
+        assert_eq!(Some(player_id), registry.player_for_sender(&player_sender));
+
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::GameEnded)).is_ok());
+        for _ in 0..INBOX_CAPACITY
+        {
+            assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::PlayerActed)).is_ok());
+        }
+        assert!(registry.store_in_inbox(&PlayerId::new_v4(), None, Arc::new(WhatChanged::PlayerActed)).is_err());
+
+        let inbox = registry.drain_inbox(&player_id).unwrap();
+        assert_eq!(INBOX_CAPACITY, inbox.len());
+        assert!(inbox.iter().all(|notification| matches!(notification.as_ref(), WhatChanged::PlayerActed)));
+        assert!(registry.drain_inbox(&player_id).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn a_composite_held_for_an_offline_player_is_unpacked_into_their_inbox()
+    {
+        let mut registry = GameRegistry::new();
+        let player_id = PlayerId::new_v4();
+        let (player_sender, _) = channel(32);
+        assert!(registry.register_player(player_id, player_sender).is_ok());
+
+        let composite = WhatChanged::Composite(vec![Arc::new(WhatChanged::PlayerActed), Arc::new(WhatChanged::GameEnded)]);
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(composite)).is_ok());
+
+        let inbox = registry.drain_inbox(&player_id).unwrap();
+        assert_eq!(2, inbox.len());
+        assert!(matches!(inbox[0].as_ref(), WhatChanged::PlayerActed) && matches!(inbox[1].as_ref(), WhatChanged::GameEnded));
+    }
+
+    #[test]
+    pub fn the_inbox_read_after_a_number_is_left_in_place_and_numbered_on_past_what_was_drained()
+    {
+        let mut registry = GameRegistry::new();
+        let player_id = PlayerId::new_v4();
+        let (player_sender, _) = channel(32);
+        assert!(registry.register_player(player_id, player_sender).is_ok());
+
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::PlayerActed)).is_ok());
+        assert_eq!(1, registry.drain_inbox(&player_id).unwrap().len());
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::PassAdvanced)).is_ok());
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::GameEnded)).is_ok());
+
+        let game_id = Uuid::new_v4();
+        let (held, _) = registry.inbox_after(&player_id, &game_id, None).unwrap();
+        assert_eq!(vec![1, 2], held.iter().map(|(sequence, _)| *sequence).collect::<Vec<usize>>());
+        let (held, _) = registry.inbox_after(&player_id, &game_id, Some(1)).unwrap();
+        assert_eq!(1, held.len());
+        assert!(held[0].0 == 2 && matches!(held[0].1.as_ref(), WhatChanged::GameEnded));
+        assert_eq!(2, registry.drain_inbox(&player_id).unwrap().len());
+        assert!(registry.inbox_after(&PlayerId::new_v4(), &game_id, None).is_none());
+    }
+
+    #[test]
+    pub fn the_inbox_read_through_one_game_leaves_out_what_was_held_about_the_players_other_game()
+    {
+        let mut registry = GameRegistry::new();
+        let player_id = PlayerId::new_v4();
+        let (player_sender, _) = channel(32);
+        assert!(registry.register_player(player_id, player_sender).is_ok());
+        let (game_1, game_2) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(registry.store_in_inbox(&player_id, Some(game_1), Arc::new(WhatChanged::PlayerActed)).is_ok());
+        assert!(registry.store_in_inbox(&player_id, Some(game_2), Arc::new(WhatChanged::GameEnded)).is_ok());
+        assert!(registry.store_in_inbox(&player_id, None, Arc::new(WhatChanged::PassAdvanced)).is_ok());
+
+        let (held, _) = registry.inbox_after(&player_id, &game_1, None).unwrap();
+        assert_eq!(vec![0, 2], held.iter().map(|(sequence, _)| *sequence).collect::<Vec<usize>>());
+        assert!(matches!(held[0].1.as_ref(), WhatChanged::PlayerActed));
+        let (held, _) = registry.inbox_after(&player_id, &game_2, None).unwrap();
+        assert_eq!(vec![1, 2], held.iter().map(|(sequence, _)| *sequence).collect::<Vec<usize>>());
+        assert!(matches!(held[0].1.as_ref(), WhatChanged::GameEnded));
+        // Draining is not done through a game, and takes the lot.
+        assert_eq!(3, registry.drain_inbox(&player_id).unwrap().len());
+    }
+
+    #[tokio::test]
+    pub async fn everyone_waiting_on_a_players_inbox_is_woken_by_what_it_takes()
+    {
+        let mut registry = GameRegistry::new();
+        let player_id = PlayerId::new_v4();
+        let (player_sender, _) = channel(32);
+        assert!(registry.register_player(player_id, player_sender).is_ok());
+        let game_id = Uuid::new_v4();
+
+        // A socket and a long-poll both open on the same player, neither yet waiting when the notice lands.
+        let (_, mut socket) = registry.inbox_after(&player_id, &game_id, None).unwrap();
+        let (_, mut poll) = registry.inbox_after(&player_id, &game_id, None).unwrap();
+        assert!(registry.store_in_inbox(&player_id, Some(game_id), Arc::new(WhatChanged::PlayerActed)).is_ok());
+
+        let woken = std::time::Duration::from_secs(1);
+        assert!(tokio::time::timeout(woken, socket.changed()).await.map_or(false, |changed| changed.is_ok()));
+        assert!(tokio::time::timeout(woken, poll.changed()).await.map_or(false, |changed| changed.is_ok()));
+    }
+
+    #[test]
+    pub fn the_lobby_summary_follows_players_in_and_out_and_goes_with_the_game()
+    {
+        let mut registry = GameRegistry::new();
+        let (gm, player) = (PlayerId::new_v4(), PlayerId::new_v4());
+        let game_id = Uuid::new_v4();
+        let (sender, _) = channel(32);
+        assert!(registry.register_player(gm, sender.clone()).is_ok());
+        assert!(registry.register_player(player, sender).is_ok());
+
+        assert!(registry.new_game(gm, game_id, Game::new()).is_ok());
+        let opened = registry.lobby().get(&game_id).cloned().unwrap();
+        assert_eq!(1, opened.players);
+        assert!(!opened.in_combat);
+
+        assert!(registry.join_game(player, game_id).is_ok());
+        assert_eq!(2, registry.lobby().get(&game_id).unwrap().players);
+        assert!(registry.lobby().get(&game_id).unwrap().last_activity >= opened.last_activity);
+        assert!(registry.leave_game(player, game_id).is_ok());
+        assert_eq!(1, registry.lobby().get(&game_id).unwrap().players);
+
+        let seen = registry.lobby().get(&game_id).unwrap().last_activity;
+        assert!(registry.set_game_name(&game_id, "Harbour Job").is_ok());
+        registry.refresh_summary(&game_id, false);
+        assert_eq!("Harbour Job", &*registry.lobby().get(&game_id).unwrap().name);
+        assert_eq!(seen, registry.lobby().get(&game_id).unwrap().last_activity);
+
+        assert!(registry.delete_game(game_id).is_ok());
+        assert!(registry.lobby().get(&game_id).is_none());
+        assert_eq!(0, registry.lobby().iter().count());
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{GameId, PlayerId, dispatcher::Outcome};
+
+// Outcomes remembered per player.  Clients retry within moments of the first attempt, so only the latest few keys need to be kept.
+pub const REPLAY_DEPTH: usize = 32;
+
+// The key a client sent a request under, and a fingerprint of what it sent - the HTTP layer hashes the route and body - so that the key
+// sent again with something else is refused rather than answered with an outcome that was never meant for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdempotencyKey
+{
+    pub key: String,
+    pub fingerprint: u64,
+}
+
+impl IdempotencyKey
+{
+    pub fn new(key: &str, fingerprint: u64) -> IdempotencyKey
+    {
+        IdempotencyKey { key: String::from(key), fingerprint }
+    }
+}
+
+// What each player's keyed requests came to, so a request sent again under the same key - a client retrying over a bad connection - is
+// answered with the first outcome instead of being applied twice.  A key only stands for a request to the game it was sent to.
+#[derive(Default)]
+pub struct ReplayCache
+{
+    players: HashMap<PlayerId, VecDeque<Replay>>,
+}
+
+struct Replay
+{
+    game_id: Option<GameId>,
+    key: IdempotencyKey,
+    request: &'static str,
+    outcome: Outcome,
+}
+
+pub enum Replayed
+{
+    Fresh,
+    Outcome(Outcome),
+    // The key was last used for a different request; the name is of that one.
+    KeyReused(&'static str),
+}
+
+impl ReplayCache
+{
+    pub fn lookup(&self, player_id: &PlayerId, game_id: Option<GameId>, key: &IdempotencyKey, request: &'static str) -> Replayed
+    {
+        let Some(replay) = self.players.get(player_id)
+            .and_then(|replays| replays.iter().find(|replay| replay.game_id == game_id && replay.key.key == key.key))
+        else { return Replayed::Fresh };
+
+        if replay.request != request || replay.key.fingerprint != key.fingerprint
+        {
+            return Replayed::KeyReused(replay.request);
+        }
+        replay.outcome.replay().map_or(Replayed::Fresh, Replayed::Outcome)
+    }
+
+    // Refusals are not kept, any more than outcomes that cannot be replayed, so the request runs again if it is retried: whatever turned
+    // it away may have changed since.
+    pub fn record(&mut self, player_id: PlayerId, game_id: Option<GameId>, key: IdempotencyKey, request: &'static str, outcome: &Outcome)
+    {
+        if matches!(outcome, Outcome::Error(_))
+        {
+            return;
+        }
+        let Some(outcome) = outcome.replay()
+        else { return };
+
+        let replays = self.players.entry(player_id).or_default();
+        if replays.len() == REPLAY_DEPTH
+        {
+            replays.pop_front();
+        }
+        replays.push_back(Replay { game_id, key, request, outcome });
+    }
+
+    pub fn forget(&mut self, player_id: &PlayerId)
+    {
+        self.players.remove(player_id);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use crate::gamerunner::{dispatcher::Outcome, Error, ErrorContext, ErrorKind};
+
+    use super::{IdempotencyKey, ReplayCache, Replayed, REPLAY_DEPTH};
+
+    #[test]
+    pub fn a_key_replays_its_first_outcome_only_for_the_same_player_game_and_request()
+    {
+        let (mork, elfie) = (Uuid::new_v4(), Uuid::new_v4());
+        let (game, other_game) = (Some(Uuid::new_v4()), Some(Uuid::new_v4()));
+        let mut cache = ReplayCache::default();
+
+        cache.record(mork, game, IdempotencyKey::new("attack-1", 7), "TakeAction", &Outcome::ActionTaken);
+
+        assert!(matches!(cache.lookup(&mork, game, &IdempotencyKey::new("attack-1", 7), "TakeAction"), Replayed::Outcome(Outcome::ActionTaken)));
+        assert!(matches!(cache.lookup(&elfie, game, &IdempotencyKey::new("attack-1", 7), "TakeAction"), Replayed::Fresh));
+        assert!(matches!(cache.lookup(&mork, other_game, &IdempotencyKey::new("attack-1", 7), "TakeAction"), Replayed::Fresh));
+        assert!(matches!(cache.lookup(&mork, game, &IdempotencyKey::new("attack-2", 7), "TakeAction"), Replayed::Fresh));
+        assert!(matches!(cache.lookup(&mork, game, &IdempotencyKey::new("attack-1", 7), "Engage"), Replayed::KeyReused("TakeAction")));
+        assert!(matches!(cache.lookup(&mork, game, &IdempotencyKey::new("attack-1", 8), "TakeAction"), Replayed::KeyReused("TakeAction")));
+
+        cache.forget(&mork);
+        assert!(matches!(cache.lookup(&mork, game, &IdempotencyKey::new("attack-1", 7), "TakeAction"), Replayed::Fresh));
+    }
+
+    #[test]
+    pub fn a_refusal_is_not_remembered()
+    {
+        let mork = Uuid::new_v4();
+        let mut cache = ReplayCache::default();
+        let refusal = Outcome::Error(Error { message: String::from("Not your turn."), kind: ErrorKind::InvalidStateAction, context: ErrorContext::default() });
+
+        cache.record(mork, None, IdempotencyKey::new("attack-1", 7), "TakeAction", &refusal);
+        assert!(matches!(cache.lookup(&mork, None, &IdempotencyKey::new("attack-1", 7), "TakeAction"), Replayed::Fresh));
+    }
+
+    #[test]
+    pub fn only_the_latest_keys_are_remembered()
+    {
+        let mork = Uuid::new_v4();
+        let mut cache = ReplayCache::default();
+        let key = |key: usize| IdempotencyKey::new(&key.to_string(), 0);
+
+        for sent in 0..=REPLAY_DEPTH
+        {
+            cache.record(mork, None, key(sent), "ChatSent", &Outcome::ChatSent(sent));
+        }
+
+        assert!(matches!(cache.lookup(&mork, None, &key(0), "ChatSent"), Replayed::Fresh));
+        assert!(matches!(cache.lookup(&mork, None, &key(1), "ChatSent"), Replayed::Outcome(Outcome::ChatSent(1))));
+        assert!(matches!(cache.lookup(&mork, None, &key(REPLAY_DEPTH), "ChatSent"), Replayed::Outcome(Outcome::ChatSent(sent)) if sent == REPLAY_DEPTH));
+    }
+}
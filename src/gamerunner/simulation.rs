@@ -0,0 +1,290 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{collections::VecDeque, sync::Arc};
+
+use tokio::{sync::{mpsc::{Receiver, Sender}, oneshot}, task::JoinHandle};
+use tracing::debug;
+
+use crate::tracker::{character::Character, game::ActionType, rules::{self, Edition}};
+
+use super::{CharacterId, GameId, PlayerId, dispatcher::{Action, Outcome, Request, Roll, Message}, loadgen::send, notifier::WhatChanged};
+
+// Bot players: stand-ins that join a game like anyone else and then answer the runner's prompts on their own - rolling initiative when
+// the phase opens and acting when told it is their turn.  A GM can seat a few to rehearse the pacing of an encounter, and a soak test
+// can seat many to keep the runner busy with realistic traffic.  Each bot rolls from its own seeded RNG, so a run can be repeated.
+
+#[derive(Clone)]
+pub enum BotBehaviour
+{
+    // Mostly simple actions, with a complex one about a third of the time.
+    Random,
+    // Works through the list one action per turn, starting over at the end.
+    Scripted(Vec<ActionType>),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BotReport
+{
+    pub initiatives_rolled: usize,
+    pub actions_taken: usize,
+    pub errors: usize,
+}
+
+pub struct BotHandle
+{
+    pub player_id: PlayerId,
+    pub character_id: CharacterId,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<BotReport>,
+}
+
+impl BotHandle
+{
+    // Stops the bot wherever it is and returns what it did.  A bot also stops by itself when its game ends.
+    pub async fn stop(self) -> BotReport
+    {
+        let _ = self.stop.send(());
+        self.task.await.unwrap_or_default()
+    }
+}
+
+struct Bot
+{
+    runner: Sender<Message>,
+    inbox: Receiver<Arc<WhatChanged>>,
+    // Notifications that arrived while the bot was waiting on a reply, to be answered once it has it.
+    backlog: VecDeque<Arc<WhatChanged>>,
+    player_id: PlayerId,
+    game_id: GameId,
+    character: Character,
+    character_id: CharacterId,
+    // Starts on the default edition and follows the GM's changes from then on, so a bot seated after a change rolls by the old rules.
+    edition: Edition,
+    behaviour: BotBehaviour,
+    rng: StdRng,
+    turns: usize,
+    report: BotReport,
+}
+
+impl Bot
+{
+    fn roll_initiative(&mut self) -> i8
+    {
+        rules::roll_initiative(self.edition, &mut self.rng, &self.character)
+    }
+
+    fn next_action(&mut self) -> ActionType
+    {
+        let action = match &self.behaviour
+        {
+            BotBehaviour::Random if self.rng.gen_ratio(1, 3) => ActionType::Complex,
+            BotBehaviour::Random => ActionType::Simple,
+            BotBehaviour::Scripted(actions) if actions.is_empty() => ActionType::Free,
+            BotBehaviour::Scripted(actions) => actions[self.turns % actions.len()],
+        };
+        self.turns += 1;
+
+        action
+    }
+
+    async fn next_change(&mut self) -> Option<Arc<WhatChanged>>
+    {
+        match self.backlog.pop_front()
+        {
+            Some(change) => Some(change),
+            None => self.inbox.recv().await,
+        }
+    }
+
+    async fn request(&mut self, request: Request) -> bool
+    {
+        // The runner may itself be waiting for room in this bot's inbox, so the inbox is kept drained while the reply is awaited; left
+        // alone, the two would each sit waiting on the other.
+        let reply = send(&self.runner, Some(self.player_id), Some(self.game_id), request);
+        tokio::pin!(reply);
+        let outcome = loop
+        {
+            tokio::select!
+            {
+                outcome = &mut reply => break outcome,
+                Some(change) = self.inbox.recv() => self.backlog.push_back(change),
+            }
+        };
+
+        match outcome
+        {
+            Ok(Outcome::Error(err)) =>
+            {
+                debug!("Bot {} had a request refused: {}", self.player_id, err.message);
+                self.report.errors += 1;
+                false
+            },
+            Ok(_) => true,
+            Err(_) =>
+            {
+                self.report.errors += 1;
+                false
+            },
+        }
+    }
+
+    // Returns false once there is nothing left for the bot to do.
+    async fn react(&mut self, change: &WhatChanged) -> bool
+    {
+        for part in change.parts()
+        {
+            if !self.react_to(part).await
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    async fn react_to(&mut self, change: &WhatChanged) -> bool
+    {
+        match change
+        {
+            WhatChanged::StartingInitiativePhase | WhatChanged::InitiativeReminder { .. } =>
+            {
+                let roll = self.roll_initiative();
+                if self.request(Request::AddInitiativeRoll(Roll { character_id: self.character_id, roll })).await
+                {
+                    self.report.initiatives_rolled += 1;
+                }
+            },
+            WhatChanged::YourTurn { character } if *character == self.character_id =>
+            {
+                let action = self.next_action();
+                if self.request(Request::TakeAction(Action { character_id: self.character_id, action, roll: None, catalog: None })).await
+                {
+                    self.report.actions_taken += 1;
+                }
+            },
+            WhatChanged::EditionChanged(edition) => self.edition = *edition,
+            WhatChanged::GameEnded | WhatChanged::GameArchived => return false,
+            _ => {},
+        }
+
+        true
+    }
+}
+
+// Registers a new player for the bot, joins it to the game and adds its character.  The GM still decides whether that character is in a
+// fight, the same as for anyone else's.
+pub async fn attach_bot(runner: &Sender<Message>, game_id: GameId, character: Character, behaviour: BotBehaviour, seed: u64) -> Result<BotHandle, String>
+{
+    let (player_id, inbox) = match send(runner, None, None, Request::NewPlayer).await?
+    {
+        Outcome::NewPlayer(new_player) => (new_player.player_id, new_player.player_1_receiver),
+        _ => return Err(String::from("Registering a bot did not return a player id.")),
+    };
+
+    if let Outcome::Error(err) = send(runner, Some(player_id), Some(game_id), Request::JoinGame).await?
+    {
+        return Err(err.message);
+    }
+
+    let character_id = match send(runner, Some(player_id), Some(game_id), Request::AddCharacter(character.clone())).await?
+    {
+        Outcome::CharacterAdded((_, character_id)) => character_id,
+        Outcome::Error(err) => return Err(err.message),
+        _ => return Err(String::from("Adding a bot's character did not return its id.")),
+    };
+
+    let mut bot = Bot
+    {
+        runner: runner.clone(), inbox, backlog: VecDeque::new(), player_id, game_id, character, character_id, edition: Edition::default(), behaviour,
+        rng: StdRng::seed_from_u64(seed), turns: 0, report: BotReport::default()
+    };
+    let (stop, mut stopped) = oneshot::channel::<()>();
+
+    let task = tokio::spawn(async move {
+        loop
+        {
+            tokio::select!
+            {
+                _ = &mut stopped => break,
+                change = bot.next_change() => match change
+                {
+                    Some(change) if bot.react(&change).await => {},
+                    _ => break,
+                },
+            }
+        }
+
+        debug!("Bot {} in game {} stopped.", bot.player_id, bot.game_id);
+        bot.report
+    });
+
+    debug!("Bot {} seated in game {} with character {}.", player_id, game_id, character_id);
+    Ok(BotHandle { player_id, character_id, stop, task })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{sync::Arc, time::Duration};
+
+    use tokio::sync::mpsc::{channel, Receiver};
+    use uuid::Uuid;
+
+    use crate::{gamerunner::{game_runner, loadgen::send, dispatcher::{Outcome, Request}, notifier::WhatChanged}, tracker::{character::{Character, Metatypes}, game::ActionType}};
+
+    use super::{attach_bot, BotBehaviour};
+
+    fn runner_bot(name: &str) -> Character
+    {
+        let mut character = Character::new_pc(Metatypes::Human, String::from(name));
+        Arc::make_mut(&mut character.stats).insert(String::from("Reaction"), 4);
+        Arc::make_mut(&mut character.stats).insert(String::from("Intuition"), 3);
+        character
+    }
+
+    async fn wait_for(inbox: &mut Receiver<Arc<WhatChanged>>, wanted: fn(&WhatChanged) -> bool)
+    {
+        let waiting = async { while let Some(change) = inbox.recv().await { if change.parts().into_iter().any(wanted) { return; } } };
+        assert!(tokio::time::timeout(Duration::from_secs(5), waiting).await.is_ok());
+    }
+
+    #[tokio::test]
+    pub async fn bots_roll_initiative_when_the_phase_opens_and_act_when_it_is_their_turn()
+    {
+        let (runner, receiver) = channel(16);
+        tokio::spawn(async move { game_runner(receiver).await; });
+
+        let (gm, mut gm_inbox) = match send(&runner, None, None, Request::NewPlayer).await.unwrap()
+        {
+            Outcome::NewPlayer(new_player) => (new_player.player_id, new_player.player_1_receiver),
+            _ => panic!("Registering the GM should return a player id."),
+        };
+        let game_id = match send(&runner, Some(gm), Some(Uuid::new_v4()), Request::New).await.unwrap()
+        {
+            Outcome::Created(game_id) => game_id,
+            _ => panic!("Creating a game should return its id."),
+        };
+
+        let bots = vec![
+            attach_bot(&runner, game_id, runner_bot("Bot One"), BotBehaviour::Scripted(vec![ActionType::Complex]), 1).await.unwrap(),
+            attach_bot(&runner, game_id, runner_bot("Bot Two"), BotBehaviour::Random, 2).await.unwrap(),
+        ];
+
+        let combatants = bots.iter().map(|bot| bot.character_id).collect();
+        assert!(matches!(send(&runner, Some(gm), Some(game_id), Request::StartCombat(combatants)).await, Ok(Outcome::CombatStarted)));
+        assert!(matches!(send(&runner, Some(gm), Some(game_id), Request::BeginInitiativePhase).await, Ok(Outcome::InitiativePhaseStarted)));
+
+        wait_for(&mut gm_inbox, |change| matches!(change, WhatChanged::InitiativeAdded(_))).await;
+        wait_for(&mut gm_inbox, |change| matches!(change, WhatChanged::InitiativeAdded(_))).await;
+        assert!(matches!(send(&runner, Some(gm), Some(game_id), Request::StartCombatRound).await, Ok(Outcome::CombatRoundStarted)));
+        wait_for(&mut gm_inbox, |change| matches!(change, WhatChanged::PlayerActed)).await;
+
+        let mut reports = Vec::new();
+        for bot in bots
+        {
+            reports.push(bot.stop().await);
+        }
+
+        assert!(reports.iter().all(|report| report.initiatives_rolled == 1 && report.errors == 0));
+        assert!(reports.iter().map(|report| report.actions_taken).sum::<usize>() >= 1);
+    }
+}
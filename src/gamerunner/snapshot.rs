@@ -0,0 +1,229 @@
+use std::{collections::{BTreeMap, HashSet}, sync::{Arc, atomic::{AtomicU64, Ordering}}};
+
+use arc_swap::ArcSwap;
+
+use crate::tracker::{game::{Game, CombatantState, TurnState, StatusEffect}, character::Metatypes, combat::Engagement, names::Name};
+
+use super::{CharacterId, GameId, PlayerId, registry::GameRegistry, lobby::{GameSummary, ListingOrder}, dispatcher::GameFilter, allowed::AllowedRequests};
+
+// What the read-only queries are answered from for one game, as it stood when the runner last finished a message to it.  It holds only
+// what the whole table may see: the lobby summary, the table's view of the combatants and the cast, who is locked in melee with whom and
+// what each of them could do next.  Who sits at the table is kept so the web side can turn everyone else away.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GameSnapshot
+{
+    pub summary: GameSummary,
+    pub gm: PlayerId,
+    // Everyone seated at the game, the GM included.
+    pub players: HashSet<PlayerId>,
+    pub cast: Vec<CastMember>,
+    pub combatants: Vec<CombatantState>,
+    pub allowed: AllowedRequests,
+    pub round: usize,
+    pub pass: usize,
+    pub order: Vec<OrderEntry>,
+    pub engagements: Vec<Engagement>,
+}
+
+// One place in what is left of the pass's initiative order, as the table may see it: hidden NPCs are left out, NPC scores withheld and
+// timed events not shown at all.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OrderEntry
+{
+    pub character: CharacterId,
+    pub name: Name,
+    pub initiative: Option<i8>,
+    pub acting: bool,
+}
+
+// One of the players' characters as the table sees it, for a cast list; the GM's NPCs and anyone hidden are left out.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CastMember
+{
+    pub character: CharacterId,
+    pub name: Name,
+    pub metatype: Metatypes,
+    pub status: Vec<StatusEffect>,
+    pub turn: TurnState,
+}
+
+impl CastMember
+{
+    pub fn table_cast(game: &Game) -> Vec<CastMember>
+    {
+        game.get_pcs().into_iter()
+            .filter(|character| !game.is_hidden(&character.id))
+            .map(|character| 
+            {
+                let summary = game.summarize(character, false);
+                CastMember { character: summary.character.id, name: summary.character.name.clone(), metatype: summary.character.metatype,
+                    status: summary.status, turn: summary.turn }
+            })
+            .collect()
+    }
+}
+
+impl OrderEntry
+{
+    pub fn table_order(game: &Game) -> Vec<OrderEntry>
+    {
+        game.initiative_order().into_iter()
+            .filter(|(id, _)| !game.is_hidden(id))
+            .filter_map(|(id, initiative)| game.get_cast_by_id(&id).map(|character| OrderEntry
+            {
+                character: id,
+                name: character.name.clone(),
+                initiative: if character.player_character { Some(initiative) } else { None },
+                acting: game.turn_state(&id) == TurnState::Acting,
+            }))
+            .collect()
+    }
+}
+
+// Shared between the runner, which republishes a game after each message to it, and the web handlers, which read from it without
+// queueing behind the messages that change games or taking any lock.  Each game's snapshot is swapped whole, so a reader never sees one
+// half written.  The map of games and the listing order are swapped whole too, but only when a game comes or goes or a fight starts or
+// ends, so publishing one game's change costs the same however many games there are.
+#[derive(Clone, Default)]
+pub struct QuerySnapshots
+{
+    games: Arc<ArcSwap<BTreeMap<GameId, Arc<ArcSwap<GameSnapshot>>>>>,
+    order: Arc<ArcSwap<ListingOrder>>,
+    // The lobby's generation when the set of games was last brought into line with it.
+    seen_generation: Arc<AtomicU64>,
+}
+
+impl QuerySnapshots
+{
+    pub fn game(&self, game_id: &GameId) -> Option<Arc<GameSnapshot>>
+    {
+        self.games.load().get(game_id).map(|slot| slot.load_full())
+    }
+
+    // Whether the player already has a seat at the game, as its GM or as a player.
+    pub fn seats(&self, game_id: &GameId, player_id: &PlayerId) -> bool
+    {
+        self.games.load().get(game_id).map_or(false, |slot| slot.load().players.contains(player_id))
+    }
+
+    // Whether the player runs any game still in play; what the server's own health is shown to, there being no operator role as such.
+    pub fn runs_a_game(&self, player_id: &PlayerId) -> bool
+    {
+        self.games.load().values().any(|slot| slot.load().gm == *player_id)
+    }
+
+    // Pages the same way as Request::Enumerate.  Joined and Running depend on who is asking, so they are left to the runner and get None.
+    pub fn list(&self, filter: GameFilter, page: usize, page_size: Option<usize>) -> Option<Vec<(GameId, GameSummary)>>
+    {
+        let in_combat = match filter
+        {
+            GameFilter::All => None,
+            GameFilter::Open => Some(false),
+            GameFilter::InCombat => Some(true),
+            GameFilter::Joined | GameFilter::Running => return None,
+        };
+
+        let page_size = page_size.unwrap_or(usize::MAX).max(1);
+        let (order, games) = (self.order.load(), self.games.load());
+        Some(order.page(in_combat, page, page_size).iter()
+            .filter_map(|game_id| games.get(game_id).map(|slot| (*game_id, slot.load().summary.clone())))
+            .collect())
+    }
+
+    // Called by the runner once a message is done with.  The game it was sent to is republished; games created or deleted on the side,
+    // such as a clone or everything a departing GM ran, are caught by the lobby's generation having moved on.
+    pub fn republish(&self, registry: &GameRegistry, game_id: Option<GameId>)
+    {
+        if let Some(game_id) = game_id
+        {
+            self.publish(registry, game_id);
+        }
+
+        if self.seen_generation.load(Ordering::Acquire) != registry.lobby().generation()
+        {
+            self.catch_up(registry);
+        }
+    }
+
+    // Every game over again, for a runner taking over from one that panicked and may not have published its last change.
+    pub fn republish_all(&self, registry: &GameRegistry)
+    {
+        let game_ids = registry.lobby().iter().map(|(game_id, _)| *game_id).collect::<Vec<GameId>>();
+        for game_id in game_ids
+        {
+            self.publish(registry, game_id);
+        }
+        self.catch_up(registry);
+    }
+
+    // Brings the set of games published into line with the lobby's: games it no longer has are dropped and ones it has gained added.
+    fn catch_up(&self, registry: &GameRegistry)
+    {
+        let published = self.games.load();
+        let gone = published.keys().filter(|game_id| registry.lobby().get(game_id).is_none());
+        let added = registry.lobby().iter().map(|(game_id, _)| game_id).filter(|game_id| !published.contains_key(*game_id));
+        let changed = gone.chain(added).copied().collect::<Vec<GameId>>();
+        drop(published);
+
+        for game_id in changed
+        {
+            self.publish(registry, game_id);
+        }
+        self.seen_generation.store(registry.lobby().generation(), Ordering::Release);
+    }
+
+    fn publish(&self, registry: &GameRegistry, game_id: GameId)
+    {
+        let snapshot = registry.lobby().get(&game_id).map(|summary|
+        {
+            let game = registry.get_game(&game_id);
+            let combatants = registry.combat_sync(&game_id).map_or(Vec::new(), |sync| sync.table.current());
+            // Hidden NPCs are kept out of the table's combatants, and so out of what the table is told they could do.
+            let mut allowed = game.map_or(AllowedRequests::default(), AllowedRequests::of);
+            allowed.characters.retain(|id, _| combatants.iter().any(|combatant| combatant.character == *id));
+
+            GameSnapshot
+            {
+                summary: summary.clone(), gm: registry.gm_id(&game_id).copied().unwrap_or_default(), combatants, allowed,
+                players: registry.players_by_game(&game_id).cloned().unwrap_or_default(),
+                cast: game.map_or(Vec::new(), CastMember::table_cast),
+                round: game.map_or(0, Game::current_round),
+                pass: game.map_or(0, Game::current_pass),
+                order: game.map_or(Vec::new(), OrderEntry::table_order),
+                // A hidden NPC in melee would give itself away, so engagements are shown only between combatants the table can see.
+                engagements: game.map_or(Vec::new(), |game| game.engagements().into_iter()
+                    .filter(|engagement| !game.is_hidden(&engagement.attacker) && !game.is_hidden(&engagement.defender))
+                    .collect()),
+            }
+        });
+
+        match snapshot
+        {
+            Some(snapshot) =>
+            {
+                let in_combat = snapshot.summary.in_combat;
+                let slot = self.games.load().get(&game_id).cloned();
+                let was_in_combat = match slot
+                {
+                    Some(slot) => Some(slot.swap(Arc::new(snapshot)).summary.in_combat),
+                    None =>
+                    {
+                        let slot = Arc::new(ArcSwap::from_pointee(snapshot));
+                        self.games.rcu(|games| { let mut games = BTreeMap::clone(games); games.insert(game_id, slot.clone()); games });
+                        None
+                    },
+                };
+                if was_in_combat != Some(in_combat)
+                {
+                    self.order.rcu(|order| { let mut order = ListingOrder::clone(order); order.place(game_id, in_combat); order });
+                }
+            },
+            None if self.games.load().contains_key(&game_id) =>
+            {
+                self.order.rcu(|order| { let mut order = ListingOrder::clone(order); order.remove(&game_id); order });
+                self.games.rcu(|games| { let mut games = BTreeMap::clone(games); games.remove(&game_id); games });
+            },
+            None => {},
+        }
+    }
+}
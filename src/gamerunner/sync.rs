@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use crate::tracker::game::CombatantState;
+
+use super::CharacterId;
+
+// After every message that touches a game the runner compares where its combatants stand against what it last sent, and sends only the
+// combatants that moved.  Each view - the GM's and the table's - numbers its own payloads, so a client that sees a gap knows to ask for
+// a full sync rather than draw a stale card; every so often one goes out anyway.
+
+// Deltas sent between full syncs.
+pub const FULL_SYNC_INTERVAL: u64 = 16;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CombatSync
+{
+    // The combatants that changed since the last payload, and those that have left the fight or dropped out of view.
+    Delta { sequence: u64, changed: Vec<CombatantState>, removed: Vec<CharacterId> },
+    // Everyone in view; replaces whatever the client had.
+    Full { sequence: u64, combatants: Vec<CombatantState> },
+}
+
+impl CombatSync
+{
+    pub fn sequence(&self) -> u64
+    {
+        match self
+        {
+            CombatSync::Delta { sequence, .. } | CombatSync::Full { sequence, .. } => *sequence,
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct SyncView
+{
+    sequence: u64,
+    deltas_since_full: u64,
+    sent: BTreeMap<CharacterId, CombatantState>,
+}
+
+impl SyncView
+{
+    // Records the view as it now stands and returns what to send for it, or None if nothing in it changed.  The first payload, and every
+    // one after FULL_SYNC_INTERVAL deltas, is a full sync.
+    pub fn update(&mut self, states: Vec<CombatantState>) -> Option<CombatSync>
+    {
+        let current = states.into_iter().map(|state| (state.character, state)).collect::<BTreeMap<CharacterId, CombatantState>>();
+        if current == self.sent
+        {
+            return None;
+        }
+
+        let changed = current.values().filter(|state| self.sent.get(&state.character) != Some(*state)).cloned().collect::<Vec<CombatantState>>();
+        let removed = self.sent.keys().filter(|id| !current.contains_key(id)).copied().collect::<Vec<CharacterId>>();
+        let first = self.sequence == 0;
+        self.sequence += 1;
+        self.sent = current;
+
+        if first || self.deltas_since_full >= FULL_SYNC_INTERVAL
+        {
+            self.deltas_since_full = 0;
+            return Some(self.full());
+        }
+
+        self.deltas_since_full += 1;
+        Some(CombatSync::Delta { sequence: self.sequence, changed, removed })
+    }
+
+    // Everything last sent, under the latest sequence number; what a client that missed a payload asks for.
+    pub fn full(&self) -> CombatSync
+    {
+        CombatSync::Full { sequence: self.sequence, combatants: self.current() }
+    }
+
+    pub fn current(&self) -> Vec<CombatantState>
+    {
+        self.sent.values().cloned().collect()
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct CombatSyncState
+{
+    pub gm: SyncView,
+    pub table: SyncView,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use crate::tracker::game::{CombatantState, TurnState, StatusEffect};
+
+    use super::{SyncView, CombatSync, FULL_SYNC_INTERVAL};
+
+    fn state(character: Uuid, turn: TurnState) -> CombatantState
+    {
+        CombatantState { character, turn, status: Vec::new(), wounds: None }
+    }
+
+    #[test]
+    pub fn only_combatants_that_moved_are_sent_after_the_first_full_sync()
+    {
+        let (mork, elfie) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut view = SyncView::default();
+
+        assert!(view.update(Vec::new()).is_none());
+        let first = view.update(vec![state(mork, TurnState::AwaitingInitiative), state(elfie, TurnState::AwaitingInitiative)]);
+        assert!(matches!(first, Some(CombatSync::Full { sequence: 1, combatants }) if combatants.len() == 2));
+        assert!(view.update(vec![state(mork, TurnState::AwaitingInitiative), state(elfie, TurnState::AwaitingInitiative)]).is_none());
+
+        let engaged = CombatantState { status: vec![StatusEffect::Engaged(elfie)], ..state(mork, TurnState::Waiting) };
+        assert_eq!(Some(CombatSync::Delta { sequence: 2, changed: vec![engaged.clone()], removed: Vec::new() }),
+            view.update(vec![engaged.clone(), state(elfie, TurnState::AwaitingInitiative)]));
+        assert_eq!(Some(CombatSync::Delta { sequence: 3, changed: Vec::new(), removed: vec![elfie] }), view.update(vec![engaged.clone()]));
+        assert_eq!(CombatSync::Full { sequence: 3, combatants: vec![engaged] }, view.full());
+    }
+
+    #[test]
+    pub fn a_full_sync_follows_every_run_of_deltas()
+    {
+        let mork = Uuid::new_v4();
+        let mut view = SyncView::default();
+        let turns = [TurnState::Waiting, TurnState::Acting];
+
+        let payloads = (0..=FULL_SYNC_INTERVAL + 1).filter_map(|step| view.update(vec![state(mork, turns[step as usize % 2])])).collect::<Vec<CombatSync>>();
+
+        assert_eq!(FULL_SYNC_INTERVAL as usize + 2, payloads.len());
+        assert!(payloads.iter().enumerate().all(|(index, payload)| payload.sequence() == index as u64 + 1));
+        assert!(matches!(payloads[0], CombatSync::Full { .. }));
+        assert!(payloads[1..=FULL_SYNC_INTERVAL as usize].iter().all(|payload| matches!(payload, CombatSync::Delta { .. })));
+        assert!(matches!(payloads[FULL_SYNC_INTERVAL as usize + 1], CombatSync::Full { .. }));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use tokio::sync::{mpsc::{channel as mpsc_channel, Sender}, oneshot::channel};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::tracker::character::{Character, Metatypes};
+
+use super::{game_runner, CharacterId, GameId, PlayerId, dispatcher::{Message, NewPlayer, Outcome, Request}};
+
+// The setup every runner test starts from - a runner, a GM with a game, players seated with a character each, combat underway - for the
+// crate's own tests and, with the testing feature, for anyone else's.  The helpers panic rather than return errors: a table that cannot
+// be set up is a failed test.
+//
+//     let table = TestTable::new().with_players(4).combat_ready().await;
+//     let outcome = table.send(table.gm, Request::QueryMissingInitiatives).await;
+
+pub struct TestTable
+{
+    players: usize,
+    character: fn() -> Character,
+}
+
+// A game set up by TestTable.  Each player's notification channel was dropped on registration, so whatever they are sent waits in their
+// inbox for a FetchInbox.
+pub struct SeatedTable
+{
+    pub runner: Sender<Message>,
+    pub gm: PlayerId,
+    pub game_id: GameId,
+    pub players: Vec<(PlayerId, CharacterId)>,
+}
+
+impl Default for TestTable
+{
+    fn default() -> TestTable
+    {
+        TestTable::new()
+    }
+}
+
+impl TestTable
+{
+    pub fn new() -> TestTable
+    {
+        TestTable { players: 0, character: create_character }
+    }
+
+    pub fn with_players(self, players: usize) -> TestTable
+    {
+        TestTable { players, ..self }
+    }
+
+    // What each player brings to the table; by default a random character with no stats.
+    pub fn with_character(self, character: fn() -> Character) -> TestTable
+    {
+        TestTable { character, ..self }
+    }
+
+    // A runner of its own, the GM's game, and every player joined with their character added.
+    pub async fn seated(self) -> SeatedTable
+    {
+        let runner = init();
+        let (gm, game_id) = add_new_game(&runner).await;
+
+        let mut players = Vec::with_capacity(self.players);
+        for _ in 0..self.players
+        {
+            players.push(add_char(&runner, game_id, (self.character)()).await);
+        }
+
+        SeatedTable { runner, gm, game_id, players }
+    }
+
+    // Seated, with every player's character declared into combat and the initiative phase open.
+    pub async fn combat_ready(self) -> SeatedTable
+    {
+        let table = self.seated().await;
+        let combatants = table.players.iter().map(|(_, character_id)| *character_id).collect();
+
+        assert!(matches!(table.send(table.gm, Request::StartCombat(combatants)).await, Outcome::CombatStarted));
+        assert!(matches!(table.send(table.gm, Request::BeginInitiativePhase).await, Outcome::InitiativePhaseStarted));
+
+        table
+    }
+}
+
+impl SeatedTable
+{
+    // Sends the request to the table's game as the given player and waits for the answer.
+    pub async fn send(&self, player_id: PlayerId, request: Request) -> Outcome
+    {
+        let (reply_channel, reply) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player_id), game_id: Some(self.game_id), reply_channel, msg: request };
+
+        assert!(self.runner.send(msg).await.is_ok(), "The game runner has shut down.");
+        reply.await.expect("The game runner dropped the reply channel.")
+    }
+
+    pub fn player_characters(&self) -> HashMap<PlayerId, CharacterId>
+    {
+        self.players.iter().copied().collect()
+    }
+}
+
+pub fn init() -> Sender<Message>
+{
+    let _ = tracing_subscriber::fmt().with_test_writer().try_init();
+
+    let (sender, receiver) = mpsc_channel(1);
+    tokio::spawn(async { game_runner(receiver).await; });
+
+    debug!("Runner started for a test table.");
+    sender
+}
+
+pub async fn add_new_game(runner: &Sender<Message>) -> (PlayerId, GameId)
+{
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: None, game_id: None, reply_channel, msg: Request::NewPlayer }).await.is_ok());
+    let gm = match reply.await
+    {
+        Ok(Outcome::NewPlayer(new_player)) => new_player.player_id,
+        _ => panic!("Should have received a NewPlayer object with the id and messaging channel."),
+    };
+
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: Some(gm), game_id: Some(Uuid::new_v4()), reply_channel, msg: Request::New }).await.is_ok());
+    match reply.await
+    {
+        Ok(Outcome::Created(game_id)) =>
+        {
+            debug!("GM {} has created game {}.", gm, game_id);
+            (gm, game_id)
+        },
+        _ => panic!("The oneshot channel closed while waiting for reply."),
+    }
+}
+
+// Registers a new player against the game without joining it, and hands back their registration, notification channel included.
+pub async fn player_join_game(runner: &Sender<Message>, game_id: GameId) -> NewPlayer
+{
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: None, game_id: Some(game_id), reply_channel, msg: Request::NewPlayer }).await.is_ok(),
+        "The game runner input channel closed prematurely.");
+
+    match reply.await
+    {
+        Ok(Outcome::NewPlayer(new_player)) => new_player,
+        Ok(_) => panic!("Was expecting NewPlayer registration confirmation."),
+        Err(_) => panic!("Game input channel has closed."),
+    }
+}
+
+pub fn create_character() -> Character
+{
+    let names: [&str; 5] = ["Matrox", "El See-Dee", "BusShock", "Junkyard", "Lo Hax"];
+    let metatypes = [Metatypes::Dwarf, Metatypes::Elf, Metatypes::Human, Metatypes::Orc, Metatypes::Troll];
+
+    if rand::random::<usize>() % 2 == 1
+    {
+        return Character::new_npc(metatypes[rand::random::<usize>() % 5], String::from(names[rand::random::<usize>() % 5]));
+    }
+
+    Character::new_pc(metatypes[rand::random::<usize>() % 5], String::from(names[rand::random::<usize>() % 5]))
+}
+
+// A new player, joined to the game, with a random character added.
+pub async fn create_and_add_char(runner: &Sender<Message>, game_id: GameId) -> (PlayerId, CharacterId)
+{
+    add_char(runner, game_id, create_character()).await
+}
+
+async fn add_char(runner: &Sender<Message>, game_id: GameId, character: Character) -> (PlayerId, CharacterId)
+{
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: None, game_id: None, reply_channel, msg: Request::NewPlayer }).await.is_ok());
+    let player_id = match reply.await
+    {
+        Ok(Outcome::NewPlayer(player)) => player.player_id,
+        _ => panic!("Attempt to create new player has failed."),
+    };
+
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel, msg: Request::JoinGame }).await.is_ok());
+    match reply.await
+    {
+        Ok(Outcome::JoinedGame(state)) => assert_eq!(player_id, state.for_player),
+        _ => panic!("Attempt to join game failed."),
+    }
+
+    let (reply_channel, reply) = channel();
+    assert!(runner.send(Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel, msg: Request::AddCharacter(character) }).await.is_ok());
+    match reply.await
+    {
+        Ok(Outcome::CharacterAdded((_, character_id))) => (player_id, character_id),
+        Ok(_) => panic!("Should have received CharacterAdded outcome - interface changed."),
+        Err(_) => panic!("Channel closed."),
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
+
+use argon2::{Argon2, PasswordHasher, PasswordVerifier, password_hash::{SaltString, PasswordHash}};
+use tracing::{debug, error};
+use parking_lot::RwLock;
+use rand::rngs::OsRng;
+use rocket::{figment::value::magic::RelativePathBuf, serde::{Serialize, Deserialize, json}};
+use uuid::Uuid;
+
+use super::store::{self, StoreError};
+
+// Durable logins layered over the runner's player ids.  An account is just a username and password pinned to a player id: logging in
+// from a new device points that device's session at the same player id, and the runner's reconnect does the rest.
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+// Where the accounts are kept, read out of Rocket.toml.  A relative path is taken from the directory Rocket.toml is in, not from wherever
+// the server happened to be started.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AccountConfig
+{
+    #[serde(default = "AccountConfig::default_store")]
+    pub account_store: RelativePathBuf,
+}
+
+impl AccountConfig
+{
+    pub fn new() -> AccountConfig
+    {
+        AccountConfig { account_store: AccountConfig::default_store() }
+    }
+
+    fn default_store() -> RelativePathBuf
+    {
+        RelativePathBuf::from("accounts.json")
+    }
+
+    pub fn store_path(&self) -> PathBuf
+    {
+        self.account_store.relative()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct Account
+{
+    pub username: String,
+    pub password_hash: String,
+    pub player_id: Uuid,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AccountError
+{
+    InvalidUsername,
+    PasswordTooShort,
+    UsernameTaken,
+    BadCredentials,
+    HashFailure,
+}
+
+pub struct AccountStore
+{
+    accounts: RwLock<HashMap<String, Account>>,
+    store: Option<PathBuf>,
+}
+
+// What an unknown username's password is checked against, so that turning it down costs the same Argon2 verify as a wrong password
+// for a real account.  Hashed with the same defaults as every stored password, so the two take the same time.
+fn dummy_hash() -> &'static str
+{
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    DUMMY_HASH.get_or_init(|| 
+    {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default().hash_password(b"no account has this password", &salt).map(|hash| hash.to_string()).unwrap_or_default()
+    })
+}
+
+// Hashing and verifying are deliberately slow, so both run on the blocking pool rather than holding up an async worker.
+fn hash_password(password: String) -> Result<String, AccountError>
+{
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default().hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| { error!("Could not hash a password: {}", err); AccountError::HashFailure })
+}
+
+fn verify_password(password: &str, stored_hash: &str) -> bool
+{
+    let Ok(hash) = PasswordHash::new(stored_hash)
+    else
+    {
+        error!("A stored password hash is malformed.");
+        return false;
+    };
+
+    Argon2::default().verify_password(password.as_bytes(), &hash).is_ok()
+}
+
+impl AccountStore
+{
+    pub fn new() -> AccountStore
+    {
+        AccountStore { accounts: RwLock::new(HashMap::new()), store: None }
+    }
+
+    // Same arrangement as the session store: load whatever was saved.  A store that cannot be read, or cannot be moved aside when it will
+    // not parse, stops the server from starting rather than being written over by the first registration.
+    pub fn with_store(path: PathBuf) -> Result<AccountStore, StoreError>
+    {
+        let mut accounts = HashMap::new();
+
+        if let Some(stored) = store::load::<Vec<Account>>(&path, "account")?
+        {
+            for account in stored
+            {
+                accounts.insert(account.username.clone(), account);
+            }
+            debug!("Restored {} accounts from {}", accounts.len(), path.display());
+        }
+
+        // Hashed now rather than on the first login for an unknown name, which would otherwise take longer than the rest.
+        dummy_hash();
+
+        Ok(AccountStore { accounts: RwLock::new(accounts), store: Some(path) })
+    }
+
+    pub fn save(&self)
+    {
+        let Some(path) = &self.store
+        else { return };
+
+        let stored: Vec<Account> = self.accounts.read().values().cloned().collect();
+
+        match json::to_string(&stored)
+        {
+            Ok(contents) => if let Err(err) = store::write_private(path, &contents) { error!("Could not write account store {}: {}", path.display(), err) },
+            Err(err) => error!("Could not serialize accounts: {}", err),
+        }
+    }
+
+    // Usernames are matched without regard to case or surrounding whitespace.
+    fn normalize(username: &str) -> String
+    {
+        username.trim().to_lowercase()
+    }
+
+    pub async fn register(&self, username: &str, password: &str, player_id: Uuid) -> Result<(), AccountError>
+    {
+        let username = AccountStore::normalize(username);
+        if username.is_empty()
+        {
+            return Err(AccountError::InvalidUsername);
+        }
+
+        if password.chars().count() < MIN_PASSWORD_LENGTH
+        {
+            return Err(AccountError::PasswordTooShort);
+        }
+
+        let password = String::from(password);
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(password)).await.map_err(|_| AccountError::HashFailure)??;
+
+        {
+            let mut accounts = self.accounts.write();
+            if accounts.contains_key(&username)
+            {
+                return Err(AccountError::UsernameTaken);
+            }
+            accounts.insert(username.clone(), Account { username, password_hash, player_id });
+        }
+        self.save();
+
+        Ok(())
+    }
+
+    // Unknown usernames and wrong passwords are reported the same way and take as long, so a failed login does not reveal which accounts
+    // exist.  The hash is copied out so the store is not held locked through the verify.
+    pub async fn login(&self, username: &str, password: &str) -> Result<Uuid, AccountError>
+    {
+        let stored = self.accounts.read().get(&AccountStore::normalize(username)).map(|account| (account.password_hash.clone(), account.player_id));
+        let password = String::from(password);
+
+        let verified = tokio::task::spawn_blocking(move || match stored
+        {
+            Some((password_hash, player_id)) => verify_password(&password, &password_hash).then_some(player_id),
+            None =>
+            {
+                verify_password(&password, dummy_hash());
+                None
+            },
+        }).await;
+
+        match verified
+        {
+            Ok(Some(player_id)) => Ok(player_id),
+            Ok(None) => Err(AccountError::BadCredentials),
+            Err(_) => Err(AccountError::HashFailure),
+        }
+    }
+
+    // Forgets any account pinned to the player id.  Nothing is kept back, the password hash included.
+    pub fn remove_player(&self, player_id: Uuid)
+    {
+        self.accounts.write().retain(|_, account| account.player_id != player_id);
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use super::{AccountStore, AccountError};
+
+    #[tokio::test]
+    pub async fn a_wrong_password_and_an_unknown_username_are_turned_down_alike()
+    {
+        let accounts = AccountStore::new();
+        let player_id = Uuid::new_v4();
+        accounts.register("Wraith", "correct horse", player_id).await.unwrap();
+
+        assert_eq!(Ok(player_id), accounts.login(" wraith ", "correct horse").await);
+        assert_eq!(Err(AccountError::BadCredentials), accounts.login("wraith", "wrong horse").await);
+        assert_eq!(Err(AccountError::BadCredentials), accounts.login("nobody", "correct horse").await);
+        assert_eq!(Err(AccountError::UsernameTaken), accounts.register("WRAITH", "another password", Uuid::new_v4()).await);
+    }
+}
@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use rocket::{fs::{relative, FileServer, Options}, serde::Deserialize};
+
+#[cfg(feature = "dev-reload")]
+use notify::{RecommendedWatcher, RecursiveMode, Watcher, EventKind};
+#[cfg(feature = "dev-reload")]
+use rocket::{get, routes, Build, Rocket, Shutdown, State, response::stream::{Event, EventStream}, tokio::{select, sync::broadcast::{self, error::RecvError}}};
+#[cfg(feature = "dev-reload")]
+use tracing::{debug, error, warn};
+#[cfg(feature = "embed-assets")]
+use std::{borrow::Cow, fs, io, path::Path};
+#[cfg(feature = "embed-assets")]
+use rocket::{Data, Request, Route, http::{ContentType, Method, uri::{Segments, fmt::Path as UriPath}}, route::{Handler, Outcome}};
+#[cfg(feature = "embed-assets")]
+use rust_embed::RustEmbed;
+#[cfg(feature = "embed-assets")]
+use tempfile::TempDir;
+
+// Where the pages and static files are read from, and whether to watch them while developing.  template_dir is Rocket's own key, read
+// here as well so the watcher knows where to look; the rest sit beside it in Rocket.toml.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AssetConfig
+{
+    #[serde(default = "default_template_dir")]
+    pub template_dir: PathBuf,
+    #[serde(default = "default_static_dir")]
+    pub static_dir: PathBuf,
+    // Watch both directories and have any open page reload itself when something in them changes.  Needs the dev-reload feature.
+    #[serde(default)]
+    pub dev_reload: bool,
+}
+
+fn default_template_dir() -> PathBuf
+{
+    PathBuf::from("templates")
+}
+
+fn default_static_dir() -> PathBuf
+{
+    PathBuf::from(relative!("resources/static"))
+}
+
+impl AssetConfig
+{
+    pub fn new() -> AssetConfig
+    {
+        AssetConfig { template_dir: default_template_dir(), static_dir: default_static_dir(), dev_reload: false }
+    }
+
+    // What the dev_reload template helper writes into a page's head: a listener on the change stream that reloads the page.
+    pub fn reload_script(stream: &str) -> String
+    {
+        format!("<script>new EventSource(\"{}\").addEventListener(\"changed\", () => location.reload());</script>", stream)
+    }
+}
+
+// A single-binary deploy may have no static directory at all, with the bundled files standing in for it.
+pub fn static_files(config: &AssetConfig) -> FileServer
+{
+    if cfg!(feature = "embed-assets")
+    {
+        FileServer::new(&config.static_dir, Options::Index | Options::Missing)
+    }
+    else
+    {
+        FileServer::from(&config.static_dir)
+    }
+}
+
+// Hands out each changed path to every page listening on the reload stream.
+#[cfg(feature = "dev-reload")]
+pub struct AssetWatch
+{
+    changes: broadcast::Sender<String>,
+}
+
+// Starts watching the template and static directories and mounts the reload stream at mount_point.  A debug build of Rocket re-reads a
+// changed template on the next render and the file server reads from disk on every request, so the pages only need telling to reload.
+// The watcher stops when it is dropped, so the caller has to hold on to it for as long as the server runs.
+#[cfg(feature = "dev-reload")]
+pub fn watch_assets(rocket: Rocket<Build>, config: &AssetConfig, mount_point: &str) -> (Rocket<Build>, Option<RecommendedWatcher>)
+{
+    if !config.dev_reload
+    {
+        return (rocket, None);
+    }
+
+    let (changes, _) = broadcast::channel::<String>(16);
+    let sender = changes.clone();
+    let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result
+    {
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) =>
+        {
+            for path in event.paths
+            {
+                debug!("Asset {} changed; telling open pages to reload.", path.display());
+                let _ = sender.send(path.display().to_string());
+            }
+        },
+        Ok(_) => {},
+        Err(err) => warn!("The asset watcher reported an error: {}", err),
+    });
+
+    let watcher = watcher.and_then(|mut watcher| {
+        watcher.watch(&config.template_dir, RecursiveMode::Recursive)?;
+        watcher.watch(&config.static_dir, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+
+    match watcher
+    {
+        Ok(watcher) => (rocket.manage(AssetWatch { changes }).mount(mount_point, routes![asset_changes]), Some(watcher)),
+        Err(err) =>
+        {
+            error!("Could not watch {} and {} ({}); assets will not be reloaded.", config.template_dir.display(), config.static_dir.display(), err);
+            (rocket, None)
+        }
+    }
+}
+
+#[cfg(feature = "dev-reload")]
+#[get("/reload")]
+pub fn asset_changes(watch: &State<AssetWatch>, mut end: Shutdown) -> EventStream![]
+{
+    let mut changes = watch.changes.subscribe();
+    EventStream! {
+        loop {
+            let path = select! {
+                change = changes.recv() => match change {
+                    Ok(path) => path,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut end => break,
+            };
+
+            yield Event::data(path).event("changed");
+        }
+    }
+}
+
+// The templates and static files as they were when the binary was built, for shipping the manager as a single executable.
+#[cfg(feature = "embed-assets")]
+#[derive(RustEmbed)]
+#[folder = "resources/templates/"]
+struct BundledTemplates;
+
+#[cfg(feature = "embed-assets")]
+#[derive(RustEmbed)]
+#[folder = "resources/static/"]
+struct BundledStatic;
+
+// Rocket only loads templates from a directory, so the bundled ones are written out to a fresh one under the system's temp directory,
+// along with anything in the configured template_dir that was not bundled.  The directory is created with a random name only we can
+// write to, and is removed when the returned TempDir is dropped, so the caller holds it for as long as the server runs.
+#[cfg(feature = "embed-assets")]
+pub fn unpack_templates(config: &AssetConfig) -> io::Result<TempDir>
+{
+    let unpacked = tempfile::Builder::new().prefix("scm-templates-").tempdir()?;
+    let target = unpacked.path();
+    if config.template_dir.is_dir()
+    {
+        copy_tree(&config.template_dir, target)?;
+    }
+
+    for name in BundledTemplates::iter()
+    {
+        let Some(template) = BundledTemplates::get(&name)
+        else { continue };
+        let path = target.join(name.as_ref());
+        if let Some(parent) = path.parent()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, template.data)?;
+    }
+
+    Ok(unpacked)
+}
+
+#[cfg(feature = "embed-assets")]
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()>
+{
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)?
+    {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir()
+        {
+            copy_tree(&entry.path(), &target)?;
+        }
+        else
+        {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Serves the bundled static files.  It is mounted ahead of the FileServer on the configured static_dir, and forwards anything it was not
+// built with so that the FileServer gets a chance at it.
+#[cfg(feature = "embed-assets")]
+#[derive(Clone)]
+pub struct BundledFiles;
+
+#[cfg(feature = "embed-assets")]
+impl BundledFiles
+{
+    // FileServer's default rank is 10; this has to be tried first.
+    const RANK: isize = 5;
+}
+
+#[cfg(feature = "embed-assets")]
+#[rocket::async_trait]
+impl Handler for BundledFiles
+{
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r>
+    {
+        let file = req.segments::<Segments<'_, UriPath>>(0..).ok()
+            .and_then(|segments| segments.to_path_buf(false).ok())
+            .and_then(|path| {
+                let content_type = path.extension().and_then(|ext| ext.to_str()).and_then(ContentType::from_extension).unwrap_or(ContentType::Binary);
+                let name = path.to_str()?.replace('\\', "/");
+                BundledStatic::get(&name).map(|file| (content_type, Cow::into_owned(file.data)))
+            });
+
+        Outcome::from_or_forward(req, data, file)
+    }
+}
+
+#[cfg(feature = "embed-assets")]
+impl From<BundledFiles> for Vec<Route>
+{
+    fn from(server: BundledFiles) -> Vec<Route>
+    {
+        vec![Route::ranked(BundledFiles::RANK, Method::Get, "/<path..>", server)]
+    }
+}
@@ -0,0 +1,138 @@
+use tracing::{debug, error};
+use rocket::{Request, Response, fairing::{Fairing, Info, Kind}, http::{Header, Method, Status}, request::{FromRequest, Outcome, self}, serde::Deserialize, options};
+
+use super::{proxy::Forwarded, idempotency::IDEMPOTENCY_KEY_HEADER};
+
+// Cross-origin access to the JSON API.  Browsers only let a page on another origin read our responses - or send the JSON bodies that
+// need a preflight - if we name that origin here, so the default of no origins keeps the API same-site only.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct CorsConfig
+{
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+}
+
+impl CorsConfig
+{
+    pub fn new() -> CorsConfig
+    {
+        CorsConfig { cors_origins: Vec::new() }
+    }
+
+    // Every allowed origin gets credentialed access, so "*" would hand the session cookie's authority to any page on the web.  It is
+    // dropped here rather than honoured; origins have to be named.
+    pub fn normalized(mut self) -> CorsConfig
+    {
+        if self.cors_origins.iter().any(|allowed| allowed.trim() == "*")
+        {
+            error!("cors_origins may not contain \"*\" since cross-origin requests carry the session cookie; name each origin instead.");
+        }
+        self.cors_origins.retain(|allowed| allowed.trim() != "*");
+        self
+    }
+
+    pub fn allows(&self, origin: &str) -> bool
+    {
+        self.cors_origins.iter().any(|allowed| allowed != "*" && allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    }
+}
+
+pub struct Cors
+{
+    config: CorsConfig,
+    api_prefix: String,
+}
+
+impl Cors
+{
+    pub fn new(config: CorsConfig, api_prefix: String) -> Cors
+    {
+        Cors { config, api_prefix }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors
+{
+    fn info(&self) -> Info
+    {
+        Info { name: "CORS headers for the JSON API", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>)
+    {
+        if !request.uri().path().as_str().starts_with(&self.api_prefix)
+        {
+            return;
+        }
+
+        let Some(origin) = request.headers().get_one("Origin")
+        else { return };
+
+        // The allowed origin is echoed back rather than sent as "*", since the session cookie only travels with credentialed requests.
+        response.adjoin_header(Header::new("Vary", "Origin"));
+        if !self.config.allows(origin)
+        {
+            debug!("Cross-origin request from {} is not in the allowed list.", origin);
+            return;
+        }
+
+        response.set_header(Header::new("Access-Control-Allow-Origin", origin.to_string()));
+        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+
+        if request.method() == Method::Options
+        {
+            response.set_header(Header::new("Access-Control-Allow-Methods", "GET, POST, PUT, DELETE, OPTIONS"));
+            response.set_header(Header::new("Access-Control-Allow-Headers", format!("Content-Type, {}", IDEMPOTENCY_KEY_HEADER)));
+            response.set_header(Header::new("Access-Control-Max-Age", "600"));
+        }
+    }
+}
+
+// Guards every route that changes something.  Browsers name the page behind a cross-site request in the Origin header and a page cannot
+// forge it, so a post from a page that is neither ours nor in cors_origins is turned away before the session cookie it rode in on is
+// used.  Requests with no Origin at all come from outside a browser, where there is no ambient cookie to abuse.
+pub struct TrustedOrigin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for TrustedOrigin
+{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error>
+    {
+        let Some(origin) = request.headers().get_one("Origin")
+        else { return Outcome::Success(TrustedOrigin) };
+
+        let allowed = request.rocket().state::<CorsConfig>().map_or(false, |config| config.allows(origin));
+        if allowed || same_origin(request, origin)
+        {
+            Outcome::Success(TrustedOrigin)
+        }
+        else
+        {
+            debug!("Refused a {} to {} from the page at {}.", request.method(), request.uri(), origin);
+            Outcome::Failure((Status::Forbidden, ()))
+        }
+    }
+}
+
+// Whether the Origin names the host the request was sent to - the one the proxy was asked for, when there is a trusted proxy in front.
+fn same_origin(request: &Request<'_>, origin: &str) -> bool
+{
+    let forwarded_host = if Forwarded::trusts_headers(request) { request.headers().get_one("X-Forwarded-Host") } else { None };
+
+    let Some(host) = forwarded_host.or_else(|| request.headers().get_one("Host"))
+    else { return false };
+
+    // An opaque origin ("null", from sandboxed frames and some redirects) has no host and never matches.
+    origin.split_once("://").map_or(false, |(_, authority)| authority.trim_end_matches('/').eq_ignore_ascii_case(host.trim()))
+}
+
+// Answers preflight requests for any API route; the fairing above decides whether the answer carries permission.
+#[options("/<_..>")]
+pub async fn preflight() -> Status
+{
+    Status::NoContent
+}
@@ -1,7 +1,9 @@
-
-use rocket::{response::Responder};
+use rocket::{Request, http::Status, response::{self, Responder}, serde::json::Json};
 use rocket_dyn_templates::Template;
 
+use crate::gamerunner::Error as RunnerError;
+use super::serde::{ErrorBody, ErrorDetail};
+
 
 #[derive(Responder, Debug)]
 pub enum Error
@@ -12,4 +14,52 @@ pub enum Error
     Forbidden(Template),
     #[response(status=404)]
     NotFound(Template),
-}
\ No newline at end of file
+    #[response(status=503)]
+    ServiceUnavailable(Template),
+}
+
+// A refusal from the JSON API, sent as an ErrorBody.  One the game runner made keeps the context it gave with it.
+#[derive(Debug)]
+pub struct ApiError
+{
+    pub status: Status,
+    pub body: ErrorBody,
+}
+
+impl ApiError
+{
+    pub fn new(status: Status, message: String) -> ApiError
+    {
+        ApiError { status, body: ErrorBody { message, context: None } }
+    }
+
+    pub fn refused(status: Status, err: RunnerError) -> ApiError
+    {
+        let context = err.context;
+        let detail = ErrorDetail
+        {
+            game_id: context.game_id,
+            character_id: context.character_id,
+            request: context.request.map(String::from),
+            expected_state: context.expected_state,
+            actual_state: context.actual_state,
+        };
+        ApiError { status, body: ErrorBody { message: err.message, context: Some(detail) } }
+    }
+}
+
+impl From<(Status, String)> for ApiError
+{
+    fn from((status, message): (Status, String)) -> ApiError
+    {
+        ApiError::new(status, message)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError
+{
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static>
+    {
+        (self.status, Json(self.body)).respond_to(request)
+    }
+}
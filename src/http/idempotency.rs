@@ -0,0 +1,50 @@
+use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}};
+
+use rocket::{Request as HttpRequest, http::Status, request::{FromRequest, Outcome, self}, serde::{Serialize, json}};
+
+use crate::gamerunner::{dispatcher::Request, replay::IdempotencyKey};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+// Longer than any key a client has reason to make up, and short enough that a player's remembered keys stay small.
+const MAX_KEY_LENGTH: usize = 255;
+
+// The Idempotency-Key header, for the posts a client might retry over a bad connection.  A post sent again under the same key, to the
+// same route with the same body, gets the first answer back rather than being applied twice; no header, and the post runs as it always
+// did.
+pub struct Idempotency
+{
+    key: Option<String>,
+}
+
+impl Idempotency
+{
+    // Puts the request under the key, fingerprinted with the route and body it came with.
+    pub fn keyed<T: Serialize>(&self, route: &str, body: &T, request: Request) -> Request
+    {
+        let Some(key) = &self.key
+        else { return request };
+
+        let mut hasher = DefaultHasher::new();
+        route.hash(&mut hasher);
+        json::to_string(body).unwrap_or_default().hash(&mut hasher);
+        Request::Idempotent(IdempotencyKey::new(key, hasher.finish()), Box::new(request))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Idempotency
+{
+    type Error = String;
+
+    async fn from_request(request: &'r HttpRequest<'_>) -> request::Outcome<Self, Self::Error>
+    {
+        match request.headers().get_one(IDEMPOTENCY_KEY_HEADER).map(str::trim)
+        {
+            None => Outcome::Success(Idempotency { key: None }),
+            Some(key) if key.is_empty() || key.len() > MAX_KEY_LENGTH =>
+                Outcome::Failure((Status::BadRequest, format!("An {} must be between 1 and {} characters.", IDEMPOTENCY_KEY_HEADER, MAX_KEY_LENGTH))),
+            Some(key) => Outcome::Success(Idempotency { key: Some(String::from(key)) }),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{collections::HashMap, net::{SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr}, path::PathBuf, sync::Arc};
+
+use parking_lot::Mutex;
+use tracing::{debug, error};
+use rocket::serde::Deserialize;
+use tokio::{io::{AsyncRead, AsyncWrite, copy_bidirectional}, net::{TcpListener, TcpStream}};
+
+// Where else to take connections besides the address and port Rocket binds itself, read out of Rocket.toml alongside the proxy
+// settings.  Rocket only serves one listener, so every extra one relays its connections to Rocket's own.  Rocket then sees them coming
+// from loopback, so each relay records who it is relaying for in RelayedPeers.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ListenConfig
+{
+    // Each entry is either an address and port (e.g. "[::]:8000") or "unix:" and a socket path (e.g. "unix:/run/combat.sock").
+    #[serde(default)]
+    pub listeners: Vec<String>,
+    // Those of the listeners above with a reverse proxy in front, whose X-Forwarded-* headers are believed.  On any other listener a
+    // client could set them to whatever it liked.
+    #[serde(default)]
+    pub proxied_listeners: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Listener
+{
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl ListenConfig
+{
+    pub fn new() -> ListenConfig
+    {
+        ListenConfig { listeners: Vec::new(), proxied_listeners: Vec::new() }
+    }
+
+    pub fn is_proxied(&self, listener: &Listener) -> bool
+    {
+        self.proxied_listeners.iter().any(|spec| Listener::parse(spec).as_ref() == Ok(listener))
+    }
+
+    // Entries that cannot be made sense of are logged and left out rather than stopping the server from starting.
+    pub fn parsed(&self) -> Vec<Listener>
+    {
+        self.listeners.iter().filter_map(|spec| match Listener::parse(spec)
+        {
+            Ok(listener) => Some(listener),
+            Err(err) =>
+            {
+                error!("Ignoring the listener {:?} in Rocket.toml: {}", spec, err);
+                None
+            }
+        }).collect()
+    }
+}
+
+impl Listener
+{
+    pub fn parse(spec: &str) -> Result<Listener, String>
+    {
+        let spec = spec.trim();
+        if let Some(path) = spec.strip_prefix("unix:")
+        {
+            if path.is_empty()
+            {
+                return Err(String::from("a unix listener needs a socket path."));
+            }
+            return Ok(Listener::Unix(PathBuf::from(path)));
+        }
+
+        spec.parse::<SocketAddr>().map(Listener::Tcp).map_err(|_| String::from("expected an address and port, or unix: and a socket path."))
+    }
+}
+
+// Who a relayed connection really came from - None over a unix socket, which has no address to give - and whether the listener it
+// came in on has a proxy in front.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RelayedPeer
+{
+    pub client: Option<IpAddr>,
+    pub proxied: bool,
+}
+
+// The connections the relays have open to Rocket, keyed by the address each connected from, which is what Rocket gives as the remote
+// end of a relayed request.  Entries last as long as the connection.
+#[derive(Clone, Default)]
+pub struct RelayedPeers
+{
+    peers: Arc<Mutex<HashMap<SocketAddr, RelayedPeer>>>,
+}
+
+impl RelayedPeers
+{
+    pub fn lookup(&self, remote: &SocketAddr) -> Option<RelayedPeer>
+    {
+        self.peers.lock().get(remote).copied()
+    }
+}
+
+// Where the relays connect to reach Rocket.  A wildcard bind is reached over loopback of the same family.
+pub fn upstream(address: IpAddr, port: u16) -> SocketAddr
+{
+    let address = match address
+    {
+        IpAddr::V4(v4) if v4.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(v6) if v6.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        _ => address,
+    };
+    SocketAddr::new(address, port)
+}
+
+// Binds the listener and relays every connection it accepts to Rocket until the server exits.  A listener that cannot be bound is
+// logged and given up on; the others carry on.
+pub async fn relay(listener: Listener, upstream: SocketAddr, peers: RelayedPeers, proxied: bool)
+{
+    match listener
+    {
+        Listener::Tcp(address) =>
+        {
+            let tcp = match TcpListener::bind(address).await
+            {
+                Ok(tcp) => tcp,
+                Err(err) =>
+                {
+                    error!("Could not listen on {}: {}", address, err);
+                    return;
+                },
+            };
+            debug!("Relaying connections on {} to {}.", address, upstream);
+            loop
+            {
+                match tcp.accept().await
+                {
+                    Ok((stream, client)) =>
+                    {
+                        tokio::spawn(forward(stream, upstream, peers.clone(), RelayedPeer { client: Some(client.ip()), proxied }));
+                    },
+                    Err(err) => error!("Accepting a connection on {} failed: {}", address, err),
+                }
+            }
+        },
+        #[cfg(unix)]
+        Listener::Unix(path) =>
+        {
+            use std::os::unix::fs::FileTypeExt;
+
+            // A socket file left behind by an earlier run would make the bind fail.  Anything else at the path was put there by someone
+            // and is left alone.
+            match std::fs::symlink_metadata(&path)
+            {
+                Ok(metadata) if metadata.file_type().is_socket() => { let _ = std::fs::remove_file(&path); },
+                Ok(_) =>
+                {
+                    error!("Not listening on {}: something other than a socket is already there.", path.display());
+                    return;
+                },
+                Err(_) => {},
+            }
+            let unix = match tokio::net::UnixListener::bind(&path)
+            {
+                Ok(unix) => unix,
+                Err(err) =>
+                {
+                    error!("Could not listen on {}: {}", path.display(), err);
+                    return;
+                },
+            };
+            debug!("Relaying connections on {} to {}.", path.display(), upstream);
+            loop
+            {
+                match unix.accept().await
+                {
+                    Ok((stream, _)) => { tokio::spawn(forward(stream, upstream, peers.clone(), RelayedPeer { client: None, proxied })); },
+                    Err(err) => error!("Accepting a connection on {} failed: {}", path.display(), err),
+                }
+            }
+        },
+        #[cfg(not(unix))]
+        Listener::Unix(path) => error!("Unix sockets are not available on this platform; not listening on {}.", path.display()),
+    }
+}
+
+async fn forward<S>(mut inbound: S, upstream: SocketAddr, peers: RelayedPeers, peer: RelayedPeer)
+where S: AsyncRead + AsyncWrite + Unpin
+{
+    let mut outbound = match TcpStream::connect(upstream).await
+    {
+        Ok(outbound) => outbound,
+        Err(err) =>
+        {
+            error!("Could not reach the server at {} to relay a connection: {}", upstream, err);
+            return;
+        },
+    };
+
+    // Recorded before a byte is relayed, so it is there by the time Rocket reads the request.
+    let relayed_from = outbound.local_addr().ok();
+    if let Some(relayed_from) = relayed_from
+    {
+        peers.peers.lock().insert(relayed_from, peer);
+    }
+
+    if let Err(err) = copy_bidirectional(&mut inbound, &mut outbound).await
+    {
+        debug!("A relayed connection closed with an error: {}", err);
+    }
+
+    if let Some(relayed_from) = relayed_from
+    {
+        peers.peers.lock().remove(&relayed_from);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{net::{SocketAddr, IpAddr, Ipv4Addr}, path::PathBuf, time::Duration};
+
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+    use super::{ListenConfig, Listener, RelayedPeer, RelayedPeers, relay};
+
+    #[test]
+    pub fn a_listener_is_an_address_and_port_or_a_unix_socket_path()
+    {
+        assert_eq!(Ok(Listener::Tcp("[::]:8000".parse().unwrap())), Listener::parse("[::]:8000"));
+        assert_eq!(Ok(Listener::Tcp("127.0.0.1:9000".parse().unwrap())), Listener::parse("  127.0.0.1:9000 "));
+        assert_eq!(Ok(Listener::Unix(PathBuf::from("/run/combat.sock"))), Listener::parse("unix:/run/combat.sock"));
+    }
+
+    #[test]
+    pub fn a_listener_with_no_port_or_no_socket_path_is_refused()
+    {
+        assert!(Listener::parse("unix:").is_err());
+        assert!(Listener::parse("127.0.0.1").is_err());
+        assert!(Listener::parse("localhost:8000").is_err());
+        assert!(Listener::parse("").is_err());
+
+        let config = ListenConfig { listeners: vec![String::from("[::]:8000"), String::from("nonsense")], proxied_listeners: Vec::new() };
+        assert_eq!(vec![Listener::Tcp("[::]:8000".parse().unwrap())], config.parsed());
+    }
+
+    #[test]
+    pub fn only_the_listeners_named_as_proxied_are_proxied()
+    {
+        let config = ListenConfig 
+        { 
+            listeners: vec![String::from("[::]:8000"), String::from("unix:/run/combat.sock")], 
+            proxied_listeners: vec![String::from("unix:/run/combat.sock")],
+        };
+
+        assert!(config.is_proxied(&Listener::Unix(PathBuf::from("/run/combat.sock"))));
+        assert!(!config.is_proxied(&Listener::Tcp("[::]:8000".parse().unwrap())));
+    }
+
+    #[tokio::test]
+    pub async fn rocket_can_look_up_who_a_relayed_connection_came_from()
+    {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_address = upstream.local_addr().unwrap();
+        let listen_on = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+        let peers = RelayedPeers::default();
+        tokio::spawn(relay(Listener::Tcp(listen_on), upstream_address, peers.clone(), false));
+
+        let mut client = None;
+        for _ in 0..50
+        {
+            match TcpStream::connect(listen_on).await
+            {
+                Ok(stream) => { client = Some(stream); break; },
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        }
+        let mut client = client.expect("The relay never started listening.");
+        client.write_all(b"ping").await.unwrap();
+
+        let (mut relayed, remote): (TcpStream, SocketAddr) = upstream.accept().await.unwrap();
+        let mut ping = [0u8; 4];
+        relayed.read_exact(&mut ping).await.unwrap();
+
+        assert_eq!(Some(RelayedPeer { client: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), proxied: false }), peers.lookup(&remote));
+        assert!(peers.lookup(&upstream_address).is_none());
+    }
+}
@@ -1,9 +1,20 @@
-use rocket::get;
+use rocket::{get, State, serde::json, futures::{Sink, SinkExt, Stream, StreamExt}, tokio::select};
+use rocket_ws::{WebSocket, Channel, Message as Frame};
+use tokio::sync::oneshot::channel;
+use tracing::debug;
 use uuid::Uuid;
 
 use rocket::response::stream::{Event, EventStream};
 use rocket::tokio::time::{self, Duration};
 
+use crate::gamerunner::{dispatcher::{Request, Message, Outcome, Roll, Action}, notifier::{WhatChanged, InitiativeEntry}, absence::AbsentFallback, sync::CombatSync};
+use crate::tracker::{game::{ActionType, AfterPass, TimedEvent, RewindTarget, SkipReason}, character::{WoundView, WoundTier}, encounter::TriggerAction, journal::ChatAudience, 
+    rules::Edition, catalog::Ammunition};
+
+use super::{serde::{CommandFrame, ClientCommand, CommandReply, ActionKind, InitiativeRoll, InboxNotice, SocketFrame, ChangeDetail, InitiativeSlot, TimedEventView, 
+    TriggerView, WoundsView, CatalogEntry, CombatantView, AbsencePolicyView}, validation::Validate, queue::RunnerPipe, server::{do_send, metatype_view, turn_name, status_name}, 
+    session::Session, metagame::Metagame, cors::TrustedOrigin};
+
 #[get("/<group_id>")]
 pub fn start_message_stream(group_id: Uuid) -> EventStream![] {
     EventStream! {
@@ -14,3 +25,399 @@ pub fn start_message_stream(group_id: Uuid) -> EventStream![] {
         }
     }
 }
+
+// The two-way connection to a game.  What the player is told about the game is pushed down it as it lands, and commands sent up it are
+// run as the player and answered on it.  The Origin is checked as for a post, since a page elsewhere could otherwise open a socket that
+// rides the session cookie.
+#[get("/<id>/socket")]
+pub fn command_socket(_origin: TrustedOrigin, id: Uuid, ws: WebSocket, session: Session, state: &State<Metagame<'_>>) -> Channel<'static>
+{
+    let (player_id, pipe) = (session.player_id(), state.game_runner_pipe.clone());
+
+    ws.channel(move |stream| Box::pin(async move
+    {
+        let (outgoing, incoming) = stream.split();
+        serve_socket(id, player_id, pipe, incoming, outgoing).await
+    }))
+}
+
+// Runs a socket until the client goes.  The player's inbox for the game is read from where the last read left off, and in between the
+// socket waits on either the inbox filling or a frame coming up.  Kept apart from the route so it can be driven without a connection.
+pub async fn serve_socket<I, O, E>(game_id: Uuid, player_id: Uuid, pipe: RunnerPipe, mut incoming: I, mut outgoing: O) -> Result<(), E>
+where I: Stream<Item = Result<Frame, E>> + Unpin, O: Sink<Frame, Error = E> + Unpin
+{
+    let mut since = None;
+
+    loop
+    {
+        let (runner_sender, response_channel) = channel::<Outcome>();
+        let msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: runner_sender, msg: Request::InboxAfter(since) };
+
+        // A player who isn't at the table, or a runner that can't be reached, ends the socket; the reason goes down it first.
+        let mut ready = match do_send(msg, pipe.clone(), response_channel).await
+        {
+            Ok(Outcome::InboxAfter { notifications, ready }) =>
+            {
+                for (sequence, change) in notifications
+                {
+                    outgoing.send(text_frame(&SocketFrame::Notice(inbox_notice(sequence, &change)))).await?;
+                    since = Some(sequence);
+                }
+                ready
+            },
+            Ok(Outcome::Error(err)) => return close(&mut outgoing, err.message).await,
+            Ok(_) => return close(&mut outgoing, String::from("Unexpected response from the game runner.")).await,
+            Err(refusal) => return close(&mut outgoing, refusal.body.message).await,
+        };
+
+        select!
+        {
+            _ = ready.changed() => {},
+            frame = incoming.next() => match frame
+            {
+                Some(Ok(Frame::Text(text))) =>
+                {
+                    let reply = run_command(&text, game_id, player_id, pipe.clone()).await;
+                    outgoing.send(text_frame(&SocketFrame::Reply(reply))).await?;
+                },
+                Some(Ok(Frame::Close(_))) | None => return Ok(()),
+                // Pings are answered underneath; binary frames carry nothing this socket reads.
+                Some(Ok(_)) => {},
+                Some(Err(err)) => return Err(err),
+            },
+        }
+    }
+}
+
+// A change from the player's inbox as it goes to a client, over a socket or a poll.
+pub fn inbox_notice(sequence: usize, change: &WhatChanged) -> InboxNotice
+{
+    InboxNotice { sequence, kind: change.kind(), detail: change_detail(change) }
+}
+
+fn change_detail(change: &WhatChanged) -> ChangeDetail
+{
+    match change
+    {
+        WhatChanged::NewPlayer(joined) => ChangeDetail::NewPlayer { player_id: joined.player_id, name: joined.name.to_string() },
+        WhatChanged::NewCharacter(added) => ChangeDetail::NewCharacter { player_id: added.player_id, char_id: added.character_id, metatype: metatype_view(added.metatype) },
+        WhatChanged::StartingInitiativePhase => ChangeDetail::StartingInitiativePhase,
+        WhatChanged::StartingCombatRound => ChangeDetail::StartingCombatRound,
+        WhatChanged::PlayerActed => ChangeDetail::PlayerActed,
+        WhatChanged::NamedActionTaken { character, name } => ChangeDetail::NamedActionTaken { char_id: *character, name: name.clone() },
+        WhatChanged::TurnAdvanced(turn) => ChangeDetail::TurnAdvanced { pass: turn.pass, up: initiative_slots(&turn.up), on_deck: initiative_slots(&turn.on_deck), 
+            events_up: turn.events_up.iter().map(timed_event_view).collect(), events_on_deck: turn.events_on_deck.iter().map(timed_event_view).collect() },
+        WhatChanged::PassEnded(after) => ChangeDetail::PassEnded { next_round: *after == AfterPass::NextRound },
+        WhatChanged::PassAdvanced => ChangeDetail::PassAdvanced,
+        WhatChanged::RoundAdvanced => ChangeDetail::RoundAdvanced,
+        WhatChanged::CombatStarted(order) => ChangeDetail::CombatStarted(initiative_slots(order)),
+        WhatChanged::UpNext { character } => ChangeDetail::UpNext { char_id: *character },
+        WhatChanged::YourTurn { character } => ChangeDetail::YourTurn { char_id: *character },
+        WhatChanged::CombatEnded => ChangeDetail::CombatEnded,
+        WhatChanged::CharactersRested(characters) => ChangeDetail::CharactersRested(characters.clone()),
+        WhatChanged::GameEnded => ChangeDetail::GameEnded,
+        WhatChanged::GameArchived => ChangeDetail::GameArchived,
+        WhatChanged::DrainTestPending(character) => ChangeDetail::DrainTestPending(*character),
+        WhatChanged::CombatDeclared(characters) => ChangeDetail::CombatDeclared(characters.clone()),
+        WhatChanged::InitiativeAdded(character) => ChangeDetail::InitiativeAdded(*character),
+        WhatChanged::InitiativeAdjusted { character, delta } => ChangeDetail::InitiativeAdjusted { char_id: *character, delta: *delta },
+        WhatChanged::TimedEventUp(event) => ChangeDetail::TimedEventUp(timed_event_view(event)),
+        WhatChanged::TriggersFired(triggers) => ChangeDetail::TriggersFired(triggers.iter().map(|trigger| match &trigger.action
+        {
+            TriggerAction::Reinforcements(characters) => TriggerView { round: trigger.round, pass: trigger.pass, reinforcements: characters.clone(), prompt: None },
+            TriggerAction::Prompt(note) => TriggerView { round: trigger.round, pass: trigger.pass, reinforcements: Vec::new(), prompt: Some(note.clone()) },
+        }).collect()),
+        WhatChanged::ReinforcementsArrived(characters) => ChangeDetail::ReinforcementsArrived(characters.clone()),
+        WhatChanged::CharacterMoved(character) => ChangeDetail::CharacterMoved(*character),
+        WhatChanged::Engaged { attacker, defender } => ChangeDetail::Engaged { attacker: *attacker, defender: *defender },
+        WhatChanged::Disengaged { character, from } => ChangeDetail::Disengaged { char_id: *character, from: *from },
+        WhatChanged::WentOnFullDefense(character) => ChangeDetail::WentOnFullDefense(*character),
+        WhatChanged::AreaAttackResolved(character) => ChangeDetail::AreaAttackResolved(*character),
+        WhatChanged::DamageEventApplied { source, targets } => ChangeDetail::DamageEventApplied { source: *source, targets: targets.clone() },
+        WhatChanged::ResistanceTestMade(character) => ChangeDetail::ResistanceTestMade(*character),
+        WhatChanged::SpellCast(character) => ChangeDetail::SpellCast(*character),
+        WhatChanged::SpellDropped(character) => ChangeDetail::SpellDropped(*character),
+        WhatChanged::PassesOverridden(character) => ChangeDetail::PassesOverridden(*character),
+        WhatChanged::ArmorDegraded(character) => ChangeDetail::ArmorDegraded(*character),
+        WhatChanged::RoundEnded(summary) => ChangeDetail::RoundEnded { passes: summary.passes, combatants: summary.combatants.clone(), 
+            outstanding_resistance_tests: summary.outstanding_resistance_tests },
+        WhatChanged::Chat(line) =>
+        {
+            let (audience, to) = match line.audience
+            {
+                ChatAudience::Table => ("table", None),
+                ChatAudience::Gm => ("gm", None),
+                ChatAudience::Player(player_id) => ("player", Some(player_id)),
+            };
+            ChangeDetail::Chat { from: line.from, audience: String::from(audience), to, text: line.text.clone() }
+        },
+        WhatChanged::DiceRolled(record) => ChangeDetail::DiceRolled { char_id: record.character, label: record.label.clone(), dice: record.roll.dice.clone(), 
+            hits: record.roll.hits, glitch: record.roll.glitch },
+        WhatChanged::CharacterDamaged { character, wounds } => ChangeDetail::CharacterDamaged { char_id: *character, wounds: wounds_view(wounds) },
+        WhatChanged::CharacterHealed { character, wounds } => ChangeDetail::CharacterHealed { char_id: *character, wounds: wounds_view(wounds) },
+        WhatChanged::EdgeSpent(character) => ChangeDetail::EdgeSpent(*character),
+        WhatChanged::Rewound(target) => ChangeDetail::Rewound(String::from(match target
+        {
+            RewindTarget::PassStart => "pass_start",
+            RewindTarget::PreviousTurn => "previous_turn",
+        })),
+        WhatChanged::CheckpointRestored(label) => ChangeDetail::CheckpointRestored(label.clone()),
+        WhatChanged::InitiativeReminder { characters } => ChangeDetail::InitiativeReminder { characters: characters.clone() },
+        WhatChanged::InitiativeTimeoutChanged(timeout) => ChangeDetail::InitiativeTimeoutChanged { timeout_ms: timeout.map(|timeout| timeout.as_millis() as u64) },
+        WhatChanged::EditionChanged(edition) => ChangeDetail::EditionChanged(String::from(match edition
+        {
+            Edition::SR4 => "sr4",
+            Edition::SR5 => "sr5",
+            Edition::Anarchy => "anarchy",
+            Edition::Generic => "generic",
+        })),
+        WhatChanged::ActionLabelsChanged(labels) => ChangeDetail::ActionLabelsChanged { free: labels.free.clone(), simple: labels.simple.clone(), complex: labels.complex.clone() },
+        WhatChanged::ActionCatalogChanged(catalog) => ChangeDetail::ActionCatalogChanged(catalog.iter().map(|action| CatalogEntry
+        {
+            name: action.name.clone(),
+            cost: match action.cost
+            {
+                ActionType::Free => ActionKind::Free,
+                ActionType::Simple => ActionKind::Simple,
+                ActionType::Complex => ActionKind::Complex,
+            },
+            description: action.description.clone(),
+            fires: match action.ammunition { Ammunition::Fires(rounds) => Some(rounds), _ => None },
+            reloads: matches!(action.ammunition, Ammunition::Reloads),
+        }).collect()),
+        WhatChanged::CastLimitsChanged(limits) => ChangeDetail::CastLimitsChanged { per_player: limits.per_player, cast: limits.cast, unique_names: limits.unique_names },
+        WhatChanged::CombatantsSynced(sync) =>
+        {
+            let (full, changed, removed) = match sync
+            {
+                CombatSync::Delta { changed, removed, .. } => (false, changed, removed.clone()),
+                CombatSync::Full { combatants, .. } => (true, combatants, Vec::new()),
+            };
+            let changed = changed.iter().map(|combatant| CombatantView { char_id: combatant.character, turn: String::from(turn_name(combatant.turn)), 
+                status: combatant.status.iter().map(|status| String::from(status_name(status))).collect(), wounds: combatant.wounds.as_ref().map(wounds_view) }).collect();
+            ChangeDetail::CombatantsSynced { sequence: sync.sequence(), full, changed, removed }
+        },
+        WhatChanged::JackedIn(character) => ChangeDetail::JackedIn(*character),
+        WhatChanged::JackedOut(character) => ChangeDetail::JackedOut(*character),
+        WhatChanged::MatrixActionTaken { decker, target } => ChangeDetail::MatrixActionTaken { decker: *decker, target: *target },
+        WhatChanged::PlaneChanged { character, plane } => ChangeDetail::PlaneChanged { char_id: *character, plane: *plane },
+        WhatChanged::CharacterUpdated { character, fields } => ChangeDetail::CharacterUpdated { char_id: *character, fields: fields.iter().map(|field| String::from(*field)).collect() },
+        WhatChanged::CharacterUpdatePending(character) => ChangeDetail::CharacterUpdatePending(*character),
+        WhatChanged::PlayerDeparted(player_id) => ChangeDetail::PlayerDeparted(*player_id),
+        WhatChanged::GameCloned(game_id) => ChangeDetail::GameCloned(*game_id),
+        WhatChanged::PlayerUnreachable(player_id) => ChangeDetail::PlayerUnreachable(*player_id),
+        WhatChanged::PlayerAbsent { player, absent } => ChangeDetail::PlayerAbsent { player_id: *player, absent: *absent },
+        WhatChanged::AbsencePolicyChanged(policy) => ChangeDetail::AbsencePolicyChanged(policy.map(|policy| AbsencePolicyView
+        {
+            timeout_ms: policy.timeout.as_millis() as u64,
+            fallback: String::from(match policy.fallback
+            {
+                AbsentFallback::DelegateToGm => "delegate_to_gm",
+                AbsentFallback::Skip => "skip",
+            }),
+        })),
+        WhatChanged::TurnSkipped(character) => ChangeDetail::TurnSkipped(*character),
+        WhatChanged::SittingOut { character, reason } => ChangeDetail::SittingOut { char_id: *character, reason: reason.map(|reason| String::from(match reason
+        {
+            SkipReason::Unconscious => "unconscious",
+            SkipReason::Delaying => "delaying",
+            SkipReason::Absent => "absent",
+        })) },
+        WhatChanged::Composite(changes) => ChangeDetail::Composite(changes.iter().map(|change| change_detail(change)).collect()),
+    }
+}
+
+fn initiative_slots(order: &[InitiativeEntry]) -> Vec<InitiativeSlot>
+{
+    order.iter().map(|entry| InitiativeSlot { char_id: entry.character, initiative: entry.initiative }).collect()
+}
+
+fn timed_event_view(event: &TimedEvent) -> TimedEventView
+{
+    TimedEventView { id: event.id, label: event.label.clone(), initiative: event.initiative, rounds_away: event.rounds_away }
+}
+
+fn wounds_view(wounds: &WoundView) -> WoundsView
+{
+    match wounds
+    {
+        WoundView::Exact(monitor) => WoundsView::Exact { physical_filled: monitor.physical_filled, physical_max: monitor.physical_max, 
+            stun_filled: monitor.stun_filled, stun_max: monitor.stun_max },
+        WoundView::Tier(tier) => WoundsView::Tier(String::from(match tier
+        {
+            WoundTier::Unhurt => "unhurt",
+            WoundTier::LightlyHurt => "lightly_hurt",
+            WoundTier::BadlyHurt => "badly_hurt",
+            WoundTier::Critical => "critical",
+            WoundTier::Down => "down",
+        })),
+    }
+}
+
+fn text_frame(frame: &SocketFrame) -> Frame
+{
+    Frame::Text(json::to_string(frame).unwrap_or_default())
+}
+
+async fn close<O, E>(outgoing: &mut O, reason: String) -> Result<(), E>
+where O: Sink<Frame, Error = E> + Unpin
+{
+    outgoing.send(text_frame(&SocketFrame::Reply(CommandReply { id: None, ok: false, error: Some(reason) }))).await?;
+    outgoing.send(Frame::Close(None)).await
+}
+
+// Commands a client sends up the socket it holds for notifications, so that rolling initiative or taking an action costs one frame
+// instead of an HTTP request of its own.  Each frame is turned into the same runner request the HTTP routes send, as the socket's
+// player, and answered with a reply carrying the frame's id.  The socket was opened for one game and is only good for that one; a frame
+// naming another is turned away rather than run against a game the socket never checked.
+pub async fn run_command(frame: &str, game_id: Uuid, player_id: Uuid, pipe: RunnerPipe) -> CommandReply
+{
+    let frame = match json::from_str::<CommandFrame>(frame)
+    {
+        Ok(frame) => frame,
+        Err(err) => return CommandReply { id: None, ok: false, error: Some(format!("The command could not be read: {}", err)) },
+    };
+    debug!("Command {} received from player {} for game {}.", frame.id, player_id, game_id);
+    if frame.game_id != game_id
+    {
+        return CommandReply { id: Some(frame.id), ok: false, error: Some(String::from("This socket is for another game.")) };
+    }
+
+    let request = match frame.command
+    {
+        ClientCommand::RollInitiative { char_id, roll } =>
+        {
+            let problems = InitiativeRoll { char_id, roll }.problems();
+            if !problems.is_empty()
+            {
+                return CommandReply { id: Some(frame.id), ok: false, error: Some(problems.join(" ")) };
+            }
+            Request::AddInitiativeRoll(Roll { character_id: char_id, roll })
+        },
+        ClientCommand::TakeAction { char_id, action, roll, catalog } =>
+        {
+            let action = match action
+            {
+                ActionKind::Free => ActionType::Free,
+                ActionKind::Simple => ActionType::Simple,
+                ActionKind::Complex => ActionType::Complex,
+            };
+            Request::TakeAction(Action { character_id: char_id, action, roll, catalog })
+        },
+    };
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(player_id), game_id: Some(game_id), reply_channel: runner_sender, msg: request };
+
+    match do_send(msg, pipe, response_channel).await
+    {
+        Ok(Outcome::Error(err)) => CommandReply { id: Some(frame.id), ok: false, error: Some(err.message) },
+        Ok(_) => CommandReply { id: Some(frame.id), ok: true, error: None },
+        Err(refusal) => CommandReply { id: Some(frame.id), ok: false, error: Some(refusal.body.message) },
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use rocket::futures::{channel::mpsc::{unbounded, UnboundedReceiver, SendError}, StreamExt};
+    use rocket::serde::json;
+    use rocket_ws::Message as Frame;
+    use uuid::Uuid;
+
+    use crate::gamerunner::{dispatcher::Request, notifier::{ChangeKind, WhatChanged}, testing::{TestTable, add_new_game}};
+    use crate::tracker::character::{WoundView, WoundTier};
+    use crate::http::{queue::{RunnerPipe, OverflowPolicy}, serde::{SocketFrame, InboxNotice, ChangeDetail, WoundsView}};
+
+    use super::{serve_socket, inbox_notice};
+
+    // The next thing down the socket that is not a notice, skipping whatever the player was told in between.
+    async fn next_reply(outgoing: &mut UnboundedReceiver<Frame>) -> (Option<u64>, bool)
+    {
+        while let Some(frame) = outgoing.next().await
+        {
+            if let Frame::Text(text) = frame
+            {
+                if let Ok(SocketFrame::Reply(reply)) = json::from_str::<SocketFrame>(&text)
+                {
+                    return (reply.id, reply.ok);
+                }
+            }
+        }
+        panic!("The socket closed without replying.");
+    }
+
+    async fn next_notice_of(outgoing: &mut UnboundedReceiver<Frame>, kind: ChangeKind) -> usize
+    {
+        while let Some(frame) = outgoing.next().await
+        {
+            if let Frame::Text(text) = frame
+            {
+                match json::from_str::<SocketFrame>(&text)
+                {
+                    Ok(SocketFrame::Notice(notice)) if notice.kind == kind => return notice.sequence,
+                    _ => {},
+                }
+            }
+        }
+        panic!("The socket closed before a {:?} notice came down it.", kind);
+    }
+
+    #[tokio::test]
+    pub async fn a_socket_runs_the_commands_sent_up_it_and_pushes_what_the_player_is_told_down_it()
+    {
+        let table = TestTable::new().with_players(1).combat_ready().await;
+        let (player_id, character_id) = table.players[0];
+
+        let (to_socket, incoming) = unbounded::<Result<Frame, SendError>>();
+        let (outgoing, mut from_socket) = unbounded::<Frame>();
+        let pipe = RunnerPipe::new(table.runner.clone(), OverflowPolicy::Wait);
+        let socket = tokio::spawn(serve_socket(table.game_id, player_id, pipe, incoming, outgoing));
+
+        let roll = format!(r#"{{"id":7,"game_id":"{}","command":{{"RollInitiative":{{"char_id":"{}","roll":10}}}}}}"#, table.game_id, character_id);
+        to_socket.unbounded_send(Ok(Frame::Text(roll))).unwrap();
+        assert_eq!((Some(7), true), next_reply(&mut from_socket).await);
+
+        to_socket.unbounded_send(Ok(Frame::Text(String::from("not a command")))).unwrap();
+        assert_eq!((None, false), next_reply(&mut from_socket).await);
+
+        // The player's other game is not this socket's to command, whatever the frame says.
+        let (_, other_game) = add_new_game(&table.runner).await;
+        let elsewhere = format!(r#"{{"id":8,"game_id":"{}","command":{{"RollInitiative":{{"char_id":"{}","roll":10}}}}}}"#, other_game, character_id);
+        to_socket.unbounded_send(Ok(Frame::Text(elsewhere))).unwrap();
+        assert_eq!((Some(8), false), next_reply(&mut from_socket).await);
+
+        // Nothing is sent up the socket here; the notice comes down because the player's inbox filled.
+        table.send(table.gm, Request::StartCombatRound).await;
+        next_notice_of(&mut from_socket, ChangeKind::YourTurn).await;
+
+        drop(to_socket);
+        assert!(socket.await.unwrap().is_ok());
+    }
+
+    #[test]
+    pub fn a_notice_carries_what_the_change_did_and_reads_back_typed()
+    {
+        let character = Uuid::new_v4();
+        let change = WhatChanged::CharacterDamaged { character, wounds: WoundView::Tier(WoundTier::BadlyHurt) };
+
+        let text = json::to_string(&inbox_notice(4, &change)).unwrap();
+        let notice = json::from_str::<InboxNotice>(&text).unwrap();
+        assert!(notice.sequence == 4 && notice.kind == ChangeKind::CharacterDamaged);
+        assert!(matches!(notice.detail, ChangeDetail::CharacterDamaged { char_id, wounds: WoundsView::Tier(ref tier) } if char_id == character && tier == "badly_hurt"));
+    }
+
+    #[tokio::test]
+    pub async fn a_socket_opened_by_someone_not_at_the_table_says_why_and_closes()
+    {
+        let table = TestTable::new().seated().await;
+
+        let (_to_socket, incoming) = unbounded::<Result<Frame, SendError>>();
+        let (outgoing, mut from_socket) = unbounded::<Frame>();
+        let pipe = RunnerPipe::new(table.runner.clone(), OverflowPolicy::Wait);
+
+        assert!(serve_socket(Uuid::new_v4(), Uuid::new_v4(), pipe, incoming, outgoing).await.is_ok());
+        assert_eq!((None, false), next_reply(&mut from_socket).await);
+        assert!(matches!(from_socket.next().await, Some(Frame::Close(None))));
+    }
+}
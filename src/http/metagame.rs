@@ -1,32 +1,68 @@
 use std::collections::HashMap;
 
-use log::debug;
+use tracing::debug;
 use parking_lot::RwLock;
 use rocket::http::uri::Origin;
-use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 
-use crate::gamerunner::dispatcher::Message;
+use crate::tracker::names::Name;
+
+use super::queue::RunnerPipe;
 
 
 pub struct Metagame<'s>
 {
-    pub game_runner_pipe: Sender<Message>,
+    pub game_runner_pipe: RunnerPipe,
     pub game_details: RwLock<HashMap<Uuid, GameAdditionalInformation<'s>>>,
 }
 
 impl<'s> Metagame<'s>
 {
-    pub fn new<'a>(my_channel: Sender<Message>) -> Metagame<'a>
+    pub fn new<'a>(my_channel: RunnerPipe) -> Metagame<'a>
     {
         Metagame { game_runner_pipe: my_channel, game_details: RwLock::new(HashMap::new())}
     }
 
-    pub fn new_game(&self, game_id: Uuid, gm_id: Uuid, game_name: String, game_url: Origin<'s>)
+    pub fn new_game(&self, game_id: Uuid, gm_id: Uuid, game_name: Name, game_url: Origin<'s>)
     {
         let mut detail_set = self.game_details.write();
 
-        detail_set.insert(game_id, GameAdditionalInformation{gm_id, game_name, game_url});
+        // Eight hex digits is short enough to read out across a table; a repeat is all but impossible, but costs nothing to rule out.
+        let mut invite_code = Metagame::new_invite_code();
+        while detail_set.values().any(|game| game.invite_code == invite_code)
+        {
+            invite_code = Metagame::new_invite_code();
+        }
+
+        let overlay_token = Uuid::new_v4().simple().to_string();
+        detail_set.insert(game_id, GameAdditionalInformation{gm_id, game_name, game_url, invite_code, overlay_token});
+    }
+
+    fn new_invite_code() -> String
+    {
+        Uuid::new_v4().simple().to_string()[..8].to_uppercase()
+    }
+
+    pub fn invite_code(&self, game_id: Uuid) -> Option<String>
+    {
+        self.game_details.read().get(&game_id).map(|game| game.invite_code.clone())
+    }
+
+    pub fn overlay_token(&self, game_id: Uuid) -> Option<String>
+    {
+        self.game_details.read().get(&game_id).map(|game| game.overlay_token.clone())
+    }
+
+    pub fn overlay_token_matches(&self, game_id: Uuid, token: &str) -> bool
+    {
+        self.game_details.read().get(&game_id).map_or(false, |game| game.overlay_token == token)
+    }
+
+    // Codes are matched without regard to case or the spaces around them, as players are likely to type them in by hand.
+    pub fn game_by_invite(&self, invite_code: &str) -> Option<Uuid>
+    {
+        let invite_code = invite_code.trim();
+        self.game_details.read().iter().find(|(_, game)| game.invite_code.eq_ignore_ascii_case(invite_code)).map(|(game_id, _)| *game_id)
     }
 
     pub fn validate_ownership(&self, player_id: Uuid, game_id: Uuid) -> bool
@@ -43,7 +79,7 @@ impl<'s> Metagame<'s>
         else {return false};
     }
 
-    pub fn game_name(&self, game_id: Uuid) -> Option<String>
+    pub fn game_name(&self, game_id: Uuid) -> Option<Name>
     {
         let lock = self.game_details.read();
 
@@ -51,12 +87,67 @@ impl<'s> Metagame<'s>
 
         return Some(game.game_name.clone());
     }
+
+    pub fn forget_game(&self, game_id: Uuid)
+    {
+        self.game_details.write().remove(&game_id);
+    }
+
+    // The runner has already deleted the games; this drops what the web side kept about them.
+    pub fn forget_gm(&self, gm_id: Uuid)
+    {
+        self.game_details.write().retain(|_, game| game.gm_id != gm_id);
+    }
 }
 
 pub struct GameAdditionalInformation<'a>
 {
     pub gm_id: Uuid,
-    pub game_name: String,
+    pub game_name: Name,
     pub game_url: Origin<'a>,
+    // Lets a player join without finding the game in the lobby; the GM hands it out.
+    pub invite_code: String,
+    // Lets a stream overlay, which has no session of its own, read the initiative order.  Only the GM is shown it.
+    pub overlay_token: String,
+}
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use rocket::http::uri::Origin;
+    use tokio::sync::mpsc::channel;
+    use uuid::Uuid;
+
+    use crate::http::queue::{RunnerPipe, OverflowPolicy};
+
+    use super::Metagame;
 
-}
\ No newline at end of file
+    fn metagame<'a>() -> Metagame<'a>
+    {
+        let (sender, _) = channel(1);
+        Metagame::new(RunnerPipe::new(sender, OverflowPolicy::Wait))
+    }
+
+    #[test]
+    pub fn an_invite_code_finds_its_game_however_the_player_typed_it()
+    {
+        let metagame = metagame();
+        let (game_id, other_game_id, gm_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        metagame.new_game(game_id, gm_id, Arc::from("Bug City"), Origin::ROOT);
+        metagame.new_game(other_game_id, gm_id, Arc::from("Seattle"), Origin::ROOT);
+
+        let invite_code = metagame.invite_code(game_id).unwrap();
+        assert_eq!(8, invite_code.len());
+        assert_ne!(Some(invite_code.clone()), metagame.invite_code(other_game_id));
+
+        assert_eq!(Some(game_id), metagame.game_by_invite(&invite_code));
+        assert_eq!(Some(game_id), metagame.game_by_invite(&format!("  {}\n", invite_code.to_lowercase())));
+        assert_eq!(Some(other_game_id), metagame.game_by_invite(&metagame.invite_code(other_game_id).unwrap()));
+        assert_eq!(None, metagame.game_by_invite(""));
+        assert_eq!(None, metagame.game_by_invite("NOTACODE"));
+
+        metagame.forget_game(game_id);
+        assert_eq!(None, metagame.game_by_invite(&invite_code));
+    }
+}
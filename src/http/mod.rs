@@ -5,4 +5,14 @@ pub mod renders;
 pub mod errors;
 pub mod session;
 pub mod metagame;
-pub mod messaging;
\ No newline at end of file
+pub mod messaging;
+pub mod accounts;
+pub mod proxy;
+pub mod cors;
+pub mod validation;
+pub mod idempotency;
+pub mod queue;
+pub mod assets;
+pub mod listen;
+pub mod startup;
+pub mod store;
\ No newline at end of file
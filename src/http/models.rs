@@ -4,50 +4,77 @@ use rocket::serde::{Serialize, Deserialize};
 use rocket::form::FromForm;
 use uuid::Uuid;
 
-use crate::tracker::character::{Character, Metatypes};
+use crate::tracker::{character::{Character, Metatypes}, magic::Plane, names::Name};
+
+use super::validation::MAX_NAME_LENGTH;
 
 #[derive(Serialize, Deserialize)]
 pub struct IndexModel<'r>
 {
     pub player_handle: &'r str,
-    pub summaries: Vec<GameSummary>
+    pub summaries: Vec<GameSummary>,
+    pub csrf_token: Arc<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct GameSummary
 {
-    pub game_name: String,
+    pub game_name: Name,
     pub url: String,
     pub gm: Uuid
 }
 
+#[derive(Serialize)]
+pub struct LobbyModel<'r>
+{
+    pub player_handle: &'r str,
+    pub games: Vec<LobbyGame>,
+    pub csrf_token: Arc<String>,
+}
+
+// One joinable game as the lobby lists it.
+#[derive(Serialize)]
+pub struct LobbyGame
+{
+    pub game_id: Uuid,
+    pub game_name: Name,
+    pub gm_name: Name,
+    pub players: usize,
+    pub characters: usize,
+    pub phase: String,
+}
+
 #[derive(Serialize)]
 pub struct GMView
 {
     pub game_id: Uuid,
+    pub invite_code: String,
+    pub overlay_token: String,
     pub pcs: Vec<SimpleCharacterView>,
     pub npcs: Vec<SimpleCharacterView>,
+    pub csrf_token: Arc<String>,
 }
 
 #[derive(Serialize)]
 pub struct SimpleCharacterView
 {
-    pub char_name: String,
+    pub char_name: Name,
     pub char_id: Uuid,
     pub metatype: Metatypes,
+    pub plane: Plane,
 }
 
 impl From<Character> for SimpleCharacterView
 {
     fn from(src: Character) -> Self {
-        SimpleCharacterView { char_name: src.name.clone(), char_id: src.id.clone(), metatype: src.metatype }
+        SimpleCharacterView { char_name: src.name.clone(), char_id: src.id.clone(), metatype: src.metatype, plane: src.plane }
     }
 }
 
 impl From<&Character> for SimpleCharacterView
 {
     fn from(src: &Character) -> Self {
-        SimpleCharacterView { char_name: src.name.clone(), char_id: src.id.clone(), metatype: src.metatype }
+        SimpleCharacterView { char_name: src.name.clone(), char_id: src.id.clone(), metatype: src.metatype, plane: src.plane }
     }
 }
 
@@ -56,8 +83,9 @@ pub struct PlayerView
 {
     pub player_handle: Arc<String>,
     pub game_id: Uuid,
-    pub game_name: String,
-    pub character_state: Option<SimpleCharacterView>
+    pub game_name: Name,
+    pub character_state: Option<SimpleCharacterView>,
+    pub csrf_token: Arc<String>,
 }
 
 // #[derive(Serialize)]
@@ -72,15 +100,28 @@ pub struct PlayerView
 #[derive(FromForm)]
 pub struct NewGame<'r>
 {
-    pub game_name: &'r str
+    #[field(validate = len(1..=MAX_NAME_LENGTH))]
+    pub game_name: &'r str,
+    pub csrf_token: &'r str,
+}
+
+// Either a game picked from the lobby or an invite code typed in; the game id wins if both are sent.
+#[derive(FromForm)]
+pub struct JoinRequest<'r>
+{
+    pub game_id: Option<Uuid>,
+    pub invite_code: Option<&'r str>,
+    pub csrf_token: &'r str,
 }
 
 #[derive(FromForm)]
 pub struct NewCharacter<'r>
 {
+    #[field(validate = len(1..=MAX_NAME_LENGTH))]
     pub char_name: &'r str,
     pub metatype: &'r str,
     pub is_npc: bool,
+    pub csrf_token: &'r str,
 }
 
 impl From<NewCharacter<'_>> for Character
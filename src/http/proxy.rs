@@ -0,0 +1,120 @@
+use std::net::IpAddr;
+
+use tracing::debug;
+use rocket::{Request, request::{FromRequest, Outcome, self}, serde::Deserialize};
+
+use super::listen::{RelayedPeer, RelayedPeers};
+
+// Settings for running behind a reverse proxy, read out of Rocket.toml alongside Rocket's own keys.  TLS itself needs nothing here:
+// Rocket terminates it whenever a [default.tls] table gives it a certificate chain and key.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ProxyConfig
+{
+    // Prefix every route is mounted under, for when the proxy serves the manager from a sub-path (e.g. "/combat").
+    #[serde(default)]
+    pub base_path: String,
+    // Only believe X-Forwarded-For/X-Forwarded-Proto when there is a proxy in front of Rocket's own address and port to set them;
+    // otherwise any client can forge them.  The extra listeners are trusted one by one in ListenConfig.
+    #[serde(default)]
+    pub trust_proxy: bool,
+}
+
+impl ProxyConfig
+{
+    pub fn new() -> ProxyConfig
+    {
+        ProxyConfig { base_path: String::new(), trust_proxy: false }
+    }
+
+    // Tidies whatever was configured into either "" or "/something" with no trailing slash, which is what mount points and links want.
+    pub fn normalized(mut self) -> ProxyConfig
+    {
+        let trimmed = self.base_path.trim().trim_matches('/');
+        self.base_path = if trimmed.is_empty() { String::new() } else { format!("/{}", trimmed) };
+        self
+    }
+
+    pub fn mount_point(&self, path: &str) -> String
+    {
+        match path
+        {
+            "/" if !self.base_path.is_empty() => self.base_path.clone(),
+            _ => format!("{}{}", self.base_path, path),
+        }
+    }
+
+    pub fn link(&self, path: &str) -> String
+    {
+        format!("{}{}", self.base_path, path)
+    }
+}
+
+// Who is on the other end of a request, and whether they reached us over https - either directly, or as reported by a trusted proxy.
+pub struct Forwarded
+{
+    pub client: Option<IpAddr>,
+    pub https: bool,
+}
+
+impl Forwarded
+{
+    pub fn inspect(request: &Request<'_>) -> Forwarded
+    {
+        let direct_tls = request.rocket().config().tls_enabled();
+        // A relayed request comes from loopback as far as Rocket can tell, so the relay's record of the real client stands in for it.
+        let direct_client = match Forwarded::relayed(request)
+        {
+            Some(peer) => peer.client,
+            None => request.client_ip(),
+        };
+
+        if !Forwarded::trusts_headers(request)
+        {
+            return Forwarded { client: direct_client, https: direct_tls };
+        }
+
+        // X-Forwarded-For accumulates one address per hop; the left-most is the original client.
+        let client = request.headers().get_one("X-Forwarded-For")
+            .and_then(|header| header.split(',').next())
+            .and_then(|first| first.trim().parse::<IpAddr>().ok())
+            .or(direct_client);
+        let https = match request.headers().get_one("X-Forwarded-Proto")
+        {
+            Some(proto) => proto.trim().eq_ignore_ascii_case("https"),
+            None => direct_tls,
+        };
+
+        Forwarded { client, https }
+    }
+
+    // Whether the X-Forwarded-* headers on the request were set by a proxy of ours rather than by whoever sent it: on a relayed
+    // connection that is up to the listener it came in on, and otherwise to trust_proxy.
+    pub fn trusts_headers(request: &Request<'_>) -> bool
+    {
+        match Forwarded::relayed(request)
+        {
+            Some(peer) => peer.proxied,
+            None => request.rocket().state::<ProxyConfig>().map_or(false, |config| config.trust_proxy),
+        }
+    }
+
+    fn relayed(request: &Request<'_>) -> Option<RelayedPeer>
+    {
+        let remote = request.remote()?;
+        request.rocket().state::<RelayedPeers>().and_then(|peers| peers.lookup(&remote))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Forwarded
+{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error>
+    {
+        let forwarded = Forwarded::inspect(request);
+        debug!("Request from {:?} (https: {})", forwarded.client, forwarded.https);
+        Outcome::Success(forwarded)
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}};
+
+use tracing::{debug, warn};
+use rocket::serde::{Serialize, Deserialize};
+use tokio::sync::mpsc::{Sender, error::TrySendError};
+
+use crate::gamerunner::{dispatcher::Message, PLAYER_CHANNEL_CAPACITY};
+
+// How big the channels into and out of the game runner are, and what a request does when the runner's queue is full.  Read out of
+// Rocket.toml alongside the proxy and CORS settings.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct QueueConfig
+{
+    #[serde(default = "QueueConfig::default_runner_capacity")]
+    pub runner_queue_capacity: usize,
+    #[serde(default = "QueueConfig::default_player_capacity")]
+    pub player_channel_capacity: usize,
+    #[serde(default)]
+    pub queue_overflow: OverflowPolicy,
+    // How often the runner is asked to look over every game's clocks, so deadlines and absences come due at a table nobody is using.
+    #[serde(default = "QueueConfig::default_review_seconds")]
+    pub review_seconds: u64,
+}
+
+impl QueueConfig
+{
+    pub fn new() -> QueueConfig
+    {
+        QueueConfig 
+        { 
+            runner_queue_capacity: QueueConfig::default_runner_capacity(), 
+            player_channel_capacity: QueueConfig::default_player_capacity(), 
+            queue_overflow: OverflowPolicy::Wait,
+            review_seconds: QueueConfig::default_review_seconds(),
+        }
+    }
+
+    fn default_runner_capacity() -> usize
+    {
+        10
+    }
+
+    fn default_player_capacity() -> usize
+    {
+        PLAYER_CHANNEL_CAPACITY
+    }
+
+    fn default_review_seconds() -> u64
+    {
+        5
+    }
+
+    pub fn review_interval(&self) -> std::time::Duration
+    {
+        std::time::Duration::from_secs(self.review_seconds)
+    }
+
+    // A zero capacity would panic when the channel is made, so it is bumped to one.
+    pub fn normalized(mut self) -> QueueConfig
+    {
+        self.runner_queue_capacity = self.runner_queue_capacity.max(1);
+        self.player_channel_capacity = self.player_channel_capacity.max(1);
+        self.review_seconds = self.review_seconds.max(1);
+        self
+    }
+}
+
+// Waiting keeps every request but lets a busy table stall the ones behind it; rejecting turns the request away with a 503 so the client
+// can back off and retry.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum OverflowPolicy
+{
+    #[default]
+    Wait,
+    Reject,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueueError
+{
+    Saturated,
+    Closed,
+    // The runner panicked and is being restarted.
+    Recovering,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct QueueStats
+{
+    pub capacity: usize,
+    pub queued: usize,
+    // Requests that found the queue full, whether they then waited or were turned away.
+    pub saturated: u64,
+    pub rejected: u64,
+}
+
+// The web side's end of the runner's queue.  Clones share the counters, so every handler's sends show up in the one set of stats.
+#[derive(Clone)]
+pub struct RunnerPipe
+{
+    sender: Sender<Message>,
+    policy: OverflowPolicy,
+    saturated: Arc<AtomicU64>,
+    rejected: Arc<AtomicU64>,
+    recovering: Arc<AtomicBool>,
+}
+
+impl RunnerPipe
+{
+    pub fn new(sender: Sender<Message>, policy: OverflowPolicy) -> RunnerPipe
+    {
+        RunnerPipe { sender, policy, saturated: Arc::new(AtomicU64::new(0)), rejected: Arc::new(AtomicU64::new(0)), recovering: Arc::new(AtomicBool::new(false)) }
+    }
+
+    // Set by the runner's supervisor while it restarts a runner that panicked; sends are turned away until it is cleared.
+    pub fn set_recovering(&self, recovering: bool)
+    {
+        self.recovering.store(recovering, Ordering::Release);
+    }
+
+    pub async fn send(&self, msg: Message) -> Result<(), QueueError>
+    {
+        if self.recovering.load(Ordering::Acquire)
+        {
+            return Err(QueueError::Recovering);
+        }
+
+        match self.sender.try_send(msg)
+        {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Closed(_)) => Err(QueueError::Closed),
+            Err(TrySendError::Full(msg)) =>
+            {
+                self.saturated.fetch_add(1, Ordering::Relaxed);
+                match self.policy
+                {
+                    OverflowPolicy::Reject =>
+                    {
+                        self.rejected.fetch_add(1, Ordering::Relaxed);
+                        warn!("The game runner's queue is full ({} requests); turning a request away.", self.sender.max_capacity());
+                        Err(QueueError::Saturated)
+                    },
+                    OverflowPolicy::Wait =>
+                    {
+                        warn!("The game runner's queue is full ({} requests); waiting for room.", self.sender.max_capacity());
+                        self.sender.send(msg).await.map_err(|_| QueueError::Closed)
+                    },
+                }
+            },
+        }
+    }
+
+    pub fn stats(&self) -> QueueStats
+    {
+        let capacity = self.sender.max_capacity();
+        let stats = QueueStats 
+        { 
+            capacity, 
+            queued: capacity - self.sender.capacity(), 
+            saturated: self.saturated.load(Ordering::Relaxed), 
+            rejected: self.rejected.load(Ordering::Relaxed),
+        };
+        debug!("Runner queue stats: {:?}", stats);
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use tokio::sync::{mpsc::channel, oneshot};
+
+    use crate::gamerunner::dispatcher::{Message, Request};
+
+    use super::{RunnerPipe, OverflowPolicy, QueueError, QueueStats};
+
+    fn message() -> Message
+    {
+        let (reply_channel, _) = oneshot::channel();
+        Message { player_id: None, game_id: None, reply_channel, msg: Request::FetchInbox }
+    }
+
+    #[tokio::test]
+    pub async fn a_full_queue_turns_requests_away_under_reject_and_counts_each_one_saturated_and_rejected()
+    {
+        let (sender, _receiver) = channel(1);
+        let pipe = RunnerPipe::new(sender, OverflowPolicy::Reject);
+
+        assert_eq!(Ok(()), pipe.send(message()).await);
+        assert_eq!(Err(QueueError::Saturated), pipe.send(message()).await);
+        // Clones share the counters.
+        assert_eq!(Err(QueueError::Saturated), pipe.clone().send(message()).await);
+        assert_eq!(QueueStats { capacity: 1, queued: 1, saturated: 2, rejected: 2 }, pipe.stats());
+    }
+
+    #[tokio::test]
+    pub async fn a_full_queue_holds_requests_under_wait_until_there_is_room_and_counts_them_saturated_but_not_rejected()
+    {
+        let (sender, mut receiver) = channel(1);
+        let pipe = RunnerPipe::new(sender, OverflowPolicy::Wait);
+
+        assert_eq!(Ok(()), pipe.send(message()).await);
+        let waiting = tokio::spawn({
+            let pipe = pipe.clone();
+            async move { pipe.send(message()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(QueueStats { capacity: 1, queued: 1, saturated: 1, rejected: 0 }, pipe.stats());
+
+        // Taking the first off the queue makes room for the one waiting.
+        assert!(receiver.recv().await.is_some());
+        assert_eq!(Ok(()), waiting.await.unwrap());
+        assert_eq!(QueueStats { capacity: 1, queued: 1, saturated: 1, rejected: 0 }, pipe.stats());
+    }
+}
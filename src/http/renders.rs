@@ -1,13 +1,13 @@
 
-use log::debug;
-use rocket::{get, post, State, response::Redirect, uri, form::{FromForm, Form}};
+use tracing::debug;
+use rocket::{get, post, State, Responder, response::Redirect, uri, form::{FromForm, Form}, http::{Cookie, CookieJar, Header}};
 use rocket_dyn_templates::{Template, context};
 use uuid::Uuid;
-use tokio::sync::{oneshot::channel, mpsc::Sender};
+use tokio::sync::oneshot::channel;
 
-use crate::{gamerunner::dispatcher::{Message, Request, Outcome}, http::{session::NewSessionOutcome, models::NewGame}, tracker::character::Character};
+use crate::{gamerunner::{dispatcher::{Message, Request, Outcome, Delivery, GameFilter}, snapshot::QuerySnapshots, ErrorKind}, http::{session::NewSessionOutcome, models::NewGame}, tracker::{character::Character, names::Name}};
 
-use super::{models::{GameSummary, GMView, IndexModel, PlayerView, SimpleCharacterView, NewCharacter}, errors::Error, session::Session, metagame::Metagame};
+use super::{server::overlay_view, models::{GameSummary, GMView, IndexModel, LobbyModel, LobbyGame, JoinRequest, PlayerView, SimpleCharacterView, NewCharacter}, errors::Error, session::{Session, SessionMap, Registration, REGISTRATION_COOKIE, REGISTRATION_TOKEN_HEADER}, metagame::Metagame, proxy::ProxyConfig, validation::MAX_NAME_LENGTH, queue::{RunnerPipe, QueueError}};
 
 #[get("/")]
 pub async fn index(state: &State<Metagame<'_>>, session: Session) -> Result<Template, Error>
@@ -22,26 +22,28 @@ pub async fn index(state: &State<Metagame<'_>>, session: Session) -> Result<Temp
     }
 
 
-    let model = IndexModel { player_handle: &session.handle_as_ref(), summaries  };
+    let model = IndexModel { player_handle: &session.handle_as_ref(), summaries, csrf_token: session.csrf_token() };
 
 
     return Ok(Template::render("index", model));
 }
 
 #[post("/game", data = "<new_game>")]
-pub async fn create_game(state: &State<Metagame<'_>>, session: Session, new_game: Form<NewGame<'_>>) -> Result<Redirect, Error>
+pub async fn create_game(state: &State<Metagame<'_>>, proxy: &State<ProxyConfig>, session: Session, new_game: Form<NewGame<'_>>) -> Result<Redirect, Error>
 {
-    let my_sender = state.game_runner_pipe.clone();
+    check_csrf(&session, new_game.csrf_token)?;
+    register_with_runner(&session, state).await?;
 
-    let response = send_and_recv(Uuid::new_v4(), Request::New, my_sender).await?;
+    let response = send_as(Some(session.player_id()), None, Request::New, state.game_runner_pipe.clone()).await?;
 
     match response
     {
         Outcome::Created(game_id) =>
         {   
-            
-            state.new_game(game_id, session.player_id(), String::from(new_game.game_name), uri!(game_view(game_id)));
-            return Ok(Redirect::to(uri!(game_view(game_id))));
+            // The lobby and the public listings are read off the runner's summaries, so the runner needs the name as well.
+            send_as(Some(session.player_id()), Some(game_id), Request::NameGame(String::from(new_game.game_name)), state.game_runner_pipe.clone()).await?;
+            state.new_game(game_id, session.player_id(), Name::from(new_game.game_name), uri!(game_view(game_id)));
+            return Ok(Redirect::to(proxy.link(&uri!(game_view(game_id)).to_string())));
         }
         _ =>
         {
@@ -53,6 +55,75 @@ pub async fn create_game(state: &State<Metagame<'_>>, session: Session, new_game
 
 }
 
+// The games a player could sit down at now: those not yet in a fight.  Games in combat can still be joined with their invite code.
+#[get("/lobby")]
+pub async fn lobby(state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>, session: Session) -> Template
+{
+    let games = snapshots.list(GameFilter::Open, 0, None).unwrap_or_default().into_iter().map(|(game_id, summary)| LobbyGame
+    {
+        game_id,
+        game_name: state.game_name(game_id).unwrap_or(summary.name),
+        gm_name: summary.gm_name,
+        players: summary.players,
+        characters: summary.characters,
+        phase: summary.phase,
+    }).collect();
+
+    Template::render("lobby", LobbyModel { player_handle: &session.handle_as_ref(), games, csrf_token: session.csrf_token() })
+}
+
+#[post("/lobby/join", data = "<join>")]
+pub async fn join_game(state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>, proxy: &State<ProxyConfig>, session: Session, join: Form<JoinRequest<'_>>) 
+    -> Result<Redirect, Error>
+{
+    check_csrf(&session, join.csrf_token)?;
+
+    let Some(game_id) = join.game_id.or_else(|| join.invite_code.and_then(|code| state.game_by_invite(code)))
+    else { return Err(join_refused(ErrorKind::NoMatchingGame, "No game goes by that invite code.")) };
+
+    // Someone already at the table - a player clicking Join twice, or the GM following their own invite - is just shown to their seat.
+    let to_game = Redirect::to(proxy.link(&uri!(game_view(game_id)).to_string()));
+    if snapshots.seats(&game_id, &session.player_id())
+    {
+        return Ok(to_game);
+    }
+
+    register_with_runner(&session, state).await?;
+    match send_as(Some(session.player_id()), Some(game_id), Request::JoinGame, state.game_runner_pipe.clone()).await?
+    {
+        Outcome::JoinedGame(_) => Ok(to_game),
+        Outcome::Error(err) =>
+        {
+            debug!("Player {} could not join game {}: {}", session.player_id(), game_id, err.message);
+            Err(join_refused(err.kind, &err.message))
+        },
+        _ => Err(Error::InternalServerError(Template::render("error_pages/500", context! {action_name: "join a game", error: "The Game replied with an unexpected message."}))),
+    }
+}
+
+// The runner's reason for turning a player away, on the page for it.  A game that isn't there is not found; anything else is a refusal.
+fn join_refused(kind: ErrorKind, reason: &str) -> Error
+{
+    let page = Template::render("error_pages/join_refused", context! {reason});
+    match kind
+    {
+        ErrorKind::NoMatchingGame | ErrorKind::UnknownId => Error::NotFound(page),
+        _ => Error::Forbidden(page),
+    }
+}
+
+// The overlay as a page of its own, for a browser source to show on stream.  It reloads itself every few seconds, since a browser source
+// has nothing to click.
+#[get("/overlay/<id>?<token>")]
+pub async fn overlay_page(id: Uuid, token: &str, state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>) -> Result<Template, Error>
+{
+    match overlay_view(id, token, state, snapshots)
+    {
+        Some(view) => Ok(Template::render("overlay", view)),
+        None => Err(Error::NotFound(Template::render("error_pages/404", context!{}))),
+    }
+}
+
 #[get("/game/<id>")]
 pub async fn game_view(id: Uuid, session: Session, state: &State<Metagame<'_>>) -> Result<Template, Error>
 {
@@ -77,7 +148,7 @@ pub async fn game_view(id: Uuid, session: Session, state: &State<Metagame<'_>>)
 
 async fn build_player_view(game_id: Uuid, session: &Session, state: &State<Metagame<'_>>) -> Result<Template, Error>
 {
-    let game_name = state.game_name(game_id).unwrap_or(String::from(""));
+    let game_name = state.game_name(game_id).unwrap_or_else(|| Name::from(""));
     let view: PlayerView;
 
     if session.has_character_for(game_id)
@@ -86,7 +157,7 @@ async fn build_player_view(game_id: Uuid, session: &Session, state: &State<Metag
         {
             Outcome::Found(char) => 
             {
-                view = PlayerView {player_handle: session.handle_as_ref(), game_id, game_name, character_state: Some(SimpleCharacterView::from(char.unwrap().as_ref()))};
+                view = PlayerView {player_handle: session.handle_as_ref(), game_id, game_name, character_state: Some(SimpleCharacterView::from(char.unwrap().as_ref())), csrf_token: session.csrf_token() };
             }
             _ => {
                 let err = "Boy howdy, something really went south here.  We received a completely unexpected message type from the GameRunner for creating a game.";
@@ -96,20 +167,20 @@ async fn build_player_view(game_id: Uuid, session: &Session, state: &State<Metag
     }
     else
     {
-        view = PlayerView {player_handle: session.handle_as_ref(), game_id, game_name, character_state: None };
+        view = PlayerView {player_handle: session.handle_as_ref(), game_id, game_name, character_state: None, csrf_token: session.csrf_token() };
     }
 
-    // let view = PlayerView {game_id, game_name, character_state: None };
+    // let view = PlayerView {game_id, game_name, character_state: None, csrf_token: session.csrf_token() };
 
     Ok(Template::render("player_view", view))
 }
 
-async fn build_gm_view(game_id: Uuid, _sesion: &Session, state: &State<Metagame<'_>>) -> Result<Template, Error>
+async fn build_gm_view(game_id: Uuid, session: &Session, state: &State<Metagame<'_>>) -> Result<Template, Error>
 {
     let outcome = send_and_recv(game_id, Request::GetPcCast, state.game_runner_pipe.clone()).await?;
     let mut pcs: Vec<SimpleCharacterView>;
     let mut npcs: Vec<SimpleCharacterView>;
-    let _game_name = state.game_name(game_id).unwrap_or(String::from(""));
+    let _game_name = state.game_name(game_id).unwrap_or_else(|| Name::from(""));
 
     match outcome
     {
@@ -119,7 +190,7 @@ async fn build_gm_view(game_id: Uuid, _sesion: &Session, state: &State<Metagame<
             debug!("Converting Character to SimpleCharacterView for {} records", cast.len());
             for member in cast
             {
-                pcs.push(SimpleCharacterView::from(member.as_ref()));
+                pcs.push(SimpleCharacterView::from(member.character.as_ref()));
             }
         }
         _ => 
@@ -138,7 +209,7 @@ async fn build_gm_view(game_id: Uuid, _sesion: &Session, state: &State<Metagame<
             npcs = Vec::with_capacity(cast.len());
             for member in cast
             {
-                npcs.push(SimpleCharacterView::from(member.as_ref()));
+                npcs.push(SimpleCharacterView::from(member.character.as_ref()));
             }
         }
         _ => 
@@ -148,12 +219,15 @@ async fn build_gm_view(game_id: Uuid, _sesion: &Session, state: &State<Metagame<
         }
     }
 
-    return Ok(Template::render("gm_view", GMView { game_id, pcs, npcs }));
+    let invite_code = state.invite_code(game_id).unwrap_or_default();
+    let overlay_token = state.overlay_token(game_id).unwrap_or_default();
+    return Ok(Template::render("gm_view", GMView { game_id, invite_code, overlay_token, pcs, npcs, csrf_token: session.csrf_token() }));
 }
 
 #[post("/game/<id>/add_npc", data="<npc>")]
-pub async fn add_npc(id: Uuid, session: Session, state: &State<Metagame<'_>>, npc: Form<NewCharacter<'_>>) -> Result<Redirect, Error>
+pub async fn add_npc(id: Uuid, session: Session, state: &State<Metagame<'_>>, proxy: &State<ProxyConfig>, npc: Form<NewCharacter<'_>>) -> Result<Redirect, Error>
 {
+    check_csrf(&session, npc.csrf_token)?;
 
     if !state.validate_ownership(session.player_id(), id)
     {
@@ -169,7 +243,7 @@ pub async fn add_npc(id: Uuid, session: Session, state: &State<Metagame<'_>>, np
         Outcome::CharacterAdded(_) => 
         {
             // return Ok(Template::render("added", context!{game_id: id}));
-            return Ok(Redirect::to(uri!(game_view(id))));
+            return Ok(Redirect::to(proxy.link(&uri!(game_view(id)).to_string())));
         },
         Outcome::Error(err) => {return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: err.message})))},
         _ => {return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: "The Game replied with an unexpected message."})))}
@@ -177,8 +251,9 @@ pub async fn add_npc(id: Uuid, session: Session, state: &State<Metagame<'_>>, np
 }
 
 #[post("/game/<id>/add_pc", data="<pc>")]
-pub async fn add_pc(id: Uuid, session: Session, state: &State<Metagame<'_>>, pc: Form<NewCharacter<'_>>) -> Result<Redirect, Error>
+pub async fn add_pc(id: Uuid, session: Session, sessions: &State<SessionMap>, state: &State<Metagame<'_>>, proxy: &State<ProxyConfig>, pc: Form<NewCharacter<'_>>) -> Result<Redirect, Error>
 {
+    check_csrf(&session, pc.csrf_token)?;
     let character = Character::from(pc.into_inner());
 
     let result = send_and_recv(id, Request::AddCharacter(character), state.game_runner_pipe.clone()).await?;
@@ -188,7 +263,8 @@ pub async fn add_pc(id: Uuid, session: Session, state: &State<Metagame<'_>>, pc:
         Outcome::CharacterAdded((_, char_id)) => 
         {
             session.add_pc(id, char_id);
-            return Ok(Redirect::to(uri!(game_view(id))));
+            sessions.save();
+            return Ok(Redirect::to(proxy.link(&uri!(game_view(id)).to_string())));
         },
         Outcome::Error(err) => {return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: err.message})))},
         _ => {return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: "The Game replied with an unexpected message."})))}
@@ -197,38 +273,91 @@ pub async fn add_pc(id: Uuid, session: Session, state: &State<Metagame<'_>>, pc:
     
 }
 
+#[derive(Responder)]
+pub struct RegisterPage
+{
+    page: Template,
+    token: Header<'static>,
+}
+
 #[get("/<_..>", rank = 11)]
-pub async fn no_session() -> Template
+pub async fn no_session(registration: Registration) -> RegisterPage
 {
-    Template::render("register", context!{})
+    let token = Header::new(REGISTRATION_TOKEN_HEADER, registration.token.clone());
+    RegisterPage { page: Template::render("register", context!{csrf_token: registration.token}), token }
 }
 
 #[derive(FromForm)]
 pub struct UserHandle<'r> {
-    #[field(name = "player_handle")]
-    player_handle: &'r str
+    #[field(name = "player_handle", validate = len(1..=MAX_NAME_LENGTH))]
+    player_handle: &'r str,
+    csrf_token: &'r str,
 }
 
 #[post("/gen_session", data = "<submission>")]
-pub async fn new_session(_proof_of_session: NewSessionOutcome, session: Session, submission: Form<UserHandle<'_>>) -> Redirect
+pub async fn new_session(_proof_of_session: NewSessionOutcome, registration: Registration, session: Session, sessions: &State<SessionMap>, proxy: &State<ProxyConfig>, cookies: &CookieJar<'_>, submission: Form<UserHandle<'_>>) -> Result<Redirect, Error>
 {
+    if !registration.verify(submission.csrf_token)
+    {
+        debug!("Sign-in rejected: the form's token did not match the registration cookie.");
+        return Err(Error::Forbidden(Template::render("error_pages/403", context!{})));
+    }
+
+    cookies.remove(Cookie::named(REGISTRATION_COOKIE));
     session.set_handle(String::from(submission.player_handle));
-    Redirect::to(uri!("/"))
+    sessions.save();
+    Ok(Redirect::to(proxy.link("/")))
+}
+
+fn check_csrf(session: &Session, token: &str) -> Result<(), Error>
+{
+    if session.verify_csrf(token)
+    {
+        Ok(())
+    }
+    else
+    {
+        debug!("Form post rejected: CSRF token did not match the session.");
+        Err(Error::Forbidden(Template::render("error_pages/403", context!{})))
+    }
+}
+
+// The session's player is known to the web side from the moment it has a handle, but the runner only learns of them here; a player it
+// already knows is simply reconnected.
+async fn register_with_runner(session: &Session, state: &Metagame<'_>) -> Result<(), Error>
+{
+    match send_as(Some(session.player_id()), None, Request::Reconnect(Delivery::Inbox), state.game_runner_pipe.clone()).await?
+    {
+        Outcome::Reconnected(_) => Ok(()),
+        Outcome::Error(err) => Err(Error::InternalServerError(Template::render("error_pages/500", context! {action_name: "register with the game runner", error: err.message}))),
+        _ => Err(Error::InternalServerError(Template::render("error_pages/500", context! {action_name: "register with the game runner", error: "The Game replied with an unexpected message."}))),
+    }
+}
+
+async fn send_and_recv(game_id: Uuid, body: Request, sender: RunnerPipe) -> Result<Outcome, Error>
+{
+    send_as(None, Some(game_id), body, sender).await
 }
 
-async fn send_and_recv(game_id: Uuid, body: Request, sender: Sender<Message>) -> Result<Outcome, Error>
+async fn send_as(player_id: Option<Uuid>, game_id: Option<Uuid>, body: Request, sender: RunnerPipe) -> Result<Outcome, Error>
 {
     let (their_sender, my_receiver) = channel::<Outcome>();
-    let msg = Message { player_id: None, game_id:Some(game_id), reply_channel: their_sender, msg: body };
-    if let Err(_err) = sender.send(msg).await
+    let msg = Message { player_id, game_id, reply_channel: their_sender, msg: body };
+    match sender.send(msg).await
     {
-        return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: "The game runner closed its channel."})));
+        Ok(()) => {},
+        Err(QueueError::Saturated) =>
+            return Err(Error::ServiceUnavailable(Template::render("error_pages/503", context! {action_name: "reach the game", error: "The game runner's queue is full."}))),
+        Err(QueueError::Recovering) =>
+            return Err(Error::ServiceUnavailable(Template::render("error_pages/503", context! {action_name: "reach the game", error: "The game runner is restarting."}))),
+        Err(QueueError::Closed) =>
+            return Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: "The game runner closed its channel."}))),
     }
 
     match my_receiver.await 
     {
         Ok(outcome) => Ok(outcome),
         Err(_err) => 
-            Err(Error::InternalServerError(Template::render("500", context! {action_name: "create a character", error: "The reply channel was closed."}))),
+            Err(Error::ServiceUnavailable(Template::render("error_pages/503", context! {action_name: "reach the game", error: "The game runner stopped before answering."}))),
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
-use rocket::serde::{Serialize, Deserialize};
+use rocket::{serde::{Serialize, Deserialize}, FromFormField};
 use uuid::Uuid;
 
+use crate::{tracker::{names::Name, magic::Plane}, gamerunner::notifier::ChangeKind};
+
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -62,9 +64,119 @@ pub struct InitiativeRoll
     pub roll: i8,
 }
 
+// The answer to several rolls sent at once.  Applied is false if any roll was refused, in which case none of them were kept.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InitiativeRollResults
+{
+    pub applied: bool,
+    pub rolls: Vec<InitiativeRollResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InitiativeRollResult
+{
+    pub char_id: Uuid,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "rocket::serde")]
+pub enum DamageKind
+{
+    Physical,
+    Stun,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "rocket::serde")]
+pub enum ArmorKind
+{
+    Ballistic,
+    Impact,
+}
+
+// Boxes of damage to mark on a character.  Giving the attack's AP, or the armor it hits (ballistic if only the AP is given), lets the
+// character's armor turn physical damage it stops into stun.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Damage
+{
+    #[serde(rename = "type")]
+    pub damage_type: DamageKind,
+    pub amount: u8,
+    pub ap: Option<i8>,
+    pub armor: Option<ArmorKind>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub enum Healing
+{
+    FirstAid { hits: u8, rating: u8, #[serde(rename = "type")] damage_type: DamageKind },
+    Magic { hits: u8, force: u8 },
+    Rest { days: u16 },
+}
+
+// Boxes taken back off each track.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Recovered
+{
+    pub physical: u8,
+    pub stun: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(crate = "rocket::serde")]
+pub enum ActionKind
+{
+    Free,
+    Simple,
+    Complex,
+}
+
+// A command sent up a connection the client already holds open.  The id is the client's own, and comes back on the reply so answers can
+// be matched to commands when several are in flight.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CommandFrame
+{
+    pub id: u64,
+    pub game_id: Uuid,
+    pub command: ClientCommand,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub enum ClientCommand
+{
+    RollInitiative { char_id: Uuid, roll: i8 },
+    TakeAction { char_id: Uuid, action: ActionKind, roll: Option<String>, catalog: Option<String> },
+}
+
+// The id is missing only when the frame could not be read at all.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CommandReply
+{
+    pub id: Option<u64>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
 
+// What comes down a game's socket: something the player was told, or the answer to one of their commands.
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
+pub enum SocketFrame
+{
+    Notice(InboxNotice),
+    Reply(CommandReply),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
 pub enum Metatypes
 {
     Human,
@@ -72,4 +184,421 @@ pub enum Metatypes
     Elf,
     Troll,
     Orc,
-}
\ No newline at end of file
+}
+
+#[derive(FromFormField)]
+pub enum GameFilter
+{
+    All,
+    Joined,
+    Running,
+    Open,
+    InCombat,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameList
+{
+    pub page: usize,
+    pub game_ids: Vec<Uuid>,
+    // The same games, in the same order, with what the lobby shows of each.
+    pub games: Vec<GameListing>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameListing
+{
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub gm_name: String,
+    pub players: usize,
+    pub characters: usize,
+    pub phase: String,
+    pub in_combat: bool,
+    // None where the GM has set no limit.
+    pub per_player_limit: Option<usize>,
+    pub cast_limit: Option<usize>,
+    pub unique_names: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TurnSnapshot
+{
+    pub game_id: Uuid,
+    pub phase: String,
+    pub players: usize,
+    pub combatants: Vec<CombatantTurn>,
+    // The requests the GM could send right now that would not be refused for the phase.
+    pub gm_allowed: Vec<String>,
+    pub engagements: Vec<EngagementView>,
+}
+
+// One of the players' characters as the table sees it.  Statuses only the owner or GM would know of are never included.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CastMemberView
+{
+    pub char_id: Uuid,
+    pub name: String,
+    pub metatype: Metatypes,
+    pub status: Vec<String>,
+    pub turn: String,
+}
+
+// Two combatants locked in melee; the reach differential is from the attacker's side.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct EngagementView
+{
+    pub attacker: Uuid,
+    pub defender: Uuid,
+    pub reach_differential: i8,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CombatantTurn
+{
+    pub char_id: Uuid,
+    pub turn: String,
+    // What the character's owner could send for them right now.
+    pub allowed: Vec<String>,
+}
+
+// What a stream overlay shows: the round and pass, and the order still to come, as the table sees it.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OverlayView
+{
+    pub game_id: Uuid,
+    pub game_name: String,
+    pub phase: String,
+    pub round: usize,
+    // Counted from 1, as the table would say it.
+    pub pass: usize,
+    pub order: Vec<OverlayEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OverlayEntry
+{
+    pub name: String,
+    // Withheld for NPCs.
+    pub initiative: Option<i8>,
+    pub acting: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Resumed
+{
+    pub player_id: Uuid,
+    pub active_games: Vec<Uuid>,
+}
+
+#[derive(FromFormField)]
+pub enum ReportScope
+{
+    LastCombat,
+    Session,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CombatantSummary
+{
+    pub char_id: Uuid,
+    pub name: Name,
+    pub damage_dealt: u16,
+    pub damage_taken: u16,
+    pub free_actions: u16,
+    pub simple_actions: u16,
+    pub complex_actions: u16,
+    pub edge_spent: u16,
+    pub average_initiative: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionReport
+{
+    pub game_id: Uuid,
+    pub combats: usize,
+    pub combatants: Vec<CombatantSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InitiativeScore
+{
+    pub char_id: Uuid,
+    pub initiative: i8,
+}
+
+// actions holds "free", "simple" or "complex" for each action, in the order taken.  pass counts from 0, and initiative is left out
+// of an NPC's turns for everyone but the GM.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TimelineTurn
+{
+    pub pass: usize,
+    pub initiative: Option<i8>,
+    pub char_id: Uuid,
+    pub actions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TimelineRound
+{
+    pub round: usize,
+    pub initiatives: Vec<InitiativeScore>,
+    pub turns: Vec<TimelineTurn>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CombatTimeline
+{
+    pub game_id: Uuid,
+    pub rounds: Vec<TimelineRound>,
+}
+
+#[derive(FromFormField)]
+pub enum ExportFormat
+{
+    Csv,
+    Jsonl,
+}
+
+#[derive(FromFormField)]
+pub enum JournalKind
+{
+    Chat,
+    Roll,
+    CombatStarted,
+    RoundStarted,
+    InitiativeRolled,
+    InitiativeAdjusted,
+    ActionTaken,
+    Damage,
+    Healed,
+    EdgeSpent,
+    Rewound,
+    CheckpointRestored,
+    HouseRule,
+    GmProxy,
+}
+
+// One journal entry flattened for export.  recorded_at is in seconds since the Unix epoch.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct JournalLine
+{
+    pub sequence: usize,
+    pub recorded_at: u64,
+    pub kind: String,
+    pub actor: Option<Uuid>,
+    pub target: Option<Uuid>,
+    pub detail: String,
+}
+
+// One notification from the player's inbox, for a client polling for them.  kind is which change it was ("TurnAdvanced", "Chat") and
+// detail what it carried; sequence is the inbox's numbering, to poll on from.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InboxNotice
+{
+    pub sequence: usize,
+    pub kind: ChangeKind,
+    pub detail: ChangeDetail,
+}
+
+// What a change carried, one variant to each kind and named the same.  The runner's own types are mirrored with ids and plain values,
+// and its small enums - turn states, wound tiers, editions - go as the same snake_case words the rest of the API uses for them.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub enum ChangeDetail
+{
+    NewPlayer { player_id: Uuid, name: String },
+    NewCharacter { player_id: Uuid, char_id: Uuid, metatype: Metatypes },
+    StartingInitiativePhase,
+    StartingCombatRound,
+    PlayerActed,
+    NamedActionTaken { char_id: Uuid, name: String },
+    TurnAdvanced { pass: usize, up: Vec<InitiativeSlot>, on_deck: Vec<InitiativeSlot>, events_up: Vec<TimedEventView>, events_on_deck: Vec<TimedEventView> },
+    // next_round is false when someone still has a pass left this round.
+    PassEnded { next_round: bool },
+    PassAdvanced,
+    RoundAdvanced,
+    CombatStarted(Vec<InitiativeSlot>),
+    UpNext { char_id: Uuid },
+    YourTurn { char_id: Uuid },
+    CombatEnded,
+    CharactersRested(Vec<Uuid>),
+    GameEnded,
+    GameArchived,
+    DrainTestPending(Uuid),
+    CombatDeclared(Vec<Uuid>),
+    InitiativeAdded(Uuid),
+    InitiativeAdjusted { char_id: Uuid, delta: i8 },
+    TimedEventUp(TimedEventView),
+    TriggersFired(Vec<TriggerView>),
+    ReinforcementsArrived(Vec<Uuid>),
+    CharacterMoved(Uuid),
+    Engaged { attacker: Uuid, defender: Uuid },
+    Disengaged { char_id: Uuid, from: Uuid },
+    WentOnFullDefense(Uuid),
+    AreaAttackResolved(Uuid),
+    DamageEventApplied { source: Uuid, targets: Vec<Uuid> },
+    ResistanceTestMade(Uuid),
+    SpellCast(Uuid),
+    SpellDropped(Uuid),
+    PassesOverridden(Uuid),
+    ArmorDegraded(Uuid),
+    RoundEnded { passes: usize, combatants: Vec<Uuid>, outstanding_resistance_tests: usize },
+    // audience is "table", "gm" or "player"; to is the player a whisper went to.
+    Chat { from: Uuid, audience: String, to: Option<Uuid>, text: String },
+    DiceRolled { char_id: Uuid, label: String, dice: Vec<u8>, hits: u8, glitch: bool },
+    CharacterDamaged { char_id: Uuid, wounds: WoundsView },
+    CharacterHealed { char_id: Uuid, wounds: WoundsView },
+    EdgeSpent(Uuid),
+    Rewound(String),
+    CheckpointRestored(String),
+    InitiativeReminder { characters: Vec<Uuid> },
+    InitiativeTimeoutChanged { timeout_ms: Option<u64> },
+    EditionChanged(String),
+    ActionLabelsChanged { free: String, simple: String, complex: String },
+    ActionCatalogChanged(Vec<CatalogEntry>),
+    CastLimitsChanged { per_player: Option<usize>, cast: Option<usize>, unique_names: bool },
+    // A full sync replaces everything the client had; removed is always empty for one.
+    CombatantsSynced { sequence: u64, full: bool, changed: Vec<CombatantView>, removed: Vec<Uuid> },
+    JackedIn(Uuid),
+    JackedOut(Uuid),
+    MatrixActionTaken { decker: Uuid, target: Uuid },
+    PlaneChanged { char_id: Uuid, plane: Plane },
+    CharacterUpdated { char_id: Uuid, fields: Vec<String> },
+    CharacterUpdatePending(Uuid),
+    PlayerDeparted(Uuid),
+    GameCloned(Uuid),
+    PlayerUnreachable(Uuid),
+    PlayerAbsent { player_id: Uuid, absent: bool },
+    AbsencePolicyChanged(Option<AbsencePolicyView>),
+    TurnSkipped(Uuid),
+    SittingOut { char_id: Uuid, reason: Option<String> },
+    Composite(Vec<ChangeDetail>),
+}
+
+// A place in the initiative order.  initiative is withheld where the recipient may not see it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct InitiativeSlot
+{
+    pub char_id: Uuid,
+    pub initiative: Option<i8>,
+}
+
+// rounds_away counts round starts still to go before the event enters the order; 0 means the next one.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct TimedEventView
+{
+    pub id: Uuid,
+    pub label: String,
+    pub initiative: i8,
+    pub rounds_away: u32,
+}
+
+// An encounter trigger that fired: either reinforcements joining or a note prompting the GM.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct TriggerView
+{
+    pub round: usize,
+    pub pass: usize,
+    pub reinforcements: Vec<Uuid>,
+    pub prompt: Option<String>,
+}
+
+// Wounds as the recipient may see them: the boxes themselves for the GM and the character's owner, a tier for everyone else.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub enum WoundsView
+{
+    Exact { physical_filled: i8, physical_max: i8, stun_filled: i8, stun_max: i8 },
+    Tier(String),
+}
+
+// fires is the rounds an attack uses, if it uses any.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct CatalogEntry
+{
+    pub name: String,
+    pub cost: ActionKind,
+    pub description: String,
+    pub fires: Option<i8>,
+    pub reloads: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct CombatantView
+{
+    pub char_id: Uuid,
+    pub turn: String,
+    pub status: Vec<String>,
+    pub wounds: Option<WoundsView>,
+}
+
+// fallback is "delegate_to_gm" or "skip".
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct AbsencePolicyView
+{
+    pub timeout_ms: u64,
+    pub fallback: String,
+}
+
+// The game's name, typed out by the GM to show they mean the game they are about to delete or archive.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameConfirmation
+{
+    pub game_name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Credentials
+{
+    pub username: String,
+    pub password: String,
+}
+
+// The body of every refusal from the JSON API.  context is there when the game runner refused the request, and says what it was about:
+// the game, character and request, and for a request made at the wrong point in a fight, the state wanted and - to the game's own GM
+// and players - the state it was in.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ErrorBody
+{
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<ErrorDetail>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct ErrorDetail
+{
+    pub game_id: Option<Uuid>,
+    pub character_id: Option<Uuid>,
+    pub request: Option<String>,
+    pub expected_state: Option<String>,
+    pub actual_state: Option<String>,
+}
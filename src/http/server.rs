@@ -1,17 +1,21 @@
 
-use log::debug;
-use rocket::{State, http::{Status, ContentType}, serde::json::Json, post, put, get, };
-use tokio::sync::{mpsc::Sender, oneshot::channel};
+use tracing::debug;
+use std::{sync::Arc, time::{Duration, UNIX_EPOCH}};
+
+use rocket::{State, http::{Status, ContentType, Header, CookieJar, Cookie}, serde::json::{self, Json}, response::stream::TextStream, post, put, get, delete, Responder};
+use tokio::sync::oneshot::channel;
 use tokio::sync::oneshot::Receiver as OneShotReceiver;
 use uuid::Uuid;
 
-use crate::{gamerunner::dispatcher::{Request, Message, Outcome, Roll}, http::{serde::{NewGame, InitiativeRoll}, metagame::Metagame},};
+use crate::{gamerunner::{dispatcher::{Request, Message, Outcome, Delivery, Roll, RollResult, DamageApplication, HealingApplication, GameQuery, GameFilter as RunnerGameFilter}, snapshot::{QuerySnapshots, GameSnapshot}, lobby::GameSummary, ErrorKind}, http::{serde::{NewGame, InitiativeRoll, InitiativeRollResults, InitiativeRollResult, GameFilter, GameList, GameListing, TurnSnapshot, CastMemberView, EngagementView, OverlayView, OverlayEntry, CombatantTurn, Resumed, ReportScope, SessionReport, CombatantSummary, CombatTimeline, TimelineRound, TimelineTurn, InitiativeScore, ExportFormat, JournalKind, JournalLine, InboxNotice, Credentials, GameConfirmation, Damage, DamageKind, ArmorKind, Healing, Recovered}, metagame::Metagame, session::{Session, SessionMap, session_cookie}, proxy::Forwarded, cors::TrustedOrigin, errors::ApiError, accounts::{AccountStore, AccountError, MIN_PASSWORD_LENGTH}, validation::validate, idempotency::Idempotency, queue::{RunnerPipe, QueueError, QueueStats}},};
+use crate::tracker::{game::{ActionType, TurnState, StatusEffect}, combat::DamageType, gear::ArmorTestType, rules::Healing as RunnerHealing, report::ReportScope as RunnerReportScope, journal::{JournalEntry, JournalEvent, JournalEventKind, JournalFilter, ChatAudience}};
 
 use super::serde::{Character, AddedCharacterJson, NewState, BeginCombat};
+use super::messaging::inbox_notice;
 
 
 #[post("/api/game/new")]
-pub async fn new_game(state: &State<Metagame<'_>>) -> Result<Json<NewGame>, (Status, String)>
+pub async fn new_game(_origin: TrustedOrigin, state: &State<Metagame<'_>>) -> Result<Json<NewGame>, ApiError>
 {
     debug!("Request received to generate new game.");
     let msg_channel = state.game_runner_pipe.clone();
@@ -30,19 +34,640 @@ pub async fn new_game(state: &State<Metagame<'_>>) -> Result<Json<NewGame>, (Sta
                 },
                 Outcome::Error(err) => {
                     debug!("Game creation error.  Message: {}", err.message);
-                    return Err((Status::InternalServerError, err.message));
+                    return Err(ApiError::refused(Status::InternalServerError, err));
                 },
                 _ => {unreachable!()}
             }
         },
         Err(err) => {
-            return Err((Status::InternalServerError, err));
+            return Err(err);
         },
     }
 
     
 }
 
+#[get("/games?<filter>&<page>&<page_size>")]
+pub async fn list_games(filter: Option<GameFilter>, page: Option<usize>, page_size: Option<usize>, session: Option<Session>, state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>) 
+    -> Result<Json<GameList>, ApiError>
+{
+    debug!("Request received to list games.");
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let filter = match filter.unwrap_or(GameFilter::All)
+    {
+        GameFilter::All => RunnerGameFilter::All,
+        GameFilter::Joined => RunnerGameFilter::Joined,
+        GameFilter::Running => RunnerGameFilter::Running,
+        GameFilter::Open => RunnerGameFilter::Open,
+        GameFilter::InCombat => RunnerGameFilter::InCombat,
+    };
+    let page = page.unwrap_or(0);
+
+    // The public listings are read off the snapshots, so a lobby polling for games never waits behind the games themselves.
+    if let Some(summaries) = snapshots.list(filter, page, page_size)
+    {
+        return Ok(Json(game_list(page, summaries, state)));
+    }
+
+    // Joined and Running are about whoever is asking, so the runner needs to know who that is; without a session it refuses them.
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: session.map(|session| session.player_id()), game_id: None, reply_channel: runner_sender, msg: Request::Enumerate(GameQuery { filter, page, page_size }) };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::Summaries(summaries)) => Ok(Json(game_list(page, summaries, state))),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::Forbidden, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// The runner does not know what games are called; the name the game was created under here is used where there is one.
+fn game_list(page: usize, summaries: Vec<(Uuid, GameSummary)>, state: &Metagame<'_>) -> GameList
+{
+    let games = summaries.iter().map(|(game_id, summary)| GameListing
+    {
+        game_id: *game_id,
+        game_name: state.game_name(*game_id).unwrap_or_else(|| summary.name.clone()).to_string(),
+        gm_name: summary.gm_name.to_string(),
+        players: summary.players,
+        characters: summary.characters,
+        phase: summary.phase.clone(),
+        in_combat: summary.in_combat,
+        per_player_limit: summary.cast_limits.per_player,
+        cast_limit: summary.cast_limits.cast,
+        unique_names: summary.cast_limits.unique_names,
+    }).collect();
+
+    GameList { page, game_ids: summaries.into_iter().map(|(id, _)| id).collect(), games }
+}
+
+// Where the game's fight stands, as the table sees it: answered from the snapshot the runner last published, not by asking the runner.
+// Only those seated at the game are shown it, and only the GM is told what the GM could send.
+#[get("/<id>/turn")]
+pub fn turn_state(id: Uuid, session: Session, snapshots: &State<QuerySnapshots>) -> Result<Json<TurnSnapshot>, ApiError>
+{
+    debug!("Request received for the turn state of game {}.", id);
+    let snapshot = seated_snapshot(id, &session, snapshots)?;
+
+    let combatants = snapshot.combatants.iter().map(|combatant| CombatantTurn
+    {
+        char_id: combatant.character,
+        turn: String::from(turn_name(combatant.turn)),
+        allowed: snapshot.allowed.characters.get(&combatant.character).map_or(Vec::new(), |allowed| allowed.iter().map(|request| String::from(*request)).collect()),
+    }).collect();
+    let gm_allowed = if snapshot.gm == session.player_id() 
+    { 
+        snapshot.allowed.gm.iter().map(|request| String::from(*request)).collect() 
+    } 
+    else 
+    { 
+        Vec::new() 
+    };
+    let engagements = snapshot.engagements.iter().map(|engagement| EngagementView
+    {
+        attacker: engagement.attacker, defender: engagement.defender, reach_differential: engagement.reach_differential
+    }).collect();
+
+    Ok(Json(TurnSnapshot { game_id: id, phase: snapshot.summary.phase.clone(), players: snapshot.summary.players, combatants, gm_allowed, engagements }))
+}
+
+// The players' characters as the table sees them, from the same snapshot as the turn state and shown to the same people.
+#[get("/<id>/cast")]
+pub fn cast_list(id: Uuid, session: Session, snapshots: &State<QuerySnapshots>) -> Result<Json<Vec<CastMemberView>>, ApiError>
+{
+    debug!("Request received for the cast of game {}.", id);
+    let snapshot = seated_snapshot(id, &session, snapshots)?;
+
+    Ok(Json(snapshot.cast.iter().map(|member| CastMemberView
+    {
+        char_id: member.character,
+        name: member.name.to_string(),
+        metatype: metatype_view(member.metatype),
+        status: member.status.iter().map(|status| String::from(status_name(status))).collect(),
+        turn: String::from(turn_name(member.turn)),
+    }).collect()))
+}
+
+// A game the caller is not seated at gets the same answer as one that does not exist, so outsiders cannot probe for games.
+fn seated_snapshot(id: Uuid, session: &Session, snapshots: &QuerySnapshots) -> Result<Arc<GameSnapshot>, ApiError>
+{
+    match snapshots.game(&id)
+    {
+        Some(snapshot) if snapshot.players.contains(&session.player_id()) => Ok(snapshot),
+        _ => Err(ApiError::new(Status::NotFound, format!("No game with id {} is running.", id))),
+    }
+}
+
+pub fn metatype_view(metatype: crate::tracker::character::Metatypes) -> super::serde::Metatypes
+{
+    match metatype
+    {
+        crate::tracker::character::Metatypes::Human => super::serde::Metatypes::Human,
+        crate::tracker::character::Metatypes::Dwarf => super::serde::Metatypes::Dwarf,
+        crate::tracker::character::Metatypes::Elf => super::serde::Metatypes::Elf,
+        crate::tracker::character::Metatypes::Orc => super::serde::Metatypes::Orc,
+        crate::tracker::character::Metatypes::Troll => super::serde::Metatypes::Troll,
+    }
+}
+
+pub fn turn_name(turn: TurnState) -> &'static str
+{
+    match turn
+    {
+        TurnState::OutOfCombat => "out_of_combat",
+        TurnState::AwaitingInitiative => "awaiting_initiative",
+        TurnState::Waiting => "waiting",
+        TurnState::Acting => "acting",
+        TurnState::Resolved => "resolved",
+    }
+}
+
+// Only what the table can see ever reaches the snapshot's cast, but every status is named so a new one cannot slip through unnamed.
+pub fn status_name(status: &StatusEffect) -> &'static str
+{
+    match status
+    {
+        StatusEffect::FullDefense => "full_defense",
+        StatusEffect::AstralProjecting => "astral_projecting",
+        StatusEffect::DualNatured => "dual_natured",
+        StatusEffect::Engaged(_) => "engaged",
+        StatusEffect::Sustaining(_) => "sustaining",
+        StatusEffect::JackedIn => "jacked_in",
+        StatusEffect::ResistanceTestPending => "resistance_test_pending",
+        StatusEffect::Hidden => "hidden",
+    }
+}
+
+// For a stream overlay - an OBS browser source, say - which has no session to show, so the game's overlay token stands in for one.  A
+// wrong token gets the same answer as a game that does not exist.
+#[get("/<id>/overlay?<token>")]
+pub fn overlay(id: Uuid, token: &str, state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>) -> Result<Json<OverlayView>, ApiError>
+{
+    debug!("Request received for the overlay of game {}.", id);
+    overlay_view(id, token, state, snapshots).map(Json).ok_or_else(|| ApiError::new(Status::NotFound, format!("No game with id {} is running.", id)))
+}
+
+pub fn overlay_view(id: Uuid, token: &str, state: &Metagame<'_>, snapshots: &QuerySnapshots) -> Option<OverlayView>
+{
+    if !state.overlay_token_matches(id, token)
+    {
+        return None;
+    }
+    let snapshot = snapshots.game(&id)?;
+
+    let order = snapshot.order.iter().map(|entry| OverlayEntry { name: entry.name.to_string(), initiative: entry.initiative, acting: entry.acting }).collect();
+    Some(OverlayView { game_id: id, game_name: state.game_name(id).map_or(String::new(), |name| name.to_string()), phase: snapshot.summary.phase.clone(),
+        round: snapshot.round, pass: snapshot.pass + 1, order })
+}
+
+// Called by a client returning with a stored session cookie, possibly after a server restart.  Web clients hear about their games by
+// polling or over a socket, both of which read the inbox, so the new notification channel is not held onto here and the runner is told so.
+#[post("/reconnect")]
+pub async fn resume_session(_origin: TrustedOrigin, session: Session, state: &State<Metagame<'_>>) -> Result<Json<Resumed>, ApiError>
+{
+    debug!("Request received to resume a session.");
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: None, reply_channel: runner_sender, msg: Request::Reconnect(Delivery::Inbox) };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::Reconnected(reconnection)) => Ok(Json(Resumed { player_id: reconnection.player_id, active_games: reconnection.active_games })),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::BadRequest, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+fn account_error(err: AccountError) -> (Status, String)
+{
+    match err
+    {
+        AccountError::InvalidUsername => (Status::BadRequest, String::from("A username is required.")),
+        AccountError::PasswordTooShort => (Status::BadRequest, String::from(format!("Passwords must be at least {} characters long.", MIN_PASSWORD_LENGTH))),
+        AccountError::UsernameTaken => (Status::Conflict, String::from("That username is already taken.")),
+        AccountError::BadCredentials => (Status::Unauthorized, String::from("Unknown username or wrong password.")),
+        AccountError::HashFailure => (Status::InternalServerError, String::from("The password could not be stored.")),
+    }
+}
+
+// Registration pins the session's current player id to the new account, so anything this browser already joined comes along with it.
+#[post("/account/register", format = "json", data = "<credentials>")]
+pub async fn register_account(_origin: TrustedOrigin, credentials: Json<Credentials>, session: Session, accounts: &State<AccountStore>) -> Result<Status, ApiError>
+{
+    debug!("Request received to register account {}.", credentials.username);
+    validate(&*credentials)?;
+
+    accounts.register(&credentials.username, &credentials.password, session.player_id()).await.map_err(account_error)?;
+
+    Ok(Status::Created)
+}
+
+// Logging in replaces this session with a new one, under a new id and cookie, for the account's player id, then reconnects as that player
+// to pick up its games.
+#[post("/account/login", format = "json", data = "<credentials>")]
+pub async fn login(_origin: TrustedOrigin, credentials: Json<Credentials>, session: Session, forwarded: Forwarded, cookies: &CookieJar<'_>, accounts: &State<AccountStore>, 
+    sessions: &State<SessionMap>, state: &State<Metagame<'_>>) -> Result<Json<Resumed>, ApiError>
+{
+    debug!("Request received to log in as {}.", credentials.username);
+    validate(&*credentials)?;
+
+    let player_id = accounts.login(&credentials.username, &credentials.password).await.map_err(account_error)?;
+    if let Some(session_id) = cookies.get("shadowrun_combat_session").and_then(|cookie| Uuid::parse_str(cookie.value()).ok())
+    {
+        sessions.drop_session(session_id);
+    }
+    let session_id = Uuid::new_v4();
+    sessions.add_session(session_id, session.signed_in_as(player_id));
+    cookies.add(session_cookie(session_id, sessions.ttl(), forwarded.https));
+
+    let msg_channel = state.game_runner_pipe.clone();
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(player_id), game_id: None, reply_channel: runner_sender, msg: Request::Reconnect(Delivery::Inbox) };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::Reconnected(reconnection)) => Ok(Json(Resumed { player_id: reconnection.player_id, active_games: reconnection.active_games })),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::BadRequest, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// Deletes the player outright: the runner drops them from every game, ending the ones they run, and then their account, every session
+// they hold and this browser's cookie go too.
+#[delete("/account")]
+pub async fn delete_account(_origin: TrustedOrigin, session: Session, cookies: &CookieJar<'_>, accounts: &State<AccountStore>, sessions: &State<SessionMap>, state: &State<Metagame<'_>>) 
+    -> Result<Status, ApiError>
+{
+    let player_id = session.player_id();
+    debug!("Request received to delete the account of player {}.", player_id);
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(player_id), game_id: None, reply_channel: runner_sender, msg: Request::DeleteAccount };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::AccountDeleted) => {},
+        Ok(Outcome::Error(err)) => return Err(ApiError::refused(Status::BadRequest, err)),
+        Ok(_) => return Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => return Err(err),
+    }
+
+    state.forget_gm(player_id);
+    accounts.remove_player(player_id);
+    sessions.drop_player_sessions(player_id);
+    cookies.remove(Cookie::named("shadowrun_combat_session"));
+
+    Ok(Status::NoContent)
+}
+
+// Deleting a game cannot be undone, so the GM has to name the game they mean as well as give its id; a game the web side holds no name
+// for is confirmed with its id instead.
+#[delete("/games/<id>", format = "json", data = "<confirmation>")]
+pub async fn delete_game(_origin: TrustedOrigin, id: Uuid, confirmation: Json<GameConfirmation>, session: Session, state: &State<Metagame<'_>>) -> Result<Status, ApiError>
+{
+    debug!("Request received to delete game {}.", id);
+    confirm_game_name(id, &confirmation, state)?;
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::Delete };
+
+    match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+    {
+        Ok(Outcome::Destroyed) =>
+        {
+            state.forget_game(id);
+            Ok(Status::NoContent)
+        },
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(game_error_status(&err.kind), err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// An archived game leaves the lobby and takes no more play, but its reports and journal can still be read.  Confirmed as for a delete.
+#[post("/games/<id>/archive", format = "json", data = "<confirmation>")]
+pub async fn archive_game(_origin: TrustedOrigin, id: Uuid, confirmation: Json<GameConfirmation>, session: Session, state: &State<Metagame<'_>>) -> Result<Status, ApiError>
+{
+    debug!("Request received to archive game {}.", id);
+    confirm_game_name(id, &confirmation, state)?;
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::Archive };
+
+    match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+    {
+        Ok(Outcome::Archived) => Ok(Status::NoContent),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(game_error_status(&err.kind), err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// A game the runner has no record of is not found; anything else it refuses - the caller not being the GM, say - is forbidden.
+fn game_error_status(kind: &ErrorKind) -> Status
+{
+    match kind
+    {
+        ErrorKind::NoMatchingGame | ErrorKind::UnknownId => Status::NotFound,
+        _ => Status::Forbidden,
+    }
+}
+
+fn confirm_game_name(id: Uuid, confirmation: &GameConfirmation, state: &Metagame<'_>) -> Result<(), ApiError>
+{
+    let expected = state.game_name(id).map_or(id.to_string(), |name| name.to_string());
+    if confirmation.game_name.trim() != expected.as_str()
+    {
+        return Err(ApiError::new(Status::UnprocessableEntity, String::from("The name given does not match the game's; nothing was changed.")));
+    }
+
+    Ok(())
+}
+
+// Ends this browser's session without touching the player's account or games; they can log back in from a new session.
+#[post("/account/logout")]
+pub async fn logout(_origin: TrustedOrigin, session: Session, cookies: &CookieJar<'_>, sessions: &State<SessionMap>) -> Status
+{
+    debug!("Request received to log out player {}.", session.player_id());
+    if let Some(session_id) = cookies.get("shadowrun_combat_session").and_then(|cookie| Uuid::parse_str(cookie.value()).ok())
+    {
+        sessions.drop_session(session_id);
+    }
+    cookies.remove(Cookie::named("shadowrun_combat_session"));
+
+    Status::NoContent
+}
+
+// How full the game runner's queue is and how often it has filled up.  How busy the server is says something about every table on it, so
+// only a session that GMs a game is shown.
+#[get("/runner/queue")]
+pub async fn queue_stats(session: Session, state: &State<Metagame<'_>>, snapshots: &State<QuerySnapshots>) -> Result<Json<QueueStats>, ApiError>
+{
+    if !snapshots.runs_a_game(&session.player_id())
+    {
+        return Err(ApiError::new(Status::Forbidden, String::from("Only a game's GM may see the runner's queue.")));
+    }
+
+    Ok(Json(state.game_runner_pipe.stats()))
+}
+
+// The report is served as an attachment so a browser following the link saves it rather than displaying it.
+#[derive(Responder)]
+pub struct ReportDownload
+{
+    inner: Json<SessionReport>,
+    disposition: Header<'static>,
+}
+
+#[get("/<id>/report?<scope>")]
+pub async fn combat_report(id: Uuid, scope: Option<ReportScope>, session: Session, state: &State<Metagame<'_>>) -> Result<ReportDownload, ApiError>
+{
+    debug!("Request received for the combat report of game {}.", id);
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let scope = match scope.unwrap_or(ReportScope::Session)
+    {
+        ReportScope::LastCombat => RunnerReportScope::LastCombat,
+        ReportScope::Session => RunnerReportScope::Session,
+    };
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::CombatReport(scope) };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::CombatReportIs(report)) => 
+        {
+            let combatants = report.combatants.iter().map(|tally| CombatantSummary 
+            { 
+                char_id: tally.character, 
+                name: tally.name.clone(), 
+                damage_dealt: tally.damage_dealt, 
+                damage_taken: tally.damage_taken, 
+                free_actions: tally.free_actions, 
+                simple_actions: tally.simple_actions, 
+                complex_actions: tally.complex_actions, 
+                edge_spent: tally.edge_spent, 
+                average_initiative: tally.average_initiative() 
+            }).collect();
+            let disposition = Header::new("Content-Disposition", format!("attachment; filename=\"combat-report-{}.json\"", id));
+
+            Ok(ReportDownload { inner: Json(SessionReport { game_id: id, combats: report.combats, combatants }), disposition })
+        },
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::Forbidden, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// Round by round, who rolled what and who acted when, for drawing the fight out.  Covers the game's current or most recent combat.
+#[get("/<id>/timeline")]
+pub async fn round_timeline(id: Uuid, session: Session, state: &State<Metagame<'_>>) -> Result<Json<CombatTimeline>, ApiError>
+{
+    debug!("Request received for the round timeline of game {}.", id);
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::RoundTimeline };
+
+    match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::RoundTimelineIs(rounds)) =>
+        {
+            let rounds = rounds.into_iter().map(|round| TimelineRound
+            {
+                round: round.round,
+                initiatives: round.initiatives.into_iter().map(|(char_id, initiative)| InitiativeScore { char_id, initiative }).collect(),
+                turns: round.turns.into_iter().map(|turn| TimelineTurn
+                {
+                    pass: turn.pass,
+                    initiative: turn.initiative,
+                    char_id: turn.character,
+                    actions: turn.actions.iter().map(|action| String::from(match action
+                    {
+                        ActionType::Free => "free",
+                        ActionType::Simple => "simple",
+                        ActionType::Complex => "complex",
+                    })).collect(),
+                }).collect(),
+            }).collect();
+
+            Ok(Json(CombatTimeline { game_id: id, rounds }))
+        },
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::Forbidden, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// Streams the journal one line per entry, either as CSV (with a header row) or as JSON lines.  since and until are Unix timestamps in
+// seconds; kind may be given more than once.
+#[get("/<id>/journal?<format>&<since>&<until>&<kind>")]
+pub async fn export_journal(id: Uuid, format: Option<ExportFormat>, since: Option<u64>, until: Option<u64>, kind: Vec<JournalKind>, session: Session, 
+    state: &State<Metagame<'_>>) -> Result<(ContentType, TextStream![String]), ApiError>
+{
+    debug!("Request received to export the journal of game {}.", id);
+    let msg_channel = state.game_runner_pipe.clone();
+
+    let filter = JournalFilter 
+    { 
+        since: since.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)), 
+        until: until.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)), 
+        kinds: kind.into_iter().map(|kind| match kind
+        {
+            JournalKind::Chat => JournalEventKind::Chat,
+            JournalKind::Roll => JournalEventKind::Roll,
+            JournalKind::CombatStarted => JournalEventKind::CombatStarted,
+            JournalKind::RoundStarted => JournalEventKind::RoundStarted,
+            JournalKind::InitiativeRolled => JournalEventKind::InitiativeRolled,
+            JournalKind::InitiativeAdjusted => JournalEventKind::InitiativeAdjusted,
+            JournalKind::ActionTaken => JournalEventKind::ActionTaken,
+            JournalKind::Damage => JournalEventKind::Damage,
+            JournalKind::Healed => JournalEventKind::Healed,
+            JournalKind::EdgeSpent => JournalEventKind::EdgeSpent,
+            JournalKind::Rewound => JournalEventKind::Rewound,
+            JournalKind::CheckpointRestored => JournalEventKind::CheckpointRestored,
+            JournalKind::HouseRule => JournalEventKind::HouseRule,
+            JournalKind::GmProxy => JournalEventKind::GmProxy,
+        }).collect() 
+    };
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::ExportJournal(filter) };
+
+    let entries = match do_send(msg, msg_channel, response_channel).await
+    {
+        Ok(Outcome::JournalEntries(entries)) => entries,
+        Ok(Outcome::Error(err)) => return Err(ApiError::refused(Status::Forbidden, err)),
+        Ok(_) => return Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => return Err(err),
+    };
+
+    // Each TextStream! is its own type, so the rows are rendered first and streamed out of the one stream.
+    let lines = entries.iter().map(journal_line).collect::<Vec<JournalLine>>();
+    let (content_type, rows) = match format.unwrap_or(ExportFormat::Jsonl)
+    {
+        ExportFormat::Csv => 
+        {
+            let header = String::from("sequence,recorded_at,kind,actor,target,detail\n");
+            (ContentType::CSV, std::iter::once(header).chain(lines.iter().map(csv_row)).collect::<Vec<String>>())
+        },
+        ExportFormat::Jsonl => 
+        {
+            (ContentType::new("application", "jsonl"), lines.iter().filter_map(|line| json::to_string(line).ok()).map(|text| text + "\n").collect::<Vec<String>>())
+        },
+    };
+
+    Ok((content_type, TextStream! {
+        for row in rows
+        {
+            yield row;
+        }
+    }))
+}
+
+fn journal_line(entry: &JournalEntry) -> JournalLine
+{
+    let recorded_at = entry.recorded_at.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (kind, actor, target, detail) = match &entry.event
+    {
+        JournalEvent::Chat(line) => 
+        {
+            let target = match line.audience { ChatAudience::Player(player) => Some(player), _ => None };
+            let audience = match line.audience { ChatAudience::Table => "table", ChatAudience::Gm => "gm", ChatAudience::Player(_) => "whisper" };
+            ("chat", Some(line.from), target, format!("{}: {}", audience, line.text))
+        },
+        JournalEvent::Roll(record) => ("roll", Some(record.character), None, format!("{}: {} dice, {} hits{}", record.label, record.roll.dice.len(), 
+            record.roll.hits, if record.roll.glitch { ", glitched" } else { "" })),
+        JournalEvent::CombatStarted(combatants) => ("combat_started", None, None, format!("{} combatants", combatants.len())),
+        JournalEvent::RoundStarted(round) => ("round_started", None, None, round.to_string()),
+        JournalEvent::InitiativeRolled { character, roll } => ("initiative_rolled", Some(*character), None, roll.to_string()),
+        JournalEvent::InitiativeAdjusted { character, delta, reason } => ("initiative_adjusted", Some(*character), None, format!("{:+} ({})", delta, reason)),
+        JournalEvent::GmProxy { character, player } => ("gm_proxy", Some(*character), Some(*player), String::from("GM acted for the absent player")),
+        JournalEvent::ActionTaken { character, action, name: Some(name), pass, initiative } => ("action_taken", Some(*character), None, format!("{} - {:?} (pass {}, initiative {})", name, action, pass, initiative)),
+        JournalEvent::ActionTaken { character, action, name: None, pass, initiative } => ("action_taken", Some(*character), None, format!("{:?} (pass {}, initiative {})", action, pass, initiative)),
+        JournalEvent::Damage { source, target, boxes, kind } => ("damage", *source, Some(*target), format!("{} {:?}", boxes, kind)),
+        JournalEvent::Healed { character, boxes, kind } => ("healed", None, Some(*character), format!("{} {:?}", boxes, kind)),
+        JournalEvent::EdgeSpent { character, points } => ("edge_spent", Some(*character), None, points.to_string()),
+        JournalEvent::Rewound(target) => ("rewound", None, None, format!("{:?}", target)),
+        JournalEvent::CheckpointRestored(name) => ("checkpoint_restored", None, None, name.clone()),
+        JournalEvent::HouseRule { rule, note } => ("house_rule", None, None, format!("{}: {}", rule, note)),
+    };
+
+    JournalLine { sequence: entry.sequence, recorded_at, kind: String::from(kind), actor, target, detail }
+}
+
+fn csv_row(line: &JournalLine) -> String
+{
+    let id = |id: Option<Uuid>| id.map_or(String::new(), |id| id.to_string());
+
+    format!("{},{},{},{},{},\"{}\"\n", line.sequence, line.recorded_at, line.kind, id(line.actor), id(line.target), line.detail.replace('"', "\"\""))
+}
+
+const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_POLL_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Long-polling on the player's notification inbox, for clients that can hold neither a websocket nor an event stream open through
+// whatever sits in front of the server.  Answers as soon as the inbox holds anything numbered after since - at once, if it already does -
+// or with an empty list once the timeout runs out.  The timeout is seconds, with or without an s ("30s"), or milliseconds ("500ms"), and
+// is capped at a minute.  The wait is on the inbox itself, so a waiting client costs the runner nothing, and polling does not count as
+// being at the table.
+#[get("/<id>/events?<since>&<timeout>")]
+pub async fn poll_events(id: Uuid, since: Option<usize>, timeout: Option<&str>, session: Session, state: &State<Metagame<'_>>) 
+    -> Result<Json<Vec<InboxNotice>>, ApiError>
+{
+    debug!("Request received to poll the events of game {} after {:?}.", id, since);
+    let timeout = match timeout
+    {
+        Some(text) => poll_timeout(text).ok_or((Status::BadRequest, format!("{} is not a timeout; give seconds (30s) or milliseconds (500ms).", text)))?,
+        None => DEFAULT_POLL_TIMEOUT,
+    };
+    let deadline = tokio::time::Instant::now() + timeout.min(MAX_POLL_TIMEOUT);
+
+    loop
+    {
+        let (runner_sender, response_channel) = channel::<Outcome>();
+        let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: Request::InboxAfter(since) };
+
+        let (notifications, mut ready) = match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+        {
+            Ok(Outcome::InboxAfter { notifications, ready }) => (notifications, ready),
+            Ok(Outcome::Error(err)) => return Err(ApiError::refused(Status::Forbidden, err)),
+            Ok(_) => return Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+            Err(err) => return Err(err),
+        };
+
+        if !notifications.is_empty()
+        {
+            return Ok(Json(notifications.iter()
+                .map(|(sequence, change)| inbox_notice(*sequence, change))
+                .collect()));
+        }
+        // The receiver was subscribed as the inbox was read, so anything that landed since counts as a change and is not missed.  A
+        // closed one means the player has gone from the runner, which the next read reports.
+        if tokio::time::timeout_at(deadline, ready.changed()).await.is_err()
+        {
+            return Ok(Json(Vec::new()));
+        }
+    }
+}
+
+fn poll_timeout(text: &str) -> Option<Duration>
+{
+    if let Some(millis) = text.strip_suffix("ms")
+    {
+        return millis.parse::<u64>().ok().map(Duration::from_millis);
+    }
+
+    text.strip_suffix('s').unwrap_or(text).parse::<u64>().ok().map(Duration::from_secs)
+}
+
 #[get("/demo")]
 pub fn get_example_char <'r> () -> Json<Character<'r>>
 {
@@ -69,11 +694,12 @@ pub fn get_state_demo() -> Json<NewState>
     Json(change)
 }
 
-#[post("/<id>/character", data = "<character>")]
-pub async fn add_new_character(id: Uuid, character: Json<Character<'_>>, state: &State<Metagame<'_>>) -> 
-    Result<Json<AddedCharacterJson>, (Status, String)>
+#[post("/<id>/character", format = "json", data = "<character>")]
+pub async fn add_new_character(_origin: TrustedOrigin, id: Uuid, character: Json<Character<'_>>, state: &State<Metagame<'_>>) -> 
+    Result<Json<AddedCharacterJson>, ApiError>
 {
     debug!("Received request to add a character to a game.");
+    validate(&*character)?;
 
     let (request, response_channel) = channel::<Outcome>();
     let msg_channel = state.game_runner_pipe.clone();
@@ -94,22 +720,23 @@ pub async fn add_new_character(id: Uuid, character: Json<Character<'_>>, state:
                     return Ok(Json(response_json));        
                 },
                 Outcome::Error(err) => {
-                    return Err((Status::BadRequest, err.message));
+                    return Err(ApiError::refused(Status::BadRequest, err));
                 },
                 _ => {unreachable!()}
             }
         },
         Err(err) => {
-            debug!("Adding a character failed: {}", err);
-            return Err((Status::BadRequest, err));
+            debug!("Adding a character failed: {}", err.body.message);
+            return Err(err);
         },
     }
 }
 
-#[put("/<id>/state", data = "<new_state>")]
-pub async fn change_game_state(id: Uuid, new_state: Json<NewState>, state: &State<Metagame<'_>>) -> 
-    Result<(Status, (ContentType, ())), (Status, String)>
+#[put("/<id>/state", format = "json", data = "<new_state>")]
+pub async fn change_game_state(_origin: TrustedOrigin, id: Uuid, new_state: Json<NewState>, state: &State<Metagame<'_>>) -> 
+    Result<(Status, (ContentType, ())), ApiError>
 {
+    validate(&*new_state)?;
     let (game_sender, game_receiver) = channel::<Outcome>();
     let msg_channel = state.game_runner_pipe.clone();
     let msg: Message;
@@ -138,7 +765,7 @@ pub async fn change_game_state(id: Uuid, new_state: Json<NewState>, state: &Stat
         Ok(response_msg) => {
             match response_msg {
                 Outcome::Error(err) => {
-                    return Err((Status::BadRequest, err.message));
+                    return Err(ApiError::refused(Status::BadRequest, err));
                 }
                 _ => {
                     return Ok((Status::Ok, (ContentType::JSON, ())));
@@ -146,16 +773,17 @@ pub async fn change_game_state(id: Uuid, new_state: Json<NewState>, state: &Stat
         }
         },
         Err(err) => {
-            return Err((Status::InternalServerError, err));
+            return Err(err);
         },
     }
 
 }
 
-#[post("/<id>/initiative", data = "<character_init>")]
-pub async fn add_initiative_roll(id: Uuid, character_init: Json<InitiativeRoll>, state: &State<Metagame<'_>>) ->
-    Result<(Status, (ContentType, ())), (Status, String)>
+#[post("/<id>/initiative", format = "json", data = "<character_init>")]
+pub async fn add_initiative_roll(_origin: TrustedOrigin, id: Uuid, character_init: Json<InitiativeRoll>, state: &State<Metagame<'_>>) ->
+    Result<(Status, (ContentType, ())), ApiError>
 {
+    validate(&*character_init)?;
     let (game_sender, response_channel) = channel::<Outcome>();
     let msg_channel = state.game_runner_pipe.clone();
     // let msg : RequestMessage = RequestMessage::AddInitiativeRoll
@@ -176,7 +804,7 @@ pub async fn add_initiative_roll(id: Uuid, character_init: Json<InitiativeRoll>,
             match response
             {
                 Outcome::Error(err) => {
-                    return Err((Status::BadRequest, err.message));
+                    return Err(ApiError::refused(Status::BadRequest, err));
                 },
 
                 Outcome::InitiativeRollAdded => {
@@ -185,14 +813,129 @@ pub async fn add_initiative_roll(id: Uuid, character_init: Json<InitiativeRoll>,
                 _ => {unreachable!()}
             }
         },
-        Err(error_string) => {
-            return Err((Status::InternalServerError, error_string));
+        Err(err) => {
+            return Err(err);
         },
     }
 }
 
-async fn do_send(msg: Message, msg_channel: Sender<Message>, response_channel: OneShotReceiver<Outcome>) 
-    -> Result<Outcome, String>
+// Rolls for several characters in one go, checked against the sender's session: a player may roll only for their own characters, the
+// GM for anyone's.  Either every roll is kept or none is, and the body says how each one fared; a refused set comes back as a 422.
+#[post("/<id>/initiatives", format = "json", data = "<rolls>")]
+pub async fn add_initiative_rolls(_origin: TrustedOrigin, id: Uuid, rolls: Json<Vec<InitiativeRoll>>, session: Session, idempotency: Idempotency, state: &State<Metagame<'_>>) 
+    -> Result<(Status, Json<InitiativeRollResults>), ApiError>
+{
+    debug!("Request received to add {} initiative rolls to game {}.", rolls.len(), id);
+    validate(&*rolls)?;
+
+    let request = idempotency.keyed(&format!("/{}/initiatives", id), &*rolls,
+        Request::AddInitiativeRolls(rolls.iter().map(|roll| Roll { character_id: roll.char_id, roll: roll.roll }).collect()));
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: request };
+
+    match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+    {
+        Ok(Outcome::InitiativeRollsAdded(results)) => Ok((Status::Ok, Json(roll_results(true, results)))),
+        Ok(Outcome::InitiativeRollsRejected(results)) => Ok((Status::UnprocessableEntity, Json(roll_results(false, results)))),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(Status::BadRequest, err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+fn roll_results(applied: bool, results: Vec<RollResult>) -> InitiativeRollResults
+{
+    let rolls = results.into_iter().map(|result| InitiativeRollResult { char_id: result.character_id, error: result.error.map(|err| err.message) }).collect();
+    InitiativeRollResults { applied, rolls }
+}
+
+// The GM may mark damage on anyone in the game, a player only on their own characters.
+#[post("/games/<id>/characters/<char_id>/damage", format = "json", data = "<damage>")]
+pub async fn damage_character(_origin: TrustedOrigin, id: Uuid, char_id: Uuid, damage: Json<Damage>, session: Session, idempotency: Idempotency, state: &State<Metagame<'_>>) -> Result<Status, ApiError>
+{
+    debug!("Request received to damage character {} in game {}.", char_id, id);
+    validate(&*damage)?;
+
+    let armor = match (damage.armor, damage.ap)
+    {
+        (None, None) => None,
+        (armor, ap) => Some((armor_test(armor.unwrap_or(ArmorKind::Ballistic)), ap.unwrap_or(0))),
+    };
+    let application = DamageApplication { source: None, target: char_id, boxes: damage.amount, kind: damage_type(damage.damage_type), armor };
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let request = idempotency.keyed(&format!("/games/{}/characters/{}/damage", id, char_id), &*damage, Request::ApplyDamage(application));
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: request };
+
+    match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+    {
+        Ok(Outcome::DamageApplied) => Ok(Status::NoContent),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(character_error_status(&err.kind), err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+// Healing follows the same rules as damage; the answer is the boxes that came back off each track.
+#[post("/games/<id>/characters/<char_id>/heal", format = "json", data = "<healing>")]
+pub async fn heal_character(_origin: TrustedOrigin, id: Uuid, char_id: Uuid, healing: Json<Healing>, session: Session, idempotency: Idempotency, state: &State<Metagame<'_>>) -> Result<Json<Recovered>, ApiError>
+{
+    debug!("Request received to heal character {} in game {}.", char_id, id);
+    validate(&*healing)?;
+
+    let application = match *healing
+    {
+        Healing::FirstAid { hits, rating, damage_type: kind } => RunnerHealing::FirstAid { hits, rating, kind: damage_type(kind) },
+        Healing::Magic { hits, force } => RunnerHealing::Magic { hits, force },
+        Healing::Rest { days } => RunnerHealing::Rest { days },
+    };
+    let request = idempotency.keyed(&format!("/games/{}/characters/{}/heal", id, char_id), &*healing,
+        Request::Heal(HealingApplication { character_id: char_id, healing: application }));
+
+    let (runner_sender, response_channel) = channel::<Outcome>();
+    let msg = Message { player_id: Some(session.player_id()), game_id: Some(id), reply_channel: runner_sender, msg: request };
+
+    match do_send(msg, state.game_runner_pipe.clone(), response_channel).await
+    {
+        Ok(Outcome::Healed(recovery)) => Ok(Json(Recovered { physical: recovery.physical, stun: recovery.stun })),
+        Ok(Outcome::Error(err)) => Err(ApiError::refused(character_error_status(&err.kind), err)),
+        Ok(_) => Err(ApiError::new(Status::InternalServerError, String::from("Unexpected response from the game runner."))),
+        Err(err) => Err(err),
+    }
+}
+
+fn damage_type(kind: DamageKind) -> DamageType
+{
+    match kind
+    {
+        DamageKind::Physical => DamageType::Physical,
+        DamageKind::Stun => DamageType::Stun,
+    }
+}
+
+fn armor_test(kind: ArmorKind) -> ArmorTestType
+{
+    match kind
+    {
+        ArmorKind::Ballistic => ArmorTestType::Ballistic,
+        ArmorKind::Impact => ArmorTestType::Impact,
+    }
+}
+
+fn character_error_status(kind: &ErrorKind) -> Status
+{
+    match kind
+    {
+        ErrorKind::UnauthorizedAction => Status::Forbidden,
+        ErrorKind::NoSuchCharacter | ErrorKind::UnknownId | ErrorKind::NoMatchingGame => Status::NotFound,
+        _ => Status::BadRequest,
+    }
+}
+
+// A full queue only comes back as an error when the overflow policy says to turn requests away, and then it is a 503 so the client
+// knows to try again rather than that something broke.
+pub(crate) async fn do_send(msg: Message, msg_channel: RunnerPipe, response_channel: OneShotReceiver<Outcome>) 
+    -> Result<Outcome, ApiError>
 {
 
     match msg_channel.send(msg).await
@@ -201,15 +944,24 @@ async fn do_send(msg: Message, msg_channel: Sender<Message>, response_channel: O
             match response_channel.await
             {
                 Ok(game_msg) => {return Ok(game_msg)},
+                // The runner only drops a reply unsent when it panics on the request, and it is restarted straight after.
                 Err(_) => {
-                    debug!("One shot send failed.  The one shot may have been closed by the other side with no message.");
-                    return Err(String::from("One shot send failed.  The one shot may have been closed by the other side with no message."))
+                    debug!("One shot send failed.  The game runner stopped before answering.");
+                    return Err(ApiError::new(Status::ServiceUnavailable, String::from("The game runner stopped before answering; try again shortly.")))
                 },
             }
         },
-        Err(_) => {
+        Err(QueueError::Saturated) => {
+            debug!("The game runner's queue is full and the request was turned away.");
+            return Err(ApiError::new(Status::ServiceUnavailable, String::from("The game runner is busy; try again shortly.")));
+        },
+        Err(QueueError::Recovering) => {
+            debug!("The game runner is restarting and the request was turned away.");
+            return Err(ApiError::new(Status::ServiceUnavailable, String::from("The game runner is restarting; try again shortly.")));
+        },
+        Err(QueueError::Closed) => {
             debug!("Blocking send failed on game create.  Channel may be defunct.");
-            return Err(String::from("Blocking send failed on game create request.  Channel may have closed."));
+            return Err(ApiError::new(Status::InternalServerError, String::from("Blocking send failed on game create request.  Channel may have closed.")));
         },
     }
 }
@@ -247,4 +999,72 @@ fn copy_character(character: &Character) -> crate::tracker::character::Character
     
     
    game_char
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use rocket::http::{Status, uri::Origin};
+    use tokio::sync::{mpsc::channel, oneshot};
+    use uuid::Uuid;
+
+    use crate::{gamerunner::{ErrorKind, dispatcher::{Message, Request}}, http::{metagame::Metagame, queue::{RunnerPipe, OverflowPolicy}, serde::GameConfirmation}};
+
+    use super::{confirm_game_name, game_error_status, do_send};
+
+    fn confirming(game_name: &str) -> GameConfirmation
+    {
+        GameConfirmation { game_name: String::from(game_name) }
+    }
+
+    #[tokio::test]
+    pub async fn a_request_turned_away_by_a_full_or_restarting_runner_is_answered_with_a_503()
+    {
+        let (sender, _receiver) = channel(1);
+        let pipe = RunnerPipe::new(sender, OverflowPolicy::Reject);
+        let (reply_channel, _) = oneshot::channel();
+        assert!(pipe.send(Message { player_id: None, game_id: None, reply_channel, msg: Request::FetchInbox }).await.is_ok());
+
+        let (reply_channel, response_channel) = oneshot::channel();
+        let Err(refusal) = do_send(Message { player_id: None, game_id: None, reply_channel, msg: Request::FetchInbox }, pipe.clone(), response_channel).await
+        else { panic!("A full queue under Reject should turn the request away.") };
+        assert_eq!(Status::ServiceUnavailable, refusal.status);
+        assert_eq!(1, pipe.stats().rejected);
+
+        pipe.set_recovering(true);
+        let (reply_channel, response_channel) = oneshot::channel();
+        let Err(refusal) = do_send(Message { player_id: None, game_id: None, reply_channel, msg: Request::FetchInbox }, pipe.clone(), response_channel).await
+        else { panic!("A restarting runner should turn the request away.") };
+        assert_eq!(Status::ServiceUnavailable, refusal.status);
+    }
+
+    #[test]
+    pub fn a_game_is_confirmed_by_its_exact_name_or_by_its_id_when_the_web_side_has_no_name_for_it()
+    {
+        let (sender, _) = channel(1);
+        let metagame = Metagame::new(RunnerPipe::new(sender, OverflowPolicy::Wait));
+        let (named, unnamed) = (Uuid::new_v4(), Uuid::new_v4());
+        metagame.new_game(named, Uuid::new_v4(), Arc::from("Bug City"), Origin::ROOT);
+
+        assert!(confirm_game_name(named, &confirming("Bug City"), &metagame).is_ok());
+        assert!(confirm_game_name(named, &confirming("  Bug City\n"), &metagame).is_ok());
+        let by_id = named.to_string();
+        for wrong in ["bug city", "Bug", "", by_id.as_str()]
+        {
+            assert!(matches!(confirm_game_name(named, &confirming(wrong), &metagame), Err(err) if err.status == Status::UnprocessableEntity));
+        }
+
+        assert!(confirm_game_name(unnamed, &confirming(&unnamed.to_string()), &metagame).is_ok());
+        assert!(matches!(confirm_game_name(unnamed, &confirming("Bug City"), &metagame), Err(err) if err.status == Status::UnprocessableEntity));
+    }
+
+    #[test]
+    pub fn a_missing_game_is_not_found_and_any_other_refusal_is_forbidden()
+    {
+        assert_eq!(Status::NotFound, game_error_status(&ErrorKind::NoMatchingGame));
+        assert_eq!(Status::NotFound, game_error_status(&ErrorKind::UnknownId));
+        assert_eq!(Status::Forbidden, game_error_status(&ErrorKind::UnauthorizedAction));
+        assert_eq!(Status::Forbidden, game_error_status(&ErrorKind::GameArchived));
+    }
+}
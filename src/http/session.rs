@@ -1,15 +1,21 @@
-use std::{collections::HashMap, sync::Arc};
-use log::debug;
+use std::{collections::HashMap, sync::Arc, path::PathBuf};
+use tracing::{debug, error};
 use parking_lot::{RwLock, Mutex};
-use rocket::{Request, request::{FromRequest, Outcome, self}, http::Cookie, time::{OffsetDateTime, Duration}};
+use rocket::{Request, Responder, catch, request::{FromRequest, Outcome, self}, http::{Cookie, Header, SameSite, Status}, time::{OffsetDateTime, Duration}, 
+    figment::value::magic::RelativePathBuf, serde::{Serialize, Deserialize, json}};
+use tokio::sync::Notify;
 use uuid::Uuid;
 
+use super::{proxy::Forwarded, store::{self, StoreError}};
+
 pub struct SessionData
 {
     pub gm_of_games: Vec<Uuid>,
     pub handle: Arc<String>,
     pub player_id: Arc<Uuid>,
-    pub game_to_character: HashMap<Uuid, Uuid>
+    pub game_to_character: HashMap<Uuid, Uuid>,
+    pub csrf_token: Arc<String>,
+    pub last_seen: OffsetDateTime,
 }
 
 impl SessionData
@@ -22,8 +28,106 @@ impl SessionData
             handle: Arc::new(String::from("__none__")), 
             player_id: Arc::new(Uuid::new_v4()),
             game_to_character: HashMap::new(), 
+            csrf_token: Arc::new(new_csrf_token()),
+            last_seen: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+// How long a session lasts without being used, how often the sweeper clears out the ones that have run out, and where they are kept
+// between restarts.  Read out of Rocket.toml alongside the queue and proxy settings; a relative store path is taken from the directory
+// Rocket.toml is in.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionConfig
+{
+    #[serde(default = "SessionConfig::default_ttl_minutes")]
+    pub session_ttl_minutes: u32,
+    #[serde(default = "SessionConfig::default_sweep_minutes")]
+    pub session_sweep_minutes: u32,
+    #[serde(default = "SessionConfig::default_store")]
+    pub session_store: RelativePathBuf,
+}
+
+impl SessionConfig
+{
+    pub fn new() -> SessionConfig
+    {
+        SessionConfig 
+        { 
+            session_ttl_minutes: SessionConfig::default_ttl_minutes(), 
+            session_sweep_minutes: SessionConfig::default_sweep_minutes(), 
+            session_store: SessionConfig::default_store(),
         }
     }
+
+    fn default_store() -> RelativePathBuf
+    {
+        RelativePathBuf::from("sessions.json")
+    }
+
+    pub fn store_path(&self) -> PathBuf
+    {
+        self.session_store.relative()
+    }
+
+    fn default_ttl_minutes() -> u32
+    {
+        24 * 60
+    }
+
+    fn default_sweep_minutes() -> u32
+    {
+        10
+    }
+
+    // A zero would expire every session on its next request, or spin the sweeper, so both are held to at least a minute.
+    pub fn normalized(mut self) -> SessionConfig
+    {
+        self.session_ttl_minutes = self.session_ttl_minutes.max(1);
+        self.session_sweep_minutes = self.session_sweep_minutes.max(1);
+        self
+    }
+
+    pub fn ttl(&self) -> Duration
+    {
+        Duration::minutes(self.session_ttl_minutes.into())
+    }
+
+    pub fn sweep_interval(&self) -> std::time::Duration
+    {
+        std::time::Duration::from_secs(u64::from(self.session_sweep_minutes) * 60)
+    }
+}
+
+// Form posts ride on the session cookie, which the browser attaches to cross-site submissions too; the token embedded in each rendered
+// form is what proves a post actually came from one of our pages.
+fn new_csrf_token() -> String
+{
+    Uuid::new_v4().simple().to_string()
+}
+
+// Compares every byte regardless of where the first mismatch is, so timing does not leak how much of a guess was right.
+fn constant_time_eq(expected: &[u8], given: &[u8]) -> bool
+{
+    expected.len() == given.len() && expected.iter().zip(given).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// On-disk form of a session, so that a browser holding a session cookie from before a restart gets its player id back.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct StoredSession
+{
+    pub session_id: Uuid,
+    pub handle: String,
+    pub player_id: Uuid,
+    pub gm_of_games: Vec<Uuid>,
+    pub game_to_character: HashMap<Uuid, Uuid>,
+    #[serde(default = "new_csrf_token")]
+    pub csrf_token: String,
+    // Seconds since the epoch.  Sessions saved before this was kept count as seen at startup.
+    #[serde(default)]
+    pub last_seen: Option<i64>,
 }
 
 pub struct Session
@@ -38,6 +142,48 @@ impl Session
         Session { session_data: Arc::new(Mutex::new(SessionData::new())) }
     }
 
+    pub fn from_stored(stored: StoredSession) -> Session
+    {
+        let data = SessionData 
+        { 
+            gm_of_games: stored.gm_of_games, 
+            handle: Arc::new(stored.handle), 
+            player_id: Arc::new(stored.player_id), 
+            game_to_character: stored.game_to_character,
+            csrf_token: Arc::new(stored.csrf_token),
+            last_seen: stored.last_seen.and_then(|seen| OffsetDateTime::from_unix_timestamp(seen).ok()).unwrap_or_else(OffsetDateTime::now_utc),
+        };
+
+        Session { session_data: Arc::new(Mutex::new(data)) }
+    }
+
+    pub fn to_stored(&self, session_id: Uuid) -> StoredSession
+    {
+        let data = self.session_data.lock();
+
+        StoredSession 
+        { 
+            session_id, 
+            handle: (*data.handle).clone(), 
+            player_id: *data.player_id, 
+            gm_of_games: data.gm_of_games.clone(), 
+            game_to_character: data.game_to_character.clone(),
+            csrf_token: (*data.csrf_token).clone(),
+            last_seen: Some(data.last_seen.unix_timestamp()),
+        }
+    }
+
+    // Marks the session as used just now, pushing its expiry back a full TTL.
+    pub fn touch(&self)
+    {
+        self.session_data.lock().last_seen = OffsetDateTime::now_utc();
+    }
+
+    pub fn expired(&self, ttl: Duration, now: OffsetDateTime) -> bool
+    {
+        self.session_data.lock().last_seen + ttl <= now
+    }
+
     pub fn clone(&self) -> Session
     {
         Session { session_data: self.session_data.clone()}
@@ -62,11 +208,32 @@ impl Session
         data.gm_of_games.push(game_id);
     }
 
+    // What a login turns this session into: the account's player under the same handle, with a fresh CSRF token.  It goes in under a new
+    // session id too, so an id a browser was handed before logging in - by a page that planted it, say - is worth nothing afterwards.
+    pub fn signed_in_as(&self, player_id: Uuid) -> Session
+    {
+        let data = SessionData { handle: self.handle_as_ref(), player_id: Arc::new(player_id), ..SessionData::new() };
+
+        Session { session_data: Arc::new(Mutex::new(data)) }
+    }
+
     pub fn player_id(&self) -> Uuid
     {
         (*self.session_data.lock().player_id).clone()
     }
 
+    pub fn csrf_token(&self) -> Arc<String>
+    {
+        self.session_data.lock().csrf_token.clone()
+    }
+
+    pub fn verify_csrf(&self, token: &str) -> bool
+    {
+        let data = self.session_data.lock();
+
+        constant_time_eq(data.csrf_token.as_bytes(), token.as_bytes())
+    }
+
     pub fn add_pc(&self, game_id: Uuid, char_id: Uuid)
     {
         let mut data = self.session_data.lock();
@@ -89,9 +256,23 @@ impl Session
     }
 }
 
+// Clones share the same sessions, so the sweeper and saver tasks can each hold one while Rocket manages another.
+#[derive(Clone)]
 pub struct SessionMap
 {
-    sessions: RwLock<HashMap<Uuid, Session>>
+    sessions: Arc<RwLock<HashMap<Uuid, Session>>>,
+    store: Option<PathBuf>,
+    // Rung by every change; the saver writes the store out once for however many changes rang it in the meantime.
+    unsaved: Arc<Notify>,
+    ttl: Duration,
+}
+
+// What a session cookie turned out to point at.
+pub enum SessionLookup
+{
+    Active(Session),
+    Expired,
+    Unknown,
 }
 
 
@@ -100,7 +281,72 @@ impl SessionMap
 {
     pub fn new() -> SessionMap
     {
-        SessionMap { sessions: RwLock::new(HashMap::new()) }
+        SessionMap { sessions: Arc::new(RwLock::new(HashMap::new())), store: None, unsaved: Arc::new(Notify::new()), ttl: Duration::DAY }
+    }
+
+    // A session map backed by a file.  Whatever sessions were saved there before the last shutdown are loaded back in; a missing file
+    // means starting with no sessions, and one that will not parse is moved aside first.
+    pub fn with_store(path: PathBuf) -> Result<SessionMap, StoreError>
+    {
+        let mut sessions = HashMap::new();
+
+        if let Some(stored) = store::load::<Vec<StoredSession>>(&path, "session")?
+        {
+            for stored_session in stored
+            {
+                sessions.insert(stored_session.session_id, Session::from_stored(stored_session));
+            }
+            debug!("Restored {} sessions from {}", sessions.len(), path.display());
+        }
+
+        Ok(SessionMap { sessions: Arc::new(RwLock::new(sessions)), store: Some(path), unsaved: Arc::new(Notify::new()), ttl: Duration::DAY })
+    }
+
+    pub fn with_ttl(self, ttl: Duration) -> SessionMap
+    {
+        SessionMap { ttl, ..self }
+    }
+
+    pub fn ttl(&self) -> Duration
+    {
+        self.ttl
+    }
+
+    // Asks the saver to write the store out.  Nothing is written here, so a request that changes a session never waits on the disk.
+    pub fn save(&self)
+    {
+        if self.store.is_some()
+        {
+            self.unsaved.notify_one();
+        }
+    }
+
+    fn write_store(&self)
+    {
+        let Some(path) = &self.store
+        else { return };
+
+        let stored: Vec<StoredSession> = self.sessions.read().iter().map(|(id, session)| session.to_stored(*id)).collect();
+
+        match json::to_string(&stored)
+        {
+            Ok(contents) => if let Err(err) = store::write_private(path, &contents) { error!("Could not write session store {}: {}", path.display(), err) },
+            Err(err) => error!("Could not serialize sessions: {}", err),
+        }
+    }
+
+    // Runs for the life of the server, writing the store out on the blocking pool whenever something has changed since the last write.
+    pub async fn run_saver(self)
+    {
+        loop
+        {
+            self.unsaved.notified().await;
+            let map = self.clone();
+            if tokio::task::spawn_blocking(move || map.write_store()).await.is_err()
+            {
+                error!("Writing the session store panicked.");
+            }
+        }
     }
 
     pub fn find_session(&self, id: Uuid) -> Option<Session>
@@ -116,14 +362,70 @@ impl SessionMap
         }
     }
 
+    // Looks the session up for a request.  A live one is renewed; one left idle past the TTL is dropped on the spot.
+    pub fn resume(&self, id: Uuid) -> SessionLookup
+    {
+        let Some(session) = self.find_session(id)
+        else { return SessionLookup::Unknown };
+
+        if session.expired(self.ttl, OffsetDateTime::now_utc())
+        {
+            debug!("Session {} has expired.", id);
+            self.drop_session(id);
+            return SessionLookup::Expired;
+        }
+
+        session.touch();
+        SessionLookup::Active(session)
+    }
+
+    // Drops every session idle past the TTL, saving only if there were any.  Returns how many went.
+    pub fn sweep(&self) -> usize
+    {
+        let now = OffsetDateTime::now_utc();
+        let swept = {
+            let mut sessions = self.sessions.write();
+            let before = sessions.len();
+            sessions.retain(|_, session| !session.expired(self.ttl, now));
+            before - sessions.len()
+        };
+
+        if swept > 0
+        {
+            debug!("Swept {} expired sessions.", swept);
+            self.save();
+        }
+        swept
+    }
+
+    // Runs for the life of the server, sweeping on every interval.
+    pub async fn run_sweeper(self, interval: std::time::Duration)
+    {
+        let mut ticker = tokio::time::interval(interval);
+        loop
+        {
+            ticker.tick().await;
+            self.sweep();
+        }
+    }
+
     pub fn add_session(&self, id: Uuid, session: Session)
     {
         self.sessions.write().insert(id, session);
+        self.save();
     }
 
     pub fn drop_session(&self, id: Uuid)
     {
         self.sessions.write().remove(&id);
+        self.save();
+    }
+
+    // Every device the player is logged in on, not just the one asking.
+    pub fn drop_player_sessions(&self, player_id: Uuid)
+    {
+        self.sessions.write().retain(|_, session| session.player_id() != player_id);
+        self.save();
     }
 }
 
@@ -133,12 +435,96 @@ pub struct NoSession
 
 }
 
+// The cookie carries the session's id and runs out a TTL after it was last renewed, so a browser forgets it no later than the server.
+pub fn session_cookie_for(request: &Request<'_>, session_id: Uuid, ttl: Duration) -> Cookie<'static>
+{
+    session_cookie(session_id, ttl, Forwarded::inspect(request).https)
+}
+
+pub fn session_cookie(session_id: Uuid, ttl: Duration, https: bool) -> Cookie<'static>
+{
+    // Once the browser is talking https - to us or to the proxy in front of us - keep the session cookie off plain http.
+    // Lax rather than Strict, so following a link to a game from elsewhere still arrives signed in.
+    Cookie::build("shadowrun_combat_session", session_id.to_string())
+        .expires(OffsetDateTime::now_utc().saturating_add(ttl))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(https)
+        .finish()
+}
+
+// The sign-in form has no session to carry a CSRF token yet, so it is issued one of its own: once in a SameSite=Strict cookie, and
+// once in the page (and in a header, for clients that are not browsers).  A cross-site post neither gets the cookie sent along nor can
+// read the page, so only a form we served can present both halves.
+pub const REGISTRATION_COOKIE: &str = "shadowrun_combat_registration";
+pub const REGISTRATION_TOKEN_HEADER: &str = "X-Registration-Token";
+
+pub struct Registration
+{
+    pub token: String,
+}
+
+impl Registration
+{
+    pub fn verify(&self, token: &str) -> bool
+    {
+        constant_time_eq(self.token.as_bytes(), token.as_bytes())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Registration
+{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error>
+    {
+        if let Some(cookie) = request.cookies().get(REGISTRATION_COOKIE)
+        {
+            return Outcome::Success(Registration { token: String::from(cookie.value()) });
+        }
+
+        let token = new_csrf_token();
+        let cookie = Cookie::build(REGISTRATION_COOKIE, token.clone())
+            .same_site(SameSite::Strict)
+            .http_only(true)
+            .secure(Forwarded::inspect(request).https)
+            .finish();
+        request.cookies().add(cookie);
+
+        Outcome::Success(Registration { token })
+    }
+}
+
+// Sent on every 401 the session guard raises.  Routes turn down bad credentials with a 401 of their own, so the header is what tells a
+// client its session is gone for good and the way back is to register a new one.
+pub const SESSION_EXPIRED_HEADER: &str = "X-Session-Expired";
+
+#[derive(Responder)]
+#[response(status = 401)]
+pub struct SessionExpired
+{
+    message: &'static str,
+    reregister: Header<'static>,
+}
+
+#[catch(401)]
+pub fn session_expired() -> SessionExpired
+{
+    SessionExpired
+    {
+        message: "Your session has expired. Register a new session to continue.",
+        reregister: Header::new(SESSION_EXPIRED_HEADER, "register"),
+    }
+}
+
 #[derive(Debug)]
 pub enum NewSessionOutcome
 {
     New,
     Exists,
     Expired,
+    Unproven,
 
 }
 
@@ -173,14 +559,21 @@ impl<'r> FromRequest<'r> for Session
 
                 let map = request.rocket().state::<SessionMap>().unwrap_or_else(|| panic!());
 
-                match map.find_session(session_id)
+                match map.resume(session_id)
                 {
-                    Some(session) => 
+                    SessionLookup::Active(session) => 
                     {
                         debug!("Session UUID maps to a valid Session object");
+                        request.cookies().add(session_cookie_for(request, session_id, map.ttl()));
                         return Outcome::Success(session);
                     },
-                    None => 
+                    SessionLookup::Expired =>
+                    {
+                        debug!("Session UUID maps to an expired Session - rejecting.");
+                        request.cookies().remove(Cookie::named("shadowrun_combat_session"));
+                        return Outcome::Failure((Status::Unauthorized, NoSession {}))
+                    },
+                    SessionLookup::Unknown => 
                     {
                         debug!("Session UUID does NOT map to a valid Session object - throwing.");
                         return Outcome::Forward(())
@@ -204,6 +597,13 @@ impl<'r> FromRequest<'r> for NewSessionOutcome
     async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error>
     {
         debug!("Starting NewSessionOutcome guard.");
+        // Checked before the old session is dropped, so that a cross-site post cannot sign a player out by starting a session over theirs.
+        if request.cookies().get(REGISTRATION_COOKIE).is_none()
+        {
+            debug!("Session request arrived without the sign-in page's registration cookie.");
+            return Outcome::Failure((Status::Forbidden, NewSessionOutcome::Unproven));
+        }
+
         let new_session_id = Uuid::new_v4();
         let response: Outcome<Self, Self::Error>;
         match request.cookies().get("shadowrun_combat_session")
@@ -234,13 +634,56 @@ impl<'r> FromRequest<'r> for NewSessionOutcome
         let new_session = Session::new();
         let map = request.rocket().state::<SessionMap>().unwrap_or_else(|| panic!());
         map.add_session(new_session_id, new_session);
-        let session_cookie = Cookie::build("shadowrun_combat_session", new_session_id.to_string())
-            .expires(OffsetDateTime::now_utc().saturating_add(Duration::DAY))
-            .finish();
-        request.cookies().add(session_cookie);
+        request.cookies().add(session_cookie_for(request, new_session_id, map.ttl()));
 
         debug!("Finishing new session with value {}", response);
 
         return response;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests
+{
+    use std::{fs, time::Duration};
+
+    use uuid::Uuid;
+
+    use super::{Session, SessionMap};
+
+    #[test]
+    pub fn signing_in_keeps_the_handle_but_not_the_csrf_token_or_player()
+    {
+        let session = Session::new();
+        session.set_handle(String::from("Wraith"));
+        let account_player = Uuid::new_v4();
+
+        let signed_in = session.signed_in_as(account_player);
+        assert_eq!(account_player, signed_in.player_id());
+        assert_eq!(session.handle_as_ref(), signed_in.handle_as_ref());
+        assert!(!signed_in.verify_csrf(&session.csrf_token()));
+    }
+
+    #[tokio::test]
+    pub async fn the_saver_writes_sessions_out_after_the_change_has_been_answered()
+    {
+        let dir = std::env::temp_dir().join(format!("scm-session-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sessions.json");
+
+        let sessions = SessionMap::with_store(path.clone()).unwrap();
+        tokio::spawn(sessions.clone().run_saver());
+        let session_id = Uuid::new_v4();
+        sessions.add_session(session_id, Session::new());
+        assert!(!path.exists());
+
+        for _ in 0..50
+        {
+            if path.exists() { break; }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let restored = SessionMap::with_store(path).unwrap();
+        assert!(restored.find_session(session_id).is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
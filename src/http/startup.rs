@@ -0,0 +1,191 @@
+use std::{net::{IpAddr, TcpListener}, time::Duration};
+
+use tracing::{info, warn};
+use rocket::{Rocket, Build, Orbit, serde::Deserialize};
+
+use super::store::StoreError;
+
+// What the server does when the port it was given is taken, read out of Rocket.toml alongside the listener settings.  By default it
+// gives up at once, as Rocket itself would.
+#[derive(Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct StartupConfig
+{
+    // How many more times to try the configured port, bind_retry_seconds apart, before moving on to the fallbacks.
+    #[serde(default)]
+    pub bind_retries: u32,
+    #[serde(default = "StartupConfig::default_retry_seconds")]
+    pub bind_retry_seconds: u64,
+    // Ports to try, in order and once each, when the configured one never comes free.
+    #[serde(default)]
+    pub fallback_ports: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub enum StartupError
+{
+    // Neither the configured port nor any fallback could be bound; holds the last failure.
+    Bind(IpAddr, u16, std::io::Error),
+    // Rocket failed to start or stopped with an error of its own.
+    Launch(String),
+    // A store the server keeps on disk is there but could not be used, and starting would write over it.
+    Store(StoreError),
+}
+
+impl std::fmt::Display for StartupError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            StartupError::Bind(address, port, err) => write!(f, "could not bind {}:{} ({})", address, port, err),
+            StartupError::Launch(err) => write!(f, "the server failed: {}", err),
+            StartupError::Store(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl StartupConfig
+{
+    pub fn new() -> StartupConfig
+    {
+        StartupConfig { bind_retries: 0, bind_retry_seconds: StartupConfig::default_retry_seconds(), fallback_ports: Vec::new() }
+    }
+
+    fn default_retry_seconds() -> u64
+    {
+        2
+    }
+
+    // Finds the first port Rocket will be able to bind on the address: the configured one, retried, then each fallback.  The check
+    // binds and lets go again, so something else could still take the port before Rocket does; launch reports that as a Launch error.
+    pub async fn choose_port(&self, address: IpAddr, port: u16) -> Result<u16, StartupError>
+    {
+        let mut last_failure = None;
+        for attempt in 0..=self.bind_retries
+        {
+            if attempt > 0
+            {
+                tokio::time::sleep(Duration::from_secs(self.bind_retry_seconds)).await;
+            }
+            match TcpListener::bind((address, port))
+            {
+                Ok(_) => return Ok(port),
+                Err(err) =>
+                {
+                    warn!("Port {} on {} is not available ({}); {} of {} tries made.", port, address, err, attempt + 1, self.bind_retries + 1);
+                    last_failure = Some((port, err));
+                },
+            }
+        }
+
+        for fallback in self.fallback_ports.iter().copied()
+        {
+            match TcpListener::bind((address, fallback))
+            {
+                Ok(_) =>
+                {
+                    warn!("Falling back to port {} on {}.", fallback, address);
+                    return Ok(fallback);
+                },
+                Err(err) =>
+                {
+                    warn!("Fallback port {} on {} is not available either ({}).", fallback, address, err);
+                    last_failure = Some((fallback, err));
+                },
+            }
+        }
+
+        let (port, err) = last_failure.expect("At least one bind has been tried.");
+        Err(StartupError::Bind(address, port, err))
+    }
+}
+
+// Points Rocket at the port choose_port settled on.
+pub fn bind_to(rocket: Rocket<Build>, port: u16) -> Rocket<Build>
+{
+    let figment = rocket.figment().clone().merge(("port", port));
+    rocket.configure(figment)
+}
+
+// Logs where the server ended up listening once it has.
+pub fn log_bound_address(rocket: &Rocket<Orbit>)
+{
+    let config = rocket.config();
+    let scheme = if config.tls_enabled() { "https" } else { "http" };
+    info!("Listening on {}://{}:{}", scheme, config.address, config.port);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{net::{IpAddr, Ipv4Addr, TcpListener}, time::Duration};
+
+    use super::{StartupConfig, StartupError};
+
+    const LOOPBACK: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    // A port nothing is listening on, found by binding port 0 and letting go of whatever the system handed out.
+    fn free_port() -> u16
+    {
+        TcpListener::bind((LOOPBACK, 0)).unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    pub async fn a_free_port_is_taken_as_configured()
+    {
+        let port = free_port();
+        assert_eq!(port, StartupConfig::new().choose_port(LOOPBACK, port).await.unwrap());
+    }
+
+    #[tokio::test]
+    pub async fn a_taken_port_with_no_retries_or_fallbacks_is_a_bind_error_naming_it()
+    {
+        let held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+
+        let result = StartupConfig::new().choose_port(LOOPBACK, port).await;
+        assert!(matches!(result, Err(StartupError::Bind(address, failed, _)) if address == LOOPBACK && failed == port));
+    }
+
+    #[tokio::test]
+    pub async fn a_taken_port_falls_back_to_the_first_fallback_that_is_free()
+    {
+        let held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+        let also_held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let taken_fallback = also_held.local_addr().unwrap().port();
+        let free_fallback = free_port();
+
+        let config = StartupConfig { bind_retries: 0, bind_retry_seconds: 0, fallback_ports: vec![taken_fallback, free_fallback] };
+        assert_eq!(free_fallback, config.choose_port(LOOPBACK, port).await.unwrap());
+    }
+
+    #[tokio::test]
+    pub async fn when_every_fallback_is_taken_the_last_one_tried_is_reported()
+    {
+        let held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+        let also_held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let fallback = also_held.local_addr().unwrap().port();
+
+        let config = StartupConfig { bind_retries: 1, bind_retry_seconds: 0, fallback_ports: vec![fallback] };
+        let result = config.choose_port(LOOPBACK, port).await;
+        assert!(matches!(result, Err(StartupError::Bind(_, failed, _)) if failed == fallback));
+    }
+
+    #[tokio::test]
+    pub async fn a_port_let_go_of_between_retries_is_taken_on_the_retry()
+    {
+        let held = TcpListener::bind((LOOPBACK, 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+        tokio::spawn(async move
+        {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            drop(held);
+        });
+
+        let config = StartupConfig { bind_retries: 3, bind_retry_seconds: 1, fallback_ports: vec![free_port()] };
+        assert_eq!(port, config.choose_port(LOOPBACK, port).await.unwrap());
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs, io::{self, Write}, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+
+use tracing::{debug, error};
+use rocket::serde::{de::DeserializeOwned, json};
+
+// The files the server keeps its accounts and sessions in.  Both hold secrets - password hashes, bearer session ids - so they are written
+// readable by the server's user alone, and never in place: a crash part way through a write leaves the last good copy where it was.
+
+#[derive(Debug)]
+pub enum StoreError
+{
+    // The file is there but could not be read at all.
+    Unreadable(PathBuf, io::Error),
+    // The file could not be parsed, and could not be moved out of the way either; starting anyway would write over it.
+    Stuck(PathBuf, io::Error),
+}
+
+impl std::fmt::Display for StoreError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            StoreError::Unreadable(path, err) => write!(f, "{} could not be read ({})", path.display(), err),
+            StoreError::Stuck(path, err) => write!(f, "{} could not be parsed or moved aside ({})", path.display(), err),
+        }
+    }
+}
+
+// Reads a store back in.  A missing file is nothing saved yet.  One that does not parse is renamed out of the way with the time it was
+// found, so it can be looked at and nothing written later lands on top of it, and the store starts empty.
+pub fn load<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Option<T>, StoreError>
+{
+    let contents = match fs::read_to_string(path)
+    {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound =>
+        {
+            debug!("No {} store at {}; starting fresh.", what, path.display());
+            return Ok(None);
+        },
+        Err(err) => return Err(StoreError::Unreadable(path.to_path_buf(), err)),
+    };
+
+    match json::from_str::<T>(&contents)
+    {
+        Ok(stored) => Ok(Some(stored)),
+        Err(err) =>
+        {
+            let found = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default();
+            let aside = with_suffix(path, &format!(".unreadable-{}", found));
+            fs::rename(path, &aside).map_err(|err| StoreError::Stuck(path.to_path_buf(), err))?;
+
+            error!("The {} store {} could not be parsed ({}); it was moved to {} and the server starts without it.", what, path.display(), err, aside.display());
+            Ok(None)
+        },
+    }
+}
+
+// Writes the whole store to a file beside it, then renames that over the old one.
+pub fn write_private(path: &Path, contents: &str) -> io::Result<()>
+{
+    let staged = with_suffix(path, ".tmp");
+    let mut file = private_file(&staged)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(&staged, path)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf
+{
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(unix)]
+fn private_file(path: &Path) -> io::Result<fs::File>
+{
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode only applies to a file being created; one left behind by an earlier crash keeps whatever it had.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn private_file(path: &Path) -> io::Result<fs::File>
+{
+    fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::fs;
+
+    use super::{load, write_private};
+
+    #[test]
+    pub fn a_store_that_will_not_parse_is_moved_aside_rather_than_written_over()
+    {
+        let dir = std::env::temp_dir().join(format!("scm-store-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("accounts.json");
+        fs::write(&path, "[{\"username\": ").unwrap();
+
+        assert!(matches!(load::<Vec<String>>(&path, "account"), Ok(None)));
+        assert!(!path.exists());
+        let aside: Vec<_> = fs::read_dir(&dir).unwrap().filter_map(Result::ok).collect();
+        assert_eq!(1, aside.len());
+        assert_eq!("[{\"username\": ", fs::read_to_string(aside[0].path()).unwrap());
+
+        write_private(&path, "[\"wraith\"]").unwrap();
+        assert_eq!(Some(vec![String::from("wraith")]), load::<Vec<String>>(&path, "account").unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(0o600, fs::metadata(&path).unwrap().permissions().mode() & 0o777);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
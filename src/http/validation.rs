@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use rocket::http::Status;
+use uuid::Uuid;
+
+use super::serde::{Character, InitiativeRoll, NewState, State, Credentials, Damage, Healing};
+
+// Checks applied to request bodies before anything is handed to the game runner.  Serde has already made sure the shapes and UUIDs
+// parse; these are the limits a well-formed but unreasonable body would otherwise carry straight into game state.
+
+pub const MAX_NAME_LENGTH: usize = 64;
+pub const MAX_USERNAME_LENGTH: usize = 32;
+pub const MAX_PASSWORD_LENGTH: usize = 256;
+// A starting roll is the initiative score plus up to five initiative dice; nothing legal gets near these bounds.
+pub const MIN_INITIATIVE_ROLL: i8 = 1;
+pub const MAX_INITIATIVE_ROLL: i8 = 80;
+pub const MAX_COMBATANTS: usize = 100;
+// Longer than any condition monitor gets, overflow included, even on the toughest troll; the game clamps to the real track.
+pub const MAX_DAMAGE_BOXES: u8 = 40;
+// No weapon or ammunition in the books comes close to either end.
+pub const MIN_ARMOR_PEN: i8 = -20;
+pub const MAX_ARMOR_PEN: i8 = 20;
+// Hits can't outnumber the dice rolled, and no pool, skill or Force a character can reach runs past these.
+pub const MAX_HEALING_HITS: u8 = 40;
+pub const MAX_HEALING_RATING: u8 = 20;
+pub const MAX_REST_DAYS: u16 = 365;
+
+pub trait Validate
+{
+    // Every problem found, so a client can fix them all in one go rather than one resubmission per mistake.
+    fn problems(&self) -> Vec<String>;
+}
+
+// Runs a body's checks and turns any failures into the 422 the handlers return as-is.
+pub fn validate<T: Validate + ?Sized>(body: &T) -> Result<(), (Status, String)>
+{
+    let problems = body.problems();
+
+    if problems.is_empty()
+    {
+        Ok(())
+    }
+    else
+    {
+        Err((Status::UnprocessableEntity, problems.join(" ")))
+    }
+}
+
+pub fn check_name(field: &str, name: &str, problems: &mut Vec<String>)
+{
+    let trimmed = name.trim();
+
+    if trimmed.is_empty()
+    {
+        problems.push(String::from(format!("{} must not be blank.", field)));
+    }
+    else if trimmed.chars().count() > MAX_NAME_LENGTH
+    {
+        problems.push(String::from(format!("{} must be at most {} characters long.", field, MAX_NAME_LENGTH)));
+    }
+    else if trimmed.chars().any(|c| c.is_control())
+    {
+        problems.push(String::from(format!("{} must not contain control characters.", field)));
+    }
+}
+
+pub fn check_id(field: &str, id: &Uuid, problems: &mut Vec<String>)
+{
+    if id.is_nil()
+    {
+        problems.push(String::from(format!("{} must not be the nil UUID.", field)));
+    }
+}
+
+impl Validate for Character<'_>
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+        check_name("Character name", self.name, &mut problems);
+        problems
+    }
+}
+
+impl Validate for InitiativeRoll
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+        check_id("char_id", &self.char_id, &mut problems);
+
+        if self.roll < MIN_INITIATIVE_ROLL || self.roll > MAX_INITIATIVE_ROLL
+        {
+            problems.push(String::from(format!("Initiative roll must be between {} and {}; got {}.", MIN_INITIATIVE_ROLL, MAX_INITIATIVE_ROLL, self.roll)));
+        }
+
+        problems
+    }
+}
+
+impl Validate for Vec<InitiativeRoll>
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+
+        if self.is_empty()
+        {
+            problems.push(String::from("At least one initiative roll must be given."));
+        }
+        else if self.len() > MAX_COMBATANTS
+        {
+            problems.push(String::from(format!("At most {} initiative rolls may be sent at once.", MAX_COMBATANTS)));
+        }
+        for (index, roll) in self.iter().enumerate()
+        {
+            problems.extend(roll.problems().into_iter().map(|problem| format!("Roll {}: {}", index, problem)));
+        }
+
+        problems
+    }
+}
+
+impl Validate for Damage
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+
+        if self.amount == 0 || self.amount > MAX_DAMAGE_BOXES
+        {
+            problems.push(String::from(format!("Damage must be between 1 and {} boxes; got {}.", MAX_DAMAGE_BOXES, self.amount)));
+        }
+
+        if let Some(ap) = self.ap.filter(|ap| *ap < MIN_ARMOR_PEN || *ap > MAX_ARMOR_PEN)
+        {
+            problems.push(String::from(format!("Armor penetration must be between {} and {}; got {}.", MIN_ARMOR_PEN, MAX_ARMOR_PEN, ap)));
+        }
+
+        problems
+    }
+}
+
+impl Validate for Healing
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+
+        let (hits, field, rating) = match self
+        {
+            Healing::FirstAid { hits, rating, .. } => (*hits, "First Aid rating", *rating),
+            Healing::Magic { hits, force } => (*hits, "Force", *force),
+            Healing::Rest { days } =>
+            {
+                if *days == 0 || *days > MAX_REST_DAYS
+                {
+                    problems.push(String::from(format!("Rest must last between 1 and {} days; got {}.", MAX_REST_DAYS, days)));
+                }
+                return problems;
+            },
+        };
+
+        if hits > MAX_HEALING_HITS
+        {
+            problems.push(String::from(format!("Hits must be at most {}; got {}.", MAX_HEALING_HITS, hits)));
+        }
+
+        if rating == 0 || rating > MAX_HEALING_RATING
+        {
+            problems.push(String::from(format!("{} must be between 1 and {}; got {}.", field, MAX_HEALING_RATING, rating)));
+        }
+
+        problems
+    }
+}
+
+impl Validate for NewState
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+
+        if let State::Combat(combat) = &self.to_state
+        {
+            if combat.participants.is_empty()
+            {
+                problems.push(String::from("Combat needs at least one participant."));
+            }
+            else if combat.participants.len() > MAX_COMBATANTS
+            {
+                problems.push(String::from(format!("Combat is limited to {} participants.", MAX_COMBATANTS)));
+            }
+
+            let mut seen = HashSet::new();
+            for participant in &combat.participants
+            {
+                check_id("participants", participant, &mut problems);
+                if !seen.insert(participant)
+                {
+                    problems.push(String::from(format!("Participant {} is listed more than once.", participant)));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+impl Validate for Credentials
+{
+    fn problems(&self) -> Vec<String>
+    {
+        let mut problems = Vec::new();
+        let username = self.username.trim();
+
+        if username.is_empty() || username.chars().count() > MAX_USERNAME_LENGTH
+        {
+            problems.push(String::from(format!("Usernames must be between 1 and {} characters long.", MAX_USERNAME_LENGTH)));
+        }
+        else if !username.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        {
+            problems.push(String::from("Usernames may only contain letters, digits, '_', '-' and '.'."));
+        }
+
+        if self.password.chars().count() > MAX_PASSWORD_LENGTH
+        {
+            problems.push(String::from(format!("Passwords must be at most {} characters long.", MAX_PASSWORD_LENGTH)));
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::http::serde::{Damage, DamageKind, Healing};
+
+    use super::{Validate, MAX_DAMAGE_BOXES, MAX_REST_DAYS};
+
+    fn damage(amount: u8, ap: Option<i8>) -> Damage
+    {
+        Damage { damage_type: DamageKind::Physical, amount, ap, armor: None }
+    }
+
+    #[test]
+    pub fn damage_is_refused_past_any_condition_monitor_or_with_armor_penetration_out_of_reason()
+    {
+        assert!(damage(1, None).problems().is_empty());
+        assert!(damage(MAX_DAMAGE_BOXES, Some(-20)).problems().is_empty());
+        assert!(damage(6, Some(20)).problems().is_empty());
+
+        assert_eq!(1, damage(0, None).problems().len());
+        assert_eq!(1, damage(200, None).problems().len());
+        assert_eq!(1, damage(6, Some(i8::MAX)).problems().len());
+        assert_eq!(2, damage(u8::MAX, Some(i8::MIN)).problems().len());
+    }
+
+    #[test]
+    pub fn healing_is_refused_with_no_rating_to_cap_it_or_more_hits_and_days_than_anyone_gets()
+    {
+        assert!(Healing::FirstAid { hits: 0, rating: 3, damage_type: DamageKind::Stun }.problems().is_empty());
+        assert!(Healing::Magic { hits: 4, force: 6 }.problems().is_empty());
+        assert!(Healing::Rest { days: MAX_REST_DAYS }.problems().is_empty());
+
+        assert_eq!(1, Healing::FirstAid { hits: 3, rating: 0, damage_type: DamageKind::Physical }.problems().len());
+        assert_eq!(2, Healing::Magic { hits: u8::MAX, force: u8::MAX }.problems().len());
+        assert_eq!(1, Healing::Rest { days: 0 }.problems().len());
+        assert_eq!(1, Healing::Rest { days: u16::MAX }.problems().len());
+    }
+}
@@ -0,0 +1,6 @@
+// The server binary, the load-test binary and the benchmarks all build on the same modules.
+pub mod tracker;
+pub mod http;
+pub mod gamerunner;
+#[cfg(feature = "client")]
+pub mod client;
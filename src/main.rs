@@ -1,25 +1,38 @@
 
-use log::{debug, error};
-use rocket::fs::{FileServer, relative};
-use rocket::routes;
+use std::time::Duration;
+
+use tracing::{debug, error};
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+use rocket::{routes, catchers, fairing::AdHoc};
 use rocket_dyn_templates::Template;
+use rocket_dyn_templates::handlebars::{Helper, Handlebars, Context, RenderContext, Output, HelperResult};
 use tokio::sync::mpsc;
 
-pub mod tracker;
-pub mod http;
-pub mod gamerunner;
-
-use crate::gamerunner::dispatcher::Message;
-use crate::http::metagame::Metagame;
-use crate::http::server::{new_game, get_example_char, add_new_character, change_game_state, get_state_demo};
-use crate::http::renders::{index, create_game, game_view, no_session, new_session, add_npc, add_pc};
-use crate::http::messaging::start_message_stream;
-use crate::http::session::SessionMap;
+use shadowrun::gamerunner::dispatcher::Message;
+use shadowrun::gamerunner::hooks::HookChain;
+use shadowrun::gamerunner::snapshot::QuerySnapshots;
+use shadowrun::gamerunner::{RunnerState, SharedRunner, run_shared, run_review_clock};
+use shadowrun::http::metagame::Metagame;
+use shadowrun::http::server::{new_game, list_games, turn_state, cast_list, overlay, resume_session, register_account, login, logout, delete_account, delete_game, archive_game, queue_stats, combat_report, round_timeline, export_journal, poll_events, get_example_char, add_new_character, add_initiative_rolls, damage_character, heal_character, change_game_state, get_state_demo};
+use shadowrun::http::renders::{index, create_game, lobby, join_game, overlay_page, game_view, no_session, new_session, add_npc, add_pc};
+use shadowrun::http::messaging::{start_message_stream, command_socket};
+use shadowrun::http::session::{SessionMap, SessionConfig, session_expired};
+use shadowrun::http::accounts::{AccountStore, AccountConfig};
+use shadowrun::http::proxy::ProxyConfig;
+use shadowrun::http::cors::{Cors, CorsConfig, preflight};
+use shadowrun::http::queue::{QueueConfig, RunnerPipe};
+use shadowrun::http::assets::{AssetConfig, static_files};
+use shadowrun::http::listen::{ListenConfig, RelayedPeers, relay, upstream};
+use shadowrun::http::startup::{StartupConfig, StartupError, bind_to, log_bound_address};
 
 #[rocket::main]
-async fn main() {
-    // Get logging enabled.
-    env_logger::init();
+async fn main() -> Result<(), StartupError> {
+    // Get logging enabled.  RUST_LOG filters as before, and can now narrow by span field too (e.g. RUST_LOG='[dispatch{game_id}]=debug').
+    // Closing each span logs how long it was busy, which is where the runner loop's time goes.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
     
     debug!("Beginning launch of Shadowrun Combat Manager");
     if let Ok(home_dir) = std::env::current_dir()
@@ -34,26 +47,231 @@ async fn main() {
         }
     }
 
-    let (runner_sender, runner_receiver) = mpsc::channel::<Message>(10);
+    // Pull the proxy settings out of the same figment Rocket is configured from, so one Rocket.toml covers everything.
+    let rocket = rocket::build();
+    let queue = match rocket.figment().extract::<QueueConfig>()
+    {
+        Ok(config) => config.normalized(),
+        Err(err) =>
+        {
+            error!("Queue settings in Rocket.toml could not be read ({}); using the default capacities and waiting when the queue is full.", err);
+            QueueConfig::new()
+        }
+    };
+
+    let (runner_sender, runner_receiver) = mpsc::channel::<Message>(queue.runner_queue_capacity);
 
     // let (mut main_sender, mut main_receiver) = mpsc::channel::<MainMessages>(2);
 
     // tokio::spawn(async move {launch_server(main_sender.clone()).await;});
-    tokio::spawn(async move {gamerunner::game_runner(runner_receiver).await;});
+    let snapshots = QuerySnapshots::default();
+    tokio::spawn(run_review_clock(runner_sender.clone(), queue.review_interval()));
+    let runner_pipe = RunnerPipe::new(runner_sender, queue.queue_overflow);
+    let runner_state = RunnerState::new(runner_receiver, HookChain::new(), queue.player_channel_capacity);
+    tokio::spawn(supervise_runner(runner_state, snapshots.clone(), runner_pipe.clone()));
 
-    let session_map = SessionMap::new();
-    let game_state = Metagame::new(runner_sender);
+    let session_config = match rocket.figment().extract::<SessionConfig>()
+    {
+        Ok(config) => config.normalized(),
+        Err(err) =>
+        {
+            error!("Session settings in Rocket.toml could not be read ({}); sessions last a day without use.", err);
+            SessionConfig::new()
+        }
+    };
+    let session_map = match SessionMap::with_store(session_config.store_path())
+    {
+        Ok(sessions) => sessions.with_ttl(session_config.ttl()),
+        Err(err) =>
+        {
+            error!("The server cannot start: {}.", err);
+            return Err(StartupError::Store(err));
+        }
+    };
+    tokio::spawn(session_map.clone().run_sweeper(session_config.sweep_interval()));
+    tokio::spawn(session_map.clone().run_saver());
+    let account_config = match rocket.figment().extract::<AccountConfig>()
+    {
+        Ok(config) => config,
+        Err(err) =>
+        {
+            error!("Account settings in Rocket.toml could not be read ({}); keeping accounts in accounts.json.", err);
+            AccountConfig::new()
+        }
+    };
+    let accounts = match AccountStore::with_store(account_config.store_path())
+    {
+        Ok(accounts) => accounts,
+        Err(err) =>
+        {
+            error!("The server cannot start: {}.", err);
+            return Err(StartupError::Store(err));
+        }
+    };
+    let game_state = Metagame::new(runner_pipe);
+    let proxy = match rocket.figment().extract::<ProxyConfig>()
+    {
+        Ok(config) => config.normalized(),
+        Err(err) => 
+        {
+            error!("Proxy settings in Rocket.toml could not be read ({}); serving from the root without trusting forwarded headers.", err);
+            ProxyConfig::new()
+        }
+    };
+    let cors = match rocket.figment().extract::<CorsConfig>()
+    {
+        Ok(config) => config.normalized(),
+        Err(err) => 
+        {
+            error!("CORS settings in Rocket.toml could not be read ({}); no cross-origin access will be allowed.", err);
+            CorsConfig::new()
+        }
+    };
+    let assets = match rocket.figment().extract::<AssetConfig>()
+    {
+        Ok(config) => config,
+        Err(err) =>
+        {
+            error!("Asset settings in Rocket.toml could not be read ({}); serving the bundled static files without watching them.", err);
+            AssetConfig::new()
+        }
+    };
+    let listen = match rocket.figment().extract::<ListenConfig>()
+    {
+        Ok(config) => config,
+        Err(err) =>
+        {
+            error!("Listener settings in Rocket.toml could not be read ({}); listening only on Rocket's own address and port.", err);
+            ListenConfig::new()
+        }
+    };
+    let startup = match rocket.figment().extract::<StartupConfig>()
+    {
+        Ok(config) => config,
+        Err(err) =>
+        {
+            error!("Startup settings in Rocket.toml could not be read ({}); giving up at once if the port is taken.", err);
+            StartupConfig::new()
+        }
+    };
+
+    // Settle on a port before the relays are pointed at it; a server that cannot get one says why and exits non-zero.
+    let rocket_config = rocket.figment().extract::<rocket::Config>().unwrap_or_default();
+    let port = match startup.choose_port(rocket_config.address, rocket_config.port).await
+    {
+        Ok(port) => port,
+        Err(err) =>
+        {
+            error!("The server cannot start: {}.", err);
+            return Err(err);
+        }
+    };
+    let rocket = bind_to(rocket, port);
+    let relayed_peers = RelayedPeers::default();
+    for listener in listen.parsed()
+    {
+        let proxied = listen.is_proxied(&listener);
+        tokio::spawn(relay(listener, upstream(rocket_config.address, port), relayed_peers.clone(), proxied));
+    }
+    let base_path = proxy.base_path.clone();
+
+    // A single-binary build carries its own templates; Rocket is pointed at them before it goes looking for the configured directory.
+    // The unpacked directory is deleted when it is dropped, so like the watcher below it is held here until launch returns.
+    #[cfg(feature = "embed-assets")]
+    let (rocket, _unpacked_templates) = match shadowrun::http::assets::unpack_templates(&assets)
+    {
+        Ok(template_dir) =>
+        {
+            let figment = rocket.figment().clone().merge(("template_dir", template_dir.path()));
+            (rocket.configure(figment), Some(template_dir))
+        },
+        Err(err) =>
+        {
+            error!("The bundled templates could not be unpacked ({}); falling back to {}.", err, assets.template_dir.display());
+            (rocket, None)
+        }
+    };
+    #[cfg(feature = "embed-assets")]
+    let rocket = rocket.mount(proxy.mount_point("/res").as_str(), shadowrun::http::assets::BundledFiles);
+
+    // The watcher has to outlive the server, so it is held here until launch returns.
+    #[cfg(feature = "dev-reload")]
+    let (rocket, _asset_watcher) = shadowrun::http::assets::watch_assets(rocket, &assets, proxy.mount_point("/dev").as_str());
+    #[cfg(feature = "dev-reload")]
+    let reload_script = if _asset_watcher.is_some() { AssetConfig::reload_script(&proxy.link("/dev/reload")) } else { String::new() };
+    #[cfg(not(feature = "dev-reload"))]
+    let reload_script = String::new();
+    #[cfg(not(feature = "dev-reload"))]
+    if assets.dev_reload
+    {
+        tracing::warn!("dev_reload is set, but this build was made without the dev-reload feature; assets will not be watched.");
+    }
 
-    let _ = rocket::build()
+    rocket
         .manage(game_state)
+        .manage(snapshots)
         .manage(session_map)
-        .mount("/res", FileServer::from(relative!("resources/static")))
-        .mount("/api", routes![new_game, get_example_char, add_new_character, change_game_state, get_state_demo])
-        .mount("/messages", routes![start_message_stream])
-        .mount("/", routes![index, create_game, game_view, no_session, new_session, add_npc, add_pc])
-        .attach(Template::fairing())
+        .manage(accounts)
+        .manage(proxy.clone())
+        .manage(relayed_peers)
+        .manage(cors.clone())
+        .mount(proxy.mount_point("/res").as_str(), static_files(&assets))
+        .mount(proxy.mount_point("/api").as_str(), routes![preflight, new_game, list_games, turn_state, cast_list, overlay, resume_session, register_account, login, logout, delete_account, delete_game, archive_game, queue_stats, combat_report, round_timeline, export_journal, poll_events, get_example_char, add_new_character, add_initiative_rolls, damage_character, heal_character, change_game_state, get_state_demo])
+        .mount(proxy.mount_point("/messages").as_str(), routes![start_message_stream, command_socket])
+        .mount(proxy.mount_point("/").as_str(), routes![index, create_game, lobby, join_game, overlay_page, game_view, no_session, new_session, add_npc, add_pc])
+        .register(proxy.mount_point("/").as_str(), catchers![session_expired])
+        .attach(Cors::new(cors, proxy.mount_point("/api")))
+        // Templates write their links as {{base}}/path so they keep working when mounted under a base path.
+        .attach(Template::custom(move |engines| {
+            let base_path = base_path.clone();
+            engines.handlebars.register_helper("base", Box::new(move |_: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                out.write(&base_path)?;
+                Ok(())
+            }));
+            let reload_script = reload_script.clone();
+            engines.handlebars.register_helper("dev_reload", Box::new(move |_: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                out.write(&reload_script)?;
+                Ok(())
+            }));
+        }))
+        .attach(AdHoc::on_liftoff("Bound address", |rocket| Box::pin(async move { log_bound_address(rocket); })))
         .launch()
-        .await;
+        .await
+        .map(|_| ())
+        .map_err(|err|
+        {
+            error!("The server stopped with an error: {}.", err);
+            StartupError::Launch(err.to_string())
+        })
+}
+
+// How long a panicked runner is left down before it is restarted, so one that panics on every message does not spin.
+const RUNNER_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+// Keeps the game runner going.  A runner that panics is restarted on the same queue and registry it left behind, with the game it was
+// handling put back as it was before the message that panicked, and the web side answers 503 until it is back.
+async fn supervise_runner(state: SharedRunner, snapshots: QuerySnapshots, pipe: RunnerPipe)
+{
+    loop
+    {
+        let runner = tokio::spawn(run_shared(state.clone(), snapshots.clone()));
+        pipe.set_recovering(false);
+
+        match runner.await
+        {
+            Ok(()) =>
+            {
+                debug!("The game runner's queue has closed; no longer supervising it.");
+                return;
+            },
+            Err(err) =>
+            {
+                pipe.set_recovering(true);
+                error!("The game runner failed ({}); restarting it in {:?}.", err, RUNNER_RESTART_DELAY);
+                tokio::time::sleep(RUNNER_RESTART_DELAY).await;
+            },
+        }
+    }
 }
 
 #[derive(PartialEq)]
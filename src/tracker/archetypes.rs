@@ -0,0 +1,149 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{character::{Character, Metatypes, Skill, Modifier, ModifierSource, ModifierTarget}, gear::{Weapon, FiringFeature, Armour, ReloadMethod, DamageType}};
+
+// Ready-made sheets for session zero: a player picks one, names it and is at the table with something playable, to be filled out later
+// with UpdateCharacter.  The numbers are the core book's sample characters rounded off, not an attempt at a legal build.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Archetype
+{
+    StreetSamurai,
+    Decker,
+    Mage,
+    Rigger,
+}
+
+impl Archetype
+{
+    pub const ALL: [Archetype; 4] = [Archetype::StreetSamurai, Archetype::Decker, Archetype::Mage, Archetype::Rigger];
+
+    pub fn label(&self) -> &'static str
+    {
+        match self
+        {
+            Archetype::StreetSamurai => "Street Samurai",
+            Archetype::Decker => "Decker",
+            Archetype::Mage => "Mage",
+            Archetype::Rigger => "Rigger",
+        }
+    }
+
+    pub fn build(&self, name: String) -> Character
+    {
+        // Body, Agility, Reaction, Strength, Charisma, Intuition, Logic, Willpower, Edge, Essence, Magic.
+        let (metatype, stats, skills, weapons, armor, modifiers) = match self
+        {
+            Archetype::StreetSamurai => (Metatypes::Orc, [6, 5, 5, 5, 2, 3, 2, 3, 2, 2, 0],
+                vec![("Pistols", "Agility", 5), ("Automatics", "Agility", 4), ("Blades", "Agility", 5), ("Dodge", "Reaction", 3), ("Perception", "Intuition", 3)],
+                vec![heavy_pistol(), katana()], armor_jacket(),
+                vec![augmentation("Wired Reflexes 2", ModifierTarget::InitiativePasses, 2)]),
+            Archetype::Decker => (Metatypes::Human, [3, 3, 4, 2, 3, 5, 6, 4, 3, 5, 0],
+                vec![("Hacking", "Logic", 5), ("Cybercombat", "Logic", 5), ("Computer", "Logic", 4), ("Electronic Warfare", "Logic", 3), ("Pistols", "Agility", 2)],
+                vec![light_pistol()], lined_coat(),
+                vec![augmentation("Hot Sim", ModifierTarget::MatrixPasses, 1)]),
+            Archetype::Mage => (Metatypes::Elf, [3, 3, 3, 2, 5, 4, 4, 5, 3, 6, 5],
+                vec![("Spellcasting", "Magic", 5), ("Counterspelling", "Magic", 4), ("Summoning", "Magic", 4), ("Assensing", "Intuition", 3), ("Pistols", "Agility", 2)],
+                vec![light_pistol()], lined_coat(), Vec::new()),
+            Archetype::Rigger => (Metatypes::Dwarf, [5, 3, 4, 3, 2, 4, 5, 4, 3, 4, 0],
+                vec![("Pilot Ground Craft", "Reaction", 5), ("Gunnery", "Agility", 4), ("Automatics", "Agility", 3), ("Electronics", "Logic", 3), ("Perception", "Intuition", 3)],
+                vec![smg()], armor_jacket(),
+                vec![augmentation("Control Rig", ModifierTarget::MatrixPasses, 1)]),
+        };
+
+        let mut character = Character::new_pc(metatype, name);
+        character.stats = Arc::new(["Body", "Agility", "Reaction", "Strength", "Charisma", "Intuition", "Logic", "Willpower", "Edge", "Essence", "Magic"]
+            .iter().zip(stats).filter(|(_, value)| *value != 0).map(|(stat, value)| (String::from(*stat), value)).collect::<HashMap<String, i8>>());
+        character.skills = Arc::new(skills.into_iter().map(|(skill, stat, rating)| Skill { name: String::from(skill), subtype: None, stat: String::from(stat),
+            specialized: false, specialization_type: String::new(), rating }).collect());
+        character.weapons = Arc::new(weapons);
+        character.armor = Arc::new(vec![armor]);
+        character.modifiers = Arc::new(modifiers);
+        // 8 boxes on each track, plus half of Body (physical) or Willpower (stun), rounded up.
+        character.physical_track_max = 8 + (character.stat("Body") + 1) / 2;
+        character.stun_track_max = 8 + (character.stat("Willpower") + 1) / 2;
+
+        character
+    }
+}
+
+fn augmentation(name: &str, target: ModifierTarget, value: i8) -> Modifier
+{
+    Modifier { name: String::from(name), source: ModifierSource::Augmentation, target, value }
+}
+
+fn firearm(weapon_type: &str, weapon_name: &str, skill: &str, feature: FiringFeature) -> Weapon
+{
+    Weapon { weapon_type: String::from(weapon_type), weapon_name: String::from(weapon_name), assoc_skill: String::from(skill), firing_features: vec![feature],
+        current_feature: 0, reach: None, electric: false }
+}
+
+fn clip_fed(damage: &str, armor_pen: i8, reload_size: i8, fire_modes: &[&str], recoil_comp: i8) -> FiringFeature
+{
+    FiringFeature { feature_name: String::from("Standard"), reloads: ReloadMethod::Clip, reload_size, armor_pen, damage_type: DamageType::Physical,
+        damage_equation: String::from(damage), requires_reconfig: false, fire_modes: fire_modes.iter().map(|mode| String::from(*mode)).collect(),
+        recoil_comp, alt_recoil_comp: recoil_comp, current_fire_mode: 0, loaded: reload_size }
+}
+
+fn heavy_pistol() -> Weapon
+{
+    firearm("Heavy Pistol", "Ares Predator IV", "Pistols", clip_fed("5P", -1, 15, &["SA"], 0))
+}
+
+fn light_pistol() -> Weapon
+{
+    firearm("Light Pistol", "Fichetti Security 600", "Pistols", clip_fed("4P", 0, 30, &["SA"], 1))
+}
+
+fn smg() -> Weapon
+{
+    firearm("Submachine Gun", "Ingram Smartgun X", "Automatics", clip_fed("5P", 0, 32, &["BF", "FA"], 2))
+}
+
+fn katana() -> Weapon
+{
+    Weapon { weapon_type: String::from("Blade"), weapon_name: String::from("Katana"), assoc_skill: String::from("Blades"), firing_features: Vec::new(),
+        current_feature: 0, reach: Some(1), electric: false }
+}
+
+fn armor_jacket() -> Armour
+{
+    Armour { name: String::from("Armor Jacket"), ballistic_rating: 8, impact_rating: 6, degradation: 0 }
+}
+
+fn lined_coat() -> Armour
+{
+    Armour { name: String::from("Lined Coat"), ballistic_rating: 6, impact_rating: 4, degradation: 0 }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tracker::{character::ModifierTarget, gear::ArmorTestType};
+
+    use super::Archetype;
+
+    #[test]
+    pub fn every_archetype_comes_out_ready_to_fight()
+    {
+        for archetype in Archetype::ALL
+        {
+            let character = archetype.build(String::from(archetype.label()));
+
+            assert!(character.player_character);
+            assert_eq!(archetype.label(), &*character.name);
+            assert!(character.stat("Reaction") > 0 && character.stat("Intuition") > 0);
+            assert!(!character.weapons.is_empty() && character.armor_rating(ArmorTestType::Ballistic) > 0);
+            assert!(character.physical_track_max > 8 && character.stun_track_max > 8);
+        }
+    }
+
+    #[test]
+    pub fn each_archetype_carries_its_own_edge()
+    {
+        assert_eq!(2, Archetype::StreetSamurai.build(String::from("Sam")).extra_initiative_passes());
+        assert_eq!(1, Archetype::Decker.build(String::from("Deck")).modifier_total(ModifierTarget::MatrixPasses));
+        assert_eq!(5, Archetype::Mage.build(String::from("Mage")).stat("Magic"));
+        assert_eq!(5, Archetype::Rigger.build(String::from("Rig")).skill("Pilot Ground Craft"));
+        assert_eq!(0, Archetype::Rigger.build(String::from("Rig")).stat("Magic"));
+    }
+}
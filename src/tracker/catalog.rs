@@ -0,0 +1,120 @@
+use super::game::ActionType;
+
+// The table's own actions - "Reload", "Suppressive fire" - each costing one of the edition's action types.  A character taking one spends
+// that cost as usual; the name is what the journal and the GM's notifications show.  Each game keeps its own catalog.
+
+// What an action does to the ammunition in the character's readied weapon.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Ammunition
+{
+    Untouched,
+    // An attack firing this many rounds.  The last rounds in the gun can still be fired as a short burst; an empty gun cannot fire at all.
+    Fires(i8),
+    Reloads,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CatalogAction
+{
+    pub name: String,
+    pub cost: ActionType,
+    pub description: String,
+    pub ammunition: Ammunition,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActionCatalog
+{
+    actions: Vec<CatalogAction>,
+}
+
+impl ActionCatalog
+{
+    pub fn new() -> ActionCatalog
+    {
+        ActionCatalog { actions: Vec::new() }
+    }
+
+    // Defining an action under a name already in use replaces it.  Names are matched without regard to case, so "reload" and "Reload"
+    // are the same action.
+    pub fn define(&mut self, action: CatalogAction) -> Result<(), String>
+    {
+        if action.name.trim().is_empty()
+        {
+            return Err(String::from("A cataloged action needs a name."));
+        }
+        if let Ammunition::Fires(rounds) = action.ammunition
+        {
+            if rounds <= 0
+            {
+                return Err(format!("An attack has to fire at least one round, not {}.", rounds));
+            }
+        }
+
+        self.actions.retain(|existing| !existing.name.eq_ignore_ascii_case(&action.name));
+        self.actions.push(action);
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool
+    {
+        let before = self.actions.len();
+        self.actions.retain(|existing| !existing.name.eq_ignore_ascii_case(name));
+
+        self.actions.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CatalogAction>
+    {
+        self.actions.iter().find(|existing| existing.name.eq_ignore_ascii_case(name))
+    }
+
+    // In the order they were defined.
+    pub fn list(&self) -> Vec<CatalogAction>
+    {
+        self.actions.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tracker::game::ActionType;
+
+    use super::{ActionCatalog, Ammunition, CatalogAction};
+
+    fn reload() -> CatalogAction
+    {
+        CatalogAction { name: String::from("Reload"), cost: ActionType::Simple, description: String::from("Swap in a fresh clip."),
+            ammunition: Ammunition::Reloads }
+    }
+
+    #[test]
+    pub fn actions_are_found_and_replaced_by_name_without_regard_to_case()
+    {
+        let mut catalog = ActionCatalog::new();
+        assert!(catalog.define(reload()).is_ok());
+        assert!(catalog.define(CatalogAction { name: String::from("  "), ..reload() }).is_err());
+
+        let slow_reload = CatalogAction { name: String::from("RELOAD"), cost: ActionType::Complex, ..reload() };
+        assert!(catalog.define(slow_reload.clone()).is_ok());
+        assert_eq!(vec![slow_reload.clone()], catalog.list());
+        assert_eq!(Some(&slow_reload), catalog.get("reload"));
+
+        assert!(!catalog.remove("Suppressive fire"));
+        assert!(catalog.remove("Reload"));
+        assert!(catalog.get("reload").is_none());
+    }
+
+    #[test]
+    pub fn an_attack_firing_no_rounds_or_fewer_is_refused()
+    {
+        let mut catalog = ActionCatalog::new();
+        for rounds in [0, -100, i8::MIN]
+        {
+            assert!(catalog.define(CatalogAction { name: String::from("Burst"), ammunition: Ammunition::Fires(rounds), ..reload() }).is_err());
+        }
+        assert!(catalog.define(CatalogAction { name: String::from("Burst"), ammunition: Ammunition::Fires(3), ..reload() }).is_ok());
+    }
+}
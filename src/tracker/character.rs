@@ -1,26 +1,35 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::{HashMap, HashSet}, sync::Arc};
 
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
+use rand::Rng;
 
-use super::gear::{Weapon, Armour};
+use super::{gear::{Weapon, Armour, ArmorTestType}, magic::Plane, names::Name, rules, dice};
 
+// The bulky parts of the sheet sit behind their own Arcs, so copying a Character - which Arc::make_mut does whenever a snapshot or a
+// cast-list reply still holds the old one - only bumps reference counts.  Marking damage copies the handful of scalars; degrading armor
+// copies the armor list and nothing else.  Mutate a shared part through Arc::make_mut on that field.
 pub struct Character
 {
-    pub name: String,
+    pub name: Name,
     pub id: Uuid,
     pub player_character: bool,
     pub metatype: Metatypes,
-    pub stats: HashMap<String, i8>,
-    pub qualities: HashSet<Quality>,
-    pub skills: Vec<Skill>,
-    pub weapons: Vec<Weapon>,
-    pub armor: Vec<Armour>,
+    pub stats: Arc<HashMap<String, i8>>,
+    pub qualities: Arc<HashSet<Quality>>,
+    pub skills: Arc<Vec<Skill>>,
+    pub weapons: Arc<Vec<Weapon>>,
+    pub armor: Arc<Vec<Armour>>,
     pub physical_track_max: i8, // Total player health
     pub physical_track_filled: i8, // current damage
     pub stun_track_max: i8,
     pub stun_track_filled: i8,
     pub current_weapon_index: usize,
+    pub modifiers: Arc<Vec<Modifier>>,
+    pub roll_macros: Arc<Vec<RollMacro>>,
+    pub plane: Plane,
+    // Set when the sheet spells out its initiative; otherwise the edition works it out from the stats.
+    pub initiative: Option<InitiativeFormula>,
 }
 
 impl Character 
@@ -28,42 +37,172 @@ impl Character
     pub fn new_pc(metatype: Metatypes, name: String) -> Character
     {
         Character {
-            name,
+            name: Name::from(name),
             id: Uuid::new_v4(),
             player_character: true,
             metatype,
-            stats: HashMap::new(),
-            qualities: HashSet::new(),
-            skills: Vec::new(),
-            weapons: Vec::new(),
-            armor: Vec::new(),
+            stats: Arc::new(HashMap::new()),
+            qualities: Arc::new(HashSet::new()),
+            skills: Arc::new(Vec::new()),
+            weapons: Arc::new(Vec::new()),
+            armor: Arc::new(Vec::new()),
             physical_track_max: 0,
             physical_track_filled: 0,
             stun_track_max: 0,
             stun_track_filled: 0,
             current_weapon_index: 0,
+            modifiers: Arc::new(Vec::new()),
+            roll_macros: Arc::new(Vec::new()),
+            plane: Plane::Physical,
+            initiative: None,
         }
     }
 
     pub fn new_npc(metatype: Metatypes, name: String) -> Character
     {
         Character {
-            name,
+            name: Name::from(name),
             id: Uuid::new_v4(),
             player_character: false,
             metatype,
-            stats: HashMap::new(),
-            qualities: HashSet::new(),
-            skills: Vec::new(),
-            weapons: Vec::new(),
-            armor: Vec::new(),
+            stats: Arc::new(HashMap::new()),
+            qualities: Arc::new(HashSet::new()),
+            skills: Arc::new(Vec::new()),
+            weapons: Arc::new(Vec::new()),
+            armor: Arc::new(Vec::new()),
             physical_track_max: 0,
             physical_track_filled: 0,
             stun_track_max: 0,
             stun_track_filled: 0,
             current_weapon_index: 0,
+            modifiers: Arc::new(Vec::new()),
+            roll_macros: Arc::new(Vec::new()),
+            plane: Plane::Physical,
+            initiative: None,
         }
     }
+
+    // Reach of whatever the character currently has in hand, plus the innate reach trolls get for being enormous.
+    pub fn reach(&self) -> i8
+    {
+        let weapon_reach = self.weapons.get(self.current_weapon_index).and_then(|weapon| weapon.reach).unwrap_or(0);
+
+        let reach = match self.metatype
+        {
+            Metatypes::Troll => weapon_reach + 1,
+            _ => weapon_reach,
+        };
+
+        reach + self.modifier_total(ModifierTarget::Reach)
+    }
+
+    // Sum of every quality, power and augmentation modifier the character carries for the given stat.
+    pub fn modifier_total(&self, target: ModifierTarget) -> i8
+    {
+        self.modifiers.iter().filter(|modifier| modifier.target == target).fold(0, |total: i8, modifier| total.saturating_add(modifier.value))
+    }
+
+    pub fn stat(&self, name: &str) -> i8
+    {
+        self.stats.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn condition_monitor(&self) -> ConditionMonitor
+    {
+        ConditionMonitor
+        {
+            physical_filled: self.physical_track_filled,
+            physical_max: self.physical_track_max,
+            stun_filled: self.stun_track_filled,
+            stun_max: self.stun_track_max,
+        }
+    }
+
+    // The best rating the character has in the skill, or 0 if they do not have it.
+    pub fn skill(&self, name: &str) -> i8
+    {
+        self.skills.iter().filter(|skill| skill.name == name).map(|skill| skill.rating).max().unwrap_or(0)
+    }
+
+    // Best worn piece for the test type, plus anything the character's powers or 'ware add on top.
+    pub fn armor_rating(&self, test: ArmorTestType) -> i8
+    {
+        let worn = self.armor.iter().map(|armour| armour.rating(test)).max().unwrap_or(0);
+
+        worn.saturating_add(self.modifier_total(ModifierTarget::Armor))
+    }
+
+    // Body plus whatever armor is left after the attack's armor penetration - or none at all when a called shot found a gap.
+    pub fn soak_pool(&self, test: ArmorTestType, armor_pen: i8, bypass_armor: bool) -> u8
+    {
+        let armor = if bypass_armor { 0 } else { self.armor_rating(test).saturating_add(armor_pen).max(0) };
+
+        rules::pool(&[self.stat("Body"), armor])
+    }
+
+    // Extra initiative passes granted by wired reflexes, synaptic boosters, adept reflexes and the like.
+    pub fn extra_initiative_passes(&self) -> usize
+    {
+        self.modifier_total(ModifierTarget::InitiativePasses).max(0) as usize
+    }
+
+    pub fn initiative_dice(&self) -> u8
+    {
+        (1 + self.modifier_total(ModifierTarget::InitiativeDice)).max(1) as u8
+    }
+
+    pub fn roll_macro(&self, name: &str) -> Option<&RollMacro>
+    {
+        self.roll_macros.iter().find(|roll_macro| roll_macro.name == name)
+    }
+}
+
+// The boxes on a character's physical and stun tracks, filled and available.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ConditionMonitor
+{
+    pub physical_filled: i8,
+    pub physical_max: i8,
+    pub stun_filled: i8,
+    pub stun_max: i8,
+}
+
+impl ConditionMonitor
+{
+    // How badly hurt the character looks from across the table, judged by whichever track is the worse off.
+    pub fn tier(&self) -> WoundTier
+    {
+        let track = |filled: i8, max: i8| {
+            let (filled, max) = (filled.max(0) as i16, max as i16);
+            if filled == 0 { WoundTier::Unhurt }
+            // Without a track length there is no telling how bad it is, only that they are hurt.
+            else if max <= 0 { WoundTier::LightlyHurt }
+            else if filled >= max { WoundTier::Down }
+            else if filled * 3 < max { WoundTier::LightlyHurt }
+            else if filled * 3 < max * 2 { WoundTier::BadlyHurt }
+            else { WoundTier::Critical }
+        };
+
+        track(self.physical_filled, self.physical_max).max(track(self.stun_filled, self.stun_max))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum WoundTier
+{
+    Unhurt,
+    LightlyHurt,
+    BadlyHurt,
+    Critical,
+    Down,
+}
+
+// What someone at the table is told about a character's wounds.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WoundView
+{
+    Exact(ConditionMonitor),
+    Tier(WoundTier),
 }
 
 impl Clone for Character
@@ -84,12 +223,16 @@ impl Clone for Character
             self.physical_track_filled.clone(), 
             stun_track_max: self.stun_track_max.clone(), 
             stun_track_filled: self.stun_track_filled.clone(), 
-            current_weapon_index: self.current_weapon_index.clone() 
+            current_weapon_index: self.current_weapon_index.clone(),
+            modifiers: self.modifiers.clone(),
+            roll_macros: self.roll_macros.clone(),
+            plane: self.plane,
+            initiative: self.initiative,
         }
     }
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Metatypes
 {
     Human,
@@ -107,6 +250,114 @@ pub struct Quality
     pub skill_modifier: i8,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModifierSource
+{
+    Quality,
+    AdeptPower,
+    Augmentation,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModifierTarget
+{
+    InitiativeDice,
+    InitiativePasses,
+    AstralPasses,
+    MatrixPasses,
+    Reach,
+    Armor,
+    FreeActions,
+}
+
+// A structured bonus (or penalty) from something the character has - a quality, an adept power, or a piece of 'ware.  Game reads
+// these when it sets the character up for combat rather than assuming every character is the same.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Modifier
+{
+    pub name: String,
+    pub source: ModifierSource,
+    pub target: ModifierTarget,
+    pub value: i8,
+}
+
+// A named dice pool the player rolls often enough to want a shortcut for - "Ares Predator attack: 14d6".
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RollMacro
+{
+    pub name: String,
+    pub pool: u8,
+}
+
+impl RollMacro
+{
+    // Reads the "name: 14d6" form.  Everything in Shadowrun is rolled on d6s, so any other die size is refused, and the "d6" may be left off.
+    pub fn parse(definition: &str) -> Option<RollMacro>
+    {
+        let (name, dice) = definition.rsplit_once(':')?;
+        let (name, dice) = (name.trim(), dice.trim().to_lowercase());
+        let count = match dice.split_once('d')
+        {
+            Some((count, "6")) => count,
+            Some(_) => return None,
+            None => dice.as_str(),
+        };
+
+        match count.trim().parse::<u8>()
+        {
+            Ok(pool) if !name.is_empty() => Some(RollMacro { name: String::from(name), pool }),
+            _ => None,
+        }
+    }
+}
+
+// Initiative as the sheet writes it, "8 + 3d6": a fixed score plus the sum of some d6s.  Knowing the dice is what lets a roll typed in
+// by a player be checked - 8 + 3d6 can come to anything from 11 to 26 and nothing else.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InitiativeFormula
+{
+    pub base: i8,
+    pub dice: u8,
+}
+
+impl InitiativeFormula
+{
+    // Reads "8 + 3d6" or "3d6 + 8" (either part may be left off, but not both).  As with roll macros only d6s are accepted, and a formula
+    // whose best roll would not fit an initiative score is refused.
+    pub fn parse(formula: &str) -> Option<InitiativeFormula>
+    {
+        let (mut base, mut dice) = (None, None);
+        for term in formula.split('+').map(|term| term.trim().to_lowercase())
+        {
+            match term.split_once('d')
+            {
+                Some((count, "6")) if dice.is_none() => dice = Some(count.trim().parse::<u8>().ok().filter(|count| *count > 0)?),
+                None if base.is_none() => base = Some(term.parse::<i8>().ok().filter(|base| *base >= 0)?),
+                _ => return None,
+            }
+        }
+
+        let formula = InitiativeFormula { base: base.unwrap_or(0), dice: dice.unwrap_or(0) };
+        if (base.is_none() && dice.is_none()) || formula.base as i16 + formula.dice as i16 * 6 > i8::MAX as i16
+        {
+            return None;
+        }
+
+        Some(formula)
+    }
+
+    // Lowest and highest scores the formula can roll.
+    pub fn range(&self) -> (i8, i8)
+    {
+        (self.base + self.dice as i8, self.base + self.dice as i8 * 6)
+    }
+
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> i8
+    {
+        self.base + dice::sum(rng, self.dice) as i8
+    }
+}
+
 #[derive(Clone)]
 pub struct Skill
 {
@@ -116,4 +367,100 @@ pub struct Skill
     pub specialized: bool,
     pub specialization_type: String,
     pub rating: i8
-}
\ No newline at end of file
+}
+
+// An edit to a character sheet, applied by the server.  Only the fields that are set change; stats are merged into the sheet's rather
+// than replacing them.  The name and roll macros are bookkeeping and can change at any time - everything else feeds into the fight.
+#[derive(Clone, Default)]
+pub struct CharacterPatch
+{
+    pub name: Option<Name>,
+    pub roll_macros: Option<Vec<RollMacro>>,
+    pub metatype: Option<Metatypes>,
+    pub stats: HashMap<String, i8>,
+    pub skills: Option<Vec<Skill>>,
+    pub weapons: Option<Vec<Weapon>>,
+    pub armor: Option<Vec<Armour>>,
+    pub physical_track_max: Option<i8>,
+    pub stun_track_max: Option<i8>,
+    pub current_weapon_index: Option<usize>,
+    pub initiative: Option<InitiativeFormula>,
+}
+
+impl CharacterPatch
+{
+    pub fn affects_combat(&self) -> bool
+    {
+        self.metatype.is_some() || !self.stats.is_empty() || self.skills.is_some() || self.weapons.is_some() || self.armor.is_some()
+            || self.physical_track_max.is_some() || self.stun_track_max.is_some() || self.current_weapon_index.is_some() || self.initiative.is_some()
+    }
+
+    // The fields the patch would actually change on the sheet as it stands.  Anything set to the value it already has is left out.
+    pub fn changes(&self, current: &Character) -> Vec<&'static str>
+    {
+        let mut changed = Vec::new();
+
+        if self.name.as_ref().map_or(false, |name| *name != current.name) { changed.push("name"); }
+        if self.roll_macros.as_ref().map_or(false, |macros| *macros != *current.roll_macros) { changed.push("roll_macros"); }
+        if self.metatype.map_or(false, |metatype| metatype != current.metatype) { changed.push("metatype"); }
+        if self.stats.iter().any(|(stat, value)| current.stats.get(stat) != Some(value)) { changed.push("stats"); }
+        if self.skills.is_some() { changed.push("skills"); }
+        if self.weapons.is_some() { changed.push("weapons"); }
+        if self.armor.is_some() { changed.push("armor"); }
+        if self.physical_track_max.map_or(false, |max| max != current.physical_track_max) { changed.push("physical_track_max"); }
+        if self.stun_track_max.map_or(false, |max| max != current.stun_track_max) { changed.push("stun_track_max"); }
+        if self.current_weapon_index.map_or(false, |index| index != current.current_weapon_index) { changed.push("current_weapon_index"); }
+        if self.initiative.map_or(false, |formula| Some(formula) != current.initiative) { changed.push("initiative"); }
+
+        changed
+    }
+
+    // Checks the patch against the sheet it would be applied to, and returns the fields it changes.
+    pub fn validate(&self, current: &Character) -> Result<Vec<&'static str>, String>
+    {
+        if self.name.as_ref().map_or(false, |name| name.trim().is_empty())
+        {
+            return Err(String::from("A character's name cannot be blank."));
+        }
+
+        if let Some((stat, value)) = self.stats.iter().find(|(_, value)| **value < 0)
+        {
+            return Err(String::from(format!("{} cannot be set to {}.", stat, value)));
+        }
+
+        if self.physical_track_max.map_or(false, |max| max < 1) || self.stun_track_max.map_or(false, |max| max < 1)
+        {
+            return Err(String::from("A condition monitor needs at least one box."));
+        }
+
+        let weapon_count = self.weapons.as_ref().map_or(current.weapons.len(), |weapons| weapons.len());
+        let index = self.current_weapon_index.unwrap_or(current.current_weapon_index);
+        if self.current_weapon_index.is_some() && index >= weapon_count
+        {
+            return Err(String::from(format!("There is no weapon at index {}.", index)));
+        }
+
+        let changed = self.changes(current);
+        if changed.is_empty()
+        {
+            return Err(String::from("The patch does not change anything on the sheet."));
+        }
+
+        Ok(changed)
+    }
+
+    pub fn apply(&self, character: &mut Character)
+    {
+        if let Some(name) = &self.name { character.name = name.clone(); }
+        if let Some(macros) = &self.roll_macros { character.roll_macros = Arc::new(macros.clone()); }
+        if let Some(metatype) = self.metatype { character.metatype = metatype; }
+        if !self.stats.is_empty() { Arc::make_mut(&mut character.stats).extend(self.stats.iter().map(|(stat, value)| (stat.clone(), *value))); }
+        if let Some(skills) = &self.skills { character.skills = Arc::new(skills.clone()); }
+        if let Some(weapons) = &self.weapons { character.weapons = Arc::new(weapons.clone()); }
+        if let Some(armor) = &self.armor { character.armor = Arc::new(armor.clone()); }
+        if let Some(max) = self.physical_track_max { character.physical_track_max = max; }
+        if let Some(max) = self.stun_track_max { character.stun_track_max = max; }
+        if let Some(index) = self.current_weapon_index { character.current_weapon_index = index; }
+        if let Some(formula) = self.initiative { character.initiative = Some(formula); }
+    }
+}
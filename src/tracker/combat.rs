@@ -0,0 +1,245 @@
+use uuid::Uuid;
+
+use super::{gear::ArmorTestType, rules::{self, Edition}};
+
+// Dice pool modifiers for the attacker side of a ranged attack.  The dice may well be rolled physically at the table; the point here
+// is to take the modifier arithmetic off the GM's plate.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RangeBand
+{
+    Short,
+    Medium,
+    Long,
+    Extreme,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Lighting
+{
+    Full,
+    Glare,
+    Partial,
+    Darkness,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Cover
+{
+    None,
+    Partial,
+    Good,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FiringMode
+{
+    SingleShot,
+    SemiAuto,
+    Burst,
+    LongBurst,
+    FullAuto,
+}
+
+impl FiringMode
+{
+    pub fn rounds(&self) -> i8
+    {
+        match self
+        {
+            FiringMode::SingleShot | FiringMode::SemiAuto => 1,
+            FiringMode::Burst => 3,
+            FiringMode::LongBurst => 6,
+            FiringMode::FullAuto => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RangedAttack
+{
+    pub attacker: Uuid,
+    pub target: Option<Uuid>,
+    pub range: RangeBand,
+    pub lighting: Lighting,
+    pub cover: Cover,
+    pub firing_mode: FiringMode,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AttackModifiers
+{
+    pub range: i8,
+    pub lighting: i8,
+    pub cover: i8,
+    pub recoil: i8,
+    pub running: i8,
+    pub sustaining: i8,
+    pub total: i8,
+}
+
+impl AttackModifiers
+{
+    pub fn new(edition: Edition, attack: &RangedAttack, recoil_comp: i8, strength: i8, running: i8, sustaining: i8) -> AttackModifiers
+    {
+        let range = rules::range_modifier(edition, attack.range);
+        let lighting = rules::lighting_modifier(edition, attack.lighting);
+        let cover = rules::cover_modifier(edition, attack.cover);
+        let recoil = rules::recoil_modifier(edition, attack.firing_mode, recoil_comp, strength);
+
+        let total = [lighting, cover, recoil, running, sustaining].into_iter().fold(range, i8::saturating_add);
+
+        AttackModifiers { range, lighting, cover, recoil, running, sustaining, total }
+    }
+}
+
+// Two combatants locked in melee.  The reach differential is from the attacker's point of view - positive means the attacker has
+// the longer reach.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Engagement
+{
+    pub attacker: Uuid,
+    pub defender: Uuid,
+    pub reach_differential: i8,
+}
+
+// Grenades, rockets and the like.  The further the ordnance travels under its own power the more dice of scatter it takes; every hit
+// on the attack test pulls the landing point a meter back towards the target.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Ordnance
+{
+    Standard,
+    Aerodynamic,
+    Launched,
+    Missile,
+}
+
+impl Ordnance
+{
+    pub fn scatter_dice(&self) -> u8
+    {
+        match self
+        {
+            Ordnance::Standard => 1,
+            Ordnance::Aerodynamic => 2,
+            Ordnance::Launched => 3,
+            Ordnance::Missile => 4,
+        }
+    }
+}
+
+// The GM decides who is standing in the blast once the scatter is known, so the attack carries that list rather than the game trying
+// to work out positions it does not track.
+#[derive(Debug, Clone)]
+pub struct AreaAttack
+{
+    pub attacker: Uuid,
+    pub ordnance: Ordnance,
+    pub hits: u8,
+    pub armor_test: ArmorTestType,
+    pub armor_pen: i8,
+    pub in_blast: Vec<Uuid>,
+}
+
+// One burst of damage the GM lands on several characters at once - autofire into a crowd, a grenade whose scatter is already settled,
+// a wall coming down.  Nobody's action pays for it; the source is just what the prompts name as its cause.
+#[derive(Debug, Clone)]
+pub struct DamageEvent
+{
+    pub source: Uuid,
+    pub targets: Vec<Uuid>,
+    pub armor_test: ArmorTestType,
+    pub armor_pen: i8,
+}
+
+// Direction is read off the 2D6 scatter diagram, with 2 and 12 straight back at the thrower.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Scatter
+{
+    pub direction: u8,
+    pub meters: u16,
+}
+
+impl Scatter
+{
+    pub fn new(direction: u8, rolled_distance: u16, hits: u8) -> Scatter
+    {
+        Scatter { direction, meters: rolled_distance.saturating_sub(hits as u16) }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResistanceTest
+{
+    Damage,
+    Spell,
+    Drain,
+}
+
+// A test some character owes before the GM can finish resolving an effect.  The source is whoever caused it - for drain, the caster.
+// Damage carries the suggested soak pool so nobody has to add up body, armor and AP at the table.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ResistancePrompt
+{
+    pub character_id: Uuid,
+    pub source: Uuid,
+    pub test: ResistanceTest,
+    pub soak_pool: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AreaResolution
+{
+    pub scatter: Scatter,
+    pub affected: Vec<Uuid>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DamageType
+{
+    Physical,
+    Stun,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use crate::tracker::rules::{self, Edition};
+
+    use super::{RangedAttack, RangeBand, Lighting, Cover, FiringMode, AttackModifiers, Scatter};
+
+    #[test]
+    pub fn attack_modifiers_total_every_environmental_factor()
+    {
+        let attack = RangedAttack
+        {
+            attacker: Uuid::new_v4(), target: None, range: RangeBand::Long, lighting: Lighting::Partial, cover: Cover::Partial,
+            firing_mode: FiringMode::Burst
+        };
+
+        let modifiers = AttackModifiers::new(Edition::SR4, &attack, 0, 3, -2, 0);
+
+        assert_eq!(-3, modifiers.range);
+        assert_eq!(-2, modifiers.lighting);
+        assert_eq!(-2, modifiers.cover);
+        assert_eq!(-2, modifiers.recoil);
+        assert_eq!(-11, modifiers.total);
+    }
+
+    #[test]
+    pub fn recoil_compensation_never_turns_recoil_into_a_bonus()
+    {
+        assert_eq!(0, rules::recoil_modifier(Edition::SR4, FiringMode::SingleShot, 3, 0));
+        assert_eq!(0, rules::recoil_modifier(Edition::SR4, FiringMode::Burst, 3, 0));
+        assert_eq!(-6, rules::recoil_modifier(Edition::SR4, FiringMode::FullAuto, 3, 0));
+    }
+
+    #[test]
+    pub fn hits_on_the_attack_reduce_scatter_but_never_below_zero()
+    {
+        assert_eq!(2, Scatter::new(7, 5, 3).meters);
+        assert_eq!(0, Scatter::new(7, 2, 3).meters);
+    }
+}
@@ -0,0 +1,72 @@
+use rand::Rng;
+
+// The game's own dice.  Most rolls are still made physically at the table, but a handful of things (scatter, GM-side NPC rolls) are
+// less hassle when the tracker rolls them.  Every roll takes its random source as a parameter so the caller decides how it is seeded.
+
+pub fn roll_d6<R: Rng + ?Sized>(rng: &mut R) -> u8
+{
+    rng.gen_range(1..=6)
+}
+
+pub fn roll_d6s<R: Rng + ?Sized>(rng: &mut R, count: u8) -> Vec<u8>
+{
+    (0..count).map(|_| roll_d6(rng)).collect()
+}
+
+pub fn sum<R: Rng + ?Sized>(rng: &mut R, count: u8) -> u16
+{
+    roll_d6s(rng, count).iter().map(|die| *die as u16).sum()
+}
+
+// A Shadowrun style dice pool - fives and sixes are hits, and a pool where more than half the dice come up ones has glitched.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PoolRoll
+{
+    pub dice: Vec<u8>,
+    pub hits: u8,
+    pub glitch: bool,
+}
+
+impl PoolRoll
+{
+    pub fn from_dice(dice: Vec<u8>) -> PoolRoll
+    {
+        let hits = dice.iter().filter(|die| **die >= 5).count() as u8;
+        let ones = dice.iter().filter(|die| **die == 1).count();
+        let glitch = !dice.is_empty() && ones * 2 > dice.len();
+
+        PoolRoll { dice, hits, glitch }
+    }
+}
+
+pub fn roll_pool<R: Rng + ?Sized>(rng: &mut R, pool: u8) -> PoolRoll
+{
+    PoolRoll::from_dice(roll_d6s(rng, pool))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{PoolRoll, roll_d6s};
+
+    #[test]
+    pub fn fives_and_sixes_are_hits_and_a_majority_of_ones_is_a_glitch()
+    {
+        let roll = PoolRoll::from_dice(vec![1, 1, 1, 5, 6]);
+        assert_eq!(2, roll.hits);
+        assert!(roll.glitch);
+
+        let roll = PoolRoll::from_dice(vec![1, 1, 3, 5]);
+        assert_eq!(1, roll.hits);
+        assert!(!roll.glitch);
+    }
+
+    #[test]
+    pub fn every_die_rolled_lands_between_one_and_six()
+    {
+        let dice = roll_d6s(&mut rand::thread_rng(), 200);
+
+        assert_eq!(200, dice.len());
+        assert!(dice.iter().all(|die| (1..=6).contains(die)));
+    }
+}
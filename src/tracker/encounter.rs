@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+// A fight the GM has set up ahead of time: who is in it, any initiative bonuses or penalties they have already ruled on (ambushers,
+// people caught napping), and which NPCs the players should not know are there until they show themselves.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StagedEncounter
+{
+    pub name: String,
+    pub combatants: Vec<Uuid>,
+    pub initiative_modifiers: HashMap<Uuid, i8>,
+    pub hidden: Vec<Uuid>,
+    // Armed when the encounter is launched, and fired as the fight reaches each one.
+    pub triggers: Vec<ScriptedTrigger>,
+}
+
+// Something the GM has set to happen by itself once the fight reaches a round and pass, both counted from 1.  Triggers fire as a round
+// or a pass begins; one set for a pass the round never reaches fires as the next round begins.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScriptedTrigger
+{
+    pub round: usize,
+    pub pass: usize,
+    pub action: TriggerAction,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TriggerAction
+{
+    // The characters join the fight.  Arriving as a round begins, they roll initiative with everyone else; arriving at a later pass,
+    // they wait for the next round's.
+    Reinforcements(Vec<Uuid>),
+    // Nothing happens by itself; the GM is prompted with the note.
+    Prompt(String),
+}
+
+impl StagedEncounter
+{
+    pub fn new(name: &str) -> StagedEncounter
+    {
+        StagedEncounter { name: String::from(name), combatants: Vec::new(), initiative_modifiers: HashMap::new(), hidden: Vec::new(), triggers: Vec::new() }
+    }
+
+    // Every id the encounter mentions, whether it fights or not.
+    pub fn referenced_ids(&self) -> impl Iterator<Item = &Uuid>
+    {
+        let reinforcements = self.triggers.iter().flat_map(|trigger| match &trigger.action
+        {
+            TriggerAction::Reinforcements(characters) => characters.as_slice(),
+            TriggerAction::Prompt(_) => &[],
+        });
+        self.combatants.iter().chain(self.initiative_modifiers.keys()).chain(self.hidden.iter()).chain(reinforcements)
+    }
+}
@@ -1,9 +1,10 @@
-use std::{collections::{HashMap, hash_map::Entry}, sync::Arc};
+use std::{collections::{HashMap, HashSet, hash_map::Entry}, sync::Arc};
 
-use log::debug;
+use tracing::debug;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use uuid::Uuid;
 
-use super::{character::Character, initiative::{InitTracker, PassState}};
+use super::{character::{Character, CharacterPatch, ConditionMonitor, RollMacro, WoundTier, WoundView}, gear::{ArmorTestType, Weapon}, initiative::{InitTracker, PassState, TrackerState}, movement::{Gait, MovementRecord}, combat::{RangedAttack, AttackModifiers, Engagement, AreaAttack, AreaResolution, DamageEvent, Scatter, ResistancePrompt, ResistanceTest, DamageType}, dice, magic::{SpellDeclaration, SustainedSpell, Plane}, journal::{Journal, JournalEvent, JournalEntry, JournalFilter, ChatLine, RollRecord}, report::{CombatReport, ReportScope}, timeline::RoundTimeline, encounter::{StagedEncounter, ScriptedTrigger, TriggerAction}, names::Name, house_rules::{HouseRules, HouseRuleEvent}, matrix::{MatrixGrid, MatrixTarget, MatrixActionDeclaration, MatrixResolution, MATRIX_ACTIONS_PER_PASS}, rules::{self, Edition, Maneuver, ActionLabels, Healing, Recovery}, catalog::{ActionCatalog, CatalogAction, Ammunition}};
 
 // The game struct and methods coordinate actions and activity through combat.  The game struct is responsible for ensuring that
 // initiative passes flow smoothly (albeit through the tracker), for keeping straight what actions a character can perform on any 
@@ -27,8 +28,12 @@ use super::{character::Character, initiative::{InitTracker, PassState}};
 // rounds.  Initiative tracking is now handled by the InitiativeTracker, so Game merely needs to call next() until the return type indicates
 // we've hit the end.
 
+// Checkpoints a game keeps.  Each holds the whole cast and fight, so past this many the oldest is let go to make room for a new one.
+pub const CHECKPOINT_CAPACITY: usize = 16;
+
+#[derive(Clone)]
 pub struct Game {
-    current_state: State,
+    current_state: Phase,
 
     cast: HashMap<Uuid, Arc<Character>>,
 
@@ -39,9 +44,58 @@ pub struct Game {
     next_id: Vec<Uuid>,
     current_initiative: i8,
     next_initiative: i8,
+    // Counted from 1 as each round's initiative phase opens; 0 before the first.
+    round: usize,
     // initiative_player_map: HashMap<i8, Vec<Uuid>>,
     combatant_data: HashMap<Uuid, CharacterCombatData>,
-    
+    movement_ledger: Vec<MovementRecord>,
+    engagements: Vec<(Uuid, Uuid)>,
+    pending_resistance: Vec<ResistancePrompt>,
+    // Events the GM has scheduled that have yet to enter the order, and those in it this round.
+    timed_events: Vec<TimedEvent>,
+    events_in_order: Vec<TimedEvent>,
+    // Triggers armed by the launched encounter, and those fired since the runner last collected them.
+    triggers: Vec<ScriptedTrigger>,
+    fired_triggers: Vec<ScriptedTrigger>,
+    // Characters marked as delaying or away, passed over when their turn comes up just as anyone knocked out is; and the turns passed
+    // over since the runner last collected them.
+    sitting_out: HashMap<Uuid, SkipReason>,
+    skipped_turns: Vec<(Uuid, SkipReason)>,
+    // Sustained spells outlive any one combat, so they are not cleared with the rest of the combat data.
+    sustained_spells: Vec<SustainedSpell>,
+    // The GM's devices and hosts, who is jacked in, and the marks and damage traded between them.  Like sustained spells it outlives a
+    // combat.
+    matrix: MatrixGrid,
+    journal: Journal,
+    // The state at the start of each turn so far this pass, oldest first, so the GM can rewind.
+    turn_history: Vec<GameSnapshot>,
+    // Named checkpoints the GM has saved, oldest first.
+    checkpoints: Vec<(String, GameSnapshot)>,
+    staged_encounters: Vec<StagedEncounter>,
+    // Added to a combatant's initiative roll when it comes in.  Set from a launched encounter or a GM ruling made before the roll, and
+    // cleared when the combat ends.
+    initiative_adjustments: HashMap<Uuid, i8>,
+    hidden: HashSet<Uuid>,
+    // Combat-affecting edits players asked for mid-fight, one per character, held until the GM approves them.
+    pending_patches: HashMap<Uuid, CharacterPatch>,
+    // Scripts the GM has attached; they belong to the table, not to any one combat, so rewinds and checkpoints leave them alone.
+    house_rules: HouseRules,
+    // Whether the turn moves on by itself once everyone up has resolved, instead of waiting for the GM.
+    auto_advance: bool,
+    // Whether the next pass starts by itself when one ends, instead of waiting for the GM.
+    auto_cycle_passes: bool,
+    visibility: VisibilityOptions,
+    // Which edition's numbers the table plays by.
+    edition: Edition,
+    // The GM's own names for the action types, in place of the edition's.
+    action_labels: Option<ActionLabels>,
+    action_catalog: ActionCatalog,
+    // Whether damage, ammunition and Edge spent stay with the characters from one fight to the next, and into a rematch.  On unless the
+    // GM turns it off, in which case the player characters who fought are rested as each combat ends; the GM's NPCs never are.
+    carry_over: bool,
+    // Edge spent that the journal does not show: brought forward from the game this one is a rematch of, less whatever rest has since
+    // given back.
+    edge_brought_forward: HashMap<Uuid, i32>,
 }
 
 
@@ -50,7 +104,7 @@ impl Game {
     pub fn new() -> Game
     {
         Game {
-            current_state: State::PreCombat,
+            current_state: Phase::PreCombat,
             cast: HashMap::new(),
 
             // Combat specific data
@@ -59,9 +113,336 @@ impl Game {
             next_id: Vec::new(),
             current_initiative: 0, 
             next_initiative: 0,
+            round: 0,
             // initiative_player_map: HashMap::new(),
             combatant_data: HashMap::new(),
+            movement_ledger: Vec::new(),
+            engagements: Vec::new(),
+            pending_resistance: Vec::new(),
+            timed_events: Vec::new(),
+            events_in_order: Vec::new(),
+            triggers: Vec::new(),
+            fired_triggers: Vec::new(),
+            sitting_out: HashMap::new(),
+            skipped_turns: Vec::new(),
+            sustained_spells: Vec::new(),
+            matrix: MatrixGrid::new(),
+            journal: Journal::new(),
+            turn_history: Vec::new(),
+            checkpoints: Vec::new(),
+            staged_encounters: Vec::new(),
+            initiative_adjustments: HashMap::new(),
+            hidden: HashSet::new(),
+            pending_patches: HashMap::new(),
+            house_rules: HouseRules::new(),
+            auto_advance: false,
+            auto_cycle_passes: false,
+            visibility: VisibilityOptions::default(),
+            edition: Edition::default(),
+            action_labels: None,
+            action_catalog: ActionCatalog::new(),
+            carry_over: true,
+            edge_brought_forward: HashMap::new(),
+        }
+    }
+
+    pub fn journal(self: &Game) -> &Journal
+    {
+        &self.journal
+    }
+
+    pub fn journal_entries(self: &Game, filter: &JournalFilter) -> Vec<JournalEntry>
+    {
+        self.journal.filtered(filter)
+    }
+
+    // The journal after the given entry, as the reader may see it.  The GM sees all of it.  Everyone else loses chat that was not meant
+    // for them, anything about a hidden character, and the numbers on NPCs they are not otherwise shown - initiative, rolls, and damage
+    // unless the visibility options show NPC wounds exactly.
+    pub fn journal_after(self: &Game, after: Option<usize>, reader: Uuid, gm_view: bool) -> Vec<JournalEntry>
+    {
+        let entries = self.journal.after(after);
+        if gm_view
+        {
+            return entries.to_vec();
+        }
+
+        let npc = |id: &Uuid| self.cast.get(id).map_or(false, |character| !character.player_character);
+        let shown = |id: &Uuid| !self.is_hidden(id);
+
+        entries.iter()
+            .filter_map(|entry|
+            {
+                let visible = match &entry.event
+                {
+                    JournalEvent::Chat(line) => line.visible_to(reader, false),
+                    JournalEvent::Roll(record) => !npc(&record.character),
+                    JournalEvent::CombatStarted(combatants) =>
+                    {
+                        let combatants = combatants.iter().copied().filter(|id| shown(id)).collect();
+                        return Some(JournalEntry { event: JournalEvent::CombatStarted(combatants), ..entry.clone() });
+                    },
+                    JournalEvent::InitiativeRolled { character, .. } | JournalEvent::InitiativeAdjusted { character, .. } => !npc(character),
+                    JournalEvent::Damage { source, target, .. } =>
+                        source.as_ref().map_or(true, shown) && matches!(self.wounds_seen_by(target, false), Some(WoundView::Exact(_))),
+                    JournalEvent::Healed { character, .. } => matches!(self.wounds_seen_by(character, false), Some(WoundView::Exact(_))),
+                    JournalEvent::ActionTaken { character, .. } | JournalEvent::EdgeSpent { character, .. } | JournalEvent::GmProxy { character, .. } => shown(character),
+                    JournalEvent::RoundStarted(_) | JournalEvent::Rewound(_) | JournalEvent::CheckpointRestored(_) | JournalEvent::HouseRule { .. } => true,
+                };
+
+                visible.then(|| entry.clone())
+            })
+            .collect()
+    }
+
+    pub fn record_chat(self: &mut Game, line: ChatLine) -> usize
+    {
+        self.journal.record(JournalEvent::Chat(line))
+    }
+
+    // Notes in the journal that the GM stood in for an absent player, alongside the roll or action it covers.
+    pub fn record_proxy(self: &mut Game, character_id: Uuid, player_id: Uuid) -> usize
+    {
+        self.journal.record(JournalEvent::GmProxy { character: character_id, player: player_id })
+    }
+
+    pub fn chat_visible_to(self: &Game, reader: Uuid, reader_is_gm: bool) -> Vec<ChatLine>
+    {
+        self.journal.chat_visible_to(reader, reader_is_gm)
+    }
+
+    pub fn attach_house_rule(self: &mut Game, name: &str, event: HouseRuleEvent, source: &str) -> Result<(), GameError>
+    {
+        self.house_rules.attach(name, event, source).map_err(|msg| GameError::new(ErrorKind::InvalidHouseRule, msg))
+    }
+
+    pub fn remove_house_rule(self: &mut Game, name: &str) -> Result<(), GameError>
+    {
+        if !self.house_rules.remove(name)
+        {
+            return Err(GameError::new(ErrorKind::UnknownHouseRule, String::from(format!("There is no house rule named {}.", name))));
+        }
+
+        Ok(())
+    }
+
+    pub fn house_rules(self: &Game) -> Vec<(String, HouseRuleEvent)>
+    {
+        self.house_rules.list()
+    }
+
+    // Marks boxes off the target's physical or stun track, after any damage house rules have had their say.  The source is whoever dealt
+    // the damage, if anyone did; it only matters to the combat report.
+    pub fn apply_damage(self: &mut Game, source: Option<Uuid>, target: Uuid, boxes: u8, kind: DamageType) -> Result<(), GameError>
+    {
+        if let Some(unknown) = source.filter(|source| !self.cast.contains_key(source))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", unknown))));
+        }
+
+        let Some(character) = self.cast.get_mut(&target)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", target))));
+        };
+
+        let boxes = self.house_rules.on_damage(&character.name, kind, boxes, character.condition_monitor());
+        let character = Arc::make_mut(character);
+        match kind
+        {
+            DamageType::Physical => character.physical_track_filled = marked_track(character.physical_track_filled, character.physical_track_max, boxes as i16),
+            DamageType::Stun => character.stun_track_filled = marked_track(character.stun_track_filled, character.stun_track_max, boxes as i16),
+        }
+        self.journal.record(JournalEvent::Damage { source, target, boxes, kind });
+
+        Ok(())
+    }
+
+    // The kind of damage the target takes once their armor, less the attack's penetration, has been weighed against it.
+    pub fn damage_against_armor(self: &Game, target: Uuid, boxes: u8, kind: DamageType, armor_test: ArmorTestType, armor_pen: i8) -> Result<DamageType, GameError>
+    {
+        let Some(character) = self.cast.get(&target)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", target))));
+        };
+
+        Ok(rules::damage_against_armor(kind, boxes, character.armor_rating(armor_test).saturating_add(armor_pen)))
+    }
+
+    // Takes back however many boxes the healing earns under the rules, journaling each track that changed.
+    pub fn heal<R: Rng + ?Sized>(self: &mut Game, rng: &mut R, character_id: Uuid, healing: Healing) -> Result<Recovery, GameError>
+    {
+        let Some(character) = self.cast.get_mut(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let recovery = rules::recovery(rng, character, healing);
+        let character = Arc::make_mut(character);
+        character.physical_track_filled = marked_track(character.physical_track_filled, character.physical_track_max, -(recovery.physical as i16));
+        character.stun_track_filled = marked_track(character.stun_track_filled, character.stun_track_max, -(recovery.stun as i16));
+        for (boxes, kind) in [(recovery.physical, DamageType::Physical), (recovery.stun, DamageType::Stun)]
+        {
+            if boxes > 0
+            {
+                self.journal.record(JournalEvent::Healed { character: character_id, boxes, kind });
+            }
+        }
+        debug!("{} recovered {} physical and {} stun boxes.", character_id, recovery.physical, recovery.stun);
+
+        Ok(recovery)
+    }
+
+    // A character can spend Edge up to their Edge attribute over the session; returns how much they have left.
+    pub fn spend_edge(self: &mut Game, character_id: Uuid, points: u8) -> Result<u8, GameError>
+    {
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let remaining = (character.stat("Edge").max(0) as u16).saturating_sub(self.edge_spent(character_id));
+        if points as u16 > remaining
+        {
+            return Err(GameError::new(ErrorKind::NoEdgeLeft, String::from(format!("Character {} has only {} Edge left to spend.", character_id, remaining))));
+        }
+
+        self.journal.record(JournalEvent::EdgeSpent { character: character_id, points });
+
+        Ok((remaining - points as u16) as u8)
+    }
+
+    fn edge_spent(self: &Game, character_id: Uuid) -> u16
+    {
+        let brought_forward = self.edge_brought_forward.get(&character_id).copied().unwrap_or(0);
+        (self.journal.edge_spent(character_id) as i32 + brought_forward).max(0) as u16
+    }
+
+    // The GM's deliberate rest and repair: the characters' wounds are healed, their armor mended, their guns reloaded and their Edge
+    // given back.  Nobody is touched unless every id is in the cast.
+    pub fn rest(self: &mut Game, characters: &[Uuid]) -> Result<(), GameError>
+    {
+        if let Some(unknown) = characters.iter().find(|character_id| !self.cast.contains_key(character_id))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", unknown))));
+        }
+
+        for character_id in characters
+        {
+            let character = Arc::make_mut(self.cast.get_mut(character_id).unwrap());
+            character.physical_track_filled = 0;
+            character.stun_track_filled = 0;
+            for armour in Arc::make_mut(&mut character.armor).iter_mut()
+            {
+                armour.degradation = 0;
+            }
+            for feature in Arc::make_mut(&mut character.weapons).iter_mut().flat_map(|weapon| weapon.firing_features.iter_mut())
+            {
+                feature.loaded = feature.reload_size;
+            }
+            self.edge_brought_forward.insert(*character_id, -(self.journal.edge_spent(*character_id) as i32));
+            debug!("{} has rested.", character_id);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_carry_over(self: &mut Game, on: bool)
+    {
+        self.carry_over = on;
+    }
+
+    pub fn carry_over(self: &Game) -> bool
+    {
+        self.carry_over
+    }
+
+    pub fn combat_report(self: &Game, scope: ReportScope) -> CombatReport
+    {
+        let entries = match scope
+        {
+            ReportScope::LastCombat => self.journal.since_last_combat(),
+            ReportScope::Session => self.journal.entries(),
+        };
+
+        let mut report = CombatReport::from_entries(entries);
+        for tally in report.combatants.iter_mut()
+        {
+            tally.name = self.cast.get(&tally.character).map_or(Name::from(""), |character| character.name.clone());
+        }
+
+        report
+    }
+
+    // The rounds of the current (or last) combat.  Players are not shown the NPCs staged as hidden, nor any NPC's initiative.
+    pub fn round_timeline(self: &Game, gm_view: bool) -> Vec<RoundTimeline>
+    {
+        let mut rounds = RoundTimeline::from_entries(self.journal.since_last_combat());
+        if !gm_view
+        {
+            let npcs = self.get_npcs().iter().map(|npc| npc.id).collect::<Vec<Uuid>>();
+            for round in rounds.iter_mut()
+            {
+                for hidden in &self.hidden
+                {
+                    round.without(hidden);
+                }
+                for npc in &npcs
+                {
+                    round.withhold_score(npc);
+                }
+            }
+        }
+
+        rounds
+    }
+
+    // Defining a macro under a name the character already uses replaces the old one.
+    pub fn define_roll_macro(self: &mut Game, character_id: Uuid, roll_macro: RollMacro) -> Result<(), GameError>
+    {
+        let Some(character) = self.cast.get_mut(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let macros = Arc::make_mut(&mut Arc::make_mut(character).roll_macros);
+        macros.retain(|existing| existing.name != roll_macro.name);
+        macros.push(roll_macro);
+
+        Ok(())
+    }
+
+    pub fn has_roll_macro(self: &Game, character_id: Uuid, name: &str) -> bool
+    {
+        self.cast.get(&character_id).map_or(false, |character| character.roll_macro(name).is_some())
+    }
+
+    pub fn roll_dice<R: Rng + ?Sized>(self: &mut Game, rng: &mut R, character_id: Uuid, label: &str, pool: u8) -> Result<RollRecord, GameError>
+    {
+        if !self.cast.contains_key(&character_id)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
         }
+
+        let record = RollRecord { character: character_id, label: String::from(label), roll: dice::roll_pool(rng, pool) };
+        debug!("{} rolled {} for {}: {} hits.", character_id, pool, label, record.roll.hits);
+        self.journal.record(JournalEvent::Roll(record.clone()));
+
+        Ok(record)
+    }
+
+    pub fn roll_macro<R: Rng + ?Sized>(self: &mut Game, rng: &mut R, character_id: Uuid, name: &str) -> Result<RollRecord, GameError>
+    {
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let Some(pool) = character.roll_macro(name).map(|roll_macro| roll_macro.pool)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownRollMacro, String::from(format!("Character {} has no roll macro named {}.", character_id, name))));
+        };
+
+        self.roll_dice(rng, character_id, name, pool)
     }
 
     // **********************************************************************************
@@ -84,19 +465,72 @@ impl Game {
     pub fn retire_cast_member(self: &mut Game, cast_member_id: Uuid)
     {
         self.cast.remove(&cast_member_id);
+        self.engagements.retain(|(attacker, defender)| *attacker != cast_member_id && *defender != cast_member_id);
+        self.sustained_spells.retain(|spell| spell.caster != cast_member_id);
+        self.matrix.jack_out(cast_member_id);
+        self.pending_patches.remove(&cast_member_id);
+    }
+
+    // What is left of a player who deleted their account.  Their characters sitting out of the fight go; any in it stay so the fight still
+    // adds up, but under the placeholder name and without the player's macros, in the saved turns and checkpoints as well.  Whatever the
+    // player said at the table goes from the journal.
+    pub fn forget_player(self: &mut Game, player_id: Uuid, characters: &HashSet<Uuid>, placeholder: Name)
+    {
+        let anonymize = |cast: &mut HashMap<Uuid, Arc<Character>>|
+        {
+            for character_id in characters
+            {
+                if let Some(character) = cast.get_mut(character_id)
+                {
+                    let character = Arc::make_mut(character);
+                    character.name = placeholder.clone();
+                    character.roll_macros = Arc::new(Vec::new());
+                }
+            }
+        };
+
+        anonymize(&mut self.cast);
+        for snapshot in self.turn_history.iter_mut().chain(self.checkpoints.iter_mut().map(|(_, snapshot)| snapshot))
+        {
+            anonymize(&mut snapshot.cast);
+        }
+
+        let benched: Vec<Uuid> = characters.iter().filter(|character_id| !self.combatant_data.contains_key(character_id)).copied().collect();
+        for character_id in benched
+        {
+            self.retire_cast_member(character_id);
+        }
+
+        self.journal.purge_player(player_id);
     }
 
     // **********************************************************************************
     // State retrieval methods
 
-    pub fn current_state(self: &mut Game)->String
+    pub fn current_state(self: &Game)->String
     {
         self.current_state.to_string()
     }
 
+    pub fn is_in_combat(self: &Game) -> bool
+    {
+        self.current_state != Phase::PreCombat
+    }
+
+    pub fn phase(self: &Game) -> Phase
+    {
+        self.current_state
+    }
+
+    // The phases the game can move into right now: those the current phase leads to, less any the fight is not ready for.
+    pub fn allowed_transitions(self: &Game) -> Vec<Phase>
+    {
+        self.current_state.allowed_transitions().into_iter().filter(|next| self.check_transition(*next).is_ok()).collect()
+    }
+
     pub fn waiting_for(self: &Game)->Option<Vec<Uuid>>
     {
-        if self.current_state != State::ActionRound
+        if !self.in_phase(Gated::TurnOrder)
         {
             return Option::None;
         }
@@ -111,6 +545,8 @@ impl Game {
                 Some(data) => {
                     if !data.has_resolved {blockers.push(*uuid)}
                 }
+                // A timed event has nobody to wait on; the GM moves past it.
+                None if self.timed_event(uuid).is_some() => {}
                 None => unreachable!()
             }
             // match self.combatant_data.entry(*uuid) {
@@ -147,7 +583,7 @@ impl Game {
 
     pub fn on_deck(self: &Game) -> Option<Vec<Uuid>>
     {
-        if self.current_state != State::ActionRound
+        if !self.in_phase(Gated::TurnOrder)
         {
             return None;
         }
@@ -162,6 +598,29 @@ impl Game {
         }
     }
 
+    // Everyone still to act this pass with their initiative, highest first.
+    pub fn initiative_order(self: &Game) -> Vec<(Uuid, i8)>
+    {
+        let mut order: Vec<(Uuid, i8)> = self.collect_all_remaining_events()
+            .map_or(Vec::new(), |events| events.into_iter()
+                .flat_map(|(initiative, ids)| ids.into_iter().map(move |id| (id, initiative)))
+                .collect());
+        order.sort_by(|(_, left), (_, right)| right.cmp(left));
+
+        order
+    }
+
+    // Makes the order of tied initiatives reproducible, for tests and simulated fights.
+    pub fn seed_initiative_ties(self: &mut Game, seed: u64)
+    {
+        self.init_tracker.set_rng(StdRng::seed_from_u64(seed));
+    }
+
+    pub fn current_pass(self: &Game) -> usize
+    {
+        self.init_tracker.current_pass()
+    }
+
     pub fn collect_all_remaining_events(self: & Game) -> Option<HashMap<i8, Vec<Uuid>>>
     {
         let events = self.init_tracker.get_ordered_inits();
@@ -243,7 +702,7 @@ impl Game {
 
     pub fn get_current_init(self: &Game) -> Option<i8>
     {
-        if self.current_state != State::ActionRound
+        if !self.in_phase(Gated::TurnOrder)
         {
             None
         }
@@ -259,7 +718,7 @@ impl Game {
 
     pub fn get_next_init(self: &Game) -> Option<i8>
     {
-        if self.current_state != State::ActionRound
+        if !self.in_phase(Gated::TurnOrder)
         {
             None
         }
@@ -344,6 +803,19 @@ impl Game {
         return undeclared;
     }
 
+    // What the order will look like from the rolls submitted so far, highest first, and who has yet to roll.
+    pub fn preview_initiative_order(self: &mut Game) -> Result<InitiativePreview, GameError>
+    {
+        self.require_phase(Gated::PreviewOrder)?;
+
+        let mut order: Vec<(Uuid, i8)> = self.init_tracker.get_ordered_inits().into_iter().map(|(initiative, id)| (id, initiative)).collect();
+        order.sort_by(|(_, left), (_, right)| right.cmp(left));
+        let mut outstanding = self.collect_undeclared_initiatives();
+        outstanding.sort();
+
+        Ok(InitiativePreview { order, outstanding })
+    }
+
     pub fn get_cast(self: &Game) -> Vec<Arc<Character>>
     {
         let mut result = Vec::new();
@@ -365,6 +837,125 @@ impl Game {
         self.filter_cast_by(true)
     }
 
+    // Edits that could change the fight - stats, gear, tracks - wait for the GM if the character is in one, unless the GM is the one
+    // making them.  Returns the fields changed, or that would change once approved.
+    pub fn update_character(self: &mut Game, character_id: Uuid, patch: CharacterPatch, gm_approved: bool) -> Result<PatchOutcome, GameError>
+    {
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let changed = patch.validate(character).map_err(|msg| GameError::new(ErrorKind::InvalidPatch, msg))?;
+        let in_combat = self.current_state != Phase::PreCombat && self.combatant_data.contains_key(&character_id);
+
+        if in_combat && patch.affects_combat() && !gm_approved
+        {
+            self.pending_patches.insert(character_id, patch);
+            return Ok(PatchOutcome::AwaitingApproval(changed));
+        }
+
+        self.apply_patch(character_id, &patch);
+        Ok(PatchOutcome::Applied(changed))
+    }
+
+    // Applies the edit the character's player is waiting on.  The sheet may have moved on since it was asked for, so it is checked again.
+    pub fn approve_character_update(self: &mut Game, character_id: Uuid) -> Result<Vec<&'static str>, GameError>
+    {
+        let Some(patch) = self.pending_patches.remove(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} has no edit waiting for approval.", character_id))));
+        };
+
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let changed = patch.validate(character).map_err(|msg| GameError::new(ErrorKind::InvalidPatch, msg))?;
+        self.apply_patch(character_id, &patch);
+
+        Ok(changed)
+    }
+
+    pub fn pending_character_updates(self: &Game) -> Vec<Uuid>
+    {
+        self.pending_patches.keys().copied().collect()
+    }
+
+    fn apply_patch(self: &mut Game, character_id: Uuid, patch: &CharacterPatch)
+    {
+        if let Some(character) = self.cast.get_mut(&character_id)
+        {
+            patch.apply(Arc::make_mut(character));
+
+            // Passes come from the sheet, so a changed sheet can change them for the rest of the fight.
+            if let Some(combat_data) = self.combatant_data.get_mut(&character_id)
+            {
+                combat_data.derive_passes(self.edition, character);
+            }
+        }
+    }
+
+    // Everything a client needs to draw one character's card.  Without full detail - a player looking at someone else's character - the
+    // status list keeps to what the table could see: not who is jacked in, what they are sustaining, or what tests they still owe.
+    pub fn summarize(self: &Game, character: Arc<Character>, full_detail: bool) -> CharacterSummary
+    {
+        let id = character.id;
+        let mut status = Vec::new();
+
+        if self.is_on_full_defense(id)
+        {
+            status.push(StatusEffect::FullDefense);
+        }
+        match character.plane
+        {
+            Plane::AstralProjecting => status.push(StatusEffect::AstralProjecting),
+            Plane::DualNatured => status.push(StatusEffect::DualNatured),
+            Plane::Physical => {},
+        }
+        status.extend(self.engagements.iter().filter_map(|(attacker, defender)| match (*attacker == id, *defender == id)
+        {
+            (true, _) => Some(StatusEffect::Engaged(*defender)),
+            (_, true) => Some(StatusEffect::Engaged(*attacker)),
+            _ => None,
+        }));
+
+        if full_detail
+        {
+            status.extend(self.sustained_spells.iter().filter(|spell| spell.caster == id).map(|spell| StatusEffect::Sustaining(spell.spell.clone())));
+            if self.matrix.is_jacked_in(id)
+            {
+                status.push(StatusEffect::JackedIn);
+            }
+            if self.pending_resistance.iter().any(|prompt| prompt.character_id == id)
+            {
+                status.push(StatusEffect::ResistanceTestPending);
+            }
+            if self.is_hidden(&id)
+            {
+                status.push(StatusEffect::Hidden);
+            }
+        }
+
+        let turn = self.turn_state(&id);
+
+        CharacterSummary { condition: character.condition_monitor(), character, status, turn }
+    }
+
+    pub fn turn_state(self: &Game, id: &Uuid) -> TurnState
+    {
+        match (self.current_state, self.combatant_data.get(id))
+        {
+            (_, None) | (Phase::PreCombat, _) => TurnState::OutOfCombat,
+            (Phase::Initiative, Some(data)) if !data.declared_initiative => TurnState::AwaitingInitiative,
+            (Phase::Initiative, Some(_)) => TurnState::Waiting,
+            (Phase::ActionRound, Some(data)) if data.has_resolved => TurnState::Resolved,
+            (Phase::ActionRound, Some(_)) if self.current_turn_id.contains(id) => TurnState::Acting,
+            (Phase::ActionRound, Some(_)) => TurnState::Waiting,
+        }
+    }
+
     fn filter_cast_by(self: &Game, player_owned: bool) -> Vec<Arc<Character>>
     {
         let mut result = Vec::new();
@@ -396,15 +987,68 @@ impl Game {
     // ******************************************************************************************
     // State change methods
 
-    pub fn end_combat(self: &mut Game)
+    pub fn end_combat(self: &mut Game) -> Result<(), GameError>
     {
-        self.current_state = State::PreCombat;
+        self.check_transition(Phase::PreCombat)?;
+
+        if !self.carry_over
+        {
+            let fought = self.get_combatants().into_iter()
+                .filter(|id| self.cast.get(id).map_or(false, |character| character.player_character))
+                .collect::<Vec<Uuid>>();
+            let _ = self.rest(&fought);
+        }
+
+        self.current_state = Phase::PreCombat;
         self.current_turn_id.clear();
         self.next_id.clear();
         self.combatant_data.clear();
+        self.movement_ledger.clear();
+        self.engagements.clear();
+        self.pending_resistance.clear();
+        self.timed_events.clear();
+        self.events_in_order.clear();
+        self.triggers.clear();
+        self.sitting_out.retain(|_, reason| *reason != SkipReason::Delaying);
+        self.skipped_turns.clear();
         self.current_initiative = 0;
         self.next_initiative = 0;
+        self.round = 0;
         self.init_tracker.reset();
+        self.turn_history.clear();
+        self.initiative_adjustments.clear();
+        self.hidden.clear();
+
+        Ok(())
+    }
+
+    // A new game for the same table: the cast, the encounters the GM has staged, the house rules and the turn settings.  Nothing of the
+    // fight itself comes across - no combat, journal, history, checkpoints, spells or matrix.  The cast comes across as it stands if the
+    // table carries damage and resources over, Edge spent included, and rested otherwise.
+    pub fn rematch(self: &Game) -> Game
+    {
+        let mut rematch = Game::new();
+        rematch.cast = self.cast.clone();
+        rematch.carry_over = self.carry_over;
+        if self.carry_over
+        {
+            rematch.edge_brought_forward = self.cast.keys().map(|character_id| (*character_id, self.edge_spent(*character_id) as i32)).collect();
+        }
+        else
+        {
+            let cast = self.cast.keys().copied().collect::<Vec<Uuid>>();
+            let _ = rematch.rest(&cast);
+        }
+        rematch.staged_encounters = self.staged_encounters.clone();
+        rematch.house_rules = self.house_rules.clone();
+        rematch.auto_advance = self.auto_advance;
+        rematch.auto_cycle_passes = self.auto_cycle_passes;
+        rematch.visibility = self.visibility;
+        rematch.edition = self.edition;
+        rematch.action_labels = self.action_labels.clone();
+        rematch.action_catalog = self.action_catalog.clone();
+
+        rematch
     }
 
     pub fn add_combatant(self: &mut Game, combatant: Uuid) -> Result<(), GameError>
@@ -416,9 +1060,7 @@ impl Game {
                 ErrorKind::UnknownCastId, String::from(format!("ID {} does not match against any ID in the cast list.", combatant))
             ));
         }
-        let combatant_data = CharacterCombatData::new();
-
-        // TODO: Look up character and review their gear, augs etc. to fill in turns_per_round and/or update any other fields
+        let combatant_data = CharacterCombatData::for_character(self.edition, &self.cast[&combatant]);
         self.combatant_data.insert(combatant, combatant_data);
 
         Ok(())
@@ -428,6 +1070,7 @@ impl Game {
     {
 
         let mut bad_ids = Vec::<String>::new();
+        let combatants = involved.clone();
 
         // Set up Characters;
         // for id in involved.drain(0..involved.len() - 1)
@@ -442,66 +1085,137 @@ impl Game {
         
         if !bad_ids.is_empty() {
             let missing_ids = bad_ids.join(", ");
-            return Err(GameError{
-                kind: ErrorKind::UnknownCastId,
-                msg: String::from(format!("The character(s) with id(s) {} is not registered as a cast member of this adventure.", missing_ids))
-            });
+            return Err(GameError::new(
+                ErrorKind::UnknownCastId,
+                String::from(format!("The character(s) with id(s) {} is not registered as a cast member of this adventure.", missing_ids))
+            ));
         }
 
+        self.journal.record(JournalEvent::CombatStarted(combatants));
+
         Ok(())
     }
 
-    pub fn start_initiative_phase(self: &mut Game) -> Result<(), GameError>
+    // Refuses a step taken outside the phases the table below allows it in.
+    fn require_phase(self: &Game, step: Gated) -> Result<(), GameError>
     {
-        debug!("Starting initiative.");
-        if self.current_state != State::PreCombat && self.current_state != State::ActionRound
+        let (phases, message) = step.rule();
+        if phases.contains(&self.current_state)
         {
-            debug!("Current state of game {} is not allowed to transition into Initiative.", self.current_state.to_string());
-            return Err(GameError::new
-            (
-                ErrorKind::InvalidStateAction, String::from("You may not call begin_initiative unless in the PreCombat or InitiativePass phase.")
-            ));
+            return Ok(());
         }
 
-        if self.combatant_data.len() == 0
+        debug!("{:?} is not allowed while the game is in {}.", step, self.current_state.to_string());
+        Err(GameError::wrong_phase(phases, String::from(message)))
+    }
+
+    fn in_phase(self: &Game, step: Gated) -> bool
+    {
+        self.require_phase(step).is_ok()
+    }
+
+    // Whether the game may move from its current phase into the next one: the move has to be one the current phase allows, and the fight
+    // has to be ready for it.
+    fn check_transition(self: &Game, next: Phase) -> Result<(), GameError>
+    {
+        if !self.current_state.allowed_transitions().contains(&next)
         {
-            debug!("The play field has not had any combatants identified.");
-            return Err(GameError::new
-            (
-                ErrorKind::UnknownCastId, String::from("You may not begin an initiative round if no one is going to fight.")
-            ))
+            debug!("Current state of game {} is not allowed to transition into {}.", self.current_state.to_string(), next.to_string());
+            let message = match next
+            {
+                Phase::Initiative => String::from("You may not call begin_initiative unless in the PreCombat or InitiativePass phase."),
+                Phase::ActionRound => String::from("Not in the initiative state.  Cannot advance to combat."),
+                Phase::PreCombat => String::from("There is no combat to end."),
+            };
+            let from: Vec<Phase> = [Phase::PreCombat, Phase::Initiative, Phase::ActionRound].into_iter()
+                .filter(|phase| phase.allowed_transitions().contains(&next))
+                .collect();
+            return Err(GameError::wrong_phase(&from, message));
         }
 
-        if self.unresolved_turn() || self.on_deck().is_some()
+        match next
         {
-            debug!("There are still unresolved events this turn.");
-            return Err(GameError::new
-            (
-                ErrorKind::UnresolvedCombatant, String::from("There are still unresolved events this turn - you may not start the next turn.")
-            ))
+            Phase::Initiative if self.combatant_data.len() == 0 =>
+            {
+                debug!("The play field has not had any combatants identified.");
+                Err(GameError::new
+                (
+                    ErrorKind::UnknownCastId, String::from("You may not begin an initiative round if no one is going to fight.")
+                ))
+            },
+            Phase::Initiative if self.unresolved_turn() || self.on_deck().is_some() =>
+            {
+                debug!("There are still unresolved events this turn.");
+                Err(GameError::new
+                (
+                    ErrorKind::UnresolvedCombatant, String::from("There are still unresolved events this turn - you may not start the next turn.")
+                ))
+            },
+            Phase::ActionRound if self.combatant_data.values().any(|combatant| !combatant.declared_initiative) =>
+            {
+                Err(GameError::new(
+                    ErrorKind::InvalidStateAction, 
+                    String::from("Not all combatants have supplied their initiative.  Cannot begin passes.")
+                ))
+            },
+            _ => Ok(()),
         }
+    }
+
+    pub fn start_initiative_phase(self: &mut Game) -> Result<(), GameError>
+    {
+        debug!("Starting initiative.");
+        self.check_transition(Phase::Initiative)?;
 
-        self.current_state = State::Initiative;
+        self.current_state = Phase::Initiative;
+        self.derive_passes();
         self.reset_actions();
+        self.reset_movement();
+        self.end_full_defense();
         self.init_tracker.end_turn();
+        self.round += 1;
+        self.journal.record(JournalEvent::RoundStarted(self.round));
+        self.fire_triggers();
     
 
         Ok(())
     }
 
+    // A roll the character could never have made - below the least or above the most their sheet and the edition allow - is a typo or
+    // worse, and is refused.
     pub fn accept_initiative_roll(self: &mut Game, character_id: Uuid, initiative: i8) -> Result<(), GameError>
     {
-        if self.current_state != State::Initiative
+        self.take_initiative_roll(character_id, initiative, true)
+    }
+
+    // The GM's way past the bounds, for the edge that blew up or the sheet that is not filled out yet.
+    pub fn override_initiative_roll(self: &mut Game, character_id: Uuid, initiative: i8) -> Result<(), GameError>
+    {
+        self.take_initiative_roll(character_id, initiative, false)
+    }
+
+    fn take_initiative_roll(self: &mut Game, character_id: Uuid, initiative: i8, bounded: bool) -> Result<(), GameError>
+    {
+        self.require_phase(Gated::RollInitiative)?;
+
+        if let Some(character) = self.cast.get(&character_id).filter(|_| bounded)
         {
-            return Err(GameError{
-                kind: ErrorKind::InvalidStateAction,
-                msg: String::from(format!("The game is not in the initiative phase: you cannot add a new initiative roll."))
-            });
+            let (lowest, highest) = rules::initiative_range(self.edition, character);
+            if initiative < lowest || initiative > highest
+            {
+                return Err(GameError::new(ErrorKind::InitiativeOutOfRange, 
+                    String::from(format!("An initiative of {} is out of range: {} can only roll {} to {}.", initiative, character.name, lowest, highest))));
+            }
         }
 
-        // TODO: scan the ID'd character to 
         if let Some(combat_data) = self.combatant_data.get_mut(&character_id)
         {
+            let initiative = initiative + self.initiative_adjustments.get(&character_id).copied().unwrap_or(0);
+            let initiative = match self.cast.get(&character_id)
+            {
+                Some(character) => self.house_rules.on_initiative(&character.name, initiative, character.condition_monitor()),
+                None => initiative,
+            };
             self.init_tracker.add_new_event
             (
                 character_id, 
@@ -511,7 +1225,17 @@ impl Game {
                 combat_data.matrix_passes
             );
 
+            if self.matrix.is_jacked_in(character_id)
+            {
+                self.init_tracker.login_matrix(character_id);
+            }
+            if self.cast.get(&character_id).map_or(false, |character| character.plane == Plane::AstralProjecting)
+            {
+                self.init_tracker.enter_astral_space(character_id);
+            }
+
             combat_data.declared_initiative = true;
+            self.journal.record(JournalEvent::InitiativeRolled { character: character_id, roll: initiative });
         }
         else
         {
@@ -521,31 +1245,172 @@ impl Game {
         Ok(())
     }
 
-    pub fn start_combat_rounds(self: &mut Game) -> Result<(), GameError>
+    // A GM ruling that moves a combatant up or down the order.  Someone yet to roll has it added to the roll when it comes in; someone
+    // waiting to act is moved at once, and whoever is acting now keeps their turn and carries the new score into any later passes.
+    // Returns the new score, or None while it waits on the roll.
+    // Rolls for the character on the table's behalf - the sheet's own formula if it has one, otherwise Reaction + Intuition plus whatever
+    // the edition adds from the dice - and takes the result as their roll.  Returns what it came to before any adjustments or house rules.
+    pub fn roll_initiative<R: Rng + ?Sized>(self: &mut Game, rng: &mut R, character_id: Uuid) -> Result<i8, GameError>
+    {
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let roll = rules::roll_initiative(self.edition, rng, character);
+        self.accept_initiative_roll(character_id, roll)?;
+
+        Ok(roll)
+    }
+
+    pub fn adjust_initiative(self: &mut Game, character_id: Uuid, delta: i8, reason: String) -> Result<Option<i8>, GameError>
     {
-        if self.current_state != State::Initiative
+        let Some(combat_data) = self.combatant_data.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", character_id))));
+        };
+
+        let adjusted = if self.current_state == Phase::PreCombat || !combat_data.declared_initiative
         {
-            return Err(GameError::new(
-                ErrorKind::InvalidStateAction,
-                String::from("Not in the initiative state.  Cannot advance to combat.")
-            ));
+            let pending = self.initiative_adjustments.entry(character_id).or_insert(0);
+            *pending = pending.saturating_add(delta);
+            None
+        }
+        else
+        {
+            let held_over = match self.init_tracker.adjust(character_id, delta)
+            {
+                PassState::Next((_, initiative)) => Some(initiative),
+                _ => None,
+            };
+
+            let initiative = if self.next_id.contains(&character_id)
+            {
+                self.next_initiative.saturating_add(delta)
+            }
+            else if self.current_turn_id.contains(&character_id)
+            {
+                held_over.unwrap_or(self.current_initiative.saturating_add(delta))
+            }
+            else if let Some(initiative) = held_over
+            {
+                initiative
+            }
+            else
+            {
+                return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} has no turn left this round to adjust.", character_id))));
+            };
+
+            // Whoever is on deck may no longer be next in line, either way the adjustment went.
+            if self.current_state == Phase::ActionRound
+            {
+                self.reload_on_deck(character_id, delta);
+            }
+
+            Some(initiative)
+        };
+
+        self.journal.record(JournalEvent::InitiativeAdjusted { character: character_id, delta, reason });
+        Ok(adjusted)
+    }
+
+    // Puts the on-deck group back into the pass, moving the adjusted character by delta if they are in it, and draws the on-deck group
+    // again.
+    fn reload_on_deck(&mut self, adjusted: Uuid, delta: i8)
+    {
+        for id in self.next_id.drain(..)
+        {
+            let score = if id == adjusted { self.next_initiative.saturating_add(delta) } else { self.next_initiative };
+            self.init_tracker.requeue(id, score);
         }
 
-        for combatant in self.combatant_data.values()
+        if let PassState::Next(on_deck) = self.init_tracker.next()
         {
-            if !combatant.declared_initiative
+            self.next_initiative = on_deck.1;
+            self.next_id.push(on_deck.0);
+            while let PassState::Next(same_turn) = self.init_tracker.next_if_match(self.next_initiative)
             {
-                return Err(GameError::new(
-                    ErrorKind::InvalidStateAction, 
-                    String::from("Not all combatants have supplied their initiative.  Cannot begin passes.")
-                ));
+                self.next_id.push(same_turn.0);
+            }
+        }
+    }
+
+    // Puts an event with no character behind it into the initiative order at the given score, the given number of rounds from now.  It
+    // comes up once, in the first pass of its round, as a turn of its own that the GM advances past like any other.  Scheduled for the
+    // round under way, it goes in at once if its score is still to come this pass; otherwise it waits for the next round.
+    pub fn schedule_event(self: &mut Game, label: String, initiative: i8, rounds: u32) -> Result<TimedEvent, GameError>
+    {
+        if !self.is_in_combat()
+        {
+            return Err(GameError::wrong_phase(&[Phase::Initiative, Phase::ActionRound], String::from("Events can only be scheduled during a combat.")));
+        }
+
+        if label.trim().is_empty()
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("A timed event needs a label.")));
+        }
+
+        let mut event = TimedEvent { id: Uuid::new_v4(), label, initiative, rounds_away: rounds };
+        if self.current_state == Phase::ActionRound
+        {
+            let still_to_come = self.current_pass() == 0 && self.get_current_init().map_or(false, |current| initiative < current);
+            if rounds == 0 && still_to_come
+            {
+                self.init_tracker.one_shot_this_pass(event.id, initiative);
+                self.reload_on_deck(event.id, 0);
+                self.events_in_order.push(event.clone());
+                return Ok(event);
             }
+
+            // The round under way has already started, so it counts as one of the rounds waited.
+            event.rounds_away = rounds.saturating_sub(1);
+        }
+
+        self.timed_events.push(event.clone());
+        Ok(event)
+    }
+
+    // The event behind an id in the order this round, if it is one.
+    pub fn timed_event(self: &Game, id: &Uuid) -> Option<&TimedEvent>
+    {
+        self.events_in_order.iter().find(|event| event.id == *id)
+    }
+
+    // Events scheduled but not yet in the order, soonest first.
+    pub fn scheduled_events(self: &Game) -> Vec<TimedEvent>
+    {
+        let mut scheduled = self.timed_events.clone();
+        scheduled.sort_by(|left, right| left.rounds_away.cmp(&right.rounds_away).then(right.initiative.cmp(&left.initiative)));
+        scheduled
+    }
+
+    // Called as a round's passes begin: events due this round go into the first pass, and the rest are a round nearer.
+    fn enter_due_events(&mut self)
+    {
+        let (due, waiting): (Vec<TimedEvent>, Vec<TimedEvent>) = self.timed_events.drain(..).partition(|event| event.rounds_away == 0);
+        self.timed_events = waiting.into_iter().map(|event| TimedEvent { rounds_away: event.rounds_away - 1, ..event }).collect();
+
+        for event in &due
+        {
+            self.init_tracker.one_shot_this_pass(event.id, event.initiative);
         }
+        self.events_in_order = due;
+    }
 
+    pub fn start_combat_rounds(self: &mut Game) -> Result<(), GameError>
+    {
+        self.check_transition(Phase::ActionRound)?;
+
+        self.enter_due_events();
         self.initialize_initiatives()?;
-        self.current_state = State::ActionRound;
+        self.current_state = Phase::ActionRound;
+        for (rule, note) in self.house_rules.on_round_start(self.combatant_data.len())
+        {
+            self.journal.record(JournalEvent::HouseRule { rule, note });
+        }
+        self.turn_history.clear();
 
-        return Ok(()); 
+        return self.pass_over_sitting_out().or_else(Game::pass_over_ended);
     }
 
     fn initialize_initiatives(&mut self) -> Result<(), GameError>
@@ -580,9 +1445,9 @@ impl Game {
                 self.next_initiative = top_init.1;
                 self.next_id.push(top_init.0);
 
-                while let PassState::Next(_) = self.init_tracker.next_if_match(self.next_initiative)
+                while let PassState::Next(same_turn) = self.init_tracker.next_if_match(self.next_initiative)
                 {
-                    self.next_id.push(top_init.0);
+                    self.next_id.push(same_turn.0);
                 }
                 
             },
@@ -594,13 +1459,14 @@ impl Game {
 
     pub fn next_initiative_pass(self: &mut Game) -> Result<(), GameError>
     {
-        if self.current_state != State::ActionRound
-        {
-            return Err(GameError{
-                kind: ErrorKind::InvalidStateAction,
-                msg: String::from("The game is not in the character turn phase.  You cannot begin an initiative turn.")
-            })
-        }
+        self.open_pass(false)
+    }
+
+    // A pass the GM opens starts the turn history afresh.  One that opens by itself as the last one ends keeps it, so a rewind can still
+    // reach back over the join into the pass before.
+    fn open_pass(self: &mut Game, keep_history: bool) -> Result<(), GameError>
+    {
+        self.require_phase(Gated::OpenPass)?;
 
         if self.current_turn_id.len() > 0
         {
@@ -615,7 +1481,15 @@ impl Game {
         {
             PassState::Ready => 
             {
-                return self.initialize_initiatives();
+                // Action budgets are granted per initiative pass, not per combat round.
+                self.reset_actions();
+                self.initialize_initiatives()?;
+                self.fire_triggers();
+                if !keep_history
+                {
+                    self.turn_history.clear();
+                }
+                return self.pass_over_sitting_out().or_else(Game::pass_over_ended);
             },
             PassState::AllDone =>
             {
@@ -631,13 +1505,7 @@ impl Game {
 
     pub fn advance_round(self: &mut Game) -> Result<(), GameError>
     {
-        if self.current_state != State::ActionRound
-        {
-            return Err(GameError{
-                kind: ErrorKind::InvalidStateAction,
-                msg: String::from(format!("The game is not in the character turn phase.  You cannot advance the action in this way."))
-            })
-        }
+        self.require_phase(Gated::AdvanceTurn)?;
 
         // Make sure all current characters have signalled they are done
         if self.unresolved_turn()
@@ -660,9 +1528,9 @@ impl Game {
         {
             self.next_initiative = on_deck.1;
             self.next_id.push(on_deck.0);
-            while let PassState::Next(_) = self.init_tracker.next_if_match(self.next_initiative)
+            while let PassState::Next(same_turn) = self.init_tracker.next_if_match(self.next_initiative)
             {
-                self.next_id.push(on_deck.0);
+                self.next_id.push(same_turn.0);
             }
         }
         else
@@ -672,66 +1540,352 @@ impl Game {
 
         if self.current_turn_id.len() == 0
         {
+            // With pass cycling on, the next pass starts straight away and only the end of the whole round comes back to the GM.
+            if self.auto_cycle_passes && self.after_pass() == Some(AfterPass::NextPass)
+            {
+                return self.open_pass(true);
+            }
+
             return Err(GameError::new(ErrorKind::EndOfInitiative, String::from("End of initiative order.")))
         }
 
+        self.pass_over_sitting_out()
+    }
+
+    // Resolves everyone up this turn who is down, delaying or away, and moves the turn on if that leaves nobody to wait for.  Otherwise
+    // the turn is ready to be played, and to be rewound to.
+    fn pass_over_sitting_out(&mut self) -> Result<(), GameError>
+    {
+        if self.skip_sitting_out()
+        {
+            return self.advance_round();
+        }
+
+        self.turn_history.push(self.snapshot());
         Ok(())
     }
 
-    fn unresolved_turn(&mut self) -> bool
+    // A pass that opens with everyone up sitting out can run out before anyone acts; that is not an error to whoever opened it.
+    fn pass_over_ended(err: GameError) -> Result<(), GameError>
     {
-        for id in &self.current_turn_id
+        match err.kind
         {
-            if let Some(combat_data) = self.combatant_data.get(&id)
+            ErrorKind::EndOfInitiative => Ok(()),
+            _ => Err(err),
+        }
+    }
+
+    // Returns whether passing over the sitting out left nobody up this turn to wait for.
+    fn skip_sitting_out(&mut self) -> bool
+    {
+        let sitting_out = self.current_turn_id.iter()
+            .filter(|id| self.combatant_data.get(id).map_or(false, |data| !data.has_resolved))
+            .filter_map(|id| self.skip_reason(id).map(|reason| (*id, reason)))
+            .collect::<Vec<(Uuid, SkipReason)>>();
+        if sitting_out.is_empty()
+        {
+            return false;
+        }
+
+        for (id, reason) in sitting_out
+        {
+            debug!("{} is passed over: {:?}.", id, reason);
+            self.combatant_data.get_mut(&id).unwrap().resolve();
+            self.skipped_turns.push((id, reason));
+        }
+
+        !self.unresolved_turn()
+    }
+
+    // Why the character would be passed over when their turn comes up, if they would be.  A mark the GM or player set wins over the
+    // character's wounds.
+    pub fn skip_reason(self: &Game, character_id: &Uuid) -> Option<SkipReason>
+    {
+        if let Some(reason) = self.sitting_out.get(character_id)
+        {
+            return Some(*reason);
+        }
+
+        self.cast.get(character_id).filter(|character| character.condition_monitor().tier() == WoundTier::Down).map(|_| SkipReason::Unconscious)
+    }
+
+    // Marks the character as sitting out their turns, or clears the mark with None.  Anyone marked who is up right now is passed over at
+    // once; returns whether that moved the turn on.
+    pub fn sit_out(self: &mut Game, character_id: Uuid, reason: Option<SkipReason>) -> Result<bool, GameError>
+    {
+        if !self.cast.contains_key(&character_id)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        }
+
+        match reason
+        {
+            Some(reason) => { self.sitting_out.insert(character_id, reason); },
+            None => { self.sitting_out.remove(&character_id); },
+        }
+
+        if self.current_state == Phase::ActionRound && self.current_turn_id.contains(&character_id) && self.skip_sitting_out()
+        {
+            return self.advance_round().or_else(Game::pass_over_ended).map(|_| true);
+        }
+
+        Ok(false)
+    }
+
+    // The turns passed over since this was last called, in the order they came up.
+    pub fn take_skipped_turns(self: &mut Game) -> Vec<(Uuid, SkipReason)>
+    {
+        std::mem::take(&mut self.skipped_turns)
+    }
+
+    fn snapshot(self: &Game) -> GameSnapshot
+    {
+        GameSnapshot 
+        { 
+            current_state: self.current_state, 
+            cast: self.cast.clone(), 
+            init_tracker: self.init_tracker.dump(), 
+            current_turn_id: self.current_turn_id.clone(), 
+            next_id: self.next_id.clone(), 
+            current_initiative: self.current_initiative, 
+            next_initiative: self.next_initiative, 
+            round: self.round, 
+            combatant_data: self.combatant_data.clone(), 
+            movement_ledger: self.movement_ledger.clone(), 
+            engagements: self.engagements.clone(), 
+            pending_resistance: self.pending_resistance.clone(), 
+            timed_events: self.timed_events.clone(), 
+            events_in_order: self.events_in_order.clone(), 
+            triggers: self.triggers.clone(), 
+            sustained_spells: self.sustained_spells.clone(), 
+            matrix: self.matrix.clone(), 
+        }
+    }
+
+    fn restore(self: &mut Game, snapshot: GameSnapshot)
+    {
+        self.current_state = snapshot.current_state;
+        self.cast = snapshot.cast;
+        self.init_tracker.restore(snapshot.init_tracker);
+        self.current_turn_id = snapshot.current_turn_id;
+        self.next_id = snapshot.next_id;
+        self.current_initiative = snapshot.current_initiative;
+        self.next_initiative = snapshot.next_initiative;
+        self.round = snapshot.round;
+        self.combatant_data = snapshot.combatant_data;
+        self.movement_ledger = snapshot.movement_ledger;
+        self.engagements = snapshot.engagements;
+        self.pending_resistance = snapshot.pending_resistance;
+        self.timed_events = snapshot.timed_events;
+        self.events_in_order = snapshot.events_in_order;
+        self.triggers = snapshot.triggers;
+        self.sustained_spells = snapshot.sustained_spells;
+        self.matrix = snapshot.matrix;
+    }
+
+    // Staging under a name that is already in use replaces that encounter.
+    pub fn stage_encounter(self: &mut Game, encounter: StagedEncounter) -> Result<(), GameError>
+    {
+        if let Some(unknown) = encounter.referenced_ids().find(|id| !self.cast.contains_key(id))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} in encounter {} does not match any cast member.", unknown, encounter.name))));
+        }
+
+        self.staged_encounters.retain(|staged| staged.name != encounter.name);
+        self.staged_encounters.push(encounter);
+
+        Ok(())
+    }
+
+    pub fn staged_encounters(self: &Game) -> Vec<StagedEncounter>
+    {
+        self.staged_encounters.clone()
+    }
+
+    // Starts combat with the staged combatants, modifiers and hidden NPCs, and takes the encounter off the staging list.
+    pub fn launch_encounter(self: &mut Game, name: &str) -> Result<StagedEncounter, GameError>
+    {
+        self.require_phase(Gated::LaunchEncounter)?;
+
+        let Some(index) = self.staged_encounters.iter().position(|staged| staged.name == name)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownEncounter, String::from(format!("There is no staged encounter named {}.", name))));
+        };
+
+        // A character may have been retired since the encounter was staged.
+        self.add_combatants(self.staged_encounters[index].combatants.clone())?;
+        let encounter = self.staged_encounters.remove(index);
+        self.initiative_adjustments = encounter.initiative_modifiers.clone();
+        self.hidden = encounter.hidden.iter().copied().collect();
+        self.triggers = encounter.triggers.clone();
+
+        Ok(encounter)
+    }
+
+    pub fn current_round(self: &Game) -> usize
+    {
+        self.round
+    }
+
+    // The triggers that have fired since this was last called, in the order they fired.
+    pub fn take_fired_triggers(self: &mut Game) -> Vec<ScriptedTrigger>
+    {
+        std::mem::take(&mut self.fired_triggers)
+    }
+
+    // Fires every armed trigger the fight has reached, as a round or pass begins.  Reinforcements no longer in the cast, or already
+    // fighting, are passed over.
+    fn fire_triggers(&mut self)
+    {
+        let (round, pass) = (self.round, self.current_pass() + 1);
+        let (due, armed): (Vec<ScriptedTrigger>, Vec<ScriptedTrigger>) = self.triggers.drain(..)
+            .partition(|trigger| trigger.round < round || (trigger.round == round && trigger.pass <= pass));
+        self.triggers = armed;
+
+        for trigger in due
+        {
+            if let TriggerAction::Reinforcements(characters) = &trigger.action
             {
-                if !combat_data.has_resolved
+                let arriving = characters.iter().copied().filter(|character| !self.combatant_data.contains_key(character)).collect::<Vec<Uuid>>();
+                for character in arriving
                 {
-                    return true;
+                    let _ = self.add_combatant(character);
                 }
             }
+            debug!("Trigger for round {} pass {} fired in round {} pass {}.", trigger.round, trigger.pass, round, pass);
+            self.fired_triggers.push(trigger);
         }
-
-        return false;
     }
 
-    pub fn take_action(self: &mut Game, actor: Uuid, action_type: ActionType) -> Result<(), GameError>
+    pub fn is_hidden(self: &Game, character_id: &Uuid) -> bool
     {
+        self.hidden.contains(character_id)
+    }
 
-        if self.current_state != State::ActionRound
+    // Saving under a name that is already in use replaces that checkpoint.  A game already holding CHECKPOINT_CAPACITY of them loses
+    // its oldest.
+    pub fn save_checkpoint(self: &mut Game, name: &str)
+    {
+        let snapshot = self.snapshot();
+        self.checkpoints.retain(|(existing, _)| existing != name);
+        if self.checkpoints.len() >= CHECKPOINT_CAPACITY
         {
-            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("The game is not in the character turn phase.  You cannot take an action."))));
+            self.checkpoints.remove(0);
         }
+        self.checkpoints.push((String::from(name), snapshot));
+    }
 
-        // Rules for taking action: 
-        // If it is the current initiative of the actor trying to act, then the actor may attempt to perform any of their actions.
-        // if it is NOT the current initiative of the actor trying to act, they may only take free actions.
+    pub fn checkpoints(self: &Game) -> Vec<String>
+    {
+        self.checkpoints.iter().map(|(name, _)| name.clone()).collect()
+    }
 
-        // So - get the actors for the current initiative out
-        // let result = self.initiative_player_map.get_mut(&self.current_initiative);
-        if !self.combatant_data.contains_key(&actor)
+    // The checkpoint is kept, so the same one can be restored again.  Turn history starts over from the restored state.
+    pub fn restore_checkpoint(self: &mut Game, name: &str) -> Result<(), GameError>
+    {
+        let Some(snapshot) = self.checkpoints.iter().find(|(existing, _)| existing == name).map(|(_, snapshot)| snapshot.clone())
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCheckpoint, String::from(format!("There is no checkpoint named {}.", name))));
+        };
+
+        self.restore(snapshot);
+        self.turn_history.clear();
+        if self.current_state == Phase::ActionRound
         {
-            return Err(GameError::new(
-                ErrorKind::EndOfInitiative,
-                String::from(format!("The current initiative value {} does not map to any valid combatants.", self.current_initiative))
-            ))
+            self.turn_history.push(self.snapshot());
         }
+        self.journal.record(JournalEvent::CheckpointRestored(String::from(name)));
 
-        // let current_combatants = result.unwrap();
-        
+        Ok(())
+    }
 
-        if self.current_turn_id.contains(&actor) || action_type == ActionType::Free
+    // Puts the fight back the way it was at the start of the pass, or at the start of the turn before this one.  Damage, actions and
+    // movement since then are undone; the journal is not, but records the rewind.
+    pub fn rewind(self: &mut Game, target: RewindTarget) -> Result<(), GameError>
+    {
+        self.require_phase(Gated::Rewind)?;
+
+        let keep = match target
         {
-            match self.combatant_data.entry(actor)
+            // The history can run back into earlier passes that cycled on by themselves; the start of this one is its first turn in it.
+            RewindTarget::PassStart => self.turn_history.iter().position(|snapshot| snapshot.init_tracker.current_pass == self.current_pass())
+                .map_or(1, |start| start + 1),
+            RewindTarget::PreviousTurn if self.turn_history.len() > 1 => self.turn_history.len() - 1,
+            RewindTarget::PreviousTurn => 
             {
-                Entry::Occupied(mut entry) => 
-                {
-                    let combat_data = entry.get_mut();
+                return Err(GameError::new(ErrorKind::NothingToRewind, String::from("This is the first turn of the pass - there is no earlier turn to rewind to.")));
+            },
+        };
 
-                    if action_type != ActionType::Free && combat_data.has_resolved
-                    {
-                        return Err(GameError::new
-                        (
-                            ErrorKind::NoAction,
+        self.turn_history.truncate(keep);
+        let Some(snapshot) = self.turn_history.last().cloned()
+        else {
+            return Err(GameError::new(ErrorKind::NothingToRewind, String::from("No turns have been recorded this pass.")));
+        };
+
+        self.restore(snapshot);
+        self.journal.record(JournalEvent::Rewound(target));
+        debug!("Rewound to {:?}; {} turns remain in the history.", target, self.turn_history.len());
+
+        Ok(())
+    }
+
+    fn unresolved_turn(&self) -> bool
+    {
+        for id in &self.current_turn_id
+        {
+            if let Some(combat_data) = self.combatant_data.get(&id)
+            {
+                if !combat_data.has_resolved
+                {
+                    return true;
+                }
+            }
+        }
+
+        return false;
+    }
+
+    pub fn take_action(self: &mut Game, actor: Uuid, action_type: ActionType) -> Result<(), GameError>
+    {
+        self.spend_action(actor, action_type, None)
+    }
+
+    fn spend_action(self: &mut Game, actor: Uuid, action_type: ActionType, name: Option<String>) -> Result<(), GameError>
+    {
+
+        self.require_phase(Gated::TakeAction)?;
+
+        // Rules for taking action: 
+        // If it is the current initiative of the actor trying to act, then the actor may attempt to perform any of their actions.
+        // if it is NOT the current initiative of the actor trying to act, they may only take free actions.
+
+        // So - get the actors for the current initiative out
+        // let result = self.initiative_player_map.get_mut(&self.current_initiative);
+        if !self.combatant_data.contains_key(&actor)
+        {
+            return Err(GameError::new(
+                ErrorKind::EndOfInitiative,
+                String::from(format!("The current initiative value {} does not map to any valid combatants.", self.current_initiative))
+            ))
+        }
+
+        // let current_combatants = result.unwrap();
+        
+
+        if self.current_turn_id.contains(&actor) || action_type == ActionType::Free
+        {
+            match self.combatant_data.entry(actor)
+            {
+                Entry::Occupied(mut entry) => 
+                {
+                    let combat_data = entry.get_mut();
+
+                    if action_type != ActionType::Free && combat_data.has_resolved
+                    {
+                        return Err(GameError::new
+                        (
+                            ErrorKind::NoAction,
                             String::from("You've already resolved your allowed action.")
                         ))
                     }
@@ -769,7 +1923,7 @@ impl Game {
                             }
                         },
                         ActionType::Complex => {
-                            if combat_data.simple_actions < 2 {
+                            if combat_data.simple_actions < combat_data.budget.simple {
                                 return Err(GameError::new
                                 (
                                     ErrorKind::NoAction,
@@ -810,1851 +1964,4838 @@ impl Game {
                 String::from(format!("It is not character {}'s turn.", actor))
             ));
         }
-        
 
+        self.journal.record(JournalEvent::ActionTaken { character: actor, action: action_type, name, pass: self.current_pass(), initiative: self.current_initiative });
         
         Ok(())
     }
 
-    fn reset_actions(&mut self)
+    // Passes over a combatant who is up this turn without spending any of their actions, for when nobody is there to take them.
+    pub fn skip_turn(self: &mut Game, actor: Uuid) -> Result<(), GameError>
     {
-        for (_id, data) in &mut self.combatant_data
+        self.require_phase(Gated::SkipTurn)?;
+
+        if !self.current_turn_id.contains(&actor)
         {
-            data.reset();
+            return Err(GameError::new(ErrorKind::UnresolvedCombatant, format!("It is not character {}'s turn.", actor)));
         }
-    }
 
-}
+        match self.combatant_data.get_mut(&actor)
+        {
+            Some(combat_data) => combat_data.resolve(),
+            None => return Err(GameError::new(ErrorKind::UnknownCastId, format!("The combat data for combatant {} was not recorded.", actor))),
+        }
 
-pub struct CharacterCombatData {
-    declared_initiative: bool,
-    initiative_passes: usize,
-    astral_passes: usize,
-    matrix_passes: usize,
-    // actions: HashMap<ActionType, usize>,
-    free_actions: usize,
-    simple_actions: usize,
-    complex_actions: usize,
-    has_resolved: bool,
+        Ok(())
+    }
 
-}
+    // None while anyone is still up or on deck in the current pass.
+    pub fn after_pass(self: &Game) -> Option<AfterPass>
+    {
+        if !self.in_phase(Gated::TurnOrder) || !self.current_turn_id.is_empty() || !self.next_id.is_empty() 
+            || !self.init_tracker.get_ordered_inits().is_empty()
+        {
+            return None;
+        }
 
-impl CharacterCombatData {
-    pub fn new()->CharacterCombatData {
-        CharacterCombatData 
-        { 
-            declared_initiative: false,
-            initiative_passes: 0, 
-            astral_passes: 3,
-            matrix_passes: 3,
-            free_actions: 1, 
-            simple_actions: 2, 
-            complex_actions: 1, 
-            // actions: HashMap::new(),
-            has_resolved: false,
+        if self.init_tracker.held_over().next().is_some()
+        {
+            Some(AfterPass::NextPass)
+        }
+        else
+        {
+            Some(AfterPass::NextRound)
         }
     }
 
-    pub fn reset(self: &mut CharacterCombatData) {
-        self.free_actions = 1;
-        self.simple_actions = 2;
-        self.complex_actions = 1;
-        self.has_resolved = false;
+    pub fn set_auto_advance(self: &mut Game, on: bool)
+    {
+        self.auto_advance = on;
     }
 
-    pub fn resolve(self: &mut CharacterCombatData) {
-        self.has_resolved = true;
+    pub fn auto_advance(self: &Game) -> bool
+    {
+        self.auto_advance
     }
-}
-
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
-enum State {
-    PreCombat,
-    Initiative,
-    ActionRound,
-    // PostRound,
-    // Other,
-}
 
-impl State {
-    pub fn to_string(self: &State) -> String
+    pub fn set_auto_cycle_passes(self: &mut Game, on: bool)
     {
-        match self {
-            State::PreCombat => String::from("PreCombat"),
-            State::Initiative => String::from("Initiative Rolls"),
-            State::ActionRound => String::from("Initiative Pass"),
-            // State::PostRound => String::from("End Of Round"),
-            // State::Other => String::from("Other"),
-        }
+        self.auto_cycle_passes = on;
     }
-}
-
-#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
-pub enum ActionType {
-    Free = 0,
-    Simple = 1,
-    Complex = 2
-}
-
-#[derive(Debug)]
-pub struct GameError {
-    pub kind: ErrorKind,
-    pub msg: String,
-}
 
-impl GameError {
-    pub fn new(kind: ErrorKind, msg: String) -> GameError
+    pub fn auto_cycle_passes(self: &Game) -> bool
     {
-        GameError{kind, msg}
+        self.auto_cycle_passes
     }
-}
-
-#[derive(Debug)]
-pub enum ErrorKind {
-    InvalidStateAction,
-    UnknownCastId,
-    EndOfInitiative,
-    EndOfInitiativePass,
-    NoAction,
-    GameStateInconsistency,
-    UnresolvedCombatant,
-}
-
-#[derive(Debug)]
-pub enum GameValue {
-    PlayerId(Uuid),
-    CurrentState(String),
-}
-
-
-#[cfg(test)]
-mod tests
-{
-    use uuid::Uuid;
-
-    use crate::tracker::{game::{ActionType}, character::{Character, Metatypes}};
 
-    use super::Game;
+    pub fn set_visibility(self: &mut Game, options: VisibilityOptions)
+    {
+        self.visibility = options;
+    }
 
-    pub fn init() {
-        let _ = env_logger::builder().is_test(true).try_init();
+    pub fn visibility(self: &Game) -> VisibilityOptions
+    {
+        self.visibility
     }
 
-    macro_rules! populate {
-        ($game:expr, $($char:expr),*) => {
+    // A change mid-fight reaches the combatants' action budgets from the next pass and their passes from the next roll.
+    pub fn set_edition(self: &mut Game, edition: Edition)
+    {
+        self.edition = edition;
+        for (id, data) in &mut self.combatant_data
+        {
+            if let Some(character) = self.cast.get(id)
             {
-                let local_game: &mut Game = $game;
-                let mut ids = Vec::<Uuid>::new();
-
-                $(let char_id:Uuid = local_game.add_cast_member($char); if let Err(_) = local_game.add_combatant(char_id) {panic!();}; ids.push(char_id);)*
-
-                ids
+                data.budget = rules::actions_per_pass(edition, character);
+                data.derive_passes(edition, character);
             }
-        };
+        }
     }
 
-    fn build_orc()->Character
+    pub fn edition(self: &Game) -> Edition
     {
-        Character::new_pc(Metatypes::Orc, String::from("Zorc"))
+        self.edition
     }
 
-    fn build_elf()->Character
+    // None goes back to the edition's names.
+    pub fn set_action_labels(self: &mut Game, labels: Option<ActionLabels>)
     {
-        Character::new_pc(Metatypes::Elf, String::from("Lef"))
+        self.action_labels = labels;
     }
 
-    fn build_dwarf()->Character
+    pub fn action_labels(self: &Game) -> ActionLabels
     {
-        Character::new_npc(Metatypes::Dwarf, String::from("Dorf"))
+        self.action_labels.clone().unwrap_or_else(|| rules::action_labels(self.edition))
     }
 
-
-    #[test]
-    pub fn when_a_game_is_built_it_starts_in_pre_combat_state_with_empty_cast_and_combat_sets()
+    pub fn define_catalog_action(self: &mut Game, action: CatalogAction) -> Result<(), GameError>
     {
-        let mut game = Game::new();
-
-        assert_eq!(game.current_state(), String::from("PreCombat"));
-        assert_eq!(game.waiting_for(), None);
-        assert_eq!(game.on_deck(), None);
-        assert_eq!(game.get_combatants().len(), 0);
+        self.action_catalog.define(action).map_err(|msg| GameError::new(ErrorKind::InvalidCatalogAction, msg))
     }
 
-    #[test]
-    pub fn adding_a_cast_member_to_a_game_increases_the_cast_size_by_one()
+    pub fn remove_catalog_action(self: &mut Game, name: &str) -> Result<(), GameError>
     {
+        if !self.action_catalog.remove(name)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCatalogAction, String::from(format!("There is no cataloged action named {}.", name))));
+        }
 
-        let cast_member = Character::new_pc(Metatypes::Human, String::from("Demo"));
-        let mut game: Game = Game::new();
-
-        let pre_add_size = game.cast_size();
-        game.add_cast_member(cast_member);
+        Ok(())
+    }
 
-        assert_eq!(game.cast_size(), pre_add_size + 1);
-        
+    pub fn action_catalog(self: &Game) -> Vec<CatalogAction>
+    {
+        self.action_catalog.list()
     }
 
-    #[test]
-    pub fn removing_a_cast_member_decreases_cast_size_by_one()
+    // Spends what the cataloged action costs, exactly as take_action would, and journals it under its name.  An action that fires or
+    // reloads works on the actor's readied weapon; an attack with nothing left in the gun is refused without spending anything.
+    pub fn take_catalog_action(self: &mut Game, actor: Uuid, name: &str) -> Result<CatalogAction, GameError>
     {
-        let cast_member = Character::new_pc(Metatypes::Elf, String::from("Delfmo"));
-        //  cast_member.id;
-        let mut game: Game = Game::new();
+        let Some(action) = self.action_catalog.get(name).cloned()
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCatalogAction, String::from(format!("There is no cataloged action named {}.", name))));
+        };
 
-        let id = game.add_cast_member(cast_member);
+        let rearmed = match action.ammunition
+        {
+            Ammunition::Untouched => None,
+            ammunition => Some(self.feed_readied_weapon(actor, ammunition)?),
+        };
 
-        let pre_remove_size = game.cast_size();
-        game.retire_cast_member(id);
-        assert_eq!(game.cast_size(), pre_remove_size - 1); 
+        self.spend_action(actor, action.cost, Some(action.name.clone()))?;
+        if let Some(character) = rearmed
+        {
+            self.cast.insert(actor, character);
+        }
+        Ok(action)
     }
 
-    #[test]
-    pub fn all_cast_members_uuids_can_be_retrieved_at_any_time()
+    // The actor as they will be once the ammunition is used, left for the caller to store once the action itself has gone through.
+    fn feed_readied_weapon(self: &Game, actor: Uuid, ammunition: Ammunition) -> Result<Arc<Character>, GameError>
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        let Some(mut character) = self.cast.get(&actor).cloned()
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", actor))));
+        };
 
-        mork.player_character = true;
-        dorf.player_character = false;
-        melf.player_character = true;
+        let character_mut = Arc::make_mut(&mut character);
+        let index = character_mut.current_weapon_index;
+        let Some(feature) = Arc::make_mut(&mut character_mut.weapons).get_mut(index).and_then(Weapon::selected_feature_mut)
+        else {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} has no firearm readied.", actor))));
+        };
 
-        let mut game = Game::new();
+        match ammunition
+        {
+            Ammunition::Untouched => {},
+            Ammunition::Fires(_) if feature.loaded <= 0 =>
+            {
+                return Err(GameError::new(ErrorKind::OutOfAmmo, String::from(format!("Character {}'s weapon is empty and must be reloaded.", actor))));
+            },
+            Ammunition::Fires(rounds) =>
+            {
+                debug!("{} fires {} of the {} rounds loaded.", actor, rounds.min(feature.loaded), feature.loaded);
+                feature.loaded = (feature.loaded - rounds).max(0);
+            },
+            Ammunition::Reloads => feature.loaded = feature.reload_size,
+        }
 
-        let mork_id = game.add_cast_member(mork);
-        let dorf_id = game.add_cast_member(dorf);
-        let melf_id = game.add_cast_member(melf);
+        Ok(character)
+    }
 
-        let cast = game.get_cast();
-        let ids = vec![mork_id, dorf_id, melf_id];
+    // What a reader is told of a character's wounds, or None if they are told nothing.  The GM and every player character's wounds are
+    // shown exactly; an NPC's are shown as far as the visibility options allow, and not at all if it was staged hidden.
+    pub fn wounds_seen_by(self: &Game, character_id: &Uuid, gm_view: bool) -> Option<WoundView>
+    {
+        let character = self.cast.get(character_id)?;
+        let monitor = character.condition_monitor();
+        if gm_view || character.player_character
+        {
+            return Some(WoundView::Exact(monitor));
+        }
+        if self.is_hidden(character_id)
+        {
+            return None;
+        }
 
-        assert!(cast.len() == 3);
-        assert!(ids.contains(&cast.get(0).unwrap().id));
-        assert!(ids.contains(&cast.get(1).unwrap().id));
-        assert!(ids.contains(&cast.get(2).unwrap().id));
-        
+        match self.visibility.npc_wounds
+        {
+            WoundDisclosure::Exact => Some(WoundView::Exact(monitor)),
+            WoundDisclosure::Tiers => Some(WoundView::Tier(monitor.tier())),
+            WoundDisclosure::Hidden => None,
+        }
     }
 
-    #[test]
-    pub fn get_cast_generates_empty_vec_if_no_characters_added()
+    // Where each combatant stands - turn, status and wounds - as the GM or the table sees it, in a stable order.  The table's view leaves
+    // out anyone staged hidden.
+    pub fn combatant_states(self: &Game, gm_view: bool) -> Vec<CombatantState>
     {
-        let game = Game::new();
+        let mut ids = self.combatant_data.keys().filter(|id| gm_view || !self.is_hidden(id)).collect::<Vec<&Uuid>>();
+        ids.sort();
 
-        let ids = game.get_cast();
-        assert!(ids.is_empty());
+        ids.into_iter().filter_map(|id| self.cast.get(id)).map(|character| {
+            let summary = self.summarize(Arc::clone(character), gm_view);
+            CombatantState { character: character.id, turn: summary.turn, status: summary.status, wounds: self.wounds_seen_by(&character.id, gm_view) }
+        }).collect()
+    }
+
+    // With auto-advance on, moves the turn on if nobody up this turn is left to resolve.  Returns whether it did.
+    pub fn advance_if_resolved(self: &mut Game) -> bool
+    {
+        if !self.auto_advance || !self.in_phase(Gated::TurnOrder) || self.current_turn_id.is_empty() || self.unresolved_turn()
+        {
+            return false;
+        }
+
+        match self.advance_round()
+        {
+            Ok(()) | Err(GameError { kind: ErrorKind::EndOfInitiative, .. }) => true,
+            Err(_) => false,
+        }
+    }
+
+    // One player's turns for several characters tied on the current initiative, taken together: every action is checked and applied or
+    // none of them are.  If that resolves everyone up this turn, the initiative moves on as though the GM had advanced it.  Returns
+    // whether it did.
+    pub fn take_turns(self: &mut Game, actions: &[(Uuid, ActionType)]) -> Result<bool, GameError>
+    {
+        self.require_phase(Gated::TakeAction)?;
+
+        if let Some((actor, _)) = actions.iter().find(|(actor, _)| !self.current_turn_id.contains(actor))
+        {
+            return Err(GameError::new(ErrorKind::UnresolvedCombatant, String::from(format!("It is not character {}'s turn, so it cannot act in a batch.", actor))));
+        }
+
+        let before = self.clone();
+        for (actor, action_type) in actions
+        {
+            if let Err(err) = self.take_action(*actor, *action_type)
+            {
+                *self = before;
+                return Err(err);
+            }
+        }
+
+        if actions.is_empty() || self.unresolved_turn()
+        {
+            return Ok(false);
+        }
+
+        // Running out of initiative for the pass still moves the turn on; the GM starts the next pass.
+        match self.advance_round()
+        {
+            Ok(()) | Err(GameError { kind: ErrorKind::EndOfInitiative, .. }) => Ok(true),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn declare_movement(self: &mut Game, mover: Uuid, meters: u16, sprint_hits: u8) -> Result<Gait, GameError>
+    {
+        self.require_phase(Gated::Move)?;
+
+        let rates = match self.cast.get(&mover)
+        {
+            Some(character) => rules::movement_rates(self.edition, character),
+            None => {
+                return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", mover))));
+            }
+        };
+
+        if !self.current_turn_id.contains(&mover)
+        {
+            return Err(GameError::new(ErrorKind::UnresolvedCombatant, String::from(format!("It is not character {}'s turn.", mover))));
+        }
+
+        let Some(combat_data) = self.combatant_data.get_mut(&mover)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The combat data for combatant {} was not recorded.", mover))));
+        };
+
+        // Saturated, so a wild distance is refused as out of reach rather than wrapping round to a short one.
+        let total = combat_data.meters_moved.saturating_add(meters);
+        let Some(gait) = rates.gait_for(total, sprint_hits)
+        else {
+            return Err(GameError::new(
+                ErrorKind::ExceedsMovementRate, 
+                String::from(format!("Moving {}m this turn exceeds the character's running rate of {}m.", total, rates.run))
+            ));
+        };
+
+        combat_data.meters_moved = total;
+        combat_data.gait = gait;
+        self.movement_ledger.push(MovementRecord { character_id: mover, meters, total_this_turn: total, gait, pass: self.init_tracker.current_pass() });
+
+        Ok(gait)
+    }
+
+    pub fn movement_ledger(self: &Game) -> Vec<MovementRecord>
+    {
+        self.movement_ledger.clone()
+    }
+
+    pub fn running_modifier(self: &Game, combatant: Uuid) -> i8
+    {
+        self.combatant_data.get(&combatant).map_or(0, |data| rules::running_modifier(self.edition, data.gait))
+    }
+
+    pub fn ranged_attack_modifiers(self: &Game, attack: &RangedAttack) -> Result<AttackModifiers, GameError>
+    {
+        self.require_phase(Gated::Attack)?;
+
+        let Some(attacker) = self.cast.get(&attack.attacker)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", attack.attacker))));
+        };
+
+        if !self.combatant_data.contains_key(&attack.attacker)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", attack.attacker))));
+        }
+
+        if let Some(target) = attack.target
+        {
+            if !self.cast.contains_key(&target)
+            {
+                return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The target id {} does not match any cast member.", target))));
+            }
+        }
+
+        let recoil_comp = attacker.weapons.get(attacker.current_weapon_index)
+            .and_then(Weapon::selected_feature)
+            .map_or(0, |feature| feature.recoil_comp);
+
+        let modifiers = AttackModifiers::new(self.edition, attack, recoil_comp, attacker.stat("Strength"), self.running_modifier(attack.attacker), 
+            self.sustaining_modifier(attack.attacker));
+        debug!("Ranged attack by {}: range {}, lighting {}, cover {}, recoil {}, running {}, sustaining {} for a total of {}.", 
+            attack.attacker, modifiers.range, modifiers.lighting, modifiers.cover, modifiers.recoil, modifiers.running, 
+            modifiers.sustaining, modifiers.total);
+
+        Ok(modifiers)
+    }
+
+    pub fn engage(self: &mut Game, attacker: Uuid, defender: Uuid) -> Result<Engagement, GameError>
+    {
+        if attacker == defender
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("A character cannot engage themselves in melee.")));
+        }
+
+        if !self.combatant_data.contains_key(&defender)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", defender))));
+        }
+
+        if self.are_engaged(attacker, defender)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("Those characters are already engaged in melee.")));
+        }
+
+        // Closing to melee costs an action - take_action does the state, turn and budget checks for us.
+        self.take_action(attacker, rules::action_cost(self.edition, Maneuver::Engage))?;
+        self.engagements.push((attacker, defender));
+
+        Ok(self.to_engagement(attacker, defender))
+    }
+
+    pub fn disengage(self: &mut Game, character: Uuid, from: Uuid) -> Result<(), GameError>
+    {
+        if !self.are_engaged(character, from)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("Those characters are not engaged in melee.")));
+        }
+
+        self.take_action(character, rules::action_cost(self.edition, Maneuver::Disengage))?;
+        self.engagements.retain(|(attacker, defender)| 
+            !((*attacker == character && *defender == from) || (*attacker == from && *defender == character)));
+
+        Ok(())
+    }
+
+    pub fn are_engaged(self: &Game, first: Uuid, second: Uuid) -> bool
+    {
+        self.engagements.iter().any(|(attacker, defender)| 
+            (*attacker == first && *defender == second) || (*attacker == second && *defender == first))
+    }
+
+    pub fn engagements(self: &Game) -> Vec<Engagement>
+    {
+        self.engagements.iter().map(|(attacker, defender)| self.to_engagement(*attacker, *defender)).collect()
+    }
+
+    fn to_engagement(self: &Game, attacker: Uuid, defender: Uuid) -> Engagement
+    {
+        let reach_of = |id: &Uuid| self.cast.get(id).map_or(0, |character| character.reach());
+
+        Engagement { attacker, defender, reach_differential: reach_of(&attacker) - reach_of(&defender) }
+    }
+
+    // Throwing or launching the ordnance is a simple action.  The scatter is rolled here, but who ends up inside the blast is the GM's
+    // call - each of them is queued up to make a damage resistance test.
+    pub fn resolve_area_attack(self: &mut Game, attack: &AreaAttack) -> Result<AreaResolution, GameError>
+    {
+        if let Some(unknown) = attack.in_blast.iter().find(|id| !self.cast.contains_key(id))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} in the blast radius does not match any cast member.", unknown))));
+        }
+
+        self.take_action(attack.attacker, rules::action_cost(self.edition, Maneuver::AreaAttack))?;
+
+        let mut rng = rand::thread_rng();
+        let direction = dice::sum(&mut rng, 2) as u8;
+        let distance = dice::sum(&mut rng, attack.ordnance.scatter_dice());
+        let scatter = Scatter::new(direction, distance, attack.hits);
+        debug!("Area attack by {} scattered {} meters in direction {}.", attack.attacker, scatter.meters, scatter.direction);
+
+        for character_id in &attack.in_blast
+        {
+            let soak_pool = self.cast.get(character_id).map(|character| character.soak_pool(attack.armor_test, attack.armor_pen, false));
+            self.pending_resistance.push(ResistancePrompt { character_id: *character_id, source: attack.attacker, test: ResistanceTest::Damage, soak_pool });
+        }
+
+        Ok(AreaResolution { scatter, affected: attack.in_blast.clone() })
+    }
+
+    // Queues a damage resistance test for each target, in the order the GM listed them; a target listed twice is asked once.  An
+    // unknown target turns the whole event away, so nobody is left half-hit.
+    pub fn apply_damage_event(self: &mut Game, event: &DamageEvent) -> Result<Vec<ResistancePrompt>, GameError>
+    {
+        if let Some(unknown) = event.targets.iter().find(|id| !self.cast.contains_key(id))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The target {} does not match any cast member.", unknown))));
+        }
+
+        let mut prompts: Vec<ResistancePrompt> = Vec::with_capacity(event.targets.len());
+        for character_id in &event.targets
+        {
+            if prompts.iter().any(|prompt| prompt.character_id == *character_id)
+            {
+                continue;
+            }
+            let soak_pool = self.cast.get(character_id).map(|character| character.soak_pool(event.armor_test, event.armor_pen, false));
+            prompts.push(ResistancePrompt { character_id: *character_id, source: event.source, test: ResistanceTest::Damage, soak_pool });
+        }
+        debug!("Damage from {} queued resistance tests for {} targets.", event.source, prompts.len());
+
+        self.pending_resistance.extend(prompts.iter().copied());
+        Ok(prompts)
+    }
+
+    pub fn soak_pool(self: &Game, character_id: Uuid, armor_test: ArmorTestType, armor_pen: i8, bypass_armor: bool) -> Result<u8, GameError>
+    {
+        let Some(character) = self.cast.get(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        Ok(character.soak_pool(armor_test, armor_pen, bypass_armor))
+    }
+
+    // Knocks points off one piece of the character's armor - from damage, acid, or whatever else the GM rules has chewed through it.
+    pub fn degrade_armor(self: &mut Game, character_id: Uuid, armor_index: usize, points: i8) -> Result<(), GameError>
+    {
+        let Some(character) = self.cast.get_mut(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        let Some(armour) = Arc::make_mut(&mut Arc::make_mut(character).armor).get_mut(armor_index)
+        else {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} has no armor at index {}.", character_id, armor_index))));
+        };
+
+        armour.degradation += points;
+        debug!("{}'s {} degraded by {} points.", character_id, armour.name, points);
+
+        Ok(())
+    }
+
+    pub fn pending_resistance_tests(self: &Game) -> Vec<ResistancePrompt>
+    {
+        self.pending_resistance.clone()
+    }
+
+    // Clears the oldest outstanding resistance test for the character.
+    pub fn resistance_test_made(self: &mut Game, character_id: Uuid) -> Result<ResistancePrompt, GameError>
+    {
+        let Some(index) = self.pending_resistance.iter().position(|prompt| prompt.character_id == character_id)
+        else {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} has no resistance test outstanding.", character_id))));
+        };
+
+        Ok(self.pending_resistance.remove(index))
+    }
+
+    // Casting is a complex action.  Every target owes a test to resist the spell and the caster owes a drain test, all of which go into
+    // the same queue as damage resistance so the GM can see what is still outstanding.
+    pub fn cast_spell(self: &mut Game, declaration: &SpellDeclaration) -> Result<Vec<ResistancePrompt>, GameError>
+    {
+        if declaration.force == 0
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("A spell must be cast at a force of at least 1.")));
+        }
+
+        if let Some(unknown) = declaration.targets.iter().find(|id| !self.cast.contains_key(id))
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The target id {} does not match any cast member.", unknown))));
+        }
+
+        self.take_action(declaration.caster, rules::action_cost(self.edition, Maneuver::CastSpell))?;
+        debug!("{} cast {} at force {} on {} targets.", declaration.caster, declaration.spell, declaration.force, declaration.targets.len());
+
+        let mut prompts: Vec<ResistancePrompt> = declaration.targets.iter()
+            .map(|target| ResistancePrompt { character_id: *target, source: declaration.caster, test: ResistanceTest::Spell, soak_pool: None })
+            .collect();
+        prompts.push(ResistancePrompt { character_id: declaration.caster, source: declaration.caster, test: ResistanceTest::Drain, soak_pool: None });
+        self.pending_resistance.extend(prompts.iter());
+
+        if declaration.sustained
+        {
+            self.sustained_spells.push(SustainedSpell::from_declaration(declaration));
+        }
+
+        Ok(prompts)
+    }
+
+    // Dropping a sustained spell is free, and can be done whether or not it is the caster's turn.
+    pub fn drop_spell(self: &mut Game, caster: Uuid, spell: &str) -> Result<SustainedSpell, GameError>
+    {
+        let Some(index) = self.sustained_spells.iter().position(|sustained| sustained.caster == caster && sustained.spell == spell)
+        else {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} is not sustaining {}.", caster, spell))));
+        };
+
+        Ok(self.sustained_spells.remove(index))
+    }
+
+    pub fn sustained_spells(self: &Game) -> Vec<SustainedSpell>
+    {
+        self.sustained_spells.clone()
+    }
+
+    pub fn sustaining_modifier(self: &Game, caster: Uuid) -> i8
+    {
+        rules::sustaining_modifier(self.edition, self.sustained_spells.iter().filter(|spell| spell.caster == caster).count())
+    }
+
+    pub fn register_matrix_target(self: &mut Game, target: MatrixTarget) -> Uuid
+    {
+        self.matrix.register(target)
+    }
+
+    pub fn matrix(self: &Game) -> &MatrixGrid
+    {
+        &self.matrix
+    }
+
+    // A decker who jacks in mid-round switches to their Matrix passes straight away; otherwise it takes effect with their next roll.
+    pub fn jack_in(self: &mut Game, decker: Uuid) -> Result<(), GameError>
+    {
+        if !self.cast.contains_key(&decker)
+        {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", decker))));
+        }
+
+        if !self.matrix.jack_in(decker)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} is already jacked in.", decker))));
+        }
+        self.init_tracker.login_matrix(decker);
+
+        Ok(())
+    }
+
+    pub fn jack_out(self: &mut Game, decker: Uuid) -> Result<(), GameError>
+    {
+        if !self.matrix.jack_out(decker)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} is not jacked in.", decker))));
+        }
+        self.init_tracker.logout_matrix(decker);
+
+        Ok(())
+    }
+
+    // Matrix actions come out of their own budget, on the decker's turn, and leave the physical action budget alone.
+    pub fn declare_matrix_action<R: Rng + ?Sized>(self: &mut Game, rng: &mut R, declaration: &MatrixActionDeclaration) -> Result<MatrixResolution, GameError>
+    {
+        self.require_phase(Gated::MatrixAction)?;
+
+        if !self.matrix.is_jacked_in(declaration.decker)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("Character {} is not jacked in.", declaration.decker))));
+        }
+
+        let Some(icon) = self.matrix.icon(declaration.target)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownMatrixTarget, String::from(format!("The id {} does not match any Matrix device or host.", declaration.target))));
+        };
+
+        if icon.bricked()
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("{} is bricked.", icon.target.name))));
+        }
+
+        if !self.current_turn_id.contains(&declaration.decker)
+        {
+            return Err(GameError::new(ErrorKind::UnresolvedCombatant, String::from(format!("It is not character {}'s turn.", declaration.decker))));
+        }
+
+        let Some(combat_data) = self.combatant_data.get_mut(&declaration.decker)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", declaration.decker))));
+        };
+
+        if combat_data.matrix_actions == 0
+        {
+            return Err(GameError::new(ErrorKind::NoAction, String::from("You've already taken your Matrix action this pass.")));
+        }
+
+        let attack_pool = self.cast.get(&declaration.decker)
+            .map_or(0, |decker| rules::pool(&[decker.skill(declaration.action.skill()), decker.stat("Logic")]));
+        let Some(resolution) = self.matrix.resolve(rng, declaration, attack_pool)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownMatrixTarget, String::from(format!("The id {} does not match any Matrix device or host.", declaration.target))));
+        };
+        combat_data.matrix_actions -= 1;
+
+        debug!("{} made a {:?} against {}: {} marks, {} damage.", declaration.decker, declaration.action, declaration.target, resolution.marks, resolution.damage);
+        Ok(resolution)
+    }
+
+    // Projecting takes the Magic attribute; the projection moves to astral initiative at once if the character is already in the order,
+    // otherwise from their next roll.  Returning to the body puts them back on their physical passes.
+    pub fn change_plane(self: &mut Game, character_id: Uuid, plane: Plane) -> Result<(), GameError>
+    {
+        let Some(character) = self.cast.get_mut(&character_id)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any cast member.", character_id))));
+        };
+
+        if plane == Plane::AstralProjecting && character.stat("Magic") <= 0
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from(format!("{} has no Magic and cannot project.", character.name))));
+        }
+
+        Arc::make_mut(character).plane = plane;
+        match plane
+        {
+            Plane::AstralProjecting => self.init_tracker.enter_astral_space(character_id),
+            _ => self.init_tracker.exit_astral_space(character_id),
+        };
+
+        Ok(())
+    }
+
+    pub fn plane(self: &Game, character_id: Uuid) -> Option<Plane>
+    {
+        self.cast.get(&character_id).map(|character| character.plane)
+    }
+
+    // The body of a projecting magician is left behind with nobody home, and cannot defend itself.
+    pub fn is_helpless(self: &Game, character_id: Uuid) -> bool
+    {
+        self.plane(character_id) == Some(Plane::AstralProjecting)
+    }
+
+    // Full defense is an interrupt: it can be declared at any point in the action round, and costs the defender their next complex
+    // action.  If that is still available this pass it is spent immediately, otherwise it comes out of their next turn.  The defense
+    // bonus lasts until the end of the combat turn.
+    pub fn go_full_defense(self: &mut Game, defender: Uuid) -> Result<FullDefenseCost, GameError>
+    {
+        self.require_phase(Gated::FullDefense)?;
+
+        if self.is_helpless(defender)
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("A body left behind by an astral projection cannot defend itself.")));
+        }
+
+        let Some(combat_data) = self.combatant_data.get_mut(&defender)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", defender))));
+        };
+
+        if combat_data.full_defense
+        {
+            return Err(GameError::new(ErrorKind::InvalidStateAction, String::from("The character is already on full defense.")));
+        }
+
+        combat_data.full_defense = true;
+
+        if !combat_data.has_resolved && combat_data.simple_actions == combat_data.budget.simple && combat_data.complex_actions > 0
+        {
+            combat_data.complex_actions -= 1;
+            combat_data.has_resolved = true;
+            Ok(FullDefenseCost::ThisPass)
+        }
+        else
+        {
+            combat_data.owes_full_defense = true;
+            Ok(FullDefenseCost::NextTurn)
+        }
+    }
+
+    // GM ruling on how many extra physical passes a combatant gets, overriding whatever their sheet says.  None hands the decision back
+    // to the sheet.  Takes effect from the next initiative roll the combatant makes.
+    pub fn override_initiative_passes(self: &mut Game, combatant: Uuid, passes: Option<usize>) -> Result<(), GameError>
+    {
+        let Some(combat_data) = self.combatant_data.get_mut(&combatant)
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", combatant))));
+        };
+
+        combat_data.passes_override = passes;
+        if let Some(character) = self.cast.get(&combatant)
+        {
+            combat_data.derive_passes(self.edition, character);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_on_full_defense(self: &Game, combatant: Uuid) -> bool
+    {
+        self.combatant_data.get(&combatant).map_or(false, |data| data.full_defense)
+    }
+
+    pub fn remaining_actions(self: &Game, combatant: Uuid) -> Result<ActionBudget, GameError>
+    {
+        match self.combatant_data.get(&combatant)
+        {
+            Some(combat_data) => Ok(combat_data.remaining()),
+            None => Err(GameError::new(
+                ErrorKind::UnknownCastId,
+                String::from(format!("The id {} does not match any registered combatant.", combatant))
+            ))
+        }
+    }
+
+    // Everything the combatant could do right now without being turned away, worked out from the same checks the actions themselves
+    // make.  Free actions and dropping a spell can be done out of turn; the rest wait for the combatant to be up.
+    pub fn available_actions(self: &Game, combatant: Uuid) -> Result<Vec<AvailableAction>, GameError>
+    {
+        let (Some(character), Some(combat_data)) = (self.cast.get(&combatant), self.combatant_data.get(&combatant))
+        else {
+            return Err(GameError::new(ErrorKind::UnknownCastId, String::from(format!("The id {} does not match any registered combatant.", combatant))));
+        };
+
+        let mut available = Vec::new();
+        if self.in_phase(Gated::RollInitiative) && !combat_data.declared_initiative
+        {
+            available.push(AvailableAction::RollInitiative);
+        }
+
+        if self.in_phase(Gated::TakeAction)
+        {
+            let budget = combat_data.remaining();
+            let up = self.current_turn_id.contains(&combatant);
+
+            if budget.free > 0
+            {
+                available.push(AvailableAction::Free);
+            }
+            if up && budget.simple > 0
+            {
+                available.push(AvailableAction::Simple);
+                if character.weapons.get(character.current_weapon_index).map_or(false, |weapon| !weapon.firing_features.is_empty())
+                {
+                    available.push(AvailableAction::RangedAttack);
+                }
+                available.push(AvailableAction::Engage);
+                if self.engagements.iter().any(|(attacker, defender)| *attacker == combatant || *defender == combatant)
+                {
+                    available.push(AvailableAction::Disengage);
+                }
+                available.push(AvailableAction::AreaAttack);
+            }
+            if up && budget.complex > 0
+            {
+                available.push(AvailableAction::Complex);
+                if character.stat("Magic") > 0
+                {
+                    available.push(AvailableAction::CastSpell);
+                }
+            }
+            if up
+            {
+                available.push(AvailableAction::Move);
+            }
+            if !combat_data.full_defense && !self.is_helpless(combatant)
+            {
+                available.push(AvailableAction::FullDefense);
+            }
+            if up && self.matrix.is_jacked_in(combatant) && combat_data.matrix_actions > 0
+            {
+                available.push(AvailableAction::MatrixAction);
+            }
+        }
+
+        available.extend(self.sustained_spells.iter()
+            .filter(|sustained| sustained.caster == combatant)
+            .map(|sustained| AvailableAction::DropSpell(sustained.spell.clone())));
+        available.push(if self.matrix.is_jacked_in(combatant) { AvailableAction::JackOut } else { AvailableAction::JackIn });
+        if character.plane == Plane::AstralProjecting
+        {
+            available.push(AvailableAction::ReturnToBody);
+        }
+        else if character.stat("Magic") > 0
+        {
+            available.push(AvailableAction::Project);
+        }
+        if (character.stat("Edge").max(0) as u16) > self.edge_spent(combatant)
+        {
+            available.push(AvailableAction::SpendEdge);
+        }
+
+        Ok(available)
+    }
+
+    fn reset_actions(&mut self)
+    {
+        for (_id, data) in &mut self.combatant_data
+        {
+            data.reset();
+        }
+    }
+
+    // Passes are worked out fresh from the character sheet every combat turn, so 'ware switched on or off between turns is picked up.
+    fn derive_passes(&mut self)
+    {
+        for (id, data) in &mut self.combatant_data
+        {
+            if let Some(character) = self.cast.get(id)
+            {
+                data.derive_passes(self.edition, character);
+            }
+        }
+    }
+
+    fn end_full_defense(&mut self)
+    {
+        for (_id, data) in &mut self.combatant_data
+        {
+            data.full_defense = false;
+        }
+    }
+
+    fn reset_movement(&mut self)
+    {
+        for (_id, data) in &mut self.combatant_data
+        {
+            data.meters_moved = 0;
+            data.gait = Gait::Stationary;
+        }
+        self.movement_ledger.clear();
+    }
+
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RewindTarget
+{
+    PassStart,
+    PreviousTurn,
+}
+
+// Everything a rewind or a checkpoint puts back - the whole game apart from its journal.  The cast is a map of Arcs, so holding a copy of
+// it per turn is cheap.
+#[derive(Clone)]
+// A track with boxes marked (or, given a negative count, cleared), done wide so no count can wrap it.  It never drops below empty, and
+// never fills past its length - or, on a sheet with no length given, past what the track can hold.  A track already past a length
+// lowered since is left where it is rather than pulled back by new damage.
+fn marked_track(filled: i8, max: i8, boxes: i16) -> i8
+{
+    let length = if max > 0 { max as i16 } else { i8::MAX as i16 };
+    (filled as i16 + boxes).clamp(0, length.max(filled as i16)) as i8
+}
+
+struct GameSnapshot
+{
+    current_state: Phase,
+    cast: HashMap<Uuid, Arc<Character>>,
+    init_tracker: TrackerState,
+    current_turn_id: Vec<Uuid>,
+    next_id: Vec<Uuid>,
+    current_initiative: i8,
+    next_initiative: i8,
+    round: usize,
+    combatant_data: HashMap<Uuid, CharacterCombatData>,
+    movement_ledger: Vec<MovementRecord>,
+    engagements: Vec<(Uuid, Uuid)>,
+    pending_resistance: Vec<ResistancePrompt>,
+    timed_events: Vec<TimedEvent>,
+    events_in_order: Vec<TimedEvent>,
+    triggers: Vec<ScriptedTrigger>,
+    sustained_spells: Vec<SustainedSpell>,
+    matrix: MatrixGrid,
+}
+
+#[derive(Clone)]
+pub struct CharacterCombatData {
+    declared_initiative: bool,
+    initiative_passes: usize,
+    astral_passes: usize,
+    matrix_passes: usize,
+    passes_override: Option<usize>,
+    // actions: HashMap<ActionType, usize>,
+    free_actions: usize,
+    // What the combatant gets back at the start of each pass.
+    budget: ActionBudget,
+    simple_actions: usize,
+    complex_actions: usize,
+    has_resolved: bool,
+    meters_moved: u16,
+    gait: Gait,
+    full_defense: bool,
+    owes_full_defense: bool,
+    matrix_actions: usize,
+
+}
+
+impl CharacterCombatData {
+    pub fn new()->CharacterCombatData {
+        CharacterCombatData 
+        { 
+            declared_initiative: false,
+            initiative_passes: 0, 
+            astral_passes: 0,
+            matrix_passes: 0,
+            passes_override: None,
+            free_actions: 1, 
+            budget: ActionBudget { free: 1, simple: 2, complex: 1 },
+            simple_actions: 2, 
+            complex_actions: 1, 
+            // actions: HashMap::new(),
+            has_resolved: false,
+            meters_moved: 0,
+            gait: Gait::Stationary,
+            full_defense: false,
+            owes_full_defense: false,
+            matrix_actions: MATRIX_ACTIONS_PER_PASS,
+        }
+    }
+
+    // Combat data for a specific character, with the passes and action budget their qualities, powers and 'ware entitle them to.
+    pub fn for_character(edition: Edition, character: &Character) -> CharacterCombatData {
+        let mut combat_data = CharacterCombatData::new();
+
+        combat_data.derive_passes(edition, character);
+        combat_data.budget = rules::actions_per_pass(edition, character);
+        combat_data.reset();
+
+        combat_data
+    }
+
+    pub fn derive_passes(self: &mut CharacterCombatData, edition: Edition, character: &Character) {
+        let passes = rules::passes(edition, character, self.passes_override);
+
+        self.initiative_passes = passes.extra_physical;
+        self.astral_passes = passes.astral;
+        self.matrix_passes = passes.matrix;
+    }
+
+    pub fn reset(self: &mut CharacterCombatData) {
+        self.free_actions = self.budget.free;
+        self.simple_actions = self.budget.simple;
+        self.complex_actions = self.budget.complex;
+        self.has_resolved = false;
+        self.matrix_actions = MATRIX_ACTIONS_PER_PASS;
+
+        // A full defense declared after the character's complex action was already gone is paid for out of this, their next turn.
+        if self.owes_full_defense
+        {
+            self.simple_actions = 0;
+            self.complex_actions = 0;
+            self.has_resolved = true;
+            self.owes_full_defense = false;
+        }
+    }
+
+    pub fn resolve(self: &mut CharacterCombatData) {
+        self.has_resolved = true;
+    }
+
+    // Reports what the combatant could still legally do this pass, rather than the raw counters: a complex action is off the table
+    // once a simple action has been spent, and nothing but free actions remain once the combatant has resolved.
+    pub fn remaining(self: &CharacterCombatData) -> ActionBudget {
+        if self.has_resolved
+        {
+            return ActionBudget { free: self.free_actions, simple: 0, complex: 0 };
+        }
+
+        ActionBudget 
+        { 
+            free: self.free_actions, 
+            simple: self.simple_actions, 
+            complex: if self.simple_actions < self.budget.simple { 0 } else { self.complex_actions } 
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SkipReason {
+    Unconscious,
+    Delaying,
+    // Away from the table with nobody acting for them.
+    Absent,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StatusEffect {
+    FullDefense,
+    AstralProjecting,
+    DualNatured,
+    Engaged(Uuid),
+    Sustaining(String),
+    JackedIn,
+    ResistanceTestPending,
+    Hidden,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TurnState {
+    OutOfCombat,
+    AwaitingInitiative,
+    Waiting,
+    Acting,
+    Resolved,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PatchOutcome {
+    Applied(Vec<&'static str>),
+    AwaitingApproval(Vec<&'static str>),
+}
+
+#[derive(Clone)]
+pub struct CharacterSummary {
+    pub character: Arc<Character>,
+    pub condition: ConditionMonitor,
+    pub status: Vec<StatusEffect>,
+    pub turn: TurnState,
+}
+
+// The part of a combatant's card that moves during a fight, small enough to send again every time it changes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CombatantState {
+    pub character: Uuid,
+    pub turn: TurnState,
+    pub status: Vec<StatusEffect>,
+    pub wounds: Option<WoundView>,
+}
+
+// Something that happens at a point in the initiative order without a character behind it - a bomb timer, reinforcements arriving.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimedEvent {
+    pub id: Uuid,
+    pub label: String,
+    pub initiative: i8,
+    // Round starts still to go before it enters the order; 0 means the next one.
+    pub rounds_away: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InitiativePreview {
+    pub order: Vec<(Uuid, i8)>,
+    pub outstanding: Vec<Uuid>,
+}
+
+// What comes next once nobody is left to act in the current pass.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AfterPass {
+    // Someone still has a pass left this round.
+    NextPass,
+    // Everyone has used up their passes; the next thing is a fresh initiative phase.
+    NextRound,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FullDefenseCost {
+    ThisPass,
+    NextTurn,
+}
+
+// One thing a combatant may do at the moment, roughly one per request a client would send for it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AvailableAction {
+    RollInitiative,
+    Free,
+    Simple,
+    Complex,
+    Move,
+    RangedAttack,
+    Engage,
+    Disengage,
+    AreaAttack,
+    CastSpell,
+    DropSpell(String),
+    FullDefense,
+    JackIn,
+    JackOut,
+    MatrixAction,
+    Project,
+    ReturnToBody,
+    SpendEdge,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ActionBudget {
+    pub free: usize,
+    pub simple: usize,
+    pub complex: usize,
+}
+
+// Where a game is in the flow above.  Combat always opens with initiative, and a round of passes either rolls initiative again for the
+// next round or ends.  Combat can be ended from any phase but PreCombat.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Phase {
+    PreCombat,
+    Initiative,
+    ActionRound,
+    // PostRound,
+    // Other,
+}
+
+impl Phase {
+    pub fn allowed_transitions(self: &Phase) -> Vec<Phase>
+    {
+        match self {
+            Phase::PreCombat => vec![Phase::Initiative],
+            Phase::Initiative => vec![Phase::ActionRound, Phase::PreCombat],
+            Phase::ActionRound => vec![Phase::Initiative, Phase::PreCombat],
+        }
+    }
+
+    pub fn to_string(self: &Phase) -> String
+    {
+        match self {
+            Phase::PreCombat => String::from("PreCombat"),
+            Phase::Initiative => String::from("Initiative Rolls"),
+            Phase::ActionRound => String::from("Initiative Pass"),
+            // Phase::PostRound => String::from("End Of Round"),
+            // Phase::Other => String::from("Other"),
+        }
+    }
+}
+
+// How much the players are shown of an NPC's wounds.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WoundDisclosure
+{
+    Exact,
+    Tiers,
+    Hidden,
+}
+
+// What the players are allowed to see of the GM's side of the fight.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct VisibilityOptions
+{
+    pub npc_wounds: WoundDisclosure,
+}
+
+impl Default for VisibilityOptions
+{
+    fn default() -> Self
+    {
+        VisibilityOptions { npc_wounds: WoundDisclosure::Tiers }
+    }
+}
+
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+pub enum ActionType {
+    Free = 0,
+    Simple = 1,
+    Complex = 2
+}
+
+#[derive(Debug)]
+pub struct GameError {
+    pub kind: ErrorKind,
+    pub msg: String,
+    // The phase or phases the game would have had to be in, when that is why it refused.
+    pub expected: Option<String>,
+}
+
+impl GameError {
+    pub fn new(kind: ErrorKind, msg: String) -> GameError
+    {
+        GameError{kind, msg, expected: None}
+    }
+
+    pub fn wrong_phase(expected: &[Phase], msg: String) -> GameError
+    {
+        let phases: Vec<String> = expected.iter().map(Phase::to_string).collect();
+        GameError{kind: ErrorKind::InvalidStateAction, msg, expected: Some(phases.join(" or "))}
+    }
+}
+
+// The steps the game only takes in certain phases.  Which phases those are is read off the one table in `rule` rather than checked
+// piecemeal in each method.
+#[derive(Debug, Clone, Copy)]
+enum Gated
+{
+    PreviewOrder,
+    RollInitiative,
+    OpenPass,
+    AdvanceTurn,
+    LaunchEncounter,
+    Rewind,
+    TakeAction,
+    SkipTurn,
+    Move,
+    Attack,
+    MatrixAction,
+    FullDefense,
+    TurnOrder,
+}
+
+impl Gated
+{
+    // The phases each step may be taken in, and what the table is told when it is tried in another.
+    fn rule(self: Gated) -> (&'static [Phase], &'static str)
+    {
+        match self
+        {
+            Gated::PreviewOrder => (&[Phase::Initiative], "The game is not in the initiative phase: there is no order to preview."),
+            Gated::RollInitiative => (&[Phase::Initiative], "The game is not in the initiative phase: you cannot add a new initiative roll."),
+            Gated::OpenPass => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot begin an initiative turn."),
+            Gated::AdvanceTurn => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot advance the action in this way."),
+            Gated::LaunchEncounter => (&[Phase::PreCombat], "A staged encounter can only be launched before combat has started."),
+            Gated::Rewind => (&[Phase::ActionRound], "The game is not in the character turn phase.  There is nothing to rewind."),
+            Gated::TakeAction => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot take an action."),
+            Gated::SkipTurn => (&[Phase::ActionRound], "The game is not in the character turn phase.  No turn can be skipped."),
+            Gated::Move => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot move."),
+            Gated::Attack => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot attack."),
+            Gated::MatrixAction => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot take a Matrix action."),
+            Gated::FullDefense => (&[Phase::ActionRound], "The game is not in the character turn phase.  You cannot go on full defense."),
+            Gated::TurnOrder => (&[Phase::ActionRound], "The game is not in the character turn phase.  There is no turn order."),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    InvalidStateAction,
+    UnknownCastId,
+    EndOfInitiative,
+    EndOfInitiativePass,
+    NoAction,
+    GameStateInconsistency,
+    UnresolvedCombatant,
+    ExceedsMovementRate,
+    UnknownRollMacro,
+    NoEdgeLeft,
+    NothingToRewind,
+    UnknownCheckpoint,
+    UnknownEncounter,
+    InvalidHouseRule,
+    UnknownHouseRule,
+    UnknownMatrixTarget,
+    InvalidPatch,
+    InitiativeOutOfRange,
+    InvalidCatalogAction,
+    UnknownCatalogAction,
+    OutOfAmmo,
+}
+
+#[derive(Debug)]
+pub enum GameValue {
+    PlayerId(Uuid),
+    CurrentState(String),
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use rand::{SeedableRng, rngs::StdRng};
+    use uuid::Uuid;
+
+    use crate::tracker::{game::{ActionType, ActionBudget, FullDefenseCost, GameError, ErrorKind, RewindTarget, Phase}, character::{Character, CharacterPatch, ConditionMonitor, InitiativeFormula, Metatypes, Modifier, ModifierSource, ModifierTarget, RollMacro, WoundTier, WoundView}, journal::{JournalEvent, JournalEventKind, ChatLine, ChatAudience}, gear::{Weapon, Armour, ArmorTestType}, movement::{Gait, RUNNING_MODIFIER}, combat::{RangedAttack, RangeBand, Lighting, Cover, FiringMode, AreaAttack, DamageEvent, Ordnance, ResistanceTest, DamageType}, magic::{SpellDeclaration, Plane}, encounter::{StagedEncounter, ScriptedTrigger, TriggerAction}, house_rules::HouseRuleEvent, matrix::{MatrixTarget, MatrixTargetKind, MatrixAction, MatrixActionDeclaration}, rules::{Edition, ActionLabels, Healing, Recovery}};
+
+    use crate::tracker::{catalog::{CatalogAction, Ammunition}, archetypes::Archetype};
+
+    use super::{Game, CHECKPOINT_CAPACITY, AvailableAction, PatchOutcome, AfterPass, SkipReason, StatusEffect, TurnState, TimedEvent, VisibilityOptions, WoundDisclosure};
+
+    pub fn init() {
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
+    }
+
+    macro_rules! populate {
+        ($game:expr, $($char:expr),*) => {
+            {
+                let local_game: &mut Game = $game;
+                let mut ids = Vec::<Uuid>::new();
+
+                $(let char_id:Uuid = local_game.add_cast_member($char); if let Err(_) = local_game.add_combatant(char_id) {panic!();}; ids.push(char_id);)*
+
+                ids
+            }
+        };
+    }
+
+    fn build_orc()->Character
+    {
+        Character::new_pc(Metatypes::Orc, String::from("Zorc"))
+    }
+
+    fn build_elf()->Character
+    {
+        Character::new_pc(Metatypes::Elf, String::from("Lef"))
+    }
+
+    fn build_dwarf()->Character
+    {
+        Character::new_npc(Metatypes::Dwarf, String::from("Dorf"))
+    }
+
+
+    #[test]
+    pub fn when_a_game_is_built_it_starts_in_pre_combat_state_with_empty_cast_and_combat_sets()
+    {
+        let game = Game::new();
+
+        assert_eq!(game.current_state(), String::from("PreCombat"));
+        assert_eq!(game.waiting_for(), None);
+        assert_eq!(game.on_deck(), None);
+        assert_eq!(game.get_combatants().len(), 0);
+    }
+
+    #[test]
+    pub fn adding_a_cast_member_to_a_game_increases_the_cast_size_by_one()
+    {
+
+        let cast_member = Character::new_pc(Metatypes::Human, String::from("Demo"));
+        let mut game: Game = Game::new();
+
+        let pre_add_size = game.cast_size();
+        game.add_cast_member(cast_member);
+
+        assert_eq!(game.cast_size(), pre_add_size + 1);
+        
+    }
+
+    #[test]
+    pub fn removing_a_cast_member_decreases_cast_size_by_one()
+    {
+        let cast_member = Character::new_pc(Metatypes::Elf, String::from("Delfmo"));
+        //  cast_member.id;
+        let mut game: Game = Game::new();
+
+        let id = game.add_cast_member(cast_member);
+
+        let pre_remove_size = game.cast_size();
+        game.retire_cast_member(id);
+        assert_eq!(game.cast_size(), pre_remove_size - 1); 
+    }
+
+    #[test]
+    pub fn all_cast_members_uuids_can_be_retrieved_at_any_time()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = true;
+        dorf.player_character = false;
+        melf.player_character = true;
+
+        let mut game = Game::new();
+
+        let mork_id = game.add_cast_member(mork);
+        let dorf_id = game.add_cast_member(dorf);
+        let melf_id = game.add_cast_member(melf);
+
+        let cast = game.get_cast();
+        let ids = vec![mork_id, dorf_id, melf_id];
+
+        assert!(cast.len() == 3);
+        assert!(ids.contains(&cast.get(0).unwrap().id));
+        assert!(ids.contains(&cast.get(1).unwrap().id));
+        assert!(ids.contains(&cast.get(2).unwrap().id));
+        
+    }
+
+    #[test]
+    pub fn get_cast_generates_empty_vec_if_no_characters_added()
+    {
+        let game = Game::new();
+
+        let ids = game.get_cast();
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    pub fn get_npcs_returns_partial_set_of_characters()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = true;
+        dorf.player_character = false;
+        melf.player_character = true;
+
+        let mut game = Game::new();
+
+        let _mork_id = game.add_cast_member(mork);
+        let dorf_id = game.add_cast_member(dorf);
+        let _melf_id = game.add_cast_member(melf);
+
+        let cast = game.get_npcs();
+
+        assert!(cast.len() == 1);
+        assert!(cast.get(0).unwrap().id == dorf_id);
+    }
+
+    #[test]
+    pub fn if_no_cast_members_are_npcs_get_npcs_returns_empty_vec()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = true;
+        dorf.player_character = true;
+        melf.player_character = true;
+
+        let mut game = Game::new();
+
+        let _mork_id = game.add_cast_member(mork);
+        let _dorf_id = game.add_cast_member(dorf);
+        let _melf_id = game.add_cast_member(melf);
+
+        let ids = game.get_npcs();
+
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    pub fn get_pcs_returns_partial_set_of_characters()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = true;
+        dorf.player_character = false;
+        melf.player_character = true;
+
+        let mut game = Game::new();
+
+        let mork_id = game.add_cast_member(mork);
+        let _dorf_id = game.add_cast_member(dorf);
+        let melf_id = game.add_cast_member(melf);
+
+        let cast = game.get_pcs();
+        let ids = vec![mork_id, melf_id];
+
+        assert!(ids.len() == 2);
+        assert!(ids.contains(&cast.get(0).unwrap().id));
+        assert!(ids.contains(&cast.get(1).unwrap().id));
+    }
+
+    #[test]
+    pub fn if_no_cast_members_are_pcs_get_npcs_returns_empty_vec()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = false;
+        dorf.player_character = false;
+        melf.player_character = false;
+
+        let mut game = Game::new();
+
+        let _mork_id = game.add_cast_member(mork);
+        let _dorf_id = game.add_cast_member(dorf);
+        let _melf_id = game.add_cast_member(melf);
+
+        let ids = game.get_pcs();
+
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    pub fn get_cast_by_id_returns_some_with_character_if_character_id_is_in_cast()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = false;
+        dorf.player_character = true;
+        melf.player_character = false;
+
+        let mut game = Game::new();
+
+        let _mork_id = game.add_cast_member(mork);
+        let dorf_id = game.add_cast_member(dorf);
+        let _melf_id = game.add_cast_member(melf);
+
+        assert!(game.get_cast_by_id(&dorf_id).is_some());
+        let found_dorf = game.get_cast_by_id(&dorf_id).unwrap();
+        assert_eq!(dorf_id, found_dorf.id);
+        
+    }
+
+    #[test]
+    pub fn get_cast_by_id_returns_none_if_character_id_is_not_in_cast()
+    {
+        let mut mork = build_orc();
+        let mut dorf = build_dwarf();
+        let mut melf = build_elf();
+
+        mork.player_character = false;
+        dorf.player_character = true;
+        melf.player_character = false;
+
+        let mut game = Game::new();
+
+        let _mork_id = game.add_cast_member(mork);
+        let _dorf_id = game.add_cast_member(dorf);
+        let _melf_id = game.add_cast_member(melf);
+
+        assert!(game.get_cast_by_id(&Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    pub fn currently_up_produces_full_list_of_combatants_with_current_initiative()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 14).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        match game.currently_up() 
+        {
+            Some(chars) => 
+            {
+                assert!(chars.contains(ids.get(1).unwrap()));
+                assert!(chars.contains(ids.get(2).unwrap()));
+                assert!(!chars.contains(ids.get(0).unwrap()));
+            },
+            None => {panic!("currently_up test failed: should have produced ids for both mork and belf.")}
+        }
+    }
+
+    #[test]
+    pub fn currently_up_excludes_combatants_before_and_after_current_init()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 16).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+
+        match game.currently_up()
+        {
+            Some(chars) =>
+            {
+                assert!(chars.len() == 1);
+                assert!(chars.contains(ids.get(1).unwrap()));
+            }
+            None => {panic!("currently_up_excludes failed: should have returned at least one ID.")}
+        }
+    }
+
+    #[test]
+    pub fn currently_up_is_empty_before_action_rounds()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 16).is_ok());
+
+        assert!(game.currently_up().is_none());
+
+    }
+
+    #[test]
+    pub fn waiting_for_produces_a_list_of_ids_for_characters_who_have_not_acted_on_the_current_combat_turn()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        // let dorf_id = dorf.id;
+        let mork = build_orc();
+        // let mork_id = mork.id;
+        let belf = build_elf();
+        // let belf_id = belf.id;
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+
+        let blockers_option = game.waiting_for();
+        assert_ne!(blockers_option, None);
+        let blockers = blockers_option.unwrap();
+        assert_eq!(blockers.len(), 1);
+        let blocking_id = *blockers.get(0).unwrap();
+        assert_eq!(blocking_id, *ids.get(2).unwrap());
+    }
+
+    #[test]
+    pub fn waiting_for_produces_empty_list_if_all_characters_in_combat_turn_have_acted()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+
+        assert_eq!(game.waiting_for(), None);
+    }
+
+    #[test]
+    pub fn waiting_for_produces_none_if_not_in_combat_turns_phase()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        
+        let mork_id = game.add_cast_member(mork);
+        let dorf_id = game.add_cast_member(dorf);
+
+        assert!(game.waiting_for().is_none());
+
+        assert!(game.add_combatant(dorf_id).is_ok());
+        assert!(game.add_combatant(mork_id).is_ok());
+
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.waiting_for().is_none());
+
+        assert!(game.accept_initiative_roll(dorf_id, 22).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 12).is_ok());
+        assert!(game.waiting_for().is_none());
+    }
+
+    #[test]
+    pub fn on_deck_returns_list_of_ids_whose_action_turn_follows_current()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        let option = game.on_deck();
+        assert_ne!(None, option);
+        let on_deck = option.unwrap();
+        assert_eq!(1, on_deck.len());
+        assert!(on_deck.contains(&ids.get(0).unwrap()));
+    }
+
+    #[test]
+    pub fn on_deck_produces_none_if_game_not_processing_combat_turns()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        assert_eq!(None, game.on_deck());
+    }
+
+    #[test]
+    pub fn on_deck_returns_none_if_no_combatants_remain_to_act_on_this_turn()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+
+        assert!(game.advance_round().is_ok());
+        assert_eq!(None, game.on_deck());
+
+    }
+
+    #[test]
+    pub fn collect_all_events_returns_all_initiatives_and_associated_events()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+
+        let some = game.collect_all_remaining_events();
+        assert!(some.is_some());
+        let events = some.unwrap();
+        assert!(events.len() == 2);
+        assert!(events.contains_key(&12));
+        assert!(events.contains_key(&9));
+        assert!(events.get(&12).unwrap().len() == 2);
+        assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
+        assert!(events.get(&12).unwrap().contains(ids.get(2).unwrap()));
+        assert!(events.get(&9).unwrap().len() == 1);
+        assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
+    }
+
+    #[test]
+    pub fn collect_all_events_operates_in_all_phases()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.collect_all_remaining_events().is_none());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.collect_all_remaining_events().is_some());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.collect_all_remaining_events().is_some());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+        assert!(game.collect_all_remaining_events().is_some());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.collect_all_remaining_events().is_some());
+
+        let events = game.collect_all_remaining_events().unwrap();
+        assert!(events.len() == 2);
+        assert!(events.contains_key(&12));
+        assert!(events.contains_key(&9));
+        assert!(events.get(&12).unwrap().len() == 2);
+        assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
+        assert!(events.get(&12).unwrap().contains(ids.get(2).unwrap()));
+        assert!(events.get(&9).unwrap().len() == 1);
+        assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
+    }
+
+    #[test]
+    pub fn collect_all_events_captures_only_turns_that_have_not_fully_resolved()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.collect_all_remaining_events().is_none());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        
+        match game.collect_all_remaining_events()
+        {
+            Some(events) => 
+            {
+                assert!(events.len() == 2);
+                assert!(events.contains_key(&12));
+                assert!(events.contains_key(&9));
+                assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
+                assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
+            },
+            None => {panic!("Should have been at least two initiative rolls and events.");}
+        }
+        
+    }
+
+    #[test]
+    pub fn calling_get_current_init_before_starting_combat_generates_none()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+
+        assert!(game.get_current_init().is_none());
+    }
+
+    #[test]
+    pub fn calling_get_current_init_after_starting_combat_generates_current_init()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.get_current_init().is_some());
+        assert!(game.get_current_init().unwrap() == 15);
+    }
+
+    #[test]
+    pub fn calling_get_next_init_before_starting_combat_generates_none()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+
+        assert!(game.get_next_init().is_none());
+    }
+
+    #[test]
+    pub fn calling_get_next_init_after_starting_combat_generates_next_init()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.get_next_init().is_some());
+        assert!(game.get_next_init().unwrap() == 12);
+    }
+
+    #[test]
+    pub fn calling_get_next_init_on_last_turn_generates_none()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        
+        assert!(game.get_next_init().is_none());
+    }
+
+    #[test]
+    pub fn get_all_remaining_initiatives_will_retrieve_all_unresolved_initiatives()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.get_all_remaining_initiatives().is_some());
+        assert!(game.get_all_remaining_initiatives().unwrap().len() == 3);
+    }
+
+    #[test]
+    pub fn advancing_turn_will_remove_advanced_initiative_from_get_all_unresolved_list()
+    {
+        let mut game = Game::new();
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+
+        assert!(game.get_all_remaining_initiatives().is_some());
+        assert!(game.get_all_remaining_initiatives().unwrap().len() == 3);
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.get_all_remaining_initiatives().is_some());
+        assert!(game.get_all_remaining_initiatives().unwrap().len() == 2);
+        assert!(game.get_all_remaining_initiatives().unwrap().contains(&9));
+        assert!(game.get_all_remaining_initiatives().unwrap().contains(&12));
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.get_all_remaining_initiatives().is_some());
+        assert!(game.get_all_remaining_initiatives().unwrap().len() == 1);
+        assert!(game.get_all_remaining_initiatives().unwrap().contains(&9));
+
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_err());
+        assert!(game.get_all_remaining_initiatives().is_none());
+
+    }
+
+    #[test]
+    pub fn get_combatants_returns_list_of_all_participants_in_the_fight()
+    {
+        let mut game = Game::new();
+
+        let mork = build_orc();
+        let dorf = build_dwarf();
+
+        let ids = populate!(&mut game, mork, dorf);
+
+        let combatants = game.get_combatants();
+
+        assert_eq!(2, combatants.len());
+        assert!(combatants.contains(ids.get(0).unwrap()));
+        assert!(combatants.contains(ids.get(1).unwrap()));
+
+    }
+
+    #[test]
+    pub fn get_combatants_returns_only_cast_members_registered_in_current_combat_session()
+    {
+        let mut game = Game::new();
+
+        let mork = build_orc();
+        let dorf = build_dwarf();
+        let melf = build_elf();
+
+        let ids = populate!(&mut game, mork, dorf);
+        game.add_cast_member(melf);
+
+        let combatants = game.get_combatants();
+
+        assert_eq!(2, combatants.len());
+        assert!(combatants.contains(ids.get(0).unwrap()));
+        assert!(combatants.contains(ids.get(1).unwrap()));
+    }
+
+    #[test]
+    pub fn when_get_combatants_is_called_with_no_active_combatants_an_empty_list_is_returned()
+    {
+        let mut game = Game::new();
+
+        let mork = build_orc();
+        let dorf = build_dwarf();
+        let melf = build_elf();
+
+        game.add_cast_member(mork);
+        game.add_cast_member(dorf);
+        game.add_cast_member(melf);
+
+        let combatants = game.get_combatants();
+
+        assert_eq!(0, combatants.len());
+    }
+
+    #[test]
+    pub fn get_combatants_is_independent_of_cast_list()
+    {
+        let mut game = Game::new();
+
+        let combatants = game.get_combatants();
+        assert_eq!(0, combatants.len());
+
+        let mork = build_orc();
+        let dorf = build_dwarf();
+        let melf = build_elf();
+
+        game.add_cast_member(mork);
+        game.add_cast_member(dorf);
+        game.add_cast_member(melf);
+
+        let combatants = game.get_combatants();
+        assert_eq!(0, combatants.len());
+
+        assert_eq!(0, combatants.len());
+    }
+
+    #[test]
+    pub fn when_combat_is_ended_the_game_resets_the_combatants_list_and_resets_game_state()
+    {
+        let mut game = Game::new();
+
+        let mork = build_orc();
+        let dorf = build_dwarf();
+        let melf = build_elf();
+
+        let ids = populate!(&mut game, mork, dorf, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 13).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 22).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 14).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert_eq!(game.get_combatants().len(), 3);
+
+        assert!(game.end_combat().is_ok());
+        assert_eq!(game.get_combatants().len(), 0);
+        assert_eq!(game.current_state(), String::from("PreCombat"));
+        let on_deck = game.on_deck();
+        assert!(on_deck.is_none());
+        
+
+    }
+
+    #[test]
+    pub fn adding_non_cast_member_to_combat_results_in_unrecognized_character_error()
+    {
+        let mut game = Game::new();
+
+        let combatant_id = Uuid::new_v4();
+
+        let result = game.add_combatant(combatant_id);
+
+        assert!(result.is_err());
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnknownCastId => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn any_real_cast_member_may_be_added_to_combat()
+    {
+        let dorf = Character::new_npc(Metatypes::Dwarf, String::from("Dorf"));
+        let torll = Character::new_npc(Metatypes::Troll, String::from("Torll"));
+        let mut game = Game::new();
+
+        let combatant_id = game.add_cast_member(dorf);
+        game.add_cast_member(torll);
+
+        let result = game.add_combatant(combatant_id);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    pub fn multiple_combatants_may_be_added_in_a_vector_of_ids()
+    {
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+
+        let dorf_id = game.add_cast_member(dorf);
+        let mork_id = game.add_cast_member(mork);
+        let melf_id = game.add_cast_member(melf);
+
+        let mut combatants = Vec::<Uuid>::new();
+        combatants.push(dorf_id);
+        combatants.push(mork_id);
+        combatants.push(melf_id);
+
+        assert!(game.add_combatants(combatants).is_ok());
+
+    }
+
+    #[test]
+    pub fn any_unknown_character_ids_in_vec_cause_unknown_character_error_from_add_combatants()
+    {
+        let dorf = build_dwarf();
+        let _mork = build_orc();
+        let _melf = build_elf();
+
+        let mut game = Game::new();
+
+        let dorf_id = game.add_cast_member(dorf);
+
+        let mut combatants = Vec::<Uuid>::new();
+        combatants.push(dorf_id);
+        combatants.push(Uuid::new_v4());
+        combatants.push(Uuid::new_v4());
+
+        match game.add_combatants(combatants)
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnknownCastId => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn when_a_game_has_combatants_it_may_enter_into_initiative_phase()
+    {
+        let melf = Character::new_npc(Metatypes::Elf, String::from("Melf"));
+        let zorc = Character::new_npc(Metatypes::Orc, String::from("Zorc"));
+        let mut game = Game::new();
+
+        let melf_id = game.add_cast_member(melf);
+        let zorc_id = game.add_cast_member(zorc);
+
+        assert!(game.add_combatant(melf_id).is_ok());
+        assert!(game.add_combatant(zorc_id).is_ok());
+
+        let result = game.start_initiative_phase();
+
+        assert!(result.is_ok());
+        assert_eq!(game.current_state(), String::from("Initiative Rolls"));
+    }
+
+    #[test]
+    pub fn when_all_combatants_have_supplied_initiative_combat_round_phase_may_start()
+    {
+        let mut game = Game::new();
+        let melf = build_elf();
+        let mork = build_orc();
+        let dorf = build_dwarf();
+
+        let ids = populate!(&mut game, melf, mork, dorf);
+
+        // transition from PreCombat
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 18).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
+    
+        // transition into InitiativePass
+        assert!(game.start_combat_rounds().is_ok());
+
+    }
+
+    #[test]
+    pub fn if_game_is_in_precombat_phase_with_no_combatants_start_initiative_phase_generates_no_combatants()
+    {
+        let lef = Character::new_npc(Metatypes::Elf, String::from("Lef"));
+
+        let mut game = Game::new();
+
+        game.add_cast_member(lef);
+
+        let result = game.start_initiative_phase();
+
+        assert!(result.is_err());
+
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnknownCastId => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+
+        assert_eq!(game.current_state(), String::from("PreCombat"));
+    }
+
+    #[test]
+    pub fn adding_initiative_roll_before_entering_initiative_phase_results_in_invalid_state_action()
+    {
+        let lef = Character::new_npc(Metatypes::Elf, String::from("Lef"));
+        let mut game = Game::new();
+
+        let lef_id = game.add_cast_member(lef);
+        assert!(game.add_combatant(lef_id).is_ok());
+
+        let result = game.accept_initiative_roll(lef_id, 4);
+
+        assert!(result.is_err());
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn adding_an_initiative_for_a_combatant_in_initiative_phase_will_return_ok()
+    {
+        init();
+
+        let zorc = build_orc();
+        let dorf = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dorf);
+        assert!(game.start_initiative_phase().is_ok());
+
+        let result = game.accept_initiative_roll(*ids.get(0).unwrap(), 4);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    pub fn adding_multiple_initiatives_to_real_combatants_will_produce_ok_for_each()
+    {
+        init();
+
+
+        let zorc = build_orc();
+        let dorf = build_dwarf();
+        
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dorf);
+        assert!(game.start_initiative_phase().is_ok());
+
+        let result = game.accept_initiative_roll(*ids.get(0).unwrap(), 2);
+        assert!(result.is_ok());
+        let result = game.accept_initiative_roll(*ids.get(1).unwrap(), 13);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    pub fn add_initiative_for_character_id_not_in_cast_produces_unknown_cast_id()
+    {
+        init();
+
+        let zorc = build_orc();
+        let mork = build_orc();
+
+        let mut game = Game::new();
+        populate!(&mut game, zorc, mork);
+        assert!(game.start_initiative_phase().is_ok());
+
+        let result = game.accept_initiative_roll(Uuid::new_v4(), 2);
+        assert!(result.is_err());
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnknownCastId => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn as_initiative_rolls_are_added_game_initiative_state_updates()
+    {
+        init();
+
+        let zorc = build_orc();
+        let mork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, mork);
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.are_any_initiatives_outstanding());
+        assert!(game.collect_undeclared_initiatives().len() == 2);
+        assert!(game.collect_undeclared_initiatives().contains(ids.get(0).unwrap()));
+        assert!(game.collect_undeclared_initiatives().contains(ids.get(1).unwrap()));
+
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+
+        assert!(game.are_any_initiatives_outstanding());
+        assert!(game.collect_undeclared_initiatives().len() == 1);
+        assert!(game.collect_undeclared_initiatives().contains(ids.get(1).unwrap()));
+        assert!(!game.collect_undeclared_initiatives().contains(ids.get(0).unwrap()));
+
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(!game.are_any_initiatives_outstanding());
+        assert!(game.collect_undeclared_initiatives().len() == 0);
+    }
+
+    #[test]
+    pub fn when_all_initiatives_submitted_game_may_start_combat_rounds()
+    {
+        init();
+
+        let zorc = build_orc();
+        let mork = build_orc();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, mork, dork);
+
+        assert!(game.start_initiative_phase().is_ok());
+
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 4).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 8).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 3).is_ok());
+
+        let result = game.start_combat_rounds();
+
+        assert_eq!(game.current_state(), String::from("Initiative Pass"));
+        assert!(result.is_ok());
+
+    }
+
+    #[test]
+    pub fn starting_combat_rounds_before_all_initiatives_submitted_produces_invalid_state_action()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new ();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 1).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 15).is_ok());
+
+        let result = game.start_combat_rounds();
+
+        assert_eq!(game.current_state(), String::from("Initiative Rolls"));
+        assert!(result.is_err());
+
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn attempting_to_move_to_next_initiative_pass_fails_with_unresolved_combatant_if_there_are_unresolved_players_in_init_sequence()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        
+        // Attempting to advance to the next initiative pass should result in failure.
+        let result = game.next_initiative_pass();
+        assert!(result.is_err());
+        match result
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+
+    }
+
+    #[test]
+    pub fn advancing_to_next_initiative_pass_if_no_character_has_another_pass_results_in_error_end_of_initiative_pass()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_err());
+
+        // assert!(game.next_initiative_pass().is_err());
+        match game.next_initiative_pass()
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::EndOfInitiativePass => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+
+    }
+
+    #[test]
+    pub fn advancing_to_next_pass_before_begin_combat_round_generates_unresolved_combatant()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        
+        // assert!(game.next_initiative_pass().is_err());
+        match game.next_initiative_pass()
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+        
+    }
+
+    #[test]
+    pub fn initiative_may_advance_when_all_combatants_at_current_initiative_have_resolved()
+    {
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_err());
+        
+    }
+
+    #[test]
+    pub fn advancing_initiative_before_starting_initiative_character_has_acted_generates_unresolved_combatant()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        // assert!(game.next_initiative().is_err());
+        match game.advance_round()
+        {
+            Ok(_) => {panic!("This should not have succeeded.")},
+            Err(err) => 
+            {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
+                    _ => {panic!("Test expected different error type (UnknownCastId)");}
+                }
+            },
+        }
+
+    }
+
+    // // TODO: Add tests to ensure advance_initiative_pass succeeds when at least one character or event occurs on the next pass.
+    // // Currently cannot do that because there's no Game infrastructure to calculate or influence the pass count for any character,
+    // // or add some on-next-pass event.
+
+    #[test]
+    pub fn a_resolving_character_can_take_two_simple_actions_but_not_one_simple_one_complex_nor_three_simple()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_err());
+        assert!(game.advance_round().is_err());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_err());
+        assert!(game.advance_round().is_ok());
+    }
+
+    #[test]
+    pub fn a_resolving_character_may_take_one_complex_action_but_not_one_complex_one_single_or_two_complex()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_err());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_err());
+        assert!(game.advance_round().is_ok());
+    }
+
+    #[test]
+    pub fn a_resolving_character_may_take_their_free_action_alongside_their_simple_actions()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.advance_round().is_ok());
+    }
+
+    #[test]
+    pub fn a_resolving_character_may_take_their_free_action_alongside_their_complex_action()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
     }
 
     #[test]
-    pub fn get_npcs_returns_partial_set_of_characters()
+    pub fn any_character_may_take_their_free_action_on_any_turn()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        init();
 
-        mork.player_character = true;
-        dorf.player_character = false;
-        melf.player_character = true;
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
 
         let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
 
-        let _mork_id = game.add_cast_member(mork);
-        let dorf_id = game.add_cast_member(dorf);
-        let _melf_id = game.add_cast_member(melf);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
 
-        let cast = game.get_npcs();
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(cast.len() == 1);
-        assert!(cast.get(0).unwrap().id == dorf_id);
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Free).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.advance_round().is_ok());
     }
 
     #[test]
-    pub fn if_no_cast_members_are_npcs_get_npcs_returns_empty_vec()
+    pub fn a_character_not_resolving_may_not_take_a_simple_action()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        init();
 
-        mork.player_character = true;
-        dorf.player_character = true;
-        melf.player_character = true;
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
 
         let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
 
-        let _mork_id = game.add_cast_member(mork);
-        let _dorf_id = game.add_cast_member(dorf);
-        let _melf_id = game.add_cast_member(melf);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
 
-        let ids = game.get_npcs();
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(ids.is_empty());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Simple).is_err());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Simple).is_err());
+        assert!(game.advance_round().is_err());
     }
 
     #[test]
-    pub fn get_pcs_returns_partial_set_of_characters()
+    pub fn a_character_not_resolving_may_not_take_a_complex_action()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        init();
 
-        mork.player_character = true;
-        dorf.player_character = false;
-        melf.player_character = true;
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
 
         let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
 
-        let mork_id = game.add_cast_member(mork);
-        let _dorf_id = game.add_cast_member(dorf);
-        let melf_id = game.add_cast_member(melf);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
 
-        let cast = game.get_pcs();
-        let ids = vec![mork_id, melf_id];
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(ids.len() == 2);
-        assert!(ids.contains(&cast.get(0).unwrap().id));
-        assert!(ids.contains(&cast.get(1).unwrap().id));
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_err());
+        assert!(game.advance_round().is_err());
     }
 
     #[test]
-    pub fn if_no_cast_members_are_pcs_get_npcs_returns_empty_vec()
+    pub fn multiple_characters_may_have_same_initiative_and_may_act_together()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        init();
 
-        mork.player_character = false;
-        dorf.player_character = false;
-        melf.player_character = false;
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
 
         let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
 
-        let _mork_id = game.add_cast_member(mork);
-        let _dorf_id = game.add_cast_member(dorf);
-        let _melf_id = game.add_cast_member(melf);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
 
-        let ids = game.get_pcs();
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
 
-        assert!(ids.is_empty());
     }
 
     #[test]
-    pub fn get_cast_by_id_returns_some_with_character_if_character_id_is_in_cast()
+    pub fn advancing_the_initiative_round_before_all_active_characters_have_resolved_will_generate_unresolved_combatant()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
+        init();
 
-        mork.player_character = false;
-        dorf.player_character = true;
-        melf.player_character = false;
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        
+        match game.advance_round()
+        {
+            Ok(_) => panic!("This should have failed."),
+            Err(err) => match err.kind
+            {
+                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
+                _ => {panic!("Advancing round without resolving all actions should generate UnresolvedCombatant type error.")}
+            },
+        }
+    }
+
+    #[test]
+    pub fn the_initiative_round_may_be_advanced_only_after_all_current_players_have_fully_resolved()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dork = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, dork, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+
+        assert!(game.advance_round().is_ok());
+
+        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Simple).is_ok());
+    }
+
+    // TODO: Tests for Initiative Passes once passes are in.
+
+    #[test]
+    pub fn advancing_initiative_after_all_events_have_been_processed_results_in_end_of_initiative_error()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+
+        match game.advance_round()
+        {
+            Ok(_) => panic!("No new events on deck to process this round!"),
+            Err(err) => {
+                match err.kind
+                {
+                    crate::tracker::game::ErrorKind::EndOfInitiative => {},
+                    _ => {panic!("Should indicate we have hit the end of the round with EndOfInitiative")}
+                }
+            },
+        }
+    }
+
+    #[test]
+    pub fn when_all_events_have_resolved_combat_may_move_back_to_initiative_phase()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_err());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+    }
+
+    #[test]
+    pub fn a_game_only_offers_the_phases_it_is_ready_to_move_into()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
 
         let mut game = Game::new();
+        assert_eq!(Phase::PreCombat, game.phase());
+        assert!(game.allowed_transitions().is_empty());
+
+        let ids = populate!(&mut game, zorc, melf);
+        assert_eq!(vec![Phase::Initiative], game.allowed_transitions());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert_eq!(vec![Phase::PreCombat], game.allowed_transitions());
+        assert!(matches!(game.start_combat_rounds(), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
+
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
+        assert_eq!(vec![Phase::ActionRound, Phase::PreCombat], game.allowed_transitions());
+
+        assert!(game.start_combat_rounds().is_ok());
+        assert_eq!(Phase::ActionRound, game.phase());
+        assert_eq!(vec![Phase::PreCombat], game.allowed_transitions());
 
-        let _mork_id = game.add_cast_member(mork);
-        let dorf_id = game.add_cast_member(dorf);
-        let _melf_id = game.add_cast_member(melf);
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert_eq!(vec![Phase::Initiative, Phase::PreCombat], game.allowed_transitions());
 
-        assert!(game.get_cast_by_id(&dorf_id).is_some());
-        let found_dorf = game.get_cast_by_id(&dorf_id).unwrap();
-        assert_eq!(dorf_id, found_dorf.id);
-        
+        assert!(game.end_combat().is_ok());
+        assert_eq!(Phase::PreCombat, game.phase());
+        assert!(matches!(game.end_combat(), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
+        assert!(game.allowed_transitions().is_empty());
     }
 
     #[test]
-    pub fn get_cast_by_id_returns_none_if_character_id_is_not_in_cast()
+    pub fn a_refusal_for_the_wrong_phase_names_the_phases_that_would_have_been_right()
     {
-        let mut mork = build_orc();
-        let mut dorf = build_dwarf();
-        let mut melf = build_elf();
-
-        mork.player_character = false;
-        dorf.player_character = true;
-        melf.player_character = false;
+        init();
 
+        let zorc = build_orc();
         let mut game = Game::new();
+        let ids = populate!(&mut game, zorc);
 
-        let _mork_id = game.add_cast_member(mork);
-        let _dorf_id = game.add_cast_member(dorf);
-        let _melf_id = game.add_cast_member(melf);
+        let Err(err) = game.skip_turn(*ids.get(0).unwrap())
+        else { panic!("A turn cannot be skipped before combat.") };
+        assert_eq!(Some(String::from("Initiative Pass")), err.expected);
 
-        assert!(game.get_cast_by_id(&Uuid::new_v4()).is_none());
+        let Err(err) = game.start_combat_rounds()
+        else { panic!("Combat rounds cannot start before initiative is rolled.") };
+        assert_eq!(Some(String::from("Initiative Rolls")), err.expected);
+
+        assert!(game.start_initiative_phase().is_ok());
+        let Err(err) = game.start_initiative_phase()
+        else { panic!("Initiative cannot be opened twice.") };
+        assert_eq!(Some(String::from("PreCombat or Initiative Pass")), err.expected);
     }
 
     #[test]
-    pub fn currently_up_produces_full_list_of_combatants_with_current_initiative()
+    pub fn calling_start_initiative_phase_before_all_events_resolve_generates_unresolved_combatant()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 14).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
 
         assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
 
-        match game.currently_up() 
+        match game.start_initiative_phase()
         {
-            Some(chars) => 
+            Ok(_) => {panic!("Attempting to start the initiative phase before all characters in the last turn resolve should have failed.")},
+            Err(err) => match err.kind
             {
-                assert!(chars.contains(ids.get(1).unwrap()));
-                assert!(chars.contains(ids.get(2).unwrap()));
-                assert!(!chars.contains(ids.get(0).unwrap()));
+                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
+                _ => {panic!("Attempting to start the initiative phase before all characters in the last turn resolve should have generated UnresolvedCombatant")}
             },
-            None => {panic!("currently_up test failed: should have produced ids for both mork and belf.")}
         }
-    }
-
-    #[test]
-    pub fn currently_up_excludes_combatants_before_and_after_current_init()
-    {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
 
         assert!(game.start_initiative_phase().is_ok());
 
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 16).is_ok());
-
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 13).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 16).is_ok());
         assert!(game.start_combat_rounds().is_ok());
+        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
 
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-
-        match game.currently_up()
+        match game.start_initiative_phase()
         {
-            Some(chars) =>
+            Ok(_) => {panic!("Attempting to start the initiative phase before all on-deck characters resolve should have generated UnresolvedCombatant")},
+            Err(err) => match err.kind
             {
-                assert!(chars.len() == 1);
-                assert!(chars.contains(ids.get(1).unwrap()));
+                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
+                _ => {panic!("Attempting to start the initiative phase before all on-deck characters resolve should have generated UnresolvedCombatant.")}
             }
-            None => {panic!("currently_up_excludes failed: should have returned at least one ID.")}
         }
     }
 
     #[test]
-    pub fn currently_up_is_empty_before_action_rounds()
+    pub fn remaining_actions_reports_what_a_combatant_can_still_do_this_pass()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
 
         assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 16).is_ok());
+        assert_eq!(ActionBudget { free: 1, simple: 2, complex: 1 }, game.remaining_actions(*ids.get(0).unwrap()).unwrap());
 
-        assert!(game.currently_up().is_none());
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Simple).is_ok());
+        assert_eq!(ActionBudget { free: 1, simple: 1, complex: 0 }, game.remaining_actions(*ids.get(0).unwrap()).unwrap());
+
+        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Simple).is_ok());
+        assert_eq!(ActionBudget { free: 1, simple: 0, complex: 0 }, game.remaining_actions(*ids.get(0).unwrap()).unwrap());
 
+        assert!(game.remaining_actions(Uuid::new_v4()).is_err());
     }
 
     #[test]
-    pub fn waiting_for_produces_a_list_of_ids_for_characters_who_have_not_acted_on_the_current_combat_turn()
+    pub fn action_budgets_refresh_at_the_start_of_every_initiative_pass()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        // let dorf_id = dorf.id;
-        let mork = build_orc();
-        // let mork_id = mork.id;
-        let belf = build_elf();
-        // let belf_id = belf.id;
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let (sammy, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
+        assert!(game.start_initiative_phase().is_ok());
+        game.combatant_data.get_mut(&sammy).unwrap().initiative_passes = 1;
+        assert!(game.accept_initiative_roll(sammy, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(sammy, ActionType::Free).is_ok());
+        assert!(game.take_action(sammy, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(melf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_err());
 
-        let blockers_option = game.waiting_for();
-        assert_ne!(blockers_option, None);
-        let blockers = blockers_option.unwrap();
-        assert_eq!(blockers.len(), 1);
-        let blocking_id = *blockers.get(0).unwrap();
-        assert_eq!(blocking_id, *ids.get(2).unwrap());
+        assert!(game.next_initiative_pass().is_ok());
+        assert_eq!(ActionBudget { free: 1, simple: 2, complex: 1 }, game.remaining_actions(sammy).unwrap());
+        assert!(game.take_action(sammy, ActionType::Free).is_ok());
+        assert!(game.take_action(sammy, ActionType::Complex).is_ok());
     }
 
     #[test]
-    pub fn waiting_for_produces_empty_list_if_all_characters_in_combat_turn_have_acted()
+    pub fn declared_movement_accumulates_over_the_turn_and_is_recorded_in_the_ledger()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let zorc_id = *ids.get(0).unwrap();
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
         assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
-
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert_eq!(Gait::Walking, game.declare_movement(zorc_id, 6, 0).unwrap());
+        assert_eq!(0, game.running_modifier(zorc_id));
+        assert_eq!(Gait::Running, game.declare_movement(zorc_id, 6, 0).unwrap());
+        assert_eq!(RUNNING_MODIFIER, game.running_modifier(zorc_id));
 
-        assert_eq!(game.waiting_for(), None);
+        let ledger = game.movement_ledger();
+        assert_eq!(2, ledger.len());
+        assert_eq!(12, ledger.get(1).unwrap().total_this_turn);
+        assert_eq!(zorc_id, ledger.get(1).unwrap().character_id);
     }
 
     #[test]
-    pub fn waiting_for_produces_none_if_not_in_combat_turns_phase()
+    pub fn movement_beyond_the_running_rate_without_sprint_hits_is_rejected()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        
-        let mork_id = game.add_cast_member(mork);
-        let dorf_id = game.add_cast_member(dorf);
+        init();
 
-        assert!(game.waiting_for().is_none());
+        let zorc = build_orc();
+        let melf = build_elf();
 
-        assert!(game.add_combatant(dorf_id).is_ok());
-        assert!(game.add_combatant(mork_id).is_ok());
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let zorc_id = *ids.get(0).unwrap();
 
         assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.waiting_for().is_none());
+        match game.declare_movement(zorc_id, 30, 0)
+        {
+            Ok(_) => {panic!("Running 30m should have exceeded an ork's running rate.")},
+            Err(err) => match err.kind
+            {
+                crate::tracker::game::ErrorKind::ExceedsMovementRate => {},
+                _ => {panic!("Should have generated ExceedsMovementRate.")}
+            }
+        }
 
-        assert!(game.accept_initiative_roll(dorf_id, 22).is_ok());
-        assert!(game.accept_initiative_roll(mork_id, 12).is_ok());
-        assert!(game.waiting_for().is_none());
+        assert_eq!(Gait::Sprinting, game.declare_movement(zorc_id, 30, 3).unwrap());
+        assert!(game.declare_movement(*ids.get(1).unwrap(), 5, 0).is_err());
     }
 
     #[test]
-    pub fn on_deck_returns_list_of_ids_whose_action_turn_follows_current()
+    pub fn a_distance_that_would_overflow_the_turns_total_is_rejected_and_leaves_the_total_alone()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc);
+        let zorc_id = *ids.get(0).unwrap();
 
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        let option = game.on_deck();
-        assert_ne!(None, option);
-        let on_deck = option.unwrap();
-        assert_eq!(1, on_deck.len());
-        assert!(on_deck.contains(&ids.get(0).unwrap()));
+        assert_eq!(Gait::Walking, game.declare_movement(zorc_id, 10, 0).unwrap());
+        assert!(game.declare_movement(zorc_id, u16::MAX, 255).is_err());
+        assert_eq!(Gait::Running, game.declare_movement(zorc_id, 10, 0).unwrap());
+        assert_eq!(20, game.movement_ledger().last().unwrap().total_this_turn);
     }
 
     #[test]
-    pub fn on_deck_produces_none_if_game_not_processing_combat_turns()
+    pub fn movement_totals_reset_when_a_new_combat_turn_begins()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc);
+        let zorc_id = *ids.get(0).unwrap();
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.declare_movement(zorc_id, 25, 0).is_ok());
+        assert!(game.take_action(zorc_id, ActionType::Complex).is_ok());
 
-        assert_eq!(None, game.on_deck());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.movement_ledger().is_empty());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        assert_eq!(Gait::Running, game.declare_movement(zorc_id, 25, 0).unwrap());
     }
 
     #[test]
-    pub fn on_deck_returns_none_if_no_combatants_remain_to_act_on_this_turn()
+    pub fn ranged_attack_modifiers_include_the_attackers_running_penalty()
     {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let attack = RangedAttack 
+        { 
+            attacker: zorc_id, target: Some(melf_id), range: RangeBand::Medium, lighting: Lighting::Full, cover: Cover::None, 
+            firing_mode: FiringMode::SingleShot 
+        };
+        assert!(game.ranged_attack_modifiers(&attack).is_err());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
-
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert_eq!(-1, game.ranged_attack_modifiers(&attack).unwrap().total);
+        assert!(game.declare_movement(zorc_id, 20, 0).is_ok());
+        assert_eq!(-3, game.ranged_attack_modifiers(&attack).unwrap().total);
 
-        assert!(game.advance_round().is_ok());
-        assert_eq!(None, game.on_deck());
+        let stray_shot = RangedAttack { target: Some(Uuid::new_v4()), ..attack };
+        assert!(game.ranged_attack_modifiers(&stray_shot).is_err());
+    }
+
+    #[test]
+    pub fn engaging_in_melee_costs_a_simple_action_and_reports_the_reach_differential()
+    {
+        init();
+
+        let mut troll = Character::new_npc(Metatypes::Troll, String::from("Tiny"));
+        Arc::make_mut(&mut troll.weapons).push(Weapon 
+        { 
+            weapon_type: String::from("Club"), weapon_name: String::from("Stop Sign"), assoc_skill: String::from("Clubs"), 
+            firing_features: Vec::new(), current_feature: 0, reach: Some(1), electric: false 
+        });
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, troll, melf);
+        let (troll_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(troll_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
+        assert!(game.engage(melf_id, troll_id).is_err());
+        let engagement = game.engage(troll_id, melf_id).unwrap();
+        assert_eq!(2, engagement.reach_differential);
+        assert!(game.are_engaged(melf_id, troll_id));
+        assert_eq!(1, game.remaining_actions(troll_id).unwrap().simple);
+        assert!(game.engage(troll_id, melf_id).is_err());
     }
 
-    #[test]
-    pub fn collect_all_events_returns_all_initiatives_and_associated_events()
-    {
+    #[test]
+    pub fn disengaging_breaks_the_engagement_for_both_sides()
+    {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
-
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        let some = game.collect_all_remaining_events();
-        assert!(some.is_some());
-        let events = some.unwrap();
-        assert!(events.len() == 2);
-        assert!(events.contains_key(&12));
-        assert!(events.contains_key(&9));
-        assert!(events.get(&12).unwrap().len() == 2);
-        assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
-        assert!(events.get(&12).unwrap().contains(ids.get(2).unwrap()));
-        assert!(events.get(&9).unwrap().len() == 1);
-        assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
+        assert!(game.engage(zorc_id, melf_id).is_ok());
+        assert!(game.take_action(zorc_id, ActionType::Simple).is_ok());
+        assert!(game.advance_round().is_ok());
+
+        assert!(game.disengage(melf_id, zorc_id).is_ok());
+        assert!(!game.are_engaged(zorc_id, melf_id));
+        assert!(game.engagements().is_empty());
+        assert!(game.disengage(melf_id, zorc_id).is_err());
     }
 
     #[test]
-    pub fn collect_all_events_operates_in_all_phases()
+    pub fn going_on_full_defense_before_acting_spends_the_complex_action_this_pass()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
 
-        assert!(game.collect_all_remaining_events().is_none());
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.collect_all_remaining_events().is_some());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.collect_all_remaining_events().is_some());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 12).is_ok());
-        assert!(game.collect_all_remaining_events().is_some());
-
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-        assert!(game.collect_all_remaining_events().is_some());
 
-        let events = game.collect_all_remaining_events().unwrap();
-        assert!(events.len() == 2);
-        assert!(events.contains_key(&12));
-        assert!(events.contains_key(&9));
-        assert!(events.get(&12).unwrap().len() == 2);
-        assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
-        assert!(events.get(&12).unwrap().contains(ids.get(2).unwrap()));
-        assert!(events.get(&9).unwrap().len() == 1);
-        assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
+        assert_eq!(FullDefenseCost::ThisPass, game.go_full_defense(melf_id).unwrap());
+        assert!(game.is_on_full_defense(melf_id));
+        assert!(game.go_full_defense(melf_id).is_err());
+
+        assert!(game.take_action(zorc_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(melf_id, ActionType::Simple).is_err());
+        assert!(game.waiting_for().is_none());
     }
 
     #[test]
-    pub fn collect_all_events_captures_only_turns_that_have_not_fully_resolved()
+    pub fn full_defense_after_acting_is_paid_from_the_next_turn_and_cleared_with_the_combat_turn()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
 
-        assert!(game.collect_all_remaining_events().is_none());
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
-
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(zorc_id, ActionType::Complex).is_ok());
+        assert_eq!(FullDefenseCost::NextTurn, game.go_full_defense(zorc_id).unwrap());
         assert!(game.advance_round().is_ok());
-        
-        match game.collect_all_remaining_events()
-        {
-            Some(events) => 
-            {
-                assert!(events.len() == 2);
-                assert!(events.contains_key(&12));
-                assert!(events.contains_key(&9));
-                assert!(events.get(&12).unwrap().contains(ids.get(1).unwrap()));
-                assert!(events.get(&9).unwrap().contains(ids.get(0).unwrap()));
-            },
-            None => {panic!("Should have been at least two initiative rolls and events.");}
-        }
-        
+        assert!(game.take_action(melf_id, ActionType::Complex).is_ok());
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(!game.is_on_full_defense(zorc_id));
+        assert_eq!(ActionBudget { free: 1, simple: 0, complex: 0 }, game.remaining_actions(zorc_id).unwrap());
+        assert_eq!(ActionBudget { free: 1, simple: 2, complex: 1 }, game.remaining_actions(melf_id).unwrap());
     }
 
     #[test]
-    pub fn calling_get_current_init_before_starting_combat_generates_none()
+    pub fn an_area_attack_queues_a_resistance_test_for_everyone_in_the_blast()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dwarf = build_dwarf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf, dwarf);
+        let (zorc_id, melf_id, dwarf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap(), *ids.get(2).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(dwarf_id, 8).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.get_current_init().is_none());
+        let attack = AreaAttack 
+        { 
+            attacker: zorc_id, ordnance: Ordnance::Standard, hits: 6, armor_test: ArmorTestType::Impact, armor_pen: 0, in_blast: vec![melf_id, dwarf_id] 
+        };
+        let resolution = game.resolve_area_attack(&attack).unwrap();
+
+        assert_eq!(0, resolution.scatter.meters);
+        assert!((2..=12).contains(&resolution.scatter.direction));
+        assert_eq!(vec![melf_id, dwarf_id], resolution.affected);
+        assert_eq!(ActionBudget { free: 1, simple: 1, complex: 0 }, game.remaining_actions(zorc_id).unwrap());
+
+        assert_eq!(2, game.pending_resistance_tests().len());
+        assert_eq!(melf_id, game.resistance_test_made(melf_id).unwrap().character_id);
+        assert!(game.resistance_test_made(melf_id).is_err());
+        assert_eq!(1, game.pending_resistance_tests().len());
     }
 
     #[test]
-    pub fn calling_get_current_init_after_starting_combat_generates_current_init()
+    pub fn an_area_attack_with_an_unknown_victim_costs_no_action()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        init();
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let zorc = build_orc();
+        let melf = build_elf();
+
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.get_current_init().is_some());
-        assert!(game.get_current_init().unwrap() == 15);
+        let attack = AreaAttack 
+        { 
+            attacker: zorc_id, ordnance: Ordnance::Launched, hits: 0, armor_test: ArmorTestType::Impact, armor_pen: 0, in_blast: vec![Uuid::new_v4()] 
+        };
+
+        assert!(game.resolve_area_attack(&attack).is_err());
+        assert_eq!(ActionBudget { free: 1, simple: 2, complex: 1 }, game.remaining_actions(zorc_id).unwrap());
+        assert!(game.pending_resistance_tests().is_empty());
     }
 
     #[test]
-    pub fn calling_get_next_init_before_starting_combat_generates_none()
+    pub fn a_damage_event_queues_one_resistance_test_per_target_in_the_order_given()
     {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+        let dwarf = build_dwarf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        let ids = populate!(&mut game, zorc, melf, dwarf);
+        let (zorc_id, melf_id, dwarf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap(), *ids.get(2).unwrap());
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let unknown = DamageEvent { source: zorc_id, targets: vec![melf_id, Uuid::new_v4()], armor_test: ArmorTestType::Ballistic, armor_pen: 0 };
+        assert!(game.apply_damage_event(&unknown).is_err());
+        assert!(game.pending_resistance_tests().is_empty());
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        let burst = DamageEvent { source: zorc_id, targets: vec![dwarf_id, melf_id, dwarf_id], armor_test: ArmorTestType::Ballistic, armor_pen: -2 };
+        let prompts = game.apply_damage_event(&burst).unwrap();
 
-        assert!(game.get_next_init().is_none());
+        assert_eq!(vec![dwarf_id, melf_id], prompts.iter().map(|prompt| prompt.character_id).collect::<Vec<Uuid>>());
+        assert!(prompts.iter().all(|prompt| prompt.source == zorc_id && prompt.test == ResistanceTest::Damage && prompt.soak_pool.is_some()));
+        assert_eq!(prompts, game.pending_resistance_tests());
     }
 
     #[test]
-    pub fn calling_get_next_init_after_starting_combat_generates_next_init()
+    pub fn timed_events_take_their_own_turn_in_the_round_they_are_due()
     {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        assert!(game.schedule_event(String::from("Bomb timer"), 10, 0).is_err());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
+
+        let bomb = game.schedule_event(String::from("Bomb timer"), 10, 0).unwrap();
+        let security = game.schedule_event(String::from("Security response"), 15, 1).unwrap();
+        assert!(game.start_combat_rounds().is_ok());
+
+        assert_eq!(Some(vec![zorc_id]), game.currently_up());
+        assert_eq!(Some(vec![bomb.id]), game.on_deck());
+        assert_eq!(vec![TimedEvent { rounds_away: 0, ..security.clone() }], game.scheduled_events());
+
+        assert!(game.take_action(zorc_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert_eq!(Some(vec![bomb.id]), game.currently_up());
+        assert_eq!(None, game.waiting_for());
+        assert_eq!(Some(&bomb), game.timed_event(&bomb.id));
+
+        // The alarm's score has already gone by this pass; the drone's is still to come.
+        let alarm = game.schedule_event(String::from("Alarm"), 12, 0).unwrap();
+        let drone = game.schedule_event(String::from("Drone"), 5, 0).unwrap();
+        assert_eq!(Some(vec![melf_id]), game.on_deck());
+        assert_eq!(vec![(melf_id, 8), (drone.id, 5)], game.initiative_order().into_iter().filter(|(id, _)| *id != bomb.id).collect::<Vec<(Uuid, i8)>>());
+
+        assert!(game.advance_round().is_ok());
+        assert_eq!(Some(vec![melf_id]), game.currently_up());
+        assert_eq!(Some(vec![drone.id]), game.on_deck());
+
+        loop
+        {
+            for id in game.waiting_for().unwrap_or_default()
+            {
+                assert!(game.take_action(id, ActionType::Complex).is_ok());
+            }
+            match game.advance_round()
+            {
+                Ok(()) => continue,
+                Err(_) if game.next_initiative_pass().is_ok() => continue,
+                Err(_) => break,
+            }
+        }
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.get_next_init().is_some());
-        assert!(game.get_next_init().unwrap() == 12);
+        assert_eq!(Some(vec![zorc_id]), game.currently_up());
+        assert_eq!(Some(vec![security.id]), game.on_deck());
+        assert!(game.timed_event(&alarm.id).is_some());
+        assert!(game.timed_event(&bomb.id).is_none());
+        assert!(game.scheduled_events().is_empty());
     }
 
     #[test]
-    pub fn calling_get_next_init_on_last_turn_generates_none()
+    pub fn a_cataloged_action_spends_its_cost_and_is_journaled_under_its_name()
     {
+        init();
+
+        let zorc = build_orc();
+        let melf = build_elf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let reload = CatalogAction { name: String::from("Reload"), cost: ActionType::Simple, description: String::from("Swap in a fresh clip."),
+            ammunition: Ammunition::Untouched };
+        assert!(game.define_catalog_action(reload.clone()).is_ok());
+        assert!(matches!(game.remove_catalog_action("Suppressive fire"), Err(GameError { kind: ErrorKind::UnknownCatalogAction, .. })));
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        
-        assert!(game.get_next_init().is_none());
+        assert!(matches!(game.take_catalog_action(zorc_id, "Suppressive fire"), Err(GameError { kind: ErrorKind::UnknownCatalogAction, .. })));
+        assert_eq!(reload, game.take_catalog_action(zorc_id, "reload").unwrap());
+        assert!(matches!(game.journal().entries().last().map(|entry| &entry.event), 
+            Some(JournalEvent::ActionTaken { action: ActionType::Simple, name: Some(name), .. }) if name == "Reload"));
+        assert_eq!(Some(vec![zorc_id]), game.waiting_for());
+
+        assert!(game.rematch().action_catalog() == vec![reload]);
     }
 
     #[test]
-    pub fn get_all_remaining_initiatives_will_retrieve_all_unresolved_initiatives()
+    pub fn a_cataloged_attack_empties_the_readied_weapon_and_is_refused_once_it_is_dry()
     {
+        init();
+
+        let razor = Archetype::StreetSamurai.build(String::from("Razor"));
+        let melf = build_elf();
+
         let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
+        let ids = populate!(&mut game, razor, melf);
+        let (razor_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let ids = populate!(&mut game, dorf, mork, belf);
+        let loaded = |game: &Game| game.get_cast_by_id(&razor_id).map(|character| character.weapons[character.current_weapon_index].firing_features[0].loaded);
+        let burst = CatalogAction { name: String::from("Long burst"), cost: ActionType::Simple, description: String::from("Hose the room."),
+            ammunition: Ammunition::Fires(8) };
+        let reload = CatalogAction { name: String::from("Reload"), cost: ActionType::Free, description: String::from("Swap in a fresh clip."),
+            ammunition: Ammunition::Reloads };
+        assert!(game.define_catalog_action(burst).is_ok());
+        assert!(game.define_catalog_action(reload).is_ok());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
-
+        assert!(game.accept_initiative_roll(razor_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.get_all_remaining_initiatives().is_some());
-        assert!(game.get_all_remaining_initiatives().unwrap().len() == 3);
+        assert_eq!(Some(15), loaded(&game));
+        assert!(game.take_catalog_action(razor_id, "Long burst").is_ok());
+        assert_eq!(Some(7), loaded(&game));
+
+        // The last seven rounds still go out as a short burst; after that the gun is dry.
+        assert!(game.take_catalog_action(razor_id, "Long burst").is_ok());
+        assert_eq!(Some(0), loaded(&game));
+        let journaled = game.journal().entries().len();
+        assert!(matches!(game.take_catalog_action(razor_id, "Long burst"), Err(GameError { kind: ErrorKind::OutOfAmmo, .. })));
+        assert_eq!(journaled, game.journal().entries().len());
+
+        assert!(game.take_catalog_action(razor_id, "Reload").is_ok());
+        assert_eq!(Some(15), loaded(&game));
+
+        assert!(matches!(game.take_catalog_action(melf_id, "Long burst"), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
     }
 
     #[test]
-    pub fn advancing_turn_will_remove_advanced_initiative_from_get_all_unresolved_list()
+    pub fn a_cataloged_attack_fires_from_the_feature_the_weapon_is_set_up_for()
     {
-        let mut game = Game::new();
-        let dorf = build_dwarf();
-        let mork = build_orc();
-        let belf = build_elf();
-
-        let ids = populate!(&mut game, dorf, mork, belf);
+        init();
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 9).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 15).is_ok());
+        let mut razor = Archetype::StreetSamurai.build(String::from("Razor"));
+        let readied = razor.current_weapon_index;
+        let weapon = &mut Arc::make_mut(&mut razor.weapons)[readied];
+        let mut grenade_launcher = weapon.firing_features[0].clone();
+        (grenade_launcher.reload_size, grenade_launcher.loaded) = (3, 3);
+        weapon.firing_features.push(grenade_launcher);
+        weapon.current_feature = 1;
 
-        assert!(game.get_all_remaining_initiatives().is_some());
-        assert!(game.get_all_remaining_initiatives().unwrap().len() == 3);
+        let mut game = Game::new();
+        let ids = populate!(&mut game, razor, build_elf());
+        let (razor_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
+        let loaded = |game: &Game| game.get_cast_by_id(&razor_id).map_or(Vec::new(), |character| 
+            character.weapons[character.current_weapon_index].firing_features.iter().map(|feature| feature.loaded).collect::<Vec<i8>>());
+        let burst = CatalogAction { name: String::from("Long burst"), cost: ActionType::Simple, description: String::from("Hose the room."),
+            ammunition: Ammunition::Fires(8) };
+        assert!(game.define_catalog_action(burst).is_ok());
 
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(razor_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.get_all_remaining_initiatives().is_some());
-        assert!(game.get_all_remaining_initiatives().unwrap().len() == 2);
-        assert!(game.get_all_remaining_initiatives().unwrap().contains(&9));
-        assert!(game.get_all_remaining_initiatives().unwrap().contains(&12));
+        assert!(game.take_catalog_action(razor_id, "Long burst").is_ok());
+        assert_eq!(vec![15, 0], loaded(&game));
+    }
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.get_all_remaining_initiatives().is_some());
-        assert!(game.get_all_remaining_initiatives().unwrap().len() == 1);
-        assert!(game.get_all_remaining_initiatives().unwrap().contains(&9));
+    #[test]
+    pub fn healing_takes_boxes_off_the_tracks_and_journals_what_came_back()
+    {
+        init();
 
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_err());
-        assert!(game.get_all_remaining_initiatives().is_none());
+        let mut game = Game::new();
+        let razor_id = game.add_cast_member(Archetype::StreetSamurai.build(String::from("Razor")));
+        let mut rng = StdRng::seed_from_u64(932);
+
+        assert!(game.apply_damage(None, razor_id, 4, DamageType::Physical).is_ok());
+        assert!(matches!(game.heal(&mut rng, Uuid::new_v4(), Healing::Magic { hits: 3, force: 3 }), Err(GameError { kind: ErrorKind::UnknownCastId, .. })));
+        assert_eq!(Recovery { physical: 2, stun: 0 }, game.heal(&mut rng, razor_id, Healing::Magic { hits: 3, force: 2 }).unwrap());
+        assert_eq!(2, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
+        assert!(matches!(game.journal().entries().last().map(|entry| &entry.event), 
+            Some(JournalEvent::Healed { character, boxes: 2, kind: DamageType::Physical }) if *character == razor_id));
+
+        // Nothing marked on the stun track, so first aid for stun brings nothing back and journals nothing.
+        let journaled = game.journal().entries().len();
+        assert_eq!(Recovery::default(), game.heal(&mut rng, razor_id, Healing::FirstAid { hits: 4, rating: 4, kind: DamageType::Stun }).unwrap());
+        assert_eq!(journaled, game.journal().entries().len());
+
+        assert_eq!(Recovery { physical: 2, stun: 0 }, game.heal(&mut rng, razor_id, Healing::Rest { days: 30 }).unwrap());
+        assert_eq!(0, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
+    }
+
+    #[test]
+    pub fn damage_and_healing_stop_at_the_ends_of_the_track_however_many_boxes_are_given()
+    {
+        init();
 
+        let mut game = Game::new();
+        let razor_id = game.add_cast_member(Archetype::StreetSamurai.build(String::from("Razor")));
+        let zorc_id = game.add_cast_member(build_orc());
+        let mut rng = StdRng::seed_from_u64(852);
+        let track_length = game.get_cast_by_id(&razor_id).unwrap().physical_track_max;
+
+        assert!(game.apply_damage(None, razor_id, 200, DamageType::Physical).is_ok());
+        assert_eq!(track_length, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
+        assert!(game.apply_damage(None, razor_id, u8::MAX, DamageType::Physical).is_ok());
+        assert_eq!(track_length, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
+
+        // No track length on the sheet: the boxes pile up to what the track can count, and no further.
+        assert!(game.apply_damage(None, zorc_id, 200, DamageType::Stun).is_ok());
+        assert!(game.apply_damage(None, zorc_id, 200, DamageType::Stun).is_ok());
+        assert_eq!(i8::MAX, game.get_cast_by_id(&zorc_id).unwrap().stun_track_filled);
+
+        assert!(game.heal(&mut rng, razor_id, Healing::Rest { days: 255 }).is_ok());
+        assert_eq!(0, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
     }
 
     #[test]
-    pub fn get_combatants_returns_list_of_all_participants_in_the_fight()
+    pub fn characters_who_are_down_delaying_or_away_are_passed_over_when_their_turn_comes_up()
     {
-        let mut game = Game::new();
+        init();
 
-        let mork = build_orc();
+        let zorc = build_orc();
+        let melf = build_elf();
         let dorf = build_dwarf();
 
-        let ids = populate!(&mut game, mork, dorf);
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf, dorf);
+        let (zorc_id, melf_id, dorf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap(), *ids.get(2).unwrap());
 
-        let combatants = game.get_combatants();
+        assert!(game.update_character(melf_id, CharacterPatch { physical_track_max: Some(9), ..Default::default() }, false).is_ok());
+        assert!(game.apply_damage(None, melf_id, 9, DamageType::Physical).is_ok());
+        assert_eq!(Some(SkipReason::Unconscious), game.skip_reason(&melf_id));
+        assert!(matches!(game.sit_out(Uuid::new_v4(), Some(SkipReason::Delaying)), Err(GameError { kind: ErrorKind::UnknownCastId, .. })));
+        assert_eq!(Ok(false), game.sit_out(dorf_id, Some(SkipReason::Absent)).map_err(|err| err.msg));
 
-        assert_eq!(2, combatants.len());
-        assert!(combatants.contains(ids.get(0).unwrap()));
-        assert!(combatants.contains(ids.get(1).unwrap()));
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 8).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 14).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+
+        // Both ahead of Zorc sit the turn out, so the order starts with him.
+        assert_eq!(Some(vec![zorc_id]), game.waiting_for());
+        assert_eq!(vec![(melf_id, SkipReason::Unconscious), (dorf_id, SkipReason::Absent)], game.take_skipped_turns());
+        assert!(game.take_skipped_turns().is_empty());
 
+        // Delaying on his own turn passes it over at once, ending the pass.
+        assert_eq!(Ok(true), game.sit_out(zorc_id, Some(SkipReason::Delaying)).map_err(|err| err.msg));
+        assert_eq!(vec![(zorc_id, SkipReason::Delaying)], game.take_skipped_turns());
+        assert_eq!(Some(AfterPass::NextRound), game.after_pass());
+
+        assert!(game.end_combat().is_ok());
+        assert_eq!(None, game.skip_reason(&zorc_id));
+        assert_eq!(Some(SkipReason::Absent), game.skip_reason(&dorf_id));
     }
 
     #[test]
-    pub fn get_combatants_returns_only_cast_members_registered_in_current_combat_session()
+    pub fn a_table_carrying_over_keeps_damage_ammunition_and_edge_until_the_gm_calls_a_rest()
     {
-        let mut game = Game::new();
+        init();
 
-        let mork = build_orc();
-        let dorf = build_dwarf();
+        let razor = Archetype::StreetSamurai.build(String::from("Razor"));
         let melf = build_elf();
+        let dorf = build_dwarf();
 
-        let ids = populate!(&mut game, mork, dorf);
-        game.add_cast_member(melf);
+        let mut game = Game::new();
+        let ids = populate!(&mut game, razor, melf, dorf);
+        let (razor_id, melf_id, dorf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap(), *ids.get(2).unwrap());
 
-        let combatants = game.get_combatants();
+        let razor = |game: &Game| game.get_cast_by_id(&razor_id).unwrap();
+        let burst = CatalogAction { name: String::from("Long burst"), cost: ActionType::Simple, description: String::from("Hose the room."),
+            ammunition: Ammunition::Fires(8) };
+        assert!(game.define_catalog_action(burst).is_ok());
 
-        assert_eq!(2, combatants.len());
-        assert!(combatants.contains(ids.get(0).unwrap()));
-        assert!(combatants.contains(ids.get(1).unwrap()));
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(razor_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 8).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.apply_damage(None, razor_id, 3, DamageType::Physical).is_ok());
+        assert!(game.take_catalog_action(razor_id, "Long burst").is_ok());
+        assert_eq!(Ok(1), game.spend_edge(razor_id, 1).map_err(|err| err.msg));
+        assert!(game.end_combat().is_ok());
+
+        assert_eq!(3, razor(&game).physical_track_filled);
+        assert_eq!(7, razor(&game).weapons[0].firing_features[0].loaded);
+
+        let mut rematch = game.rematch();
+        assert_eq!(3, razor(&rematch).physical_track_filled);
+        assert!(rematch.spend_edge(razor_id, 1).is_ok());
+        assert!(matches!(rematch.spend_edge(razor_id, 1), Err(GameError { kind: ErrorKind::NoEdgeLeft, .. })));
+
+        assert!(matches!(game.rest(&[razor_id, Uuid::new_v4()]), Err(GameError { kind: ErrorKind::UnknownCastId, .. })));
+        assert_eq!(3, razor(&game).physical_track_filled);
+        assert!(game.rest(&[razor_id]).is_ok());
+        assert_eq!(0, razor(&game).physical_track_filled);
+        assert_eq!(15, razor(&game).weapons[0].firing_features[0].loaded);
+        assert_eq!(Ok(2), game.spend_edge(razor_id, 0).map_err(|err| err.msg));
+
+        // Without carry over, the end of the fight is a rest for the player characters in it, and only for them.
+        game.set_carry_over(false);
+        assert!(game.add_combatant(razor_id).is_ok());
+        assert!(game.add_combatant(dorf_id).is_ok());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.apply_damage(None, razor_id, 2, DamageType::Stun).is_ok());
+        assert!(game.apply_damage(None, dorf_id, 2, DamageType::Stun).is_ok());
+        assert!(game.rematch().get_cast_by_id(&razor_id).is_some_and(|character| character.stun_track_filled == 0));
+        assert!(game.end_combat().is_ok());
+        assert_eq!(0, razor(&game).stun_track_filled);
+        assert_eq!(2, game.get_cast_by_id(&dorf_id).unwrap().stun_track_filled);
     }
 
     #[test]
-    pub fn when_get_combatants_is_called_with_no_active_combatants_an_empty_list_is_returned()
+    pub fn armor_penetration_at_either_extreme_saturates_instead_of_overflowing()
     {
-        let mut game = Game::new();
-
-        let mork = build_orc();
-        let dorf = build_dwarf();
-        let melf = build_elf();
-
-        game.add_cast_member(mork);
-        game.add_cast_member(dorf);
-        game.add_cast_member(melf);
+        init();
 
-        let combatants = game.get_combatants();
+        let mut game = Game::new();
+        let ids = populate!(&mut game, Archetype::StreetSamurai.build(String::from("Razor")));
+        let razor_id = *ids.get(0).unwrap();
 
-        assert_eq!(0, combatants.len());
+        assert_eq!(Ok(DamageType::Stun), game.damage_against_armor(razor_id, 12, DamageType::Physical, ArmorTestType::Ballistic, i8::MAX)
+            .map_err(|err| err.msg));
+        assert_eq!(Ok(DamageType::Physical), game.damage_against_armor(razor_id, 1, DamageType::Physical, ArmorTestType::Ballistic, i8::MIN)
+            .map_err(|err| err.msg));
     }
 
     #[test]
-    pub fn get_combatants_is_independent_of_cast_list()
+    pub fn a_new_game_carries_damage_from_one_fight_to_the_next()
     {
-        let mut game = Game::new();
-
-        let combatants = game.get_combatants();
-        assert_eq!(0, combatants.len());
-
-        let mork = build_orc();
-        let dorf = build_dwarf();
-        let melf = build_elf();
+        init();
 
-        game.add_cast_member(mork);
-        game.add_cast_member(dorf);
-        game.add_cast_member(melf);
+        let mut game = Game::new();
+        assert!(game.carry_over());
+        let ids = populate!(&mut game, Archetype::StreetSamurai.build(String::from("Razor")));
+        let razor_id = *ids.get(0).unwrap();
 
-        let combatants = game.get_combatants();
-        assert_eq!(0, combatants.len());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(razor_id, 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        assert!(game.apply_damage(None, razor_id, 3, DamageType::Physical).is_ok());
+        assert!(game.end_combat().is_ok());
 
-        assert_eq!(0, combatants.len());
+        assert_eq!(3, game.get_cast_by_id(&razor_id).unwrap().physical_track_filled);
     }
 
     #[test]
-    pub fn when_combat_is_ended_the_game_resets_the_combatants_list_and_resets_game_state()
+    pub fn casting_a_spell_queues_resistance_and_drain_and_sustaining_it_penalises_the_caster()
     {
-        let mut game = Game::new();
+        init();
 
-        let mork = build_orc();
-        let dorf = build_dwarf();
+        let zorc = build_orc();
         let melf = build_elf();
 
-        let ids = populate!(&mut game, mork, dorf, melf);
+        let mut game = Game::new();
+        let ids = populate!(&mut game, zorc, melf);
+        let (zorc_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
         assert!(game.start_initiative_phase().is_ok());
-
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 13).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 22).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 14).is_ok());
-
+        assert!(game.accept_initiative_roll(zorc_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert_eq!(game.get_combatants().len(), 3);
+        let declaration = SpellDeclaration { caster: zorc_id, spell: String::from("Stunbolt"), force: 5, targets: vec![melf_id], sustained: true };
+        let prompts = game.cast_spell(&declaration).unwrap();
 
-        game.end_combat();
-        assert_eq!(game.get_combatants().len(), 0);
-        assert_eq!(game.current_state(), String::from("PreCombat"));
-        let on_deck = game.on_deck();
-        assert!(on_deck.is_none());
-        
+        assert_eq!(2, prompts.len());
+        assert!(prompts.iter().any(|prompt| prompt.character_id == melf_id && prompt.test == ResistanceTest::Spell));
+        assert!(prompts.iter().any(|prompt| prompt.character_id == zorc_id && prompt.test == ResistanceTest::Drain));
+        assert_eq!(prompts, game.pending_resistance_tests());
+        assert!(game.take_action(zorc_id, ActionType::Simple).is_err());
 
+        assert_eq!(-2, game.sustaining_modifier(zorc_id));
+        assert!(game.drop_spell(zorc_id, "Stunbolt").is_ok());
+        assert!(game.drop_spell(zorc_id, "Stunbolt").is_err());
+        assert_eq!(0, game.sustaining_modifier(zorc_id));
     }
 
     #[test]
-    pub fn adding_non_cast_member_to_combat_results_in_unrecognized_character_error()
+    pub fn augmentations_grant_extra_passes_and_free_actions_when_a_character_joins_combat()
     {
-        let mut game = Game::new();
+        init();
 
-        let combatant_id = Uuid::new_v4();
+        let mut sammy = build_orc();
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Wired Reflexes 2"), source: ModifierSource::Augmentation, target: ModifierTarget::InitiativePasses, value: 2 });
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Quick Hands"), source: ModifierSource::Quality, target: ModifierTarget::FreeActions, value: 1 });
+        let melf = build_elf();
 
-        let result = game.add_combatant(combatant_id);
+        let mut game = Game::new();
+        let ids = populate!(&mut game, sammy, melf);
+        let (sammy_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        assert!(result.is_err());
-        match result
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnknownCastId => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
+        assert_eq!(2, game.combatant_data.get(&sammy_id).unwrap().initiative_passes);
+        assert_eq!(0, game.combatant_data.get(&melf_id).unwrap().initiative_passes);
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert_eq!(ActionBudget { free: 2, simple: 2, complex: 1 }, game.remaining_actions(sammy_id).unwrap());
+        assert_eq!(ActionBudget { free: 1, simple: 2, complex: 1 }, game.remaining_actions(melf_id).unwrap());
     }
 
     #[test]
-    pub fn any_real_cast_member_may_be_added_to_combat()
+    pub fn an_anarchy_game_gives_every_combatant_a_single_action_and_a_single_pass()
     {
-        let dorf = Character::new_npc(Metatypes::Dwarf, String::from("Dorf"));
-        let torll = Character::new_npc(Metatypes::Troll, String::from("Torll"));
+        init();
+
+        let mut sammy = build_orc();
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Wired Reflexes 2"), source: ModifierSource::Augmentation, target: ModifierTarget::InitiativePasses, value: 2 });
+        let melf = build_elf();
+
         let mut game = Game::new();
+        game.set_edition(Edition::Anarchy);
+        let ids = populate!(&mut game, sammy, melf);
+        let (sammy_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
+        assert_eq!(0, game.combatant_data.get(&sammy_id).unwrap().initiative_passes);
 
-        let combatant_id = game.add_cast_member(dorf);
-        game.add_cast_member(torll);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(sammy_id, 23).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        let result = game.add_combatant(combatant_id);
+        assert_eq!(ActionBudget { free: 0, simple: 1, complex: 1 }, game.remaining_actions(sammy_id).unwrap());
+        assert!(game.take_action(sammy_id, ActionType::Free).is_err());
+        assert!(game.take_action(sammy_id, ActionType::Simple).is_ok());
+        assert_eq!(ActionBudget { free: 0, simple: 0, complex: 0 }, game.remaining_actions(sammy_id).unwrap());
+        assert!(game.take_action(sammy_id, ActionType::Complex).is_err());
 
-        assert!(result.is_ok());
+        game.set_edition(Edition::SR4);
+        assert_eq!(2, game.combatant_data.get(&sammy_id).unwrap().initiative_passes);
     }
 
     #[test]
-    pub fn multiple_combatants_may_be_added_in_a_vector_of_ids()
+    pub fn a_generic_game_takes_initiative_in_descending_order_in_one_pass_under_the_gms_own_action_names()
     {
-        let dorf = build_dwarf();
-        let mork = build_orc();
+        init();
+
+        let mut sammy = build_orc();
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Wired Reflexes 2"), source: ModifierSource::Augmentation, target: ModifierTarget::InitiativePasses, value: 2 });
         let melf = build_elf();
 
         let mut game = Game::new();
+        game.set_edition(Edition::Generic);
+        let ids = populate!(&mut game, sammy, melf);
+        let (sammy_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
+        assert_eq!(0, game.combatant_data.get(&sammy_id).unwrap().initiative_passes);
 
-        let dorf_id = game.add_cast_member(dorf);
-        let mork_id = game.add_cast_member(mork);
-        let melf_id = game.add_cast_member(melf);
-
-        let mut combatants = Vec::<Uuid>::new();
-        combatants.push(dorf_id);
-        combatants.push(mork_id);
-        combatants.push(melf_id);
-
-        assert!(game.add_combatants(combatants).is_ok());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(sammy_id, 8).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 17).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
+        assert!(game.take_action(sammy_id, ActionType::Complex).is_err());
+        assert!(game.take_action(melf_id, ActionType::Free).is_ok());
+        assert!(game.take_action(melf_id, ActionType::Complex).is_ok());
+        assert_eq!(ActionBudget { free: 0, simple: 0, complex: 0 }, game.remaining_actions(melf_id).unwrap());
+
+        assert_eq!("Action", game.action_labels().label(ActionType::Simple));
+        game.set_action_labels(Some(ActionLabels { free: String::from("Reaction"), simple: String::from("Move"), complex: String::from("Standard") }));
+        assert_eq!("Standard", game.action_labels().label(ActionType::Complex));
+        assert_eq!("Standard", game.rematch().action_labels().label(ActionType::Complex));
+        game.set_action_labels(None);
+        assert_eq!("Bonus action", game.action_labels().label(ActionType::Free));
     }
 
     #[test]
-    pub fn any_unknown_character_ids_in_vec_cause_unknown_character_error_from_add_combatants()
+    pub fn a_gm_override_replaces_the_passes_derived_from_the_character_sheet()
     {
-        let dorf = build_dwarf();
-        let _mork = build_orc();
-        let _melf = build_elf();
+        init();
+
+        let mut sammy = build_orc();
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Wired Reflexes 1"), source: ModifierSource::Augmentation, target: ModifierTarget::InitiativePasses, value: 1 });
+        Arc::make_mut(&mut sammy.modifiers).push(Modifier { name: String::from("Hot Sim"), source: ModifierSource::Augmentation, target: ModifierTarget::MatrixPasses, value: 1 });
+        let melf = build_elf();
 
         let mut game = Game::new();
+        let ids = populate!(&mut game, sammy, melf);
+        let (sammy_id, melf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let dorf_id = game.add_cast_member(dorf);
+        assert_eq!(4, game.combatant_data.get(&sammy_id).unwrap().matrix_passes);
+        assert!(game.override_initiative_passes(melf_id, Some(2)).is_ok());
+        assert!(game.override_initiative_passes(Uuid::new_v4(), Some(2)).is_err());
 
-        let mut combatants = Vec::<Uuid>::new();
-        combatants.push(dorf_id);
-        combatants.push(Uuid::new_v4());
-        combatants.push(Uuid::new_v4());
+        assert!(game.start_initiative_phase().is_ok());
+        assert_eq!(1, game.combatant_data.get(&sammy_id).unwrap().initiative_passes);
+        assert_eq!(2, game.combatant_data.get(&melf_id).unwrap().initiative_passes);
 
-        match game.add_combatants(combatants)
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnknownCastId => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
+        assert!(game.override_initiative_passes(melf_id, None).is_ok());
+        assert_eq!(0, game.combatant_data.get(&melf_id).unwrap().initiative_passes);
     }
 
     #[test]
-    pub fn when_a_game_has_combatants_it_may_enter_into_initiative_phase()
+    pub fn soak_pools_account_for_armor_penetration_degradation_and_called_shots()
     {
-        let melf = Character::new_npc(Metatypes::Elf, String::from("Melf"));
-        let zorc = Character::new_npc(Metatypes::Orc, String::from("Zorc"));
-        let mut game = Game::new();
+        init();
 
-        let melf_id = game.add_cast_member(melf);
-        let zorc_id = game.add_cast_member(zorc);
+        let mut zorc = build_orc();
+        Arc::make_mut(&mut zorc.stats).insert(String::from("Body"), 5);
+        Arc::make_mut(&mut zorc.armor).push(Armour { name: String::from("Armor Jacket"), ballistic_rating: 8, impact_rating: 6, degradation: 0 });
 
-        assert!(game.add_combatant(melf_id).is_ok());
-        assert!(game.add_combatant(zorc_id).is_ok());
+        let mut game = Game::new();
+        let zorc_id = game.add_cast_member(zorc);
 
-        let result = game.start_initiative_phase();
+        assert_eq!(13, game.soak_pool(zorc_id, ArmorTestType::Ballistic, 0, false).unwrap());
+        assert_eq!(11, game.soak_pool(zorc_id, ArmorTestType::Impact, 0, false).unwrap());
+        assert_eq!(10, game.soak_pool(zorc_id, ArmorTestType::Ballistic, -3, false).unwrap());
+        assert_eq!(5, game.soak_pool(zorc_id, ArmorTestType::Ballistic, -10, false).unwrap());
+        assert_eq!(5, game.soak_pool(zorc_id, ArmorTestType::Ballistic, 0, true).unwrap());
 
-        assert!(result.is_ok());
-        assert_eq!(game.current_state(), String::from("Initiative Rolls"));
+        assert!(game.degrade_armor(zorc_id, 0, 2).is_ok());
+        assert!(game.degrade_armor(zorc_id, 1, 2).is_err());
+        assert_eq!(11, game.soak_pool(zorc_id, ArmorTestType::Ballistic, 0, false).unwrap());
+        assert!(game.soak_pool(Uuid::new_v4(), ArmorTestType::Ballistic, 0, false).is_err());
     }
 
     #[test]
-    pub fn when_all_combatants_have_supplied_initiative_combat_round_phase_may_start()
+    pub fn initiative_order_lists_every_remaining_combatant_from_highest_to_lowest()
     {
+        init();
+
         let mut game = Game::new();
-        let melf = build_elf();
-        let mork = build_orc();
         let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
 
-        let ids = populate!(&mut game, melf, mork, dorf);
+        let ids = populate!(&mut game, dorf, mork, belf);
 
-        // transition from PreCombat
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 18).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
-    
-        // transition into InitiativePass
+        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 8).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 20).is_ok());
+        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 14).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
+        let order = game.initiative_order();
+        assert_eq!(vec![(*ids.get(1).unwrap(), 20), (*ids.get(2).unwrap(), 14), (*ids.get(0).unwrap(), 8)], order);
     }
 
     #[test]
-    pub fn if_game_is_in_precombat_phase_with_no_combatants_start_initiative_phase_generates_no_combatants()
+    pub fn a_roll_macro_rolls_its_pool_under_its_own_name_and_lands_in_the_journal()
     {
-        let lef = Character::new_npc(Metatypes::Elf, String::from("Lef"));
-
-        let mut game = Game::new();
-
-        game.add_cast_member(lef);
-
-        let result = game.start_initiative_phase();
-
-        assert!(result.is_err());
-
-        match result
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnknownCastId => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
-
-        assert_eq!(game.current_state(), String::from("PreCombat"));
-    }
+        init();
 
-    #[test]
-    pub fn adding_initiative_roll_before_entering_initiative_phase_results_in_invalid_state_action()
-    {
-        let lef = Character::new_npc(Metatypes::Elf, String::from("Lef"));
         let mut game = Game::new();
+        let mork = build_orc();
+        let ids = populate!(&mut game, mork);
+        let mork_id = *ids.get(0).unwrap();
 
-        let lef_id = game.add_cast_member(lef);
-        assert!(game.add_combatant(lef_id).is_ok());
+        let roll_macro = RollMacro::parse("Ares Predator attack: 14d6").unwrap();
+        assert_eq!(14, roll_macro.pool);
+        assert!(RollMacro::parse("Fireball: 3d8").is_none());
+        assert!(game.define_roll_macro(mork_id, roll_macro).is_ok());
 
-        let result = game.accept_initiative_roll(lef_id, 4);
+        let mut rng = rand::thread_rng();
+        let record = game.roll_macro(&mut rng, mork_id, "Ares Predator attack").unwrap();
+        assert_eq!(14, record.roll.dice.len());
+        assert_eq!("Ares Predator attack", record.label);
+        assert!(matches!(game.roll_macro(&mut rng, mork_id, "Punch"), Err(GameError{kind: ErrorKind::UnknownRollMacro, ..})));
 
-        assert!(result.is_err());
-        match result
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
+        assert_eq!(Some(&JournalEvent::Roll(record)), game.journal().entries().last().map(|entry| &entry.event));
     }
 
     #[test]
-    pub fn adding_an_initiative_for_a_combatant_in_initiative_phase_will_return_ok()
+    pub fn rewinding_a_turn_restores_the_previous_turns_order_and_undoes_actions_taken_since()
     {
         init();
 
-        let zorc = build_orc();
+        let mut game = Game::new();
         let dorf = build_dwarf();
+        let mork = build_orc();
+
+        let ids = populate!(&mut game, dorf, mork);
+        let (dorf_id, mork_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dorf);
         assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 10).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        let result = game.accept_initiative_roll(*ids.get(0).unwrap(), 4);
+        assert!(matches!(game.rewind(RewindTarget::PreviousTurn), Err(GameError{kind: ErrorKind::NothingToRewind, ..})));
+
+        assert!(game.take_action(dorf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert_eq!(Some(vec![mork_id]), game.currently_up());
+
+        assert!(game.rewind(RewindTarget::PreviousTurn).is_ok());
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
+        assert!(game.take_action(dorf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
 
-        assert!(result.is_ok());
+        assert!(game.rewind(RewindTarget::PassStart).is_ok());
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
+        assert_eq!(Some(&JournalEvent::Rewound(RewindTarget::PassStart)), game.journal().entries().last().map(|entry| &entry.event));
     }
 
     #[test]
-    pub fn adding_multiple_initiatives_to_real_combatants_will_produce_ok_for_each()
+    pub fn rewinding_a_turn_breaks_off_engagements_made_since()
     {
         init();
 
-
-        let zorc = build_orc();
+        let mut game = Game::new();
         let dorf = build_dwarf();
-        
+        let mork = build_orc();
+
+        let ids = populate!(&mut game, dorf, mork);
+        let (dorf_id, mork_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dorf);
         assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 10).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        let result = game.accept_initiative_roll(*ids.get(0).unwrap(), 2);
-        assert!(result.is_ok());
-        let result = game.accept_initiative_roll(*ids.get(1).unwrap(), 13);
-        assert!(result.is_ok());
+        assert!(game.engage(dorf_id, mork_id).is_ok());
+        assert!(game.take_action(dorf_id, ActionType::Simple).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.are_engaged(dorf_id, mork_id));
+
+        assert!(game.rewind(RewindTarget::PreviousTurn).is_ok());
+        assert!(game.engagements().is_empty());
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
     }
 
     #[test]
-    pub fn add_initiative_for_character_id_not_in_cast_produces_unknown_cast_id()
+    pub fn only_the_newest_checkpoints_are_kept()
     {
         init();
 
-        let zorc = build_orc();
-        let mork = build_orc();
-
         let mut game = Game::new();
-        populate!(&mut game, zorc, mork);
-        assert!(game.start_initiative_phase().is_ok());
-
-        let result = game.accept_initiative_roll(Uuid::new_v4(), 2);
-        assert!(result.is_err());
-        match result
+        for checkpoint in 0..=CHECKPOINT_CAPACITY
         {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnknownCastId => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
+            game.save_checkpoint(&checkpoint.to_string());
         }
+
+        let kept = game.checkpoints();
+        assert_eq!(CHECKPOINT_CAPACITY, kept.len());
+        assert_eq!(Some(&String::from("1")), kept.first());
+        assert_eq!(Some(&CHECKPOINT_CAPACITY.to_string()), kept.last());
+        assert!(matches!(game.restore_checkpoint("0"), Err(GameError { kind: ErrorKind::UnknownCheckpoint, .. })));
     }
 
     #[test]
-    pub fn as_initiative_rolls_are_added_game_initiative_state_updates()
+    pub fn restoring_a_checkpoint_brings_back_the_cast_and_combat_state_as_saved()
     {
         init();
 
-        let zorc = build_orc();
-        let mork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, mork);
-        assert!(game.start_initiative_phase().is_ok());
+        let dorf = build_dwarf();
+        let mork = build_orc();
 
-        assert!(game.are_any_initiatives_outstanding());
-        assert!(game.collect_undeclared_initiatives().len() == 2);
-        assert!(game.collect_undeclared_initiatives().contains(ids.get(0).unwrap()));
-        assert!(game.collect_undeclared_initiatives().contains(ids.get(1).unwrap()));
+        let ids = populate!(&mut game, dorf, mork);
+        let (dorf_id, mork_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap());
 
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
+        game.save_checkpoint("before the ambush");
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 20).is_ok());
+        game.retire_cast_member(mork_id);
 
-        assert!(game.are_any_initiatives_outstanding());
-        assert!(game.collect_undeclared_initiatives().len() == 1);
-        assert!(game.collect_undeclared_initiatives().contains(ids.get(1).unwrap()));
-        assert!(!game.collect_undeclared_initiatives().contains(ids.get(0).unwrap()));
+        assert!(matches!(game.restore_checkpoint("after the ambush"), Err(GameError{kind: ErrorKind::UnknownCheckpoint, ..})));
+        assert!(game.restore_checkpoint("before the ambush").is_ok());
 
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
-        assert!(!game.are_any_initiatives_outstanding());
-        assert!(game.collect_undeclared_initiatives().len() == 0);
+        assert_eq!(2, game.cast_size());
+        assert!(!game.is_in_combat());
+        assert_eq!(vec![String::from("before the ambush")], game.checkpoints());
     }
 
     #[test]
-    pub fn when_all_initiatives_submitted_game_may_start_combat_rounds()
+    pub fn a_launched_encounter_starts_combat_with_its_preset_initiative_modifiers_and_hidden_npcs()
     {
         init();
 
-        let zorc = build_orc();
-        let mork = build_orc();
-        let dork = build_dwarf();
-
+        // Staged combatants join the fight when the encounter launches, so they are only added to the cast here.
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, mork, dork);
+        let dorf_id = game.add_cast_member(build_dwarf());
+        let mork_id = game.add_cast_member(build_orc());
+        game.add_cast_member(build_elf());
+
+        let mut ambush = StagedEncounter::new("ambush");
+        ambush.combatants = vec![dorf_id, mork_id];
+        ambush.initiative_modifiers.insert(mork_id, 4);
+        ambush.hidden.push(mork_id);
+
+        let mut bad = StagedEncounter::new("bad");
+        bad.combatants.push(Uuid::new_v4());
+        assert!(game.stage_encounter(bad).is_err());
+        assert!(game.stage_encounter(ambush).is_ok());
+
+        assert!(matches!(game.launch_encounter("picnic"), Err(GameError{kind: ErrorKind::UnknownEncounter, ..})));
+        assert!(game.launch_encounter("ambush").is_ok());
+        assert!(game.staged_encounters().is_empty());
+        assert!(game.is_hidden(&mork_id));
+        assert!(!game.is_hidden(&dorf_id));
 
         assert!(game.start_initiative_phase().is_ok());
-
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 4).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 8).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 3).is_ok());
-
-        let result = game.start_combat_rounds();
-
-        assert_eq!(game.current_state(), String::from("Initiative Pass"));
-        assert!(result.is_ok());
-
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 10).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
+        assert_eq!(vec![(mork_id, 14), (dorf_id, 12)], game.initiative_order());
     }
 
     #[test]
-    pub fn starting_combat_rounds_before_all_initiatives_submitted_produces_invalid_state_action()
+    pub fn an_encounters_triggers_fire_as_the_fight_reaches_their_round_and_pass()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
-        let mut game = Game::new ();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let mut game = Game::new();
+        let dorf_id = game.add_cast_member(build_dwarf());
+        let mork_id = game.add_cast_member(build_orc());
+
+        let mut ambush = StagedEncounter::new("ambush");
+        ambush.combatants = vec![dorf_id];
+        let backup = ScriptedTrigger { round: 1, pass: 2, action: TriggerAction::Reinforcements(vec![mork_id]) };
+        let sirens = ScriptedTrigger { round: 2, pass: 1, action: TriggerAction::Prompt(String::from("Sirens in the distance")) };
+        let overdue = ScriptedTrigger { round: 1, pass: 9, action: TriggerAction::Prompt(String::from("The roof gives way")) };
+        ambush.triggers = vec![backup.clone(), sirens.clone(), overdue.clone()];
+        assert!(game.stage_encounter(ambush).is_ok());
+        assert!(game.launch_encounter("ambush").is_ok());
+        assert!(game.override_initiative_passes(dorf_id, Some(1)).is_ok());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 1).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 15).is_ok());
-
-        let result = game.start_combat_rounds();
+        assert_eq!(1, game.current_round());
+        assert!(game.take_fired_triggers().is_empty());
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.start_combat_rounds().is_ok());
 
-        assert_eq!(game.current_state(), String::from("Initiative Rolls"));
-        assert!(result.is_err());
+        assert!(game.take_action(dorf_id, ActionType::Complex).is_ok());
+        assert!(matches!(game.advance_round(), Err(GameError { kind: ErrorKind::EndOfInitiative, .. })));
+        assert!(game.next_initiative_pass().is_ok());
+        assert_eq!(vec![backup], game.take_fired_triggers());
+        assert!(game.get_combatants().contains(&mork_id));
 
-        match result
+        loop
         {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
+            for id in game.waiting_for().unwrap_or_default()
             {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
+                assert!(game.take_action(id, ActionType::Complex).is_ok());
+            }
+            match game.advance_round()
+            {
+                Ok(()) => continue,
+                Err(_) if game.next_initiative_pass().is_ok() => continue,
+                Err(_) => break,
+            }
         }
+
+        assert!(game.start_initiative_phase().is_ok());
+        assert_eq!(2, game.current_round());
+        assert_eq!(vec![sirens, overdue], game.take_fired_triggers());
+        assert!(game.take_fired_triggers().is_empty());
     }
 
     #[test]
-    pub fn attempting_to_move_to_next_initiative_pass_fails_with_unresolved_combatant_if_there_are_unresolved_players_in_init_sequence()
+    pub fn the_initiative_preview_orders_submitted_rolls_and_lists_who_has_not_rolled()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let dorf = build_dwarf();
+        let mork = build_orc();
+        let belf = build_elf();
+
+        let ids = populate!(&mut game, dorf, mork, belf);
+        let (dorf_id, mork_id, belf_id) = (*ids.get(0).unwrap(), *ids.get(1).unwrap(), *ids.get(2).unwrap());
 
+        assert!(game.preview_initiative_order().is_err());
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-        assert!(game.start_combat_rounds().is_ok());
-        
-        // Attempting to advance to the next initiative pass should result in failure.
-        let result = game.next_initiative_pass();
-        assert!(result.is_err());
-        match result
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
+        assert!(game.accept_initiative_roll(dorf_id, 7).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 15).is_ok());
 
+        let preview = game.preview_initiative_order().unwrap();
+        assert_eq!(vec![(mork_id, 15), (dorf_id, 7)], preview.order);
+        assert_eq!(vec![belf_id], preview.outstanding);
     }
 
     #[test]
-    pub fn advancing_to_next_initiative_pass_if_no_character_has_another_pass_results_in_error_end_of_initiative_pass()
+    pub fn rolling_initiative_for_a_character_adds_their_reaction_and_intuition_to_their_initiative_dice()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let mut mork = build_orc();
+        Arc::make_mut(&mut mork.stats).insert(String::from("Reaction"), 4);
+        Arc::make_mut(&mut mork.stats).insert(String::from("Intuition"), 3);
+        let ids = populate!(&mut game, mork, build_dwarf());
+        let (mork_id, dorf_id) = (ids[0], ids[1]);
 
+        let mut rng = StdRng::seed_from_u64(896);
+        assert!(game.roll_initiative(&mut rng, mork_id).is_err());
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-        assert!(game.start_combat_rounds().is_ok());
-
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_err());
-
-        // assert!(game.next_initiative_pass().is_err());
-        match game.next_initiative_pass()
-        {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::EndOfInitiativePass => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
-        }
+        let roll = game.roll_initiative(&mut rng, mork_id).unwrap();
+        assert!((8..=13).contains(&roll));
 
+        let preview = game.preview_initiative_order().unwrap();
+        assert_eq!(vec![(mork_id, roll)], preview.order);
+        assert_eq!(vec![dorf_id], preview.outstanding);
     }
 
     #[test]
-    pub fn advancing_to_next_pass_before_begin_combat_round_generates_unresolved_combatant()
+    pub fn a_sheets_initiative_formula_is_what_gets_rolled_and_what_typed_in_rolls_are_held_to()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
+        assert_eq!(Some(InitiativeFormula { base: 8, dice: 3 }), InitiativeFormula::parse("8 + 3d6"));
+        assert_eq!(Some(InitiativeFormula { base: 8, dice: 3 }), InitiativeFormula::parse("3D6+8"));
+        assert_eq!(Some(InitiativeFormula { base: 0, dice: 2 }), InitiativeFormula::parse("2d6"));
+        for refused in ["", "8 + 3d8", "3d6 + 2d6", "-2 + 1d6", "100 + 5d6", "0d6"]
+        {
+            assert!(InitiativeFormula::parse(refused).is_none(), "{} should not parse", refused);
+        }
 
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_orc(), build_dwarf());
+        let (mork_id, dorf_id) = (ids[0], ids[1]);
+        let formula = InitiativeFormula::parse("8 + 3d6").unwrap();
+        let patch = CharacterPatch { initiative: Some(formula), ..Default::default() };
+        assert!(game.update_character(mork_id, patch.clone(), false).is_ok());
+        assert!(game.update_character(dorf_id, patch, false).is_ok());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-        
-        // assert!(game.next_initiative_pass().is_err());
-        match game.next_initiative_pass()
+        for out_of_range in [10, 27]
         {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::InvalidStateAction => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
+            assert!(matches!(game.accept_initiative_roll(mork_id, out_of_range), Err(GameError { kind: ErrorKind::InitiativeOutOfRange, .. })));
         }
-        
+        assert!(game.accept_initiative_roll(mork_id, 26).is_ok());
+
+        let mut rng = StdRng::seed_from_u64(906);
+        let roll = game.roll_initiative(&mut rng, dorf_id).unwrap();
+        assert!((11..=26).contains(&roll));
     }
 
     #[test]
-    pub fn initiative_may_advance_when_all_combatants_at_current_initiative_have_resolved()
+    pub fn the_round_timeline_records_the_pass_and_initiative_each_action_was_taken_on()
     {
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
+        init();
 
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc());
+        let (dorf_id, zorc_id) = (ids[0], ids[1]);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 7).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_err());
-        
+        assert!(game.take_action(dorf_id, ActionType::Complex).is_ok());
+
+        let rounds = game.round_timeline(true);
+        assert_eq!(1, rounds.len());
+        assert_eq!(vec![(dorf_id, 12), (zorc_id, 7)], rounds[0].initiatives);
+        assert_eq!(1, rounds[0].turns.len());
+        assert_eq!((0, Some(12), dorf_id), (rounds[0].turns[0].pass, rounds[0].turns[0].initiative, rounds[0].turns[0].character));
+
+        // Dorf is an NPC, so the table sees him act but not his score.
+        let rounds = game.round_timeline(false);
+        assert_eq!(vec![(zorc_id, 7)], rounds[0].initiatives);
+        assert_eq!((0, None, dorf_id), (rounds[0].turns[0].pass, rounds[0].turns[0].initiative, rounds[0].turns[0].character));
     }
 
     #[test]
-    pub fn advancing_initiative_before_starting_initiative_character_has_acted_generates_unresolved_combatant()
+    pub fn the_journal_after_an_entry_keeps_whispers_and_npc_numbers_from_players_but_not_from_the_gm()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc());
+        let (dorf_id, zorc_id) = (ids[0], ids[1]);
 
+        let first = game.record_chat(ChatLine { from: alice, audience: ChatAudience::Table, text: String::from("Here they come.") });
+        game.record_chat(ChatLine { from: bob, audience: ChatAudience::Gm, text: String::from("I slip out the back.") });
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 7).is_ok());
+        assert!(game.apply_damage(Some(zorc_id), dorf_id, 3, DamageType::Physical).is_ok());
+        assert!(game.apply_damage(Some(dorf_id), zorc_id, 2, DamageType::Stun).is_ok());
+
+        assert_eq!(6, game.journal_after(Some(first), alice, true).len());
+        let kinds = game.journal_after(Some(first), alice, false).iter().map(|entry| entry.event.kind()).collect::<Vec<JournalEventKind>>();
+        assert_eq!(vec![JournalEventKind::RoundStarted, JournalEventKind::InitiativeRolled, JournalEventKind::Damage], kinds);
+        assert_eq!(4, game.journal_after(Some(first), bob, false).len());
+
+        game.set_visibility(VisibilityOptions { npc_wounds: WoundDisclosure::Exact });
+        assert_eq!(4, game.journal_after(None, alice, false).iter().filter(|entry| entry.event.kind() != JournalEventKind::InitiativeRolled).count());
+    }
+
+    #[test]
+    pub fn available_actions_follow_the_phase_the_turn_and_what_the_combatant_has_left()
+    {
+        init();
 
+        let mut game = Game::new();
+        let mut zorc = build_orc();
+        Arc::make_mut(&mut zorc.stats).insert(String::from("Magic"), 4);
+        let ids = populate!(&mut game, build_dwarf(), zorc);
+        let (dorf_id, zorc_id) = (ids[0], ids[1]);
+
+        assert!(game.available_actions(Uuid::new_v4()).is_err());
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.available_actions(dorf_id).unwrap().contains(&AvailableAction::RollInitiative));
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(zorc_id, 7).is_ok());
+        assert!(!game.available_actions(dorf_id).unwrap().contains(&AvailableAction::RollInitiative));
         assert!(game.start_combat_rounds().is_ok());
 
-        // assert!(game.next_initiative().is_err());
-        match game.advance_round()
+        let dorf = game.available_actions(dorf_id).unwrap();
+        for action in [AvailableAction::Free, AvailableAction::Simple, AvailableAction::Complex, AvailableAction::Move, AvailableAction::FullDefense]
         {
-            Ok(_) => {panic!("This should not have succeeded.")},
-            Err(err) => 
-            {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
-                    _ => {panic!("Test expected different error type (UnknownCastId)");}
-                }
-            },
+            assert!(dorf.contains(&action), "{:?} should be open to the combatant who is up", action);
         }
+        assert!(!dorf.contains(&AvailableAction::CastSpell) && !dorf.contains(&AvailableAction::Project));
 
-    }
+        let zorc = game.available_actions(zorc_id).unwrap();
+        assert!(zorc.contains(&AvailableAction::Free) && zorc.contains(&AvailableAction::Project));
+        assert!(!zorc.contains(&AvailableAction::Simple) && !zorc.contains(&AvailableAction::CastSpell) && !zorc.contains(&AvailableAction::Move));
 
-    // // TODO: Add tests to ensure advance_initiative_pass succeeds when at least one character or event occurs on the next pass.
-    // // Currently cannot do that because there's no Game infrastructure to calculate or influence the pass count for any character,
-    // // or add some on-next-pass event.
+        assert!(game.take_action(dorf_id, ActionType::Simple).is_ok());
+        let dorf = game.available_actions(dorf_id).unwrap();
+        assert!(dorf.contains(&AvailableAction::Simple) && !dorf.contains(&AvailableAction::Complex));
+
+        assert!(game.go_full_defense(dorf_id).is_ok());
+        assert!(!game.available_actions(dorf_id).unwrap().contains(&AvailableAction::FullDefense));
+    }
 
     #[test]
-    pub fn a_resolving_character_can_take_two_simple_actions_but_not_one_simple_one_complex_nor_three_simple()
+    pub fn damaging_a_character_leaves_earlier_snapshots_alone_and_shares_the_rest_of_the_sheet()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
+        let mut game = Game::new();
+        let mut zorc = build_orc();
+        Arc::make_mut(&mut zorc.stats).insert(String::from("Body"), 5);
+        let zorc_id = game.add_cast_member(zorc);
+
+        let before = game.get_cast_by_id(&zorc_id).unwrap();
+        assert!(game.apply_damage(None, zorc_id, 3, DamageType::Physical).is_ok());
+        let after = game.get_cast_by_id(&zorc_id).unwrap();
+
+        assert_eq!(0, before.physical_track_filled);
+        assert_eq!(3, after.physical_track_filled);
+        assert!(Arc::ptr_eq(&before.stats, &after.stats));
+        assert!(Arc::ptr_eq(&before.armor, &after.armor));
+    }
+
+    #[test]
+    pub fn house_rules_adjust_damage_and_initiative_and_leave_notes_at_round_start()
+    {
+        init();
 
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let (mork_id, belf_id) = (ids[0], ids[1]);
+
+        assert!(game.attach_house_rule("double-physical", HouseRuleEvent::Damage, r#"if kind == "Physical" { boxes *= 2; }"#).is_ok());
+        assert!(game.attach_house_rule("wounds", HouseRuleEvent::Initiative, "roll -= physical_filled;").is_ok());
+        assert!(game.attach_house_rule("banner", HouseRuleEvent::RoundStart, r#""Round begins with " + combatants + " combatants""#).is_ok());
+        assert!(matches!(game.attach_house_rule("broken", HouseRuleEvent::Damage, "boxes *= ;"), Err(GameError { kind: ErrorKind::InvalidHouseRule, .. })));
+
+        assert!(game.apply_damage(None, mork_id, 2, DamageType::Physical).is_ok());
+        assert_eq!(4, game.get_cast_by_id(&mork_id).unwrap().physical_track_filled);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 10).is_ok());
+        assert_eq!(vec![(belf_id, 10), (mork_id, 8)], game.preview_initiative_order().unwrap().order);
 
         assert!(game.start_combat_rounds().is_ok());
+        assert!(game.journal().entries().iter().any(|entry| entry.event == JournalEvent::HouseRule { rule: String::from("banner"), 
+            note: String::from("Round begins with 2 combatants") }));
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_err());
-        assert!(game.advance_round().is_err());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_err());
-        assert!(game.advance_round().is_ok());
+        assert!(game.remove_house_rule("wounds").is_ok());
+        assert!(matches!(game.remove_house_rule("wounds"), Err(GameError { kind: ErrorKind::UnknownHouseRule, .. })));
     }
 
     #[test]
-    pub fn a_resolving_character_may_take_one_complex_action_but_not_one_complex_one_single_or_two_complex()
+    pub fn a_jacked_in_decker_spends_matrix_actions_separately_from_physical_ones()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let mut decker = build_elf();
+        Arc::make_mut(&mut decker.stats).insert(String::from("Logic"), 6);
+        let ids = populate!(&mut game, decker, build_orc());
+        let (decker_id, mork_id) = (ids[0], ids[1]);
+        let host = game.register_matrix_target(MatrixTarget { name: String::from("Ares host"), kind: MatrixTargetKind::Host, rating: 2, firewall: 2 });
+        let spike = MatrixActionDeclaration { decker: decker_id, target: host, action: MatrixAction::DataSpike };
+        let mut rng = rand::thread_rng();
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-
+        assert!(game.accept_initiative_roll(decker_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 7).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_err());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_err());
-        assert!(game.advance_round().is_ok());
+        assert!(matches!(game.declare_matrix_action(&mut rng, &spike), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
+        assert!(game.jack_in(decker_id).is_ok());
+        assert!(game.declare_matrix_action(&mut rng, &spike).is_ok());
+        assert!(matches!(game.declare_matrix_action(&mut rng, &spike), Err(GameError { kind: ErrorKind::NoAction, .. })));
+        assert!(game.take_action(decker_id, ActionType::Complex).is_ok());
+
+        let elsewhere = MatrixActionDeclaration { target: Uuid::new_v4(), ..spike };
+        assert!(matches!(game.declare_matrix_action(&mut rng, &elsewhere), Err(GameError { kind: ErrorKind::UnknownMatrixTarget, .. })));
+        assert!(game.jack_out(decker_id).is_ok());
+        assert!(game.jack_out(decker_id).is_err());
     }
 
     #[test]
-    pub fn a_resolving_character_may_take_their_free_action_alongside_their_simple_actions()
+    pub fn a_projecting_magician_is_marked_on_the_cast_list_and_their_body_cannot_defend()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let mut mage = build_elf();
+        Arc::make_mut(&mut mage.stats).insert(String::from("Magic"), 5);
+        let ids = populate!(&mut game, mage, build_orc());
+        let (mage_id, mork_id) = (ids[0], ids[1]);
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(matches!(game.change_plane(mork_id, Plane::AstralProjecting), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
+        assert!(game.change_plane(mage_id, Plane::AstralProjecting).is_ok());
+        assert_eq!(Plane::AstralProjecting, game.get_cast_by_id(&mage_id).unwrap().plane);
+        assert!(game.is_helpless(mage_id));
+        assert!(!game.is_helpless(mork_id));
 
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(mage_id, 20).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 5).is_ok());
         assert!(game.start_combat_rounds().is_ok());
+        assert!(matches!(game.go_full_defense(mage_id), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.advance_round().is_ok());
+        assert!(game.change_plane(mage_id, Plane::Physical).is_ok());
+        assert!(game.go_full_defense(mage_id).is_ok());
     }
 
     #[test]
-    pub fn a_resolving_character_may_take_their_free_action_alongside_their_complex_action()
+    pub fn a_character_summary_reports_the_condition_monitor_status_effects_and_turn_state()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let (mork_id, melf_id) = (ids[0], ids[1]);
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        assert!(game.apply_damage(None, mork_id, 3, DamageType::Stun).is_ok());
+        assert!(game.jack_in(mork_id).is_ok());
+        let summary = game.summarize(game.get_cast_by_id(&mork_id).unwrap(), true);
+        assert_eq!(3, summary.condition.stun_filled);
+        assert_eq!(vec![StatusEffect::JackedIn], summary.status);
+        assert_eq!(TurnState::OutOfCombat, summary.turn);
 
+        assert!(game.start_initiative_phase().is_ok());
+        assert_eq!(TurnState::AwaitingInitiative, game.summarize(game.get_cast_by_id(&mork_id).unwrap(), true).turn);
+        assert!(game.accept_initiative_roll(mork_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(melf_id, 6).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
+        assert_eq!(TurnState::Acting, game.summarize(game.get_cast_by_id(&mork_id).unwrap(), true).turn);
+        assert_eq!(TurnState::Waiting, game.summarize(game.get_cast_by_id(&melf_id).unwrap(), true).turn);
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+
+        let seen_by_others = game.summarize(game.get_cast_by_id(&mork_id).unwrap(), false);
+        assert_eq!(TurnState::Resolved, seen_by_others.turn);
+        assert!(seen_by_others.status.is_empty());
     }
 
     #[test]
-    pub fn any_character_may_take_their_free_action_on_any_turn()
+    pub fn combat_fields_wait_for_the_gm_during_combat_but_other_edits_apply_at_once()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
-
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let mork_id = ids[0];
 
-        assert!(game.start_combat_rounds().is_ok());
+        let mut tougher = CharacterPatch::default();
+        tougher.stats.insert(String::from("Body"), 9);
+        assert!(matches!(game.update_character(mork_id, tougher.clone(), false), Ok(PatchOutcome::Applied(_))));
+        assert_eq!(9, game.get_cast_by_id(&mork_id).unwrap().stat("Body"));
 
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Free).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.advance_round().is_ok());
+        assert!(game.start_initiative_phase().is_ok());
+        tougher.stats.insert(String::from("Body"), 10);
+        assert!(matches!(game.update_character(mork_id, tougher, false), Ok(PatchOutcome::AwaitingApproval(_))));
+        assert_eq!(9, game.get_cast_by_id(&mork_id).unwrap().stat("Body"));
+        assert_eq!(vec![mork_id], game.pending_character_updates());
+
+        let renamed = CharacterPatch { name: Some(std::sync::Arc::from("Morkus")), ..Default::default() };
+        assert!(matches!(game.update_character(mork_id, renamed.clone(), false), Ok(PatchOutcome::Applied(fields)) if fields == vec!["name"]));
+        assert!(matches!(game.update_character(mork_id, renamed, false), Err(GameError { kind: ErrorKind::InvalidPatch, .. })));
+
+        assert_eq!(vec!["stats"], game.approve_character_update(mork_id).unwrap());
+        assert_eq!(10, game.get_cast_by_id(&mork_id).unwrap().stat("Body"));
+        assert!(matches!(game.approve_character_update(mork_id), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
     }
 
     #[test]
-    pub fn a_character_not_resolving_may_not_take_a_simple_action()
+    pub fn a_gm_adjustment_waits_for_the_roll_or_moves_the_combatant_at_once()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc(), build_elf());
+        let (dorf_id, mork_id, belf_id) = (ids[0], ids[1], ids[2]);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-
+        assert_eq!(None, game.adjust_initiative(dorf_id, -2, String::from("Startled by the alarm")).unwrap());
+        assert!(game.accept_initiative_roll(dorf_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 16).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
+        assert_eq!(Some(vec![dorf_id]), game.on_deck());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Simple).is_err());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Simple).is_err());
-        assert!(game.advance_round().is_err());
+        assert_eq!(Some(14), game.adjust_initiative(belf_id, 6, String::from("Wired reflexes kicked in")).unwrap());
+        assert_eq!(Some(vec![mork_id]), game.currently_up());
+        assert_eq!(Some(vec![belf_id]), game.on_deck());
+
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
+        assert!(matches!(game.adjust_initiative(mork_id, 1, String::from("Too late")), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
+
+        let adjustments = game.journal().entries().iter().filter(|entry| matches!(entry.event, JournalEvent::InitiativeAdjusted { .. })).count();
+        assert_eq!(2, adjustments);
     }
 
     #[test]
-    pub fn a_character_not_resolving_may_not_take_a_complex_action()
+    pub fn tied_characters_take_their_turns_together_or_not_at_all_and_then_the_turn_moves_on()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc(), build_elf());
+        let (dorf_id, mork_id, belf_id) = (ids[0], ids[1], ids[2]);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 20).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 33).is_ok());
-
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 14).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Free).is_ok());
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Complex).is_err());
-        assert!(game.advance_round().is_err());
+        let out_of_turn = [(mork_id, ActionType::Complex), (dorf_id, ActionType::Complex)];
+        assert!(matches!(game.take_turns(&out_of_turn), Err(GameError { kind: ErrorKind::UnresolvedCombatant, .. })));
+
+        let one_too_many = [(mork_id, ActionType::Complex), (belf_id, ActionType::Complex), (belf_id, ActionType::Simple)];
+        assert!(matches!(game.take_turns(&one_too_many), Err(GameError { kind: ErrorKind::NoAction, .. })));
+        assert!(game.journal().entries().iter().all(|entry| !matches!(entry.event, JournalEvent::ActionTaken { .. })));
+
+        assert!(matches!(game.take_turns(&[(mork_id, ActionType::Complex)]), Ok(false)));
+        assert!(matches!(game.take_turns(&[(belf_id, ActionType::Simple), (belf_id, ActionType::Simple)]), Ok(true)));
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
     }
 
     #[test]
-    pub fn multiple_characters_may_have_same_initiative_and_may_act_together()
+    pub fn with_auto_advance_on_the_turn_moves_on_once_the_last_combatant_up_resolves()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc(), build_elf());
+        let (dorf_id, mork_id, belf_id) = (ids[0], ids[1], ids[2]);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
-
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 14).is_ok());
         assert!(game.start_combat_rounds().is_ok());
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
+        assert!(!game.advance_if_resolved());
+
+        game.set_auto_advance(true);
+        assert!(game.advance_if_resolved());
+        assert_eq!(Some(vec![dorf_id]), game.currently_up());
 
+        assert!(game.take_action(dorf_id, ActionType::Simple).is_ok());
+        assert!(!game.advance_if_resolved());
+        assert!(game.take_action(dorf_id, ActionType::Simple).is_ok());
+        assert!(game.advance_if_resolved());
+        assert_eq!(None, game.currently_up());
     }
 
     #[test]
-    pub fn advancing_the_initiative_round_before_all_active_characters_have_resolved_will_generate_unresolved_combatant()
+    pub fn players_see_an_npcs_wounds_only_as_far_as_the_visibility_options_allow()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
+        let mut game = Game::new();
+        let ids = populate!(&mut game, build_dwarf(), build_orc());
+        let (dorf_id, zorc_id) = (ids[0], ids[1]);
+        assert!(game.apply_damage(None, dorf_id, 1, DamageType::Physical).is_ok());
+        assert!(game.apply_damage(None, zorc_id, 1, DamageType::Stun).is_ok());
+
+        let dorf_monitor = game.get_cast_by_id(&dorf_id).unwrap().condition_monitor();
+        assert_eq!(Some(WoundView::Exact(dorf_monitor)), game.wounds_seen_by(&dorf_id, true));
+        assert_eq!(Some(WoundView::Tier(WoundTier::LightlyHurt)), game.wounds_seen_by(&dorf_id, false));
+        assert!(matches!(game.wounds_seen_by(&zorc_id, false), Some(WoundView::Exact(_))));
+
+        game.set_visibility(VisibilityOptions { npc_wounds: WoundDisclosure::Hidden });
+        assert_eq!(None, game.wounds_seen_by(&dorf_id, false));
+        assert!(game.wounds_seen_by(&dorf_id, true).is_some());
+
+        let monitor = ConditionMonitor { physical_filled: 4, physical_max: 9, stun_filled: 9, stun_max: 9 };
+        assert_eq!(WoundTier::Down, monitor.tier());
+        assert_eq!(WoundTier::BadlyHurt, ConditionMonitor { stun_filled: 0, ..monitor }.tier());
+        assert_eq!(WoundTier::Critical, ConditionMonitor { physical_filled: 6, stun_filled: 0, ..monitor }.tier());
+    }
+
+    #[test]
+    pub fn a_skipped_turn_resolves_the_combatant_without_spending_their_actions()
+    {
+        init();
 
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_dwarf(), build_orc());
+        let (dorf_id, mork_id) = (ids[0], ids[1]);
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
+        assert!(matches!(game.skip_turn(mork_id), Err(GameError { kind: ErrorKind::InvalidStateAction, .. })));
 
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(dorf_id, 12).is_ok());
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Simple).is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        
-        match game.advance_round()
-        {
-            Ok(_) => panic!("This should have failed."),
-            Err(err) => match err.kind
-            {
-                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
-                _ => {panic!("Advancing round without resolving all actions should generate UnresolvedCombatant type error.")}
-            },
-        }
+
+        assert!(matches!(game.skip_turn(dorf_id), Err(GameError { kind: ErrorKind::UnresolvedCombatant, .. })));
+        assert!(game.skip_turn(mork_id).is_ok());
+        assert_eq!(None, game.waiting_for());
+        assert!(game.take_action(mork_id, ActionType::Free).is_ok());
+        assert_eq!(1, game.journal().entries().iter().filter(|entry| matches!(entry.event, JournalEvent::ActionTaken { .. })).count());
     }
 
     #[test]
-    pub fn the_initiative_round_may_be_advanced_only_after_all_current_players_have_fully_resolved()
+    pub fn the_end_of_a_pass_says_whether_another_pass_or_a_new_round_comes_next()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-        let dork = build_dwarf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, dork, melf);
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let (mork_id, belf_id) = (ids[0], ids[1]);
+        assert!(game.override_initiative_passes(mork_id, Some(1)).is_ok());
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(2).unwrap(), 13).is_ok());
-
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert_eq!(None, game.after_pass());
 
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
         assert!(game.advance_round().is_ok());
-
-        assert!(game.take_action(*ids.get(2).unwrap(), ActionType::Simple).is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
+        assert!(matches!(game.advance_round(), Err(GameError { kind: ErrorKind::EndOfInitiative, .. })));
+        assert_eq!(Some(AfterPass::NextPass), game.after_pass());
+
+        assert!(game.next_initiative_pass().is_ok());
+        assert_eq!(Some(vec![mork_id]), game.currently_up());
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+        assert!(matches!(game.advance_round(), Err(GameError { kind: ErrorKind::EndOfInitiative, .. })));
+        assert_eq!(Some(AfterPass::NextRound), game.after_pass());
     }
 
-    // TODO: Tests for Initiative Passes once passes are in.
-
     #[test]
-    pub fn advancing_initiative_after_all_events_have_been_processed_results_in_end_of_initiative_error()
+    pub fn with_pass_cycling_on_the_next_pass_starts_by_itself_and_only_the_end_of_the_round_is_reported()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, melf);
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let (mork_id, belf_id) = (ids[0], ids[1]);
+        assert!(game.override_initiative_passes(mork_id, Some(1)).is_ok());
+        game.set_auto_cycle_passes(true);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
-
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
 
-        match game.advance_round()
-        {
-            Ok(_) => panic!("No new events on deck to process this round!"),
-            Err(err) => {
-                match err.kind
-                {
-                    crate::tracker::game::ErrorKind::EndOfInitiative => {},
-                    _ => {panic!("Should indicate we have hit the end of the round with EndOfInitiative")}
-                }
-            },
-        }
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert_eq!(1, game.current_pass());
+        assert_eq!(Some(vec![mork_id]), game.currently_up());
+
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
+        assert!(matches!(game.advance_round(), Err(GameError { kind: ErrorKind::EndOfInitiative, .. })));
+        assert_eq!(Some(AfterPass::NextRound), game.after_pass());
     }
 
     #[test]
-    pub fn when_all_events_have_resolved_combat_may_move_back_to_initiative_phase()
+    pub fn a_rewind_reaches_back_over_a_pass_that_cycled_on_by_itself()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, melf);
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        let (mork_id, belf_id) = (ids[0], ids[1]);
+        assert!(game.override_initiative_passes(mork_id, Some(1)).is_ok());
+        game.set_auto_cycle_passes(true);
 
         assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 14).is_ok());
-
+        assert!(game.accept_initiative_roll(mork_id, 14).is_ok());
+        assert!(game.accept_initiative_roll(belf_id, 8).is_ok());
         assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+
+        assert!(game.take_action(mork_id, ActionType::Complex).is_ok());
         assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
+        assert!(game.advance_round().is_ok());
+        assert_eq!(1, game.current_pass());
 
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 12).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 23).is_ok());
+        assert!(game.rewind(RewindTarget::PreviousTurn).is_ok());
+        assert_eq!(0, game.current_pass());
+        assert_eq!(Some(vec![belf_id]), game.currently_up());
 
-        assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_err());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.take_action(belf_id, ActionType::Complex).is_ok());
         assert!(game.advance_round().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
+        assert!(game.rewind(RewindTarget::PassStart).is_ok());
+        assert_eq!(1, game.current_pass());
+        assert_eq!(Some(vec![mork_id]), game.currently_up());
     }
 
     #[test]
-    pub fn calling_start_initiative_phase_before_all_events_resolve_generates_unresolved_combatant()
+    pub fn forgetting_a_player_renames_their_fighters_drops_their_bench_and_purges_their_chat()
     {
         init();
 
-        let zorc = build_orc();
-        let melf = build_elf();
-
+        let (leaver, stayer) = (Uuid::new_v4(), Uuid::new_v4());
         let mut game = Game::new();
-        let ids = populate!(&mut game, zorc, melf);
-
-        assert!(game.start_initiative_phase().is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 23).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 12).is_ok());
+        let ids = populate!(&mut game, build_orc(), build_dwarf());
+        let (mork_id, dorf_id) = (ids[0], ids[1]);
+        let belf_id = game.add_cast_member(build_elf());
 
-        assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(0).unwrap(), ActionType::Complex).is_ok());
-        assert!(game.advance_round().is_ok());
+        game.record_chat(ChatLine { from: leaver, audience: ChatAudience::Table, text: String::from("Hoi, chummers.") });
+        game.record_chat(ChatLine { from: stayer, audience: ChatAudience::Player(leaver), text: String::from("Watch the door.") });
+        let kept = game.record_chat(ChatLine { from: stayer, audience: ChatAudience::Table, text: String::from("Moving in.") });
 
-        match game.start_initiative_phase()
-        {
-            Ok(_) => {panic!("Attempting to start the initiative phase before all characters in the last turn resolve should have failed.")},
-            Err(err) => match err.kind
-            {
-                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
-                _ => {panic!("Attempting to start the initiative phase before all characters in the last turn resolve should have generated UnresolvedCombatant")}
-            },
-        }
+        let characters = [mork_id, belf_id].into_iter().collect();
+        game.forget_player(leaver, &characters, Arc::from("Departed runner"));
 
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+        assert_eq!("Departed runner", &*game.get_cast_by_id(&mork_id).unwrap().name);
+        assert!(game.get_cast_by_id(&belf_id).is_none());
+        assert_eq!("Dorf", &*game.get_cast_by_id(&dorf_id).unwrap().name);
 
-        assert!(game.start_initiative_phase().is_ok());
+        assert_eq!(1, game.journal().entries().len());
+        assert_eq!(kept, game.journal().entries()[0].sequence);
+        assert!(game.chat_visible_to(leaver, false).iter().all(|line| line.from != leaver));
+    }
 
-        assert!(game.accept_initiative_roll(*ids.get(0).unwrap(), 13).is_ok());
-        assert!(game.accept_initiative_roll(*ids.get(1).unwrap(), 16).is_ok());
-        assert!(game.start_combat_rounds().is_ok());
-        assert!(game.take_action(*ids.get(1).unwrap(), ActionType::Complex).is_ok());
+    #[test]
+    pub fn a_rematch_keeps_the_cast_and_settings_but_starts_with_no_fight_and_an_empty_journal()
+    {
+        init();
 
-        match game.start_initiative_phase()
-        {
-            Ok(_) => {panic!("Attempting to start the initiative phase before all on-deck characters resolve should have generated UnresolvedCombatant")},
-            Err(err) => match err.kind
-            {
-                crate::tracker::game::ErrorKind::UnresolvedCombatant => {},
-                _ => {panic!("Attempting to start the initiative phase before all on-deck characters resolve should have generated UnresolvedCombatant.")}
-            }
-        }
+        let mut game = Game::new();
+        let ids = populate!(&mut game, build_orc(), build_elf());
+        game.set_auto_advance(true);
+        assert!(game.start_initiative_phase().is_ok());
+        assert!(game.accept_initiative_roll(ids[0], 14).is_ok());
+
+        let rematch = game.rematch();
+        assert_eq!(2, rematch.get_cast().len());
+        assert!(rematch.get_combatants().is_empty());
+        assert!(rematch.auto_advance());
+        assert!(rematch.journal().entries().is_empty());
+        assert_eq!("PreCombat", rematch.current_state());
     }
-
-}
\ No newline at end of file
+}
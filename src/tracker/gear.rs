@@ -4,6 +4,7 @@ pub enum DamageType {
     Stun
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ArmorTestType {
     Ballistic,
     Impact
@@ -27,10 +28,22 @@ pub struct Weapon {
     pub weapon_name: String,
     pub assoc_skill: String,
     pub firing_features: Vec<FiringFeature>,
+    // Which of the firing features the weapon is set up for; the rest need a reconfiguration to use.
+    pub current_feature: usize,
     pub reach: Option<i8>,
     pub electric: bool,
 }
 
+impl Weapon {
+    pub fn selected_feature(&self) -> Option<&FiringFeature> {
+        self.firing_features.get(self.current_feature)
+    }
+
+    pub fn selected_feature_mut(&mut self) -> Option<&mut FiringFeature> {
+        self.firing_features.get_mut(self.current_feature)
+    }
+}
+
 #[derive(Clone)]
 pub struct FiringFeature {
     pub feature_name: String,
@@ -44,6 +57,7 @@ pub struct FiringFeature {
     pub recoil_comp: i8, // Some weapons have recoil compensation in the base config
     pub alt_recoil_comp: i8, // Some weapons can be reconfigured for better recoil compensation.
     pub current_fire_mode: usize,
+    pub loaded: i8, // Rounds left in the gun; a reload brings it back up to reload_size.
 }
 
 pub struct AmmoTypes
@@ -60,4 +74,16 @@ pub struct Armour {
     pub name: String,
     pub ballistic_rating: i8,
     pub impact_rating: i8,
+    pub degradation: i8, // rating points lost to damage
+}
+
+impl Armour {
+    pub fn rating(&self, test: ArmorTestType) -> i8 {
+        let base = match test {
+            ArmorTestType::Ballistic => self.ballistic_rating,
+            ArmorTestType::Impact => self.impact_rating,
+        };
+
+        (base - self.degradation).max(0)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,218 @@
+use rhai::{Engine, AST, Scope, Dynamic, packages::{Package, StandardPackage}};
+use tracing::warn;
+
+use super::{character::ConditionMonitor, combat::DamageType};
+
+// GM-written Rhai scripts that bend the rules at fixed points in the game, so a table's house rules need no rebuild of the crate.  Each
+// game keeps its own scripts, and every run gets a fresh engine with hard limits and nothing to reach outside the values it is handed:
+// a script can change the number in front of it and nothing else.
+
+// Enough for any sensible rule; a runaway loop hits this long before it holds up the runner.
+const MAX_OPERATIONS: u64 = 10_000;
+const MAX_SOURCE_LENGTH: usize = 4_096;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HouseRuleEvent
+{
+    // Before boxes are marked.  In scope: target, kind ("Physical" or "Stun"), boxes (writable), and the target's physical_filled,
+    // physical_max, stun_filled and stun_max.
+    Damage,
+    // Before an initiative roll is entered.  In scope: character, roll (writable), and the same track values as for damage.
+    Initiative,
+    // When the first pass of a combat round begins.  In scope: combatants, the number taking part.  A string returned by the script is
+    // written to the journal as a note from the rule.
+    RoundStart,
+}
+
+#[derive(Clone)]
+pub struct HouseRule
+{
+    pub name: String,
+    pub event: HouseRuleEvent,
+    pub source: String,
+    ast: AST,
+}
+
+#[derive(Clone)]
+pub struct HouseRules
+{
+    rules: Vec<HouseRule>,
+}
+
+fn sandboxed_engine() -> Engine
+{
+    let mut engine = Engine::new_raw();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(16);
+    engine.set_max_expr_depths(32, 16);
+    engine.set_max_string_size(1_024);
+    engine.set_max_array_size(256);
+    engine.set_max_map_size(256);
+    engine.register_global_module(StandardPackage::new().as_shared_module());
+    engine
+}
+
+fn push_tracks(scope: &mut Scope, tracks: ConditionMonitor)
+{
+    scope.push_constant("physical_filled", tracks.physical_filled as i64);
+    scope.push_constant("physical_max", tracks.physical_max as i64);
+    scope.push_constant("stun_filled", tracks.stun_filled as i64);
+    scope.push_constant("stun_max", tracks.stun_max as i64);
+}
+
+impl HouseRules
+{
+    pub fn new() -> HouseRules
+    {
+        HouseRules { rules: Vec::new() }
+    }
+
+    // Compiles the script up front so a typo is reported to the GM now rather than silently skipped mid-fight.  A rule with an existing
+    // name replaces it.
+    pub fn attach(&mut self, name: &str, event: HouseRuleEvent, source: &str) -> Result<(), String>
+    {
+        if source.len() > MAX_SOURCE_LENGTH
+        {
+            return Err(String::from(format!("House rule scripts are limited to {} bytes.", MAX_SOURCE_LENGTH)));
+        }
+
+        let ast = sandboxed_engine().compile(source).map_err(|err| String::from(format!("House rule {} does not compile: {}", name, err)))?;
+        let rule = HouseRule { name: String::from(name), event, source: String::from(source), ast };
+
+        match self.rules.iter_mut().find(|existing| existing.name == name)
+        {
+            Some(existing) => *existing = rule,
+            None => self.rules.push(rule),
+        }
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool
+    {
+        let before = self.rules.len();
+        self.rules.retain(|rule| rule.name != name);
+        self.rules.len() != before
+    }
+
+    pub fn list(&self) -> Vec<(String, HouseRuleEvent)>
+    {
+        self.rules.iter().map(|rule| (rule.name.clone(), rule.event)).collect()
+    }
+
+    // Runs every rule for the event in the order they were attached, each seeing the value the last one left.  A rule that fails at run
+    // time is skipped, leaving the value as it was, so one broken script cannot stall the table.
+    fn adjust(&self, event: HouseRuleEvent, variable: &str, value: i64, setup: impl Fn(&mut Scope)) -> i64
+    {
+        let engine = sandboxed_engine();
+        let mut value = value;
+
+        for rule in self.rules.iter().filter(|rule| rule.event == event)
+        {
+            let mut scope = Scope::new();
+            setup(&mut scope);
+            scope.push(variable, value);
+
+            match engine.run_ast_with_scope(&mut scope, &rule.ast)
+            {
+                Ok(_) => value = scope.get_value::<i64>(variable).unwrap_or(value),
+                Err(err) => warn!("House rule {} failed and was skipped: {}", rule.name, err),
+            }
+        }
+
+        value
+    }
+
+    pub fn on_damage(&self, target: &str, kind: DamageType, boxes: u8, tracks: ConditionMonitor) -> u8
+    {
+        let kind = match kind { DamageType::Physical => "Physical", DamageType::Stun => "Stun" };
+        let adjusted = self.adjust(HouseRuleEvent::Damage, "boxes", boxes as i64, |scope| {
+            scope.push_constant("target", String::from(target));
+            scope.push_constant("kind", kind);
+            push_tracks(scope, tracks);
+        });
+
+        adjusted.clamp(0, u8::MAX as i64) as u8
+    }
+
+    pub fn on_initiative(&self, character: &str, roll: i8, tracks: ConditionMonitor) -> i8
+    {
+        let adjusted = self.adjust(HouseRuleEvent::Initiative, "roll", roll as i64, |scope| {
+            scope.push_constant("character", String::from(character));
+            push_tracks(scope, tracks);
+        });
+
+        adjusted.clamp(i8::MIN as i64, i8::MAX as i64) as i8
+    }
+
+    pub fn on_round_start(&self, combatants: usize) -> Vec<(String, String)>
+    {
+        let engine = sandboxed_engine();
+        let mut notes = Vec::new();
+
+        for rule in self.rules.iter().filter(|rule| rule.event == HouseRuleEvent::RoundStart)
+        {
+            let mut scope = Scope::new();
+            scope.push_constant("combatants", combatants as i64);
+
+            match engine.eval_ast_with_scope::<Dynamic>(&mut scope, &rule.ast)
+            {
+                Ok(result) if result.is_string() => notes.push((rule.name.clone(), result.to_string())),
+                Ok(_) => {},
+                Err(err) => warn!("House rule {} failed and was skipped: {}", rule.name, err),
+            }
+        }
+
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tracker::{character::ConditionMonitor, combat::DamageType};
+
+    use super::{HouseRules, HouseRuleEvent};
+
+    #[test]
+    pub fn a_damage_rule_can_change_the_boxes_marked()
+    {
+        let mut rules = HouseRules::new();
+        assert!(rules.attach("stun-is-softer", HouseRuleEvent::Damage, r#"if kind == "Stun" { boxes = boxes / 2; }"#).is_ok());
+
+        assert_eq!(3, rules.on_damage("someone", DamageType::Stun, 6, ConditionMonitor::default()));
+        assert_eq!(6, rules.on_damage("someone", DamageType::Physical, 6, ConditionMonitor::default()));
+    }
+
+    #[test]
+    pub fn an_initiative_rule_can_apply_custom_wound_modifiers()
+    {
+        let mut rules = HouseRules::new();
+        let wounds = "roll -= (physical_filled + stun_filled) / 2;";
+        assert!(rules.attach("harsher-wounds", HouseRuleEvent::Initiative, wounds).is_ok());
+
+        let tracks = ConditionMonitor { physical_filled: 4, physical_max: 10, stun_filled: 2, stun_max: 10 };
+        assert_eq!(9, rules.on_initiative("someone", 12, tracks));
+    }
+
+    #[test]
+    pub fn scripts_that_do_not_compile_are_refused_and_runaway_scripts_are_skipped()
+    {
+        let mut rules = HouseRules::new();
+        assert!(rules.attach("typo", HouseRuleEvent::Damage, "boxes = = 2;").is_err());
+        assert!(rules.list().is_empty());
+
+        assert!(rules.attach("forever", HouseRuleEvent::Damage, "loop { boxes += 1; }").is_ok());
+        assert_eq!(4, rules.on_damage("someone", DamageType::Physical, 4, ConditionMonitor::default()));
+    }
+
+    #[test]
+    pub fn round_start_rules_return_notes_for_the_journal()
+    {
+        let mut rules = HouseRules::new();
+        assert!(rules.attach("reminder", HouseRuleEvent::RoundStart, r#"if combatants > 3 { "Big fight: remember the called-shot house rule." }"#).is_ok());
+
+        assert!(rules.on_round_start(2).is_empty());
+        assert_eq!(vec![(String::from("reminder"), String::from("Big fight: remember the called-shot house rule."))], rules.on_round_start(4));
+    }
+}
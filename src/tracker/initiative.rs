@@ -1,6 +1,8 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use uuid::Uuid;
 
 
+#[derive(Clone)]
 pub struct InitTracker {
     // The primary initiative tracker: all initiatives are inserted into the heap and popped on demand.
     initiatives: Vec<Initiative>,
@@ -9,9 +11,13 @@ pub struct InitTracker {
     // post-turn storage.  An Initiative that is popped from the heap but can still act on a subsequent turn is held here.
     // During the pass turn-over, it will be moved back into initiatives for the next go round.
     overflow: Vec<Initiative>,
+    // Draws the tie-breaker for each new event.  Entropy-seeded unless a test or simulation injects its own, in which case the order of
+    // tied initiatives is the same on every run.
+    rng: StdRng,
 }
 
-struct Initiative {
+#[derive(Clone, Debug)]
+pub struct Initiative {
     pub id: Uuid,
     pub initiative: i8,
     pub in_astral_space: bool,
@@ -19,6 +25,19 @@ struct Initiative {
     pub in_matrix: bool,
     pub matrix_passes: usize,
     pub passes: usize,
+    // Orders events on the same initiative; the higher value acts first.  Drawn once when the event is added and kept across passes, so
+    // a tie resolves the same way every pass.
+    pub tie_breaker: u32,
+}
+
+// Everything the tracker holds about the round in progress, as plain data: the events still to act this pass in ascending order (the
+// next to act is last), the events held over for later passes, and the pass number.  Restoring it puts the tracker back exactly where
+// it was, without touching the tie-breaking RNG.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackerState {
+    pub current_pass: usize,
+    pub initiatives: Vec<Initiative>,
+    pub overflow: Vec<Initiative>,
 }
 
 #[derive(PartialEq, Debug)]
@@ -40,24 +59,62 @@ impl InitTracker {
     }
 
     pub fn new(size_hint: Option<usize>) -> InitTracker {
+        InitTracker::with_rng(size_hint, StdRng::from_entropy())
+    }
+
+    pub fn with_rng(size_hint: Option<usize>, rng: StdRng) -> InitTracker {
         match size_hint
         {
             Some(size) => 
             {
                 let mut inits = Vec::new();
                 inits.reserve(size);
-                InitTracker { initiatives: inits, current_pass: 0, overflow: Vec::new() }
+                InitTracker { initiatives: inits, current_pass: 0, overflow: Vec::new(), rng }
             },
             None => 
             {
                 let mut inits = Vec::new();
                 inits.reserve(20); // Completely arbitrary, but seems reasonable.
-                InitTracker { initiatives: inits, current_pass: 0, overflow: Vec::new()}
+                InitTracker { initiatives: inits, current_pass: 0, overflow: Vec::new(), rng }
             },
         }
         
     }
 
+    pub fn seeded(size_hint: Option<usize>, seed: u64) -> InitTracker {
+        InitTracker::with_rng(size_hint, StdRng::seed_from_u64(seed))
+    }
+
+    // Swaps in a new tie-breaking RNG; events already queued keep the tie-breakers they were given.
+    pub fn set_rng(&mut self, rng: StdRng)
+    {
+        self.rng = rng;
+    }
+
+    pub fn dump(&self) -> TrackerState
+    {
+        TrackerState { current_pass: self.current_pass, initiatives: self.initiatives.clone(), overflow: self.overflow.clone() }
+    }
+
+    pub fn restore(&mut self, state: TrackerState)
+    {
+        self.current_pass = state.current_pass;
+        self.initiatives = state.initiatives;
+        self.overflow = state.overflow;
+    }
+
+    // The events still to act this pass, in the order next() would hand them out, without handing them out.
+    pub fn iter(&self) -> impl Iterator<Item = &Initiative>
+    {
+        self.initiatives.iter().rev()
+    }
+
+    // The events held over for a later pass, in no particular order.
+    pub fn held_over(&self) -> impl Iterator<Item = &Initiative>
+    {
+        self.overflow.iter()
+    }
+
     pub fn reset(&mut self)
     {
         self.initiatives.clear();
@@ -67,7 +124,7 @@ impl InitTracker {
 
     pub fn add_new_event(&mut self, id: Uuid, initiative: i8, passes: usize, astral_passes: usize, matrix_passes: usize) -> PassState
     {
-        let init = Initiative{id, initiative, in_astral_space: false, astral_passes, in_matrix: false, matrix_passes, passes};
+        let init = Initiative{id, initiative, in_astral_space: false, astral_passes, in_matrix: false, matrix_passes, passes, tie_breaker: self.rng.gen()};
         match self.initiatives.binary_search(&init)
         {
             // It's goin' in either way - just a gotta find the right place.
@@ -80,7 +137,7 @@ impl InitTracker {
 
     pub fn on_next_pass(&mut self, id: Uuid, initiative: i8, passes: usize, astral_passes: usize, matrix_passes: usize) -> PassState
     {
-        let init = Initiative{id, initiative, in_astral_space: false, astral_passes, in_matrix: false, matrix_passes, passes};
+        let init = Initiative{id, initiative, in_astral_space: false, astral_passes, in_matrix: false, matrix_passes, passes, tie_breaker: self.rng.gen()};
         self.overflow.push(init);
 
         PassState::AcceptedRequest
@@ -97,7 +154,8 @@ impl InitTracker {
             passes: self.current_pass + 1,
             astral_passes: 0, 
             in_matrix: false, 
-            matrix_passes: 0
+            matrix_passes: 0,
+            tie_breaker: self.rng.gen(),
         };
 
         self.overflow.push(init);
@@ -105,6 +163,56 @@ impl InitTracker {
         PassState::AcceptedRequest
     }
 
+    // for timed events that will happen in the current initiative pass, once.
+    pub fn one_shot_this_pass(&mut self, id: Uuid, initiative: i8) -> PassState
+    {
+        self.requeue(id, initiative)
+    }
+
+    // Moves an event up or down the order by delta, for this pass and any it is held over for.  Returns the new score, or UnknownId if
+    // the event is not waiting to act.
+    pub fn adjust(&mut self, id: Uuid, delta: i8) -> PassState
+    {
+        let mut adjusted = None;
+        for init in self.overflow.iter_mut().filter(|init| init.id == id)
+        {
+            init.initiative = init.initiative.saturating_add(delta);
+            adjusted = Some(init.initiative);
+        }
+
+        if let Some(index) = self.initiatives.iter().position(|init| init.id == id)
+        {
+            let mut init = self.initiatives.remove(index);
+            init.initiative = init.initiative.saturating_add(delta);
+            adjusted = Some(init.initiative);
+            match self.initiatives.binary_search(&init)
+            {
+                Ok(index) => self.initiatives.insert(index, init),
+                Err(index) => self.initiatives.insert(index, init),
+            }
+        }
+
+        match adjusted
+        {
+            Some(initiative) => PassState::Next((id, initiative)),
+            None => PassState::UnknownId(id),
+        }
+    }
+
+    // Puts an event that has already been taken off the order back into the current pass, once.  Any later passes it has are already held
+    // over, so this copy does not carry any of its own.
+    pub fn requeue(&mut self, id: Uuid, initiative: i8) -> PassState
+    {
+        let init = Initiative{id, initiative, in_astral_space: false, astral_passes: 0, in_matrix: false, matrix_passes: 0, passes: 0, tie_breaker: self.rng.gen()};
+        match self.initiatives.binary_search(&init)
+        {
+            Ok(index) => self.initiatives.insert(index, init),
+            Err(index) => self.initiatives.insert(index, init),
+        }
+
+        PassState::AcceptedRequest
+    }
+
     pub fn get_ordered_inits(& self) -> Vec::<(i8, Uuid)>
     {
         let mut ordering = Vec::<(i8, Uuid)>::new();
@@ -299,7 +407,7 @@ impl InitTracker {
 impl PartialEq for Initiative {
 
     fn eq(&self, other: &Self) -> bool {
-        self.initiative == other.initiative
+        self.initiative == other.initiative && self.tie_breaker == other.tie_breaker
     }
 
 }
@@ -320,7 +428,7 @@ impl Ord for Initiative {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         if self.initiative == other.initiative
         {
-            std::cmp::Ordering::Equal
+            self.tie_breaker.cmp(&other.tie_breaker)
         }
         else if self.initiative > other.initiative
         {
@@ -342,7 +450,7 @@ mod tests
 
     pub fn init()
     {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
     }
 
     #[test]
@@ -578,6 +686,7 @@ mod tests
                 in_matrix: false,
                 matrix_passes: 4,
                 passes: 5,
+                tie_breaker: 0,
             })
         }
 
@@ -645,6 +754,27 @@ mod tests
         assert_eq!(1, next_events.len());
     }
 
+    #[test]
+    pub fn adjusting_an_event_reorders_the_pass_and_carries_over_to_later_passes()
+    {
+        init();
+
+        let mut tracker = InitTracker::new(None);
+        let (slow, fast) = (Uuid::new_v4(), Uuid::new_v4());
+        tracker.add_new_event(slow, 8, 2, 0, 0);
+        tracker.add_new_event(fast, 12, 1, 0, 0);
+        assert_eq!(PassState::Ready, tracker.begin_new_pass());
+
+        assert_eq!(PassState::Next((slow, 14)), tracker.adjust(slow, 6));
+        assert_eq!(PassState::Next((slow, 14)), tracker.next());
+        assert_eq!(PassState::Next((fast, 12)), tracker.next());
+
+        assert_eq!(PassState::Next((slow, 11)), tracker.adjust(slow, -3));
+        assert_eq!(PassState::UnknownId(fast), tracker.adjust(fast, 1));
+        assert_eq!(PassState::Ready, tracker.begin_new_pass());
+        assert_eq!(PassState::Next((slow, 11)), tracker.next());
+    }
+
     #[test]
     pub fn end_turn_resets_all_state_of_the_tracker()
     {
@@ -879,4 +1009,47 @@ mod tests
 
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    pub fn trackers_seeded_alike_break_ties_alike()
+    {
+        init();
+
+        let tied = (0..6).map(|_| Uuid::new_v4()).collect::<Vec<Uuid>>();
+        let order = |seed: u64| {
+            let mut tracker = InitTracker::seeded(None, seed);
+            for id in &tied
+            {
+                tracker.add_new_event(*id, 10, 1, 0, 0);
+            }
+            tracker.iter().map(|event| event.id).collect::<Vec<Uuid>>()
+        };
+
+        assert_eq!(order(7), order(7));
+        assert_eq!(6, order(7).len());
+    }
+
+    #[test]
+    pub fn iterating_does_not_consume_and_a_dump_restores_the_queue_mid_pass()
+    {
+        init();
+
+        let mut tracker = InitTracker::seeded(None, 1);
+        let (fast, slow) = (Uuid::new_v4(), Uuid::new_v4());
+        tracker.add_new_event(fast, 15, 2, 0, 0);
+        tracker.add_new_event(slow, 8, 1, 0, 0);
+        assert_eq!(PassState::Ready, tracker.begin_new_pass());
+
+        assert_eq!(vec![fast, slow], tracker.iter().map(|event| event.id).collect::<Vec<Uuid>>());
+        assert_eq!(2, tracker.iter().count());
+
+        let saved = tracker.dump();
+        assert_eq!(PassState::Next((fast, 15)), tracker.next());
+        assert_eq!(vec![fast], tracker.held_over().map(|event| event.id).collect::<Vec<Uuid>>());
+
+        tracker.restore(saved.clone());
+        assert_eq!(saved, tracker.dump());
+        assert_eq!(PassState::Next((fast, 15)), tracker.next());
+        assert_eq!(PassState::Next((slow, 8)), tracker.next());
+    }
+}
@@ -0,0 +1,289 @@
+use std::time::SystemTime;
+
+use uuid::Uuid;
+
+use super::{dice::PoolRoll, game::{ActionType, RewindTarget}, combat::DamageType};
+
+// The game's running record of what was said and done at the table, in the order it happened.  Entries are numbered so a client can
+// ask for everything after the last entry it saw.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ChatAudience
+{
+    Table,
+    Gm,
+    Player(Uuid),
+}
+
+// A chat message.  The sender and any whisper target are player ids, not character ids - people talk, characters act.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChatLine
+{
+    pub from: Uuid,
+    pub audience: ChatAudience,
+    pub text: String,
+}
+
+impl ChatLine
+{
+    // The GM reads everything, whispers included; everyone else reads the table, plus whatever they sent or was sent to them.
+    pub fn visible_to(&self, reader: Uuid, reader_is_gm: bool) -> bool
+    {
+        if reader_is_gm || self.from == reader
+        {
+            return true;
+        }
+
+        match self.audience
+        {
+            ChatAudience::Table => true,
+            ChatAudience::Gm => false,
+            ChatAudience::Player(target) => target == reader,
+        }
+    }
+}
+
+// A pool the tracker rolled for a character, under whatever label the roll was made with (usually a macro's name).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RollRecord
+{
+    pub character: Uuid,
+    pub label: String,
+    pub roll: PoolRoll,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum JournalEvent
+{
+    Chat(ChatLine),
+    Roll(RollRecord),
+    CombatStarted(Vec<Uuid>),
+    // A round's initiative phase opened.  Rounds count from 1 in each combat.
+    RoundStarted(usize),
+    InitiativeRolled { character: Uuid, roll: i8 },
+    InitiativeAdjusted { character: Uuid, delta: i8, reason: String },
+    // Where the turn order stood when the action was taken: the pass, and the initiative score up.
+    // The name is the catalog entry's, when the action was one.
+    ActionTaken { character: Uuid, action: ActionType, name: Option<String>, pass: usize, initiative: i8 },
+    Damage { source: Option<Uuid>, target: Uuid, boxes: u8, kind: DamageType },
+    Healed { character: Uuid, boxes: u8, kind: DamageType },
+    EdgeSpent { character: Uuid, points: u8 },
+    Rewound(RewindTarget),
+    CheckpointRestored(String),
+    HouseRule { rule: String, note: String },
+    // The GM rolled or acted for a character because its player was away.
+    GmProxy { character: Uuid, player: Uuid },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JournalEventKind
+{
+    Chat,
+    Roll,
+    CombatStarted,
+    RoundStarted,
+    InitiativeRolled,
+    InitiativeAdjusted,
+    ActionTaken,
+    Damage,
+    Healed,
+    EdgeSpent,
+    Rewound,
+    CheckpointRestored,
+    HouseRule,
+    GmProxy,
+}
+
+impl JournalEvent
+{
+    pub fn kind(&self) -> JournalEventKind
+    {
+        match self
+        {
+            JournalEvent::Chat(_) => JournalEventKind::Chat,
+            JournalEvent::Roll(_) => JournalEventKind::Roll,
+            JournalEvent::CombatStarted(_) => JournalEventKind::CombatStarted,
+            JournalEvent::RoundStarted(_) => JournalEventKind::RoundStarted,
+            JournalEvent::InitiativeRolled { .. } => JournalEventKind::InitiativeRolled,
+            JournalEvent::InitiativeAdjusted { .. } => JournalEventKind::InitiativeAdjusted,
+            JournalEvent::ActionTaken { .. } => JournalEventKind::ActionTaken,
+            JournalEvent::Damage { .. } => JournalEventKind::Damage,
+            JournalEvent::Healed { .. } => JournalEventKind::Healed,
+            JournalEvent::EdgeSpent { .. } => JournalEventKind::EdgeSpent,
+            JournalEvent::Rewound(_) => JournalEventKind::Rewound,
+            JournalEvent::CheckpointRestored(_) => JournalEventKind::CheckpointRestored,
+            JournalEvent::HouseRule { .. } => JournalEventKind::HouseRule,
+            JournalEvent::GmProxy { .. } => JournalEventKind::GmProxy,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct JournalEntry
+{
+    pub sequence: usize,
+    pub recorded_at: SystemTime,
+    pub event: JournalEvent,
+}
+
+// Narrows an export of the journal.  Both ends of the time range are inclusive, and an empty list of kinds means every kind.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct JournalFilter
+{
+    pub since: Option<SystemTime>,
+    pub until: Option<SystemTime>,
+    pub kinds: Vec<JournalEventKind>,
+}
+
+impl JournalFilter
+{
+    pub fn matches(&self, entry: &JournalEntry) -> bool
+    {
+        self.since.map_or(true, |since| entry.recorded_at >= since)
+            && self.until.map_or(true, |until| entry.recorded_at <= until)
+            && (self.kinds.is_empty() || self.kinds.contains(&entry.event.kind()))
+    }
+}
+
+#[derive(Clone)]
+pub struct Journal
+{
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal
+{
+    pub fn new() -> Journal
+    {
+        Journal { entries: Vec::new() }
+    }
+
+    pub fn record(&mut self, event: JournalEvent) -> usize
+    {
+        let sequence = self.entries.last().map_or(0, |entry| entry.sequence + 1);
+        self.entries.push(JournalEntry { sequence, recorded_at: SystemTime::now(), event });
+
+        sequence
+    }
+
+    pub fn entries(&self) -> &[JournalEntry]
+    {
+        &self.entries
+    }
+
+    pub fn filtered(&self, filter: &JournalFilter) -> Vec<JournalEntry>
+    {
+        self.entries.iter().filter(|entry| filter.matches(entry)).cloned().collect()
+    }
+
+    // Every entry numbered after the given one, or the whole journal for None.  Purged lines leave gaps in the numbering, so this goes by
+    // the numbers rather than by position.
+    pub fn after(&self, sequence: Option<usize>) -> &[JournalEntry]
+    {
+        let start = sequence.map_or(0, |sequence| self.entries.partition_point(|entry| entry.sequence <= sequence));
+
+        &self.entries[start..]
+    }
+
+    // Everything since the most recent combat began, or the whole journal if there has not been one.
+    pub fn since_last_combat(&self) -> &[JournalEntry]
+    {
+        let start = self.entries.iter().rposition(|entry| matches!(entry.event, JournalEvent::CombatStarted(_))).unwrap_or(0);
+
+        &self.entries[start..]
+    }
+
+    pub fn edge_spent(&self, character: Uuid) -> u16
+    {
+        self.entries.iter()
+            .filter_map(|entry| match entry.event
+            {
+                JournalEvent::EdgeSpent { character: spender, points } if spender == character => Some(points as u16),
+                _ => None,
+            })
+            .sum()
+    }
+
+    // Drops every chat line the player sent or was whispered, for when they delete their account.  The other entries keep their sequence
+    // numbers.  Returns how many lines went.
+    pub fn purge_player(&mut self, player: Uuid) -> usize
+    {
+        let before = self.entries.len();
+        self.entries.retain(|entry| !matches!(&entry.event, JournalEvent::Chat(line) if line.from == player || line.audience == ChatAudience::Player(player)));
+
+        before - self.entries.len()
+    }
+
+    pub fn chat_visible_to(&self, reader: Uuid, reader_is_gm: bool) -> Vec<ChatLine>
+    {
+        self.entries.iter()
+            .filter_map(|entry| match &entry.event
+            {
+                JournalEvent::Chat(line) if line.visible_to(reader, reader_is_gm) => Some(line.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use std::time::{SystemTime, Duration};
+
+    use super::{Journal, JournalEvent, JournalEventKind, JournalFilter, ChatLine, ChatAudience};
+
+    #[test]
+    pub fn whispers_are_only_read_by_the_sender_the_target_and_the_gm()
+    {
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut journal = Journal::new();
+
+        assert_eq!(0, journal.record(JournalEvent::Chat(ChatLine { from: alice, audience: ChatAudience::Table, text: String::from("I kick in the door.") })));
+        assert_eq!(1, journal.record(JournalEvent::Chat(ChatLine { from: alice, audience: ChatAudience::Player(bob), text: String::from("Cover me.") })));
+        assert_eq!(2, journal.record(JournalEvent::Chat(ChatLine { from: bob, audience: ChatAudience::Gm, text: String::from("I pocket the credstick.") })));
+
+        assert_eq!(2, journal.chat_visible_to(alice, false).len());
+        assert_eq!(3, journal.chat_visible_to(bob, false).len());
+        assert_eq!(1, journal.chat_visible_to(carol, false).len());
+        assert_eq!(3, journal.chat_visible_to(carol, true).len());
+    }
+
+    #[test]
+    pub fn entries_after_a_sequence_number_skip_the_gaps_purged_lines_leave()
+    {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut journal = Journal::new();
+
+        journal.record(JournalEvent::Chat(ChatLine { from: alice, audience: ChatAudience::Table, text: String::from("Ready?") }));
+        journal.record(JournalEvent::Chat(ChatLine { from: bob, audience: ChatAudience::Table, text: String::from("Ready.") }));
+        journal.record(JournalEvent::CombatStarted(vec![alice]));
+        journal.purge_player(bob);
+
+        assert_eq!(2, journal.after(None).len());
+        assert_eq!(vec![2], journal.after(Some(0)).iter().map(|entry| entry.sequence).collect::<Vec<usize>>());
+        assert_eq!(vec![2], journal.after(Some(1)).iter().map(|entry| entry.sequence).collect::<Vec<usize>>());
+        assert!(journal.after(Some(2)).is_empty());
+        assert!(journal.after(Some(9)).is_empty());
+    }
+
+    #[test]
+    pub fn an_export_filter_narrows_by_kind_and_by_time()
+    {
+        let sam = Uuid::new_v4();
+        let mut journal = Journal::new();
+
+        journal.record(JournalEvent::CombatStarted(vec![sam]));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 11 });
+        journal.record(JournalEvent::Chat(ChatLine { from: sam, audience: ChatAudience::Table, text: String::from("Frag out!") }));
+
+        let by_kind = JournalFilter { kinds: vec![JournalEventKind::InitiativeRolled, JournalEventKind::Chat], ..Default::default() };
+        assert_eq!(vec![1, 2], journal.filtered(&by_kind).iter().map(|entry| entry.sequence).collect::<Vec<usize>>());
+
+        let future = JournalFilter { since: Some(SystemTime::now() + Duration::from_secs(60)), ..Default::default() };
+        assert!(journal.filtered(&future).is_empty());
+        assert_eq!(3, journal.filtered(&JournalFilter::default()).len());
+    }
+}
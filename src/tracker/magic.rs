@@ -0,0 +1,42 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+// Spellcasting.  The spell itself is resolved at the table - the tracker keeps the paperwork: who must resist it, the caster's drain
+// test, and which spells are still being sustained (each of which drags on every other test the caster makes).
+
+#[derive(Debug, Clone)]
+pub struct SpellDeclaration
+{
+    pub caster: Uuid,
+    pub spell: String,
+    pub force: u8,
+    pub targets: Vec<Uuid>,
+    pub sustained: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SustainedSpell
+{
+    pub caster: Uuid,
+    pub spell: String,
+    pub force: u8,
+    pub targets: Vec<Uuid>,
+}
+
+impl SustainedSpell
+{
+    pub fn from_declaration(declaration: &SpellDeclaration) -> SustainedSpell
+    {
+        SustainedSpell { caster: declaration.caster, spell: declaration.spell.clone(), force: declaration.force, targets: declaration.targets.clone() }
+    }
+}
+
+// Where a character is.  A projecting magician acts on astral initiative while their body sits helpless; the dual-natured perceive and
+// act on both planes at once and keep their normal initiative.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Plane
+{
+    Physical,
+    AstralProjecting,
+    DualNatured,
+}
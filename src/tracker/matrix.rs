@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+use uuid::Uuid;
+
+use super::dice::{self, PoolRoll};
+
+// The Matrix runs alongside the physical fight.  The GM registers the devices and hosts that matter to the scene; deckers jack in, which
+// puts them on their Matrix initiative passes, and then attack those icons with Matrix actions paid for out of their own budget, apart
+// from whatever their meat body is doing.  The tracker rolls both sides, and keeps the marks each decker holds and the damage each icon
+// has taken.
+
+pub const MAX_MARKS: u8 = 3;
+// Matrix actions a jacked-in decker may take on each of their turns, on top of their physical actions.
+pub const MATRIX_ACTIONS_PER_PASS: usize = 1;
+// Every mark the attacker already holds on the target adds this much to a data spike.
+pub const DAMAGE_PER_MARK: u8 = 2;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MatrixTargetKind
+{
+    Device,
+    Host,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatrixTarget
+{
+    pub name: String,
+    pub kind: MatrixTargetKind,
+    pub rating: u8,
+    pub firewall: u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatrixIcon
+{
+    pub id: Uuid,
+    pub target: MatrixTarget,
+    pub damage: u8,
+}
+
+impl MatrixIcon
+{
+    pub fn condition_monitor(&self) -> u8
+    {
+        8 + (self.target.rating + 1) / 2
+    }
+
+    pub fn bricked(&self) -> bool
+    {
+        self.damage >= self.condition_monitor()
+    }
+
+    pub fn defense_pool(&self) -> u8
+    {
+        self.target.rating.saturating_add(self.target.firewall)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MatrixAction
+{
+    // Sneak a mark onto the target.  Net hits earn one mark, up to MAX_MARKS.
+    HackOnTheFly,
+    // Attack the target directly.  Net hits, plus DAMAGE_PER_MARK for each mark already held, in Matrix damage.
+    DataSpike,
+}
+
+impl MatrixAction
+{
+    // The skill rolled with Logic for the action.
+    pub fn skill(&self) -> &'static str
+    {
+        match self
+        {
+            MatrixAction::HackOnTheFly => "Hacking",
+            MatrixAction::DataSpike => "Cybercombat",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatrixActionDeclaration
+{
+    pub decker: Uuid,
+    pub target: Uuid,
+    pub action: MatrixAction,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatrixResolution
+{
+    pub decker: Uuid,
+    pub target: Uuid,
+    pub action: MatrixAction,
+    pub attack: PoolRoll,
+    pub defense: PoolRoll,
+    pub marks: u8,
+    pub damage: u8,
+    pub bricked: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MatrixGrid
+{
+    icons: Vec<MatrixIcon>,
+    marks: HashMap<(Uuid, Uuid), u8>,
+    jacked_in: HashSet<Uuid>,
+}
+
+impl MatrixGrid
+{
+    pub fn new() -> MatrixGrid
+    {
+        MatrixGrid::default()
+    }
+
+    pub fn register(&mut self, target: MatrixTarget) -> Uuid
+    {
+        let id = Uuid::new_v4();
+        self.icons.push(MatrixIcon { id, target, damage: 0 });
+
+        id
+    }
+
+    pub fn icons(&self) -> &[MatrixIcon]
+    {
+        &self.icons
+    }
+
+    pub fn icon(&self, id: Uuid) -> Option<&MatrixIcon>
+    {
+        self.icons.iter().find(|icon| icon.id == id)
+    }
+
+    pub fn marks(&self, decker: Uuid, target: Uuid) -> u8
+    {
+        self.marks.get(&(decker, target)).copied().unwrap_or(0)
+    }
+
+    // Every mark held, as (decker, icon, marks).
+    pub fn all_marks(&self) -> Vec<(Uuid, Uuid, u8)>
+    {
+        self.marks.iter().map(|((decker, target), marks)| (*decker, *target, *marks)).collect()
+    }
+
+    pub fn jack_in(&mut self, decker: Uuid) -> bool
+    {
+        self.jacked_in.insert(decker)
+    }
+
+    // Marks are tied to the persona, so a decker who jacks out loses them.
+    pub fn jack_out(&mut self, decker: Uuid) -> bool
+    {
+        self.marks.retain(|(holder, _), _| *holder != decker);
+        self.jacked_in.remove(&decker)
+    }
+
+    pub fn is_jacked_in(&self, decker: Uuid) -> bool
+    {
+        self.jacked_in.contains(&decker)
+    }
+
+    // Rolls the decker's pool against the icon's rating + firewall and applies the result.  The caller has already checked that the
+    // decker may act and that the icon exists.
+    pub fn resolve<R: Rng + ?Sized>(&mut self, rng: &mut R, declaration: &MatrixActionDeclaration, attack_pool: u8) -> Option<MatrixResolution>
+    {
+        let held = self.marks(declaration.decker, declaration.target);
+        let icon = self.icons.iter_mut().find(|icon| icon.id == declaration.target)?;
+
+        let attack = dice::roll_pool(rng, attack_pool);
+        let defense = dice::roll_pool(rng, icon.defense_pool());
+        let net_hits = attack.hits.saturating_sub(defense.hits);
+
+        let (marks, damage) = match declaration.action
+        {
+            MatrixAction::HackOnTheFly if net_hits > 0 => ((held + 1).min(MAX_MARKS), 0),
+            MatrixAction::DataSpike if net_hits > 0 => (held, net_hits.saturating_add(held * DAMAGE_PER_MARK)),
+            _ => (held, 0),
+        };
+
+        icon.damage = icon.damage.saturating_add(damage).min(icon.condition_monitor());
+        let bricked = icon.bricked();
+        if marks > 0
+        {
+            self.marks.insert((declaration.decker, declaration.target), marks);
+        }
+
+        Some(MatrixResolution { decker: declaration.decker, target: declaration.target, action: declaration.action, attack, defense, marks, damage, bricked })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use rand::{SeedableRng, rngs::StdRng};
+    use uuid::Uuid;
+
+    use super::{MatrixGrid, MatrixTarget, MatrixTargetKind, MatrixAction, MatrixActionDeclaration, MAX_MARKS};
+
+    #[test]
+    pub fn marks_accumulate_to_the_cap_and_are_lost_on_jacking_out()
+    {
+        let mut grid = MatrixGrid::new();
+        let decker = Uuid::new_v4();
+        let camera = grid.register(MatrixTarget { name: String::from("Lobby camera"), kind: MatrixTargetKind::Device, rating: 0, firewall: 0 });
+        let mut rng = StdRng::seed_from_u64(3);
+
+        assert!(grid.jack_in(decker));
+        let hack = MatrixActionDeclaration { decker, target: camera, action: MatrixAction::HackOnTheFly };
+        for _ in 0..10
+        {
+            assert!(grid.resolve(&mut rng, &hack, 12).is_some());
+        }
+        assert_eq!(MAX_MARKS, grid.marks(decker, camera));
+
+        assert!(grid.jack_out(decker));
+        assert_eq!(0, grid.marks(decker, camera));
+        assert!(grid.resolve(&mut rng, &MatrixActionDeclaration { decker, target: Uuid::new_v4(), action: MatrixAction::DataSpike }, 12).is_none());
+    }
+
+    #[test]
+    pub fn a_data_spike_hits_harder_for_every_mark_held_and_can_brick_the_icon()
+    {
+        let mut grid = MatrixGrid::new();
+        let decker = Uuid::new_v4();
+        let host = grid.register(MatrixTarget { name: String::from("Corp host"), kind: MatrixTargetKind::Host, rating: 0, firewall: 0 });
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let spike = MatrixActionDeclaration { decker, target: host, action: MatrixAction::DataSpike };
+        let resolution = grid.resolve(&mut rng, &spike, 6).unwrap();
+        assert_eq!(resolution.attack.hits, resolution.damage);
+
+        let mut bricked = resolution.bricked;
+        for _ in 0..20
+        {
+            bricked = grid.resolve(&mut rng, &spike, 12).unwrap().bricked;
+        }
+        assert!(bricked);
+        assert_eq!(grid.icon(host).unwrap().condition_monitor(), grid.icon(host).unwrap().damage);
+    }
+}
@@ -1,4 +1,18 @@
 pub mod game;
 pub mod character;
 pub mod gear;
-pub mod initiative;
\ No newline at end of file
+pub mod initiative;
+pub mod movement;
+pub mod combat;
+pub mod dice;
+pub mod magic;
+pub mod journal;
+pub mod report;
+pub mod timeline;
+pub mod rules;
+pub mod encounter;
+pub mod names;
+pub mod house_rules;
+pub mod matrix;
+pub mod archetypes;
+pub mod catalog;
\ No newline at end of file
@@ -0,0 +1,108 @@
+use uuid::Uuid;
+
+use super::character::Metatypes;
+
+// Movement is budgeted per combat turn (the whole round of initiative passes), not per pass.  A combatant's gait for the turn is
+// decided by the total distance they have covered so far: anything up to the walking rate is a walk, anything up to the running rate
+// is a run, and anything past that is only possible as a sprint - which needs the hits from a Running test to extend the running rate.
+
+// Running (or sprinting) costs the runner on every other test they make that turn.
+pub const RUNNING_MODIFIER: i8 = -2;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Gait
+{
+    Stationary,
+    Walking,
+    Running,
+    Sprinting,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MovementRates
+{
+    pub walk: u16,
+    pub run: u16,
+    pub sprint_per_hit: u16,
+}
+
+impl MovementRates
+{
+    pub fn for_metatype(metatype: Metatypes) -> MovementRates
+    {
+        match metatype
+        {
+            Metatypes::Dwarf => MovementRates { walk: 8, run: 20, sprint_per_hit: 1 },
+            Metatypes::Troll => MovementRates { walk: 10, run: 25, sprint_per_hit: 1 },
+            Metatypes::Human | Metatypes::Elf | Metatypes::Orc => MovementRates { walk: 10, run: 25, sprint_per_hit: 2 },
+        }
+    }
+
+    // Works out the gait needed to cover the total distance this turn, or None if the distance is out of reach even with the
+    // supplied sprint hits.
+    pub fn gait_for(&self, total_distance: u16, sprint_hits: u8) -> Option<Gait>
+    {
+        if total_distance == 0
+        {
+            Some(Gait::Stationary)
+        }
+        else if total_distance <= self.walk
+        {
+            Some(Gait::Walking)
+        }
+        else if total_distance <= self.run
+        {
+            Some(Gait::Running)
+        }
+        else if total_distance <= self.run + (sprint_hits as u16 * self.sprint_per_hit)
+        {
+            Some(Gait::Sprinting)
+        }
+        else
+        {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MovementRecord
+{
+    pub character_id: Uuid,
+    pub meters: u16,
+    pub total_this_turn: u16,
+    pub gait: Gait,
+    pub pass: usize,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tracker::character::Metatypes;
+
+    use super::{MovementRates, Gait};
+
+    #[test]
+    pub fn gait_escalates_with_distance_up_to_the_running_rate()
+    {
+        let rates = MovementRates::for_metatype(Metatypes::Human);
+
+        assert_eq!(Some(Gait::Stationary), rates.gait_for(0, 0));
+        assert_eq!(Some(Gait::Walking), rates.gait_for(10, 0));
+        assert_eq!(Some(Gait::Running), rates.gait_for(11, 0));
+        assert_eq!(Some(Gait::Running), rates.gait_for(25, 0));
+        assert_eq!(None, rates.gait_for(26, 0));
+    }
+
+    #[test]
+    pub fn sprint_hits_extend_the_running_rate_by_the_metatype_sprint_increment()
+    {
+        let human = MovementRates::for_metatype(Metatypes::Human);
+        let dwarf = MovementRates::for_metatype(Metatypes::Dwarf);
+
+        assert_eq!(Some(Gait::Sprinting), human.gait_for(31, 3));
+        assert_eq!(None, human.gait_for(32, 3));
+        assert_eq!(Some(Gait::Sprinting), dwarf.gait_for(23, 3));
+        assert_eq!(None, dwarf.gait_for(24, 3));
+    }
+}
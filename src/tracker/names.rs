@@ -0,0 +1,77 @@
+use std::{collections::HashSet, sync::Arc};
+
+// A name shared rather than copied: cloning one is a reference-count bump, however many outcomes and notifications it ends up in.
+pub type Name = Arc<str>;
+
+// One allocation per distinct name.  A table of twenty gangers all called "Ganger" holds one string between them, and every summary
+// or notification that mentions them hands out another handle to it.
+pub struct NameTable
+{
+    names: HashSet<Name>,
+}
+
+impl NameTable
+{
+    pub fn new() -> NameTable
+    {
+        NameTable { names: HashSet::new() }
+    }
+
+    pub fn intern(&mut self, name: &str) -> Name
+    {
+        if let Some(existing) = self.names.get(name)
+        {
+            return existing.clone();
+        }
+
+        let name: Name = Arc::from(name);
+        self.names.insert(name.clone());
+        name
+    }
+
+    // Lets go of names nothing outside the table still refers to.  Cheap enough to run whenever something that held names goes away.
+    pub fn prune(&mut self)
+    {
+        self.names.retain(|name| Arc::strong_count(name) > 1);
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.names.len()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use super::NameTable;
+
+    #[test]
+    pub fn interning_the_same_name_twice_hands_back_the_same_allocation()
+    {
+        let mut table = NameTable::new();
+
+        let first = table.intern("Ganger");
+        let second = table.intern("Ganger");
+        let other = table.intern("Lieutenant");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(2, table.len());
+    }
+
+    #[test]
+    pub fn pruning_drops_only_names_nobody_else_holds()
+    {
+        let mut table = NameTable::new();
+
+        let kept = table.intern("Ganger");
+        drop(table.intern("Lieutenant"));
+        table.prune();
+
+        assert_eq!(1, table.len());
+        assert!(Arc::ptr_eq(&kept, &table.intern("Ganger")));
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::{game::ActionType, journal::{JournalEntry, JournalEvent}, names::Name};
+
+// Post-fight numbers, tallied from the journal rather than kept alongside it, so the report can never disagree with the record.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReportScope
+{
+    LastCombat,
+    Session,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CombatantStats
+{
+    pub character: Uuid,
+    pub name: Name,
+    pub damage_dealt: u16,
+    pub damage_taken: u16,
+    pub free_actions: u16,
+    pub simple_actions: u16,
+    pub complex_actions: u16,
+    pub edge_spent: u16,
+    pub initiative_rolls: Vec<i8>,
+}
+
+impl CombatantStats
+{
+    pub fn new(character: Uuid) -> CombatantStats
+    {
+        CombatantStats
+        {
+            character,
+            name: Name::from(""),
+            damage_dealt: 0,
+            damage_taken: 0,
+            free_actions: 0,
+            simple_actions: 0,
+            complex_actions: 0,
+            edge_spent: 0,
+            initiative_rolls: Vec::new(),
+        }
+    }
+
+    pub fn average_initiative(&self) -> Option<f32>
+    {
+        if self.initiative_rolls.is_empty()
+        {
+            return None;
+        }
+
+        Some(self.initiative_rolls.iter().map(|roll| *roll as f32).sum::<f32>() / self.initiative_rolls.len() as f32)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CombatReport
+{
+    pub combats: usize,
+    pub combatants: Vec<CombatantStats>,
+}
+
+impl CombatReport
+{
+    pub fn from_entries(entries: &[JournalEntry]) -> CombatReport
+    {
+        let mut combats = 0;
+        let mut stats: HashMap<Uuid, CombatantStats> = HashMap::new();
+
+        for entry in entries
+        {
+            match &entry.event
+            {
+                JournalEvent::CombatStarted(combatants) =>
+                {
+                    combats += 1;
+                    for character in combatants
+                    {
+                        stats.entry(*character).or_insert_with(|| CombatantStats::new(*character));
+                    }
+                },
+                JournalEvent::InitiativeRolled { character, roll } => 
+                    stats.entry(*character).or_insert_with(|| CombatantStats::new(*character)).initiative_rolls.push(*roll),
+                JournalEvent::ActionTaken { character, action, .. } =>
+                {
+                    let tally = stats.entry(*character).or_insert_with(|| CombatantStats::new(*character));
+                    match action
+                    {
+                        ActionType::Free => tally.free_actions += 1,
+                        ActionType::Simple => tally.simple_actions += 1,
+                        ActionType::Complex => tally.complex_actions += 1,
+                    }
+                },
+                JournalEvent::Damage { source, target, boxes, .. } =>
+                {
+                    stats.entry(*target).or_insert_with(|| CombatantStats::new(*target)).damage_taken += *boxes as u16;
+                    if let Some(source) = source
+                    {
+                        stats.entry(*source).or_insert_with(|| CombatantStats::new(*source)).damage_dealt += *boxes as u16;
+                    }
+                },
+                JournalEvent::EdgeSpent { character, points } => 
+                    stats.entry(*character).or_insert_with(|| CombatantStats::new(*character)).edge_spent += *points as u16,
+                JournalEvent::Chat(_) | JournalEvent::Roll(_) | JournalEvent::RoundStarted(_) | JournalEvent::Rewound(_) | JournalEvent::CheckpointRestored(_) | JournalEvent::HouseRule { .. } | JournalEvent::InitiativeAdjusted { .. }
+                    | JournalEvent::GmProxy { .. } | JournalEvent::Healed { .. } => {},
+            }
+        }
+
+        let mut combatants: Vec<CombatantStats> = stats.into_values().collect();
+        combatants.sort_by_key(|tally| tally.character);
+
+        CombatReport { combats, combatants }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use crate::tracker::{game::ActionType, combat::DamageType, journal::{Journal, JournalEvent}};
+
+    use super::CombatReport;
+
+    #[test]
+    pub fn damage_is_credited_to_both_sides_and_initiative_is_averaged_over_every_roll()
+    {
+        let (sam, ganger) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut journal = Journal::new();
+
+        journal.record(JournalEvent::CombatStarted(vec![sam, ganger]));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 12 });
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 15 });
+        journal.record(JournalEvent::ActionTaken { character: sam, action: ActionType::Complex, name: None, pass: 0, initiative: 15 });
+        journal.record(JournalEvent::Damage { source: Some(sam), target: ganger, boxes: 6, kind: DamageType::Physical });
+        journal.record(JournalEvent::EdgeSpent { character: sam, points: 1 });
+
+        let report = CombatReport::from_entries(journal.entries());
+        assert_eq!(1, report.combats);
+
+        let sam_stats = report.combatants.iter().find(|tally| tally.character == sam).unwrap();
+        assert_eq!(6, sam_stats.damage_dealt);
+        assert_eq!(1, sam_stats.complex_actions);
+        assert_eq!(1, sam_stats.edge_spent);
+        assert_eq!(Some(13.5), sam_stats.average_initiative());
+
+        let ganger_stats = report.combatants.iter().find(|tally| tally.character == ganger).unwrap();
+        assert_eq!(6, ganger_stats.damage_taken);
+        assert_eq!(None, ganger_stats.average_initiative());
+    }
+}
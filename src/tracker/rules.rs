@@ -0,0 +1,613 @@
+use rand::Rng;
+
+use super::{character::{Character, Metatypes, ModifierTarget}, combat::{RangeBand, Lighting, Cover, FiringMode, DamageType}, dice, game::{ActionType, ActionBudget}, movement::{Gait, MovementRates, RUNNING_MODIFIER}};
+
+// The rules arithmetic - modifiers, limits, pool sizes and what things cost - kept apart from the bookkeeping so that Game, the action
+// helpers and the dice roller all work a number out the same way.  Everything here is a pure function of the edition and what it is
+// handed; where the editions differ, the difference lives in one of the tables below.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Edition
+{
+    #[default]
+    SR4,
+    SR5,
+    // A lightweight mode in the spirit of Shadowrun: Anarchy, for tables that want the tracker without the full action economy: one
+    // action a turn, one pass a round, and initiative off a single die.
+    Anarchy,
+    // Not Shadowrun at all: a d20 for initiative taken in descending order, one pass a round, and none of the Shadowrun modifiers.  The
+    // action types keep their slots but the table can call them whatever its own game does.
+    Generic,
+}
+
+// How an edition rolls initiative.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum InitiativeRoll
+{
+    // A handful of initiative dice, with the total added on.
+    Sum,
+    // One die, added on, whatever the sheet says.
+    Single,
+    // A bare d20, with nothing from the sheet.
+    D20,
+}
+
+struct Table
+{
+    // Indexed in declaration order: Short, Medium, Long, Extreme.
+    range: [i8; 4],
+    // Full, Glare, Partial, Darkness.
+    lighting: [i8; 4],
+    // None, Partial, Good.  SR5 hands cover to the defender as a bonus instead, so it costs the attacker nothing.
+    cover: [i8; 3],
+    running: i8,
+    sustaining: i8,
+    initiative: InitiativeRoll,
+    // The most anyone could roll for initiative - a sheet with nothing on it to work out a closer bound from is held to this.
+    initiative_ceiling: i8,
+    // Whether Strength adds to the shooter's own recoil compensation.
+    strength_compensates_recoil: bool,
+    // Movement is a per-metatype table in SR4 and a multiple of Agility in SR5.
+    agility_movement: bool,
+    has_limits: bool,
+    actions: ActionBudget,
+    // Passes on the astral plane and in the Matrix, before anything on the sheet.
+    astral_passes: usize,
+    matrix_passes: usize,
+    // Whether qualities, powers and 'ware add passes and free actions.  A GM ruling on passes stands either way.
+    sheet_bonuses: bool,
+    labels: [&'static str; 3],
+}
+
+const SR4: Table = Table
+{
+    range: [0, -1, -3, -6],
+    lighting: [0, -1, -2, -6],
+    cover: [0, -2, -4],
+    running: RUNNING_MODIFIER,
+    sustaining: -2,
+    initiative: InitiativeRoll::Sum,
+    initiative_ceiling: 40,
+    strength_compensates_recoil: false,
+    agility_movement: false,
+    has_limits: false,
+    actions: ActionBudget { free: 1, simple: 2, complex: 1 },
+    astral_passes: 3,
+    matrix_passes: 3,
+    sheet_bonuses: true,
+    labels: ["Free action", "Simple action", "Complex action"],
+};
+
+const SR5: Table = Table
+{
+    range: [0, -1, -3, -6],
+    lighting: [0, -3, -1, -6],
+    cover: [0, 0, 0],
+    running: RUNNING_MODIFIER,
+    sustaining: -2,
+    initiative: InitiativeRoll::Sum,
+    initiative_ceiling: 50,
+    strength_compensates_recoil: true,
+    agility_movement: true,
+    has_limits: true,
+    actions: ActionBudget { free: 1, simple: 2, complex: 1 },
+    astral_passes: 3,
+    matrix_passes: 3,
+    sheet_bonuses: true,
+    labels: ["Free action", "Simple action", "Complex action"],
+};
+
+// A single simple or complex action spends the whole turn: taking either leaves nothing of the other.
+const ANARCHY: Table = Table
+{
+    range: [0, -1, -3, -6],
+    lighting: [0, -1, -2, -6],
+    cover: [0, -2, -4],
+    running: RUNNING_MODIFIER,
+    sustaining: -2,
+    initiative: InitiativeRoll::Single,
+    initiative_ceiling: 30,
+    strength_compensates_recoil: false,
+    agility_movement: false,
+    has_limits: false,
+    actions: ActionBudget { free: 0, simple: 1, complex: 1 },
+    astral_passes: 1,
+    matrix_passes: 1,
+    sheet_bonuses: false,
+    labels: ["Free action", "Simple action", "Complex action"],
+};
+
+// One main action a turn - simple or complex, either ends it - and a free one beside it, which is about as much structure as most
+// systems share.
+const GENERIC: Table = Table
+{
+    range: [0, 0, 0, 0],
+    lighting: [0, 0, 0, 0],
+    cover: [0, 0, 0],
+    running: 0,
+    sustaining: 0,
+    initiative: InitiativeRoll::D20,
+    // The d20 plus whatever the table's system adds to it, which the sheet does not record.
+    initiative_ceiling: 30,
+    strength_compensates_recoil: false,
+    agility_movement: false,
+    has_limits: false,
+    actions: ActionBudget { free: 1, simple: 1, complex: 1 },
+    astral_passes: 1,
+    matrix_passes: 1,
+    sheet_bonuses: false,
+    labels: ["Bonus action", "Action", "Full action"],
+};
+
+fn table(edition: Edition) -> &'static Table
+{
+    match edition
+    {
+        Edition::SR4 => &SR4,
+        Edition::SR5 => &SR5,
+        Edition::Anarchy => &ANARCHY,
+        Edition::Generic => &GENERIC,
+    }
+}
+
+// The things a combatant can do in a fight that cost one of their actions, for looking up which one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Maneuver
+{
+    Engage,
+    Disengage,
+    AreaAttack,
+    CastSpell,
+}
+
+// How many passes a combatant gets on each plane in a round.  Physical passes are counted past the first, the way the initiative
+// tracker takes them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Passes
+{
+    pub extra_physical: usize,
+    pub astral: usize,
+    pub matrix: usize,
+}
+
+// What the table calls each action type.  Only the names change; the slots and what they cost stay the edition's.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ActionLabels
+{
+    pub free: String,
+    pub simple: String,
+    pub complex: String,
+}
+
+impl ActionLabels
+{
+    pub fn label(&self, action: ActionType) -> &str
+    {
+        match action
+        {
+            ActionType::Free => &self.free,
+            ActionType::Simple => &self.simple,
+            ActionType::Complex => &self.complex,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit
+{
+    Physical,
+    Mental,
+    Social,
+}
+
+// How a character is being patched up.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Healing
+{
+    // The medic's hits on First Aid + Logic, mending one kind of damage; no more boxes come back than the medic's First Aid rating.
+    FirstAid { hits: u8, rating: u8, kind: DamageType },
+    // A Heal spell's hits, no more than its Force.  Magic only mends physical damage.
+    Magic { hits: u8, force: u8 },
+    // Days of natural rest, rolled out by the game.
+    Rest { days: u16 },
+}
+
+// Boxes taken back off each track.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Recovery
+{
+    pub physical: u8,
+    pub stun: u8,
+}
+
+pub fn range_modifier(edition: Edition, range: RangeBand) -> i8
+{
+    table(edition).range[range as usize]
+}
+
+pub fn lighting_modifier(edition: Edition, lighting: Lighting) -> i8
+{
+    table(edition).lighting[lighting as usize]
+}
+
+pub fn cover_modifier(edition: Edition, cover: Cover) -> i8
+{
+    table(edition).cover[cover as usize]
+}
+
+// Every round fired is a point of recoil, and the shooter soaks up one of them for free before the weapon's compensation (and in SR5
+// their Strength) takes the rest.  Compensation never turns recoil into a bonus.
+pub fn recoil_modifier(edition: Edition, firing_mode: FiringMode, recoil_comp: i8, strength: i8) -> i8
+{
+    let own = if table(edition).strength_compensates_recoil { 1 + (strength.max(0) + 2) / 3 } else { 1 };
+    let uncompensated = firing_mode.rounds() - own - recoil_comp;
+
+    if uncompensated > 0 { -uncompensated } else { 0 }
+}
+
+pub fn running_modifier(edition: Edition, gait: Gait) -> i8
+{
+    match gait
+    {
+        Gait::Running | Gait::Sprinting => table(edition).running,
+        Gait::Stationary | Gait::Walking => 0,
+    }
+}
+
+// Saturates rather than wrapping, so a caster holding an absurd number of spells gets the worst penalty there is, not a bonus.
+pub fn sustaining_modifier(edition: Edition, sustained_count: usize) -> i8
+{
+    let count = i8::try_from(sustained_count).unwrap_or(i8::MAX);
+    table(edition).sustaining.saturating_mul(count)
+}
+
+// A combatant's allowance for each pass, before anything is spent.
+pub fn actions_per_pass(edition: Edition, character: &Character) -> ActionBudget
+{
+    let table = table(edition);
+    let extra_free_actions = if table.sheet_bonuses { character.modifier_total(ModifierTarget::FreeActions) } else { 0 };
+
+    ActionBudget { free: (table.actions.free as i16 + extra_free_actions as i16).max(0) as usize, ..table.actions }
+}
+
+pub fn passes(edition: Edition, character: &Character, extra_physical_override: Option<usize>) -> Passes
+{
+    let table = table(edition);
+    let with_modifier = |base: usize, target: ModifierTarget|
+    {
+        let modifier = if table.sheet_bonuses { character.modifier_total(target) } else { 0 };
+        (base as i16 + modifier as i16).max(0) as usize
+    };
+    let from_sheet = if table.sheet_bonuses { character.extra_initiative_passes() } else { 0 };
+
+    Passes
+    {
+        extra_physical: extra_physical_override.unwrap_or(from_sheet),
+        astral: with_modifier(table.astral_passes, ModifierTarget::AstralPasses),
+        matrix: with_modifier(table.matrix_passes, ModifierTarget::MatrixPasses),
+    }
+}
+
+pub fn action_labels(edition: Edition) -> ActionLabels
+{
+    let [free, simple, complex] = table(edition).labels;
+
+    ActionLabels { free: String::from(free), simple: String::from(simple), complex: String::from(complex) }
+}
+
+pub fn action_cost(_edition: Edition, maneuver: Maneuver) -> ActionType
+{
+    match maneuver
+    {
+        Maneuver::Engage | Maneuver::Disengage | Maneuver::AreaAttack => ActionType::Simple,
+        Maneuver::CastSpell => ActionType::Complex,
+    }
+}
+
+// What the healing takes back off the character's tracks, never more than is marked on them.  Resting, stun comes back first, an hour
+// at a time on Body + Willpower; a day that starts with no stun left is a day of physical mending on Body x2.
+pub fn recovery<R: Rng + ?Sized>(rng: &mut R, character: &Character, healing: Healing) -> Recovery
+{
+    let (physical, stun) = (character.physical_track_filled.max(0) as u8, character.stun_track_filled.max(0) as u8);
+
+    match healing
+    {
+        Healing::FirstAid { hits, rating, kind: DamageType::Physical } => Recovery { physical: hits.min(rating).min(physical), stun: 0 },
+        Healing::FirstAid { hits, rating, kind: DamageType::Stun } => Recovery { physical: 0, stun: hits.min(rating).min(stun) },
+        Healing::Magic { hits, force } => Recovery { physical: hits.min(force).min(physical), stun: 0 },
+        Healing::Rest { days } =>
+        {
+            let (body, willpower) = (character.stat("Body"), character.stat("Willpower"));
+            let (mut physical_left, mut stun_left) = (physical, stun);
+            for _ in 0..days
+            {
+                if stun_left > 0
+                {
+                    for _ in 0..24
+                    {
+                        stun_left -= dice::roll_pool(rng, pool(&[body, willpower])).hits.min(stun_left);
+                    }
+                }
+                else if physical_left > 0
+                {
+                    physical_left -= dice::roll_pool(rng, pool(&[body, body])).hits.min(physical_left);
+                }
+            }
+
+            Recovery { physical: physical - physical_left, stun: stun - stun_left }
+        },
+    }
+}
+
+// Physical damage whose value does not beat the armor left after penetration only bruises, and is taken as stun instead.
+pub fn damage_against_armor(kind: DamageType, damage_value: u8, armor: i8) -> DamageType
+{
+    match kind
+    {
+        DamageType::Physical if damage_value as i16 <= armor as i16 => DamageType::Stun,
+        kind => kind,
+    }
+}
+
+// Reaction + Intuition, before any dice - or nothing, for an edition that takes nothing from the sheet.
+pub fn initiative_base(edition: Edition, character: &Character) -> i8
+{
+    match table(edition).initiative
+    {
+        InitiativeRoll::D20 => 0,
+        InitiativeRoll::Sum | InitiativeRoll::Single => character.stat("Reaction").saturating_add(character.stat("Intuition")).max(0),
+    }
+}
+
+pub fn initiative_dice(edition: Edition, character: &Character) -> u8
+{
+    match table(edition).initiative
+    {
+        InitiativeRoll::Sum => character.initiative_dice(),
+        InitiativeRoll::Single | InitiativeRoll::D20 => 1,
+    }
+}
+
+// A sheet that spells out its initiative is rolled as written, whatever the edition would have made of the stats.
+pub fn roll_initiative<R: Rng + ?Sized>(edition: Edition, rng: &mut R, character: &Character) -> i8
+{
+    if let Some(formula) = character.initiative
+    {
+        return formula.roll(rng);
+    }
+
+    let dice = initiative_dice(edition, character);
+    let bonus = match table(edition).initiative
+    {
+        InitiativeRoll::Sum | InitiativeRoll::Single => dice::sum(rng, dice),
+        InitiativeRoll::D20 => rng.gen_range(1..=20),
+    };
+
+    initiative_base(edition, character).saturating_add(bonus.min(i8::MAX as u16) as i8)
+}
+
+// Lowest and highest initiative the character could have rolled: the sheet's formula if it has one, otherwise what the edition makes of
+// Reaction and Intuition.  A sheet with neither stat on it can only be held to the edition's ceiling.
+pub fn initiative_range(edition: Edition, character: &Character) -> (i8, i8)
+{
+    if let Some(formula) = character.initiative
+    {
+        return formula.range();
+    }
+
+    let table = table(edition);
+    if !character.stats.contains_key("Reaction") && !character.stats.contains_key("Intuition") && table.initiative != InitiativeRoll::D20
+    {
+        return (0, table.initiative_ceiling);
+    }
+
+    let (base, dice) = (initiative_base(edition, character) as i16, initiative_dice(edition, character) as i16);
+    let (lowest, highest) = match table.initiative
+    {
+        InitiativeRoll::Sum => (base + dice, base + dice * 6),
+        InitiativeRoll::Single => (base + 1, base + 6),
+        InitiativeRoll::D20 => (1, table.initiative_ceiling as i16),
+    };
+
+    (lowest.min(i8::MAX as i16) as i8, highest.min(i8::MAX as i16) as i8)
+}
+
+// Attributes, skills, armor and modifiers all just add up, and a pool never goes below nothing.
+pub fn pool(parts: &[i8]) -> u8
+{
+    parts.iter().map(|part| *part as i16).sum::<i16>().clamp(0, u8::MAX as i16) as u8
+}
+
+// None where the edition has no limits.
+pub fn limit(edition: Edition, limit: Limit, character: &Character) -> Option<u8>
+{
+    if !table(edition).has_limits
+    {
+        return None;
+    }
+
+    let (doubled, first, second) = match limit
+    {
+        Limit::Physical => ("Strength", "Body", "Reaction"),
+        Limit::Mental => ("Logic", "Intuition", "Willpower"),
+        Limit::Social => ("Charisma", "Willpower", "Essence"),
+    };
+    let total = character.stat(doubled) as i16 * 2 + character.stat(first) as i16 + character.stat(second) as i16;
+
+    Some(((total.max(0) + 2) / 3) as u8)
+}
+
+pub fn limited_hits(hits: u8, limit: Option<u8>) -> u8
+{
+    limit.map_or(hits, |limit| hits.min(limit))
+}
+
+pub fn movement_rates(edition: Edition, character: &Character) -> MovementRates
+{
+    if !table(edition).agility_movement
+    {
+        return MovementRates::for_metatype(character.metatype);
+    }
+
+    let agility = character.stat("Agility").max(0) as u16;
+    let sprint_per_hit = match character.metatype
+    {
+        Metatypes::Dwarf | Metatypes::Troll => 1,
+        Metatypes::Human | Metatypes::Elf | Metatypes::Orc => 2,
+    };
+
+    MovementRates { walk: agility * 2, run: agility * 4, sprint_per_hit }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::tracker::{character::{Character, InitiativeFormula, Metatypes, Modifier, ModifierSource, ModifierTarget}, combat::{RangeBand, Lighting, Cover, FiringMode, DamageType}, game::{ActionBudget, ActionType}};
+
+    use super::{Edition, Limit, Passes, Healing, Recovery};
+
+    #[test]
+    pub fn physical_damage_that_does_not_beat_the_armor_left_is_taken_as_stun()
+    {
+        assert_eq!(DamageType::Stun, super::damage_against_armor(DamageType::Physical, 6, 6));
+        assert_eq!(DamageType::Physical, super::damage_against_armor(DamageType::Physical, 6, 6 - 2));
+        assert_eq!(DamageType::Physical, super::damage_against_armor(DamageType::Physical, 200, 0));
+        assert_eq!(DamageType::Stun, super::damage_against_armor(DamageType::Stun, 9, 0));
+    }
+
+    #[test]
+    pub fn the_sustaining_penalty_saturates_however_many_spells_are_held()
+    {
+        assert_eq!(-6, super::sustaining_modifier(Edition::SR4, 3));
+        assert_eq!(i8::MIN, super::sustaining_modifier(Edition::SR4, 200));
+        assert_eq!(i8::MIN, super::sustaining_modifier(Edition::SR5, usize::MAX));
+    }
+
+    #[test]
+    pub fn the_editions_share_range_penalties_but_not_cover_or_recoil()
+    {
+        for edition in [Edition::SR4, Edition::SR5]
+        {
+            assert_eq!(-3, super::range_modifier(edition, RangeBand::Long));
+        }
+        assert_eq!(-2, super::cover_modifier(Edition::SR4, Cover::Partial));
+        assert_eq!(0, super::cover_modifier(Edition::SR5, Cover::Partial));
+        assert_eq!(-2, super::lighting_modifier(Edition::SR4, Lighting::Partial));
+
+        assert_eq!(-6, super::recoil_modifier(Edition::SR4, FiringMode::FullAuto, 3, 6));
+        assert_eq!(-4, super::recoil_modifier(Edition::SR5, FiringMode::FullAuto, 3, 6));
+        assert_eq!(0, super::recoil_modifier(Edition::SR5, FiringMode::Burst, 0, 6));
+    }
+
+    #[test]
+    pub fn limits_only_apply_in_editions_that_have_them_and_round_up()
+    {
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        for (stat, value) in [("Strength", 4), ("Body", 3), ("Reaction", 5)]
+        {
+            Arc::make_mut(&mut sam.stats).insert(String::from(stat), value);
+        }
+
+        assert_eq!(None, super::limit(Edition::SR4, Limit::Physical, &sam));
+        assert_eq!(Some(6), super::limit(Edition::SR5, Limit::Physical, &sam));
+        assert_eq!(4, super::limited_hits(4, None));
+        assert_eq!(2, super::limited_hits(4, Some(2)));
+        assert_eq!(0, super::pool(&[3, -5]));
+    }
+
+    #[test]
+    pub fn the_initiative_range_follows_the_sheet_where_it_can_and_the_edition_ceiling_where_it_cannot()
+    {
+        let blank = Character::new_pc(Metatypes::Human, String::from("Blank"));
+        assert_eq!((0, 40), super::initiative_range(Edition::SR4, &blank));
+        assert_eq!((0, 50), super::initiative_range(Edition::SR5, &blank));
+        assert_eq!((1, 30), super::initiative_range(Edition::Generic, &blank));
+
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        Arc::make_mut(&mut sam.stats).insert(String::from("Reaction"), 4);
+        Arc::make_mut(&mut sam.stats).insert(String::from("Intuition"), 3);
+        assert_eq!((8, 13), super::initiative_range(Edition::SR4, &sam));
+        assert_eq!((8, 13), super::initiative_range(Edition::SR5, &sam));
+        assert_eq!((8, 13), super::initiative_range(Edition::Anarchy, &sam));
+
+        sam.initiative = InitiativeFormula::parse("10 + 2d6");
+        assert_eq!((12, 22), super::initiative_range(Edition::SR4, &sam));
+    }
+
+    #[test]
+    pub fn initiative_adds_the_dice_to_reaction_and_intuition()
+    {
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        Arc::make_mut(&mut sam.stats).insert(String::from("Reaction"), 4);
+        Arc::make_mut(&mut sam.stats).insert(String::from("Intuition"), 3);
+        let mut rng = StdRng::seed_from_u64(899);
+
+        assert_eq!(1, super::initiative_dice(Edition::SR4, &sam));
+        assert_eq!(1, super::initiative_dice(Edition::SR5, &sam));
+        for _ in 0..20
+        {
+            assert!((8..=13).contains(&super::roll_initiative(Edition::SR4, &mut rng, &sam)));
+            assert!((8..=13).contains(&super::roll_initiative(Edition::SR5, &mut rng, &sam)));
+        }
+    }
+
+    #[test]
+    pub fn anarchy_gives_one_action_and_one_pass_whatever_the_sheet_says()
+    {
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        Arc::make_mut(&mut sam.modifiers).push(Modifier { name: String::from("Wired Reflexes 2"), source: ModifierSource::Augmentation, target: ModifierTarget::InitiativePasses, value: 2 });
+        Arc::make_mut(&mut sam.modifiers).push(Modifier { name: String::from("Quick Hands"), source: ModifierSource::Quality, target: ModifierTarget::FreeActions, value: 1 });
+
+        assert_eq!(ActionBudget { free: 2, simple: 2, complex: 1 }, super::actions_per_pass(Edition::SR4, &sam));
+        assert_eq!(Passes { extra_physical: 2, astral: 3, matrix: 3 }, super::passes(Edition::SR4, &sam, None));
+
+        assert_eq!(ActionBudget { free: 0, simple: 1, complex: 1 }, super::actions_per_pass(Edition::Anarchy, &sam));
+        assert_eq!(Passes { extra_physical: 0, astral: 1, matrix: 1 }, super::passes(Edition::Anarchy, &sam, None));
+        assert_eq!(1, super::passes(Edition::Anarchy, &sam, Some(1)).extra_physical);
+        assert_eq!(1, super::initiative_dice(Edition::Anarchy, &sam));
+    }
+
+    #[test]
+    pub fn the_generic_edition_rolls_a_bare_d20_and_names_its_actions_its_own_way()
+    {
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        Arc::make_mut(&mut sam.stats).insert(String::from("Reaction"), 6);
+        let mut rng = StdRng::seed_from_u64(901);
+
+        for _ in 0..20
+        {
+            assert!((1..=20).contains(&super::roll_initiative(Edition::Generic, &mut rng, &sam)));
+        }
+        assert_eq!(0, super::range_modifier(Edition::Generic, RangeBand::Extreme));
+        assert_eq!(ActionBudget { free: 1, simple: 1, complex: 1 }, super::actions_per_pass(Edition::Generic, &sam));
+        assert_eq!("Action", super::action_labels(Edition::Generic).label(ActionType::Simple));
+        assert_eq!("Complex action", super::action_labels(Edition::SR4).label(ActionType::Complex));
+    }
+
+    #[test]
+    pub fn first_aid_and_magic_are_capped_and_rest_clears_stun_before_it_mends_anything_physical()
+    {
+        let mut sam = Character::new_pc(Metatypes::Human, String::from("Sam"));
+        Arc::make_mut(&mut sam.stats).insert(String::from("Body"), 4);
+        Arc::make_mut(&mut sam.stats).insert(String::from("Willpower"), 4);
+        sam.physical_track_filled = 5;
+        sam.stun_track_filled = 3;
+        let mut rng = StdRng::seed_from_u64(932);
+
+        let first_aid = Healing::FirstAid { hits: 4, rating: 3, kind: DamageType::Physical };
+        assert_eq!(Recovery { physical: 3, stun: 0 }, super::recovery(&mut rng, &sam, first_aid));
+        assert_eq!(Recovery { physical: 0, stun: 3 }, super::recovery(&mut rng, &sam, Healing::FirstAid { hits: 6, rating: 6, kind: DamageType::Stun }));
+        assert_eq!(Recovery { physical: 2, stun: 0 }, super::recovery(&mut rng, &sam, Healing::Magic { hits: 4, force: 2 }));
+        assert_eq!(Recovery::default(), super::recovery(&mut rng, &sam, Healing::Rest { days: 0 }));
+
+        // A day of rest is all stun; the physical damage waits for the next.
+        let first_day = super::recovery(&mut rng, &sam, Healing::Rest { days: 1 });
+        assert_eq!(0, first_day.physical);
+        assert_eq!(3, first_day.stun);
+        assert_eq!(Recovery { physical: 5, stun: 3 }, super::recovery(&mut rng, &sam, Healing::Rest { days: 60 }));
+    }
+}
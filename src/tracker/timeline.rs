@@ -0,0 +1,161 @@
+use uuid::Uuid;
+
+use super::{game::ActionType, journal::{JournalEntry, JournalEvent}};
+
+// How each round of a fight played out, pieced together from the journal the same way the combat report is.
+
+// One character's go at one initiative score on one pass, with every action they spent on it in the order taken.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimelineTurn
+{
+    pub pass: usize,
+    // None where the reader is not shown the character's score.
+    pub initiative: Option<i8>,
+    pub character: Uuid,
+    pub actions: Vec<ActionType>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RoundTimeline
+{
+    // Counted from 1 at the start of each combat.
+    pub round: usize,
+    pub initiatives: Vec<(Uuid, i8)>,
+    pub turns: Vec<TimelineTurn>,
+}
+
+impl RoundTimeline
+{
+    fn new(round: usize) -> RoundTimeline
+    {
+        RoundTimeline { round, initiatives: Vec::new(), turns: Vec::new() }
+    }
+
+    // Each round opens with its initiative phase, which the game marks in the journal along with the round's number.  A rewind back
+    // into an earlier round marks it again, and the rounds after it are dropped as though they had not been played.
+    pub fn from_entries(entries: &[JournalEntry]) -> Vec<RoundTimeline>
+    {
+        let mut rounds: Vec<RoundTimeline> = Vec::new();
+
+        for entry in entries
+        {
+            match &entry.event
+            {
+                JournalEvent::CombatStarted(_) => rounds.clear(),
+                JournalEvent::RoundStarted(round) =>
+                {
+                    rounds.truncate(round.saturating_sub(1));
+                    rounds.push(RoundTimeline::new(*round));
+                },
+                JournalEvent::InitiativeRolled { character, roll } =>
+                {
+                    if rounds.is_empty()
+                    {
+                        rounds.push(RoundTimeline::new(1));
+                    }
+                    if let Some(current) = rounds.last_mut()
+                    {
+                        current.initiatives.push((*character, *roll));
+                    }
+                },
+                JournalEvent::ActionTaken { character, action, pass, initiative, .. } =>
+                {
+                    if rounds.is_empty()
+                    {
+                        rounds.push(RoundTimeline::new(1));
+                    }
+                    let Some(current) = rounds.last_mut()
+                    else { continue };
+
+                    match current.turns.last_mut()
+                    {
+                        Some(turn) if turn.character == *character && turn.pass == *pass && turn.initiative == Some(*initiative) => turn.actions.push(*action),
+                        _ => current.turns.push(TimelineTurn { pass: *pass, initiative: Some(*initiative), character: *character, actions: vec![*action] }),
+                    }
+                },
+                JournalEvent::Chat(_) | JournalEvent::Roll(_) | JournalEvent::InitiativeAdjusted { .. } | JournalEvent::Damage { .. } | JournalEvent::Healed { .. } | JournalEvent::EdgeSpent { .. }
+                    | JournalEvent::Rewound(_) | JournalEvent::CheckpointRestored(_) | JournalEvent::HouseRule { .. } | JournalEvent::GmProxy { .. } => {},
+            }
+        }
+
+        rounds
+    }
+
+    // Drops a character from the round altogether, for readers who are not meant to know they are there.
+    pub fn without(&mut self, character: &Uuid)
+    {
+        self.initiatives.retain(|(rolled, _)| rolled != character);
+        self.turns.retain(|turn| turn.character != *character);
+    }
+
+    // Keeps the character's turns but not its score, for readers who see an NPC act but are not told its initiative.
+    pub fn withhold_score(&mut self, character: &Uuid)
+    {
+        self.initiatives.retain(|(rolled, _)| rolled != character);
+        for turn in self.turns.iter_mut().filter(|turn| turn.character == *character)
+        {
+            turn.initiative = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use uuid::Uuid;
+
+    use crate::tracker::{game::ActionType, journal::{Journal, JournalEvent}};
+
+    use super::{RoundTimeline, TimelineTurn};
+
+    #[test]
+    pub fn actions_are_grouped_into_turns_under_the_round_the_journal_marks_them_in()
+    {
+        let (sam, ganger) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut journal = Journal::new();
+
+        journal.record(JournalEvent::CombatStarted(vec![sam, ganger]));
+        journal.record(JournalEvent::RoundStarted(1));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 14 });
+        journal.record(JournalEvent::InitiativeRolled { character: ganger, roll: 9 });
+        journal.record(JournalEvent::ActionTaken { character: sam, action: ActionType::Simple, name: None, pass: 0, initiative: 14 });
+        journal.record(JournalEvent::ActionTaken { character: sam, action: ActionType::Simple, name: None, pass: 0, initiative: 14 });
+        journal.record(JournalEvent::ActionTaken { character: ganger, action: ActionType::Complex, name: None, pass: 0, initiative: 9 });
+        journal.record(JournalEvent::ActionTaken { character: sam, action: ActionType::Complex, name: None, pass: 1, initiative: 4 });
+        journal.record(JournalEvent::RoundStarted(2));
+        journal.record(JournalEvent::InitiativeRolled { character: ganger, roll: 11 });
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 8 });
+
+        let rounds = RoundTimeline::from_entries(journal.entries());
+        assert_eq!(2, rounds.len());
+        assert_eq!(vec![(sam, 14), (ganger, 9)], rounds[0].initiatives);
+        assert_eq!(vec![
+            TimelineTurn { pass: 0, initiative: Some(14), character: sam, actions: vec![ActionType::Simple, ActionType::Simple] },
+            TimelineTurn { pass: 0, initiative: Some(9), character: ganger, actions: vec![ActionType::Complex] },
+            TimelineTurn { pass: 1, initiative: Some(4), character: sam, actions: vec![ActionType::Complex] },
+        ], rounds[0].turns);
+
+        assert_eq!(2, rounds[1].round);
+        assert_eq!(vec![(ganger, 11), (sam, 8)], rounds[1].initiatives);
+        assert!(rounds[1].turns.is_empty());
+    }
+
+    #[test]
+    pub fn a_round_marked_again_after_a_rewind_replaces_the_rounds_from_there_on()
+    {
+        let sam = Uuid::new_v4();
+        let mut journal = Journal::new();
+
+        journal.record(JournalEvent::CombatStarted(vec![sam]));
+        journal.record(JournalEvent::RoundStarted(1));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 14 });
+        journal.record(JournalEvent::RoundStarted(2));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 10 });
+        journal.record(JournalEvent::RoundStarted(2));
+        journal.record(JournalEvent::InitiativeRolled { character: sam, roll: 12 });
+
+        let rounds = RoundTimeline::from_entries(journal.entries());
+        assert_eq!(vec![1, 2], rounds.iter().map(|round| round.round).collect::<Vec<usize>>());
+        assert_eq!(vec![(sam, 12)], rounds[1].initiatives);
+    }
+}